@@ -0,0 +1,202 @@
+//! The localhost end of the OAuth redirect.
+//!
+//! During `golem login` a [`CallbackServer`] listens on a free port of
+//! 127.0.0.1. The authorization page redirects the browser to it with the
+//! code, so nobody has to copy and paste `code#state`.
+
+use anyhow::{Context, Result, bail};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Where the browser is sent back to.
+const CALLBACK_PATH: &str = "/callback";
+
+/// How long a connection may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+const SUCCESS_PAGE: &str = "<!doctype html><title>golem</title>\
+<p>Logged in to golem. You can close this tab and go back to the terminal.</p>";
+
+const FAILURE_PAGE: &str = "<!doctype html><title>golem</title>\
+<p>Login failed; see the terminal.</p>";
+
+/// A one-shot HTTP listener for the authorization redirect.
+pub struct CallbackServer {
+    listener: TcpListener,
+    port: u16,
+}
+
+/// What a request to the listener carried.
+#[derive(Debug, PartialEq)]
+enum Callback {
+    Code(String),
+    /// The user declined, or the provider failed.
+    Error(String),
+    /// Not the redirect: a favicon, or a stale or forged state.
+    Ignored,
+}
+
+impl CallbackServer {
+    /// Listen on a free port of the loopback interface.
+    pub async fn bind() -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .context("failed to listen on localhost")?;
+        let port = listener.local_addr()?.port();
+        Ok(Self { listener, port })
+    }
+
+    /// The redirect URI to send with the authorization request.
+    pub fn redirect_uri(&self) -> String {
+        format!("http://localhost:{}{CALLBACK_PATH}", self.port)
+    }
+
+    /// Wait for the redirect carrying `state`, answer the browser, and
+    /// return the authorization code.
+    pub async fn code(self, state: &str) -> Result<String> {
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            // Browsers open connections they never use; don't wait on them
+            let Ok(Ok(callback)) =
+                tokio::time::timeout(REQUEST_TIMEOUT, handle(stream, state)).await
+            else {
+                continue;
+            };
+            match callback {
+                Callback::Code(code) => return Ok(code),
+                Callback::Error(error) => bail!("authorization failed: {error}"),
+                Callback::Ignored => {}
+            }
+        }
+    }
+}
+
+/// Read one request from `stream` and answer it.
+async fn handle(stream: TcpStream, state: &str) -> Result<Callback> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let callback = parse_request(&request_line, state);
+
+    let (status, body) = match &callback {
+        Callback::Code(_) => ("200 OK", SUCCESS_PAGE),
+        Callback::Error(_) => ("200 OK", FAILURE_PAGE),
+        Callback::Ignored => ("404 Not Found", ""),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(callback)
+}
+
+/// Make sense of a request line like `GET /callback?code=…&state=… HTTP/1.1`.
+fn parse_request(request_line: &str, state: &str) -> Callback {
+    let target = request_line.split_whitespace().nth(1).unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != CALLBACK_PATH {
+        return Callback::Ignored;
+    }
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| percent_decode(value))
+    };
+    if param("state").as_deref() != Some(state) {
+        return Callback::Ignored;
+    }
+    match (param("code"), param("error")) {
+        (Some(code), _) if !code.is_empty() => Callback::Code(code),
+        (_, Some(error)) => Callback::Error(error),
+        _ => Callback::Error("no code in the redirect".to_string()),
+    }
+}
+
+/// Undo the URL encoding of a query value.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |at: usize| bytes.get(at).and_then(|&b| (b as char).to_digit(16));
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                out.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+            (b'+', ..) => out.push(b' '),
+            (byte, ..) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_gives_the_code() {
+        assert_eq!(
+            parse_request("GET /callback?code=abc%2F123&state=s1 HTTP/1.1\r\n", "s1"),
+            Callback::Code("abc/123".to_string())
+        );
+        assert_eq!(
+            parse_request("GET /callback?error=access_denied&state=s1 HTTP/1.1", "s1"),
+            Callback::Error("access_denied".to_string())
+        );
+    }
+
+    #[test]
+    fn other_requests_are_ignored() {
+        assert_eq!(
+            parse_request("GET /favicon.ico HTTP/1.1", "s1"),
+            Callback::Ignored
+        );
+        assert_eq!(
+            parse_request("GET /callback?code=abc&state=forged HTTP/1.1", "s1"),
+            Callback::Ignored
+        );
+        assert_eq!(parse_request("", "s1"), Callback::Ignored);
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[tokio::test]
+    async fn catches_the_browser_redirect() {
+        let server = CallbackServer::bind().await.unwrap();
+        let uri = server.redirect_uri();
+        assert!(uri.starts_with("http://localhost:"), "{uri}");
+        let waiting = tokio::spawn(server.code("s1"));
+
+        let client = reqwest::Client::new();
+        let favicon = client
+            .get(uri.replace("/callback", "/favicon.ico"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(favicon.status(), 404);
+        let page = client
+            .get(format!("{uri}?code=the-code&state=s1"))
+            .send()
+            .await
+            .unwrap();
+        assert!(page.text().await.unwrap().contains("Logged in"));
+
+        assert_eq!(waiting.await.unwrap().unwrap(), "the-code");
+    }
+}
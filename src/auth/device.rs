@@ -0,0 +1,261 @@
+//! The OAuth device-code flow (RFC 8628), for providers that offer it.
+//!
+//! golem asks the provider for a short user code and shows it with a
+//! verification URL. The user enters the code on any device with a browser
+//! — a phone will do — while golem polls the token endpoint until the
+//! grant is approved. Nothing is pasted back into the terminal, which is
+//! what makes it suit a headless box reached over SSH.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+use super::oauth::{OAuthCredentials, TokenResponse, urlencoded};
+use crate::thinker::http::shared_client;
+
+/// The grant type for polling with a device code.
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// How long to wait between polls when the provider doesn't say.
+const DEFAULT_INTERVAL: u64 = 5;
+
+/// How much longer to wait between polls after a `slow_down`.
+const SLOW_DOWN: Duration = Duration::from_secs(5);
+
+/// Where a provider takes device-code requests.
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceEndpoints<'a> {
+    pub device_authorization_url: &'a str,
+    pub token_url: &'a str,
+    pub client_id: &'a str,
+    pub scopes: &'a str,
+}
+
+/// The provider's answer to a device authorization request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    /// What the user types on the verification page.
+    pub user_code: String,
+    pub verification_uri: String,
+    /// The verification page with the code filled in, if offered.
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until the codes expire.
+    pub expires_in: u64,
+    /// Seconds to wait between polls.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL
+}
+
+/// What one poll of the token endpoint said.
+#[derive(Debug)]
+enum Poll {
+    Granted(OAuthCredentials),
+    Pending,
+    SlowDown,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// Ask for a device code and the user code that goes with it.
+pub async fn request(endpoints: &DeviceEndpoints<'_>) -> Result<DeviceCode> {
+    let body = form(&[
+        ("client_id", endpoints.client_id),
+        ("scope", endpoints.scopes),
+    ]);
+    let resp = post(endpoints.device_authorization_url, body).await?;
+    if !resp.status().is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        bail!("device authorization failed: {text}");
+    }
+    resp.json()
+        .await
+        .context("invalid device authorization response")
+}
+
+/// Poll until the user approves `code`, then return the tokens. Fails when
+/// the user declines or the code expires first.
+pub async fn poll(endpoints: &DeviceEndpoints<'_>, code: &DeviceCode) -> Result<OAuthCredentials> {
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = Duration::from_secs(code.interval);
+    let body = form(&[
+        ("grant_type", DEVICE_GRANT),
+        ("device_code", &code.device_code),
+        ("client_id", endpoints.client_id),
+    ]);
+    loop {
+        if Instant::now() + interval >= deadline {
+            bail!("the code expired before it was entered; log in again");
+        }
+        tokio::time::sleep(interval).await;
+        let resp = post(endpoints.token_url, body.clone()).await?;
+        let status = resp.status().as_u16();
+        let text = resp.text().await.unwrap_or_default();
+        match outcome(status, &text)? {
+            Poll::Granted(credentials) => return Ok(credentials),
+            Poll::Pending => {}
+            Poll::SlowDown => interval += SLOW_DOWN,
+        }
+    }
+}
+
+/// Make sense of a token endpoint response.
+fn outcome(status: u16, body: &str) -> Result<Poll> {
+    if (200..300).contains(&status) {
+        let data: TokenResponse = serde_json::from_str(body).context("invalid token response")?;
+        return Ok(Poll::Granted(data.into()));
+    }
+    let Ok(error) = serde_json::from_str::<ErrorResponse>(body) else {
+        bail!("token request failed ({status}): {body}");
+    };
+    match error.error.as_str() {
+        "authorization_pending" => Ok(Poll::Pending),
+        "slow_down" => Ok(Poll::SlowDown),
+        "access_denied" => bail!("the login was declined"),
+        "expired_token" => bail!("the code expired before it was entered; log in again"),
+        other => bail!(
+            "token request failed: {}",
+            error.error_description.as_deref().unwrap_or(other)
+        ),
+    }
+}
+
+/// An `application/x-www-form-urlencoded` body, as RFC 8628 asks for.
+fn form(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{k}={}", urlencoded(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+async fn post(url: &str, body: String) -> Result<reqwest::Response> {
+    Ok(shared_client()
+        .post(url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(body)
+        .send()
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    const GRANTED: &str = r#"{"access_token":"at","refresh_token":"rt","expires_in":3600}"#;
+
+    #[test]
+    fn reads_the_poll_outcome() {
+        assert!(matches!(outcome(200, GRANTED).unwrap(), Poll::Granted(c) if c.access == "at"));
+        assert!(matches!(
+            outcome(400, r#"{"error":"authorization_pending"}"#).unwrap(),
+            Poll::Pending
+        ));
+        assert!(matches!(
+            outcome(400, r#"{"error":"slow_down"}"#).unwrap(),
+            Poll::SlowDown
+        ));
+        let declined = outcome(400, r#"{"error":"access_denied"}"#).unwrap_err();
+        assert!(declined.to_string().contains("declined"), "{declined}");
+        let expired = outcome(400, r#"{"error":"expired_token"}"#).unwrap_err();
+        assert!(expired.to_string().contains("expired"), "{expired}");
+        assert!(outcome(500, "oops").is_err());
+    }
+
+    #[test]
+    fn interval_defaults_to_five_seconds() {
+        let code: DeviceCode = serde_json::from_str(
+            r#"{"device_code":"d","user_code":"ABCD-EFGH",
+                "verification_uri":"https://example.com/device","expires_in":600}"#,
+        )
+        .unwrap();
+        assert_eq!(code.interval, 5);
+        assert!(code.verification_uri_complete.is_none());
+    }
+
+    /// Answer one request per item of `bodies`, in order, each with its
+    /// status; return the requests' bodies.
+    async fn serve(listener: TcpListener, bodies: Vec<(u16, &'static str)>) -> Vec<String> {
+        let mut requests = Vec::new();
+        for (status, body) in bodies {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; length];
+            reader.read_exact(&mut request).await.unwrap();
+            requests.push(String::from_utf8(request).unwrap());
+            let response = format!(
+                "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let mut stream = reader.into_inner();
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+        requests
+    }
+
+    #[tokio::test]
+    async fn polls_until_the_user_approves() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(
+            listener,
+            vec![
+                (
+                    200,
+                    r#"{"device_code":"dev-1","user_code":"ABCD-EFGH",
+                        "verification_uri":"https://example.com/device",
+                        "expires_in":600,"interval":0}"#,
+                ),
+                (400, r#"{"error":"authorization_pending"}"#),
+                (200, GRANTED),
+            ],
+        ));
+        let (device_url, token_url) = (format!("{base}/device"), format!("{base}/token"));
+        let endpoints = DeviceEndpoints {
+            device_authorization_url: &device_url,
+            token_url: &token_url,
+            client_id: "golem",
+            scopes: "user:inference",
+        };
+
+        let code = request(&endpoints).await.unwrap();
+        assert_eq!(code.user_code, "ABCD-EFGH");
+        let credentials = poll(&endpoints, &code).await.unwrap();
+        assert_eq!(credentials.access, "at");
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests[0], "client_id=golem&scope=user%3Ainference");
+        assert!(requests[2].contains("device_code=dev-1"), "{}", requests[2]);
+        assert!(
+            requests[2].contains("grant_type=urn%3Aietf"),
+            "{}",
+            requests[2]
+        );
+    }
+}
@@ -1,26 +1,48 @@
+pub mod callback;
+pub mod device;
 pub mod oauth;
 pub mod storage;
 
 pub use storage::AuthStorage;
 
 use anyhow::{Context, Result, bail};
+use callback::CallbackServer;
+use device::DeviceEndpoints;
+use std::collections::BTreeMap;
+use std::path::Path;
 use storage::Credential;
 
+use crate::consts::{DEFAULT_ACCOUNT, LOGIN_TIMEOUT};
+use crate::thinker::Thinker;
+use crate::thinker::anthropic::AnthropicThinker;
+
 /// Providers that support OAuth login.
 const SUPPORTED_PROVIDERS: &[&str] = &["anthropic"];
 
+/// Providers that offer the device-code flow, and where. Anthropic's OAuth
+/// doesn't yet.
+const DEVICE_PROVIDERS: &[(&str, DeviceEndpoints<'static>)] = &[];
+
 /// Complete OAuth login: exchange the authorization code and save credentials.
 ///
 /// This is the shared logic used by both the CLI `golem login` subcommand
 /// and the `/login` REPL slash command.
 ///
+/// `redirect_uri` is the one the authorization URL was built for.
+///
 /// Returns an error if the provider is not supported, the token exchange
 /// fails, or credentials cannot be saved.
-pub async fn login(db_path: &str, provider: &str, code: &str, verifier: &str) -> Result<()> {
+pub async fn login(
+    db_path: &str,
+    provider: &str,
+    code: &str,
+    verifier: &str,
+    redirect_uri: &str,
+) -> Result<()> {
     if !SUPPORTED_PROVIDERS.contains(&provider) {
         bail!("unsupported provider: {provider}");
     }
-    let credentials = oauth::exchange_code(code, verifier)
+    let credentials = oauth::exchange_code(code, verifier, redirect_uri)
         .await
         .context("token exchange failed")?;
     let storage = AuthStorage::open(db_path).context("failed to open auth storage")?;
@@ -30,6 +52,172 @@ pub async fn login(db_path: &str, provider: &str, code: &str, verifier: &str) ->
     Ok(())
 }
 
+/// The whole browser login, for `golem login`, `/login` and first-run
+/// setup: open the authorization page, get the code, and store the tokens
+/// in `db_path`.
+///
+/// The page redirects to a [`CallbackServer`] on localhost, which catches
+/// the code. With `paste` — for a browser on another machine, say over
+/// SSH — or when no local port is free, the user pastes it instead.
+pub async fn browser_login(db_path: &str, provider: &str, paste: bool) -> Result<()> {
+    let server = if paste {
+        None
+    } else {
+        match CallbackServer::bind().await {
+            Ok(server) => Some(server),
+            Err(e) => {
+                eprintln!("  warning: {e:#}; paste the code instead");
+                None
+            }
+        }
+    };
+    let redirect_uri = match &server {
+        Some(server) => server.redirect_uri(),
+        None => oauth::PASTE_REDIRECT_URI.to_string(),
+    };
+    let (url, verifier) = oauth::build_authorize_url_for(&redirect_uri);
+    let _ = open::that(&url);
+
+    println!("Open this URL to authenticate:\n");
+    println!("  {url}\n");
+
+    let code = match server {
+        Some(server) => {
+            println!("Waiting for the browser... (`golem login --paste` if it runs elsewhere)");
+            let code = tokio::time::timeout(LOGIN_TIMEOUT, server.code(&verifier))
+                .await
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "no answer from the browser within {}s",
+                        LOGIN_TIMEOUT.as_secs()
+                    )
+                })??;
+            // The same shape as a pasted code
+            format!("{code}#{verifier}")
+        }
+        None => crate::prompt::terminal()
+            .ask("Paste the authorization code: ")
+            .await?
+            .unwrap_or_default(),
+    };
+    if code.is_empty() {
+        bail!("no authorization code provided");
+    }
+
+    println!("\nExchanging code for tokens...");
+    login(db_path, provider, &code, &verifier, &redirect_uri).await
+}
+
+/// Log in with a device code, for `golem login --device` and `/login
+/// --device`: show the code and where to enter it, wait until the user
+/// has, and store the tokens in `db_path`.
+///
+/// Fails straight away for a provider that doesn't offer the flow.
+pub async fn device_login(db_path: &str, provider: &str) -> Result<()> {
+    let Some((_, endpoints)) = DEVICE_PROVIDERS.iter().find(|(name, _)| *name == provider) else {
+        bail!("{provider} doesn't offer device-code login; use --paste on a headless machine");
+    };
+    let code = device::request(endpoints).await?;
+
+    println!("On any device with a browser, open:\n");
+    println!("  {}\n", code.verification_uri);
+    println!("and enter the code  {}\n", code.user_code);
+    if let Some(complete) = &code.verification_uri_complete {
+        println!("(or open {complete} with the code filled in)\n");
+    }
+    println!("Waiting for approval...");
+
+    let credentials = device::poll(endpoints, &code).await?;
+    let storage = AuthStorage::open(db_path).context("failed to open auth storage")?;
+    storage
+        .set(provider, Credential::OAuth(credentials))
+        .context("failed to save credentials")?;
+    Ok(())
+}
+
+/// Move the credentials of a pre-SQLite `~/.golem/auth.json` at `path` —
+/// a JSON object from provider to credential — into the default accounts
+/// of `storage`, then rename the file to `auth.json.imported` so it is
+/// read only once. A credential already in the database wins over the
+/// file's. Returns how many were imported; a missing file imports none.
+pub fn import_legacy(storage: &AuthStorage, path: &Path) -> Result<usize> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    let credentials: BTreeMap<String, Credential> =
+        serde_json::from_str(&text).with_context(|| format!("invalid {}", path.display()))?;
+    let mut imported = 0;
+    for (provider, credential) in &credentials {
+        if storage.set_if_absent(provider, DEFAULT_ACCOUNT, credential)? {
+            imported += 1;
+        }
+    }
+    let mut done = path.as_os_str().to_owned();
+    done.push(".imported");
+    std::fs::rename(path, &done).with_context(|| format!("failed to rename {}", path.display()))?;
+    Ok(imported)
+}
+
+/// One stored credential, as `golem auth status` lists it.
+#[derive(Debug, Clone)]
+pub struct StoredCredential {
+    pub provider: String,
+    pub account: String,
+    /// Whether it is the account the provider uses now.
+    pub active: bool,
+    pub credential: Credential,
+}
+
+/// Every credential in `storage`, by provider and account.
+pub fn stored(storage: &AuthStorage) -> Result<Vec<StoredCredential>> {
+    storage
+        .all()?
+        .into_iter()
+        .map(|(provider, account, credential)| {
+            Ok(StoredCredential {
+                active: storage.account(&provider)? == account,
+                provider,
+                account,
+                credential,
+            })
+        })
+        .collect()
+}
+
+/// Check that `account` of `provider` in `db_path` still works with the
+/// cheapest authenticated call there is — listing the models, which costs
+/// no tokens — and return how many models it can use. An expired OAuth
+/// token is refreshed on the way, as on any other call.
+pub async fn verify(db_path: &str, provider: &str, account: &str) -> Result<usize> {
+    let storage = AuthStorage::open(db_path)?.pinned(account);
+    let thinker = match provider {
+        "anthropic" => AnthropicThinker::new(None, storage),
+        other => bail!("can't check {other} credentials"),
+    };
+    Ok(thinker.models().await?.len())
+}
+
+/// How `provider` is authenticated, for the banner and `/whoami`: `OAuth
+/// ✓`, `API key ✓`, `API key (env) ✓` from `<PROVIDER>_API_KEY`, or `not
+/// authenticated` — naming the account unless it is the default one.
+pub fn status(storage: &AuthStorage, provider: &str) -> Result<String> {
+    let env_var = format!("{}_API_KEY", provider.to_uppercase());
+    let status = match storage.get(provider)? {
+        Some(Credential::OAuth(_)) => "OAuth ✓",
+        Some(Credential::ApiKey { .. }) => "API key ✓",
+        None if std::env::var(&env_var).is_ok_and(|k| !k.is_empty()) => "API key (env) ✓",
+        None => "not authenticated",
+    };
+    let account = storage.account(provider)?;
+    Ok(if account == DEFAULT_ACCOUNT {
+        status.to_string()
+    } else {
+        format!("{status} (account {account})")
+    })
+}
+
 /// Remove stored credentials for a provider.
 ///
 /// This is the shared logic used by both the CLI `golem logout` subcommand
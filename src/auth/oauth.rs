@@ -3,10 +3,13 @@ use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use rand::RngExt;
 use sha2::{Digest, Sha256};
 
+use crate::thinker::http::shared_client;
+
 const CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
 const AUTHORIZE_URL: &str = "https://claude.ai/oauth/authorize";
 const TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
-const REDIRECT_URI: &str = "https://console.anthropic.com/oauth/code/callback";
+/// The redirect for pasting: the page shows the code to copy.
+pub const PASTE_REDIRECT_URI: &str = "https://console.anthropic.com/oauth/code/callback";
 const SCOPES: &str = "org:create_api_key user:profile user:inference";
 
 /// OAuth credentials stored after login.
@@ -52,21 +55,30 @@ fn now_ms() -> u64 {
         .as_millis() as u64
 }
 
-/// Build the authorization URL for the user to visit.
+/// Build the authorization URL for the user to visit, for a code to paste.
 /// Returns (url, pkce_verifier) — caller must keep the verifier for token exchange.
 pub fn build_authorize_url() -> (String, String) {
+    build_authorize_url_for(PASTE_REDIRECT_URI)
+}
+
+/// Like [`build_authorize_url`], but the browser is sent to `redirect_uri`
+/// with the code, e.g. a [`CallbackServer`](super::callback::CallbackServer).
+pub fn build_authorize_url_for(redirect_uri: &str) -> (String, String) {
     let pkce = generate_pkce();
 
-    let params = [
-        ("code", "true"),
+    let mut params = vec![
         ("client_id", CLIENT_ID),
         ("response_type", "code"),
-        ("redirect_uri", REDIRECT_URI),
+        ("redirect_uri", redirect_uri),
         ("scope", SCOPES),
         ("code_challenge", &pkce.challenge),
         ("code_challenge_method", "S256"),
         ("state", &pkce.verifier),
     ];
+    if redirect_uri == PASTE_REDIRECT_URI {
+        // Show the code on the page instead of redirecting with it
+        params.insert(0, ("code", "true"));
+    }
 
     let query = params
         .iter()
@@ -79,8 +91,13 @@ pub fn build_authorize_url() -> (String, String) {
 }
 
 /// Exchange an authorization code for tokens.
-/// `auth_code_raw` is the string pasted by the user, in the format `code#state`.
-pub async fn exchange_code(auth_code_raw: &str, verifier: &str) -> Result<OAuthCredentials> {
+/// `auth_code_raw` is in the format `code#state`, as the user pastes it;
+/// `redirect_uri` is the one the authorization URL was built for.
+pub async fn exchange_code(
+    auth_code_raw: &str,
+    verifier: &str,
+    redirect_uri: &str,
+) -> Result<OAuthCredentials> {
     let (code, state) = auth_code_raw.split_once('#').unwrap_or((auth_code_raw, ""));
 
     let body = serde_json::json!({
@@ -88,11 +105,11 @@ pub async fn exchange_code(auth_code_raw: &str, verifier: &str) -> Result<OAuthC
         "client_id": CLIENT_ID,
         "code": code,
         "state": state,
-        "redirect_uri": REDIRECT_URI,
+        "redirect_uri": redirect_uri,
         "code_verifier": verifier,
     });
 
-    let client = reqwest::Client::new();
+    let client = shared_client();
     let resp = client
         .post(TOKEN_URL)
         .header("Content-Type", "application/json")
@@ -106,15 +123,7 @@ pub async fn exchange_code(auth_code_raw: &str, verifier: &str) -> Result<OAuthC
     }
 
     let data: TokenResponse = resp.json().await?;
-
-    // 5 minute buffer before expiry
-    let expires = now_ms() + (data.expires_in * 1000) - (5 * 60 * 1000);
-
-    Ok(OAuthCredentials {
-        access: data.access_token,
-        refresh: data.refresh_token,
-        expires,
-    })
+    Ok(data.into())
 }
 
 /// Refresh an expired access token.
@@ -125,7 +134,7 @@ pub async fn refresh_token(refresh: &str) -> Result<OAuthCredentials> {
         "refresh_token": refresh,
     });
 
-    let client = reqwest::Client::new();
+    let client = shared_client();
     let resp = client
         .post(TOKEN_URL)
         .header("Content-Type", "application/json")
@@ -139,23 +148,28 @@ pub async fn refresh_token(refresh: &str) -> Result<OAuthCredentials> {
     }
 
     let data: TokenResponse = resp.json().await?;
-
-    let expires = now_ms() + (data.expires_in * 1000) - (5 * 60 * 1000);
-
-    Ok(OAuthCredentials {
-        access: data.access_token,
-        refresh: data.refresh_token,
-        expires,
-    })
+    Ok(data.into())
 }
 
 #[derive(serde::Deserialize)]
-struct TokenResponse {
+pub(super) struct TokenResponse {
     access_token: String,
     refresh_token: String,
     expires_in: u64,
 }
 
+impl From<TokenResponse> for OAuthCredentials {
+    fn from(data: TokenResponse) -> Self {
+        // 5 minute buffer before expiry
+        let expires = now_ms() + (data.expires_in * 1000) - (5 * 60 * 1000);
+        Self {
+            access: data.access_token,
+            refresh: data.refresh_token,
+            expires,
+        }
+    }
+}
+
 /// Verify that a PKCE verifier and challenge are correctly related.
 /// The challenge must be the base64url-encoded SHA-256 of the verifier.
 pub fn verify_pkce(verifier: &str, challenge: &str) -> bool {
@@ -165,7 +179,7 @@ pub fn verify_pkce(verifier: &str, challenge: &str) -> bool {
 }
 
 /// Minimal URL encoding for query parameters.
-fn urlencoded(s: &str) -> String {
+pub(super) fn urlencoded(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for b in s.bytes() {
         match b {
@@ -246,6 +260,18 @@ mod tests {
         assert_eq!(state_value, verifier);
     }
 
+    #[test]
+    fn only_the_paste_flow_shows_the_code() {
+        let (paste, _) = build_authorize_url();
+        assert!(paste.contains("?code=true&"), "{paste}");
+        let (local, _) = build_authorize_url_for("http://localhost:4000/callback");
+        assert!(!local.contains("code=true"), "{local}");
+        assert!(
+            local.contains("redirect_uri=http%3A%2F%2Flocalhost%3A4000%2Fcallback"),
+            "{local}"
+        );
+    }
+
     #[test]
     fn authorize_url_verifier_is_valid_pkce() {
         let (url, verifier) = build_authorize_url();
@@ -1,9 +1,11 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 
 use super::oauth::OAuthCredentials;
+use crate::consts::DEFAULT_ACCOUNT;
+use crate::events::{Event, EventBus};
 
 /// Credential types stored per provider.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -17,59 +19,161 @@ pub enum Credential {
 
 /// Manages credential storage in SQLite.
 ///
+/// A provider can have several named accounts (work, personal); `get`,
+/// `set` and `remove` act on the one [`account`](Self::account) names.
 /// Shares a database with memory and config — pass the same connection
 /// or path used for `SqliteMemory`.
 pub struct AuthStorage {
     conn: Mutex<Connection>,
+    /// Set by [`pinned`](Self::pinned): the account to use whatever is
+    /// selected.
+    pinned: Option<String>,
+    /// Set by [`with_events`](Self::with_events): where refreshed tokens
+    /// are announced.
+    events: Option<Arc<EventBus>>,
 }
 
 impl AuthStorage {
     /// Open or create a credentials table in the given database path.
     /// Use `":memory:"` for tests.
     pub fn open(path: &str) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS credentials (
-                provider TEXT PRIMARY KEY,
-                data     TEXT NOT NULL
-            )",
-        )?;
+        let conn = crate::db::open(path)?;
         Ok(Self {
             conn: Mutex::new(conn),
+            pinned: None,
+            events: None,
         })
     }
 
-    /// Get credential for a provider.
+    /// This storage announcing an [`Event::AuthChanged`] on `events`
+    /// whenever it refreshes a token.
+    pub fn with_events(self, events: Arc<EventBus>) -> Self {
+        Self {
+            events: Some(events),
+            ..self
+        }
+    }
+
+    /// This storage acting on `account` of every provider, without
+    /// selecting it for anyone else — to check one account's credentials.
+    pub fn pinned(self, account: &str) -> Self {
+        Self {
+            pinned: Some(account.to_string()),
+            ..self
+        }
+    }
+
+    /// The account `provider` uses: the last one selected, or
+    /// [`DEFAULT_ACCOUNT`].
+    pub fn account(&self, provider: &str) -> Result<String> {
+        if let Some(account) = &self.pinned {
+            return Ok(account.clone());
+        }
+        let conn = self.conn.lock().unwrap();
+        let account = conn
+            .query_row(
+                "SELECT account FROM active_accounts WHERE provider = ?1",
+                [provider],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(account.unwrap_or_else(|| DEFAULT_ACCOUNT.to_string()))
+    }
+
+    /// Use `account` for `provider` from now on, whether or not it has
+    /// credentials yet — [`set`](Self::set) stores them there.
+    pub fn set_account(&self, provider: &str, account: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO active_accounts (provider, account) VALUES (?1, ?2)
+             ON CONFLICT(provider) DO UPDATE SET account = excluded.account",
+            [provider, account],
+        )?;
+        Ok(())
+    }
+
+    /// Names of the accounts with credentials for `provider`, sorted.
+    pub fn accounts(&self, provider: &str) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT account FROM credentials WHERE provider = ?1 ORDER BY account")?;
+        let accounts = stmt
+            .query_map([provider], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(accounts)
+    }
+
+    /// Every stored credential as (provider, account, credential), sorted.
+    pub fn all(&self) -> Result<Vec<(String, String, Credential)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT provider, account, data FROM credentials ORDER BY provider, account",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter()
+            .map(|(provider, account, json)| Ok((provider, account, serde_json::from_str(&json)?)))
+            .collect()
+    }
+
+    /// Get credential for a provider's current account.
     pub fn get(&self, provider: &str) -> Result<Option<Credential>> {
+        let account = self.account(provider)?;
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT data FROM credentials WHERE provider = ?1")?;
-        let mut rows = stmt.query([provider])?;
-        match rows.next()? {
-            Some(row) => {
-                let json: String = row.get(0)?;
-                let cred: Credential = serde_json::from_str(&json)?;
-                Ok(Some(cred))
-            }
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT data FROM credentials WHERE provider = ?1 AND account = ?2",
+                [provider, &account],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match json {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
             None => Ok(None),
         }
     }
 
-    /// Store credential for a provider (upsert).
+    /// Store credential for a provider's current account (upsert).
     pub fn set(&self, provider: &str, credential: Credential) -> Result<()> {
+        let account = self.account(provider)?;
         let json = serde_json::to_string(&credential)?;
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO credentials (provider, data) VALUES (?1, ?2)
-             ON CONFLICT(provider) DO UPDATE SET data = excluded.data",
-            [provider, &json],
+            "INSERT INTO credentials (provider, account, data) VALUES (?1, ?2, ?3)
+             ON CONFLICT(provider, account) DO UPDATE SET data = excluded.data",
+            [provider, &account, &json],
         )?;
         Ok(())
     }
 
-    /// Remove credential for a provider.
+    /// Store `credential` as `account` of `provider` unless that account
+    /// already has one; true if it was stored.
+    pub fn set_if_absent(
+        &self,
+        provider: &str,
+        account: &str,
+        credential: &Credential,
+    ) -> Result<bool> {
+        let json = serde_json::to_string(credential)?;
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO credentials (provider, account, data) VALUES (?1, ?2, ?3)",
+            [provider, account, &json],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Remove credential for a provider's current account.
     pub fn remove(&self, provider: &str) -> Result<()> {
+        let account = self.account(provider)?;
         let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM credentials WHERE provider = ?1", [provider])?;
+        conn.execute(
+            "DELETE FROM credentials WHERE provider = ?1 AND account = ?2",
+            [provider, &account],
+        )?;
         Ok(())
     }
 
@@ -84,6 +188,12 @@ impl AuthStorage {
                         let refreshed = super::oauth::refresh_token(&oauth.refresh).await?;
                         oauth = refreshed.clone();
                         self.set(provider, Credential::OAuth(refreshed))?;
+                        if let Some(events) = &self.events {
+                            events.emit(Event::AuthChanged {
+                                provider: provider.to_string(),
+                                status: super::status(self, provider)?,
+                            });
+                        }
                     }
                     return Ok(Some(oauth.access));
                 }
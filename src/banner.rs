@@ -1,10 +1,46 @@
 //! Startup banner and session summary display.
 
+use std::io::IsTerminal;
 use std::path::Path;
+use tokio::io::AsyncWriteExt;
 
-use crate::consts::{AUTHOR, HOMEPAGE, REPO, format_number};
+use crate::auth::StoredCredential;
+use crate::auth::storage::Credential;
+use crate::batch::BatchResult;
+use crate::consts::{AUTHOR, DEFAULT_PAGER, HOMEPAGE, REPO, format_number};
+use crate::doctor::{Check, Status};
+use crate::engine::{Interrupted, TaskResult, TaskStatus};
+use crate::events::Progress;
+use crate::memory::SessionEntry;
+use crate::metrics::{MetricsSummary, MetricsTotals};
+use crate::schedule::{Schedule, ScheduleRun};
 use crate::thinker::TokenUsage;
 
+/// Number of most recent tasks shown when resuming a session.
+const RESUME_RECAP_ENTRIES: usize = 3;
+
+/// Task and answer are each cut to this many characters in the recap.
+const RECAP_WIDTH: usize = 60;
+
+/// Marks the final answer; its later lines are indented to match.
+const ANSWER_PREFIX: &str = "=> ";
+
+/// Narrowest the answer is wrapped to, however small the terminal.
+const MIN_ANSWER_WIDTH: usize = 20;
+
+/// How much of the startup banner to show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BannerStyle {
+    /// The golem box and every detail.
+    #[default]
+    Full,
+    /// One line: version, provider, model, shell mode and working directory.
+    Compact,
+    /// Nothing.
+    Off,
+}
+
 /// Session configuration for display in the startup banner.
 pub struct BannerInfo<'a> {
     pub provider: &'a str,
@@ -15,8 +51,16 @@ pub struct BannerInfo<'a> {
     pub memory: &'a str,
 }
 
-/// Print the startup banner with session info.
-pub fn print_banner(info: &BannerInfo) {
+/// Print the startup banner with session info, in `style`.
+pub fn print_banner(style: BannerStyle, info: &BannerInfo) {
+    match style {
+        BannerStyle::Full => print_full_banner(info),
+        BannerStyle::Compact => println!("{}", format_compact_banner(info)),
+        BannerStyle::Off => {}
+    }
+}
+
+fn print_full_banner(info: &BannerInfo) {
     println!(
         r#"
    ╔═══════════════════════════════════════╗
@@ -47,6 +91,123 @@ pub fn print_banner(info: &BannerInfo) {
     );
 }
 
+fn format_compact_banner(info: &BannerInfo) -> String {
+    format!(
+        "golem {} · {} ({}) · {} · {}",
+        env!("CARGO_PKG_VERSION"),
+        info.provider,
+        info.model,
+        info.shell_mode,
+        info.working_dir.display(),
+    )
+}
+
+/// Print a short recap of a resumed session: its size and last few tasks.
+pub fn print_resume_recap(session: &str, history: &[SessionEntry]) {
+    if history.is_empty() {
+        println!("   resuming session {session} (no earlier tasks)");
+        return;
+    }
+    println!(
+        "   resuming session {session} ({} earlier task{})",
+        history.len(),
+        if history.len() == 1 { "" } else { "s" }
+    );
+    let start = history.len().saturating_sub(RESUME_RECAP_ENTRIES);
+    for entry in &history[start..] {
+        println!(
+            "     • {} → {}",
+            one_line(&entry.task, RECAP_WIDTH),
+            one_line(&entry.answer, RECAP_WIDTH)
+        );
+    }
+}
+
+/// First line of `s`, cut to `max` characters with an ellipsis.
+pub fn one_line(s: &str, max: usize) -> String {
+    let line = s.lines().next().unwrap_or("");
+    match line.char_indices().nth(max) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None if s.lines().nth(1).is_some() => format!("{line}…"),
+        None => line.to_string(),
+    }
+}
+
+/// Print a task's answer after `=>`. On a terminal its markdown is
+/// rendered — headings, lists, tables, code — and wrapped to fit; piped,
+/// it is left as the model wrote it.
+pub fn print_answer_text(answer: &str) {
+    if std::io::stdout().is_terminal() {
+        let (columns, _) = termimad::terminal_size();
+        print!("\n{}", format_markdown_answer(answer, columns as usize));
+    } else {
+        println!("\n{ANSWER_PREFIX}{answer}");
+    }
+}
+
+/// Print `answer` like [`print_answer_text`], through `$PAGER` (else
+/// `less -R`) when it is taller than the terminal.
+pub async fn page_answer_text(answer: &str) {
+    if !std::io::stdout().is_terminal() {
+        println!("\n{ANSWER_PREFIX}{answer}");
+        return;
+    }
+    let (columns, rows) = termimad::terminal_size();
+    let text = format!("\n{}", format_markdown_answer(answer, columns as usize));
+    if text.lines().count() < rows as usize || !page(&text).await {
+        print!("{text}");
+    }
+}
+
+/// Show `text` in the pager; `false` if there is none to run.
+async fn page(text: &str) -> bool {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        return false;
+    };
+    let Ok(mut child) = tokio::process::Command::new(program)
+        .args(words)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; that's fine
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+    child.wait().await.is_ok()
+}
+
+fn format_markdown_answer(answer: &str, width: usize) -> String {
+    let width = width
+        .saturating_sub(ANSWER_PREFIX.len())
+        .max(MIN_ANSWER_WIDTH);
+    let mut skin = termimad::MadSkin::default();
+    // Centered headings read oddly right after `=>`
+    for header in &mut skin.headers {
+        header.align = termimad::Alignment::Left;
+    }
+    let rendered = skin.text(answer, Some(width)).to_string();
+    let indent = " ".repeat(ANSWER_PREFIX.len());
+    let mut out = ANSWER_PREFIX.to_string();
+    for (i, line) in rendered.lines().enumerate() {
+        if i > 0 && !line.trim().is_empty() {
+            out.push_str(&indent);
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    if rendered.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
 /// Print the session summary (token usage + farewell).
 pub fn print_session_summary(usage: TokenUsage) {
     if usage.total() > 0 {
@@ -60,6 +221,324 @@ pub fn print_session_summary(usage: TokenUsage) {
     println!("goodbye.");
 }
 
+/// Print aggregated task metrics for `golem stats`.
+pub fn print_task_stats(summary: &MetricsSummary) {
+    print!("{}", format_task_stats(summary));
+}
+
+fn format_task_stats(summary: &MetricsSummary) -> String {
+    if summary.overall.tasks == 0 {
+        return "no tasks recorded yet.\n".to_string();
+    }
+    let mut out = format_totals("all models", &summary.overall);
+    for (model, totals) in &summary.by_model {
+        out.push('\n');
+        out.push_str(&format_totals(model, totals));
+    }
+    out
+}
+
+fn format_totals(label: &str, totals: &MetricsTotals) -> String {
+    let success_rate = totals.succeeded * 100 / totals.tasks.max(1);
+    format!(
+        "{label}\n  tasks:      {} ({}% succeeded)\n  avg time:   {:.1}s\n  avg iters:  {:.1}\n  tool calls: {}\n  tokens:     {} input + {} output = {}\n",
+        format_number(totals.tasks),
+        success_rate,
+        totals.avg_duration_ms() as f64 / 1000.0,
+        totals.avg_iterations(),
+        format_number(totals.tool_calls),
+        format_number(totals.usage.input_tokens),
+        format_number(totals.usage.output_tokens),
+        format_number(totals.usage.total()),
+    )
+}
+
+/// Print what a finished task took, under its answer.
+pub fn print_task_result(result: &TaskResult) {
+    println!("{}", format_task_result(result));
+}
+
+/// `   (3 iterations, 2 tool calls, 1,234 tokens, 4.2s)`, with a note for a
+/// partial answer.
+pub fn format_task_result(result: &TaskResult) -> String {
+    let plural = |n: u64, word: &str| {
+        let s = if n == 1 { "" } else { "s" };
+        format!("{} {word}{s}", format_number(n))
+    };
+    let partial = match result.status {
+        TaskStatus::Completed => "",
+        TaskStatus::Partial => "partial answer, ",
+    };
+    format!(
+        "   ({partial}{}, {}, {}, {:.1}s)",
+        plural(result.iterations, "iteration"),
+        plural(result.tool_calls, "tool call"),
+        plural(result.usage.total(), "token"),
+        result.duration.as_secs_f64()
+    )
+}
+
+/// The counters on the spinner line while a task runs:
+/// `3/20 · 12,345 tokens · $0.0412`.
+pub fn format_progress(progress: &Progress) -> String {
+    let mut out = format!(
+        "{}/{} · {} tokens",
+        progress.iteration,
+        progress.max_iterations,
+        format_number(progress.tokens)
+    );
+    if let Some(cost) = progress.cost {
+        out.push_str(&format!(" · ${cost:.4}"));
+    }
+    out
+}
+
+/// Print the outcome of a task as one JSON document, for `--output json`.
+pub fn print_task_json(result: &anyhow::Result<TaskResult>) {
+    println!("{}", format_task_json(result));
+}
+
+fn format_task_json(result: &anyhow::Result<TaskResult>) -> String {
+    serde_json::to_string_pretty(&task_json(result)).expect("task JSON always serializes")
+}
+
+/// How a task ended, as `--output json` prints it.
+pub fn task_json(result: &anyhow::Result<TaskResult>) -> serde_json::Value {
+    match result {
+        Ok(result) => serde_json::json!({
+            "status": match result.status {
+                TaskStatus::Completed => "completed",
+                TaskStatus::Partial => "partial",
+            },
+            "answer": result.answer,
+            "iterations": result.iterations,
+            "tool_calls": result.tool_calls,
+            "usage": result.usage,
+            "duration_ms": result.duration.as_millis() as u64,
+        }),
+        Err(e) => serde_json::json!({
+            "status": if e.is::<Interrupted>() { "interrupted" } else { "failed" },
+            "error": format!("{e:#}"),
+        }),
+    }
+}
+
+/// Print how a replayed task ended next to how its recording did.
+pub fn print_replay_outcome(recorded: Option<&str>, result: &anyhow::Result<TaskResult>) {
+    println!("{}", format_replay_outcome(recorded, result));
+}
+
+fn format_replay_outcome(recorded: Option<&str>, result: &anyhow::Result<TaskResult>) -> String {
+    match (recorded, result) {
+        (Some(recorded), Ok(result)) if recorded == result.answer => {
+            "  ✓ same answer as recorded".to_string()
+        }
+        (Some(recorded), Ok(_)) => format!(
+            "  ✗ answer differs; recorded: {}",
+            one_line(recorded, RECAP_WIDTH)
+        ),
+        (Some(recorded), Err(e)) => format!(
+            "  ✗ failed: {e}; recorded: {}",
+            one_line(recorded, RECAP_WIDTH)
+        ),
+        (None, Ok(_)) => "  ✗ answered, but the recorded run never did".to_string(),
+        (None, Err(e)) => format!("  ✓ stopped like the recorded run: {e}"),
+    }
+}
+
+/// Print `golem doctor`'s findings, with the fix under each problem.
+pub fn print_doctor_report(checks: &[Check]) {
+    print!("{}", format_doctor_report(checks));
+}
+
+fn format_doctor_report(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        let mark = match check.status {
+            Status::Ok => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        };
+        // Later lines, e.g. of a TOML parse error, line up under the first
+        let indent = format!("    {:<width$}  ", "");
+        let mut lines = check.detail.trim_end().lines();
+        out.push_str(&format!(
+            "  {mark} {:<width$}  {}\n",
+            check.name,
+            lines.next().unwrap_or("")
+        ));
+        for line in lines {
+            out.push_str(format!("{indent}{line}").trim_end());
+            out.push('\n');
+        }
+        if let Some(fix) = &check.fix {
+            out.push_str(&format!("{indent}→ {fix}\n"));
+        }
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (failed, warned) = (count(Status::Fail), count(Status::Warn));
+    out.push_str(&match (failed, warned) {
+        (0, 0) => "\nall good.\n".to_string(),
+        (0, w) => format!("\nworking, with {w} warning{}.\n", plural(w)),
+        (f, _) => format!("\n{f} problem{} to fix.\n", plural(f)),
+    });
+    out
+}
+
+/// A stored credential and, with `--verify`, how many models it reached
+/// or why it was rejected.
+pub type CheckedCredential = (StoredCredential, Option<Result<usize, String>>);
+
+/// Print `golem auth status`: each provider's accounts, the active one
+/// starred, with expiry and, when checked, whether they still work.
+/// `env_keys` are the API key variables that are set.
+pub fn print_auth_status(credentials: &[CheckedCredential], env_keys: &[&str]) {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    print!("{}", format_auth_status(credentials, env_keys, now_ms));
+}
+
+fn format_auth_status(credentials: &[CheckedCredential], env_keys: &[&str], now_ms: u64) -> String {
+    let mut out = String::new();
+    if credentials.is_empty() {
+        out.push_str("no stored credentials — run `golem login`\n");
+    }
+    let width = credentials
+        .iter()
+        .map(|(c, _)| c.account.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut provider = None;
+    for (stored, checked) in credentials {
+        if provider != Some(&stored.provider) {
+            out.push_str(&format!("{}\n", stored.provider));
+            provider = Some(&stored.provider);
+        }
+        let mark = if stored.active { "*" } else { " " };
+        let kind = match &stored.credential {
+            Credential::OAuth(oauth) if oauth.expires <= now_ms => {
+                "OAuth, expired (refreshed on next use)".to_string()
+            }
+            Credential::OAuth(oauth) => {
+                let minutes = (oauth.expires - now_ms) / 60_000;
+                format!("OAuth, expires in {}h {}m", minutes / 60, minutes % 60)
+            }
+            Credential::ApiKey { key } => {
+                // Enough to tell keys apart, not enough to use one
+                let tail = key.get(key.len().saturating_sub(4)..).unwrap_or("");
+                format!("API key …{tail}")
+            }
+        };
+        let check = match checked {
+            None => String::new(),
+            Some(Ok(models)) => format!("  ✓ works ({models} models)"),
+            Some(Err(e)) => format!("  ✗ {}", one_line(e, RECAP_WIDTH)),
+        };
+        out.push_str(&format!(
+            "  {mark} {:<width$}  {kind}{check}\n",
+            stored.account
+        ));
+    }
+    for key in env_keys {
+        out.push_str(&format!(
+            "\n{key} is set; it is used when the active account has no credentials\n"
+        ));
+    }
+    out
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+/// Print two lines per batch task — how it went, then its answer or
+/// error — and the totals.
+pub fn print_batch_summary(results: &[BatchResult], report: Option<&Path>) {
+    print!("{}", format_batch_summary(results, report));
+}
+
+fn format_batch_summary(results: &[BatchResult], report: Option<&Path>) -> String {
+    let mut out = String::from("\nbatch results\n");
+    for (i, result) in results.iter().enumerate() {
+        let mark = if result.succeeded() { "✓" } else { "✗" };
+        out.push_str(&format!(
+            "  {mark} {:>3}. {} ({:.1}s)\n",
+            i + 1,
+            result.task.lines().next().unwrap_or_default(),
+            result.duration_ms as f64 / 1000.0
+        ));
+        let outcome = result.answer.as_deref().or(result.error.as_deref());
+        out.push_str(&format!(
+            "         → {}\n",
+            one_line(outcome.unwrap_or_default(), RECAP_WIDTH)
+        ));
+    }
+    let failed = results.iter().filter(|r| !r.succeeded()).count();
+    out.push_str(&format!(
+        "  {} of {} succeeded",
+        results.len() - failed,
+        results.len()
+    ));
+    if let Some(report) = report {
+        out.push_str(&format!(" — report: {}", report.display()));
+    }
+    out.push('\n');
+    out
+}
+
+/// Print each schedule with when it runs next.
+pub fn print_schedules(schedules: &[(Schedule, Option<String>)]) {
+    print!("{}", format_schedules(schedules));
+}
+
+fn format_schedules(schedules: &[(Schedule, Option<String>)]) -> String {
+    if schedules.is_empty() {
+        return "no schedules; add one with `golem schedule add \"0 9 * * *\" \"<task>\"`\n"
+            .to_string();
+    }
+    let mut out = String::new();
+    for (schedule, next) in schedules {
+        out.push_str(&format!(
+            "  {}  {}  next: {}\n    {}\n",
+            schedule.id,
+            schedule.cron,
+            next.as_deref().unwrap_or("never"),
+            one_line(&schedule.task, RECAP_WIDTH)
+        ));
+    }
+    out
+}
+
+/// Print scheduled runs, newest first, with their answer or error.
+pub fn print_schedule_runs(runs: &[ScheduleRun]) {
+    print!("{}", format_schedule_runs(runs));
+}
+
+fn format_schedule_runs(runs: &[ScheduleRun]) -> String {
+    if runs.is_empty() {
+        return "no scheduled runs yet.\n".to_string();
+    }
+    let mut out = String::new();
+    for run in runs {
+        let mark = if run.error.is_none() { "✓" } else { "✗" };
+        let outcome = run.answer.as_deref().or(run.error.as_deref());
+        out.push_str(&format!(
+            "  {mark} {} {} ({})\n    → {}\n",
+            run.due,
+            run.schedule,
+            run.status,
+            one_line(outcome.unwrap_or_default(), RECAP_WIDTH)
+        ));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,7 +555,43 @@ mod tests {
             memory: "ephemeral",
         };
         // Just verify it doesn't panic
-        print_banner(&info);
+        print_banner(BannerStyle::Full, &info);
+        print_banner(BannerStyle::Off, &info);
+    }
+
+    #[test]
+    fn compact_banner_is_one_line() {
+        let banner = format_compact_banner(&BannerInfo {
+            provider: "anthropic",
+            model: "claude-sonnet-4-5",
+            auth_status: "OAuth",
+            shell_mode: "read-only",
+            working_dir: Path::new("/srv/app"),
+            memory: "~/.golem/golem.db (global)",
+        });
+        assert_eq!(
+            banner,
+            format!(
+                "golem {} · anthropic (claude-sonnet-4-5) · read-only · /srv/app",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn progress_shows_cost_when_known() {
+        let progress = Progress {
+            iteration: 3,
+            max_iterations: 20,
+            tokens: 12_345,
+            cost: Some(0.04123),
+        };
+        assert_eq!(format_progress(&progress), "3/20 · 12,345 tokens · $0.0412");
+        let unpriced = Progress {
+            cost: None,
+            ..progress
+        };
+        assert_eq!(format_progress(&unpriced), "3/20 · 12,345 tokens");
     }
 
     #[test]
@@ -84,6 +599,7 @@ mod tests {
         let usage = TokenUsage {
             input_tokens: 1234,
             output_tokens: 567,
+            cache_read_tokens: 0,
         };
         // Just verify it doesn't panic
         print_session_summary(usage);
@@ -94,4 +610,309 @@ mod tests {
         // Should only print "goodbye." with no token line
         print_session_summary(TokenUsage::default());
     }
+
+    #[test]
+    fn print_resume_recap_does_not_panic() {
+        print_resume_recap("default", &[]);
+        let history = vec![SessionEntry {
+            task: "list files".to_string(),
+            answer: "a.txt\nb.txt".to_string(),
+        }];
+        print_resume_recap("work", &history);
+    }
+
+    #[test]
+    fn one_line_truncates() {
+        assert_eq!(one_line("short", 10), "short");
+        assert_eq!(one_line("abcdefghij", 3), "abc…");
+        assert_eq!(one_line("first\nsecond", 10), "first…");
+        assert_eq!(one_line("", 10), "");
+    }
+
+    /// `s` without its ANSI styling.
+    fn plain(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\u{1b}' {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn answer_markdown_is_rendered_and_indented() {
+        let answer = "# Disk usage\n\nTwo mounts are **nearly full**:\n\n- `/` at 91%\n- `/home` at 88%\n\n| mount | used |\n|---|---|\n| / | 91% |\n";
+        assert_eq!(
+            plain(&format_markdown_answer(answer, 40)),
+            "=> Disk usage\n\
+             \n\
+             \x20  Two mounts are nearly full:\n\
+             \n\
+             \x20  - / at 91%\n\
+             \x20  - /home at 88%\n\
+             \n\
+             \x20  │mount│used│\n\
+             \x20  ├─────┼────┤\n\
+             \x20  │/    │91% │\n"
+        );
+    }
+
+    #[test]
+    fn plain_answers_keep_their_shape() {
+        assert_eq!(plain(&format_markdown_answer("42", 40)), "=> 42\n");
+        assert_eq!(format_markdown_answer("", 40), "=> \n");
+    }
+
+    #[test]
+    fn doctor_report_shows_fixes_and_a_verdict() {
+        let check = |name: &str, status, fix: Option<&str>| Check {
+            name: name.to_string(),
+            status,
+            detail: "detail".to_string(),
+            fix: fix.map(str::to_string),
+        };
+        let report = format_doctor_report(&[
+            check("database", Status::Ok, None),
+            check("git", Status::Warn, Some("install git")),
+            check("credentials", Status::Fail, Some("run `golem login`")),
+        ]);
+        assert_eq!(
+            report,
+            "  ✓ database     detail\n\
+             \x20 ! git          detail\n\
+             \x20                → install git\n\
+             \x20 ✗ credentials  detail\n\
+             \x20                → run `golem login`\n\
+             \n1 problem to fix.\n"
+        );
+        assert!(format_doctor_report(&[check("sh", Status::Ok, None)]).ends_with("all good.\n"));
+    }
+
+    #[test]
+    fn auth_status_lists_accounts() {
+        let stored = |account: &str, active, credential| StoredCredential {
+            provider: "anthropic".to_string(),
+            account: account.to_string(),
+            active,
+            credential,
+        };
+        let oauth = |expires| {
+            Credential::OAuth(crate::auth::oauth::OAuthCredentials {
+                access: "a".to_string(),
+                refresh: "r".to_string(),
+                expires,
+            })
+        };
+        let hour = 3_600_000;
+        let report = format_auth_status(
+            &[
+                (
+                    stored("default", true, oauth(2 * hour + 300_000)),
+                    Some(Ok(9)),
+                ),
+                (
+                    stored(
+                        "work",
+                        false,
+                        Credential::ApiKey {
+                            key: "sk-ant-abcd1234".to_string(),
+                        },
+                    ),
+                    Some(Err("401 invalid x-api-key".to_string())),
+                ),
+                (stored("old", false, oauth(hour)), None),
+            ],
+            &["ANTHROPIC_API_KEY"],
+            hour,
+        );
+        assert_eq!(
+            report,
+            "anthropic\n\
+             \x20 * default  OAuth, expires in 1h 5m  ✓ works (9 models)\n\
+             \x20   work     API key …1234  ✗ 401 invalid x-api-key\n\
+             \x20   old      OAuth, expired (refreshed on next use)\n\
+             \nANTHROPIC_API_KEY is set; it is used when the active account has no credentials\n"
+        );
+        assert!(format_auth_status(&[], &[], 0).starts_with("no stored credentials"));
+    }
+
+    #[test]
+    fn format_task_stats_empty() {
+        assert_eq!(
+            format_task_stats(&MetricsSummary::default()),
+            "no tasks recorded yet.\n"
+        );
+    }
+
+    #[test]
+    fn format_task_stats_overall_and_per_model() {
+        let totals = MetricsTotals {
+            tasks: 4,
+            succeeded: 3,
+            duration_ms: 10_000,
+            iterations: 10,
+            tool_calls: 1234,
+            usage: TokenUsage {
+                input_tokens: 2000,
+                output_tokens: 500,
+                cache_read_tokens: 0,
+            },
+        };
+        let summary = MetricsSummary {
+            overall: totals.clone(),
+            by_model: vec![("sonnet".to_string(), totals)],
+        };
+        let out = format_task_stats(&summary);
+        assert!(out.starts_with("all models\n"));
+        assert!(out.contains("\nsonnet\n"));
+        assert!(out.contains("tasks:      4 (75% succeeded)"));
+        assert!(out.contains("avg time:   2.5s"));
+        assert!(out.contains("avg iters:  2.5"));
+        assert!(out.contains("tool calls: 1,234"));
+        assert!(out.contains("tokens:     2,000 input + 500 output = 2,500"));
+    }
+
+    #[test]
+    fn schedule_runs_show_their_outcome() {
+        let runs = [
+            ScheduleRun {
+                schedule: "logs".to_string(),
+                due: "2026-10-16 09:00".to_string(),
+                status: "failed".to_string(),
+                answer: None,
+                error: Some("no credentials".to_string()),
+            },
+            ScheduleRun {
+                schedule: "logs".to_string(),
+                due: "2026-10-15 09:00".to_string(),
+                status: "completed".to_string(),
+                answer: Some("all quiet".to_string()),
+                error: None,
+            },
+        ];
+        let out = format_schedule_runs(&runs);
+        assert!(
+            out.starts_with("  ✗ 2026-10-16 09:00 logs (failed)\n    → no credentials\n"),
+            "{out}"
+        );
+        assert!(out.contains("  ✓ 2026-10-15 09:00 logs (completed)\n    → all quiet\n"));
+        assert!(format_schedules(&[]).contains("golem schedule add"));
+    }
+
+    #[test]
+    fn format_batch_summary_marks_failures() {
+        let result = |task: &str, error: Option<&str>| BatchResult {
+            task: task.to_string(),
+            answer: error.is_none().then(|| "ok".to_string()),
+            error: error.map(str::to_string),
+            duration_ms: 1500,
+            iterations: None,
+            tool_calls: None,
+            usage: None,
+        };
+        let results = vec![
+            result("list files\nin detail", None),
+            result("break", Some("max iterations")),
+        ];
+        let out = format_batch_summary(&results, Some(Path::new("tasks.report.json")));
+        assert!(out.contains("  ✓   1. list files (1.5s)\n         → ok\n"));
+        assert!(out.contains("  ✗   2. break (1.5s)\n         → max iterations\n"));
+        assert!(out.contains("1 of 2 succeeded — report: tasks.report.json"));
+        let out = format_batch_summary(&results, None);
+        assert!(out.ends_with("1 of 2 succeeded\n"));
+    }
+
+    #[test]
+    fn task_result_line() {
+        let result = TaskResult {
+            answer: "done".to_string(),
+            iterations: 1,
+            tool_calls: 3,
+            usage: TokenUsage {
+                input_tokens: 1200,
+                output_tokens: 34,
+                cache_read_tokens: 0,
+            },
+            duration: std::time::Duration::from_millis(4210),
+            status: crate::engine::TaskStatus::Completed,
+        };
+        assert_eq!(
+            format_task_result(&result),
+            "   (1 iteration, 3 tool calls, 1,234 tokens, 4.2s)"
+        );
+        let partial = TaskResult {
+            status: crate::engine::TaskStatus::Partial,
+            ..result
+        };
+        assert_eq!(
+            format_task_result(&partial),
+            "   (partial answer, 1 iteration, 3 tool calls, 1,234 tokens, 4.2s)"
+        );
+    }
+
+    #[test]
+    fn task_json_for_answers_and_errors() {
+        let result = TaskResult {
+            answer: "42".to_string(),
+            iterations: 2,
+            tool_calls: 1,
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 7,
+                cache_read_tokens: 0,
+            },
+            duration: std::time::Duration::from_millis(1500),
+            status: crate::engine::TaskStatus::Partial,
+        };
+        let doc: serde_json::Value = serde_json::from_str(&format_task_json(&Ok(result))).unwrap();
+        assert_eq!(
+            doc,
+            serde_json::json!({
+                "status": "partial",
+                "answer": "42",
+                "iterations": 2,
+                "tool_calls": 1,
+                "usage": {"input_tokens": 100, "output_tokens": 7, "cache_read_tokens": 0},
+                "duration_ms": 1500,
+            })
+        );
+
+        let failed = format_task_json(&Err(anyhow::anyhow!("max iterations (5) reached")));
+        let doc: serde_json::Value = serde_json::from_str(&failed).unwrap();
+        assert_eq!(doc["status"], "failed");
+        assert_eq!(doc["error"], "max iterations (5) reached");
+        let doc: serde_json::Value =
+            serde_json::from_str(&format_task_json(&Err(Interrupted.into()))).unwrap();
+        assert_eq!(doc["status"], "interrupted");
+    }
+
+    #[test]
+    fn replay_outcome_compares_answers() {
+        let answered = |answer: &str| {
+            Ok(TaskResult {
+                answer: answer.to_string(),
+                iterations: 1,
+                tool_calls: 0,
+                usage: TokenUsage::default(),
+                duration: std::time::Duration::ZERO,
+                status: crate::engine::TaskStatus::Completed,
+            })
+        };
+        assert_eq!(
+            format_replay_outcome(Some("a"), &answered("a")),
+            "  ✓ same answer as recorded"
+        );
+        assert_eq!(
+            format_replay_outcome(Some("a"), &answered("b")),
+            "  ✗ answer differs; recorded: a"
+        );
+        assert_eq!(
+            format_replay_outcome(None, &Err(anyhow::anyhow!("out of responses"))),
+            "  ✓ stopped like the recorded run: out of responses"
+        );
+    }
 }
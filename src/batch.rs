@@ -0,0 +1,292 @@
+//! Batch mode: run a list of tasks from a file and report on each.
+//!
+//! `golem batch tasks.txt` reads one task per line (blank lines and `#`
+//! comments are skipped), or a YAML list of strings from a `.yaml`/`.yml`
+//! file. Every task gets a fresh engine, so tasks don't see each other's
+//! history and can run in parallel. Repeated `-r` flags and the REPL's
+//! [`TaskQueue`] run the same way.
+
+use anyhow::{Context, Result, bail};
+use futures::StreamExt;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::engine::Engine;
+use crate::thinker::TokenUsage;
+
+/// How one task of a batch went.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchResult {
+    pub task: String,
+    /// `None` if the task failed.
+    pub answer: Option<String>,
+    /// `None` if the task produced an answer.
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    /// Only known for tasks that produced an answer.
+    pub iterations: Option<u64>,
+    pub tool_calls: Option<u64>,
+    pub usage: Option<TokenUsage>,
+}
+
+impl BatchResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Tasks waiting for `/queue run` in the REPL.
+#[derive(Debug, Default)]
+pub struct TaskQueue {
+    tasks: Mutex<Vec<String>>,
+}
+
+impl TaskQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `task`; returns how many tasks are now waiting.
+    pub fn push(&self, task: &str) -> usize {
+        let mut tasks = self.tasks.lock().expect("task queue lock poisoned");
+        tasks.push(task.to_string());
+        tasks.len()
+    }
+
+    /// The waiting tasks, in the order they were added.
+    pub fn tasks(&self) -> Vec<String> {
+        self.tasks.lock().expect("task queue lock poisoned").clone()
+    }
+
+    /// Empty the queue, returning what was in it.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.tasks.lock().expect("task queue lock poisoned"))
+    }
+}
+
+/// Read the tasks in `path`. YAML if the extension says so, else one per line.
+pub fn read_tasks(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let tasks = if yaml {
+        parse_yaml(&text).with_context(|| format!("{} is not a YAML list", path.display()))?
+    } else {
+        parse_lines(&text)
+    };
+    if tasks.is_empty() {
+        bail!("no tasks in {}", path.display());
+    }
+    Ok(tasks)
+}
+
+/// The task text for a `-r` argument: `@path` reads it from a file, `@@…`
+/// is a literal `@…`, anything else is the task itself.
+pub fn resolve_task(arg: &str) -> Result<String> {
+    if let Some(literal) = arg.strip_prefix("@@") {
+        return Ok(format!("@{literal}"));
+    }
+    let Some(path) = arg.strip_prefix('@') else {
+        return Ok(arg.to_string());
+    };
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read task file {path}"))?;
+    let task = text.trim();
+    if task.is_empty() {
+        bail!("task file {path} is empty");
+    }
+    Ok(task.to_string())
+}
+
+fn parse_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_yaml(text: &str) -> Result<Vec<String>> {
+    let tasks: Vec<String> = serde_yaml_ng::from_str(text)?;
+    Ok(tasks
+        .into_iter()
+        .map(|task| task.trim().to_string())
+        .filter(|task| !task.is_empty())
+        .collect())
+}
+
+/// Run every task on an engine from `make_engine`, up to `parallel` at a
+/// time. Results are in task order.
+pub async fn run_batch<E, F>(
+    tasks: &[String],
+    parallel: usize,
+    mut make_engine: F,
+) -> Vec<BatchResult>
+where
+    E: Engine,
+    F: FnMut() -> E,
+{
+    futures::stream::iter(tasks)
+        .map(|task| {
+            let mut engine = make_engine();
+            async move {
+                let started = Instant::now();
+                match engine.run(task).await {
+                    Ok(result) => BatchResult {
+                        task: task.clone(),
+                        answer: Some(result.answer),
+                        error: None,
+                        duration_ms: result.duration.as_millis() as u64,
+                        iterations: Some(result.iterations),
+                        tool_calls: Some(result.tool_calls),
+                        usage: Some(result.usage),
+                    },
+                    Err(e) => BatchResult {
+                        task: task.clone(),
+                        answer: None,
+                        error: Some(e.to_string()),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        iterations: None,
+                        tool_calls: None,
+                        usage: None,
+                    },
+                }
+            }
+        })
+        .buffered(parallel.max(1))
+        .collect()
+        .await
+}
+
+/// Write `results` to `path` as pretty-printed JSON.
+pub fn write_report(path: &Path, results: &[BatchResult]) -> Result<()> {
+    let json = serde_json::to_string_pretty(results)?;
+    std::fs::write(path, json + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{TaskResult, TaskStatus};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Answers with the task reversed, or fails on tasks containing "fail".
+    struct EchoEngine;
+
+    #[async_trait]
+    impl Engine for EchoEngine {
+        async fn run(&mut self, task: &str) -> Result<TaskResult> {
+            if task.contains("fail") {
+                bail!("could not {task}");
+            }
+            Ok(TaskResult {
+                answer: task.chars().rev().collect(),
+                iterations: 1,
+                tool_calls: 0,
+                usage: TokenUsage::default(),
+                duration: Duration::from_millis(3),
+                status: TaskStatus::Completed,
+            })
+        }
+    }
+
+    #[test]
+    fn lines_skip_blanks_and_comments() {
+        let tasks = parse_lines("list files\n\n# later\n  check disk  \n");
+        assert_eq!(tasks, ["list files", "check disk"]);
+    }
+
+    #[test]
+    fn yaml_is_a_list_of_strings() {
+        let tasks = parse_yaml("- list files\n- \"check: disk\"\n- |\n  two\n  lines\n").unwrap();
+        assert_eq!(tasks, ["list files", "check: disk", "two\nlines"]);
+        assert!(parse_yaml("task: nope").is_err());
+    }
+
+    #[test]
+    fn read_tasks_picks_format_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("tasks.yaml");
+        std::fs::write(&yaml, "- a\n- b\n").unwrap();
+        assert_eq!(read_tasks(&yaml).unwrap(), ["a", "b"]);
+
+        let text = dir.path().join("tasks.txt");
+        std::fs::write(&text, "- a\n").unwrap();
+        assert_eq!(read_tasks(&text).unwrap(), ["- a"]);
+
+        let empty = dir.path().join("empty.txt");
+        std::fs::write(&empty, "# nothing\n").unwrap();
+        assert!(read_tasks(&empty).is_err());
+    }
+
+    #[tokio::test]
+    async fn results_keep_task_order() {
+        let tasks: Vec<String> = ["abc", "fail here", "xyz"].map(String::from).into();
+        for parallel in [1, 3] {
+            let results = run_batch(&tasks, parallel, || EchoEngine).await;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].answer.as_deref(), Some("cba"));
+            assert_eq!(results[0].iterations, Some(1));
+            assert_eq!(results[0].duration_ms, 3);
+            assert!(!results[1].succeeded());
+            assert_eq!(results[1].error.as_deref(), Some("could not fail here"));
+            assert_eq!(results[2].task, "xyz");
+        }
+    }
+
+    #[test]
+    fn queue_keeps_order_until_taken() {
+        let queue = TaskQueue::new();
+        assert_eq!(queue.push("first"), 1);
+        assert_eq!(queue.push("second"), 2);
+        assert_eq!(queue.tasks(), ["first", "second"]);
+        assert_eq!(queue.take(), ["first", "second"]);
+        assert!(queue.tasks().is_empty());
+    }
+
+    #[test]
+    fn report_is_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        let results = vec![BatchResult {
+            task: "t".to_string(),
+            answer: Some("a".to_string()),
+            error: None,
+            duration_ms: 5,
+            iterations: Some(2),
+            tool_calls: Some(1),
+            usage: Some(TokenUsage::default()),
+        }];
+        write_report(&path, &results).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["answer"], "a");
+        assert_eq!(json[0]["duration_ms"], 5);
+        assert_eq!(json[0]["iterations"], 2);
+    }
+
+    #[test]
+    fn run_args_can_name_a_task_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("task.md");
+        std::fs::write(
+            &path,
+            "# Fix the build\n\nRun cargo build and fix errors.\n",
+        )
+        .unwrap();
+
+        let from_file = resolve_task(&format!("@{}", path.display())).unwrap();
+        assert_eq!(
+            from_file,
+            "# Fix the build\n\nRun cargo build and fix errors."
+        );
+        assert_eq!(resolve_task("list files").unwrap(), "list files");
+        assert_eq!(resolve_task("@@home").unwrap(), "@home");
+        assert!(resolve_task("@/no/such/file.md").is_err());
+    }
+}
@@ -0,0 +1,647 @@
+//! Assembling a working golem from [`Settings`].
+//!
+//! [`GolemBuilder`] does what `main.rs` used to do by hand: it picks the
+//! thinker for the configured provider, registers the shell, remember,
+//! forget and delegate tools, opens session memory, facts and metrics in
+//! the database, counts for Prometheus, traces to an OTLP collector if one
+//! is configured, and wires an [`EventBus`] to any subscribers. The `golem`
+//! binary is built this way, and so is golem embedded in another program:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use golem::builder::GolemBuilder;
+//! use golem::config::settings::Settings;
+//! use golem::engine::Engine;
+//!
+//! let settings = Settings {
+//!     db: Some(":memory:".to_string()),
+//!     ..Settings::default()
+//! };
+//! let mut golem = GolemBuilder::new(settings)
+//!     .echo(false)
+//!     .subscriber(|event| eprintln!("{event:?}"))
+//!     .build()
+//!     .await?;
+//! let result = golem.engine.run("how much disk space is left?").await?;
+//! println!("{}", result.answer);
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Result, bail};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::auth::AuthStorage;
+use crate::config::Config;
+use crate::config::settings::Settings;
+use crate::consts::{DEFAULT_MODEL, NO_DATABASE, default_db_path};
+use crate::engine::hooks::Hook;
+use crate::engine::notify::Notifier;
+use crate::engine::react::{ReactConfig, ReactEngine};
+use crate::events::log::EventLog;
+use crate::events::{Event, EventBus};
+use crate::memory::facts::FactStore;
+use crate::memory::in_memory::InMemoryMemory;
+use crate::memory::sqlite::SqliteMemory;
+use crate::memory::{Memory, embedding};
+use crate::metrics::MetricsStore;
+use crate::prompts::load_react_template;
+use crate::prompts::persona::find_persona;
+use crate::telemetry::Tracer;
+use crate::telemetry::prometheus::{CountedHttp, Prometheus};
+use crate::thinker::anthropic::AnthropicThinker;
+use crate::thinker::http::ReqwestClient;
+use crate::thinker::human::HumanThinker;
+use crate::thinker::{DEFAULT_PARSE_RETRIES, Thinker};
+use crate::tools::delegate::DelegateTool;
+use crate::tools::facts::{ForgetTool, RememberTool};
+use crate::tools::shell::{ShellConfig, ShellTool};
+use crate::tools::{Approver, Tool, ToolPolicy, ToolRegistry};
+
+type Subscriber = Box<dyn FnMut(Event) + Send>;
+
+/// Builds a [`Golem`] from settings, with optional replacements for the
+/// parts it would otherwise make itself.
+pub struct GolemBuilder {
+    settings: Settings,
+    thinker: Option<Box<dyn Thinker>>,
+    memory: Option<Box<dyn Memory>>,
+    tools: Vec<Arc<dyn Tool>>,
+    approver: Option<Box<dyn Approver>>,
+    hooks: Vec<Arc<dyn Hook>>,
+    events: Option<Arc<EventBus>>,
+    subscribers: Vec<Subscriber>,
+    echo: bool,
+}
+
+/// An assembled golem: the engine, and the parts it shares with the
+/// engines [`Golem::engine_factory`] makes and with the caller.
+pub struct Golem {
+    pub engine: ReactEngine,
+    pub thinker: Arc<RwLock<Box<dyn Thinker>>>,
+    pub tools: Arc<ToolRegistry>,
+    pub facts: Arc<FactStore>,
+    pub metrics: Arc<MetricsStore>,
+    /// Where tasks are traced, if `[telemetry]` names a collector.
+    pub tracer: Option<Arc<Tracer>>,
+    /// What `/metrics` shows, counted by every engine of this golem.
+    pub prometheus: Arc<Prometheus>,
+    pub events: Arc<EventBus>,
+    /// Run by every engine of this golem, the notifier included.
+    pub hooks: Vec<Arc<dyn Hook>>,
+    pub config: ReactConfig,
+    pub shell: ShellConfig,
+    /// The database in use; `:memory:` for `--db none`.
+    pub db_path: String,
+    /// `anthropic`, `human`, or `custom` for a thinker passed in.
+    pub provider: String,
+    pub model: String,
+    /// How the provider is authenticated, see [`crate::auth::status`].
+    pub auth_status: String,
+    /// Where session memory lives, for the banner.
+    pub memory_label: String,
+}
+
+impl GolemBuilder {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            thinker: None,
+            memory: None,
+            tools: Vec::new(),
+            approver: None,
+            hooks: Vec::new(),
+            events: None,
+            subscribers: Vec::new(),
+            echo: ReactConfig::default().echo,
+        }
+    }
+
+    /// Think with `thinker` instead of the configured provider.
+    pub fn thinker(mut self, thinker: Box<dyn Thinker>) -> Self {
+        self.thinker = Some(thinker);
+        self
+    }
+
+    /// Keep session memory in `memory` instead of the database.
+    pub fn memory(mut self, memory: Box<dyn Memory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Offer `tool` besides the built-in ones; it replaces a built-in of
+    /// the same name.
+    pub fn tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Decide on calls that need confirmation with `approver` instead of
+    /// asking at the terminal.
+    pub fn approver(mut self, approver: Box<dyn Approver>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Run `hook` around the engine's tool calls and tasks.
+    pub fn hook(mut self, hook: Arc<dyn Hook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Emit engine events on `events` instead of a bus of its own.
+    pub fn events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Call `subscriber` with every event, on a task of its own.
+    pub fn subscriber(mut self, subscriber: impl FnMut(Event) + Send + 'static) -> Self {
+        self.subscribers.push(Box::new(subscriber));
+        self
+    }
+
+    /// Print thoughts and tool output as tasks run; on by default.
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    pub async fn build(mut self) -> Result<Golem> {
+        let settings = &self.settings;
+        let prometheus = Arc::new(Prometheus::default());
+        // `--db none` keeps memory in process; credentials, config and
+        // facts still need SQLite, so they get a private in-memory database
+        let configured_db = resolve_db_path(settings)?;
+        let no_db = configured_db == NO_DATABASE;
+        let db_path = if no_db {
+            ":memory:".to_string()
+        } else {
+            configured_db
+        };
+        let app_config = Config::open(&db_path)?;
+        let events = self.events.take().unwrap_or_default();
+
+        let (thinker, provider, model, auth_status) = match self.thinker.take() {
+            Some(thinker) => {
+                let model = thinker.model().to_string();
+                (thinker, "custom".to_string(), model, "N/A".to_string())
+            }
+            None => configured_thinker(settings, &db_path, &app_config, &prometheus, &events)?,
+        };
+
+        let defaults = ShellConfig::default();
+        let shell = ShellConfig {
+            mode: settings.shell.mode.unwrap_or(defaults.mode),
+            working_dir: settings
+                .shell
+                .work_dir
+                .clone()
+                .unwrap_or(defaults.working_dir),
+            require_confirmation: settings
+                .shell
+                .confirm
+                .unwrap_or(defaults.require_confirmation),
+            max_output_bytes: settings
+                .shell
+                .max_output_bytes
+                .unwrap_or(defaults.max_output_bytes),
+        };
+
+        // Flags, env and config files win over values saved with
+        // /iterations and /timeout
+        let saved = |key| -> Option<u64> { app_config.get(key).ok().flatten()?.parse().ok() };
+        let defaults = ReactConfig::default();
+        let config = ReactConfig {
+            max_iterations: settings
+                .max_iterations
+                .or_else(|| saved("max_iterations").map(|n| n as usize))
+                .unwrap_or(defaults.max_iterations),
+            tool_timeout: settings
+                .timeout
+                .or_else(|| saved("timeout"))
+                .map_or(defaults.tool_timeout, Duration::from_secs),
+            max_tokens_per_task: settings.max_tokens_per_task,
+            max_cost_per_task: settings.max_cost_per_task,
+            thinker_retries: settings.retries.unwrap_or(defaults.thinker_retries),
+            observation_budget: settings
+                .observation_budget
+                .unwrap_or(defaults.observation_budget),
+            echo: self.echo,
+            persona: app_config
+                .get("persona")?
+                .and_then(|name| find_persona(&name)),
+            language: match &settings.language {
+                Some(language) => Some(language.clone()),
+                None => app_config.get("language")?,
+            },
+            ..defaults
+        };
+
+        // Shared with sub-agents started by the delegate tool
+        let thinker = Arc::new(RwLock::new(thinker));
+
+        let tools = Arc::new(match self.approver.take() {
+            Some(approver) => ToolRegistry::with_approver(approver),
+            None => ToolRegistry::new(),
+        });
+        tools
+            .set_policy(ToolPolicy {
+                allow_network: settings.tools.allow_network.unwrap_or(true),
+                allow_destructive: settings.tools.allow_destructive.unwrap_or(true),
+            })
+            .await;
+        tools
+            .register(Arc::new(ShellTool::new(shell.clone())))
+            .await;
+        let facts = Arc::new(FactStore::open(&db_path)?);
+        tools
+            .register(Arc::new(RememberTool::new(Arc::clone(&facts))))
+            .await;
+        tools
+            .register(Arc::new(ForgetTool::new(Arc::clone(&facts))))
+            .await;
+        tools
+            .register(Arc::new(DelegateTool::new(Arc::clone(&thinker), &tools)))
+            .await;
+        // After the built-ins, so the caller's tools replace them
+        for tool in self.tools.drain(..) {
+            tools.register(tool).await;
+        }
+        let metrics = Arc::new(MetricsStore::open(&db_path)?);
+
+        let project = resolve_project(settings);
+        let memory_label = if self.memory.is_some() {
+            "provided by the caller".to_string()
+        } else if no_db {
+            "in-process (no database)".to_string()
+        } else {
+            let label = if db_path == ":memory:" {
+                "ephemeral"
+            } else {
+                &db_path
+            };
+            match &project {
+                Some(project) => format!("{label} (project {project})"),
+                None => format!("{label} (global)"),
+            }
+        };
+        let memory: Box<dyn Memory> = match self.memory.take() {
+            Some(memory) => memory,
+            None if no_db => Box::new(
+                InMemoryMemory::new()
+                    .with_embedder(embedding::from_settings(&self.settings.embeddings)?),
+            ),
+            None => {
+                let memory = SqliteMemory::new(&db_path)?
+                    .with_embedder(embedding::from_settings(&self.settings.embeddings)?);
+                Box::new(match project {
+                    Some(project) => memory.with_project(project),
+                    None => memory,
+                })
+            }
+        };
+        // Pick up the last active named session
+        if let Some(name) = app_config.get("session")?
+            && let Err(e) = memory.switch_session(&name).await
+        {
+            eprintln!("warning: {e}; using the default session");
+        }
+
+        let mut engine = ReactEngine::with_shared_thinker(
+            Arc::clone(&thinker),
+            Arc::clone(&tools),
+            memory,
+            config.clone(),
+        );
+        engine.set_facts(Arc::clone(&facts));
+        engine.set_metrics(Arc::clone(&metrics));
+        let tracer = match &self.settings.telemetry.endpoint {
+            Some(_) => Some(Arc::new(Tracer::new(&self.settings.telemetry)?)),
+            None => None,
+        };
+        if let Some(tracer) = &tracer {
+            engine.set_tracer(Arc::clone(tracer));
+        }
+        engine.set_prometheus(Arc::clone(&prometheus));
+        let mut hooks = std::mem::take(&mut self.hooks);
+        if let Some(secs) = self.settings.notify_after {
+            hooks.push(Arc::new(Notifier::new(Duration::from_secs(secs))));
+        }
+        for hook in &hooks {
+            engine.add_hook(Arc::clone(hook));
+        }
+        engine.set_events(Arc::clone(&events));
+        if self.settings.event_log.unwrap_or(false) {
+            let log = EventLog::open(&db_path, &engine.active_session().await?)?;
+            self.subscribers.push(Box::new(move |event| {
+                if let Err(e) = log.record(&event) {
+                    eprintln!("  warning: failed to log event: {e:#}");
+                }
+            }));
+        }
+        for mut subscriber in self.subscribers.drain(..) {
+            let mut rx = events.subscribe();
+            tokio::spawn(async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(event) => subscriber(event),
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+
+        Ok(Golem {
+            engine,
+            thinker,
+            tools,
+            facts,
+            metrics,
+            tracer,
+            prometheus,
+            events,
+            hooks,
+            config,
+            shell,
+            db_path,
+            provider,
+            model,
+            auth_status,
+            memory_label,
+        })
+    }
+}
+
+impl Golem {
+    /// Makes fresh engines that share this golem's thinker, tools, facts,
+    /// metrics, hooks and events, each with its own in-process memory and
+    /// the config it's given — for tasks that run side by side (batch,
+    /// `/queue run`, serve). Pass the main engine's config to pick up
+    /// changes made since the build.
+    pub fn engine_factory(&self) -> impl Fn(&ReactConfig) -> ReactEngine + Send + Sync + 'static {
+        let (thinker, tools) = (Arc::clone(&self.thinker), Arc::clone(&self.tools));
+        let (facts, metrics) = (Arc::clone(&self.facts), Arc::clone(&self.metrics));
+        let tracer = self.tracer.clone();
+        let prometheus = Arc::clone(&self.prometheus);
+        let (hooks, events) = (self.hooks.clone(), Arc::clone(&self.events));
+        move |config| {
+            let mut engine = ReactEngine::with_shared_thinker(
+                Arc::clone(&thinker),
+                Arc::clone(&tools),
+                Box::new(InMemoryMemory::new()),
+                config.clone(),
+            );
+            engine.set_facts(Arc::clone(&facts));
+            engine.set_metrics(Arc::clone(&metrics));
+            if let Some(tracer) = &tracer {
+                engine.set_tracer(Arc::clone(tracer));
+            }
+            engine.set_prometheus(Arc::clone(&prometheus));
+            for hook in &hooks {
+                engine.add_hook(Arc::clone(hook));
+            }
+            engine.set_events(Arc::clone(&events));
+            engine
+        }
+    }
+}
+
+/// The thinker the settings ask for, with its provider, model and auth
+/// status. Its API responses are counted in `prometheus`, and refreshed
+/// tokens announced on `events`.
+fn configured_thinker(
+    settings: &Settings,
+    db_path: &str,
+    app_config: &Config,
+    prometheus: &Arc<Prometheus>,
+    events: &Arc<EventBus>,
+) -> Result<(Box<dyn Thinker>, String, String, String)> {
+    match settings.provider.as_deref().unwrap_or("anthropic") {
+        "human" => Ok((
+            Box::new(HumanThinker),
+            "human".to_string(),
+            "—".to_string(),
+            "N/A".to_string(),
+        )),
+        "anthropic" => {
+            let auth = AuthStorage::open(db_path)?.with_events(Arc::clone(events));
+            if let Some(account) = &settings.account {
+                auth.set_account("anthropic", account)?;
+            }
+            let auth_status = crate::auth::status(&auth, "anthropic")?;
+            // Model resolution: flag/env/config file > saved /model > default
+            let model = settings
+                .model
+                .clone()
+                .or_else(|| app_config.get("model").ok().flatten());
+            let http = CountedHttp::new(Arc::new(ReqwestClient), Arc::clone(prometheus));
+            let parse_retries = settings.parse_retries.unwrap_or(DEFAULT_PARSE_RETRIES);
+            let mut thinker = AnthropicThinker::new(model.clone(), auth)
+                .with_http(Arc::new(http))
+                .with_parse_retries(parse_retries)
+                .with_examples(settings.examples.clone());
+            if let Some(template) = load_react_template()? {
+                thinker = thinker.with_prompt_template(template);
+            }
+            let model = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+            Ok((
+                Box::new(thinker),
+                "anthropic".to_string(),
+                model,
+                auth_status,
+            ))
+        }
+        other => bail!("unknown provider \"{other}\" (expected anthropic or human)"),
+    }
+}
+
+/// The database path the settings name, else `~/.golem/golem.db`, with
+/// its directory created.
+pub fn resolve_db_path(settings: &Settings) -> Result<String> {
+    let db_path = settings
+        .db
+        .clone()
+        .unwrap_or_else(|| default_db_path().to_string_lossy().to_string());
+
+    if db_path != ":memory:"
+        && db_path != NO_DATABASE
+        && let Some(parent) = std::path::Path::new(&db_path).parent()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(db_path)
+}
+
+/// Session memory is scoped to the directory golem works in — `--work-dir`
+/// if given, else the current directory — unless `--global-memory` is set.
+pub fn resolve_project(settings: &Settings) -> Option<String> {
+    if settings.global_memory.unwrap_or(false) {
+        return None;
+    }
+    let dir = match &settings.shell.work_dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir().ok()?,
+    };
+    let dir = std::fs::canonicalize(&dir).unwrap_or(dir);
+    Some(dir.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::ToolSettings;
+    use crate::engine::Engine;
+    use crate::thinker::mock::MockThinker;
+    use crate::thinker::{Step, StepResult};
+    use std::sync::Mutex;
+
+    fn in_memory() -> Settings {
+        Settings {
+            db: Some(NO_DATABASE.to_string()),
+            ..Settings::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn builds_an_engine_that_runs_tasks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut golem = GolemBuilder::new(in_memory())
+            .thinker(Box::new(MockThinker::new(vec![StepResult {
+                step: Step::Finish {
+                    thought: "known".to_string(),
+                    answer: "42".to_string(),
+                },
+                usage: None,
+            }])))
+            .echo(false)
+            .subscriber({
+                let seen = Arc::clone(&seen);
+                move |event| seen.lock().unwrap().push(event)
+            })
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(golem.provider, "custom");
+        assert_eq!(golem.memory_label, "in-process (no database)");
+        let names: Vec<_> = golem
+            .tools
+            .descriptions()
+            .await
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        for tool in ["shell", "remember", "forget", "delegate"] {
+            assert!(names.iter().any(|n| n == tool), "{tool} in {names:?}");
+        }
+
+        let result = golem.engine.run("what is the answer?").await.unwrap();
+        assert_eq!(result.answer, "42");
+        // The subscriber runs on its own task
+        tokio::task::yield_now().await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!seen.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn settings_shape_the_engine() {
+        let settings = Settings {
+            provider: Some("human".to_string()),
+            max_iterations: Some(7),
+            tools: ToolSettings {
+                allow_network: Some(false),
+                ..ToolSettings::default()
+            },
+            ..in_memory()
+        };
+        let golem = GolemBuilder::new(settings).build().await.unwrap();
+        assert_eq!(golem.provider, "human");
+        let policy = golem.tools.policy().await;
+        assert!(!policy.allow_network && policy.allow_destructive);
+        assert_eq!(golem.config.max_iterations, 7);
+        let engine = golem.engine_factory()(&golem.config);
+        assert_eq!(engine.config().max_iterations, 7);
+
+        let unknown = Settings {
+            provider: Some("openai".to_string()),
+            ..in_memory()
+        };
+        let err = GolemBuilder::new(unknown).build().await.err().unwrap();
+        assert!(err.to_string().contains("unknown provider"), "{err}");
+    }
+    /// A plugged-in `delegate`, standing in for the built-in one.
+    struct OwnDelegate;
+
+    #[async_trait::async_trait]
+    impl Tool for OwnDelegate {
+        fn name(&self) -> &str {
+            "delegate"
+        }
+
+        fn description(&self) -> &str {
+            "mine"
+        }
+
+        async fn execute(
+            &self,
+            _args: &std::collections::HashMap<String, String>,
+        ) -> Result<String> {
+            Ok("mine".to_string())
+        }
+    }
+
+    /// Counts the tasks it sees start.
+    #[derive(Default)]
+    struct Starts(Mutex<usize>);
+
+    #[async_trait::async_trait]
+    impl Hook for Starts {
+        async fn on_task_start(&self, _task: &str) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn callers_tools_replace_built_ins() {
+        let golem = GolemBuilder::new(in_memory())
+            .tool(Arc::new(OwnDelegate))
+            .echo(false)
+            .build()
+            .await
+            .unwrap();
+        let details = golem.tools.details().await;
+        let delegate = details.iter().find(|t| t.name == "delegate").unwrap();
+        assert_eq!(delegate.summary, "mine");
+    }
+
+    #[tokio::test]
+    async fn factory_engines_share_hooks_and_take_the_given_config() {
+        let starts = Arc::new(Starts::default());
+        let golem = GolemBuilder::new(in_memory())
+            .thinker(Box::new(MockThinker::new(vec![StepResult {
+                step: Step::Finish {
+                    thought: "known".to_string(),
+                    answer: "42".to_string(),
+                },
+                usage: None,
+            }])))
+            .hook(Arc::clone(&starts) as Arc<dyn Hook>)
+            .echo(false)
+            .build()
+            .await
+            .unwrap();
+        let config = ReactConfig {
+            max_iterations: 3,
+            ..golem.config.clone()
+        };
+        let mut engine = golem.engine_factory()(&config);
+        assert_eq!(engine.config().max_iterations, 3);
+        engine.run("what is the answer?").await.unwrap();
+        assert_eq!(*starts.0.lock().unwrap(), 1);
+    }
+}
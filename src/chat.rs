@@ -0,0 +1,190 @@
+//! What the chat bots (`golem slack`, `golem discord`, `golem matrix`)
+//! share.
+//!
+//! Each chat channel gets an engine of its own on a named session
+//! ([`channel_engine`]), so later tasks there build on earlier ones. Shell
+//! commands that only read run without asking; others wait for someone to
+//! click a button (or react, in Matrix), through [`Approvals`].
+
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::banner::one_line;
+use crate::builder::GolemBuilder;
+use crate::config::settings::Settings;
+use crate::engine::hooks::Hook;
+use crate::engine::react::ReactEngine;
+use crate::thinker::ToolCall;
+use crate::tools::shell::{ShellMode, Verdict, classify};
+use crate::tools::{Approver, Outcome, ToolResult, format_args};
+
+/// Unanswered approval requests are denied after this long.
+pub const APPROVAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Tool calls are shown in progress messages up to this many characters.
+const PROGRESS_CHARS: usize = 200;
+
+/// An engine that asks `approver` and reports to `hook`, on the named
+/// session `session`, created if it doesn't exist yet.
+pub async fn channel_engine(
+    settings: &Settings,
+    session: &str,
+    approver: Box<dyn Approver>,
+    hook: Arc<dyn Hook>,
+) -> Result<ReactEngine> {
+    let golem = GolemBuilder::new(settings.clone())
+        .echo(false)
+        .approver(approver)
+        .hook(hook)
+        .build()
+        .await?;
+    let engine = golem.engine;
+    if !engine.sessions().await?.iter().any(|s| s.name == session) {
+        engine.create_session(session).await?;
+    }
+    engine.switch_session(session).await?;
+    Ok(engine)
+}
+
+/// Whether the settings let the shell write, which is when write commands
+/// need approving.
+pub fn shell_writes(settings: &Settings) -> bool {
+    settings.shell.mode == Some(ShellMode::ReadWrite)
+}
+
+/// Whether a call needs someone to click Approve: shell commands known
+/// to only read run without asking, and so do writes a read-only shell
+/// will refuse anyway; anything else asks, commands that don't parse
+/// included.
+pub fn needs_approval(tool: &str, args: &HashMap<String, String>, shell_writes: bool) -> bool {
+    match (tool, args.get("command")) {
+        ("shell", Some(command)) => match classify(command) {
+            Ok(Verdict::Read) => false,
+            Ok(Verdict::Write(_)) => shell_writes,
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// What an approval request asks, in markdown Slack and Discord read.
+pub fn approval_question(tool: &str, args: &HashMap<String, String>) -> String {
+    format!("Run `{tool}`?\n```{}```", format_args(args))
+}
+
+/// A line about a finished tool call, for progress messages.
+pub fn progress_line(call: &ToolCall, result: &ToolResult) -> String {
+    let args = one_line(&format_args(&call.args), PROGRESS_CHARS);
+    match &result.outcome {
+        Outcome::Success(_) => format!("✓ `{}` `{args}`", call.tool),
+        Outcome::Error(error) => format!(
+            "✗ `{}` `{args}`: {}",
+            call.tool,
+            one_line(error, PROGRESS_CHARS)
+        ),
+    }
+}
+
+/// `text` cut to `max` characters, ending in `…` if it was longer.
+pub fn cut(text: &str, max: usize) -> String {
+    if text.char_indices().nth(max).is_none() {
+        return text.to_string();
+    }
+    let end = text
+        .char_indices()
+        .nth(max.saturating_sub(1))
+        .map_or(0, |(i, _)| i);
+    format!("{}…", &text[..end])
+}
+
+/// Approval requests waiting for a click, by id.
+#[derive(Default)]
+pub struct Approvals {
+    pending: Mutex<HashMap<String, oneshot::Sender<bool>>>,
+}
+
+impl Approvals {
+    /// A new request's id, and where its answer arrives.
+    pub fn open(&self) -> (String, oneshot::Receiver<bool>) {
+        let id = format!("{:016x}", rand::random::<u64>());
+        let (tx, rx) = oneshot::channel();
+        self.lock().insert(id.clone(), tx);
+        (id, rx)
+    }
+
+    /// Answer request `id`; false if nobody is waiting on it any more.
+    pub fn settle(&self, id: &str, approved: bool) -> bool {
+        match self.lock().remove(id) {
+            Some(tx) => tx.send(approved).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Wait for request `id` to be answered, failing after
+    /// [`APPROVAL_TIMEOUT`].
+    pub async fn wait(
+        &self,
+        id: &str,
+        answer: oneshot::Receiver<bool>,
+        tool: &str,
+    ) -> Result<bool> {
+        match tokio::time::timeout(APPROVAL_TIMEOUT, answer).await {
+            Ok(answer) => Ok(answer.unwrap_or(false)),
+            Err(_) => {
+                self.settle(id, false);
+                bail!(
+                    "nobody approved {tool} within {} minutes",
+                    APPROVAL_TIMEOUT.as_secs() / 60
+                )
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<bool>>> {
+        self.pending.lock().expect("approvals lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &str) -> HashMap<String, String> {
+        HashMap::from([("command".to_string(), command.to_string())])
+    }
+
+    #[test]
+    fn only_writes_need_approval() {
+        assert!(!needs_approval("shell", &args("ls -la"), true));
+        assert!(needs_approval("shell", &args("rm -rf build"), true));
+        // A read-only shell refuses the write without asking
+        assert!(!needs_approval("shell", &args("rm -rf build"), false));
+        assert!(needs_approval("deploy", &HashMap::new(), false));
+    }
+
+    #[test]
+    fn commands_not_known_to_read_need_approval() {
+        assert!(needs_approval("shell", &args("echo 'unterminated"), true));
+        assert!(needs_approval("shell", &args("make install"), true));
+        assert!(needs_approval("shell", &HashMap::new(), true));
+    }
+
+    #[tokio::test]
+    async fn approvals_settle_once() {
+        let approvals = Approvals::default();
+        let (id, answer) = approvals.open();
+        assert!(approvals.settle(&id, true));
+        assert!(approvals.wait(&id, answer, "shell").await.unwrap());
+        assert!(!approvals.settle(&id, false));
+    }
+
+    #[test]
+    fn cuts_long_text() {
+        assert_eq!(cut("hello", 5), "hello");
+        assert_eq!(cut("hello!", 5), "hell…");
+        assert_eq!(cut("héllo wörld", 3), "hé…");
+    }
+}
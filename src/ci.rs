@@ -0,0 +1,208 @@
+//! `--ci`: one task in a GitHub Actions job, with nobody at the terminal.
+//!
+//! Nothing prompts: calls that would ask for confirmation are refused by
+//! [`CiApprover`]. Failed tool calls become workflow annotations through
+//! the [`Annotations`] hook, the answer lands in the job summary and the
+//! step's outputs via [`write_step_summary`] and [`write_outputs`], and
+//! [`exit_code`] says how the task went.
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::engine::hooks::Hook;
+use crate::engine::{TaskResult, TaskStatus};
+use crate::thinker::ToolCall;
+use crate::tools::{Approver, Outcome, ToolResult};
+
+/// The variable a `--ci` run takes its task from when `-r` isn't given;
+/// `@path` reads it from a file, as with `-r`.
+pub const TASK_ENV: &str = "GOLEM_TASK";
+
+/// Refuses every call that needs confirmation, saying how to allow it.
+pub struct CiApprover;
+
+#[async_trait]
+impl Approver for CiApprover {
+    async fn approve(&self, tool: &str, _args: &HashMap<String, String>) -> Result<bool> {
+        bail!(
+            "{tool} needs confirmation, and nobody can confirm in --ci; set `confirm = false` to allow it"
+        )
+    }
+}
+
+/// Prints an `::error` workflow command for every tool call that failed,
+/// so it shows on the job's page.
+pub struct Annotations;
+
+#[async_trait]
+impl Hook for Annotations {
+    async fn on_tool_result(&self, _call: &ToolCall, result: &ToolResult) {
+        if let Outcome::Error(error) = &result.outcome {
+            println!("{}", annotation("error", &result.tool, error));
+        }
+    }
+}
+
+/// A workflow command like `::error title=shell::exit status 1`.
+fn annotation(level: &str, title: &str, message: &str) -> String {
+    let title = escape(title).replace(':', "%3A").replace(',', "%2C");
+    format!("::{level} title=golem {title}::{}", escape(message))
+}
+
+/// Escape what workflow commands treat specially in a message.
+fn escape(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Append a section on `task` and how it went to the job summary at
+/// `path` (`$GITHUB_STEP_SUMMARY`).
+pub fn write_step_summary(path: &Path, task: &str, result: &Result<TaskResult>) -> Result<()> {
+    let mut section = format!("### golem: {}\n\n", first_line(task));
+    match result {
+        Ok(result) => {
+            if result.status == TaskStatus::Partial {
+                section.push_str("> **Partial answer:** the iteration limit ran out first.\n\n");
+            }
+            section.push_str(result.answer.trim_end());
+            section.push_str(&format!(
+                "\n\n<sub>{} iterations · {} tool calls · {} tokens · {:.1}s</sub>\n",
+                result.iterations,
+                result.tool_calls,
+                result.usage.total(),
+                result.duration.as_secs_f64()
+            ));
+        }
+        Err(e) => section.push_str(&format!("**Failed:** {e:#}\n")),
+    }
+    append(path, &section)
+}
+
+/// Append the `answer` and `status` step outputs to `path`
+/// (`$GITHUB_OUTPUT`), for later steps to use.
+pub fn write_outputs(path: &Path, result: &Result<TaskResult>) -> Result<()> {
+    let (status, answer) = match result {
+        Ok(result) if result.status == TaskStatus::Partial => ("partial", result.answer.as_str()),
+        Ok(result) => ("completed", result.answer.as_str()),
+        Err(_) => ("failed", ""),
+    };
+    // A delimiter the answer can't contain ends the multiline value
+    let mut delimiter = "GOLEM_ANSWER".to_string();
+    while answer.contains(&delimiter) {
+        delimiter.push('_');
+    }
+    append(
+        path,
+        &format!("status={status}\nanswer<<{delimiter}\n{answer}\n{delimiter}\n"),
+    )
+}
+
+/// 0 for an answer, 1 for a failed task, 2 when the iteration limit cut
+/// the answer short.
+pub fn exit_code(result: &Result<TaskResult>) -> i32 {
+    match result {
+        Ok(result) if result.status == TaskStatus::Completed => 0,
+        Ok(_) => 2,
+        Err(_) => 1,
+    }
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("")
+}
+
+fn append(path: &Path, text: &str) -> Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thinker::TokenUsage;
+    use std::time::Duration;
+
+    fn answered(answer: &str, status: TaskStatus) -> Result<TaskResult> {
+        Ok(TaskResult {
+            answer: answer.to_string(),
+            iterations: 3,
+            tool_calls: 2,
+            usage: TokenUsage::default(),
+            duration: Duration::from_millis(1500),
+            status,
+        })
+    }
+
+    #[test]
+    fn annotations_escape_their_text() {
+        assert_eq!(
+            annotation("error", "shell", "exit 1\n100% broken"),
+            "::error title=golem shell::exit 1%0A100%25 broken"
+        );
+        assert_eq!(
+            annotation("error", "a:b,c", "x"),
+            "::error title=golem a%3Ab%2Cc::x"
+        );
+    }
+
+    #[test]
+    fn outputs_hold_a_multiline_answer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output");
+        write_outputs(&path, &answered("two\nlines", TaskStatus::Completed)).unwrap();
+        write_outputs(&path, &Err(anyhow::anyhow!("boom"))).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "status=completed\nanswer<<GOLEM_ANSWER\ntwo\nlines\nGOLEM_ANSWER\n\
+             status=failed\nanswer<<GOLEM_ANSWER\n\nGOLEM_ANSWER\n"
+        );
+
+        let sneaky = dir.path().join("sneaky");
+        write_outputs(&sneaky, &answered("GOLEM_ANSWER", TaskStatus::Completed)).unwrap();
+        assert!(
+            std::fs::read_to_string(&sneaky)
+                .unwrap()
+                .contains("answer<<GOLEM_ANSWER_\n")
+        );
+    }
+
+    #[test]
+    fn summary_notes_partial_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summary.md");
+        write_step_summary(
+            &path,
+            "check\nthe logs",
+            &answered("42", TaskStatus::Partial),
+        )
+        .unwrap();
+        let summary = std::fs::read_to_string(&path).unwrap();
+        assert!(summary.starts_with("### golem: check\n"), "{summary}");
+        assert!(summary.contains("Partial answer"), "{summary}");
+        assert!(summary.contains("42\n\n<sub>3 iterations"), "{summary}");
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(exit_code(&answered("", TaskStatus::Completed)), 0);
+        assert_eq!(exit_code(&answered("", TaskStatus::Partial)), 2);
+        assert_eq!(exit_code(&Err(anyhow::anyhow!("boom"))), 1);
+    }
+
+    #[tokio::test]
+    async fn confirmation_is_refused() {
+        let err = CiApprover
+            .approve("shell", &HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("confirm = false"), "{err}");
+    }
+}
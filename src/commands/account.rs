@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+use crate::auth::{self, storage::AuthStorage};
+
+pub struct AccountCommand;
+
+const USAGE: &str = "usage: /account [list | switch <name>]";
+
+#[async_trait]
+impl Command for AccountCommand {
+    fn name(&self) -> &str {
+        "/account"
+    }
+
+    fn description(&self) -> &str {
+        "list the provider's stored accounts or switch to another"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let provider = info.provider;
+        let storage = match AuthStorage::open(info.db_path) {
+            Ok(storage) => storage,
+            Err(e) => {
+                eprintln!("  ✗ failed to open auth storage: {e}");
+                return CommandResult::Handled;
+            }
+        };
+        let (action, name) = match args.split_once(char::is_whitespace) {
+            Some((action, name)) => (action, name.trim()),
+            None => (args, ""),
+        };
+
+        match (action, name) {
+            ("" | "list", "") => {
+                let listed = storage
+                    .account(provider)
+                    .and_then(|active| Ok((active, storage.accounts(provider)?)));
+                match listed {
+                    Ok((_, accounts)) if accounts.is_empty() => {
+                        println!("  no {provider} accounts stored — /login adds one");
+                    }
+                    Ok((active, accounts)) => {
+                        for account in accounts {
+                            let marker = if account == active { "*" } else { " " };
+                            println!("  {marker} {account}");
+                        }
+                    }
+                    Err(e) => eprintln!("  ✗ failed to list accounts: {e}"),
+                }
+                CommandResult::Handled
+            }
+            ("switch", name) if !name.is_empty() && !name.contains(char::is_whitespace) => {
+                let switched = storage
+                    .set_account(provider, name)
+                    .and_then(|()| storage.accounts(provider))
+                    .and_then(|accounts| Ok((accounts, auth::status(&storage, provider)?)));
+                match switched {
+                    Ok((accounts, status)) => {
+                        if accounts.iter().any(|account| account == name) {
+                            println!("  ✓ switched to {provider} account {name}");
+                        } else {
+                            println!(
+                                "  ✓ switched to {provider} account {name} — it has no credentials yet, /login adds them"
+                            );
+                        }
+                        CommandResult::StateChanged(StateChange::Auth(status))
+                    }
+                    Err(e) => {
+                        eprintln!("  ✗ failed to switch account: {e}");
+                        CommandResult::Handled
+                    }
+                }
+            }
+            _ => {
+                println!("  {USAGE}");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::storage::Credential;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(AccountCommand.name(), "/account");
+        assert!(!AccountCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn switch_changes_the_active_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("golem.db");
+        let db_path = db_path.to_str().unwrap();
+        let storage = AuthStorage::open(db_path).unwrap();
+        storage.set_account("anthropic", "work").unwrap();
+        storage
+            .set(
+                "anthropic",
+                Credential::ApiKey {
+                    key: "sk-work".to_string(),
+                },
+            )
+            .unwrap();
+        storage.set_account("anthropic", "default").unwrap();
+        let info = SessionInfo {
+            db_path,
+            ..test_info()
+        };
+
+        match AccountCommand.execute("switch work", &info).await {
+            CommandResult::StateChanged(StateChange::Auth(status)) => {
+                assert_eq!(status, "API key ✓ (account work)");
+            }
+            _ => panic!("expected an auth change"),
+        }
+        assert_eq!(storage.account("anthropic").unwrap(), "work");
+
+        assert!(matches!(
+            AccountCommand.execute("switch", &info).await,
+            CommandResult::Handled
+        ));
+        assert_eq!(storage.account("anthropic").unwrap(), "work");
+    }
+}
@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+/// What `/config` can change, each saved for next time.
+const KEYS: &[&str] = &["language"];
+
+const USAGE: &str = "  usage: /config [set <key> <value> | unset <key>]";
+
+pub struct ConfigCommand;
+
+#[async_trait]
+impl Command for ConfigCommand {
+    fn name(&self) -> &str {
+        "/config"
+    }
+
+    fn description(&self) -> &str {
+        "show or change settings: /config set language he"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {
+                let Some(engine) = info.engine else {
+                    eprintln!("  ✗ settings not available");
+                    return CommandResult::Handled;
+                };
+                match &engine.config().language {
+                    Some(language) => println!("  language = {language}"),
+                    None => println!("  language   (not set: the model picks)"),
+                }
+                CommandResult::Handled
+            }
+            ["set", key, value @ ..] if !value.is_empty() => change(key, Some(value.join(" "))),
+            ["unset", key] => change(key, None),
+            _ => {
+                println!("{USAGE}");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+/// Set `key` to `value`, or back to its default for `None`.
+fn change(key: &str, value: Option<String>) -> CommandResult {
+    match key {
+        "language" => {
+            match &value {
+                Some(language) => println!("  ✓ thoughts and answers now in {language}"),
+                None => println!("  ✓ language unset: the model picks"),
+            }
+            CommandResult::StateChanged(StateChange::Language(value))
+        }
+        _ => {
+            eprintln!("  ✗ unknown setting: {key} (try {})", KEYS.join(", "));
+            CommandResult::Handled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(ConfigCommand.name(), "/config");
+        assert!(!ConfigCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sets_and_unsets_the_language() {
+        assert!(matches!(
+            ConfigCommand.execute("set language Brazilian Portuguese", &test_info()).await,
+            CommandResult::StateChanged(StateChange::Language(Some(ref l))) if l == "Brazilian Portuguese"
+        ));
+        assert!(matches!(
+            ConfigCommand.execute("unset language", &test_info()).await,
+            CommandResult::StateChanged(StateChange::Language(None))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_keys_and_missing_values() {
+        for args in ["set colour red", "set language", "unset", "language he"] {
+            assert!(
+                matches!(
+                    ConfigCommand.execute(args, &test_info()).await,
+                    CommandResult::Handled
+                ),
+                "{args}"
+            );
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::memory::export::{ExportFormat, SessionExport};
+
+pub struct ExportCommand;
+
+const USAGE: &str = "usage: /export [md|json] <path>";
+
+#[async_trait]
+impl Command for ExportCommand {
+    fn name(&self) -> &str {
+        "/export"
+    }
+
+    fn description(&self) -> &str {
+        "write the session to a Markdown or JSON file"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let Some((format, path)) = parse_args(args) else {
+            println!("  {USAGE}");
+            return CommandResult::Handled;
+        };
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ export not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        let result = async {
+            let session = engine.active_session().await?;
+            let export = SessionExport::new(&session, engine.session_records().await?);
+            std::fs::write(path, export.render(format)?)?;
+            anyhow::Ok(export.tasks.len())
+        }
+        .await;
+
+        match result {
+            Ok(count) => println!("  ✓ exported {count} task(s) to {path}"),
+            Err(e) => eprintln!("  ✗ export failed: {e}"),
+        }
+        CommandResult::Handled
+    }
+}
+
+/// `[md|json] <path>` — the format defaults to the path's extension.
+fn parse_args(args: &str) -> Option<(ExportFormat, &str)> {
+    let mut parts = args.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(path), None, None) => Some((ExportFormat::from_path(path), path)),
+        (Some(format), Some(path), None) => Some((ExportFormat::parse(format)?, path)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(ExportCommand.name(), "/export");
+        assert!(!ExportCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_format_and_path() {
+        assert_eq!(
+            parse_args("json out.txt"),
+            Some((ExportFormat::Json, "out.txt"))
+        );
+        assert_eq!(
+            parse_args("session.json"),
+            Some((ExportFormat::Json, "session.json"))
+        );
+        assert_eq!(
+            parse_args("notes.md"),
+            Some((ExportFormat::Markdown, "notes.md"))
+        );
+        assert_eq!(parse_args("pdf out.pdf"), None);
+        assert_eq!(parse_args(""), None);
+        assert_eq!(parse_args("md a b"), None);
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            ExportCommand.execute("out.md", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
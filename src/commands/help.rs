@@ -21,7 +21,7 @@ impl Command for HelpCommand {
     /// Help output is generated by the registry, not here.
     /// The registry intercepts `/help` in `dispatch()` so it can
     /// list all registered commands including plugins.
-    async fn execute(&self, _info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, _args: &str, _info: &SessionInfo<'_>) -> CommandResult {
         CommandResult::Handled
     }
 }
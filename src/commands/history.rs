@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::banner::one_line;
+use crate::consts::{DEFAULT_HISTORY_LIMIT, format_number};
+use crate::memory::{MemoryEntry, SessionRecord};
+use crate::tools::{Outcome, format_args};
+
+pub struct HistoryCommand;
+
+const USAGE: &str = "usage: /history [N] | /history show <n>";
+
+/// Tasks and answers in the list view are cut to this many characters.
+const PREVIEW_CHARS: usize = 60;
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    /// List the last N entries.
+    List(usize),
+    /// Print entry n (1-based) in full.
+    Show(usize),
+}
+
+#[async_trait]
+impl Command for HistoryCommand {
+    fn name(&self) -> &str {
+        "/history"
+    }
+
+    fn description(&self) -> &str {
+        "browse past tasks: /history [N] | /history show <n>"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let Some(action) = parse_args(args) else {
+            println!("  {USAGE}");
+            return CommandResult::Handled;
+        };
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ history not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        let records = match engine.session_records().await {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("  ✗ failed to load history: {e}");
+                return CommandResult::Handled;
+            }
+        };
+
+        if records.is_empty() {
+            println!("  no tasks in this session yet");
+            return CommandResult::Handled;
+        }
+
+        match action {
+            Action::List(limit) => {
+                let start = records.len().saturating_sub(limit);
+                for (i, record) in records.iter().enumerate().skip(start) {
+                    println!(
+                        "  {:>3}. [{}] {} → {}",
+                        i + 1,
+                        record.timestamp,
+                        one_line(&record.task, PREVIEW_CHARS),
+                        one_line(&record.answer, PREVIEW_CHARS)
+                    );
+                }
+            }
+            Action::Show(n) => match records.get(n - 1) {
+                Some(record) => print_record(n, record),
+                None => eprintln!("  ✗ no entry {n} (1–{})", records.len()),
+            },
+        }
+        CommandResult::Handled
+    }
+}
+
+/// `""` → last few, `N` → last N, `show <n>` → one entry.
+fn parse_args(args: &str) -> Option<Action> {
+    let mut parts = args.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (None, _, _) => Some(Action::List(DEFAULT_HISTORY_LIMIT)),
+        (Some("show"), Some(n), None) => n.parse().ok().filter(|n| *n > 0).map(Action::Show),
+        (Some(n), None, None) => n.parse().ok().filter(|n| *n > 0).map(Action::List),
+        _ => None,
+    }
+}
+
+fn print_record(n: usize, record: &SessionRecord) {
+    println!("  #{n} [{}]", record.timestamp);
+    println!("  Task: {}", record.task);
+    for entry in &record.entries {
+        if let MemoryEntry::Iteration {
+            thought,
+            calls,
+            results,
+        } = entry
+        {
+            println!("  Thought: {thought}");
+            for (i, result) in results.iter().enumerate() {
+                if let Some(call) = calls.get(i) {
+                    println!("    [{}] $ {}", call.tool, format_args(&call.args));
+                }
+                match &result.outcome {
+                    Outcome::Success(out) => println!("    [{}] ✓ {}", result.tool, out),
+                    Outcome::Error(err) => println!("    [{}] ✗ {}", result.tool, err),
+                }
+            }
+        }
+    }
+    println!("  Answer: {}", record.answer);
+    if record.usage.total() > 0 {
+        println!(
+            "  Tokens: {} in / {} out",
+            format_number(record.usage.input_tokens),
+            format_number(record.usage.output_tokens)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(HistoryCommand.name(), "/history");
+        assert!(HistoryCommand.aliases().is_empty());
+        assert!(!HistoryCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_args() {
+        assert_eq!(parse_args(""), Some(Action::List(DEFAULT_HISTORY_LIMIT)));
+        assert_eq!(parse_args("5"), Some(Action::List(5)));
+        assert_eq!(parse_args("show 3"), Some(Action::Show(3)));
+        assert_eq!(parse_args("0"), None);
+        assert_eq!(parse_args("show 0"), None);
+        assert_eq!(parse_args("show"), None);
+        assert_eq!(parse_args("abc"), None);
+        assert_eq!(parse_args("5 6"), None);
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            HistoryCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct IterationsCommand;
+
+#[async_trait]
+impl Command for IterationsCommand {
+    fn name(&self) -> &str {
+        "/iterations"
+    }
+
+    fn description(&self) -> &str {
+        "show or set the iterations a task gets: /iterations [N]"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if args.is_empty() {
+            match info.engine {
+                Some(engine) => println!("  max iterations: {}", engine.config().max_iterations),
+                None => eprintln!("  ✗ iterations not available"),
+            }
+            return CommandResult::Handled;
+        }
+        match args.parse::<usize>() {
+            Ok(max) if max > 0 => {
+                println!("  ✓ tasks now get up to {max} iterations");
+                CommandResult::StateChanged(StateChange::MaxIterations(max))
+            }
+            _ => {
+                println!("  usage: /iterations <N>, N at least 1");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(IterationsCommand.name(), "/iterations");
+        assert!(!IterationsCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sets_a_positive_count() {
+        assert!(matches!(
+            IterationsCommand.execute("50", &test_info()).await,
+            CommandResult::StateChanged(StateChange::MaxIterations(50))
+        ));
+        for args in ["", "0", "many"] {
+            assert!(matches!(
+                IterationsCommand.execute(args, &test_info()).await,
+                CommandResult::Handled
+            ));
+        }
+    }
+}
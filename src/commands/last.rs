@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::banner::{one_line, page_answer_text};
+
+pub struct LastCommand;
+
+/// The task above the answer is cut to this many characters.
+const TASK_CHARS: usize = 60;
+
+#[async_trait]
+impl Command for LastCommand {
+    fn name(&self) -> &str {
+        "/last"
+    }
+
+    fn description(&self) -> &str {
+        "re-print the last answer in full, or the n-th last: /last [n]"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let Some(back) = parse_args(args) else {
+            println!("  usage: /last [n], n = 1 for the last answer");
+            return CommandResult::Handled;
+        };
+        let Some(engine) = info.engine else {
+            eprintln!("  ✗ answers not available");
+            return CommandResult::Handled;
+        };
+        let records = match engine.session_records().await {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("  ✗ failed to load history: {e}");
+                return CommandResult::Handled;
+            }
+        };
+        if records.is_empty() {
+            println!("  no answers in this session yet");
+            return CommandResult::Handled;
+        }
+        let Some(index) = records.len().checked_sub(back) else {
+            eprintln!(
+                "  ✗ no answer {back} back (1–{} in this session)",
+                records.len()
+            );
+            return CommandResult::Handled;
+        };
+        let record = &records[index];
+        println!(
+            "  #{} [{}] {}",
+            index + 1,
+            record.timestamp,
+            one_line(&record.task, TASK_CHARS)
+        );
+        page_answer_text(&record.answer).await;
+        CommandResult::Handled
+    }
+}
+
+/// How many answers back to go: `""` → 1, `n` → n, at least 1.
+fn parse_args(args: &str) -> Option<usize> {
+    match args {
+        "" => Some(1),
+        n => n.parse().ok().filter(|n| *n > 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(LastCommand.name(), "/last");
+        assert!(!LastCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_args() {
+        assert_eq!(parse_args(""), Some(1));
+        assert_eq!(parse_args("3"), Some(3));
+        assert_eq!(parse_args("0"), None);
+        assert_eq!(parse_args("last"), None);
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            LastCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
@@ -1,8 +1,7 @@
 use async_trait::async_trait;
 
 use super::{Command, CommandResult, SessionInfo, StateChange};
-use crate::auth;
-use crate::auth::oauth;
+use crate::auth::{self, storage::AuthStorage};
 
 pub struct LoginCommand;
 
@@ -13,44 +12,38 @@ impl Command for LoginCommand {
     }
 
     fn description(&self) -> &str {
-        "log in to the current provider"
+        "log in to the current provider (--paste: paste the code instead, --device: enter a code elsewhere)"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
         let provider = info.provider;
+        let (paste, device) = match args {
+            "" => (false, false),
+            "--paste" => (true, false),
+            "--device" => (false, true),
+            other => {
+                eprintln!("  ✗ unknown option: {other} (try /login --paste or /login --device)");
+                return CommandResult::Handled;
+            }
+        };
         println!("Logging in to {provider}...\n");
 
-        let (url, verifier) = oauth::build_authorize_url();
-        let _ = open::that(&url);
-
-        println!("Open this URL to authenticate:\n");
-        println!("  {url}\n");
-
-        print!("Paste the authorization code: ");
-        if std::io::Write::flush(&mut std::io::stdout()).is_err() {
-            return CommandResult::Handled;
-        }
-
-        let mut code = String::new();
-        if std::io::stdin().read_line(&mut code).is_err() {
-            eprintln!("  ✗ failed to read input");
-            return CommandResult::Handled;
-        }
-        let code = code.trim();
-
-        if code.is_empty() {
-            eprintln!("  ✗ no authorization code provided");
-            return CommandResult::Handled;
-        }
+        let login = if device {
+            auth::device_login(info.db_path, provider).await
+        } else {
+            auth::browser_login(info.db_path, provider, paste).await
+        };
 
-        println!("\nExchanging code for tokens...");
-        match auth::login(info.db_path, provider, code, &verifier).await {
+        match login {
             Ok(()) => {
                 println!("  ✓ logged in to {provider}");
-                CommandResult::StateChanged(StateChange::Auth("OAuth ✓".to_string()))
+                let status = AuthStorage::open(info.db_path)
+                    .and_then(|storage| auth::status(&storage, provider))
+                    .unwrap_or_else(|_| "OAuth ✓".to_string());
+                CommandResult::StateChanged(StateChange::Auth(status))
             }
             Err(e) => {
-                eprintln!("  ✗ login failed: {e}");
+                eprintln!("  ✗ login failed: {e:#}");
                 CommandResult::Handled
             }
         }
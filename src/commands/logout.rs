@@ -15,7 +15,7 @@ impl Command for LogoutCommand {
         "log out from the current provider"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, _args: &str, info: &SessionInfo<'_>) -> CommandResult {
         let provider = info.provider;
         if let Err(e) = auth::logout(info.db_path, provider) {
             eprintln!("  ✗ logout from {provider} failed: {e}");
@@ -35,7 +35,7 @@ mod tests {
     #[tokio::test]
     async fn returns_auth_changed_when_no_credentials() {
         assert!(matches!(
-            LogoutCommand.execute(&test_info()).await,
+            LogoutCommand.execute("", &test_info()).await,
             CommandResult::StateChanged(StateChange::Auth(_))
         ));
     }
@@ -53,8 +53,7 @@ mod tests {
             .unwrap();
         assert!(storage.get("anthropic").unwrap().is_some());
 
-        let info = test_info();
-        let result = LogoutCommand.execute(&info).await;
+        let result = LogoutCommand.execute("", &test_info()).await;
 
         assert!(matches!(
             result,
@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::banner::one_line;
+
+/// Where the text typed after a macro goes; without it, the text is added
+/// at the end.
+const ARGS_PLACEHOLDER: &str = "{args}";
+
+/// The prompt is cut to this many characters in `/help`.
+const DESCRIPTION_CHARS: usize = 50;
+
+/// A prompt saved under a name in the `[macros]` config table: `/deploy`
+/// runs it as a task, as if it had been typed.
+pub struct MacroCommand {
+    name: String,
+    prompt: String,
+    description: String,
+}
+
+impl MacroCommand {
+    /// `name` with or without its leading `/`.
+    pub fn new(name: &str, prompt: &str) -> Self {
+        Self {
+            name: format!("/{}", name.trim_start_matches('/')),
+            prompt: prompt.to_string(),
+            description: format!("macro: {}", one_line(prompt, DESCRIPTION_CHARS)),
+        }
+    }
+
+    /// The task for `/name args`.
+    fn expand(&self, args: &str) -> String {
+        if self.prompt.contains(ARGS_PLACEHOLDER) {
+            self.prompt
+                .replace(ARGS_PLACEHOLDER, args)
+                .trim()
+                .to_string()
+        } else if args.is_empty() {
+            self.prompt.clone()
+        } else {
+            format!("{} {args}", self.prompt)
+        }
+    }
+}
+
+#[async_trait]
+impl Command for MacroCommand {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    async fn execute(&self, args: &str, _info: &SessionInfo<'_>) -> CommandResult {
+        let task = self.expand(args);
+        println!("  {task}");
+        CommandResult::RunTask(task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        let deploy = MacroCommand::new("deploy", "run the deploy script");
+        assert_eq!(deploy.name(), "/deploy");
+        assert_eq!(deploy.description(), "macro: run the deploy script");
+        assert_eq!(MacroCommand::new("/deploy", "x").name(), "/deploy");
+    }
+
+    #[test]
+    fn args_fill_the_placeholder_or_follow_the_prompt() {
+        let logs = MacroCommand::new("logs", "show the last errors in {args} and explain them");
+        assert_eq!(
+            logs.expand("nginx"),
+            "show the last errors in nginx and explain them"
+        );
+        let deploy = MacroCommand::new("deploy", "deploy and check the health endpoint");
+        assert_eq!(deploy.expand(""), "deploy and check the health endpoint");
+        assert_eq!(
+            deploy.expand("to staging"),
+            "deploy and check the health endpoint to staging"
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_the_prompt_as_a_task() {
+        assert!(matches!(
+            MacroCommand::new("deploy", "deploy to {args}").execute("staging", &test_info()).await,
+            CommandResult::RunTask(task) if task == "deploy to staging"
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::consts::{format_bytes, format_number};
+use crate::memory::MemoryStats;
+
+pub struct MemoryCommand;
+
+const USAGE: &str = "usage: /memory stats";
+
+#[async_trait]
+impl Command for MemoryCommand {
+    fn name(&self) -> &str {
+        "/memory"
+    }
+
+    fn description(&self) -> &str {
+        "show what memory holds: /memory stats"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if !matches!(args, "" | "stats") {
+            println!("  {USAGE}");
+            return CommandResult::Handled;
+        }
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ memory stats not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        match engine.memory_stats().await {
+            Ok(stats) => print!("{}", render(&stats)),
+            Err(e) => eprintln!("  ✗ failed to read memory stats: {e}"),
+        }
+        CommandResult::Handled
+    }
+}
+
+fn render(stats: &MemoryStats) -> String {
+    let mut out = String::new();
+    if let Some(bytes) = stats.database_bytes {
+        out.push_str(&format!("  Database: {}\n", format_bytes(bytes)));
+    }
+
+    let width = stats.tables.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    out.push_str("  Tables:\n");
+    for table in &stats.tables {
+        out.push_str(&format!(
+            "    {:<width$}  {:>8} rows",
+            table.name,
+            format_number(table.rows)
+        ));
+        if let (Some(oldest), Some(newest)) = (&table.oldest, &table.newest) {
+            out.push_str(&format!("  {oldest} → {newest}"));
+        }
+        out.push('\n');
+    }
+
+    if let Some(bytes) = stats.fts_bytes {
+        out.push_str(&format!("  Full-text index: {}\n", format_bytes(bytes)));
+    }
+    out.push_str(&format!(
+        "  Vector index: {} ({} vectors)\n",
+        format_bytes(stats.vector_bytes),
+        format_number(stats.vectors)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+    use crate::memory::TableStats;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(MemoryCommand.name(), "/memory");
+        assert!(MemoryCommand.aliases().is_empty());
+        assert!(!MemoryCommand.description().is_empty());
+    }
+
+    #[test]
+    fn renders_tables_and_sizes() {
+        let stats = MemoryStats {
+            tables: vec![
+                TableStats {
+                    name: "session_history".to_string(),
+                    rows: 1200,
+                    oldest: Some("2026-01-01 00:00:00".to_string()),
+                    newest: Some("2026-02-01 00:00:00".to_string()),
+                },
+                TableStats {
+                    name: "embeddings".to_string(),
+                    rows: 3,
+                    oldest: None,
+                    newest: None,
+                },
+            ],
+            database_bytes: Some(2048),
+            fts_bytes: Some(100),
+            vectors: 3,
+            vector_bytes: 4608,
+        };
+        let out = render(&stats);
+        assert!(out.contains("Database: 2.0 KiB"));
+        assert!(out.contains("session_history     1,200 rows  2026-01-01 00:00:00 → 2026-02-01"));
+        assert!(out.contains("embeddings         "));
+        assert!(out.contains("Full-text index: 100 B"));
+        assert!(out.contains("Vector index: 4.5 KiB (3 vectors)"));
+    }
+
+    #[test]
+    fn omits_database_size_without_sqlite() {
+        let stats = MemoryStats {
+            tables: vec![],
+            database_bytes: None,
+            fts_bytes: None,
+            vectors: 0,
+            vector_bytes: 0,
+        };
+        let out = render(&stats);
+        assert!(!out.contains("Database"));
+        assert!(!out.contains("Full-text"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_subcommand() {
+        assert!(matches!(
+            MemoryCommand.execute("prune", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            MemoryCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
@@ -5,21 +5,43 @@
 //! and dynamic help generation. Plugins can register additional commands
 //! at runtime via `registry.register(Arc::new(MyCommand))`.
 
+mod account;
+mod config;
+mod export;
 mod help;
+mod history;
+mod iterations;
+mod last;
 mod login;
 mod logout;
+pub mod macros;
+mod memory;
+mod mode;
 mod model;
 mod new;
+mod persona;
+mod queue;
 mod quit;
+mod recall;
+mod search;
+mod session;
+mod step;
+mod timeout;
 mod tokens;
 mod tools;
 mod whoami;
+mod workdir;
 
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::batch::TaskQueue;
 use crate::engine::react::ReactEngine;
+use crate::prompts::persona::Persona;
 use crate::thinker::TokenUsage;
+use crate::tools::shell::ShellMode;
 
 /// Session info available to commands during execution.
 pub struct SessionInfo<'a> {
@@ -27,11 +49,17 @@ pub struct SessionInfo<'a> {
     pub model: &'a str,
     pub auth_status: &'a str,
     pub shell_mode: &'a str,
+    /// Where shell commands run.
+    pub work_dir: &'a Path,
+    /// Whether step mode is on.
+    pub step_mode: bool,
     pub tools: &'a [String],
     pub usage: TokenUsage,
     pub db_path: &'a str,
     /// Engine reference for commands that need provider access (e.g. `/model`).
     pub engine: Option<&'a ReactEngine>,
+    /// Tasks waiting for `/queue run`.
+    pub queue: Option<&'a TaskQueue>,
 }
 
 /// A state change the REPL needs to apply after a command runs.
@@ -41,6 +69,25 @@ pub enum StateChange {
     Auth(String),
     /// Active model changed (new model ID).
     Model(String),
+    /// Active named session changed (new session name).
+    Session(String),
+    /// Step mode switched on or off.
+    StepMode(bool),
+    /// Shell commands should run in this (absolute) directory.
+    WorkDir(PathBuf),
+    /// The shell tool switched between read-only and read-write.
+    ShellMode(ShellMode),
+    /// Tasks get this many iterations.
+    MaxIterations(usize),
+    /// Tool calls are cut off after this long.
+    ToolTimeout(Duration),
+    /// Tasks work as this persona, or as none.
+    Persona(Option<&'static Persona>),
+    /// Thoughts and answers are written in this language, or in whatever
+    /// language the model picks.
+    Language(Option<String>),
+    /// Run the queued tasks.
+    RunQueue,
 }
 
 /// What the REPL should do after a command runs.
@@ -52,6 +99,8 @@ pub enum CommandResult {
     Handled,
     /// Command produced a state change the REPL must apply.
     StateChanged(StateChange),
+    /// Run this text as a task, as if it had been typed (macros).
+    RunTask(String),
     /// Exit the REPL.
     Quit,
 }
@@ -70,8 +119,9 @@ pub trait Command: Send + Sync {
     /// One-line description for `/help`.
     fn description(&self) -> &str;
 
-    /// Run the command.
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult;
+    /// Run the command. `args` is the text after the command name,
+    /// trimmed, e.g. `nginx` for `/recall nginx`.
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult;
 }
 
 /// Holds registered commands. Supports runtime registration for plugins.
@@ -85,10 +135,26 @@ impl CommandRegistry {
         let commands: Vec<Arc<dyn Command>> = vec![
             Arc::new(help::HelpCommand),
             Arc::new(whoami::WhoamiCommand),
+            Arc::new(workdir::WorkdirCommand),
+            Arc::new(mode::ModeCommand),
+            Arc::new(timeout::TimeoutCommand),
+            Arc::new(iterations::IterationsCommand),
             Arc::new(tools::ToolsCommand),
             Arc::new(tokens::TokensCommand),
             Arc::new(model::ModelCommand),
+            Arc::new(persona::PersonaCommand),
+            Arc::new(config::ConfigCommand),
             Arc::new(new::NewCommand),
+            Arc::new(history::HistoryCommand),
+            Arc::new(last::LastCommand),
+            Arc::new(recall::RecallCommand),
+            Arc::new(search::SearchCommand),
+            Arc::new(memory::MemoryCommand),
+            Arc::new(session::SessionCommand),
+            Arc::new(step::StepCommand),
+            Arc::new(export::ExportCommand),
+            Arc::new(queue::QueueCommand),
+            Arc::new(account::AccountCommand),
             Arc::new(login::LoginCommand),
             Arc::new(logout::LogoutCommand),
             Arc::new(quit::QuitCommand),
@@ -103,7 +169,12 @@ impl CommandRegistry {
 
     /// Dispatch input to a matching command, or return `NotACommand`.
     pub async fn dispatch(&self, input: &str, info: &SessionInfo<'_>) -> CommandResult {
-        let cmd = input.trim();
+        let input = input.trim();
+        // Only slash commands take arguments; `quit` etc. must match exactly
+        let (cmd, args) = match input.split_once(char::is_whitespace) {
+            Some((cmd, args)) if input.starts_with('/') => (cmd, args.trim()),
+            _ => (input, ""),
+        };
 
         for command in &self.commands {
             if cmd == command.name() || command.aliases().contains(&cmd) {
@@ -112,7 +183,7 @@ impl CommandRegistry {
                     print!("{}", self.help_text());
                     return CommandResult::Handled;
                 }
-                return command.execute(info).await;
+                return command.execute(args, info).await;
             }
         }
 
@@ -125,13 +196,18 @@ impl CommandRegistry {
         CommandResult::NotACommand
     }
 
-    /// Generate help text from all registered commands.
-    pub fn help_text(&self) -> String {
-        let entries: Vec<(String, &str)> = self
-            .commands
+    /// Each command's name with its aliases, and its description, in
+    /// registration order.
+    pub fn help_entries(&self) -> Vec<(String, &str)> {
+        self.commands
             .iter()
             .map(|c| (format_label(c.name(), c.aliases()), c.description()))
-            .collect();
+            .collect()
+    }
+
+    /// Generate help text from all registered commands.
+    pub fn help_text(&self) -> String {
+        let entries = self.help_entries();
 
         let max_width = entries
             .iter()
@@ -186,10 +262,13 @@ mod tests {
             model: "claude-sonnet-4-20250514",
             auth_status: "OAuth ✓",
             shell_mode: "read-only",
+            work_dir: Path::new("/tmp/golem-sandbox"),
+            step_mode: false,
             tools: &[],
             usage: TokenUsage::default(),
             db_path: ":memory:",
             engine: None,
+            queue: None,
         }
     }
 
@@ -199,10 +278,21 @@ mod tests {
         let names = reg.names();
         assert!(names.contains(&"/help"));
         assert!(names.contains(&"/whoami"));
+        assert!(names.contains(&"/workdir"));
+        assert!(names.contains(&"/mode"));
+        assert!(names.contains(&"/timeout"));
+        assert!(names.contains(&"/iterations"));
         assert!(names.contains(&"/tools"));
         assert!(names.contains(&"/tokens"));
         assert!(names.contains(&"/model"));
         assert!(names.contains(&"/new"));
+        assert!(names.contains(&"/recall"));
+        assert!(names.contains(&"/search"));
+        assert!(names.contains(&"/session"));
+        assert!(names.contains(&"/step"));
+        assert!(names.contains(&"/export"));
+        assert!(names.contains(&"/queue"));
+        assert!(names.contains(&"/account"));
         assert!(names.contains(&"/login"));
         assert!(names.contains(&"/logout"));
         assert!(names.contains(&"/quit"));
@@ -270,7 +360,7 @@ mod tests {
             fn description(&self) -> &str {
                 "pong"
             }
-            async fn execute(&self, _info: &SessionInfo<'_>) -> CommandResult {
+            async fn execute(&self, _args: &str, _info: &SessionInfo<'_>) -> CommandResult {
                 CommandResult::Handled
             }
         }
@@ -297,7 +387,7 @@ mod tests {
             fn description(&self) -> &str {
                 "test"
             }
-            async fn execute(&self, _info: &SessionInfo<'_>) -> CommandResult {
+            async fn execute(&self, _args: &str, _info: &SessionInfo<'_>) -> CommandResult {
                 CommandResult::StateChanged(StateChange::Model("new-model".to_string()))
             }
         }
@@ -313,6 +403,85 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn slash_command_receives_args() {
+        struct EchoCommand;
+
+        #[async_trait]
+        impl Command for EchoCommand {
+            fn name(&self) -> &str {
+                "/echo"
+            }
+            fn description(&self) -> &str {
+                "test"
+            }
+            async fn execute(&self, args: &str, _info: &SessionInfo<'_>) -> CommandResult {
+                CommandResult::StateChanged(StateChange::Model(args.to_string()))
+            }
+        }
+
+        let mut reg = CommandRegistry::new();
+        reg.register(Arc::new(EchoCommand));
+
+        match reg.dispatch("/echo  hello world ", &test_info()).await {
+            CommandResult::StateChanged(StateChange::Model(args)) => {
+                assert_eq!(args, "hello world");
+            }
+            other => panic!("expected StateChanged(Model), got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn aliases_take_args_after_any_whitespace() {
+        struct EchoCommand;
+
+        #[async_trait]
+        impl Command for EchoCommand {
+            fn name(&self) -> &str {
+                "/echo"
+            }
+            fn aliases(&self) -> &[&str] {
+                &["/e"]
+            }
+            fn description(&self) -> &str {
+                "test"
+            }
+            async fn execute(&self, args: &str, _info: &SessionInfo<'_>) -> CommandResult {
+                CommandResult::StateChanged(StateChange::Model(args.to_string()))
+            }
+        }
+
+        let mut reg = CommandRegistry::new();
+        reg.register(Arc::new(EchoCommand));
+
+        for (input, expected) in [("/e\tnginx  logs", "nginx  logs"), ("/e", "")] {
+            match reg.dispatch(input, &test_info()).await {
+                CommandResult::StateChanged(StateChange::Model(args)) => {
+                    assert_eq!(args, expected, "{input:?}");
+                }
+                other => panic!("expected StateChanged(Model), got: {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn bare_word_commands_do_not_take_args() {
+        let reg = CommandRegistry::new();
+        assert!(matches!(
+            reg.dispatch("exit the vim session", &test_info()).await,
+            CommandResult::NotACommand
+        ));
+    }
+
+    #[test]
+    fn help_entries_list_aliases_and_descriptions() {
+        let reg = CommandRegistry::new();
+        let entries = reg.help_entries();
+        assert_eq!(entries.len(), reg.names().len());
+        assert_eq!(entries[0].0, "/help (/h, /?)");
+        assert!(entries.iter().all(|(_, desc)| !desc.is_empty()));
+    }
+
     #[test]
     fn format_label_no_aliases() {
         assert_eq!(format_label("/whoami", &[]), "/whoami");
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+use crate::prompt;
+use crate::tools::shell::ShellMode;
+
+pub struct ModeCommand;
+
+#[async_trait]
+impl Command for ModeCommand {
+    fn name(&self) -> &str {
+        "/mode"
+    }
+
+    fn description(&self) -> &str {
+        "show or switch the shell mode: /mode [read-only | read-write]"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if args.is_empty() {
+            println!("  shell is {}", info.shell_mode);
+            return CommandResult::Handled;
+        }
+        let Some(mode) = parse_mode(args) else {
+            println!("  usage: /mode [read-only | read-write]");
+            return CommandResult::Handled;
+        };
+        if mode.label() == info.shell_mode {
+            println!("  shell is already {}", info.shell_mode);
+            return CommandResult::Handled;
+        }
+        if mode == ShellMode::ReadWrite && !confirm_write().await {
+            println!("  shell stays {}", info.shell_mode);
+            return CommandResult::Handled;
+        }
+        println!("  ✓ shell is now {}", mode.label());
+        CommandResult::StateChanged(StateChange::ShellMode(mode))
+    }
+}
+
+fn parse_mode(arg: &str) -> Option<ShellMode> {
+    match arg {
+        "read-only" | "ro" => Some(ShellMode::ReadOnly),
+        "read-write" | "rw" => Some(ShellMode::ReadWrite),
+        _ => None,
+    }
+}
+
+/// Ask before letting the agent change files.
+async fn confirm_write() -> bool {
+    prompt::terminal()
+        .confirm("  Allow the agent to run commands that modify files? [y/N] ")
+        .await
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(ModeCommand.name(), "/mode");
+        assert!(!ModeCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_names_and_short_forms() {
+        assert_eq!(parse_mode("read-only"), Some(ShellMode::ReadOnly));
+        assert_eq!(parse_mode("rw"), Some(ShellMode::ReadWrite));
+        assert_eq!(parse_mode("yolo"), None);
+    }
+
+    #[tokio::test]
+    async fn switching_to_read_only_needs_no_confirmation() {
+        let info = SessionInfo {
+            shell_mode: "read-write",
+            ..test_info()
+        };
+        assert!(matches!(
+            ModeCommand.execute("ro", &info).await,
+            CommandResult::StateChanged(StateChange::ShellMode(ShellMode::ReadOnly))
+        ));
+    }
+
+    #[tokio::test]
+    async fn same_mode_or_bad_argument_is_handled() {
+        for args in ["", "read-only", "sideways"] {
+            assert!(matches!(
+                ModeCommand.execute(args, &test_info()).await,
+                CommandResult::Handled
+            ));
+        }
+    }
+}
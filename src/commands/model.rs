@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 
 use super::{Command, CommandResult, SessionInfo, StateChange};
+use crate::prompt;
+use crate::thinker::ModelInfo;
 
 pub struct ModelCommand;
 
@@ -11,10 +13,10 @@ impl Command for ModelCommand {
     }
 
     fn description(&self) -> &str {
-        "list and switch the active model"
+        "list and switch the active model (or /model <id>)"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
         let engine = match info.engine {
             Some(e) => e,
             None => {
@@ -38,6 +40,11 @@ impl Command for ModelCommand {
 
         let current = info.model;
 
+        // `/model <id>` switches without the menu
+        if !args.is_empty() {
+            return switch_to(&models, current, args);
+        }
+
         // Find the current model's index (1-based) for the default
         let current_idx = models.iter().position(|m| m.id == current).map(|i| i + 1);
 
@@ -56,17 +63,17 @@ impl Command for ModelCommand {
             Some(idx) => format!(" [{idx}]"),
             None => String::new(),
         };
-        print!("\n  Select model{default_label}: ");
-        if std::io::Write::flush(&mut std::io::stdout()).is_err() {
-            return CommandResult::Handled;
-        }
-
-        let mut input = String::new();
-        if std::io::stdin().read_line(&mut input).is_err() {
-            eprintln!("  ✗ failed to read input");
-            return CommandResult::Handled;
-        }
-        let input = input.trim();
+        let input = match prompt::terminal()
+            .ask(&format!("\n  Select model{default_label}: "))
+            .await
+        {
+            Ok(input) => input.unwrap_or_default(),
+            Err(_) => {
+                eprintln!("  ✗ failed to read input");
+                return CommandResult::Handled;
+            }
+        };
+        let input = input.as_str();
 
         // Empty input = keep current
         if input.is_empty() {
@@ -97,6 +104,23 @@ impl Command for ModelCommand {
     }
 }
 
+/// Switch to the model whose id or display name is `wanted`.
+fn switch_to(models: &[ModelInfo], current: &str, wanted: &str) -> CommandResult {
+    let Some(selected) = models
+        .iter()
+        .find(|m| m.id == wanted || m.display_name.eq_ignore_ascii_case(wanted))
+    else {
+        eprintln!("  ✗ unknown model: {wanted}");
+        return CommandResult::Handled;
+    };
+    if selected.id == current {
+        println!("  already using {}", selected.display_name);
+        return CommandResult::Handled;
+    }
+    println!("  ✓ model changed to {}", selected.display_name);
+    CommandResult::StateChanged(StateChange::Model(selected.id.clone()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,7 +136,40 @@ mod tests {
     async fn returns_handled_without_engine() {
         let info = super::super::tests::test_info();
         // engine is None in test_info
-        let result = ModelCommand.execute(&info).await;
+        let result = ModelCommand.execute("", &info).await;
         assert!(matches!(result, CommandResult::Handled));
     }
+
+    #[test]
+    fn switches_by_id_or_display_name() {
+        let models = vec![
+            ModelInfo {
+                id: "claude-haiku-4-5".to_string(),
+                display_name: "Claude Haiku 4.5".to_string(),
+                created_at: None,
+            },
+            ModelInfo {
+                id: "claude-sonnet-4-5".to_string(),
+                display_name: "Claude Sonnet 4.5".to_string(),
+                created_at: None,
+            },
+        ];
+        let current = "claude-haiku-4-5";
+        assert!(matches!(
+            switch_to(&models, current, "claude-sonnet-4-5"),
+            CommandResult::StateChanged(StateChange::Model(ref id)) if id == "claude-sonnet-4-5"
+        ));
+        assert!(matches!(
+            switch_to(&models, current, "claude sonnet 4.5"),
+            CommandResult::StateChanged(StateChange::Model(_))
+        ));
+        assert!(matches!(
+            switch_to(&models, current, "claude-haiku-4-5"),
+            CommandResult::Handled
+        ));
+        assert!(matches!(
+            switch_to(&models, current, "gpt"),
+            CommandResult::Handled
+        ));
+    }
 }
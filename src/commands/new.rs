@@ -14,7 +14,7 @@ impl Command for NewCommand {
         "start a new session (clear conversation history)"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, _args: &str, info: &SessionInfo<'_>) -> CommandResult {
         let engine = match info.engine {
             Some(e) => e,
             None => {
@@ -47,7 +47,7 @@ mod tests {
     #[tokio::test]
     async fn returns_handled_without_engine() {
         let info = super::super::tests::test_info();
-        let result = NewCommand.execute(&info).await;
+        let result = NewCommand.execute("", &info).await;
         assert!(matches!(result, CommandResult::Handled));
     }
 }
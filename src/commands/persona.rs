@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+use crate::prompts::persona::{PERSONAS, Persona, find_persona};
+
+pub struct PersonaCommand;
+
+#[async_trait]
+impl Command for PersonaCommand {
+    fn name(&self) -> &str {
+        "/persona"
+    }
+
+    fn description(&self) -> &str {
+        "list personas, or switch with /persona <name> (off to clear)"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let current = info.engine.and_then(|e| e.config().persona);
+        switch_to(current, args)
+    }
+}
+
+/// List the personas for no `wanted`, else switch to it.
+fn switch_to(current: Option<&'static Persona>, wanted: &str) -> CommandResult {
+    match wanted {
+        "" => {
+            for persona in PERSONAS {
+                let marker = if current == Some(persona) {
+                    " ← current"
+                } else {
+                    ""
+                };
+                println!("  {:<10} {}{marker}", persona.name, persona.description);
+            }
+            println!("\n  /persona <name> to switch, /persona off for none");
+            CommandResult::Handled
+        }
+        "off" | "none" => {
+            if current.is_none() {
+                println!("  no persona active");
+                return CommandResult::Handled;
+            }
+            println!("  ✓ persona off");
+            CommandResult::StateChanged(StateChange::Persona(None))
+        }
+        name => {
+            let Some(persona) = find_persona(name) else {
+                let names: Vec<&str> = PERSONAS.iter().map(|p| p.name).collect();
+                eprintln!("  ✗ unknown persona: {name} (try {})", names.join(", "));
+                return CommandResult::Handled;
+            };
+            if current == Some(persona) {
+                println!("  already using {}", persona.name);
+                return CommandResult::Handled;
+            }
+            println!("  ✓ persona changed to {}", persona.name);
+            CommandResult::StateChanged(StateChange::Persona(Some(persona)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(PersonaCommand.name(), "/persona");
+        assert!(!PersonaCommand.description().is_empty());
+    }
+
+    #[test]
+    fn switches_and_clears() {
+        let teacher = find_persona("teacher");
+        assert!(matches!(
+            switch_to(None, "teacher"),
+            CommandResult::StateChanged(StateChange::Persona(Some(p))) if p.name == "teacher"
+        ));
+        assert!(matches!(
+            switch_to(teacher, "teacher"),
+            CommandResult::Handled
+        ));
+        assert!(matches!(
+            switch_to(teacher, "off"),
+            CommandResult::StateChanged(StateChange::Persona(None))
+        ));
+        assert!(matches!(switch_to(None, "off"), CommandResult::Handled));
+        assert!(matches!(switch_to(None, "pirate"), CommandResult::Handled));
+        assert!(matches!(switch_to(teacher, ""), CommandResult::Handled));
+    }
+}
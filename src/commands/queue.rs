@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct QueueCommand;
+
+const USAGE: &str = "usage: /queue [list | add <task> | run | clear]";
+
+#[async_trait]
+impl Command for QueueCommand {
+    fn name(&self) -> &str {
+        "/queue"
+    }
+
+    fn description(&self) -> &str {
+        "queue tasks, then run them side by side with their own memory"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let Some(queue) = info.queue else {
+            eprintln!("  ✗ task queue not available");
+            return CommandResult::Handled;
+        };
+        let (action, rest) = match args.split_once(char::is_whitespace) {
+            Some((action, rest)) => (action, rest.trim()),
+            None => (args, ""),
+        };
+
+        match (action, rest) {
+            ("" | "list", "") => {
+                let tasks = queue.tasks();
+                if tasks.is_empty() {
+                    println!("  queue is empty — /queue add <task>");
+                }
+                for (i, task) in tasks.iter().enumerate() {
+                    println!("  {:>3}. {task}", i + 1);
+                }
+                CommandResult::Handled
+            }
+            ("add", task) if !task.is_empty() => {
+                let waiting = queue.push(task);
+                println!("  ✓ queued ({waiting} waiting) — /queue run starts them");
+                CommandResult::Handled
+            }
+            ("run", "") => {
+                if queue.tasks().is_empty() {
+                    println!("  queue is empty — /queue add <task>");
+                    return CommandResult::Handled;
+                }
+                CommandResult::StateChanged(StateChange::RunQueue)
+            }
+            ("clear", "") => {
+                let dropped = queue.take().len();
+                println!("  ✓ dropped {dropped} queued task(s)");
+                CommandResult::Handled
+            }
+            _ => {
+                println!("  {USAGE}");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::TaskQueue;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(QueueCommand.name(), "/queue");
+        assert!(!QueueCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn add_then_run() {
+        let queue = TaskQueue::new();
+        let info = SessionInfo {
+            queue: Some(&queue),
+            ..test_info()
+        };
+
+        assert!(matches!(
+            QueueCommand.execute("run", &info).await,
+            CommandResult::Handled
+        ));
+        QueueCommand.execute("add list files here", &info).await;
+        assert_eq!(queue.tasks(), ["list files here"]);
+        assert!(matches!(
+            QueueCommand.execute("run", &info).await,
+            CommandResult::StateChanged(StateChange::RunQueue)
+        ));
+
+        QueueCommand.execute("clear", &info).await;
+        assert!(queue.tasks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn add_needs_a_task() {
+        let queue = TaskQueue::new();
+        let info = SessionInfo {
+            queue: Some(&queue),
+            ..test_info()
+        };
+        QueueCommand.execute("add", &info).await;
+        assert!(queue.tasks().is_empty());
+    }
+}
@@ -18,7 +18,7 @@ impl Command for QuitCommand {
         "exit the REPL"
     }
 
-    async fn execute(&self, _info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, _args: &str, _info: &SessionInfo<'_>) -> CommandResult {
         CommandResult::Quit
     }
 }
@@ -31,7 +31,7 @@ mod tests {
     #[tokio::test]
     async fn returns_quit() {
         assert!(matches!(
-            QuitCommand.execute(&test_info()).await,
+            QuitCommand.execute("", &test_info()).await,
             CommandResult::Quit
         ));
     }
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use std::io::IsTerminal;
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::consts::DEFAULT_RECALL_LIMIT;
+use crate::memory::{MemoryEntry, RecallHit};
+
+pub struct RecallCommand;
+
+/// Matched terms are shown bold yellow on a terminal.
+const HIGHLIGHT: (&str, &str) = ("\x1b[1;33m", "\x1b[0m");
+/// And in brackets when the output is piped.
+const BRACKETS: (&str, &str) = ("[", "]");
+
+#[async_trait]
+impl Command for RecallCommand {
+    fn name(&self) -> &str {
+        "/recall"
+    }
+
+    fn description(&self) -> &str {
+        "search memory: /recall <query>"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if args.is_empty() {
+            println!("  usage: /recall <query>");
+            return CommandResult::Handled;
+        }
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ recall not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        let hits = match engine.recall(args, DEFAULT_RECALL_LIMIT).await {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("  ✗ recall failed: {e}");
+                return CommandResult::Handled;
+            }
+        };
+
+        if hits.is_empty() {
+            println!("  no matches for \"{}\"", args);
+            return CommandResult::Handled;
+        }
+
+        let (open, close) = if std::io::stdout().is_terminal() {
+            HIGHLIGHT
+        } else {
+            BRACKETS
+        };
+        for (i, hit) in hits.iter().enumerate() {
+            println!("{}", format_hit(i + 1, hit, open, close));
+        }
+        CommandResult::Handled
+    }
+}
+
+/// One numbered line: the kind of entry and its snippet, with matches
+/// between `open` and `close`.
+fn format_hit(number: usize, hit: &RecallHit, open: &str, close: &str) -> String {
+    format!(
+        "  {number}. ({}) {}",
+        kind(&hit.entry),
+        hit.marked(open, close)
+    )
+}
+
+fn kind(entry: &MemoryEntry) -> &'static str {
+    match entry {
+        MemoryEntry::Task { .. } => "task",
+        MemoryEntry::Iteration { .. } => "step",
+        MemoryEntry::Answer { .. } => "answer",
+        MemoryEntry::Retry { .. } => "retry",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(RecallCommand.name(), "/recall");
+        assert!(RecallCommand.aliases().is_empty());
+        assert!(!RecallCommand.description().is_empty());
+    }
+
+    #[test]
+    fn hits_show_highlighted_matches() {
+        use crate::memory::{MATCH_END, MATCH_START};
+        let hit = RecallHit {
+            entry: MemoryEntry::Answer {
+                thought: "done".to_string(),
+                content: "nginx restarted".to_string(),
+            },
+            snippet: format!("{MATCH_START}nginx{MATCH_END} restarted"),
+        };
+        assert_eq!(
+            format_hit(2, &hit, BRACKETS.0, BRACKETS.1),
+            "  2. (answer) [nginx] restarted"
+        );
+        assert_eq!(
+            format_hit(1, &hit, HIGHLIGHT.0, HIGHLIGHT.1),
+            "  1. (answer) \x1b[1;33mnginx\x1b[0m restarted"
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_query() {
+        assert!(matches!(
+            RecallCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            RecallCommand.execute("nginx", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveDateTime, TimeDelta};
+
+use super::{Command, CommandResult, SessionInfo};
+use crate::banner::one_line;
+use crate::consts::format_utc;
+
+pub struct SearchCommand;
+
+const USAGE: &str = "usage: /search [text] [--since <when>] [--until <when>] [--on <day>]\n  \
+     <when>: YYYY-MM-DD, today, yesterday, a weekday (e.g. tuesday), or Nd / Nh ago, in local time";
+
+/// Tasks and answers in the result list are cut to this many characters.
+const PREVIEW_CHARS: usize = 60;
+
+/// Results beyond this many are counted but not printed.
+const MAX_RESULTS: usize = 20;
+
+/// Layout of session history timestamps, which are UTC, and of the local
+/// times shown for them.
+const TIMESTAMP: &str = "%Y-%m-%d %H:%M:%S";
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// A parsed `/search` invocation. Bounds are UTC timestamps.
+#[derive(Debug, PartialEq)]
+struct Search {
+    text: String,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+#[async_trait]
+impl Command for SearchCommand {
+    fn name(&self) -> &str {
+        "/search"
+    }
+
+    fn description(&self) -> &str {
+        "find past tasks by text and time: /search nginx --on tuesday"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let search = match parse_args(args, &Local::now()) {
+            Ok(search) => search,
+            Err(e) => {
+                if !e.is_empty() {
+                    eprintln!("  ✗ {e}");
+                }
+                println!("  {USAGE}");
+                return CommandResult::Handled;
+            }
+        };
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ search not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        let matches = match engine
+            .search_session(
+                &search.text,
+                search.since.as_deref(),
+                search.until.as_deref(),
+            )
+            .await
+        {
+            Ok(m) => m,
+            Err(e) => {
+                eprintln!("  ✗ search failed: {e}");
+                return CommandResult::Handled;
+            }
+        };
+
+        if matches.is_empty() {
+            println!("  no matching tasks");
+            return CommandResult::Handled;
+        }
+
+        for m in matches.iter().take(MAX_RESULTS) {
+            println!(
+                "  [{}] ({}) {} → {}",
+                local_time(&m.timestamp),
+                m.session,
+                one_line(&m.task, PREVIEW_CHARS),
+                one_line(&m.answer, PREVIEW_CHARS)
+            );
+        }
+        if matches.len() > MAX_RESULTS {
+            println!(
+                "  … {} more; narrow the search with more words or --since/--until",
+                matches.len() - MAX_RESULTS
+            );
+        }
+        CommandResult::Handled
+    }
+}
+
+/// Split `args` into search text and time bounds, relative to `now`. An
+/// empty error means "just show usage".
+fn parse_args(args: &str, now: &DateTime<Local>) -> Result<Search, String> {
+    let mut words = Vec::new();
+    let mut since = None;
+    let mut until = None;
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let flag = match token {
+            "--since" | "--until" | "--on" => token,
+            _ => {
+                words.push(token);
+                continue;
+            }
+        };
+        let value = tokens
+            .next()
+            .ok_or_else(|| format!("{flag} needs a value"))?;
+        let unreadable = || format!("can't read date: {value}");
+        let at = parse_when(value, now).ok_or_else(unreadable)?;
+        match flag {
+            "--since" => since = Some(at),
+            "--until" => until = Some(at),
+            _ => {
+                let day = at.date_naive();
+                since = Some(midnight(day).ok_or_else(unreadable)?);
+                until = Some(day.succ_opt().and_then(midnight).ok_or_else(unreadable)?);
+            }
+        }
+    }
+    if words.is_empty() && since.is_none() && until.is_none() {
+        return Err(String::new());
+    }
+    Ok(Search {
+        text: words.join(" "),
+        since: since.map(|s| format_utc(s.timestamp() as u64)),
+        until: until.map(|s| format_utc(s.timestamp() as u64)),
+    })
+}
+
+/// A point in time a user might type, with days in local time.
+fn parse_when(value: &str, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+    let value = value.to_lowercase();
+    let today = now.date_naive();
+    let day = match value.as_str() {
+        "today" => today,
+        "yesterday" => today.pred_opt()?,
+        _ => {
+            if let Some(target) = WEEKDAYS
+                .iter()
+                .position(|day| value.len() >= 3 && day.starts_with(value.as_str()))
+            {
+                let weekday = today.weekday().num_days_from_monday() as usize;
+                // The most recent such day before today
+                let back = (weekday + 7 - target - 1) % 7 + 1;
+                today.checked_sub_days(Days::new(back as u64))?
+            } else if let Some(n) = value.strip_suffix('d').and_then(|n| n.parse().ok()) {
+                return now.checked_sub_signed(TimeDelta::try_days(n)?);
+            } else if let Some(n) = value.strip_suffix('h').and_then(|n| n.parse().ok()) {
+                return now.checked_sub_signed(TimeDelta::try_hours(n)?);
+            } else {
+                NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?
+            }
+        }
+    };
+    midnight(day)
+}
+
+/// When `day` starts locally: midnight, or the first hour after it that
+/// exists on a day clocks skip midnight.
+fn midnight(day: NaiveDate) -> Option<DateTime<Local>> {
+    (0..3).find_map(|hour| {
+        day.and_hms_opt(hour, 0, 0)?
+            .and_local_timezone(Local)
+            .earliest()
+    })
+}
+
+/// A session history timestamp in local time.
+fn local_time(timestamp: &str) -> String {
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP)
+        .map(|t| {
+            t.and_utc()
+                .with_timezone(&Local)
+                .format(TIMESTAMP)
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+    use chrono::TimeZone;
+
+    /// Friday 2026-10-16 15:30:00, local time.
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 16, 15, 30, 0).unwrap()
+    }
+
+    /// Local midnight of a date, as the UTC timestamp a bound holds.
+    fn bound(y: i32, mo: u32, d: u32) -> Option<String> {
+        let midnight = Local.with_ymd_and_hms(y, mo, d, 0, 0, 0).unwrap();
+        Some(format_utc(midnight.timestamp() as u64))
+    }
+
+    #[test]
+    fn metadata() {
+        assert_eq!(SearchCommand.name(), "/search");
+        assert!(SearchCommand.aliases().is_empty());
+        assert!(!SearchCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_dates_in_local_time() {
+        let at = |v| parse_when(v, &now()).map(|t| t.format(TIMESTAMP).to_string());
+        assert_eq!(at("2026-10-06").unwrap(), "2026-10-06 00:00:00");
+        assert_eq!(at("today").unwrap(), "2026-10-16 00:00:00");
+        assert_eq!(at("Yesterday").unwrap(), "2026-10-15 00:00:00");
+        assert_eq!(at("tuesday").unwrap(), "2026-10-13 00:00:00");
+        assert_eq!(at("thu").unwrap(), "2026-10-15 00:00:00");
+        // Today's weekday means a week ago
+        assert_eq!(at("friday").unwrap(), "2026-10-09 00:00:00");
+        assert_eq!(at("2d").unwrap(), "2026-10-14 15:30:00");
+        assert_eq!(at("3h").unwrap(), "2026-10-16 12:30:00");
+        assert_eq!(at("2026-13-01"), None);
+        assert_eq!(at("2026-02-30"), None);
+        assert_eq!(at("tu"), None);
+        assert_eq!(at("soon"), None);
+    }
+
+    #[test]
+    fn parses_text_and_bounds() {
+        assert_eq!(
+            parse_args("nginx config --on tuesday", &now()).unwrap(),
+            Search {
+                text: "nginx config".to_string(),
+                since: bound(2026, 10, 13),
+                until: bound(2026, 10, 14),
+            }
+        );
+        assert_eq!(
+            parse_args("--since 2026-10-01", &now()).unwrap(),
+            Search {
+                text: String::new(),
+                since: bound(2026, 10, 1),
+                until: None,
+            }
+        );
+        assert_eq!(parse_args("", &now()), Err(String::new()));
+        assert!(parse_args("nginx --since", &now()).is_err());
+        assert!(parse_args("nginx --until someday", &now()).is_err());
+    }
+
+    #[test]
+    fn shows_timestamps_in_local_time() {
+        let utc = format_utc(now().timestamp() as u64);
+        assert_eq!(local_time(&utc), "2026-10-16 15:30:00");
+        assert_eq!(local_time("not a time"), "not a time");
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            SearchCommand.execute("nginx", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
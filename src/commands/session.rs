@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct SessionCommand;
+
+const USAGE: &str = "usage: /session [list | new <name> | switch <name>]";
+
+#[async_trait]
+impl Command for SessionCommand {
+    fn name(&self) -> &str {
+        "/session"
+    }
+
+    fn description(&self) -> &str {
+        "list, create, or switch named sessions"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let mut parts = args.split_whitespace();
+        let action = parts.next().unwrap_or("list");
+        let name = parts.next();
+        if parts.next().is_some() {
+            println!("  {USAGE}");
+            return CommandResult::Handled;
+        }
+
+        let engine = match info.engine {
+            Some(e) => e,
+            None => {
+                eprintln!("  ✗ sessions not available");
+                return CommandResult::Handled;
+            }
+        };
+
+        match (action, name) {
+            ("list", None) => {
+                let active = engine.active_session().await.unwrap_or_default();
+                match engine.sessions().await {
+                    Ok(sessions) => {
+                        for s in sessions {
+                            let marker = if s.name == active { " ← active" } else { "" };
+                            println!(
+                                "  {} ({} tasks, created {}){marker}",
+                                s.name, s.entries, s.created_at
+                            );
+                        }
+                    }
+                    Err(e) => eprintln!("  ✗ failed to list sessions: {e}"),
+                }
+                CommandResult::Handled
+            }
+            ("new", Some(name)) => {
+                if let Err(e) = engine.create_session(name).await {
+                    eprintln!("  ✗ {e}");
+                    return CommandResult::Handled;
+                }
+                switch(engine, name).await
+            }
+            ("switch", Some(name)) => switch(engine, name).await,
+            _ => {
+                println!("  {USAGE}");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+async fn switch(engine: &crate::engine::react::ReactEngine, name: &str) -> CommandResult {
+    match engine.switch_session(name).await {
+        Ok(()) => {
+            println!("  ✓ switched to session {name}");
+            CommandResult::StateChanged(StateChange::Session(name.to_string()))
+        }
+        Err(e) => {
+            eprintln!("  ✗ {e}");
+            CommandResult::Handled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(SessionCommand.name(), "/session");
+        assert!(SessionCommand.aliases().is_empty());
+        assert!(!SessionCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn returns_handled_without_engine() {
+        assert!(matches!(
+            SessionCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_extra_args() {
+        assert!(matches!(
+            SessionCommand.execute("new my project", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
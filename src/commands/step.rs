@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct StepCommand;
+
+#[async_trait]
+impl Command for StepCommand {
+    fn name(&self) -> &str {
+        "/step"
+    }
+
+    fn description(&self) -> &str {
+        "toggle step mode (review each tool call before it runs)"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let enabled = match args {
+            "" => !info.step_mode,
+            "on" => true,
+            "off" => false,
+            _ => {
+                println!("  usage: /step [on | off]");
+                return CommandResult::Handled;
+            }
+        };
+        if enabled {
+            println!("  ✓ step mode on: each tool call waits for run, skip, edit or abort");
+        } else {
+            println!("  ✓ step mode off");
+        }
+        CommandResult::StateChanged(StateChange::StepMode(enabled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(StepCommand.name(), "/step");
+        assert!(!StepCommand.description().is_empty());
+    }
+
+    #[tokio::test]
+    async fn toggles_or_sets() {
+        let toggled = StepCommand.execute("", &test_info()).await;
+        assert!(matches!(
+            toggled,
+            CommandResult::StateChanged(StateChange::StepMode(true))
+        ));
+
+        let info = SessionInfo {
+            step_mode: false,
+            ..test_info()
+        };
+        assert!(matches!(
+            StepCommand.execute("off", &info).await,
+            CommandResult::StateChanged(StateChange::StepMode(false))
+        ));
+
+        assert!(matches!(
+            StepCommand.execute("maybe", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+}
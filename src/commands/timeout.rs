@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use std::time::Duration;
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct TimeoutCommand;
+
+#[async_trait]
+impl Command for TimeoutCommand {
+    fn name(&self) -> &str {
+        "/timeout"
+    }
+
+    fn description(&self) -> &str {
+        "show or set the tool timeout: /timeout [90 | 90s | 5m]"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if args.is_empty() {
+            match info.engine {
+                Some(engine) => println!(
+                    "  tool timeout: {}s",
+                    engine.config().tool_timeout.as_secs()
+                ),
+                None => eprintln!("  ✗ timeout not available"),
+            }
+            return CommandResult::Handled;
+        }
+        match parse_duration(args) {
+            Some(timeout) => {
+                println!("  ✓ tool calls now time out after {}s", timeout.as_secs());
+                CommandResult::StateChanged(StateChange::ToolTimeout(timeout))
+            }
+            None => {
+                println!("  usage: /timeout <seconds>, e.g. 90, 90s or 5m");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+/// Seconds, with an optional `s` or `m` suffix. Zero is not a timeout,
+/// and neither is anything too large to count in seconds.
+fn parse_duration(arg: &str) -> Option<Duration> {
+    let (number, unit) = match arg.strip_suffix('m') {
+        Some(minutes) => (minutes, 60),
+        None => (arg.strip_suffix('s').unwrap_or(arg), 1),
+    };
+    let secs: u64 = number.trim().parse().ok()?;
+    if secs == 0 {
+        return None;
+    }
+    secs.checked_mul(unit).map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(TimeoutCommand.name(), "/timeout");
+        assert!(!TimeoutCommand.description().is_empty());
+    }
+
+    #[test]
+    fn parses_seconds_and_minutes() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("0"), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("999999999999999999m"), None);
+    }
+
+    #[tokio::test]
+    async fn sets_the_timeout() {
+        assert!(matches!(
+            TimeoutCommand.execute("2m", &test_info()).await,
+            CommandResult::StateChanged(StateChange::ToolTimeout(t)) if t == Duration::from_secs(120)
+        ));
+        assert!(matches!(
+            TimeoutCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_overflowing_timeout() {
+        assert!(matches!(
+            TimeoutCommand
+                .execute("999999999999999999m", &test_info())
+                .await,
+            CommandResult::Handled
+        ));
+    }
+}
@@ -1,10 +1,19 @@
 use async_trait::async_trait;
 
 use super::{Command, CommandResult, SessionInfo};
+use crate::banner::one_line;
 use crate::consts::format_number;
+use crate::metrics::TaskRecord;
+use crate::thinker::TokenUsage;
 
 pub struct TokensCommand;
 
+/// Tasks listed when `/tokens` is given no count.
+const DEFAULT_TASKS: usize = 10;
+
+/// Task text in the table is cut to this many characters.
+const TASK_CHARS: usize = 40;
+
 #[async_trait]
 impl Command for TokensCommand {
     fn name(&self) -> &str {
@@ -12,34 +21,127 @@ impl Command for TokensCommand {
     }
 
     fn description(&self) -> &str {
-        "show session token usage"
+        "show session token usage and the last N tasks: /tokens [N]"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        let limit = match args {
+            "" => DEFAULT_TASKS,
+            n => match n.parse() {
+                Ok(n) => n,
+                Err(_) => {
+                    println!("  usage: /tokens [N]");
+                    return CommandResult::Handled;
+                }
+            },
+        };
+
         if info.usage.total() == 0 {
             println!("  no tokens used this session");
         } else {
-            println!(
-                "  {} input + {} output = {} total",
-                format_number(info.usage.input_tokens),
-                format_number(info.usage.output_tokens),
-                format_number(info.usage.total()),
-            );
+            print!("{}", format_usage(info.usage));
+        }
+
+        let Some(engine) = info.engine else {
+            return CommandResult::Handled;
+        };
+        // One model says nothing the totals didn't
+        let models = engine.session_usage_by_model();
+        if models.len() > 1 {
+            print!("\n{}", format_models(models));
+        }
+        match engine.recent_tasks(limit) {
+            Ok(records) if records.is_empty() => {}
+            Ok(records) => print!("\n{}", format_tasks(&records)),
+            Err(e) => eprintln!("  ✗ failed to read task metrics: {e}"),
         }
         CommandResult::Handled
     }
 }
 
+/// Session totals, with the input split into fresh and cached tokens once
+/// the prompt cache has been read.
+fn format_usage(usage: TokenUsage) -> String {
+    let mut out = format!(
+        "  {} input + {} output = {} total\n",
+        format_number(usage.input_tokens),
+        format_number(usage.output_tokens),
+        format_number(usage.total()),
+    );
+    if usage.cache_read_tokens > 0 {
+        out.push_str(&format!(
+            "  input: {} fresh + {} read from cache\n",
+            format_number(usage.input_tokens.saturating_sub(usage.cache_read_tokens)),
+            format_number(usage.cache_read_tokens),
+        ));
+    }
+    out
+}
+
+/// A table of the session's usage per model, in order of first use.
+fn format_models(models: &[(String, TokenUsage)]) -> String {
+    let width = models
+        .iter()
+        .map(|(model, _)| model.len())
+        .chain(["model".len()])
+        .max()
+        .unwrap_or_default();
+    let mut out = format!(
+        "  {:<width$}  {:>9}  {:>9}  {:>7}  {:>8}\n",
+        "model", "input", "cached", "output", "cost"
+    );
+    for (model, usage) in models {
+        let cost = match usage.cost(model) {
+            Some(cost) => format!("${cost:.4}"),
+            None => "—".to_string(),
+        };
+        out.push_str(&format!(
+            "  {model:<width$}  {:>9}  {:>9}  {:>7}  {:>8}\n",
+            format_number(usage.input_tokens),
+            format_number(usage.cache_read_tokens),
+            format_number(usage.output_tokens),
+            cost,
+        ));
+    }
+    out
+}
+
+/// A table of `records`, newest first: when, tokens in and out, cache
+/// hits, estimated cost, and the task.
+fn format_tasks(records: &[TaskRecord]) -> String {
+    let mut out = format!(
+        "  {:<16}  {:>9}  {:>7}  {:>9}  {:>8}  task\n",
+        "finished (UTC)", "input", "output", "cached", "cost"
+    );
+    for record in records {
+        let cost = match record.usage.cost(&record.model) {
+            Some(cost) => format!("${cost:.4}"),
+            None => "—".to_string(),
+        };
+        let task = record.task.as_deref().unwrap_or("(not recorded)");
+        let mark = if record.succeeded { "" } else { "✗ " };
+        out.push_str(&format!(
+            "  {:<16}  {:>9}  {:>7}  {:>9}  {:>8}  {mark}{}\n",
+            record.timestamp.get(..16).unwrap_or(&record.timestamp),
+            format_number(record.usage.input_tokens),
+            format_number(record.usage.output_tokens),
+            format_number(record.usage.cache_read_tokens),
+            cost,
+            one_line(task, TASK_CHARS)
+        ));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::commands::tests::test_info;
-    use crate::thinker::TokenUsage;
 
     #[tokio::test]
     async fn returns_handled_zero() {
         assert!(matches!(
-            TokensCommand.execute(&test_info()).await,
+            TokensCommand.execute("", &test_info()).await,
             CommandResult::Handled
         ));
     }
@@ -50,12 +152,111 @@ mod tests {
             usage: TokenUsage {
                 input_tokens: 1234,
                 output_tokens: 567,
+                cache_read_tokens: 0,
             },
             ..test_info()
         };
         assert!(matches!(
-            TokensCommand.execute(&info).await,
+            TokensCommand.execute("", &info).await,
+            CommandResult::Handled
+        ));
+    }
+
+    #[tokio::test]
+    async fn bad_count_is_handled() {
+        assert!(matches!(
+            TokensCommand.execute("lots", &test_info()).await,
             CommandResult::Handled
         ));
     }
+
+    #[test]
+    fn totals_split_cached_input_once_there_is_some() {
+        let usage = TokenUsage {
+            input_tokens: 12_000,
+            output_tokens: 500,
+            cache_read_tokens: 0,
+        };
+        assert_eq!(
+            format_usage(usage),
+            "  12,000 input + 500 output = 12,500 total\n"
+        );
+        let usage = TokenUsage {
+            cache_read_tokens: 10_000,
+            ..usage
+        };
+        assert_eq!(
+            format_usage(usage),
+            "  12,000 input + 500 output = 12,500 total\n  input: 2,000 fresh + 10,000 read from cache\n"
+        );
+    }
+
+    #[test]
+    fn model_table_shows_each_model() {
+        let models = [
+            (
+                "claude-sonnet-4-5".to_string(),
+                TokenUsage {
+                    input_tokens: 12_000,
+                    output_tokens: 500,
+                    cache_read_tokens: 10_000,
+                },
+            ),
+            (
+                "mock".to_string(),
+                TokenUsage {
+                    input_tokens: 1_000,
+                    output_tokens: 20,
+                    cache_read_tokens: 0,
+                },
+            ),
+        ];
+        let lines: Vec<String> = format_models(&models).lines().map(str::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "  model                  input     cached   output      cost",
+                "  claude-sonnet-4-5     12,000     10,000      500   $0.0165",
+                "  mock                   1,000          0       20         —",
+            ]
+        );
+    }
+
+    #[test]
+    fn task_table_shows_usage_cache_and_cost() {
+        let records = [
+            TaskRecord {
+                timestamp: "2026-10-16 09:30:12".to_string(),
+                task: Some("list the files in src and count them".to_string()),
+                model: "claude-sonnet-4-5".to_string(),
+                usage: TokenUsage {
+                    input_tokens: 12_000,
+                    output_tokens: 500,
+                    cache_read_tokens: 10_000,
+                },
+                succeeded: true,
+            },
+            TaskRecord {
+                timestamp: "2026-10-15 18:02:40".to_string(),
+                task: None,
+                model: "mock".to_string(),
+                usage: TokenUsage::default(),
+                succeeded: false,
+            },
+        ];
+        let lines: Vec<String> = format_tasks(&records).lines().map(str::to_string).collect();
+        assert_eq!(
+            lines[0],
+            "  finished (UTC)        input   output     cached      cost  task"
+        );
+        // 2,000 fresh at $3/M + 10,000 cached at a tenth of that + 500 at $15/M
+        assert_eq!(
+            lines[1],
+            "  2026-10-16 09:30     12,000      500     10,000   $0.0165  list the files in src and count them"
+        );
+        assert_eq!(
+            lines[2],
+            "  2026-10-15 18:02          0        0          0         —  ✗ (not recorded)"
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
+use std::time::Duration;
 
 use super::{Command, CommandResult, SessionInfo};
+use crate::tools::ToolDetails;
 
 pub struct ToolsCommand;
 
@@ -11,10 +13,31 @@ impl Command for ToolsCommand {
     }
 
     fn description(&self) -> &str {
-        "list registered tools"
+        "list registered tools; /tools -v for args, access, timeout and source"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        match args {
+            "" => {}
+            "-v" | "--verbose" => {
+                let Some(engine) = info.engine else {
+                    eprintln!("  ✗ tool details not available");
+                    return CommandResult::Handled;
+                };
+                let details = engine.tools().details().await;
+                if details.is_empty() {
+                    println!("  (no tools registered)");
+                }
+                for tool in &details {
+                    print!("{}", describe(tool, engine.config().tool_timeout));
+                }
+                return CommandResult::Handled;
+            }
+            _ => {
+                println!("  usage: /tools [-v]");
+                return CommandResult::Handled;
+            }
+        }
         if info.tools.is_empty() {
             println!("  (no tools registered)");
         } else {
@@ -26,6 +49,49 @@ impl Command for ToolsCommand {
     }
 }
 
+/// `tool` in full, as `/tools -v` prints it; `timeout` is the engine's.
+fn describe(tool: &ToolDetails, timeout: Duration) -> String {
+    let mut out = format!(
+        "  {} ({})\n    {}\n",
+        tool.name,
+        tool.source.label(),
+        tool.summary
+    );
+    match &tool.args {
+        Some(args) if args.is_empty() => out.push_str("    args:    none\n"),
+        Some(args) => {
+            let width = args.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+            for (i, (name, what)) in args.iter().enumerate() {
+                let label = if i == 0 { "args:" } else { "" };
+                out.push_str(&format!("    {label:<8} {name:<width$}  {what}\n"));
+            }
+        }
+        None => out.push_str("    args:    see the description\n"),
+    }
+    let caps = &tool.capabilities;
+    let access = if caps.read_only {
+        "read-only"
+    } else {
+        "read-write"
+    };
+    let others: Vec<&str> = caps
+        .labels()
+        .into_iter()
+        .filter(|label| *label != "read-only")
+        .collect();
+    if others.is_empty() {
+        out.push_str(&format!("    access:  {access}\n"));
+    } else {
+        out.push_str(&format!("    access:  {access} ({})\n", others.join(", ")));
+    }
+    if tool.uses_timeout {
+        out.push_str(&format!("    timeout: {}s\n", timeout.as_secs()));
+    } else {
+        out.push_str("    timeout: none (bounds its own run time)\n");
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,11 +100,60 @@ mod tests {
     #[tokio::test]
     async fn returns_handled_empty() {
         assert!(matches!(
-            ToolsCommand.execute(&test_info()).await,
+            ToolsCommand.execute("", &test_info()).await,
             CommandResult::Handled
         ));
     }
 
+    #[test]
+    fn describes_every_detail() {
+        use crate::tools::{Capabilities, ToolSource, split_args};
+
+        let (summary, args) = split_args(
+            "Copy a file. Args: {\"from\": \"<path>\", \"to\": \"<path>\"}. Overwrites.",
+        );
+        let tool = ToolDetails {
+            name: "copy".to_string(),
+            summary,
+            args,
+            capabilities: Capabilities {
+                read_only: false,
+                network: false,
+                destructive: true,
+                needs_confirmation: true,
+            },
+            uses_timeout: true,
+            source: ToolSource::Plugin,
+        };
+        assert_eq!(
+            describe(&tool, Duration::from_secs(30)),
+            "  copy (plugin)\n\
+             \x20   Copy a file. Overwrites.\n\
+             \x20   args:    from  <path>\n\
+             \x20            to    <path>\n\
+             \x20   access:  read-write (destructive, confirm)\n\
+             \x20   timeout: 30s\n"
+        );
+
+        let tool = ToolDetails {
+            args: None,
+            capabilities: Capabilities {
+                read_only: true,
+                network: false,
+                destructive: false,
+                needs_confirmation: false,
+            },
+            uses_timeout: false,
+            source: ToolSource::BuiltIn,
+            ..tool
+        };
+        let text = describe(&tool, Duration::from_secs(30));
+        assert!(text.starts_with("  copy (built-in)\n"));
+        assert!(text.contains("args:    see the description\n"));
+        assert!(text.contains("access:  read-only\n"));
+        assert!(text.contains("timeout: none"));
+    }
+
     #[tokio::test]
     async fn returns_handled_with_tools() {
         let tool_names = vec!["shell — Execute shell commands".to_string()];
@@ -47,7 +162,7 @@ mod tests {
             ..test_info()
         };
         assert!(matches!(
-            ToolsCommand.execute(&info).await,
+            ToolsCommand.execute("", &info).await,
             CommandResult::Handled
         ));
     }
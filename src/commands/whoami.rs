@@ -11,13 +11,14 @@ impl Command for WhoamiCommand {
     }
 
     fn description(&self) -> &str {
-        "show provider, model, and auth status"
+        "show provider, model, auth status, and shell settings"
     }
 
-    async fn execute(&self, info: &SessionInfo<'_>) -> CommandResult {
+    async fn execute(&self, _args: &str, info: &SessionInfo<'_>) -> CommandResult {
         println!("  provider  {} ({})", info.provider, info.model);
         println!("  auth      {}", info.auth_status);
         println!("  shell     {}", info.shell_mode);
+        println!("  workdir   {}", info.work_dir.display());
         CommandResult::Handled
     }
 }
@@ -30,7 +31,7 @@ mod tests {
     #[tokio::test]
     async fn returns_handled() {
         assert!(matches!(
-            WhoamiCommand.execute(&test_info()).await,
+            WhoamiCommand.execute("", &test_info()).await,
             CommandResult::Handled
         ));
     }
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+use super::{Command, CommandResult, SessionInfo, StateChange};
+
+pub struct WorkdirCommand;
+
+#[async_trait]
+impl Command for WorkdirCommand {
+    fn name(&self) -> &str {
+        "/workdir"
+    }
+
+    fn description(&self) -> &str {
+        "show or change where shell commands run: /workdir [path]"
+    }
+
+    async fn execute(&self, args: &str, info: &SessionInfo<'_>) -> CommandResult {
+        if args.is_empty() {
+            println!("  {}", info.work_dir.display());
+            return CommandResult::Handled;
+        }
+        match resolve(info.work_dir, args) {
+            Ok(dir) => {
+                println!("  ✓ shell commands now run in {}", dir.display());
+                CommandResult::StateChanged(StateChange::WorkDir(dir))
+            }
+            Err(e) => {
+                eprintln!("  ✗ {e}");
+                CommandResult::Handled
+            }
+        }
+    }
+}
+
+/// `arg` as an absolute directory, taking relative paths from `current`
+/// like `cd` does.
+fn resolve(current: &Path, arg: &str) -> Result<PathBuf, String> {
+    let path = match arg.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+            .ok_or("no home directory")?
+            .join(rest.trim_start_matches('/')),
+        _ => current.join(arg),
+    };
+    let dir = path
+        .canonicalize()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::test_info;
+
+    #[test]
+    fn metadata() {
+        assert_eq!(WorkdirCommand.name(), "/workdir");
+        assert!(!WorkdirCommand.description().is_empty());
+    }
+
+    #[test]
+    fn resolves_relative_to_current() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        assert_eq!(resolve(&root, "src"), Ok(root.join("src")));
+        assert_eq!(resolve(&root.join("src"), ".."), Ok(root.clone()));
+        assert!(
+            resolve(&root, "notes.txt")
+                .unwrap_err()
+                .contains("not a directory")
+        );
+        assert!(resolve(&root, "missing").is_err());
+    }
+
+    #[tokio::test]
+    async fn shows_or_changes_the_directory() {
+        assert!(matches!(
+            WorkdirCommand.execute("", &test_info()).await,
+            CommandResult::Handled
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        assert!(matches!(
+            WorkdirCommand.execute(&path, &test_info()).await,
+            CommandResult::StateChanged(StateChange::WorkDir(d)) if d == dir.path().canonicalize().unwrap()
+        ));
+    }
+}
@@ -4,6 +4,9 @@
 //! [`SqliteMemory`](crate::memory::sqlite::SqliteMemory) — pass the same
 //! path to all three.
 
+pub mod settings;
+pub mod wizard;
+
 use anyhow::{Context, Result};
 use rusqlite::Connection;
 use std::sync::Mutex;
@@ -17,14 +20,7 @@ impl Config {
     /// Open or create the config table in the given database.
     /// Use `":memory:"` for tests.
     pub fn open(path: &str) -> Result<Self> {
-        let conn = Connection::open(path).context("failed to open config database")?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS config (
-                key   TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-        )
-        .context("failed to create config table")?;
+        let conn = crate::db::open(path).context("failed to open config database")?;
         Ok(Self {
             conn: Mutex::new(conn),
         })
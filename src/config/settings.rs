@@ -0,0 +1,544 @@
+//! Startup settings from TOML files.
+//!
+//! `~/.golem/config.toml` holds user-wide defaults and `./.golem.toml`
+//! overrides them for one project. Environment variables and CLI flags
+//! override both; main.rs lays those on top with [`Settings::merge`].
+//!
+//! ```toml
+//! provider = "anthropic"
+//! account = "work"
+//! model = "claude-sonnet-4-5"
+//! max_iterations = 30
+//! timeout = 60
+//!
+//! [shell]
+//! mode = "read-write"
+//! work_dir = "."
+//! confirm = false
+//!
+//! [tools]
+//! allow_network = false
+//!
+//! [keys]
+//! newline = "ctrl-j"
+//!
+//! [macros]
+//! deploy = "run ./deploy.sh, then check that /health answers"
+//!
+//! [[examples]]
+//! task = "how full is the disk?"
+//! thought = "df reports usage"
+//! tool = "shell"
+//! args = { command = "df -h /" }
+//! observation = "/dev/sda1  100G  40G  60G  40% /"
+//! answer = "40% of / is used"
+//!
+//! [webhooks.alerts]
+//! task = "triage this alert: {{payload}}"
+//! secret_env = "ALERTS_SECRET"
+//! notify = "https://hooks.example.com/golem"
+//!
+//! [schedules.logs]
+//! cron = "0 9 * * *"
+//! task = "summarize yesterday's logs"
+//!
+//! [telemetry]
+//! endpoint = "http://localhost:4318"
+//!
+//! [embeddings]
+//! url = "http://localhost:11434/v1/embeddings"
+//! model = "nomic-embed-text"
+//! ```
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::banner::BannerStyle;
+use crate::consts::{PROJECT_CONFIG_FILE, user_config_path};
+use crate::editor::{EditMode, KeyBindings};
+use crate::prompts::react::Example;
+use crate::schedule::parse_cron;
+use crate::tools::shell::ShellMode;
+
+/// Every setting a config file may hold. `None` means the file leaves it
+/// to a lower layer, or to the built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// `anthropic` or `human`.
+    pub provider: Option<String>,
+    /// Which of the provider's stored accounts to use.
+    pub account: Option<String>,
+    pub model: Option<String>,
+    /// Database path; `:memory:` or `none` like `--db`.
+    pub db: Option<String>,
+    pub max_iterations: Option<usize>,
+    /// Tool execution timeout in seconds.
+    pub timeout: Option<u64>,
+    pub max_tokens_per_task: Option<u64>,
+    pub max_cost_per_task: Option<f64>,
+    pub retries: Option<u32>,
+    /// Times to ask the model to correct a response that isn't a step.
+    pub parse_retries: Option<usize>,
+    /// The language thoughts and answers are written in, e.g. `he`.
+    pub language: Option<String>,
+    pub observation_budget: Option<usize>,
+    pub global_memory: Option<bool>,
+    pub transcript: Option<bool>,
+    /// Keep every event of every session in the database.
+    pub event_log: Option<bool>,
+    /// Prompt keybindings: `emacs` or `vi`.
+    pub edit_mode: Option<EditMode>,
+    /// Desktop notification for tasks running at least this many seconds.
+    pub notify_after: Option<u64>,
+    /// Startup banner: `full`, `compact` or `off`.
+    pub banner: Option<BannerStyle>,
+    pub shell: ShellSettings,
+    pub tools: ToolSettings,
+    pub keys: KeyBindings,
+    /// `[macros]`: slash command names, without the `/`, to the prompt
+    /// each one runs.
+    pub macros: BTreeMap<String, String>,
+    /// `[[examples]]`: worked tasks shown in the system prompt. A project
+    /// file's examples replace the user's.
+    pub examples: Vec<Example>,
+    /// Address `golem serve` listens on.
+    pub listen: Option<String>,
+    /// Unix socket `golem daemon` listens on and `golem attach` connects
+    /// to. Relative paths are taken from the file's directory.
+    pub socket: Option<PathBuf>,
+    /// Address `golem daemon` serves Prometheus metrics on, at `/metrics`.
+    pub metrics_listen: Option<String>,
+    /// `[webhooks.<name>]`: tasks `golem serve` runs when
+    /// `/webhooks/<name>` is posted to.
+    pub webhooks: BTreeMap<String, WebhookSettings>,
+    /// `[schedules.<id>]`: tasks `golem serve` runs on a cron schedule.
+    pub schedules: BTreeMap<String, ScheduleSettings>,
+    pub telemetry: TelemetrySettings,
+    pub embeddings: EmbeddingSettings,
+}
+
+/// The `[shell]` table.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShellSettings {
+    pub mode: Option<ShellMode>,
+    /// Relative paths are taken from the file's directory.
+    pub work_dir: Option<PathBuf>,
+    /// Ask before running commands.
+    pub confirm: Option<bool>,
+    /// Tool output beyond this many bytes is truncated.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// The `[tools]` table: which tools are offered and run, by what they
+/// may do. Both default to true.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolSettings {
+    /// Tools that may reach the network.
+    pub allow_network: Option<bool>,
+    /// Tools that may irreversibly change or delete data.
+    pub allow_destructive: Option<bool>,
+}
+
+/// A `[webhooks.<name>]` table.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSettings {
+    /// The task to run; `{{payload}}` and `{{payload.a.b}}` are filled in
+    /// from the request body.
+    pub task: String,
+    /// The key requests are signed with.
+    pub secret: Option<String>,
+    /// The environment variable holding the key, instead of `secret`.
+    pub secret_env: Option<String>,
+    /// URL the result is posted to.
+    pub notify: Option<String>,
+}
+
+/// A `[schedules.<id>]` table.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleSettings {
+    /// Five fields, in local time: minute, hour, day of month, month,
+    /// day of week.
+    pub cron: String,
+    pub task: String,
+    /// URL the result is posted to.
+    pub notify: Option<String>,
+}
+
+/// The `[telemetry]` table.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetrySettings {
+    /// OTLP/HTTP collector that spans are sent to, e.g.
+    /// `http://localhost:4318`.
+    pub endpoint: Option<String>,
+    /// Extra headers sent with every export, e.g. an API key.
+    pub headers: BTreeMap<String, String>,
+}
+
+/// The `[embeddings]` table: the model memories are embedded with for
+/// semantic recall. Without one, golem hashes their words locally.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmbeddingSettings {
+    /// An OpenAI-compatible embeddings endpoint, e.g.
+    /// `https://api.openai.com/v1/embeddings`.
+    pub url: Option<String>,
+    pub model: Option<String>,
+    /// The environment variable holding the endpoint's API key, if it
+    /// needs one.
+    pub api_key_env: Option<String>,
+}
+
+impl Settings {
+    /// The user file, then the project file in `dir` on top of it.
+    /// Missing files are skipped; a file that doesn't parse is an error.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let user = Self::read(&user_config_path())?;
+        let project = Self::read(&dir.join(PROJECT_CONFIG_FILE))?;
+        Ok(user.merge(project))
+    }
+
+    /// The settings in the file at `path`, or none if it doesn't exist.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let mut settings: Self =
+            toml::from_str(&text).with_context(|| format!("invalid config {}", path.display()))?;
+        // `deploy` and `/deploy` name the same macro
+        let mut macros = BTreeMap::new();
+        for (name, prompt) in std::mem::take(&mut settings.macros) {
+            let word = name.trim_start_matches('/');
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                bail!(
+                    "invalid macro name {name:?} in {}: use one word",
+                    path.display()
+                );
+            }
+            macros.insert(word.to_string(), prompt);
+        }
+        settings.macros = macros;
+        for example in &settings.examples {
+            if example.tool.is_some() != example.observation.is_some() {
+                bail!(
+                    "example {:?} in {} needs both tool and observation, or neither",
+                    example.task,
+                    path.display()
+                );
+            }
+        }
+        for (name, webhook) in &settings.webhooks {
+            if webhook.secret.is_none() == webhook.secret_env.is_none() {
+                bail!(
+                    "webhook {name:?} in {} needs one of secret or secret_env",
+                    path.display()
+                );
+            }
+        }
+        if settings.embeddings.url.is_some() != settings.embeddings.model.is_some() {
+            bail!(
+                "[embeddings] in {} needs both url and model, or neither",
+                path.display()
+            );
+        }
+        for (id, schedule) in &settings.schedules {
+            parse_cron(&schedule.cron)
+                .with_context(|| format!("schedule {id:?} in {}", path.display()))?;
+        }
+        if let (Some(work_dir), Some(base)) = (&settings.shell.work_dir, path.parent()) {
+            settings.shell.work_dir = Some(base.join(work_dir));
+        }
+        if let (Some(socket), Some(base)) = (&settings.socket, path.parent()) {
+            settings.socket = Some(base.join(socket));
+        }
+        Ok(settings)
+    }
+
+    /// These settings with every one `over` sets replaced by its value.
+    pub fn merge(self, over: Self) -> Self {
+        let mut macros = self.macros;
+        macros.extend(over.macros);
+        let mut webhooks = self.webhooks;
+        webhooks.extend(over.webhooks);
+        let mut schedules = self.schedules;
+        schedules.extend(over.schedules);
+        let mut headers = self.telemetry.headers;
+        headers.extend(over.telemetry.headers);
+        let examples = if over.examples.is_empty() {
+            self.examples
+        } else {
+            over.examples
+        };
+        Self {
+            provider: over.provider.or(self.provider),
+            account: over.account.or(self.account),
+            model: over.model.or(self.model),
+            db: over.db.or(self.db),
+            max_iterations: over.max_iterations.or(self.max_iterations),
+            timeout: over.timeout.or(self.timeout),
+            max_tokens_per_task: over.max_tokens_per_task.or(self.max_tokens_per_task),
+            max_cost_per_task: over.max_cost_per_task.or(self.max_cost_per_task),
+            retries: over.retries.or(self.retries),
+            parse_retries: over.parse_retries.or(self.parse_retries),
+            language: over.language.or(self.language),
+            observation_budget: over.observation_budget.or(self.observation_budget),
+            global_memory: over.global_memory.or(self.global_memory),
+            transcript: over.transcript.or(self.transcript),
+            event_log: over.event_log.or(self.event_log),
+            edit_mode: over.edit_mode.or(self.edit_mode),
+            notify_after: over.notify_after.or(self.notify_after),
+            banner: over.banner.or(self.banner),
+            shell: ShellSettings {
+                mode: over.shell.mode.or(self.shell.mode),
+                work_dir: over.shell.work_dir.or(self.shell.work_dir),
+                confirm: over.shell.confirm.or(self.shell.confirm),
+                max_output_bytes: over.shell.max_output_bytes.or(self.shell.max_output_bytes),
+            },
+            tools: ToolSettings {
+                allow_network: over.tools.allow_network.or(self.tools.allow_network),
+                allow_destructive: over
+                    .tools
+                    .allow_destructive
+                    .or(self.tools.allow_destructive),
+            },
+            keys: KeyBindings {
+                cancel: over.keys.cancel.or(self.keys.cancel),
+                clear: over.keys.clear.or(self.keys.clear),
+                newline: over.keys.newline.or(self.keys.newline),
+                search: over.keys.search.or(self.keys.search),
+            },
+            macros,
+            examples,
+            listen: over.listen.or(self.listen),
+            socket: over.socket.or(self.socket),
+            metrics_listen: over.metrics_listen.or(self.metrics_listen),
+            webhooks,
+            schedules,
+            telemetry: TelemetrySettings {
+                endpoint: over.telemetry.endpoint.or(self.telemetry.endpoint),
+                headers,
+            },
+            embeddings: EmbeddingSettings {
+                url: over.embeddings.url.or(self.embeddings.url),
+                model: over.embeddings.model.or(self.embeddings.model),
+                api_key_env: over.embeddings.api_key_env.or(self.embeddings.api_key_env),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, text: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_every_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "config.toml",
+            r#"
+model = "claude-haiku-4-5"
+account = "work"
+max_iterations = 30
+max_cost_per_task = 0.5
+edit_mode = "vi"
+notify_after = 120
+banner = "compact"
+socket = "run/golem.sock"
+event_log = true
+parse_retries = 3
+language = "he"
+
+[shell]
+mode = "read-write"
+work_dir = "src"
+confirm = false
+
+[tools]
+allow_destructive = false
+
+[keys]
+search = "ctrl-s"
+
+[macros]
+"/logs" = "show the last errors in {args}"
+
+[[examples]]
+task = "how full is the disk?"
+thought = "df reports usage"
+tool = "shell"
+args = { command = "df -h /" }
+observation = "40% /"
+answer = "40% used"
+
+[webhooks.alerts]
+task = "triage this alert: {{payload}}"
+secret_env = "ALERTS_SECRET"
+
+[schedules.logs]
+cron = "0 9 * * *"
+task = "summarize yesterday's logs"
+
+[telemetry]
+endpoint = "http://localhost:4318"
+headers = { x-api-key = "secret" }
+
+[embeddings]
+url = "http://localhost:11434/v1/embeddings"
+model = "nomic-embed-text"
+"#,
+        );
+
+        let settings = Settings::read(&path).unwrap();
+        assert_eq!(settings.model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(settings.account.as_deref(), Some("work"));
+        assert_eq!(settings.max_iterations, Some(30));
+        assert_eq!(settings.max_cost_per_task, Some(0.5));
+        assert_eq!(settings.edit_mode, Some(EditMode::Vi));
+        assert_eq!(settings.notify_after, Some(120));
+        assert_eq!(settings.banner, Some(BannerStyle::Compact));
+        assert_eq!(settings.socket, Some(dir.path().join("run/golem.sock")));
+        assert_eq!(settings.event_log, Some(true));
+        assert_eq!(settings.parse_retries, Some(3));
+        assert_eq!(settings.language.as_deref(), Some("he"));
+        assert_eq!(settings.shell.mode, Some(ShellMode::ReadWrite));
+        assert_eq!(settings.shell.work_dir, Some(dir.path().join("src")));
+        assert_eq!(settings.shell.confirm, Some(false));
+        assert_eq!(settings.tools.allow_destructive, Some(false));
+        assert!(settings.tools.allow_network.is_none());
+        assert_eq!(settings.keys.search, Some("ctrl-s".parse().unwrap()));
+        assert!(settings.keys.cancel.is_none());
+        assert_eq!(settings.macros["logs"], "show the last errors in {args}");
+        assert!(settings.timeout.is_none());
+        assert_eq!(settings.examples[0].tool.as_deref(), Some("shell"));
+        assert_eq!(settings.examples[0].args["command"], "df -h /");
+        assert_eq!(
+            settings.webhooks["alerts"].task,
+            "triage this alert: {{payload}}"
+        );
+        assert_eq!(
+            settings.webhooks["alerts"].secret_env.as_deref(),
+            Some("ALERTS_SECRET")
+        );
+        assert_eq!(settings.schedules["logs"].cron, "0 9 * * *");
+        assert_eq!(
+            settings.telemetry.endpoint.as_deref(),
+            Some("http://localhost:4318")
+        );
+        assert_eq!(settings.telemetry.headers["x-api-key"], "secret");
+        assert_eq!(
+            settings.embeddings.model.as_deref(),
+            Some("nomic-embed-text")
+        );
+        assert!(settings.embeddings.api_key_env.is_none());
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::read(&dir.path().join("nope.toml")).unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn typos_and_bad_values_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let typo = write(dir.path(), "a.toml", "max_iteration = 5\n");
+        let err = Settings::read(&typo).unwrap_err();
+        assert!(format!("{err:#}").contains("max_iteration"), "{err:#}");
+        let bad = write(dir.path(), "b.toml", "[shell]\nmode = \"yolo\"\n");
+        assert!(Settings::read(&bad).is_err());
+        let key = write(dir.path(), "c.toml", "[keys]\nclear = \"hyper-l\"\n");
+        let err = Settings::read(&key).unwrap_err();
+        assert!(format!("{err:#}").contains("hyper"), "{err:#}");
+        let name = write(dir.path(), "d.toml", "[macros]\n\"two words\" = \"x\"\n");
+        let err = Settings::read(&name).unwrap_err();
+        assert!(format!("{err:#}").contains("two words"), "{err:#}");
+        let unsigned = write(dir.path(), "e.toml", "[webhooks.a]\ntask = \"x\"\n");
+        let err = Settings::read(&unsigned).unwrap_err();
+        assert!(format!("{err:#}").contains("secret"), "{err:#}");
+        let cron = write(
+            dir.path(),
+            "f.toml",
+            "[schedules.a]\ncron = \"daily\"\ntask = \"x\"\n",
+        );
+        let err = Settings::read(&cron).unwrap_err();
+        assert!(format!("{err:#}").contains("cron"), "{err:#}");
+        let example = write(
+            dir.path(),
+            "g.toml",
+            "[[examples]]\ntask = \"x\"\nthought = \"y\"\ntool = \"shell\"\nanswer = \"z\"\n",
+        );
+        let err = Settings::read(&example).unwrap_err();
+        assert!(format!("{err:#}").contains("observation"), "{err:#}");
+        let embeddings = write(dir.path(), "h.toml", "[embeddings]\nmodel = \"x\"\n");
+        let err = Settings::read(&embeddings).unwrap_err();
+        assert!(format!("{err:#}").contains("url"), "{err:#}");
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let user = Settings {
+            model: Some("user".to_string()),
+            timeout: Some(10),
+            shell: ShellSettings {
+                confirm: Some(true),
+                ..ShellSettings::default()
+            },
+            macros: BTreeMap::from([
+                ("deploy".to_string(), "user deploy".to_string()),
+                ("logs".to_string(), "user logs".to_string()),
+            ]),
+            examples: vec![Example {
+                task: "user".to_string(),
+                ..Example::default()
+            }],
+            ..Settings::default()
+        };
+        let project = Settings {
+            model: Some("project".to_string()),
+            shell: ShellSettings {
+                mode: Some(ShellMode::ReadWrite),
+                ..ShellSettings::default()
+            },
+            macros: BTreeMap::from([("deploy".to_string(), "project deploy".to_string())]),
+            ..Settings::default()
+        };
+
+        let merged = user.merge(project);
+        assert_eq!(merged.model.as_deref(), Some("project"));
+        assert_eq!(merged.timeout, Some(10));
+        assert_eq!(merged.shell.mode, Some(ShellMode::ReadWrite));
+        assert_eq!(merged.shell.confirm, Some(true));
+        assert_eq!(merged.macros["deploy"], "project deploy");
+        assert_eq!(merged.macros["logs"], "user logs");
+        assert_eq!(merged.examples[0].task, "user");
+
+        let project = Settings {
+            examples: vec![Example {
+                task: "project".to_string(),
+                ..Example::default()
+            }],
+            ..Settings::default()
+        };
+        let merged = merged.merge(project);
+        assert_eq!(merged.examples.len(), 1);
+        assert_eq!(merged.examples[0].task, "project");
+    }
+}
@@ -0,0 +1,268 @@
+//! First-run setup.
+//!
+//! With no config file and no credentials, a new user would land in a REPL
+//! that can't reach a model. [`ask`] walks them through picking a provider,
+//! how to log in, the shell mode and a working directory; main acts on the
+//! answers and writes [`Setup::to_toml`] to `~/.golem/config.toml`.
+
+use anyhow::{Result, bail};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::tools::shell::ShellMode;
+
+/// How the user chose to authenticate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Login {
+    /// Claude Pro/Max through the browser, like `golem login`.
+    OAuth,
+    ApiKey(String),
+    /// Log in later.
+    Skip,
+}
+
+/// The answers to the setup questions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    /// `anthropic` or `human`.
+    pub provider: &'static str,
+    pub login: Login,
+    pub shell_mode: ShellMode,
+    /// `None` keeps the default scratch directory in the system temp dir.
+    pub work_dir: Option<PathBuf>,
+}
+
+impl Setup {
+    /// The config file holding these answers. Credentials are not part of
+    /// it; they go to the database.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::from("# Written by golem's first-run setup\n");
+        out.push_str(&format!("provider = {}\n", quote(self.provider)));
+        out.push_str("\n[shell]\n");
+        out.push_str(&format!("mode = {}\n", quote(self.shell_mode.label())));
+        if let Some(dir) = &self.work_dir {
+            out.push_str(&format!(
+                "work_dir = {}\n",
+                quote(&dir.display().to_string())
+            ));
+        }
+        out
+    }
+}
+
+/// `s` as a TOML string.
+fn quote(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+/// Ask the setup questions on `input`, writing them to `out`. Fails if
+/// `input` ends first, e.g. on Ctrl+D.
+pub fn ask(input: &mut impl BufRead, out: &mut impl Write) -> Result<Setup> {
+    let provider = match choose(
+        input,
+        out,
+        "Which model provider?",
+        &[
+            "Anthropic (Claude)",
+            "human — you answer as the model, for trying golem out",
+        ],
+    )? {
+        0 => "anthropic",
+        _ => "human",
+    };
+
+    let login = if provider == "anthropic" {
+        match choose(
+            input,
+            out,
+            "How do you want to log in?",
+            &[
+                "Claude Pro/Max account (opens the browser)",
+                "paste an API key",
+                "later, with `golem login` or ANTHROPIC_API_KEY",
+            ],
+        )? {
+            0 => Login::OAuth,
+            1 => Login::ApiKey(read_nonempty(input, out, "API key: ")?),
+            _ => Login::Skip,
+        }
+    } else {
+        Login::Skip
+    };
+
+    let shell_mode = match choose(
+        input,
+        out,
+        "What may the agent's shell commands do?",
+        &[
+            "only read (ls, cat, grep, …)",
+            "read and write — change files, install packages",
+        ],
+    )? {
+        0 => ShellMode::ReadOnly,
+        _ => ShellMode::ReadWrite,
+    };
+
+    let work_dir = loop {
+        let answer = read_answer(
+            input,
+            out,
+            "\nWorking directory for shell commands (empty: a scratch directory): ",
+        )?;
+        if answer.is_empty() {
+            break None;
+        }
+        match directory(&answer) {
+            Ok(dir) => break Some(dir),
+            Err(e) => writeln!(out, "  ✗ {e}")?,
+        }
+    };
+
+    Ok(Setup {
+        provider,
+        login,
+        shell_mode,
+        work_dir,
+    })
+}
+
+/// Ask `question` with numbered `options` until one is picked; Enter picks
+/// the first. Returns its index.
+fn choose(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    question: &str,
+    options: &[&str],
+) -> Result<usize> {
+    writeln!(out, "\n{question}")?;
+    for (i, option) in options.iter().enumerate() {
+        writeln!(out, "  {}) {option}", i + 1)?;
+    }
+    loop {
+        let answer = read_answer(input, out, &format!("choice [1-{}, 1]: ", options.len()))?;
+        if answer.is_empty() {
+            return Ok(0);
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => writeln!(out, "  pick a number from 1 to {}", options.len())?,
+        }
+    }
+}
+
+fn read_nonempty(input: &mut impl BufRead, out: &mut impl Write, prompt: &str) -> Result<String> {
+    loop {
+        let answer = read_answer(input, out, prompt)?;
+        if !answer.is_empty() {
+            return Ok(answer);
+        }
+    }
+}
+
+/// One trimmed line after `prompt`.
+fn read_answer(input: &mut impl BufRead, out: &mut impl Write, prompt: &str) -> Result<String> {
+    write!(out, "{prompt}")?;
+    out.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        bail!("setup cancelled");
+    }
+    Ok(line.trim().to_string())
+}
+
+/// `path` as an absolute directory, with `~` for the home directory.
+fn directory(path: &str) -> Result<PathBuf> {
+    let path = match path.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+            .unwrap_or_default()
+            .join(rest.trim_start_matches('/')),
+        _ => Path::new(path).to_path_buf(),
+    };
+    match path.canonicalize() {
+        Ok(dir) if dir.is_dir() => Ok(dir),
+        Ok(dir) => bail!("{} is not a directory", dir.display()),
+        Err(e) => bail!("{}: {e}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::Settings;
+    use std::io::Cursor;
+
+    fn run(answers: &str) -> (Result<Setup>, String) {
+        let mut out = Vec::new();
+        let setup = ask(&mut Cursor::new(answers.to_string()), &mut out);
+        (setup, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn enter_takes_the_defaults() {
+        let (setup, shown) = run("\n\n\n\n");
+        assert_eq!(
+            setup.unwrap(),
+            Setup {
+                provider: "anthropic",
+                login: Login::OAuth,
+                shell_mode: ShellMode::ReadOnly,
+                work_dir: None,
+            }
+        );
+        assert!(shown.contains("Which model provider?"), "{shown}");
+    }
+
+    #[test]
+    fn bad_answers_are_asked_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let answers = format!(
+            "1\n7\n2\n\nsk-ant-test\n2\n/no/such/dir\n{}\n",
+            dir.path().display()
+        );
+        let (setup, shown) = run(&answers);
+        assert_eq!(
+            setup.unwrap(),
+            Setup {
+                provider: "anthropic",
+                login: Login::ApiKey("sk-ant-test".to_string()),
+                shell_mode: ShellMode::ReadWrite,
+                work_dir: Some(dir.path().canonicalize().unwrap()),
+            }
+        );
+        assert!(shown.contains("pick a number from 1 to 3"), "{shown}");
+        assert!(shown.contains("/no/such/dir"), "{shown}");
+    }
+
+    #[test]
+    fn human_provider_skips_login() {
+        let (setup, shown) = run("2\n1\n\n");
+        assert_eq!(setup.unwrap().login, Login::Skip);
+        assert!(!shown.contains("log in"), "{shown}");
+    }
+
+    #[test]
+    fn end_of_input_cancels() {
+        assert!(run("1\n").0.is_err());
+    }
+
+    #[test]
+    fn written_config_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let setup = Setup {
+            provider: "anthropic",
+            login: Login::Skip,
+            shell_mode: ShellMode::ReadWrite,
+            work_dir: Some(PathBuf::from("/srv/my \"app\"")),
+        };
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, setup.to_toml()).unwrap();
+
+        let settings = Settings::read(&path).unwrap();
+        assert_eq!(settings.provider.as_deref(), Some("anthropic"));
+        assert_eq!(settings.shell.mode, Some(ShellMode::ReadWrite));
+        assert_eq!(
+            settings.shell.work_dir,
+            Some(PathBuf::from("/srv/my \"app\""))
+        );
+    }
+}
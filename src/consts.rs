@@ -1,6 +1,7 @@
 //! Project-wide constants.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 pub const AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 pub const HOMEPAGE: &str = env!("CARGO_PKG_HOMEPAGE");
@@ -9,9 +10,74 @@ pub const REPO: &str = env!("CARGO_PKG_REPOSITORY");
 /// Default Anthropic model when none is specified.
 pub const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
 
+/// Account that credentials are stored under until another is chosen.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// Named session that exists in every database and is active by default.
+pub const DEFAULT_SESSION_NAME: &str = "default";
+
 /// Maximum number of prior task summaries to include in session context.
 pub const DEFAULT_SESSION_HISTORY_LIMIT: usize = 50;
 
+/// Extra entries folded into a summary when session history overflows, so
+/// compaction runs once per batch of tasks instead of after every task.
+pub const SESSION_SUMMARY_BATCH: usize = 10;
+
+/// Maximum number of semantically similar memories added to a task's context.
+pub const SEMANTIC_RECALL_LIMIT: usize = 5;
+
+/// Minimum cosine similarity for a memory to count as relevant.
+pub const SEMANTIC_MIN_SCORE: f32 = 0.35;
+
+/// Maximum number of matches shown by `/recall`.
+pub const DEFAULT_RECALL_LIMIT: usize = 10;
+
+/// Number of past tasks listed by a bare `/history`.
+pub const DEFAULT_HISTORY_LIMIT: usize = 10;
+
+/// `--db` value that keeps memory in process instead of in SQLite.
+pub const NO_DATABASE: &str = "none";
+
+/// How long a database connection waits for another connection's lock.
+pub const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Idle read-only connections kept open per database for reuse.
+pub const SQLITE_READ_POOL_SIZE: usize = 4;
+
+/// Stored memory entries larger than this many bytes are zstd-compressed.
+pub const COMPRESSION_THRESHOLD: usize = 4096;
+
+/// Set (to anything) to make cassettes re-record over the network instead
+/// of replaying.
+pub const RECORD_CASSETTES_ENV: &str = "GOLEM_RECORD_CASSETTES";
+
+/// Anthropic list prices in USD per million input and output tokens,
+/// matched as substrings of the model name; the first match wins.
+pub const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("opus-4-5", 5.0, 25.0),
+    ("opus", 15.0, 75.0),
+    ("sonnet", 3.0, 15.0),
+    ("haiku-4-5", 1.0, 5.0),
+    ("3-haiku", 0.25, 1.25),
+    ("haiku", 0.8, 4.0),
+];
+
+/// Input tokens read from the prompt cache cost this fraction of the
+/// model's input price.
+pub const CACHE_READ_PRICE: f64 = 0.1;
+
+/// Address `golem serve` listens on unless told otherwise.
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8787";
+
+/// Where `golem daemon` listens and `golem attach` connects unless told
+/// otherwise: `~/.golem/daemon.sock`.
+pub fn daemon_socket_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("daemon.sock")
+}
+
 /// Default database path: `~/.golem/golem.db`.
 /// Single DB for memory, credentials, and config.
 pub fn default_db_path() -> PathBuf {
@@ -21,6 +87,56 @@ pub fn default_db_path() -> PathBuf {
         .join("golem.db")
 }
 
+/// User-wide settings file: `~/.golem/config.toml`.
+pub fn user_config_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("config.toml")
+}
+
+/// REPL input history: `~/.golem/history`.
+pub fn history_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("history")
+}
+
+/// Where credentials lived before the database: `~/.golem/auth.json`.
+/// Imported on the next run, see [`crate::auth::import_legacy`].
+pub fn legacy_auth_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("auth.json")
+}
+
+/// The user's own ReAct system prompt, used instead of the built-in one
+/// when it exists: `~/.golem/prompts/react.md`.
+pub fn react_prompt_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("prompts")
+        .join("react.md")
+}
+
+/// What long answers are shown in when `$PAGER` isn't set. `-R` keeps
+/// the colors of rendered markdown.
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Per-project settings file, looked up in the current directory.
+pub const PROJECT_CONFIG_FILE: &str = ".golem.toml";
+
+/// Directory for raw JSONL transcripts: `~/.golem/transcripts`.
+pub fn transcripts_dir() -> PathBuf {
+    dirs::home_dir()
+        .expect("cannot determine home directory")
+        .join(".golem")
+        .join("transcripts")
+}
+
 /// Format a number with comma separators (e.g. 1,234,567).
 pub fn format_number(n: u64) -> String {
     let s = n.to_string();
@@ -34,10 +150,81 @@ pub fn format_number(n: u64) -> String {
     result
 }
 
+/// Format a byte count with a binary unit (e.g. 1.5 KiB).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Format seconds since the Unix epoch as UTC `YYYY-MM-DD HH:MM:SS`,
+/// the format SQLite's `datetime('now')` stores.
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Bytes of tool output kept verbatim in the thinker's context; older
+/// iterations beyond it are shortened to one line each.
+pub const DEFAULT_OBSERVATION_BUDGET: usize = 64 * 1024;
+
+/// Characters of the first line kept when an old observation is shortened.
+pub const OBSERVATION_SUMMARY_WIDTH: usize = 120;
+
+/// Per-task entries read at a time when a task's history is loaded.
+pub const HISTORY_PAGE_SIZE: usize = 32;
+
+/// Once this few iterations are left, the model is told to wrap up.
+pub const WRAP_UP_ITERATIONS: usize = 3;
+
+/// The longest a failed model call is waited on before it is retried,
+/// however many retries came before.
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How long any HTTP request waits to connect before it fails.
+pub const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long `golem login` waits for the browser to come back.
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Characters of a command or error shown in a spinner's phase.
+pub const PHASE_DETAIL_CHARS: usize = 50;
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_utc(1_767_323_045), "2026-01-02 03:04:05");
+    }
+
     #[test]
     fn consts_are_non_empty() {
         assert!(!AUTHOR.is_empty());
@@ -82,4 +269,12 @@ mod tests {
     fn format_number_single_digit() {
         assert_eq!(format_number(1), "1");
     }
+
+    #[test]
+    fn format_bytes_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
 }
@@ -0,0 +1,111 @@
+//! `golem attach`: a REPL whose tasks run in `golem daemon`.
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use tokio::io::{BufReader, BufWriter};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+use super::protocol::{self, Reply, Request};
+use crate::banner::{one_line, print_answer_text};
+use crate::config::settings::Settings;
+use crate::consts::history_path;
+use crate::editor::{Input, LineEditor, ModelNames};
+use crate::prompt;
+
+/// Tool output is shown up to this many characters.
+const OUTPUT_CHARS: usize = 200;
+
+/// Connect to the daemon on `socket` and read tasks until `/quit`,
+/// Ctrl+D or Ctrl+C.
+pub async fn run(settings: &Settings, socket: &Path) -> Result<()> {
+    let stream = UnixStream::connect(socket).await.with_context(|| {
+        format!(
+            "no golem daemon on {}; start one with `golem daemon`",
+            socket.display()
+        )
+    })?;
+    let (reader, writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut writer = BufWriter::new(writer);
+    let Some(Reply::Hello {
+        pid,
+        session,
+        model,
+    }) = protocol::recv(&mut reader).await?
+    else {
+        bail!("{} didn't answer like a golem daemon", socket.display());
+    };
+    println!("attached to golem daemon {pid} — session {session}, model {model}");
+    println!("tasks run there one at a time; /quit or Ctrl+D detaches");
+
+    let mut editor = LineEditor::new(
+        settings.edit_mode.unwrap_or_default(),
+        &settings.keys,
+        Some(history_path()),
+        vec!["/quit".to_string()],
+        ModelNames::default(),
+    )?;
+    let prompt = format!("golem[{session}@daemon]> ");
+    loop {
+        println!();
+        let line = match tokio::task::block_in_place(|| editor.read_line(&prompt))? {
+            Input::Line(line) => line,
+            Input::Interrupted | Input::Eof => break,
+        };
+        let task = line.trim();
+        match task {
+            "" => continue,
+            "/quit" | "/exit" | "quit" | "exit" => break,
+            _ if task.starts_with('/') => {
+                println!("Only tasks run over attach; use golem directly for {task}.");
+                continue;
+            }
+            _ => {}
+        }
+        let request = Request::Task {
+            text: task.to_string(),
+        };
+        protocol::send(&mut writer, &request).await?;
+        if !follow(&mut reader, &mut writer).await? {
+            println!("the daemon has stopped");
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Show a task's progress until it ends, answering approval questions.
+/// Returns false if the daemon hung up.
+async fn follow(
+    reader: &mut BufReader<OwnedReadHalf>,
+    writer: &mut BufWriter<OwnedWriteHalf>,
+) -> Result<bool> {
+    loop {
+        let Some(reply) = protocol::recv::<Reply>(reader).await? else {
+            return Ok(false);
+        };
+        match reply {
+            Reply::Waiting => println!("waiting for another terminal's task to finish…"),
+            Reply::Thought { text } => println!("\nThought: {text}"),
+            Reply::Tool { tool, ok, output } => {
+                let mark = if ok { "✓" } else { "✗" };
+                println!("  [{tool}] {mark} {}", one_line(&output, OUTPUT_CHARS));
+            }
+            Reply::Approve { id, question } => {
+                let approved = prompt::terminal().confirm(&question).await?;
+                protocol::send(writer, &Request::Approval { id, approved }).await?;
+            }
+            Reply::Done { answer, stats } => {
+                print_answer_text(&answer);
+                println!("{stats}");
+                return Ok(true);
+            }
+            Reply::Failed { error } => {
+                eprintln!("\nerror: {error}");
+                return Ok(true);
+            }
+            Reply::Hello { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! `golem daemon` and `golem attach`: one long-lived engine that many
+//! terminals share over a unix socket.
+//!
+//! The daemon builds its engine once — thinker, credentials, memory and
+//! session stay warm — and listens on the socket. Each `golem attach`
+//! ([`attach`]) is a REPL whose tasks run on that engine, one at a time,
+//! on the daemon's session, so every attached terminal sees the same
+//! history. Thoughts, tool calls and approval questions go to the terminal
+//! whose task is running ([`Relay`]); see [`protocol`] for the messages.
+//! With `metrics_listen` set, it serves Prometheus metrics over HTTP too.
+
+pub mod attach;
+pub mod protocol;
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+use crate::banner::format_task_result;
+use crate::builder::GolemBuilder;
+use crate::chat::Approvals;
+use crate::config::settings::Settings;
+use crate::engine::Engine;
+use crate::engine::hooks::Hook;
+use crate::engine::react::ReactEngine;
+use crate::telemetry::prometheus;
+use crate::thinker::{Step, ToolCall};
+use crate::tools::{Approver, Outcome, ToolResult, format_args};
+use protocol::{Reply, Request};
+
+/// What every connection shares.
+struct Daemon {
+    engine: tokio::sync::Mutex<ReactEngine>,
+    relay: Relay,
+    session: String,
+    model: String,
+}
+
+/// Serve terminals on `socket` until Ctrl+C.
+pub async fn run(settings: Settings, socket: &Path) -> Result<()> {
+    let relay = Relay::default();
+    let metrics_listen = settings.metrics_listen.clone();
+    let golem = GolemBuilder::new(settings)
+        .echo(false)
+        .approver(Box::new(relay.clone()))
+        .hook(Arc::new(relay.clone()))
+        .build()
+        .await?;
+    if let Some(addr) = metrics_listen {
+        let metrics = TcpListener::bind(&addr)
+            .await
+            .with_context(|| format!("failed to listen on {addr}"))?;
+        println!("serving metrics on http://{addr}/metrics");
+        tokio::spawn(prometheus::serve(metrics, Arc::clone(&golem.prometheus)));
+    }
+    let listener = bind(socket).await?;
+    let daemon = Arc::new(Daemon {
+        session: golem.engine.active_session().await?,
+        engine: tokio::sync::Mutex::new(golem.engine),
+        relay,
+        model: golem.model,
+    });
+    println!(
+        "golem daemon listening on {} (pid {}); `golem attach` to use it",
+        socket.display(),
+        std::process::id()
+    );
+    let served = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(Arc::clone(&daemon), stream));
+                }
+                Err(e) => break Err(e.into()),
+            },
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+    let _ = std::fs::remove_file(socket);
+    served
+}
+
+/// Listen on `socket`, readable by this user only. A socket file nobody
+/// answers on is left over from a daemon that died, and is replaced.
+async fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            bail!(
+                "a golem daemon is already listening on {}",
+                socket.display()
+            );
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("failed to remove stale {}", socket.display()))?;
+    }
+    let dir = match socket.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+    // Bound inside a directory only this user can enter, and moved into
+    // place once its own mode is set, so nobody else can connect before
+    let staging = dir.join(format!(".golem-{:08x}", rand::random::<u32>()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("failed to create {}", staging.display()))?;
+    let bound = bind_in(&staging, socket);
+    let _ = std::fs::remove_dir_all(&staging);
+    bound
+}
+
+/// Bind a socket in the private directory `staging`, then move it to
+/// `socket`.
+fn bind_in(staging: &Path, socket: &Path) -> Result<UnixListener> {
+    let staged = staging.join("sock");
+    let listener = UnixListener::bind(&staged)
+        .with_context(|| format!("failed to listen on {}", socket.display()))?;
+    // Whoever can connect can run commands as this user
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+    std::fs::rename(&staged, socket)
+        .with_context(|| format!("failed to listen on {}", socket.display()))?;
+    Ok(listener)
+}
+
+/// Handle one terminal until it hangs up.
+async fn serve(daemon: Arc<Daemon>, stream: UnixStream) {
+    let (reader, mut writer) = stream.into_split();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let writing = tokio::spawn(async move {
+        while let Some(reply) = rx.recv().await {
+            if protocol::send(&mut writer, &reply).await.is_err() {
+                break;
+            }
+        }
+    });
+    let _ = tx.send(Reply::Hello {
+        pid: std::process::id(),
+        session: daemon.session.clone(),
+        model: daemon.model.clone(),
+    });
+    let mut reader = BufReader::new(reader);
+    while let Ok(Some(request)) = protocol::recv::<Request>(&mut reader).await {
+        match request {
+            // Run apart, so approvals can still be read meanwhile
+            Request::Task { text } => {
+                tokio::spawn(run_task(Arc::clone(&daemon), tx.clone(), text));
+            }
+            Request::Approval { id, approved } => {
+                daemon.relay.approvals.settle(&id, approved);
+            }
+        }
+    }
+    drop(tx);
+    let _ = writing.await;
+}
+
+/// Run `task` once the engine is free, reporting to `tx`.
+async fn run_task(daemon: Arc<Daemon>, tx: mpsc::UnboundedSender<Reply>, task: String) {
+    let mut engine = match daemon.engine.try_lock() {
+        Ok(engine) => engine,
+        Err(_) => {
+            let _ = tx.send(Reply::Waiting);
+            daemon.engine.lock().await
+        }
+    };
+    daemon.relay.attach(Some(tx.clone()));
+    let result = engine.run(&task).await;
+    daemon.relay.attach(None);
+    let reply = match result {
+        Ok(result) => Reply::Done {
+            stats: format_task_result(&result),
+            answer: result.answer,
+        },
+        Err(e) => Reply::Failed {
+            error: format!("{e:#}"),
+        },
+    };
+    let _ = tx.send(reply);
+}
+
+/// Sends the running task's progress and approval questions to the
+/// terminal that gave the task.
+#[derive(Clone, Default)]
+struct Relay {
+    current: Arc<Mutex<Option<mpsc::UnboundedSender<Reply>>>>,
+    approvals: Arc<Approvals>,
+}
+
+impl Relay {
+    fn attach(&self, terminal: Option<mpsc::UnboundedSender<Reply>>) {
+        *self.current.lock().expect("relay lock poisoned") = terminal;
+    }
+
+    /// Send `reply` to the current terminal; false if it has gone.
+    fn send(&self, reply: Reply) -> bool {
+        let current = self.current.lock().expect("relay lock poisoned");
+        current.as_ref().is_some_and(|tx| tx.send(reply).is_ok())
+    }
+}
+
+#[async_trait]
+impl Hook for Relay {
+    async fn on_step(&self, step: &Step) {
+        if let Step::Act { thought, .. } = step
+            && !thought.is_empty()
+        {
+            self.send(Reply::Thought {
+                text: thought.clone(),
+            });
+        }
+    }
+
+    async fn on_tool_result(&self, _call: &ToolCall, result: &ToolResult) {
+        let (ok, output) = match &result.outcome {
+            Outcome::Success(output) => (true, output.clone()),
+            Outcome::Error(error) => (false, error.clone()),
+        };
+        self.send(Reply::Tool {
+            tool: result.tool.clone(),
+            ok,
+            output,
+        });
+    }
+}
+
+#[async_trait]
+impl Approver for Relay {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        let (id, answer) = self.approvals.open();
+        let question = format!("  Run {tool} {}? [y/N] ", format_args(args));
+        if !self.send(Reply::Approve {
+            id: id.clone(),
+            question,
+        }) {
+            self.approvals.settle(&id, false);
+            bail!("the terminal that gave this task has detached; nobody can confirm {tool}");
+        }
+        self.approvals.wait(&id, answer, tool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn binds_once_and_replaces_stale_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("run").join("daemon.sock");
+        let listener = bind(&socket).await.unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // The directory it was bound in is gone
+        let entries = std::fs::read_dir(socket.parent().unwrap()).unwrap();
+        assert_eq!(entries.count(), 1);
+        assert!(UnixStream::connect(&socket).await.is_ok());
+        let err = bind(&socket).await.unwrap_err();
+        assert!(err.to_string().contains("already listening"), "{err}");
+        // Nobody answers once the listener is gone
+        drop(listener);
+        assert!(bind(&socket).await.is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+//! What `golem attach` and `golem daemon` say to each other: one JSON
+//! object per line, tagged with its `type`.
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// From a terminal to the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Run a task on the shared engine.
+    Task { text: String },
+    /// Answer approval request `id`.
+    Approval { id: String, approved: bool },
+}
+
+/// From the daemon to a terminal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    /// Sent once, on connecting.
+    Hello {
+        pid: u32,
+        session: String,
+        model: String,
+    },
+    /// Another terminal's task is running; this one starts after it.
+    Waiting,
+    /// The model's thought before its tool calls.
+    Thought { text: String },
+    /// A tool call finished.
+    Tool {
+        tool: String,
+        ok: bool,
+        output: String,
+    },
+    /// A call needs confirming; answer with [`Request::Approval`].
+    Approve { id: String, question: String },
+    /// The task finished; `stats` is what `format_task_result` shows.
+    Done { answer: String, stats: String },
+    /// The task failed.
+    Failed { error: String },
+}
+
+/// Write `message` as one line.
+pub async fn send(writer: &mut (impl AsyncWrite + Unpin), message: &impl Serialize) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read the next message; `None` once the other side has hung up.
+pub async fn recv<T: DeserializeOwned>(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn messages_are_tagged_lines() {
+        let mut out = Vec::new();
+        send(
+            &mut out,
+            &Request::Task {
+                text: "df -h".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        send(&mut out, &Reply::Waiting).await.unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "{\"type\":\"task\",\"text\":\"df -h\"}\n{\"type\":\"waiting\"}\n"
+        );
+
+        let mut reader = BufReader::new(&out[..]);
+        let request: Option<Request> = recv(&mut reader).await.unwrap();
+        assert_eq!(
+            request,
+            Some(Request::Task {
+                text: "df -h".to_string()
+            })
+        );
+        let reply: Option<Reply> = recv(&mut reader).await.unwrap();
+        assert_eq!(reply, Some(Reply::Waiting));
+        assert_eq!(recv::<Reply>(&mut reader).await.unwrap(), None);
+    }
+}
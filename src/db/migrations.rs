@@ -0,0 +1,456 @@
+//! Versioned schema migrations for the shared database.
+//!
+//! `PRAGMA user_version` holds the number of migrations already applied.
+//! [`migrate`] runs the rest, in order, in one transaction. Every table in
+//! the file — memory, config, credentials, facts, metrics, schedules — is
+//! defined here, so a database is fully migrated whichever store opens it
+//! first.
+//!
+//! Never edit or reorder a migration that has shipped; append a new one.
+
+use anyhow::{Context, Result, bail};
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::consts::DEFAULT_SESSION_NAME;
+use crate::memory::{MemoryEntry, vector_index};
+
+/// One schema change.
+pub struct Migration {
+    pub description: &'static str,
+    pub apply: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations, oldest first. Migration `n` (1-based) leaves the
+/// database at `user_version = n`.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "baseline schema",
+        apply: baseline,
+    },
+    Migration {
+        description: "index session history by time",
+        apply: index_session_timestamps,
+    },
+    Migration {
+        description: "record the task and cache reads with task metrics",
+        apply: task_metrics_detail,
+    },
+    Migration {
+        description: "several named accounts per provider",
+        apply: credential_accounts,
+    },
+    Migration {
+        description: "scheduled tasks and their runs",
+        apply: schedules,
+    },
+    Migration {
+        description: "event log",
+        apply: event_log,
+    },
+    Migration {
+        description: "nearest-neighbour index over embeddings",
+        apply: embedding_indexes,
+    },
+    Migration {
+        description: "file full-text rows under their session",
+        apply: session_fts,
+    },
+];
+
+/// The schema version this build writes.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+/// The database's current schema version.
+pub fn version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Apply every migration newer than the database's `user_version`.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    run(conn, MIGRATIONS)
+}
+
+fn run(conn: &mut Connection, migrations: &[Migration]) -> Result<()> {
+    let latest = migrations.len() as u32;
+    if version(conn)? == latest {
+        return Ok(());
+    }
+
+    // Take the write lock before re-reading the version, so two
+    // connections opening the same file don't both migrate it
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let current = version(&tx)?;
+    if current > latest {
+        bail!(
+            "database schema version {current} is newer than this golem supports ({latest}); upgrade golem"
+        );
+    }
+    for (i, migration) in migrations.iter().enumerate().skip(current as usize) {
+        (migration.apply)(&tx)
+            .with_context(|| format!("migration {} ({}) failed", i + 1, migration.description))?;
+    }
+    tx.pragma_update(None, "user_version", latest)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Everything that existed before versioned migrations. Databases from
+/// that era have `user_version = 0` and any subset of these tables and
+/// columns, so every step here is idempotent.
+fn baseline(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memory (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+            entry TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS session_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+            task TEXT NOT NULL,
+            answer TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS embeddings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            task TEXT NOT NULL,
+            text TEXT NOT NULL,
+            model TEXT NOT NULL,
+            vector BLOB NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS config (
+            key   TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS credentials (
+            provider TEXT PRIMARY KEY,
+            data     TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS facts (
+            id         INTEGER PRIMARY KEY AUTOINCREMENT,
+            text       TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS task_metrics (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp     TEXT NOT NULL DEFAULT (datetime('now')),
+            model         TEXT NOT NULL,
+            duration_ms   INTEGER NOT NULL,
+            iterations    INTEGER NOT NULL,
+            tool_calls    INTEGER NOT NULL,
+            input_tokens  INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            success       INTEGER NOT NULL,
+            error         TEXT
+        );",
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO sessions (id, name) VALUES (1, ?1)",
+        [DEFAULT_SESSION_NAME],
+    )?;
+    // Tables created before named sessions existed belong to the default
+    for table in ["session_history", "embeddings"] {
+        add_column_if_missing(conn, table, "session_id", "INTEGER NOT NULL DEFAULT 1")?;
+    }
+    add_column_if_missing(conn, "session_history", "entries", "TEXT")?;
+    // Entries stored before project scoping stay global
+    for table in ["session_history", "embeddings"] {
+        add_column_if_missing(conn, table, "project", "TEXT")?;
+    }
+    for column in ["input_tokens", "output_tokens"] {
+        add_column_if_missing(
+            conn,
+            "session_history",
+            column,
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+    }
+    init_fts(conn)
+}
+
+/// Lets `/search` narrow by time range without scanning the whole history.
+fn index_session_timestamps(conn: &Connection) -> Result<()> {
+    conn.execute_batch("CREATE INDEX session_history_timestamp ON session_history (timestamp)")?;
+    Ok(())
+}
+
+/// Lets `/tokens` list recent tasks by name and show cache hits. Older
+/// rows keep no task text and zero cache reads.
+fn task_metrics_detail(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE task_metrics ADD COLUMN task TEXT;
+         ALTER TABLE task_metrics ADD COLUMN cache_read_tokens INTEGER NOT NULL DEFAULT 0;",
+    )?;
+    Ok(())
+}
+
+/// Key credentials by provider and account name; what was stored becomes
+/// each provider's `default` account. `active_accounts` remembers which
+/// account a provider uses, `default` when it has no row.
+fn credential_accounts(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE credentials_by_account (
+            provider TEXT NOT NULL,
+            account  TEXT NOT NULL DEFAULT 'default',
+            data     TEXT NOT NULL,
+            PRIMARY KEY (provider, account)
+         );
+         INSERT INTO credentials_by_account (provider, data)
+            SELECT provider, data FROM credentials;
+         DROP TABLE credentials;
+         ALTER TABLE credentials_by_account RENAME TO credentials;
+         CREATE TABLE active_accounts (
+            provider TEXT PRIMARY KEY,
+            account  TEXT NOT NULL
+         );",
+    )?;
+    Ok(())
+}
+
+/// `golem schedule add` keeps schedules in `schedules`; `golem serve`
+/// records each run's outcome in `schedule_runs` under the schedule's id,
+/// which may also name a schedule from a config file.
+fn schedules(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE schedules (
+            id         TEXT PRIMARY KEY,
+            cron       TEXT NOT NULL,
+            task       TEXT NOT NULL,
+            notify     TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+         );
+         CREATE TABLE schedule_runs (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            schedule    TEXT NOT NULL,
+            due         TEXT NOT NULL,
+            finished_at TEXT NOT NULL DEFAULT (datetime('now')),
+            status      TEXT NOT NULL,
+            answer      TEXT,
+            error       TEXT
+         );
+         CREATE INDEX schedule_runs_schedule ON schedule_runs (schedule, id);",
+    )?;
+    Ok(())
+}
+
+/// With `event_log` on, every event on the bus is kept in `events`, by
+/// session, as its kind and a JSON object.
+fn event_log(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE events (
+            id        INTEGER PRIMARY KEY AUTOINCREMENT,
+            session   TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            kind      TEXT NOT NULL,
+            data      TEXT NOT NULL
+         );
+         CREATE INDEX events_session ON events (session, id);",
+    )?;
+    Ok(())
+}
+
+/// Semantic recall searches a sqlite-vec index per embedder instead of
+/// comparing every stored vector; build them for what's already stored.
+fn embedding_indexes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE embedding_indexes (
+            id    INTEGER PRIMARY KEY AUTOINCREMENT,
+            model TEXT NOT NULL UNIQUE,
+            dims  INTEGER NOT NULL
+         );",
+    )?;
+    vector_index::build(conn)
+}
+
+/// `/new` drops a session's full-text rows along with its history, so
+/// rows record the session they were stored in. Earlier rows predate
+/// that and stay under none.
+fn session_fts(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE memory_fts_new USING fts5(
+            text,
+            entry UNINDEXED,
+            session_id UNINDEXED,
+            project UNINDEXED,
+            tokenize = 'porter unicode61'
+         );
+         INSERT INTO memory_fts_new (rowid, text, entry)
+            SELECT rowid, text, entry FROM memory_fts;
+         DROP TABLE memory_fts;
+         ALTER TABLE memory_fts_new RENAME TO memory_fts;",
+    )?;
+    Ok(())
+}
+
+/// Create the full-text index and backfill it from any existing entries.
+fn init_fts(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'memory_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE memory_fts USING fts5(
+            text,
+            entry UNINDEXED,
+            tokenize = 'porter unicode61'
+        );",
+    )?;
+    let mut stmt = conn.prepare("SELECT entry FROM memory ORDER BY id ASC")?;
+    let jsons = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for json in jsons {
+        let entry: MemoryEntry = serde_json::from_str(&json)?;
+        conn.execute(
+            "INSERT INTO memory_fts (text, entry) VALUES (?1, ?2)",
+            [&entry.text(), &json],
+        )?;
+    }
+    Ok(())
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1)"),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_things(conn: &Connection) -> Result<()> {
+        conn.execute_batch("CREATE TABLE things (id INTEGER PRIMARY KEY)")?;
+        Ok(())
+    }
+
+    fn add_name(conn: &Connection) -> Result<()> {
+        conn.execute_batch("ALTER TABLE things ADD COLUMN name TEXT")?;
+        Ok(())
+    }
+
+    fn fail(_conn: &Connection) -> Result<()> {
+        bail!("boom")
+    }
+
+    const TWO: &[Migration] = &[
+        Migration {
+            description: "things",
+            apply: create_things,
+        },
+        Migration {
+            description: "thing names",
+            apply: add_name,
+        },
+    ];
+
+    #[test]
+    fn fresh_database_reaches_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(version(&conn).unwrap(), latest_version());
+        // Running again is a no-op
+        migrate(&mut conn).unwrap();
+        assert_eq!(version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn applies_only_pending_migrations() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn, &TWO[..1]).unwrap();
+        assert_eq!(version(&conn).unwrap(), 1);
+        // Re-running migration 1 would fail: the table already exists
+        run(&mut conn, TWO).unwrap();
+        assert_eq!(version(&conn).unwrap(), 2);
+        conn.execute("INSERT INTO things (name) VALUES ('x')", [])
+            .unwrap();
+    }
+
+    #[test]
+    fn failed_migration_rolls_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            Migration {
+                description: "things",
+                apply: create_things,
+            },
+            Migration {
+                description: "broken",
+                apply: fail,
+            },
+        ];
+        let err = run(&mut conn, &migrations).unwrap_err();
+        assert!(format!("{err:#}").contains("migration 2 (broken) failed"));
+        assert_eq!(version(&conn).unwrap(), 0);
+        assert!(conn.prepare("SELECT * FROM things").is_err());
+    }
+
+    #[test]
+    fn stored_credentials_become_default_accounts() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn, &MIGRATIONS[..3]).unwrap();
+        conn.execute(
+            "INSERT INTO credentials (provider, data) VALUES ('anthropic', '{}')",
+            [],
+        )
+        .unwrap();
+        migrate(&mut conn).unwrap();
+        let account: String = conn
+            .query_row(
+                "SELECT account FROM credentials WHERE provider = 'anthropic'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(account, "default");
+    }
+
+    #[test]
+    fn stored_embeddings_are_indexed() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        vector_index::register();
+        run(&mut conn, &MIGRATIONS[..6]).unwrap();
+        let vector = crate::memory::embedding::to_blob(&[1.0, 0.0]);
+        conn.execute(
+            "INSERT INTO embeddings (source, task, text, model, vector, project)
+             VALUES ('session', 'old', 'answer', 'm', ?1, NULL)",
+            [&vector],
+        )
+        .unwrap();
+        migrate(&mut conn).unwrap();
+
+        let hits = vector_index::nearest(&conn, "m", 1, Some("/repo"), &[1.0, 0.0], 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].1 - 1.0).abs() < 1e-6);
+        assert!(
+            vector_index::nearest(&conn, "other", 1, None, &[1.0, 0.0], 5)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn rejects_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+        let err = migrate(&mut conn).unwrap_err();
+        assert!(err.to_string().contains("newer than this golem supports"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Shared SQLite connection setup.
+//!
+//! Memory, config, credentials, facts, and metrics each open their own
+//! connection to the same file, so every one of them goes through [`open`],
+//! which also brings the schema up to date. Memory reads through a
+//! [`ReadPool`] besides, so reads don't queue behind its writes.
+
+pub mod migrations;
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::sync::Mutex;
+
+use crate::consts::{SQLITE_BUSY_TIMEOUT, SQLITE_READ_POOL_SIZE};
+use crate::memory::vector_index;
+
+/// Open `path` in WAL mode, so readers don't block the writer, and with a
+/// busy timeout, so a connection waits for another one's write instead of
+/// failing with `database is locked`. Pending migrations are applied.
+/// This and every later connection can use sqlite-vec.
+pub fn open(path: &str) -> Result<Connection> {
+    vector_index::register();
+    let mut conn =
+        Connection::open(path).with_context(|| format!("failed to open database {path}"))?;
+    conn.busy_timeout(SQLITE_BUSY_TIMEOUT)?;
+    // In-memory databases stay in "memory" journal mode; that's fine
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+    migrations::migrate(&mut conn)?;
+    Ok(conn)
+}
+
+/// Read-only connections to a database file that [`open`] has set up,
+/// made as needed and kept for reuse. Under WAL they read side by side,
+/// and alongside the one connection that writes.
+pub struct ReadPool {
+    path: String,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl ReadPool {
+    /// A pool for `path`, or `None` for an in-memory database, which
+    /// other connections can't see.
+    pub fn new(path: &str) -> Option<Self> {
+        (path != ":memory:").then(|| Self {
+            path: path.to_string(),
+            idle: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Run `f` on an idle connection, or a new one if all are busy.
+    pub fn with<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let idle = self.idle.lock().expect("read pool lock poisoned").pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => {
+                let conn = Connection::open_with_flags(
+                    &self.path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .with_context(|| format!("failed to open database {}", self.path))?;
+                conn.busy_timeout(SQLITE_BUSY_TIMEOUT)?;
+                conn
+            }
+        };
+        let result = f(&conn);
+        let mut idle = self.idle.lock().expect("read pool lock poisoned");
+        if idle.len() < SQLITE_READ_POOL_SIZE {
+            idle.push(conn);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_database_uses_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golem.db");
+        let conn = open(path.to_str().unwrap()).unwrap();
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        let timeout: i64 = conn
+            .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timeout as u128, SQLITE_BUSY_TIMEOUT.as_millis());
+    }
+
+    #[test]
+    fn opens_in_memory() {
+        assert!(open(":memory:").is_ok());
+        assert!(ReadPool::new(":memory:").is_none());
+    }
+
+    #[test]
+    fn readers_see_writes_and_stay_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golem.db");
+        let path = path.to_str().unwrap();
+        let writer = open(path).unwrap();
+        let pool = ReadPool::new(path).unwrap();
+
+        writer
+            .execute(
+                "INSERT INTO config (key, value) VALUES ('model', 'opus')",
+                [],
+            )
+            .unwrap();
+        // Two at once: the inner read can't reuse the outer's connection
+        let model: String = pool
+            .with(|outer| {
+                let inner: String = pool.with(|conn| {
+                    Ok(conn.query_row("SELECT value FROM config", [], |row| row.get(0))?)
+                })?;
+                let outer: String =
+                    outer.query_row("SELECT value FROM config", [], |row| row.get(0))?;
+                assert_eq!(inner, outer);
+                Ok(outer)
+            })
+            .unwrap();
+        assert_eq!(model, "opus");
+        assert_eq!(pool.idle.lock().unwrap().len(), 2);
+
+        let write = pool.with(|conn| Ok(conn.execute("DELETE FROM config", [])?));
+        assert!(write.is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! The parts of Discord's HTTP API and gateway the bot uses.
+//!
+//! The bot connects to the gateway with no intents: slash commands and
+//! button clicks arrive as `INTERACTION_CREATE` whatever the intents, and
+//! the bot reads no messages.
+
+use anyhow::{Context, Result, bail};
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use crate::thinker::http::shared_client;
+
+const API_URL: &str = "https://discord.com/api/v10";
+
+/// The slash command tasks are given with.
+pub const COMMAND: &str = "golem";
+
+/// How often a rate-limited request is retried.
+const RATE_LIMIT_RETRIES: usize = 3;
+
+const APPROVE_PREFIX: &str = "golem_approve:";
+const DENY_PREFIX: &str = "golem_deny:";
+
+/// An HTTP API client for a bot token.
+pub struct DiscordApi {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl DiscordApi {
+    pub fn new(token: String) -> Self {
+        Self {
+            client: shared_client(),
+            token,
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// The gateway's WebSocket URL, with the API version and encoding.
+    pub async fn gateway_url(&self) -> Result<String> {
+        let data = self.call(Method::GET, "/gateway/bot", None).await?;
+        let url = data["url"]
+            .as_str()
+            .context("/gateway/bot returned no url")?;
+        Ok(format!("{url}/?v=10&encoding=json"))
+    }
+
+    /// Create `/golem`, or update it if the application has it already.
+    pub async fn register_command(&self, application_id: &str) -> Result<()> {
+        let command = json!({
+            "name": COMMAND,
+            "description": "Give golem a task",
+            "options": [{
+                "type": 3,
+                "name": "task",
+                "description": "What golem should do",
+                "required": true,
+            }],
+        });
+        let path = format!("/applications/{application_id}/commands");
+        self.call(Method::POST, &path, Some(command)).await?;
+        Ok(())
+    }
+
+    /// Answer an interaction within the three seconds Discord allows.
+    pub async fn respond(&self, interaction: &Interaction, response: Value) -> Result<()> {
+        let path = format!(
+            "/interactions/{}/{}/callback",
+            interaction.id, interaction.token
+        );
+        self.call(Method::POST, &path, Some(response)).await?;
+        Ok(())
+    }
+
+    /// Replace the content of an interaction's response. Works for 15
+    /// minutes after the interaction.
+    pub async fn edit_response(&self, interaction: &Interaction, content: &str) -> Result<()> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/@original",
+            interaction.application_id, interaction.token
+        );
+        let body = json!({ "content": content });
+        self.call(Method::PATCH, &path, Some(body)).await?;
+        Ok(())
+    }
+
+    /// Post `content` to `channel`, with message `components` if given.
+    pub async fn create_message(
+        &self,
+        channel: &str,
+        content: &str,
+        components: Option<Value>,
+    ) -> Result<()> {
+        let mut body = json!({ "content": content });
+        if let Some(components) = components {
+            body["components"] = components;
+        }
+        let path = format!("/channels/{channel}/messages");
+        self.call(Method::POST, &path, Some(body)).await?;
+        Ok(())
+    }
+
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .client
+                .request(method.clone(), format!("{API_URL}{path}"))
+                .header("Authorization", format!("Bot {}", self.token));
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            let resp = request
+                .send()
+                .await
+                .with_context(|| format!("{method} {path} failed"))?;
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            let data: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+            if status == StatusCode::TOO_MANY_REQUESTS && attempt < RATE_LIMIT_RETRIES {
+                let wait = data["retry_after"].as_f64().unwrap_or(1.0);
+                tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+                attempt += 1;
+                continue;
+            }
+            if !status.is_success() {
+                let message = data["message"].as_str().unwrap_or(&text);
+                bail!("{method} {path} failed ({status}): {message}");
+            }
+            return Ok(data);
+        }
+    }
+}
+
+/// A gateway message.
+#[derive(Debug, Deserialize)]
+pub struct Payload {
+    pub op: u8,
+    #[serde(default)]
+    pub d: Value,
+    /// The sequence number heartbeats report back.
+    pub s: Option<u64>,
+    /// The event name, for dispatches.
+    pub t: Option<String>,
+}
+
+/// An interaction to answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub id: String,
+    pub token: String,
+    pub application_id: String,
+    pub channel: String,
+    pub user: String,
+}
+
+/// What a gateway message asks of the bot.
+#[derive(Debug, PartialEq)]
+pub enum Incoming {
+    /// Start heartbeating at this interval, and identify.
+    Hello(Duration),
+    /// Send a heartbeat now.
+    Heartbeat,
+    /// The session is ready; the application's id.
+    Ready(String),
+    /// Someone ran `/golem <task>`.
+    Task(Interaction, String),
+    /// Someone clicked an approval button: the request's id, and whether
+    /// it was Approve.
+    Approval(Interaction, String, bool),
+    /// The gateway wants a new connection.
+    Reconnect,
+    /// Anything else.
+    Other,
+}
+
+impl Payload {
+    pub fn incoming(&self) -> Incoming {
+        match (self.op, self.t.as_deref()) {
+            (10, _) => match self.d["heartbeat_interval"].as_u64() {
+                Some(ms) => Incoming::Hello(Duration::from_millis(ms)),
+                None => Incoming::Other,
+            },
+            (1, _) => Incoming::Heartbeat,
+            (7 | 9, _) => Incoming::Reconnect,
+            (0, Some("READY")) => match self.d["application"]["id"].as_str() {
+                Some(id) => Incoming::Ready(id.to_string()),
+                None => Incoming::Other,
+            },
+            (0, Some("INTERACTION_CREATE")) => self.interaction().unwrap_or(Incoming::Other),
+            _ => Incoming::Other,
+        }
+    }
+
+    fn interaction(&self) -> Option<Incoming> {
+        let d = &self.d;
+        let field = |value: &Value| value.as_str().map(str::to_string);
+        let interaction = Interaction {
+            id: field(&d["id"])?,
+            token: field(&d["token"])?,
+            application_id: field(&d["application_id"])?,
+            channel: field(&d["channel_id"])?,
+            // In a server the user is under `member`, in a DM it isn't
+            user: field(&d["member"]["user"]["id"]).or_else(|| field(&d["user"]["id"]))?,
+        };
+        match d["type"].as_u64()? {
+            // An application command
+            2 if d["data"]["name"] == COMMAND => {
+                let task = d["data"]["options"]
+                    .as_array()?
+                    .iter()
+                    .find(|option| option["name"] == "task")?;
+                Some(Incoming::Task(interaction, field(&task["value"])?))
+            }
+            // A button click
+            3 => {
+                let custom_id = d["data"]["custom_id"].as_str()?;
+                if let Some(id) = custom_id.strip_prefix(APPROVE_PREFIX) {
+                    Some(Incoming::Approval(interaction, id.to_string(), true))
+                } else {
+                    let id = custom_id.strip_prefix(DENY_PREFIX)?;
+                    Some(Incoming::Approval(interaction, id.to_string(), false))
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The identify message, with no intents.
+pub fn identify(token: &str) -> String {
+    json!({
+        "op": 2,
+        "d": {
+            "token": token,
+            "intents": 0,
+            "properties": {
+                "os": std::env::consts::OS,
+                "browser": "golem",
+                "device": "golem",
+            },
+        },
+    })
+    .to_string()
+}
+
+/// A heartbeat carrying the last sequence number seen.
+pub fn heartbeat(seq: Option<u64>) -> String {
+    json!({ "op": 1, "d": seq }).to_string()
+}
+
+/// The response that acknowledges a command and shows "thinking…" until
+/// [`DiscordApi::edit_response`] replaces it.
+pub fn deferred() -> Value {
+    json!({ "type": 5 })
+}
+
+/// The response to a button click that replaces the clicked message with
+/// `content`, without its buttons.
+pub fn update_message(content: &str) -> Value {
+    json!({ "type": 7, "data": { "content": content, "components": [] } })
+}
+
+/// Approve and Deny buttons that answer approval `id`.
+pub fn approval_components(id: &str) -> Value {
+    let button = |label: &str, style: u8, prefix: &str| json!({ "type": 2, "style": style, "label": label, "custom_id": format!("{prefix}{id}") });
+    json!([{
+        "type": 1,
+        "components": [button("Approve", 3, APPROVE_PREFIX), button("Deny", 4, DENY_PREFIX)],
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(json: &str) -> Payload {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn reads_gateway_control_messages() {
+        assert_eq!(
+            payload(r#"{"op":10,"d":{"heartbeat_interval":41250}}"#).incoming(),
+            Incoming::Hello(Duration::from_millis(41250))
+        );
+        assert_eq!(
+            payload(r#"{"op":1,"d":null}"#).incoming(),
+            Incoming::Heartbeat
+        );
+        assert_eq!(
+            payload(r#"{"op":9,"d":false}"#).incoming(),
+            Incoming::Reconnect
+        );
+        assert_eq!(
+            payload(r#"{"op":0,"s":1,"t":"READY","d":{"application":{"id":"A1"}}}"#).incoming(),
+            Incoming::Ready("A1".to_string())
+        );
+        assert_eq!(heartbeat(Some(7)), r#"{"d":7,"op":1}"#);
+    }
+
+    #[test]
+    fn reads_slash_commands_and_clicks() {
+        let task = payload(
+            r#"{"op":0,"s":2,"t":"INTERACTION_CREATE","d":{"id":"I1","token":"tok",
+                "application_id":"A1","channel_id":"C1","type":2,
+                "member":{"user":{"id":"U1"}},
+                "data":{"name":"golem","options":[{"name":"task","type":3,"value":"disk usage?"}]}}}"#,
+        );
+        let Incoming::Task(interaction, text) = task.incoming() else {
+            panic!("expected a task");
+        };
+        assert_eq!(
+            (interaction.channel.as_str(), interaction.user.as_str()),
+            ("C1", "U1")
+        );
+        assert_eq!(text, "disk usage?");
+
+        let click = payload(
+            r#"{"op":0,"s":3,"t":"INTERACTION_CREATE","d":{"id":"I2","token":"tok",
+                "application_id":"A1","channel_id":"C1","type":3,"user":{"id":"U2"},
+                "data":{"custom_id":"golem_deny:ab12"}}}"#,
+        );
+        assert!(matches!(
+            click.incoming(),
+            Incoming::Approval(i, id, false) if i.user == "U2" && id == "ab12"
+        ));
+    }
+
+    #[test]
+    fn buttons_carry_the_approval_id() {
+        let components = approval_components("ab12");
+        let buttons = &components[0]["components"];
+        assert_eq!(buttons[0]["custom_id"], "golem_approve:ab12");
+        assert_eq!(buttons[1]["custom_id"], "golem_deny:ab12");
+    }
+}
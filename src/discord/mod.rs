@@ -0,0 +1,385 @@
+//! `golem discord`: a Discord bot on the gateway.
+//!
+//! `/golem <task>` in a channel gives it a task. Each channel gets an
+//! engine of its own whose history lives in the named session
+//! `discord-<channel id>`; a channel runs its tasks one at a time,
+//! channels run side by side. The command's response shows the tool
+//! calls as they finish, edited in place, and then the answer. With a
+//! writable shell, a command that would write posts Approve and Deny
+//! buttons in the channel first.
+
+pub mod api;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::banner::format_task_result;
+use crate::chat::{self, Approvals};
+use crate::config::settings::Settings;
+use crate::engine::hooks::Hook;
+use crate::engine::{Engine, TaskResult};
+use crate::thinker::ToolCall;
+use crate::tools::{Approver, ToolResult};
+use api::{DiscordApi, Incoming, Interaction, Payload};
+
+/// How long to wait before opening a new connection after one dropped.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Discord refuses messages longer than this many characters.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// The task is shown above its progress up to this many characters.
+const TASK_CHARS: usize = 200;
+
+/// The named session a channel's tasks are kept in.
+pub fn session_name(channel: &str) -> String {
+    format!("discord-{channel}")
+}
+
+/// A `/golem` run waiting for its channel's engine.
+struct Task {
+    interaction: Interaction,
+    text: String,
+}
+
+/// Run the bot until the gateway can't be reached again.
+pub async fn run(settings: Settings, token: String) -> Result<()> {
+    let api = Arc::new(DiscordApi::new(token));
+    let approvals = Arc::new(Approvals::default());
+    let mut channels = HashMap::new();
+    let mut registered = false;
+    // A bad token fails the first request; later ones are retried
+    let mut url = api.gateway_url().await?;
+    loop {
+        let served = serve(
+            &url,
+            &settings,
+            &api,
+            &approvals,
+            &mut channels,
+            &mut registered,
+        )
+        .await;
+        if let Err(e) = served {
+            eprintln!("warning: Discord connection dropped: {e:#}");
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+        url = api.gateway_url().await?;
+    }
+}
+
+/// Handle gateway messages on one connection until it closes or asks to
+/// be replaced.
+async fn serve(
+    url: &str,
+    settings: &Settings,
+    api: &Arc<DiscordApi>,
+    approvals: &Arc<Approvals>,
+    channels: &mut HashMap<String, mpsc::UnboundedSender<Task>>,
+    registered: &mut bool,
+) -> Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+    let mut heartbeat: Option<tokio::time::Interval> = None;
+    let mut seq = None;
+    loop {
+        let text = tokio::select! {
+            message = socket.next() => match message {
+                Some(message) => match message? {
+                    Message::Text(text) => text,
+                    Message::Close(_) => break,
+                    _ => continue,
+                },
+                None => break,
+            },
+            _ = async { heartbeat.as_mut().expect("guarded").tick().await },
+                if heartbeat.is_some() =>
+            {
+                socket.send(Message::text(api::heartbeat(seq))).await?;
+                continue;
+            }
+        };
+        let Ok(payload) = serde_json::from_str::<Payload>(&text) else {
+            continue;
+        };
+        seq = payload.s.or(seq);
+        match payload.incoming() {
+            Incoming::Hello(interval) => {
+                heartbeat = Some(tokio::time::interval(interval));
+                socket
+                    .send(Message::text(api::identify(api.token())))
+                    .await?;
+            }
+            Incoming::Heartbeat => socket.send(Message::text(api::heartbeat(seq))).await?,
+            Incoming::Ready(application_id) => {
+                if !*registered {
+                    api.register_command(&application_id).await?;
+                    *registered = true;
+                    println!("connected to Discord; use /golem <task> in a channel");
+                }
+            }
+            Incoming::Task(interaction, text) => {
+                // Before the channel's worker edits the response
+                if let Err(e) = api.respond(&interaction, api::deferred()).await {
+                    eprintln!("warning: failed to answer a Discord command: {e:#}");
+                    continue;
+                }
+                let channel = interaction.channel.clone();
+                let worker = channels.entry(channel.clone()).or_insert_with(|| {
+                    let (tx, rx) = mpsc::unbounded_channel();
+                    let reply = Reply::new(
+                        Arc::clone(api),
+                        Arc::clone(approvals),
+                        channel,
+                        chat::shell_writes(settings),
+                    );
+                    tokio::spawn(serve_channel(settings.clone(), reply, rx));
+                    tx
+                });
+                let _ = worker.send(Task { interaction, text });
+            }
+            Incoming::Approval(interaction, id, approved) => {
+                tokio::spawn(settle(
+                    Arc::clone(api),
+                    Arc::clone(approvals),
+                    interaction,
+                    id,
+                    approved,
+                ));
+            }
+            Incoming::Reconnect => break,
+            Incoming::Other => {}
+        }
+    }
+    Ok(())
+}
+
+/// Run a channel's tasks one after another.
+async fn serve_channel(settings: Settings, reply: Reply, mut tasks: mpsc::UnboundedReceiver<Task>) {
+    let mut engine = None;
+    while let Some(task) = tasks.recv().await {
+        reply.start(task.interaction, &task.text).await;
+        if engine.is_none() {
+            let session = session_name(&reply.channel);
+            let built = chat::channel_engine(
+                &settings,
+                &session,
+                Box::new(reply.clone()),
+                Arc::new(reply.clone()),
+            )
+            .await;
+            match built {
+                Ok(built) => engine = Some(built),
+                Err(e) => {
+                    reply.finish(&format!("✗ {e:#}")).await;
+                    continue;
+                }
+            }
+        }
+        let Some(engine) = engine.as_mut() else {
+            continue;
+        };
+        let result = engine.run(&task.text).await;
+        reply.finish(&answer_text(&result)).await;
+    }
+}
+
+/// A task's answer with its stats, cut to fit one message.
+fn answer_text(result: &Result<TaskResult>) -> String {
+    match result {
+        Ok(result) => {
+            let stats = format!("\n\n*{}*", format_task_result(result).trim());
+            let room = MAX_MESSAGE_CHARS - stats.chars().count();
+            format!("{}{stats}", chat::cut(&result.answer, room))
+        }
+        Err(e) => chat::cut(&format!("✗ {e:#}"), MAX_MESSAGE_CHARS),
+    }
+}
+
+/// The progress message: the task, then the latest tool calls that fit.
+fn progress_text(task: &str, lines: &[String]) -> String {
+    let header = format!("⏳ {}", chat::cut(task, TASK_CHARS));
+    let mut shown = Vec::new();
+    let mut len = header.chars().count();
+    for line in lines.iter().rev() {
+        len += line.chars().count() + 1;
+        if len > MAX_MESSAGE_CHARS {
+            break;
+        }
+        shown.push(line.as_str());
+    }
+    shown.push(&header);
+    shown.reverse();
+    shown.join("\n")
+}
+
+/// Settle a click and replace the buttons with who answered.
+async fn settle(
+    api: Arc<DiscordApi>,
+    approvals: Arc<Approvals>,
+    interaction: Interaction,
+    id: String,
+    approved: bool,
+) {
+    let content = if !approvals.settle(&id, approved) {
+        "This request has expired.".to_string()
+    } else if approved {
+        format!("✓ Approved by <@{}>", interaction.user)
+    } else {
+        format!("✗ Denied by <@{}>", interaction.user)
+    };
+    if let Err(e) = api
+        .respond(&interaction, api::update_message(&content))
+        .await
+    {
+        eprintln!("warning: failed to answer a Discord click: {e:#}");
+    }
+}
+
+/// The current task's response in a channel: the progress it shows, and
+/// where approvals are asked.
+#[derive(Clone)]
+struct Reply {
+    api: Arc<DiscordApi>,
+    approvals: Arc<Approvals>,
+    channel: String,
+    current: Arc<Mutex<Option<Current>>>,
+    /// Whether the shell may write, so write commands need approval.
+    shell_writes: bool,
+}
+
+/// The task a [`Reply`] is showing.
+struct Current {
+    interaction: Interaction,
+    task: String,
+    lines: Vec<String>,
+}
+
+impl Reply {
+    fn new(
+        api: Arc<DiscordApi>,
+        approvals: Arc<Approvals>,
+        channel: String,
+        shell_writes: bool,
+    ) -> Self {
+        Self {
+            api,
+            approvals,
+            channel,
+            current: Arc::default(),
+            shell_writes,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Current>> {
+        self.current.lock().expect("reply lock poisoned")
+    }
+
+    /// Show progress for `task` in `interaction`'s response.
+    async fn start(&self, interaction: Interaction, task: &str) {
+        *self.lock() = Some(Current {
+            interaction,
+            task: task.to_string(),
+            lines: Vec::new(),
+        });
+        self.show_progress().await;
+    }
+
+    async fn show_progress(&self) {
+        let Some((interaction, content)) = self.lock().as_ref().map(|current| {
+            let content = progress_text(&current.task, &current.lines);
+            (current.interaction.clone(), content)
+        }) else {
+            return;
+        };
+        // Past the interaction's 15 minutes progress stops showing; the
+        // answer still arrives through `finish`
+        let _ = self.api.edit_response(&interaction, &content).await;
+    }
+
+    /// Replace the progress with `content`, or post it to the channel
+    /// once the response can't be edited any more.
+    async fn finish(&self, content: &str) {
+        let Some(current) = self.lock().take() else {
+            return;
+        };
+        if self
+            .api
+            .edit_response(&current.interaction, content)
+            .await
+            .is_ok()
+        {
+            return;
+        }
+        if let Err(e) = self.api.create_message(&self.channel, content, None).await {
+            eprintln!("warning: failed to post to Discord: {e:#}");
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for Reply {
+    async fn on_tool_result(&self, call: &ToolCall, result: &ToolResult) {
+        if let Some(current) = self.lock().as_mut() {
+            current.lines.push(chat::progress_line(call, result));
+        }
+        self.show_progress().await;
+    }
+}
+
+#[async_trait]
+impl Approver for Reply {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        if !chat::needs_approval(tool, args, self.shell_writes) {
+            return Ok(true);
+        }
+        let (id, answer) = self.approvals.open();
+        let question = chat::cut(&chat::approval_question(tool, args), MAX_MESSAGE_CHARS);
+        let components = api::approval_components(&id);
+        if let Err(e) = self
+            .api
+            .create_message(&self.channel, &question, Some(components))
+            .await
+        {
+            self.approvals.settle(&id, false);
+            bail!("couldn't ask for approval in Discord: {e:#}");
+        }
+        self.approvals.wait(&id, answer, tool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_keeps_the_latest_lines() {
+        let lines: Vec<String> = (0..200).map(|i| format!("✓ `shell` `step {i}`")).collect();
+        let text = progress_text("clean up", &lines);
+        assert!(text.starts_with("⏳ clean up\n"), "{text}");
+        assert!(text.ends_with("`step 199`"), "{text}");
+        assert!(!text.contains("`step 0`"), "{text}");
+        assert!(text.chars().count() <= MAX_MESSAGE_CHARS);
+    }
+
+    #[test]
+    fn answers_fit_one_message() {
+        let result = Ok(TaskResult {
+            answer: "x".repeat(5000),
+            iterations: 1,
+            tool_calls: 0,
+            usage: Default::default(),
+            duration: Duration::from_secs(1),
+            status: crate::engine::TaskStatus::Completed,
+        });
+        let text = answer_text(&result);
+        assert_eq!(text.chars().count(), MAX_MESSAGE_CHARS);
+        assert!(text.ends_with("1.0s)*"), "{text}");
+        assert_eq!(session_name("123"), "discord-123");
+    }
+}
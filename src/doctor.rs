@@ -0,0 +1,295 @@
+//! `golem doctor`: checks that golem can work here — config files,
+//! database, credentials and the programs tasks tend to need — and says
+//! how to fix what it finds.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::auth::AuthStorage;
+use crate::auth::storage::Credential;
+use crate::config::settings::Settings;
+use crate::thinker::Thinker;
+use crate::thinker::anthropic::AnthropicThinker;
+
+/// How a check came out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// golem works, but something will get in the way sooner or later.
+    Warn,
+    /// golem won't work until it is fixed.
+    Fail,
+}
+
+/// One finding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Warn,
+            fix: Some(fix.into()),
+            ..Self::ok(name, detail)
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Fail,
+            ..Self::warn(name, detail, fix)
+        }
+    }
+}
+
+/// Programs looked for on `PATH`; the shell tool can't run without the
+/// first, and tasks often reach for the others.
+const PROGRAMS: &[(&str, bool)] = &[("sh", true), ("git", false), ("docker", false)];
+
+/// Each config file parses, and the working directory it names exists.
+pub fn check_config(user: &Path, project: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for (name, path) in [("user config", user), ("project config", project)] {
+        if !path.exists() {
+            checks.push(Check::ok(name, format!("{} (not present)", path.display())));
+            continue;
+        }
+        match Settings::read(path) {
+            Ok(settings) => {
+                checks.push(Check::ok(name, path.display().to_string()));
+                if let Some(dir) = settings.shell.work_dir
+                    && !dir.is_dir()
+                {
+                    checks.push(Check::fail(
+                        "work_dir",
+                        format!(
+                            "{} (from {}) is not a directory",
+                            dir.display(),
+                            path.display()
+                        ),
+                        "create it, or change `work_dir` under [shell]",
+                    ));
+                }
+            }
+            Err(e) => checks.push(Check::fail(
+                name,
+                format!("{e:#}"),
+                format!("fix or remove {}", path.display()),
+            )),
+        }
+    }
+    checks
+}
+
+/// The database opens, is migrated, and passes SQLite's integrity check.
+pub fn check_database(path: &str) -> Check {
+    const NAME: &str = "database";
+    if !Path::new(path).exists() {
+        return Check::ok(NAME, format!("{path} (created on first use)"));
+    }
+    let conn = match crate::db::open(path) {
+        Ok(conn) => conn,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("{e:#}"),
+                format!("move {path} aside to start over, or point --db elsewhere"),
+            );
+        }
+    };
+    let problems: rusqlite::Result<Vec<String>> = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect());
+    match problems {
+        Ok(rows) if rows == ["ok"] => Check::ok(NAME, path),
+        Ok(rows) => Check::fail(
+            NAME,
+            format!("{path}: {}", rows.join("; ")),
+            format!("restore {path} from a backup, or move it aside to start over"),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{path}: {e}"),
+            format!("move {path} aside to start over, or point --db elsewhere"),
+        ),
+    }
+}
+
+/// Anthropic credentials exist and the API accepts them. `env_key` is
+/// `ANTHROPIC_API_KEY`, used when nothing is stored.
+pub async fn check_anthropic(db_path: &str, env_key: Option<String>) -> Check {
+    const NAME: &str = "credentials";
+    let auth = match AuthStorage::open(db_path) {
+        Ok(auth) => auth,
+        Err(e) => return Check::fail(NAME, format!("{e:#}"), "see the database check"),
+    };
+    let source = match auth.get("anthropic") {
+        Ok(Some(Credential::OAuth(_))) => "OAuth login",
+        Ok(Some(Credential::ApiKey { .. })) => "stored API key",
+        Ok(None) if env_key.is_some_and(|k| !k.is_empty()) => "ANTHROPIC_API_KEY",
+        Ok(None) => {
+            return Check::fail(
+                NAME,
+                "none for anthropic",
+                "run `golem login`, or set ANTHROPIC_API_KEY",
+            );
+        }
+        Err(e) => return Check::fail(NAME, format!("{e:#}"), "run `golem login` again"),
+    };
+    verify(&AnthropicThinker::new(None, auth), source).await
+}
+
+/// Ask the provider for its models — the cheapest call that needs valid
+/// credentials.
+async fn verify(thinker: &dyn Thinker, source: &str) -> Check {
+    const NAME: &str = "credentials";
+    match thinker.models().await {
+        Ok(models) => Check::ok(
+            NAME,
+            format!("{source} works ({} models available)", models.len()),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{source} was rejected: {e:#}"),
+            "run `golem login` again, or check the key and your network",
+        ),
+    }
+}
+
+/// Each of [`PROGRAMS`] on `path` (the `PATH` variable).
+pub fn check_programs(path: Option<&OsStr>) -> Vec<Check> {
+    PROGRAMS
+        .iter()
+        .map(|&(program, required)| match find_program(program, path) {
+            Some(found) => Check::ok(program, found.display().to_string()),
+            None if required => Check::fail(
+                program,
+                "not found on PATH",
+                format!("install {program}; the shell tool runs every command through it"),
+            ),
+            None => Check::warn(
+                program,
+                "not found on PATH",
+                format!("install {program} if tasks need it"),
+            ),
+        })
+        .collect()
+}
+
+/// The first executable file called `program` in the directories of `path`.
+fn find_program(program: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thinker::mock::MockThinker;
+
+    #[test]
+    fn config_files_are_read_and_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        let project = dir.path().join(".golem.toml");
+        std::fs::write(&user, "[shell]\nwork_dir = \"missing\"\n").unwrap();
+        std::fs::write(&project, "max_iteration = 5\n").unwrap();
+
+        let checks = check_config(&user, &project);
+        let statuses: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.status)).collect();
+        assert_eq!(
+            statuses,
+            [
+                ("user config", Status::Ok),
+                ("work_dir", Status::Fail),
+                ("project config", Status::Fail),
+            ]
+        );
+        assert!(checks[2].detail.contains("max_iteration"), "{checks:?}");
+
+        let absent = check_config(&dir.path().join("a"), &dir.path().join("b"));
+        assert!(absent.iter().all(|c| c.status == Status::Ok));
+    }
+
+    #[test]
+    fn database_integrity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golem.db");
+        let path = path.to_str().unwrap();
+        assert_eq!(check_database(path).status, Status::Ok);
+
+        crate::db::open(path).unwrap();
+        assert_eq!(check_database(path).status, Status::Ok);
+
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, "this is not a database, just long enough text").unwrap();
+        let check = check_database(garbage.to_str().unwrap());
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.fix.unwrap().contains("move"));
+    }
+
+    #[tokio::test]
+    async fn missing_credentials_fail_without_a_request() {
+        let check = check_anthropic(":memory:", None).await;
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.fix.unwrap().contains("golem login"));
+    }
+
+    #[tokio::test]
+    async fn working_credentials_pass() {
+        let check = verify(&MockThinker::new(vec![]), "stored API key").await;
+        assert_eq!(check.status, Status::Ok);
+        assert_eq!(check.detail, "stored API key works (0 models available)");
+    }
+
+    #[test]
+    fn programs_are_found_on_the_path() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let sh = dir.path().join("sh");
+        std::fs::write(&sh, "#!/bin/true\n").unwrap();
+        std::fs::set_permissions(&sh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Not executable, so not a match
+        std::fs::write(dir.path().join("git"), "").unwrap();
+
+        let checks = check_programs(Some(dir.path().as_os_str()));
+        let statuses: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.status)).collect();
+        assert_eq!(
+            statuses,
+            [
+                ("sh", Status::Ok),
+                ("git", Status::Warn),
+                ("docker", Status::Warn)
+            ]
+        );
+        assert_eq!(check_programs(None)[0].status, Status::Fail);
+    }
+}
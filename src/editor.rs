@@ -0,0 +1,475 @@
+//! Line editing for the REPL prompt.
+//!
+//! Wraps rustyline: arrow-key history kept across runs in
+//! `~/.golem/history`, Ctrl+R search, bracketed paste, Emacs or Vi
+//! keybindings, and Tab completion of `/` commands, model names after
+//! `/model`, and file paths. The `[keys]` config table adds keys of the
+//! user's choosing for cancel, clear, newline and search ([`KeyBindings`]).
+//!
+//! Input can span lines: end a line with `\` to continue it, open a block
+//! with `"""` and close it with another, or press Alt+Enter for a newline.
+//! [`join_lines`] turns what was typed into the task text.
+
+use anyhow::Result;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Cmd, Config, Editor, EventHandler, Helper, KeyCode, KeyEvent, Modifiers};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Keybindings for the prompt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EditMode {
+    #[default]
+    Emacs,
+    Vi,
+}
+
+/// A key and its modifiers, written like `ctrl-r`, `alt-enter` or `f2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key(KeyEvent);
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let lower = s.trim().to_ascii_lowercase();
+        let mut rest = lower.as_str();
+        let mut mods = Modifiers::NONE;
+        loop {
+            let (modifier, after) = match rest.split_once('-') {
+                Some((modifier, after)) if !after.is_empty() => (modifier, after),
+                _ => break,
+            };
+            mods |= match modifier {
+                "ctrl" | "c" => Modifiers::CTRL,
+                "alt" | "meta" | "m" => Modifiers::ALT,
+                "shift" | "s" => Modifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{s}`")),
+            };
+            rest = after;
+        }
+        let code = match rest {
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            _ => match (rest.strip_prefix('f').map(str::parse), rest.chars().count()) {
+                (Some(Ok(n @ 1..=12)), _) => KeyCode::F(n),
+                (_, 1) => KeyCode::Char(rest.chars().next().unwrap_or_default()),
+                _ => return Err(format!("unknown key `{s}`")),
+            },
+        };
+        Ok(Self(KeyEvent::normalize(KeyEvent(code, mods))))
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+/// The `[keys]` table: extra keys for prompt actions. The defaults —
+/// Ctrl+C, Ctrl+L, Alt+Enter and Ctrl+R — keep working.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    /// Abandon the line being typed.
+    pub cancel: Option<Key>,
+    /// Clear the screen.
+    pub clear: Option<Key>,
+    /// Start a new line instead of submitting.
+    pub newline: Option<Key>,
+    /// Search history backwards.
+    pub search: Option<Key>,
+}
+
+impl KeyBindings {
+    /// The rustyline command each configured key runs.
+    fn commands(&self) -> Vec<(KeyEvent, Cmd)> {
+        [
+            (self.cancel, Cmd::Interrupt),
+            (self.clear, Cmd::ClearScreen),
+            (self.newline, Cmd::Newline),
+            (self.search, Cmd::ReverseSearchHistory),
+        ]
+        .into_iter()
+        .filter_map(|(key, cmd)| Some((key?.0, cmd)))
+        .collect()
+    }
+}
+
+/// What reading the prompt produced.
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    Line(String),
+    /// Ctrl+C at the prompt.
+    Interrupted,
+    /// Ctrl+D, or the end of piped input.
+    Eof,
+}
+
+/// Model ids offered after `/model`; shared so they can be filled in once
+/// the provider has listed them.
+pub type ModelNames = Arc<Mutex<Vec<String>>>;
+
+/// Completes the REPL prompt.
+struct PromptHelper {
+    /// `/` command names and aliases.
+    commands: Vec<String>,
+    models: ModelNames,
+    files: FilenameCompleter,
+}
+
+impl PromptHelper {
+    /// Completions for a command name or a `/model` argument, with the
+    /// byte offset they replace from. `None` leaves the word to the file
+    /// completer.
+    fn complete_word(&self, line: &str, pos: usize) -> Option<(usize, Vec<String>)> {
+        let before = &line[..pos];
+        if before.starts_with('/') && !before.contains(char::is_whitespace) {
+            let names = self
+                .commands
+                .iter()
+                .filter(|name| name.starts_with(before))
+                .cloned()
+                .collect();
+            return Some((0, names));
+        }
+        let arg = before.strip_prefix("/model ")?;
+        if arg.contains(char::is_whitespace) {
+            return None;
+        }
+        let models = self.models.lock().expect("model names lock poisoned");
+        let ids = models
+            .iter()
+            .filter(|id| id.starts_with(arg))
+            .cloned()
+            .collect();
+        Some((pos - arg.len(), ids))
+    }
+}
+
+impl Completer for PromptHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        match self.complete_word(line, pos) {
+            Some((start, words)) => {
+                let pairs = words
+                    .into_iter()
+                    .map(|word| Pair {
+                        display: word.clone(),
+                        replacement: word,
+                    })
+                    .collect();
+                Ok((start, pairs))
+            }
+            None => self.files.complete(line, pos, ctx),
+        }
+    }
+}
+
+impl Hinter for PromptHelper {
+    type Hint = String;
+}
+
+impl Highlighter for PromptHelper {}
+
+/// Keeps reading while a line ends in `\\` or a `"""` block is open.
+impl Validator for PromptHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        Ok(if is_complete(ctx.input()) {
+            ValidationResult::Valid(None)
+        } else {
+            ValidationResult::Incomplete
+        })
+    }
+}
+
+const BLOCK_QUOTE: &str = "\"\"\"";
+
+fn is_complete(input: &str) -> bool {
+    !input.ends_with('\\') && input.matches(BLOCK_QUOTE).count().is_multiple_of(2)
+}
+
+/// The task text for multi-line `input`: continuation backslashes and
+/// block quotes are dropped, the line breaks they wrapped are kept.
+pub fn join_lines(input: &str) -> String {
+    input
+        .replace("\\\n", "\n")
+        .replace(BLOCK_QUOTE, "")
+        .trim()
+        .to_string()
+}
+
+impl Helper for PromptHelper {}
+
+pub struct LineEditor {
+    editor: Editor<PromptHelper, DefaultHistory>,
+    /// Where history is loaded from and appended to; `None` keeps it in
+    /// memory only.
+    history: Option<PathBuf>,
+}
+
+impl LineEditor {
+    /// `commands` are the `/` names to complete; `models` may still be
+    /// empty and filled in later.
+    pub fn new(
+        mode: EditMode,
+        keys: &KeyBindings,
+        history: Option<PathBuf>,
+        commands: Vec<String>,
+        models: ModelNames,
+    ) -> Result<Self> {
+        let config = Config::builder()
+            .edit_mode(match mode {
+                EditMode::Emacs => rustyline::EditMode::Emacs,
+                EditMode::Vi => rustyline::EditMode::Vi,
+            })
+            .history_ignore_dups(true)?
+            .history_ignore_space(true)
+            .bracketed_paste(true)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+        editor.bind_sequence(
+            KeyEvent(KeyCode::Enter, Modifiers::ALT),
+            EventHandler::Simple(Cmd::Newline),
+        );
+        for (key, cmd) in keys.commands() {
+            editor.bind_sequence(key, EventHandler::Simple(cmd));
+        }
+        editor.set_helper(Some(PromptHelper {
+            commands,
+            models,
+            files: FilenameCompleter::new(),
+        }));
+        if let Some(path) = &history
+            && path.exists()
+        {
+            editor.load_history(path)?;
+        }
+        Ok(Self { editor, history })
+    }
+
+    /// Read one line, blocking until the user submits it. Non-empty lines
+    /// go into history.
+    pub fn read_line(&mut self, prompt: &str) -> Result<Input> {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                self.remember(&line)?;
+                Ok(Input::Line(join_lines(&line)))
+            }
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(ReadlineError::Eof) => Ok(Input::Eof),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Add `line` to history and append it to the history file.
+    fn remember(&mut self, line: &str) -> Result<()> {
+        if line.trim().is_empty() || !self.editor.add_history_entry(line)? {
+            return Ok(());
+        }
+        if let Some(path) = &self.history {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            self.editor.append_history(path)?;
+        }
+        Ok(())
+    }
+
+    /// Lines in history, oldest first.
+    pub fn history(&self) -> Vec<String> {
+        self.editor.history().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(history: Option<PathBuf>) -> LineEditor {
+        LineEditor::new(
+            EditMode::Emacs,
+            &KeyBindings::default(),
+            history,
+            vec![],
+            ModelNames::default(),
+        )
+        .unwrap()
+    }
+
+    fn helper() -> PromptHelper {
+        PromptHelper {
+            commands: ["/help", "/h", "/history", "/model", "/quit"]
+                .map(String::from)
+                .to_vec(),
+            models: Arc::new(Mutex::new(
+                ["claude-haiku-4-5", "claude-sonnet-4-5", "claude-opus-4-5"]
+                    .map(String::from)
+                    .to_vec(),
+            )),
+            files: FilenameCompleter::new(),
+        }
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let helper = helper();
+        assert_eq!(
+            helper.complete_word("/h", 2),
+            Some((0, vec!["/help".into(), "/h".into(), "/history".into()]))
+        );
+        assert_eq!(
+            helper.complete_word("/q", 2),
+            Some((0, vec!["/quit".into()]))
+        );
+        assert_eq!(helper.complete_word("/x", 2), Some((0, vec![])));
+    }
+
+    #[test]
+    fn completes_model_names_after_model() {
+        let helper = helper();
+        assert_eq!(
+            helper.complete_word("/model claude-s", 15),
+            Some((7, vec!["claude-sonnet-4-5".into()]))
+        );
+        assert_eq!(helper.complete_word("/model ", 7).unwrap().1.len(), 3);
+    }
+
+    #[test]
+    fn other_words_are_left_to_paths() {
+        let helper = helper();
+        assert_eq!(helper.complete_word("read ./sr", 9), None);
+        assert_eq!(helper.complete_word("/export md ./se", 15), None);
+    }
+
+    #[test]
+    fn continuation_and_blocks_keep_reading() {
+        assert!(is_complete("list files"));
+        assert!(!is_complete("list files \\"));
+        assert!(!is_complete("explain \"\"\"\nerror[E0382]"));
+        assert!(is_complete("explain \"\"\"\nerror[E0382]\n\"\"\""));
+    }
+
+    #[test]
+    fn joined_lines_keep_line_breaks() {
+        assert_eq!(join_lines("find large \\\nfiles"), "find large \nfiles");
+        assert_eq!(
+            join_lines("explain this:\n\"\"\"\nerror: boom\n  at main\n\"\"\""),
+            "explain this:\n\nerror: boom\n  at main"
+        );
+        assert_eq!(join_lines("  ls  "), "ls");
+    }
+
+    #[test]
+    fn history_persists_across_editors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+
+        let mut editor = editor(Some(path.clone()));
+        editor.remember("list files").unwrap();
+        editor.remember("   ").unwrap();
+        editor.remember("list files").unwrap();
+        editor.remember("/tokens").unwrap();
+
+        let keys = KeyBindings {
+            newline: Some("ctrl-j".parse().unwrap()),
+            ..KeyBindings::default()
+        };
+        let reopened = LineEditor::new(
+            EditMode::Vi,
+            &keys,
+            Some(path),
+            vec![],
+            ModelNames::default(),
+        )
+        .unwrap();
+        assert_eq!(reopened.history(), ["list files", "/tokens"]);
+    }
+
+    #[test]
+    fn multi_line_tasks_come_back_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+        let task = "explain this error:\\\nE0382 borrow of moved value";
+        editor(Some(path.clone())).remember(task).unwrap();
+        assert_eq!(editor(Some(path)).history(), [task]);
+    }
+
+    #[test]
+    fn without_a_file_history_stays_in_memory() {
+        let mut editor = editor(None);
+        editor.remember("list files").unwrap();
+        assert_eq!(editor.history(), ["list files"]);
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        let key = |s: &str| s.parse::<Key>().map(|k| k.0);
+        assert_eq!(key("ctrl-r"), Ok(KeyEvent::ctrl('R')));
+        assert_eq!(key("Ctrl-R"), Ok(KeyEvent::ctrl('R')));
+        assert_eq!(
+            key("alt-enter"),
+            Ok(KeyEvent(KeyCode::Enter, Modifiers::ALT))
+        );
+        assert_eq!(
+            key("ctrl-alt-x"),
+            Ok(KeyEvent(KeyCode::Char('X'), Modifiers::CTRL_ALT))
+        );
+        assert_eq!(key("f2"), Ok(KeyEvent(KeyCode::F(2), Modifiers::NONE)));
+        assert_eq!(key("ctrl--"), Ok(KeyEvent::ctrl('-')));
+        assert!(key("hyper-x").unwrap_err().contains("hyper"));
+        assert!(key("ctrl-banana").is_err());
+        assert!(key("f13").is_err());
+    }
+
+    #[test]
+    fn configured_keys_map_to_actions() {
+        let keys = KeyBindings {
+            clear: Some("ctrl-k".parse().unwrap()),
+            search: Some("f3".parse().unwrap()),
+            ..KeyBindings::default()
+        };
+        assert_eq!(
+            keys.commands(),
+            [
+                (KeyEvent::ctrl('K'), Cmd::ClearScreen),
+                (
+                    KeyEvent(KeyCode::F(3), Modifiers::NONE),
+                    Cmd::ReverseSearchHistory
+                ),
+            ]
+        );
+        assert!(KeyBindings::default().commands().is_empty());
+    }
+}
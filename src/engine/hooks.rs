@@ -0,0 +1,70 @@
+//! Hooks into the ReAct loop.
+//!
+//! A [`Hook`] sees every step, tool call and finished task of the engine
+//! it is added to, and may deny or rewrite tool calls before they run —
+//! enough for custom guardrails, auditing and notifications without
+//! touching the engine itself. Every method has a no-op default, so a hook
+//! only implements what it cares about.
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::TaskResult;
+use crate::thinker::{Context, Step, ToolCall};
+use crate::tools::ToolResult;
+
+/// What a hook decides about a tool call that is about to run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolDecision {
+    /// Run the call as is.
+    Allow,
+    /// Don't run it; the model sees the reason as the call's error.
+    Deny(String),
+    /// Run this call instead.
+    Modify(ToolCall),
+    /// Run none of the step's calls and fail the task with this reason.
+    Abort(String),
+}
+
+#[async_trait]
+pub trait Hook: Send + Sync {
+    /// When a task starts, or an unfinished one is resumed.
+    async fn on_task_start(&self, _task: &str) {}
+
+    /// Before the thinker is asked for the next step.
+    async fn on_step_start(&self, _context: &Context) {}
+
+    /// After the thinker returned a step, before any of its calls run.
+    async fn on_step(&self, _step: &Step) {}
+
+    /// Before a tool call runs. Hooks are asked in the order they were
+    /// added; a `Modify` is what later hooks see, and the first `Deny` or
+    /// `Abort` wins.
+    async fn on_tool_call(&self, _call: &ToolCall) -> ToolDecision {
+        ToolDecision::Allow
+    }
+
+    /// After a tool call finished, was denied, timed out or was interrupted.
+    async fn on_tool_result(&self, _call: &ToolCall, _result: &ToolResult) {}
+
+    /// After a task ended, with its result or error.
+    async fn on_task_end(&self, _task: &str, _result: &Result<TaskResult>) {}
+}
+
+/// Run `call` past every hook. Returns the call to execute and the reason
+/// it was denied, if it was; an abort is an error.
+pub(crate) async fn review_call(
+    hooks: &[Arc<dyn Hook>],
+    mut call: ToolCall,
+) -> Result<(ToolCall, Option<String>)> {
+    for hook in hooks {
+        match hook.on_tool_call(&call).await {
+            ToolDecision::Allow => {}
+            ToolDecision::Deny(reason) => return Ok((call, Some(reason))),
+            ToolDecision::Modify(modified) => call = modified,
+            ToolDecision::Abort(reason) => bail!("{reason}"),
+        }
+    }
+    Ok((call, None))
+}
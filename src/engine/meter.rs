@@ -0,0 +1,140 @@
+//! Usage that tools spend on behalf of the task calling them.
+//!
+//! `delegate` runs a whole sub-agent inside one tool call. Its model calls
+//! and iterations belong to the parent task: they count towards the
+//! parent's budgets and the session's totals. The engine runs each tool
+//! call under its task's [`Meter`], and the tool reports what it spent
+//! with [`charge`]. The meter also carries the task's [`Caller`], so the
+//! sub-agent runs under the parent's hooks, events, live config and what
+//! is left of its budgets.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use super::hooks::Hook;
+use super::react::ReactConfig;
+use crate::events::EventBus;
+use crate::thinker::TokenUsage;
+
+tokio::task_local! {
+    static METER: Meter;
+}
+
+/// What tools charged to a task since the last [`Meter::take`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Charges {
+    pub iterations: usize,
+    /// By the model that used it.
+    pub usage: Vec<(String, TokenUsage)>,
+}
+
+/// The task a tool call runs for, as work the call starts should see it.
+pub struct Caller {
+    /// The task's config as it is now, with its budgets cut to what the
+    /// task has left.
+    pub config: ReactConfig,
+    pub hooks: Vec<Arc<dyn Hook>>,
+    pub events: Arc<EventBus>,
+}
+
+/// Collects the charges of the tool calls run under it.
+#[derive(Clone, Default)]
+pub struct Meter {
+    charges: Arc<Mutex<Charges>>,
+    caller: Option<Arc<Caller>>,
+}
+
+impl Meter {
+    /// A meter for tool calls run on behalf of `caller`.
+    pub fn new(caller: Caller) -> Self {
+        Self {
+            charges: Arc::default(),
+            caller: Some(Arc::new(caller)),
+        }
+    }
+
+    /// Run `call` with its charges going to this meter.
+    pub async fn run<F: Future>(&self, call: F) -> F::Output {
+        METER.scope(self.clone(), call).await
+    }
+
+    /// The charges so far, leaving the meter empty.
+    pub fn take(&self) -> Charges {
+        std::mem::take(&mut self.charges.lock().unwrap())
+    }
+}
+
+/// The task running the current tool call, if the meter it runs under
+/// knows it.
+pub fn caller() -> Option<Arc<Caller>> {
+    METER.try_with(|meter| meter.caller.clone()).ok().flatten()
+}
+
+/// Charge `iterations` and `usage` to the task running the current tool
+/// call. Does nothing outside one, e.g. when a tool is called directly.
+pub fn charge(iterations: usize, usage: &[(String, TokenUsage)]) {
+    let _ = METER.try_with(|meter| {
+        let mut charges = meter.charges.lock().unwrap();
+        charges.iterations += iterations;
+        for (model, spent) in usage {
+            match charges.usage.iter_mut().find(|(m, _)| m == model) {
+                Some((_, total)) => total.add(*spent),
+                None => charges.usage.push((model.clone(), *spent)),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            ..TokenUsage::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn charges_go_to_the_meter_in_scope() {
+        let meter = Meter::default();
+        meter
+            .run(async {
+                charge(2, &[("a".to_string(), usage(10))]);
+                charge(
+                    1,
+                    &[("a".to_string(), usage(5)), ("b".to_string(), usage(1))],
+                );
+            })
+            .await;
+        let charges = meter.take();
+        assert_eq!(charges.iterations, 3);
+        assert_eq!(charges.usage[0], ("a".to_string(), usage(15)));
+        assert_eq!(charges.usage[1], ("b".to_string(), usage(1)));
+        assert_eq!(meter.take(), Charges::default());
+    }
+
+    #[test]
+    fn charging_outside_a_task_is_ignored() {
+        charge(1, &[("a".to_string(), usage(10))]);
+    }
+
+    #[tokio::test]
+    async fn the_caller_is_known_inside_its_meter() {
+        assert!(caller().is_none());
+        let meter = Meter::new(Caller {
+            config: ReactConfig {
+                max_iterations: 3,
+                ..ReactConfig::default()
+            },
+            hooks: Vec::new(),
+            events: Arc::new(EventBus::default()),
+        });
+        let max = meter
+            .run(async { caller().map(|c| c.config.max_iterations) })
+            .await;
+        assert_eq!(max, Some(3));
+        assert!(Meter::default().run(async { caller() }).await.is_none());
+    }
+}
@@ -1,11 +1,53 @@
+pub mod hooks;
+pub mod meter;
+pub mod notify;
+pub mod observations;
 pub mod react;
+pub mod step;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use std::fmt;
+use std::time::Duration;
+
+use crate::thinker::TokenUsage;
 
 /// The outermost boundary. main.rs only knows this trait.
 /// Middleware (auth, rate limiting, logging) wraps around it.
 #[async_trait]
 pub trait Engine: Send + Sync {
-    async fn run(&mut self, task: &str) -> Result<String>;
+    async fn run(&mut self, task: &str) -> Result<TaskResult>;
+}
+
+/// How a task that produced an answer ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    /// The model finished with an answer.
+    Completed,
+    /// The iteration budget ran out first; the answer is the model's best
+    /// guess from the steps it got to take.
+    Partial,
+}
+
+/// A finished task: the answer and what it took to get there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskResult {
+    pub answer: String,
+    pub iterations: u64,
+    pub tool_calls: u64,
+    pub usage: TokenUsage,
+    pub duration: Duration,
+    pub status: TaskStatus,
 }
+
+/// The error a task fails with when it is cancelled mid-run.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task interrupted")
+    }
+}
+
+impl std::error::Error for Interrupted {}
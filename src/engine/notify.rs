@@ -0,0 +1,162 @@
+//! Desktop notifications for long tasks.
+//!
+//! Opt-in with `notify_after` (seconds) in the config or `--notify-after`.
+//! A task that ends after at least that long — answered or failed — fires
+//! a notification, unless the terminal it runs in has focus. Focus is only
+//! known on X11 (`$WINDOWID` and `xdotool`); elsewhere golem assumes the
+//! user has looked away. [`Notifier`] is a [`Hook`], timing each task from
+//! its first step.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::hooks::Hook;
+use super::{Interrupted, TaskResult};
+use crate::banner::one_line;
+use crate::thinker::Context;
+
+/// The notification body is cut to this many characters.
+const BODY_CHARS: usize = 120;
+
+/// Sends a notification when a task ran for at least `after`.
+pub struct Notifier {
+    after: Duration,
+    started: Mutex<Option<Instant>>,
+}
+
+impl Notifier {
+    pub fn new(after: Duration) -> Self {
+        Self {
+            after,
+            started: Mutex::new(None),
+        }
+    }
+
+    /// How long the task that just ended ran, resetting for the next one.
+    fn take_elapsed(&self) -> Option<Duration> {
+        let started = self.started.lock().expect("notifier lock poisoned").take();
+        started.map(|started| started.elapsed())
+    }
+}
+
+#[async_trait]
+impl Hook for Notifier {
+    async fn on_step_start(&self, _context: &Context) {
+        self.started
+            .lock()
+            .expect("notifier lock poisoned")
+            .get_or_insert_with(Instant::now);
+    }
+
+    async fn on_task_end(&self, _task: &str, result: &Result<TaskResult>) {
+        let Some(elapsed) = self.take_elapsed() else {
+            return;
+        };
+        if elapsed < self.after || terminal_focused() == Some(true) {
+            return;
+        }
+        let Some((summary, body)) = message(result) else {
+            return;
+        };
+        if let Err(e) = notify_rust::Notification::new()
+            .appname("golem")
+            .summary(summary)
+            .body(&body)
+            .show()
+        {
+            eprintln!("  warning: failed to send notification: {e}");
+        }
+    }
+}
+
+/// Title and body for a finished task. Interrupted tasks get none: whoever
+/// pressed Ctrl+C is watching.
+fn message(result: &Result<TaskResult>) -> Option<(&'static str, String)> {
+    match result {
+        Ok(result) => Some(("golem: task finished", one_line(&result.answer, BODY_CHARS))),
+        Err(e) if e.is::<Interrupted>() => None,
+        Err(e) => Some(("golem: task failed", one_line(&e.to_string(), BODY_CHARS))),
+    }
+}
+
+/// Whether the terminal window is the active one, if that can be told.
+fn terminal_focused() -> Option<bool> {
+    let window: u64 = std::env::var("WINDOWID").ok()?.parse().ok()?;
+    let output = Command::new("xdotool")
+        .arg("getactivewindow")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let active: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(active == window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::TaskStatus;
+    use crate::thinker::TokenUsage;
+
+    fn answered(answer: &str) -> Result<TaskResult> {
+        Ok(TaskResult {
+            answer: answer.to_string(),
+            iterations: 3,
+            tool_calls: 2,
+            usage: TokenUsage::default(),
+            duration: Duration::from_secs(90),
+            status: TaskStatus::Completed,
+        })
+    }
+
+    #[test]
+    fn message_is_the_first_line_of_the_answer() {
+        assert_eq!(
+            message(&answered("Build passed.\n\nDetails follow.")),
+            Some(("golem: task finished", "Build passed.…".to_string()))
+        );
+    }
+
+    #[test]
+    fn failures_notify_but_interruptions_do_not() {
+        assert_eq!(
+            message(&Err(anyhow::anyhow!("model unavailable"))),
+            Some(("golem: task failed", "model unavailable".to_string()))
+        );
+        assert_eq!(message(&Err(Interrupted.into())), None);
+    }
+
+    #[tokio::test]
+    async fn times_each_task_from_its_first_step() {
+        let notifier = Notifier::new(Duration::from_secs(3600));
+        assert_eq!(notifier.take_elapsed(), None);
+
+        let context = Context {
+            task: "task".to_string(),
+            history: vec![],
+            session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
+            available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
+        };
+        notifier.on_step_start(&context).await;
+        let first = *notifier.started.lock().unwrap();
+        notifier.on_step_start(&context).await;
+        assert_eq!(*notifier.started.lock().unwrap(), first);
+
+        // Far below the threshold, so nothing is sent
+        notifier.on_task_end("task", &answered("done")).await;
+        assert_eq!(notifier.take_elapsed(), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Keep tool output in the thinker's context within a byte budget.
+//!
+//! A few builds or file dumps early in a task can crowd out everything
+//! after them. [`FittedHistory`] keeps the latest iterations verbatim and
+//! cuts older tool output down to one line each, as entries are added, so
+//! each step's [`Context`] is built without refitting the whole task. It
+//! only shapes the context — memory keeps every observation in full.
+//! [`load_history`] fills one while reading a task back from memory, a
+//! page at a time, so a long task's old output is never all held at once.
+//!
+//! [`Context`]: crate::thinker::Context
+
+use anyhow::Result;
+use std::collections::VecDeque;
+
+use crate::consts::{HISTORY_PAGE_SIZE, OBSERVATION_SUMMARY_WIDTH, format_number};
+use crate::memory::{Memory, MemoryEntry};
+use crate::tools::{Outcome, ToolResult};
+
+/// Replace tool output in older iterations of `history` with one-line
+/// summaries once the newer ones hold `budget` bytes of it. The latest
+/// iteration is always kept whole. `0` keeps everything.
+pub fn fit_observations(history: Vec<MemoryEntry>, budget: usize) -> Vec<MemoryEntry> {
+    let mut fitted = FittedHistory::new(budget);
+    for entry in history {
+        fitted.push(entry);
+    }
+    fitted.entries
+}
+
+/// The per-task history in `memory`, fitted to `budget`. Read a page at
+/// a time, so only the page being read and output that fits `budget` are
+/// ever held whole.
+pub async fn load_history(memory: &dyn Memory, budget: usize) -> Result<FittedHistory> {
+    let mut fitted = FittedHistory::new(budget);
+    let len = memory.history_len().await?;
+    for start in (0..len).step_by(HISTORY_PAGE_SIZE) {
+        let page = memory.history_page(start, HISTORY_PAGE_SIZE).await?;
+        for entry in page {
+            fitted.push(entry);
+        }
+    }
+    Ok(fitted)
+}
+
+/// A task's history as [`fit_observations`] would shape it, kept that way
+/// as entries are added.
+#[derive(Debug, Default)]
+pub struct FittedHistory {
+    entries: Vec<MemoryEntry>,
+    budget: usize,
+    /// Iterations still whole, oldest first, as their index in `entries`
+    /// and the bytes of output they hold.
+    whole: VecDeque<(usize, usize)>,
+    /// Bytes of output in `whole`.
+    used: usize,
+}
+
+impl FittedHistory {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    pub fn entries(&self) -> &[MemoryEntry] {
+        &self.entries
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.budget);
+    }
+
+    /// Add `entry`, then shorten the oldest whole iterations until the
+    /// rest fit the budget. Once one iteration is cut, every older one is
+    /// too, so the model never sees a gap in the middle of its recent work.
+    pub fn push(&mut self, entry: MemoryEntry) {
+        if let MemoryEntry::Iteration { results, .. } = &entry
+            && self.budget > 0
+        {
+            let size = results.iter().map(|r| output(&r.outcome).len()).sum();
+            self.whole.push_back((self.entries.len(), size));
+            self.used += size;
+        }
+        self.entries.push(entry);
+        // The latest iteration stays whole even over budget
+        while self.used > self.budget
+            && self.whole.len() > 1
+            && let Some((i, size)) = self.whole.pop_front()
+        {
+            self.used -= size;
+            if let MemoryEntry::Iteration { results, .. } = &mut self.entries[i] {
+                shorten(results);
+            }
+        }
+    }
+}
+
+fn shorten(results: &mut [ToolResult]) {
+    for result in results {
+        result.outcome = match &result.outcome {
+            Outcome::Success(out) => Outcome::Success(summarize(out)),
+            Outcome::Error(err) => Outcome::Error(summarize(err)),
+        };
+    }
+}
+
+fn output(outcome: &Outcome) -> &str {
+    let (Outcome::Success(out) | Outcome::Error(out)) = outcome;
+    out
+}
+
+/// The first line of `text`, cut short, and how much was left out.
+fn summarize(text: &str) -> String {
+    let first = text.lines().next().unwrap_or("");
+    let first = match first.char_indices().nth(OBSERVATION_SUMMARY_WIDTH) {
+        Some((i, _)) => &first[..i],
+        None => first,
+    };
+    if first.len() == text.len() {
+        return text.to_string();
+    }
+    format!(
+        "{first}… [{} bytes, {} lines; older output shortened]",
+        format_number(text.len() as u64),
+        format_number(text.lines().count() as u64)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::in_memory::InMemoryMemory;
+
+    fn iteration(out: &str) -> MemoryEntry {
+        MemoryEntry::Iteration {
+            thought: "look".to_string(),
+            calls: vec![],
+            results: vec![ToolResult {
+                tool: "shell".to_string(),
+                outcome: Outcome::Success(out.to_string()),
+            }],
+        }
+    }
+
+    fn outputs(history: &[MemoryEntry]) -> Vec<String> {
+        history
+            .iter()
+            .filter_map(|entry| match entry {
+                MemoryEntry::Iteration { results, .. } => {
+                    Some(output(&results[0].outcome).to_string())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_latest_iterations_verbatim() {
+        let big = "line\n".repeat(100);
+        let history = vec![
+            MemoryEntry::Task {
+                content: "t".to_string(),
+            },
+            iteration(&big),
+            iteration("a\nb"),
+            iteration(&big),
+            iteration(&big),
+            iteration(&big),
+        ];
+
+        let outputs = outputs(&fit_observations(history, 1200));
+        assert_eq!(
+            outputs[0],
+            "line… [500 bytes, 100 lines; older output shortened]"
+        );
+        // Would fit, but is older than a shortened iteration
+        assert_eq!(outputs[1], "a… [3 bytes, 2 lines; older output shortened]");
+        assert!(outputs[2].ends_with("shortened]"));
+        assert_eq!(outputs[3], big);
+        assert_eq!(outputs[4], big);
+    }
+
+    #[test]
+    fn latest_iteration_is_kept_even_over_budget() {
+        let big = "x".repeat(500);
+        let outputs = outputs(&fit_observations(
+            vec![iteration(&big), iteration(&big)],
+            100,
+        ));
+        assert!(outputs[0].ends_with("shortened]"));
+        assert_eq!(outputs[1], big);
+    }
+
+    #[tokio::test]
+    async fn loading_by_pages_fits_like_the_whole() {
+        let memory = InMemoryMemory::new();
+        let mut history = vec![MemoryEntry::Task {
+            content: "t".to_string(),
+        }];
+        for i in 0..HISTORY_PAGE_SIZE * 2 + 5 {
+            history.push(iteration(&format!("{i}\n{}", "x".repeat(100))));
+        }
+        for entry in &history {
+            memory.store(entry.clone()).await.unwrap();
+        }
+
+        let loaded = load_history(&memory, 1000).await.unwrap();
+        let loaded = loaded.entries();
+        assert_eq!(loaded.len(), history.len());
+        assert!(matches!(&loaded[0], MemoryEntry::Task { content } if content == "t"));
+        assert_eq!(outputs(loaded), outputs(&fit_observations(history, 1000)));
+    }
+
+    #[test]
+    fn fits_as_it_grows_like_refitting_every_step() {
+        let mut fitted = FittedHistory::new(700);
+        let mut history = Vec::new();
+        for size in [300, 50, 400, 200, 900, 100, 100] {
+            let entry = iteration(&format!("{size}\n{}", "x".repeat(size)));
+            history.push(entry.clone());
+            fitted.push(entry);
+            assert_eq!(
+                outputs(fitted.entries()),
+                outputs(&fit_observations(history.clone(), 700))
+            );
+        }
+    }
+
+    #[test]
+    fn zero_budget_keeps_everything() {
+        let big = "x".repeat(500);
+        let outputs = outputs(&fit_observations(vec![iteration(&big), iteration(&big)], 0));
+        assert_eq!(outputs, [big.clone(), big]);
+    }
+}
@@ -1,19 +1,62 @@
 use anyhow::{Result, bail};
 use async_trait::async_trait;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
-use super::Engine;
-use crate::consts::DEFAULT_SESSION_HISTORY_LIMIT;
-use crate::memory::{Memory, MemoryEntry};
+use super::hooks::{Hook, review_call};
+use super::meter::{Caller, Charges, Meter};
+use super::observations::{FittedHistory, load_history};
+use super::{Engine, Interrupted, TaskResult, TaskStatus};
+use crate::banner::one_line;
+use crate::consts::{
+    DEFAULT_OBSERVATION_BUDGET, DEFAULT_SESSION_HISTORY_LIMIT, MAX_RETRY_DELAY, PHASE_DETAIL_CHARS,
+    SEMANTIC_MIN_SCORE, SEMANTIC_RECALL_LIMIT, SESSION_SUMMARY_BATCH, format_number,
+};
+use crate::events::{Event, EventBus, EventKind, Progress, TaskError};
+use crate::memory::facts::FactStore;
+use crate::memory::{HitSource, Memory, MemoryEntry, SemanticHit, SessionEntry};
+use crate::metrics::{MetricsStore, TaskMetrics};
+use crate::prompts::persona::Persona;
+use crate::prompts::{FINAL_ANSWER_REQUEST, PLAN_REQUEST, build_reflection_prompt};
 use crate::spinner::Spinner;
-use crate::thinker::{Context, Step, Thinker, TokenUsage};
-use crate::tools::{Outcome, ToolRegistry, ToolResult};
+use crate::telemetry::prometheus::Prometheus;
+use crate::telemetry::{Span, Tracer};
+use crate::thinker::{Context, IterationBudget, Step, StepResult, Thinker, TokenUsage, ToolCall};
+use crate::tools::{Outcome, ToolRegistry, ToolResult, format_args};
+use crate::transcript::{Transcript, TranscriptEvent};
 
+#[derive(Debug, Clone)]
 pub struct ReactConfig {
     pub max_iterations: usize,
     pub tool_timeout: Duration,
+    /// After this many iterations in a row where every tool call failed,
+    /// ask the model to reflect and change approach. `0` never does.
+    pub reflect_after: usize,
+    /// Stop a task once it has used more than this many tokens.
+    pub max_tokens_per_task: Option<u64>,
+    /// Stop a task once it has cost more than this many USD. Ignored for
+    /// models without a known price.
+    pub max_cost_per_task: Option<f64>,
+    /// How many times a failed thinker call is retried before the task
+    /// fails. `0` never retries.
+    pub thinker_retries: u32,
+    /// Wait before the first retry; doubled for each one after, up to
+    /// [`MAX_RETRY_DELAY`].
+    pub retry_delay: Duration,
+    /// Bytes of tool output shown to the thinker verbatim, newest first;
+    /// older output is shortened. `0` shows everything.
+    pub observation_budget: usize,
+    /// Print thoughts, tool calls and their output to stdout as the task
+    /// runs. Off when stdout is for the result alone.
+    pub echo: bool,
+    /// Extra instructions for how the agent works and talks, from
+    /// `/persona`.
+    pub persona: Option<&'static Persona>,
+    /// The language thoughts and answers are written in, from
+    /// `--language` or `/config set language`.
+    pub language: Option<String>,
 }
 
 impl Default for ReactConfig {
@@ -21,17 +64,117 @@ impl Default for ReactConfig {
         Self {
             max_iterations: 20,
             tool_timeout: Duration::from_secs(30),
+            reflect_after: 3,
+            max_tokens_per_task: None,
+            max_cost_per_task: None,
+            thinker_retries: 2,
+            retry_delay: Duration::from_secs(2),
+            observation_budget: DEFAULT_OBSERVATION_BUDGET,
+            echo: true,
+            persona: None,
+            language: None,
         }
     }
 }
 
+impl ReactConfig {
+    /// This config with its budgets cut to what a task that has run
+    /// `spent` iterations and used `usage` on `model` has left, for a
+    /// sub-agent working on its behalf.
+    fn remaining(&self, spent: usize, usage: TokenUsage, model: &str, allowance: u32) -> Self {
+        Self {
+            max_iterations: self.max_iterations.saturating_sub(spent),
+            max_tokens_per_task: self
+                .max_tokens_per_task
+                .map(|max| (max * u64::from(allowance)).saturating_sub(usage.total())),
+            max_cost_per_task: self.max_cost_per_task.map(|max| {
+                let spent = usage.cost(model).unwrap_or(0.0);
+                (max * f64::from(allowance) - spent).max(0.0)
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Why `usage` on `model` is over this config's budget, if it is.
+    /// `allowance` multiplies the budget after the user agreed to continue.
+    fn over_budget(&self, usage: TokenUsage, model: &str, allowance: u32) -> Option<String> {
+        if let Some(max) = self.max_tokens_per_task {
+            let max = max * u64::from(allowance);
+            if usage.total() > max {
+                return Some(format!(
+                    "token budget exceeded: used {} of {} tokens",
+                    format_number(usage.total()),
+                    format_number(max)
+                ));
+            }
+        }
+        if let (Some(max), Some(cost)) = (self.max_cost_per_task, usage.cost(model)) {
+            let max = max * f64::from(allowance);
+            if cost > max {
+                return Some(format!(
+                    "cost budget exceeded: spent ${cost:.2} of ${max:.2}"
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Decides whether a task that went over its budget may keep going.
+#[async_trait]
+pub trait BudgetApprover: Send + Sync {
+    async fn extend(&self, reason: &str) -> Result<bool>;
+}
+
+/// Asks the user at the terminal.
+pub struct PromptBudgetApprover;
+
+#[async_trait]
+impl BudgetApprover for PromptBudgetApprover {
+    async fn extend(&self, reason: &str) -> Result<bool> {
+        crate::prompt::terminal()
+            .confirm(&format!("  {reason}. Continue with another budget? [y/N] "))
+            .await
+    }
+}
+
 /// The ReAct loop. Wires together a Thinker, ToolRegistry, and Memory.
 pub struct ReactEngine {
     thinker: Arc<RwLock<Box<dyn Thinker>>>,
     tools: Arc<ToolRegistry>,
     memory: Box<dyn Memory>,
+    /// The task in flight's per-task memory, fitted to the observation
+    /// budget as it grows, so each iteration's context appends to it
+    /// instead of reading it all back or refitting it.
+    history: FittedHistory,
     config: ReactConfig,
     session_usage: TokenUsage,
+    /// `session_usage` split by the model that used it, in order of first
+    /// use.
+    model_usage: Vec<(String, TokenUsage)>,
+    transcript: Option<Arc<Transcript>>,
+    tracer: Option<Arc<Tracer>>,
+    prometheus: Option<Arc<Prometheus>>,
+    facts: Option<Arc<FactStore>>,
+    metrics: Option<Arc<MetricsStore>>,
+    budget_approver: Option<Box<dyn BudgetApprover>>,
+    cancel: CancellationToken,
+    hooks: Vec<Arc<dyn Hook>>,
+    events: Arc<EventBus>,
+    /// The task in flight's last [`Event::Progress`], for new spinners.
+    status: std::sync::Mutex<Option<Progress>>,
+}
+
+/// Running counters for the task in flight.
+#[derive(Default)]
+struct TaskProgress {
+    iterations: u64,
+    tool_calls: u64,
+    usage: TokenUsage,
+    /// The answer was asked for after the iteration budget ran out.
+    partial: bool,
+    /// The task's span, which its iterations are traced inside.
+    span: Span,
 }
 
 impl ReactEngine {
@@ -40,21 +183,186 @@ impl ReactEngine {
         tools: Arc<ToolRegistry>,
         memory: Box<dyn Memory>,
         config: ReactConfig,
+    ) -> Self {
+        Self::with_shared_thinker(Arc::new(RwLock::new(thinker)), tools, memory, config)
+    }
+
+    /// Build an engine around a thinker other engines also use, so a
+    /// sub-agent follows the parent's `/model` switches.
+    pub fn with_shared_thinker(
+        thinker: Arc<RwLock<Box<dyn Thinker>>>,
+        tools: Arc<ToolRegistry>,
+        memory: Box<dyn Memory>,
+        config: ReactConfig,
     ) -> Self {
         Self {
-            thinker: Arc::new(RwLock::new(thinker)),
+            thinker,
             tools,
             memory,
+            history: FittedHistory::new(config.observation_budget),
             config,
             session_usage: TokenUsage::default(),
+            model_usage: Vec::new(),
+            transcript: None,
+            tracer: None,
+            prometheus: None,
+            facts: None,
+            metrics: None,
+            budget_approver: None,
+            cancel: CancellationToken::new(),
+            hooks: Vec::new(),
+            events: Arc::new(EventBus::default()),
+            status: std::sync::Mutex::new(None),
         }
     }
 
     /// Swap the thinker at runtime. The next iteration will use the new one.
-    pub async fn set_thinker(&self, thinker: Box<dyn Thinker>) {
+    pub async fn set_thinker(&self, mut thinker: Box<dyn Thinker>) {
+        if let Some(transcript) = &self.transcript {
+            thinker.set_transcript(Arc::clone(transcript));
+        }
         *self.thinker.write().await = thinker;
     }
 
+    /// Log tasks, tool executions, and raw model traffic to `transcript`.
+    pub async fn set_transcript(&mut self, transcript: Arc<Transcript>) {
+        self.thinker
+            .write()
+            .await
+            .set_transcript(Arc::clone(&transcript));
+        self.transcript = Some(transcript);
+    }
+
+    /// Trace every task, iteration, model call and tool call to `tracer`.
+    pub fn set_tracer(&mut self, tracer: Arc<Tracer>) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Count tasks, tokens and tool latency in `prometheus`.
+    pub fn set_prometheus(&mut self, prometheus: Arc<Prometheus>) {
+        self.prometheus = Some(prometheus);
+    }
+
+    /// Include long-term facts from `facts` in every task's context.
+    pub fn set_facts(&mut self, facts: Arc<FactStore>) {
+        self.facts = Some(facts);
+    }
+
+    /// Record duration, iterations, tool calls, and usage of every task in `metrics`.
+    pub fn set_metrics(&mut self, metrics: Arc<MetricsStore>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Ask `approver` whether an over-budget task may continue instead of
+    /// stopping it right away.
+    pub fn set_budget_approver(&mut self, approver: Box<dyn BudgetApprover>) {
+        self.budget_approver = Some(approver);
+    }
+
+    /// Stop the task in flight when `cancel` fires: running tools are
+    /// killed, the step is stored with "interrupted" observations, and the
+    /// task fails with [`Interrupted`]. Tokens can't be reset, so set a
+    /// fresh one before each task.
+    pub fn set_cancellation(&mut self, cancel: CancellationToken) {
+        self.cancel = cancel;
+    }
+
+    /// Call `hook` at every step, tool call and task end, after the hooks
+    /// added before it.
+    pub fn add_hook(&mut self, hook: Arc<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    /// Emit an [`Event::Phase`] on `events` whenever the task starts
+    /// waiting on something new.
+    pub fn set_events(&mut self, events: Arc<EventBus>) {
+        self.events = events;
+    }
+
+    /// Announce `phase` and show it on a spinner, which later phases
+    /// replace, until the spinner is stopped.
+    fn spin(&self, phase: &str) -> Spinner {
+        let status = *self.status.lock().expect("status lock poisoned");
+        let events = self
+            .events
+            .subscribe_filtered(&[EventKind::Phase, EventKind::Progress]);
+        let spinner = Spinner::follow(phase, status, events);
+        self.events.emit(Event::Phase {
+            phase: phase.to_string(),
+        });
+        spinner
+    }
+
+    /// Count `usage` of one model call towards the session and the model
+    /// that made it, and announce it.
+    async fn record_usage(&mut self, usage: TokenUsage) {
+        let model = self.thinker.read().await.model().to_string();
+        self.record_model_usage(model, usage);
+    }
+
+    /// Count `usage` by `model` towards the session, and announce it.
+    fn record_model_usage(&mut self, model: String, usage: TokenUsage) {
+        self.session_usage.add(usage);
+        match self.model_usage.iter_mut().find(|(m, _)| *m == model) {
+            Some((_, total)) => total.add(usage),
+            None => self.model_usage.push((model, usage)),
+        }
+        self.events.emit(Event::TokenUsageRecorded { usage });
+    }
+
+    /// Announce the task's counters to spinners and other status displays.
+    async fn report(&self, progress: &TaskProgress) {
+        let model = self.thinker.read().await.model().to_string();
+        let status = Progress {
+            iteration: progress.iterations as usize,
+            max_iterations: self.config.max_iterations,
+            tokens: progress.usage.total(),
+            cost: progress.usage.cost(&model),
+        };
+        *self.status.lock().expect("status lock poisoned") = Some(status);
+        self.events.emit(Event::Progress(status));
+    }
+
+    /// The tools tasks can call.
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// The settings tasks run with.
+    pub fn config(&self) -> &ReactConfig {
+        &self.config
+    }
+
+    /// Give tasks started from now on `max` iterations.
+    pub fn set_max_iterations(&mut self, max: usize) {
+        self.config.max_iterations = max;
+    }
+
+    /// Work as `persona`, or as none, from the next step on.
+    pub fn set_persona(&mut self, persona: Option<&'static Persona>) {
+        self.config.persona = persona;
+    }
+
+    /// Write thoughts and answers in `language`, or in whatever language
+    /// the model picks, from the next step on.
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.config.language = language;
+    }
+
+    /// Cut off tool calls after `timeout`, from the next step on.
+    pub fn set_tool_timeout(&mut self, timeout: Duration) {
+        self.config.tool_timeout = timeout;
+    }
+
+    /// The last `limit` tasks recorded in metrics, newest first. Empty
+    /// without a metrics store.
+    pub fn recent_tasks(&self, limit: usize) -> anyhow::Result<Vec<crate::metrics::TaskRecord>> {
+        match &self.metrics {
+            Some(metrics) => metrics.recent(limit),
+            None => Ok(vec![]),
+        }
+    }
+
     /// Access memory history (useful for tests and inspection).
     pub async fn history(&self) -> Result<Vec<MemoryEntry>> {
         self.memory.history().await
@@ -65,6 +373,12 @@ impl ReactEngine {
         self.session_usage
     }
 
+    /// Session token usage per model, in the order the models were first
+    /// used.
+    pub fn session_usage_by_model(&self) -> &[(String, TokenUsage)] {
+        &self.model_usage
+    }
+
     /// Get the current model name from the thinker.
     pub async fn model(&self) -> String {
         let thinker = self.thinker.read().await;
@@ -90,125 +404,857 @@ impl ReactEngine {
             .await
     }
 
+    /// Past tasks from every session matching `query` within a time range.
+    pub async fn search_session(
+        &self,
+        query: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> anyhow::Result<Vec<crate::memory::SessionMatch>> {
+        self.memory.search_session(query, since, until).await
+    }
+
+    /// Every task in the active session with its transcript, oldest first.
+    pub async fn session_records(&self) -> anyhow::Result<Vec<crate::memory::SessionRecord>> {
+        self.memory.session_records().await
+    }
+
+    /// Name of the active named session.
+    pub async fn active_session(&self) -> anyhow::Result<String> {
+        self.memory.active_session().await
+    }
+
+    /// All named sessions, oldest first.
+    pub async fn sessions(&self) -> anyhow::Result<Vec<crate::memory::NamedSession>> {
+        self.memory.list_sessions().await
+    }
+
+    /// Create a named session without switching to it.
+    pub async fn create_session(&self, name: &str) -> anyhow::Result<()> {
+        self.memory.create_session(name).await
+    }
+
+    /// Scope session history to another named session.
+    pub async fn switch_session(&self, name: &str) -> anyhow::Result<()> {
+        self.memory.switch_session(name).await?;
+        self.events.emit(Event::SessionChanged {
+            session: name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Full-text search over everything stored in memory, best match first.
+    pub async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<crate::memory::RecallHit>> {
+        self.memory.recall_snippets(query, limit).await
+    }
+
+    /// Entry counts and index sizes of the underlying memory.
+    pub async fn memory_stats(&self) -> anyhow::Result<crate::memory::MemoryStats> {
+        self.memory.stats().await
+    }
+
+    /// Once session history outgrows the context limit, fold the oldest
+    /// entries into one summary entry instead of silently dropping them.
+    async fn compact_session_history(&mut self) -> Result<()> {
+        let len = self.memory.session_len().await?;
+        if len <= DEFAULT_SESSION_HISTORY_LIMIT {
+            return Ok(());
+        }
+        let count = (len - DEFAULT_SESSION_HISTORY_LIMIT + SESSION_SUMMARY_BATCH).min(len);
+        let history = self.memory.session_history(len).await?;
+        let oldest = &history[..count];
+
+        let result = {
+            let spinner = self.spin("summarizing session...");
+            let thinker = self.thinker.read().await;
+            let result = thinker.summarize(oldest).await;
+            spinner.stop().await;
+            result?
+        };
+        if let Some(usage) = result.usage {
+            self.record_usage(usage).await;
+        }
+
+        self.memory
+            .compact_session(
+                count,
+                SessionEntry {
+                    task: format!("(summary of {count} earlier tasks)"),
+                    answer: result.text,
+                },
+            )
+            .await
+    }
+
+    /// Ask the thinker what it would do for `task`, without running any
+    /// tool or storing anything. Returns the plan as text.
+    pub async fn plan(&mut self, task: &str) -> Result<String> {
+        let (session_history, relevant_memory) = self.load_context(task).await?;
+        let context = Context {
+            task: task.to_string(),
+            history: vec![MemoryEntry::Task {
+                content: task.to_string(),
+            }],
+            session_history,
+            relevant_memory,
+            facts: match &self.facts {
+                Some(store) => store.list()?,
+                None => Vec::new(),
+            },
+            available_tools: self.tools.descriptions().await,
+            note: Some(PLAN_REQUEST.to_string()),
+            iteration: None,
+            persona: self.config.persona,
+            language: self.config.language.clone(),
+        };
+
+        let result = {
+            let spinner = self.spin("planning...");
+            let thinker = self.thinker.read().await;
+            let result = thinker.next_step(&context).await;
+            spinner.stop().await;
+            result?
+        };
+        if let Some(usage) = result.usage {
+            self.record_usage(usage).await;
+        }
+
+        Ok(match result.step {
+            Step::Finish { answer, .. } => answer,
+            // The thinker jumped ahead; its first calls are the plan it has
+            Step::Act { thought, calls } => {
+                let mut plan = format!("{thought}\n");
+                for (i, call) in calls.iter().enumerate() {
+                    plan.push_str(&format!(
+                        "{}. {}: {}\n",
+                        i + 1,
+                        call.tool,
+                        format_args(&call.args)
+                    ));
+                }
+                plan
+            }
+        })
+    }
+
     /// Clear session history (e.g. from `/new` command).
     pub async fn clear_session(&self) -> anyhow::Result<()> {
         self.memory.clear_session().await
     }
 }
 
-#[async_trait]
-impl Engine for ReactEngine {
-    async fn run(&mut self, task: &str) -> Result<String> {
-        // Load session history before clearing per-task memory
+impl ReactEngine {
+    /// Start `task` from scratch and run the ReAct loop on it.
+    async fn run_task(&mut self, task: &str, progress: &mut TaskProgress) -> Result<String> {
+        // A failed summary only costs old context; don't fail the task over it
+        if let Err(e) = self.compact_session_history().await {
+            eprintln!("  warning: failed to summarize session history: {e}");
+        }
+
+        // Load session context before clearing per-task memory
+        let (session_history, relevant_memory) = self.load_context(task).await?;
+
+        // Each task starts with a clean slate (per-task memory only)
+        self.memory.clear().await?;
+        self.history.clear();
+
+        if let Some(transcript) = &self.transcript {
+            transcript.record(TranscriptEvent::Task {
+                task: task.to_string(),
+            });
+        }
+
+        self.remember(MemoryEntry::Task {
+            content: task.to_string(),
+        })
+        .await?;
+
+        self.react_loop(task, session_history, relevant_memory, progress)
+            .await
+    }
+
+    /// Session history and related memories to show alongside `task`.
+    async fn load_context(&self, task: &str) -> Result<(Vec<SessionEntry>, Vec<SemanticHit>)> {
         let session_history = self
             .memory
             .session_history(DEFAULT_SESSION_HISTORY_LIMIT)
             .await?;
 
-        // Each task starts with a clean slate (per-task memory only)
-        self.memory.clear().await?;
-
-        self.memory
-            .store(MemoryEntry::Task {
-                content: task.to_string(),
+        // Pull in older answers and observations that look related. Skip
+        // answers already present in the recent session history.
+        let hits = match self
+            .memory
+            .recall_semantic(task, SEMANTIC_RECALL_LIMIT)
+            .await
+        {
+            Ok(hits) => hits,
+            Err(e) => {
+                eprintln!("  warning: semantic recall failed: {e:#}");
+                Vec::new()
+            }
+        };
+        let relevant_memory: Vec<_> = hits
+            .into_iter()
+            .filter(|hit| hit.score >= SEMANTIC_MIN_SCORE)
+            .filter(|hit| {
+                hit.source != HitSource::Session
+                    || !session_history
+                        .iter()
+                        .any(|s| s.task == hit.task && s.answer == hit.text)
             })
-            .await?;
+            .collect();
+        Ok((session_history, relevant_memory))
+    }
 
-        for iteration in 0..self.config.max_iterations {
-            let context = Context {
-                task: task.to_string(),
-                history: self.memory.history().await?,
-                session_history: session_history.clone(),
-                available_tools: self.tools.descriptions().await,
+    /// The ReAct loop, continuing from whatever per-task memory holds.
+    /// Counters in `progress` are kept up to date as it goes, so they are
+    /// accurate even when the task fails.
+    async fn react_loop(
+        &mut self,
+        task: &str,
+        session_history: Vec<SessionEntry>,
+        relevant_memory: Vec<SemanticHit>,
+        progress: &mut TaskProgress,
+    ) -> Result<String> {
+        let mut failed_streak = 0;
+        let mut reflection = None;
+        let mut allowance = 1;
+        // Iterations used, sub-agents' included
+        let mut spent = 0;
+        while spent < self.config.max_iterations {
+            let iteration = spent;
+            spent += 1;
+            if self.cancel.is_cancelled() {
+                return Err(self.interrupted());
+            }
+            progress.iterations += 1;
+            self.events.emit(Event::IterationStarted {
+                iteration: iteration + 1,
+            });
+            self.report(progress).await;
+            let mut iteration_span = progress.span.child("iteration");
+            iteration_span.set("golem.iteration", iteration + 1);
+            let budget = IterationBudget {
+                current: iteration + 1,
+                max: self.config.max_iterations,
             };
+            let note = reflection.take();
+            let mut context = self
+                .step_context(
+                    task,
+                    &session_history,
+                    &relevant_memory,
+                    note.clone(),
+                    Some(budget),
+                )
+                .await?;
+
+            for hook in &self.hooks {
+                hook.on_step_start(&context).await;
+            }
 
-            let step_result = {
-                let spinner = Spinner::start("thinking...");
-                let thinker = self.thinker.read().await;
-                let result = thinker.next_step(&context).await;
+            let mut retries = 0;
+            let step_result = loop {
+                let result = {
+                    let spinner = self.spin("thinking...");
+                    let thinker = self.thinker.read().await;
+                    let mut span = model_span(&iteration_span, thinker.model());
+                    let result = tokio::select! {
+                        result = thinker.next_step(&context) => Some(result),
+                        _ = self.cancel.cancelled() => None,
+                    };
+                    spinner.stop().await;
+                    trace_step(&mut span, result.as_ref());
+                    match result {
+                        Some(result) => result,
+                        None => return Err(self.interrupted()),
+                    }
+                };
+                let error = match result {
+                    Ok(step_result) => break step_result,
+                    Err(e) if retries < self.config.thinker_retries => e,
+                    Err(e) => return Err(e),
+                };
+
+                // Saturates, as `--retries` can be large enough to overflow
+                let factor = 2u32.checked_pow(retries).unwrap_or(u32::MAX);
+                let delay = self
+                    .config
+                    .retry_delay
+                    .saturating_mul(factor)
+                    .min(MAX_RETRY_DELAY);
+                retries += 1;
+                if self.config.echo {
+                    println!(
+                        "[iteration {}] thinker error: {error}; retrying in {:.1}s ({retries}/{})",
+                        iteration + 1,
+                        delay.as_secs_f64(),
+                        self.config.thinker_retries
+                    );
+                }
+                self.remember(MemoryEntry::Retry {
+                    error: error.to_string(),
+                })
+                .await?;
+                let spinner = self.spin(&format!(
+                    "retrying after {}",
+                    one_line(&error.to_string(), PHASE_DETAIL_CHARS)
+                ));
+                let waited = tokio::select! {
+                    _ = tokio::time::sleep(delay) => true,
+                    _ = self.cancel.cancelled() => false,
+                };
                 spinner.stop().await;
-                result?
+                if !waited {
+                    return Err(self.interrupted());
+                }
+                // Rebuilt so the retry shows up in history
+                context = self
+                    .step_context(
+                        task,
+                        &session_history,
+                        &relevant_memory,
+                        note.clone(),
+                        Some(budget),
+                    )
+                    .await?;
             };
 
             if let Some(usage) = step_result.usage {
-                self.session_usage.add(usage);
+                self.record_usage(usage).await;
+                progress.usage.add(usage);
+                self.report(progress).await;
+            }
+
+            for hook in &self.hooks {
+                hook.on_step(&step_result.step).await;
             }
+            let (Step::Act { thought, .. } | Step::Finish { thought, .. }) = &step_result.step;
+            self.events.emit(Event::ThoughtProduced {
+                thought: thought.clone(),
+            });
 
             match step_result.step {
                 Step::Act { thought, calls } => {
-                    println!("\n[iteration {}] Thought: {}", iteration + 1, thought);
-                    println!(
-                        "[iteration {}] Executing {} tool call(s)...",
-                        iteration + 1,
-                        calls.len()
-                    );
+                    if self.config.echo {
+                        println!("\n[iteration {}] Thought: {}", iteration + 1, thought);
+                        println!(
+                            "[iteration {}] Executing {} tool call(s)...",
+                            iteration + 1,
+                            calls.len()
+                        );
+                    }
+                    progress.tool_calls += calls.len() as u64;
+
+                    let mut reviewed = Vec::with_capacity(calls.len());
+                    for call in calls {
+                        reviewed.push(review_call(&self.hooks, call).await?);
+                    }
+                    let calls: Vec<ToolCall> =
+                        reviewed.iter().map(|(call, _)| call.clone()).collect();
 
                     let timeout = self.config.tool_timeout;
                     let tools = Arc::clone(&self.tools);
+                    let transcript = self.transcript.clone();
+                    let cancel = self.cancel.clone();
+                    let iteration_span = &iteration_span;
+                    let prometheus = self.prometheus.as_deref();
+                    let events = &self.events;
 
-                    let futures: Vec<_> = calls
+                    let futures: Vec<_> = reviewed
                         .into_iter()
-                        .map(|call| {
+                        .map(|(call, denial)| {
                             let tools = Arc::clone(&tools);
+                            let transcript = transcript.clone();
+                            let cancel = cancel.clone();
                             async move {
-                                match tokio::time::timeout(
-                                    timeout,
-                                    tools.execute(&call.tool, &call.args),
-                                )
-                                .await
-                                {
-                                    Ok(result) => result,
-                                    Err(_) => ToolResult {
-                                        tool: call.tool,
-                                        outcome: Outcome::Error("timed out".to_string()),
+                                events.emit(Event::ToolCallStarted { call: call.clone() });
+                                let started = Instant::now();
+                                let mut span =
+                                    iteration_span.child(format!("execute_tool {}", call.tool));
+                                span.set("gen_ai.tool.name", call.tool.as_str());
+                                span.set("golem.tool.args", format_args(&call.args));
+                                let execution = async {
+                                    if let Some(reason) = denial {
+                                        return ToolResult {
+                                            tool: call.tool.clone(),
+                                            outcome: Outcome::Error(format!("denied: {reason}")),
+                                        };
+                                    }
+                                    // Waiting on the user doesn't count against the timeout
+                                    if let Err(refusal) = tools.approve(&call.tool, &call.args).await
+                                    {
+                                        return refusal;
+                                    }
+                                    let execution = tools.execute_approved(&call.tool, &call.args);
+                                    if !tools.uses_timeout(&call.tool).await {
+                                        return execution.await;
+                                    }
+                                    match tokio::time::timeout(timeout, execution).await {
+                                        Ok(result) => result,
+                                        Err(_) => ToolResult {
+                                            tool: call.tool.clone(),
+                                            outcome: Outcome::Error(format!(
+                                                "timed out after {}s; process terminated",
+                                                timeout.as_secs()
+                                            )),
+                                        },
+                                    }
+                                };
+                                // Dropping the execution kills its processes
+                                let result = tokio::select! {
+                                    result = execution => result,
+                                    _ = cancel.cancelled() => ToolResult {
+                                        tool: call.tool.clone(),
+                                        outcome: Outcome::Error(
+                                            "interrupted by the user; process terminated".to_string(),
+                                        ),
                                     },
+                                };
+                                if let Outcome::Error(err) = &result.outcome {
+                                    span.fail(err);
                                 }
+                                events.emit(Event::ToolCallFinished {
+                                    result: result.clone(),
+                                    duration: started.elapsed(),
+                                });
+                                if let Some(prometheus) = prometheus {
+                                    prometheus.tool_finished(
+                                        &call.tool,
+                                        started.elapsed(),
+                                        matches!(result.outcome, Outcome::Success(_)),
+                                    );
+                                }
+                                if let Some(transcript) = transcript {
+                                    let (success, output) = match &result.outcome {
+                                        Outcome::Success(out) => (true, out.clone()),
+                                        Outcome::Error(err) => (false, err.clone()),
+                                    };
+                                    transcript.record(TranscriptEvent::Tool {
+                                        tool: call.tool,
+                                        args: call.args,
+                                        success,
+                                        output,
+                                        duration_ms: started.elapsed().as_millis() as u64,
+                                    });
+                                }
+                                result
                             }
                         })
                         .collect();
 
-                    let results = futures::future::join_all(futures).await;
+                    let spinner = tool_phase(&self.tools, &calls)
+                        .await
+                        .map(|phase| self.spin(&phase));
+                    let model = self.thinker.read().await.model().to_string();
+                    let meter = Meter::new(Caller {
+                        config: self
+                            .config
+                            .remaining(spent, progress.usage, &model, allowance),
+                        hooks: self.hooks.clone(),
+                        events: Arc::clone(&self.events),
+                    });
+                    let results = meter.run(futures::future::join_all(futures)).await;
+                    if let Some(spinner) = spinner {
+                        spinner.stop().await;
+                    }
+                    // What sub-agents spent is the task's, too
+                    let charges = meter.take();
+                    if charges != Charges::default() {
+                        spent += charges.iterations;
+                        progress.iterations += charges.iterations as u64;
+                        for (model, usage) in charges.usage {
+                            self.record_model_usage(model, usage);
+                            progress.usage.add(usage);
+                        }
+                        self.report(progress).await;
+                    }
+                    for (call, result) in calls.iter().zip(&results) {
+                        for hook in &self.hooks {
+                            hook.on_tool_result(call, result).await;
+                        }
+                    }
 
-                    for result in &results {
-                        match &result.outcome {
-                            Outcome::Success(out) => {
-                                println!("  [{}] ✓ {}", result.tool, out);
-                            }
-                            Outcome::Error(err) => {
-                                println!("  [{}] ✗ {}", result.tool, err);
+                    if self.config.echo {
+                        for result in &results {
+                            match &result.outcome {
+                                Outcome::Success(out) => {
+                                    println!("  [{}] ✓ {}", result.tool, out);
+                                }
+                                Outcome::Error(err) => {
+                                    println!("  [{}] ✗ {}", result.tool, err);
+                                }
                             }
                         }
                     }
 
-                    self.memory
-                        .store(MemoryEntry::Iteration { thought, results })
-                        .await?;
-                }
+                    let all_failed = !results.is_empty()
+                        && results
+                            .iter()
+                            .all(|r| matches!(r.outcome, Outcome::Error(_)));
+                    failed_streak = if all_failed { failed_streak + 1 } else { 0 };
+                    if self.config.reflect_after > 0 && failed_streak == self.config.reflect_after {
+                        if self.config.echo {
+                            println!(
+                                "[iteration {}] {failed_streak} failed iterations in a row; asking for a new approach",
+                                iteration + 1
+                            );
+                        }
+                        reflection = Some(build_reflection_prompt(failed_streak));
+                        failed_streak = 0;
+                    }
 
-                Step::Finish { thought, answer } => {
-                    println!("\n[done] Thought: {}", thought);
-                    println!("[done] Answer: {}", answer);
+                    self.remember(MemoryEntry::Iteration {
+                        thought,
+                        calls,
+                        results,
+                    })
+                    .await?;
 
-                    self.memory
-                        .store(MemoryEntry::Answer {
-                            thought,
-                            content: answer.clone(),
-                        })
-                        .await?;
+                    if self.cancel.is_cancelled() {
+                        return Err(self.interrupted());
+                    }
 
-                    // Persist task summary for future tasks in this session
-                    self.memory
-                        .store_session(crate::memory::SessionEntry {
-                            task: task.to_string(),
-                            answer: answer.clone(),
-                        })
-                        .await?;
+                    if let Some(reason) = self.config.over_budget(progress.usage, &model, allowance)
+                    {
+                        let extend = match &self.budget_approver {
+                            Some(approver) => approver.extend(&reason).await?,
+                            None => false,
+                        };
+                        if !extend {
+                            bail!("{reason}");
+                        }
+                        allowance += 1;
+                    }
+                }
 
+                Step::Finish { thought, answer } => {
+                    if self.config.echo {
+                        println!("\n[done] Thought: {}", thought);
+                        println!("[done] Answer: {}", answer);
+                    }
+                    self.store_answer(task, thought, &answer, progress.usage)
+                        .await?;
                     return Ok(answer);
                 }
             }
         }
 
-        bail!("max iterations ({}) reached", self.config.max_iterations)
+        self.best_effort_answer(task, &session_history, &relevant_memory, progress)
+            .await
+    }
+
+    /// Everything the thinker sees for the next step of `task`.
+    async fn step_context(
+        &self,
+        task: &str,
+        session_history: &[SessionEntry],
+        relevant_memory: &[SemanticHit],
+        note: Option<String>,
+        iteration: Option<IterationBudget>,
+    ) -> Result<Context> {
+        Ok(Context {
+            task: task.to_string(),
+            history: self.history.entries().to_vec(),
+            session_history: session_history.to_vec(),
+            relevant_memory: relevant_memory.to_vec(),
+            // Reloaded every iteration so a fact remembered mid-task shows up
+            facts: match &self.facts {
+                Some(store) => store.list()?,
+                None => Vec::new(),
+            },
+            available_tools: self.tools.descriptions().await,
+            note,
+            iteration,
+            persona: self.config.persona,
+            language: self.config.language.clone(),
+        })
+    }
+
+    /// Out of iterations: ask once more for the best answer the steps so
+    /// far support, so their work isn't lost. Fails if the thinker still
+    /// wants to act.
+    async fn best_effort_answer(
+        &mut self,
+        task: &str,
+        session_history: &[SessionEntry],
+        relevant_memory: &[SemanticHit],
+        progress: &mut TaskProgress,
+    ) -> Result<String> {
+        let max = self.config.max_iterations;
+        if self.config.echo {
+            println!("\n[iteration {max}] max iterations reached; asking for a best-effort answer");
+        }
+        let context = self
+            .step_context(
+                task,
+                session_history,
+                relevant_memory,
+                Some(FINAL_ANSWER_REQUEST.to_string()),
+                None,
+            )
+            .await?;
+
+        let step_result = {
+            let spinner = self.spin("wrapping up...");
+            let thinker = self.thinker.read().await;
+            let mut span = model_span(&progress.span, thinker.model());
+            let result = tokio::select! {
+                result = thinker.next_step(&context) => Some(result),
+                _ = self.cancel.cancelled() => None,
+            };
+            spinner.stop().await;
+            trace_step(&mut span, result.as_ref());
+            match result {
+                Some(result) => result?,
+                None => return Err(self.interrupted()),
+            }
+        };
+        if let Some(usage) = step_result.usage {
+            self.record_usage(usage).await;
+            progress.usage.add(usage);
+        }
+
+        let Step::Finish { thought, answer } = step_result.step else {
+            bail!("max iterations ({max}) reached");
+        };
+        if self.config.echo {
+            println!("\n[partial] Thought: {thought}");
+            println!("[partial] Answer: {answer}");
+        }
+        progress.partial = true;
+        self.store_answer(task, thought, &answer, progress.usage)
+            .await?;
+        Ok(answer)
+    }
+
+    /// Store the answer that ends `task`, in task memory and as a session
+    /// summary for future tasks.
+    async fn store_answer(
+        &mut self,
+        task: &str,
+        thought: String,
+        answer: &str,
+        usage: TokenUsage,
+    ) -> Result<()> {
+        self.remember(MemoryEntry::Answer {
+            thought,
+            content: answer.to_string(),
+        })
+        .await?;
+        self.memory
+            .store_session(SessionEntry {
+                task: task.to_string(),
+                answer: answer.to_string(),
+            })
+            .await?;
+        self.memory.record_task_usage(usage).await?;
+
+        if let Some(transcript) = &self.transcript {
+            transcript.record(TranscriptEvent::Answer {
+                answer: answer.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Store `entry` in per-task memory, and in the history contexts are
+    /// built from.
+    async fn remember(&mut self, entry: MemoryEntry) -> Result<()> {
+        self.memory.store(entry.clone()).await?;
+        self.history.push(entry);
+        Ok(())
+    }
+
+    /// Note the cancellation in the transcript and build the error for it.
+    fn interrupted(&self) -> anyhow::Error {
+        if let Some(transcript) = &self.transcript {
+            transcript.record(TranscriptEvent::Interrupted);
+        }
+        Interrupted.into()
+    }
+
+    /// Continue the task an interrupted or failed run left in per-task
+    /// memory, keeping the tool work it already did. Gets a fresh
+    /// iteration budget.
+    pub async fn resume(&mut self) -> Result<TaskResult> {
+        let Some(task) = self.memory.unfinished_task().await? else {
+            bail!("no unfinished task to resume");
+        };
+        if self.config.echo {
+            println!("Resuming: {task}");
+        }
+        // The one time per-task memory is read back: it holds a past run
+        self.history = load_history(self.memory.as_ref(), self.config.observation_budget).await?;
+
+        let started = Instant::now();
+        let mut progress = TaskProgress {
+            span: self.task_span(&task).await,
+            ..TaskProgress::default()
+        };
+        self.events.emit(Event::TaskStarted { task: task.clone() });
+        for hook in &self.hooks {
+            hook.on_task_start(&task).await;
+        }
+        let result = match self.load_context(&task).await {
+            Ok((session_history, relevant_memory)) => {
+                self.react_loop(&task, session_history, relevant_memory, &mut progress)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.finish_task(&task, started, progress, result).await
+    }
+
+    /// The span a task is traced in, with no parent.
+    async fn task_span(&self, task: &str) -> Span {
+        let Some(tracer) = &self.tracer else {
+            return Span::default();
+        };
+        let mut span = tracer.root("task");
+        span.set("golem.task", task);
+        span.set("gen_ai.request.model", self.thinker.read().await.model());
+        span
+    }
+
+    /// Build the task's result, record metrics for it and tell the hooks.
+    async fn finish_task(
+        &self,
+        task: &str,
+        started: Instant,
+        mut progress: TaskProgress,
+        result: Result<String>,
+    ) -> Result<TaskResult> {
+        *self.status.lock().expect("status lock poisoned") = None;
+        let duration = started.elapsed();
+        let result = result.map(|answer| TaskResult {
+            answer,
+            iterations: progress.iterations,
+            tool_calls: progress.tool_calls,
+            usage: progress.usage,
+            duration,
+            status: if progress.partial {
+                TaskStatus::Partial
+            } else {
+                TaskStatus::Completed
+            },
+        });
+        if let Some(metrics) = &self.metrics {
+            let record = TaskMetrics {
+                task: task.to_string(),
+                model: self.thinker.read().await.model().to_string(),
+                duration_ms: duration.as_millis() as u64,
+                iterations: progress.iterations,
+                tool_calls: progress.tool_calls,
+                usage: progress.usage,
+                error: result.as_ref().err().map(|e| e.to_string()),
+            };
+            if let Err(e) = metrics.record(&record) {
+                eprintln!("  warning: failed to record task metrics: {e}");
+            }
+        }
+        let status = match &result {
+            Ok(done) => match done.status {
+                TaskStatus::Completed => "completed",
+                TaskStatus::Partial => "partial",
+            },
+            Err(e) if e.is::<Interrupted>() => "interrupted",
+            Err(_) => "failed",
+        };
+        if let Some(prometheus) = &self.prometheus {
+            prometheus.task_finished(status, progress.iterations, progress.usage, duration);
+        }
+        let mut span = std::mem::take(&mut progress.span);
+        span.set("golem.iterations", progress.iterations);
+        span.set("golem.tool_calls", progress.tool_calls);
+        span.set_usage(progress.usage);
+        span.set("golem.status", status);
+        if let Err(e) = &result {
+            span.fail(e);
+        }
+        drop(span);
+        if let Some(tracer) = &self.tracer
+            && let Err(e) = tracer.export().await
+        {
+            eprintln!("  warning: {e:#}");
+        }
+        self.events.emit(Event::TaskFinished {
+            task: task.to_string(),
+            result: match &result {
+                Ok(done) => Ok(done.clone()),
+                Err(e) => Err(TaskError {
+                    message: format!("{e:#}"),
+                    interrupted: e.is::<Interrupted>(),
+                }),
+            },
+        });
+        for hook in &self.hooks {
+            hook.on_task_end(task, &result).await;
+        }
+        result
+    }
+}
+
+/// A span for one call to `model`, inside `parent`.
+fn model_span(parent: &Span, model: &str) -> Span {
+    let mut span = parent.child(format!("chat {model}"));
+    span.set("gen_ai.operation.name", "chat");
+    span.set("gen_ai.request.model", model);
+    span
+}
+
+/// Record how a model call went on its span; `None` if it was interrupted.
+fn trace_step(span: &mut Span, result: Option<&Result<StepResult>>) {
+    match result {
+        Some(Ok(step)) => {
+            if let Some(usage) = step.usage {
+                span.set_usage(usage);
+            }
+        }
+        Some(Err(e)) => span.fail(e),
+        None => span.fail("interrupted"),
+    }
+}
+
+/// The spinner phase while `calls` run (`running shell: cargo build`), or
+/// `None` when one of them may use the terminal itself: to ask for
+/// confirmation, or as a sub-agent with spinners of its own.
+async fn tool_phase(tools: &ToolRegistry, calls: &[ToolCall]) -> Option<String> {
+    for call in calls {
+        if tools.needs_confirmation(&call.tool).await || !tools.uses_timeout(&call.tool).await {
+            return None;
+        }
+    }
+    match calls {
+        [] => None,
+        [call] => Some(format!(
+            "running {}: {}",
+            call.tool,
+            one_line(&format_args(&call.args), PHASE_DETAIL_CHARS)
+        )),
+        calls => Some(format!("running {} tools", calls.len())),
+    }
+}
+
+#[async_trait]
+impl Engine for ReactEngine {
+    async fn run(&mut self, task: &str) -> Result<TaskResult> {
+        let started = Instant::now();
+        let mut progress = TaskProgress {
+            span: self.task_span(task).await,
+            ..TaskProgress::default()
+        };
+        self.events.emit(Event::TaskStarted {
+            task: task.to_string(),
+        });
+        for hook in &self.hooks {
+            hook.on_task_start(task).await;
+        }
+        let result = self.run_task(task, &mut progress).await;
+        self.finish_task(task, started, progress, result).await
     }
 }
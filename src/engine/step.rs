@@ -0,0 +1,170 @@
+//! Step mode: review every tool call before it runs.
+//!
+//! A middle ground between full autonomy and the human thinker — the model
+//! still thinks, but each proposed call waits for the user to run it, skip
+//! it, edit its args, or abort the task. [`StepMode`] is a [`Hook`], so it
+//! can be switched on and off while the engine keeps it (`/step`).
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::hooks::{Hook, ToolDecision};
+use crate::thinker::ToolCall;
+use crate::tools::format_args;
+
+/// Decides what happens to a tool call in step mode.
+#[async_trait]
+pub trait StepReviewer: Send + Sync {
+    async fn review(&self, call: &ToolCall) -> Result<ToolDecision>;
+}
+
+/// Asks at the terminal: run, skip, edit or abort.
+pub struct PromptStepReviewer;
+
+#[async_trait]
+impl StepReviewer for PromptStepReviewer {
+    async fn review(&self, call: &ToolCall) -> Result<ToolDecision> {
+        let prompter = crate::prompt::terminal();
+        let read_line = async |question: &str| match prompter.ask(question).await? {
+            Some(input) => Ok(input),
+            None => bail!("stdin closed"),
+        };
+        loop {
+            let input = read_line(&format!(
+                "  {}: {} — [r]un, [s]kip, [e]dit, [a]bort? ",
+                call.tool,
+                format_args(&call.args)
+            ))
+            .await?;
+            match input.as_str() {
+                "" | "r" | "run" | "y" => return Ok(ToolDecision::Allow),
+                "s" | "skip" => return Ok(ToolDecision::Deny("skipped by the user".to_string())),
+                "a" | "abort" => return Ok(ToolDecision::Abort("aborted by the user".to_string())),
+                "e" | "edit" => {
+                    let input = read_line(
+                        "  new args (a JSON object, or the value for a single-arg tool): ",
+                    )
+                    .await?;
+                    match edit_call(call, &input) {
+                        Ok(edited) => return Ok(ToolDecision::Modify(edited)),
+                        Err(e) => println!("  ✗ {e}"),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `call` with the args from `input`: a JSON object of strings, or a bare
+/// value that replaces the only arg of a single-arg call.
+fn edit_call(call: &ToolCall, input: &str) -> Result<ToolCall> {
+    let args = if input.starts_with('{') {
+        serde_json::from_str::<HashMap<String, String>>(input)
+            .map_err(|e| anyhow::anyhow!("not a JSON object of strings: {e}"))?
+    } else {
+        match call.args.keys().next() {
+            Some(key) if call.args.len() == 1 => HashMap::from([(key.clone(), input.to_string())]),
+            _ => bail!("{} takes several args; give a JSON object", call.tool),
+        }
+    };
+    Ok(ToolCall {
+        tool: call.tool.clone(),
+        args,
+    })
+}
+
+/// The step mode hook. Lets everything through while switched off.
+pub struct StepMode {
+    enabled: AtomicBool,
+    reviewer: Box<dyn StepReviewer>,
+}
+
+impl StepMode {
+    pub fn new(enabled: bool) -> Self {
+        Self::with_reviewer(enabled, Box::new(PromptStepReviewer))
+    }
+
+    /// Use a custom reviewer instead of asking on stdin.
+    pub fn with_reviewer(enabled: bool, reviewer: Box<dyn StepReviewer>) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            reviewer,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl Hook for StepMode {
+    async fn on_tool_call(&self, call: &ToolCall) -> ToolDecision {
+        if !self.enabled() {
+            return ToolDecision::Allow;
+        }
+        match self.reviewer.review(call).await {
+            Ok(decision) => decision,
+            Err(e) => ToolDecision::Abort(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(args: &[(&str, &str)]) -> ToolCall {
+        ToolCall {
+            tool: "shell".to_string(),
+            args: args
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    struct Skip;
+
+    #[async_trait]
+    impl StepReviewer for Skip {
+        async fn review(&self, _call: &ToolCall) -> Result<ToolDecision> {
+            Ok(ToolDecision::Deny("skipped".to_string()))
+        }
+    }
+
+    #[test]
+    fn bare_value_replaces_the_only_arg() {
+        let edited = edit_call(&call(&[("command", "rm -rf x")]), "ls x").unwrap();
+        assert_eq!(edited.args["command"], "ls x");
+    }
+
+    #[test]
+    fn json_replaces_all_args() {
+        let original = call(&[("path", "a"), ("content", "b")]);
+        assert!(edit_call(&original, "c").is_err());
+        let edited = edit_call(&original, r#"{"path": "d", "content": "e"}"#).unwrap();
+        assert_eq!(edited.args["path"], "d");
+        assert_eq!(edited.args["content"], "e");
+        assert!(edit_call(&original, "{not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn reviews_only_while_enabled() {
+        let step = StepMode::with_reviewer(false, Box::new(Skip));
+        let ls = call(&[("command", "ls")]);
+        assert_eq!(step.on_tool_call(&ls).await, ToolDecision::Allow);
+        step.set_enabled(true);
+        assert_eq!(
+            step.on_tool_call(&ls).await,
+            ToolDecision::Deny("skipped".to_string())
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! The event log: every event on the bus, kept in SQLite by session.
+//!
+//! With `event_log` on, [`EventLog`] subscribes to the engine's bus and
+//! stores each event as a row of the `events` table — when it arrived,
+//! which named session was active, its kind (`task_started`,
+//! `tool_call_finished`, …) and its fields as JSON — so a session's
+//! timeline survives apart from whatever the terminal showed.
+//! `golem events` prints it back.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+use super::Event;
+use crate::banner::task_json;
+use crate::tools::Outcome;
+
+/// One stored event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedEvent {
+    pub id: i64,
+    pub session: String,
+    /// UTC, RFC 3339 with milliseconds.
+    pub timestamp: String,
+    pub kind: String,
+    pub data: Value,
+}
+
+impl LoggedEvent {
+    /// The event as one JSON object: `id`, `timestamp`, `session` and
+    /// `kind`, then its fields.
+    pub fn to_json(&self) -> Value {
+        let mut line = json!({
+            "id": self.id,
+            "timestamp": self.timestamp,
+            "session": self.session,
+            "kind": self.kind,
+        });
+        if let (Some(line), Some(data)) = (line.as_object_mut(), self.data.as_object()) {
+            line.extend(data.clone());
+        }
+        line
+    }
+}
+
+/// Stores events under the named session active when they arrive.
+pub struct EventLog {
+    conn: Mutex<Connection>,
+    session: Mutex<String>,
+}
+
+impl EventLog {
+    /// Open the log in the given database, starting in `session`.
+    /// Use `":memory:"` for tests.
+    pub fn open(path: &str, session: &str) -> Result<Self> {
+        let conn = crate::db::open(path).context("failed to open the event log")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            session: Mutex::new(session.to_string()),
+        })
+    }
+
+    /// Store `event`. A [`Event::SessionChanged`] is stored under the
+    /// session it switches to, as is everything after it.
+    pub fn record(&self, event: &Event) -> Result<()> {
+        let mut session = self.session.lock().expect("event log lock poisoned");
+        if let Event::SessionChanged { session: name } = event {
+            *session = name.clone();
+        }
+        let (kind, data) = describe(event);
+        let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO events (session, timestamp, kind, data) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![*session, timestamp, kind, data.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// The last `limit` events of `session`, oldest first.
+    pub fn timeline(&self, session: &str, limit: usize) -> Result<Vec<LoggedEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, session, timestamp, kind, data FROM (
+                SELECT * FROM events WHERE session = ?1 ORDER BY id DESC LIMIT ?2
+             ) ORDER BY id ASC",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![session, limit as i64], |row| {
+            Ok(LoggedEvent {
+                id: row.get(0)?,
+                session: row.get(1)?,
+                timestamp: row.get(2)?,
+                kind: row.get(3)?,
+                data: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or(Value::Null),
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// An event's kind and fields.
+pub fn describe(event: &Event) -> (&'static str, Value) {
+    let data = match event {
+        Event::ModelChanged { model } => json!({ "model": model }),
+        Event::ShellModeChanged { mode } => json!({ "mode": mode.label() }),
+        Event::Phase { phase } => json!({ "phase": phase }),
+        Event::Progress(progress) => json!({
+            "iteration": progress.iteration,
+            "max_iterations": progress.max_iterations,
+            "tokens": progress.tokens,
+            "cost": progress.cost,
+        }),
+        Event::TaskStarted { task } => json!({ "task": task }),
+        Event::IterationStarted { iteration } => json!({ "iteration": iteration }),
+        Event::ThoughtProduced { thought } => json!({ "thought": thought }),
+        Event::ToolCallStarted { call } => json!({ "tool": call.tool, "args": call.args }),
+        Event::ToolCallFinished { result, duration } => {
+            let (ok, output) = match &result.outcome {
+                Outcome::Success(output) => (true, output),
+                Outcome::Error(error) => (false, error),
+            };
+            json!({
+                "tool": result.tool,
+                "ok": ok,
+                "output": output,
+                "duration_ms": duration.as_millis() as u64,
+            })
+        }
+        Event::TokenUsageRecorded { usage } => json!({ "usage": usage }),
+        Event::TaskFinished { task, result } => {
+            let mut data = match result {
+                Ok(done) => task_json(&Ok(done.clone())),
+                Err(e) => json!({
+                    "status": if e.interrupted { "interrupted" } else { "failed" },
+                    "error": e.message,
+                }),
+            };
+            data["task"] = json!(task);
+            data
+        }
+        Event::SessionChanged { session } => json!({ "session": session }),
+        Event::AuthChanged { provider, status } => {
+            json!({ "provider": provider, "status": status })
+        }
+    };
+    (event.kind().name(), data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::TaskError;
+    use crate::thinker::ToolCall;
+    use crate::tools::ToolResult;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_each_sessions_timeline() {
+        let log = EventLog::open(":memory:", "default").unwrap();
+        let call = ToolCall {
+            tool: "shell".to_string(),
+            args: HashMap::from([("command".to_string(), "df -h".to_string())]),
+        };
+        for event in [
+            Event::TaskStarted {
+                task: "disk?".to_string(),
+            },
+            Event::ToolCallStarted { call },
+            Event::ToolCallFinished {
+                result: ToolResult {
+                    tool: "shell".to_string(),
+                    outcome: Outcome::Error("exit 1".to_string()),
+                },
+                duration: Duration::from_millis(40),
+            },
+            Event::TaskFinished {
+                task: "disk?".to_string(),
+                result: Err(TaskError {
+                    message: "task interrupted".to_string(),
+                    interrupted: true,
+                }),
+            },
+            Event::SessionChanged {
+                session: "ops".to_string(),
+            },
+            Event::TaskStarted {
+                task: "uptime?".to_string(),
+            },
+        ] {
+            log.record(&event).unwrap();
+        }
+
+        let default = log.timeline("default", 10).unwrap();
+        let kinds: Vec<_> = default.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "task_started",
+                "tool_call_started",
+                "tool_call_finished",
+                "task_finished"
+            ]
+        );
+        assert_eq!(default[1].data["args"]["command"], "df -h");
+        assert_eq!(default[2].data["ok"], false);
+        assert_eq!(default[2].data["duration_ms"], 40);
+        assert_eq!(default[3].data["status"], "interrupted");
+        assert!(
+            default[0].timestamp.ends_with('Z'),
+            "{}",
+            default[0].timestamp
+        );
+
+        let ops = log.timeline("ops", 10).unwrap();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[1].data["task"], "uptime?");
+        let line = ops[1].to_json();
+        assert_eq!(line["kind"], "task_started");
+        assert_eq!(line["session"], "ops");
+        assert_eq!(line["task"], "uptime?");
+
+        // The latest ones, still oldest first
+        let last = log.timeline("default", 2).unwrap();
+        assert_eq!(last[0].kind, "tool_call_finished");
+        assert_eq!(last[1].kind, "task_finished");
+    }
+}
@@ -0,0 +1,357 @@
+//! Decoupled event bus for cross-component communication.
+//!
+//! Components emit events via [`EventBus::emit`] and subscribe via
+//! [`EventBus::subscribe`], or [`EventBus::subscribe_filtered`] to only
+//! wake for some [`EventKind`]s. Built on [`tokio::sync::broadcast`] so
+//! multiple listeners can react independently.
+//!
+//! The engine announces each task's lifecycle — start, iterations,
+//! thoughts, tool calls, token usage and the finish — and the REPL and
+//! auth storage announce model, shell mode and credential changes.
+//! [`log::EventLog`] keeps every event of a session in SQLite.
+
+pub mod log;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::engine::TaskResult;
+use crate::thinker::{TokenUsage, ToolCall};
+use crate::tools::ToolResult;
+use crate::tools::shell::ShellMode;
+
+/// Where a running task stands, for status displays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The iteration in flight, from 1.
+    pub iteration: usize,
+    pub max_iterations: usize,
+    /// Tokens the task has used so far.
+    pub tokens: u64,
+    /// Estimated USD so far, if the model's price is known.
+    pub cost: Option<f64>,
+}
+
+/// Why a task ended without an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskError {
+    pub message: String,
+    /// Cancelled mid-run rather than failed.
+    pub interrupted: bool,
+}
+
+/// Events that flow through the system.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The active model was changed (carries the new model ID).
+    ModelChanged { model: String },
+    /// The shell tool switched between read-only and read-write.
+    ShellModeChanged { mode: ShellMode },
+    /// What a running task is waiting on, for spinners and status
+    /// displays (`thinking...`, `running shell: cargo build`, `retrying
+    /// after …`).
+    Phase { phase: String },
+    /// A running task started an iteration or used more tokens.
+    Progress(Progress),
+    /// A task started, or an unfinished one was resumed.
+    TaskStarted { task: String },
+    /// The running task started iteration `iteration` (from 1).
+    IterationStarted { iteration: usize },
+    /// The model gave its reasoning before calling tools.
+    ThoughtProduced { thought: String },
+    /// A tool call is about to run.
+    ToolCallStarted { call: ToolCall },
+    /// A tool call finished, after `duration`.
+    ToolCallFinished {
+        result: ToolResult,
+        duration: Duration,
+    },
+    /// One model call used `usage`.
+    TokenUsageRecorded { usage: TokenUsage },
+    /// A task ended with its result, or the error it failed with.
+    TaskFinished {
+        task: String,
+        result: Result<TaskResult, TaskError>,
+    },
+    /// Session history now goes to another named session.
+    SessionChanged { session: String },
+    /// A provider's credentials changed: logged in or out, switched
+    /// account or refreshed (carries the new auth status).
+    AuthChanged { provider: String, status: String },
+}
+
+/// What an [`Event`] is about, without its fields, for choosing which
+/// events to subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    ModelChanged,
+    ShellModeChanged,
+    Phase,
+    Progress,
+    TaskStarted,
+    IterationStarted,
+    ThoughtProduced,
+    ToolCallStarted,
+    ToolCallFinished,
+    TokenUsageRecorded,
+    TaskFinished,
+    SessionChanged,
+    AuthChanged,
+}
+
+impl EventKind {
+    /// The kind in snake case, e.g. `tool_call_finished`.
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::ModelChanged => "model_changed",
+            EventKind::ShellModeChanged => "shell_mode_changed",
+            EventKind::Phase => "phase",
+            EventKind::Progress => "progress",
+            EventKind::TaskStarted => "task_started",
+            EventKind::IterationStarted => "iteration_started",
+            EventKind::ThoughtProduced => "thought_produced",
+            EventKind::ToolCallStarted => "tool_call_started",
+            EventKind::ToolCallFinished => "tool_call_finished",
+            EventKind::TokenUsageRecorded => "token_usage_recorded",
+            EventKind::TaskFinished => "task_finished",
+            EventKind::SessionChanged => "session_changed",
+            EventKind::AuthChanged => "auth_changed",
+        }
+    }
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::ModelChanged { .. } => EventKind::ModelChanged,
+            Event::ShellModeChanged { .. } => EventKind::ShellModeChanged,
+            Event::Phase { .. } => EventKind::Phase,
+            Event::Progress(_) => EventKind::Progress,
+            Event::TaskStarted { .. } => EventKind::TaskStarted,
+            Event::IterationStarted { .. } => EventKind::IterationStarted,
+            Event::ThoughtProduced { .. } => EventKind::ThoughtProduced,
+            Event::ToolCallStarted { .. } => EventKind::ToolCallStarted,
+            Event::ToolCallFinished { .. } => EventKind::ToolCallFinished,
+            Event::TokenUsageRecorded { .. } => EventKind::TokenUsageRecorded,
+            Event::TaskFinished { .. } => EventKind::TaskFinished,
+            Event::SessionChanged { .. } => EventKind::SessionChanged,
+            Event::AuthChanged { .. } => EventKind::AuthChanged,
+        }
+    }
+}
+
+/// A broadcast channel that any component can emit to or subscribe from.
+#[derive(Debug)]
+pub struct EventBus {
+    tx: broadcast::Sender<Event>,
+    capacity: usize,
+    /// A channel per [`subscribe_filtered`](Self::subscribe_filtered)
+    /// call, and the kinds it carries.
+    filtered: Mutex<Vec<(Vec<EventKind>, broadcast::Sender<Event>)>>,
+    /// A bus that also gets every event, but those of the kinds listed.
+    relay: Option<(Arc<EventBus>, Vec<EventKind>)>,
+}
+
+impl EventBus {
+    /// Create a new event bus with the given channel capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "EventBus capacity must be non-zero");
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            capacity,
+            filtered: Mutex::new(Vec::new()),
+            relay: None,
+        }
+    }
+
+    /// A bus whose events are also emitted on `parent`, except those of
+    /// `skip` kinds — for a sub-agent whose work the parent's listeners
+    /// should follow.
+    pub fn relaying_to(parent: Arc<EventBus>, skip: &[EventKind]) -> Self {
+        Self {
+            relay: Some((parent, skip.to_vec())),
+            ..Self::default()
+        }
+    }
+
+    /// Emit an event to all current subscribers.
+    /// Returns the number of receivers that will see it.
+    pub fn emit(&self, event: Event) -> usize {
+        let kind = event.kind();
+        let mut seen = 0;
+        if let Some((parent, skip)) = &self.relay
+            && !skip.contains(&kind)
+        {
+            parent.emit(event.clone());
+        }
+        let mut filtered = self.filtered.lock().expect("event bus lock poisoned");
+        // Channels whose receivers are all gone are dropped
+        filtered.retain(|(_, tx)| tx.receiver_count() > 0);
+        for (kinds, tx) in filtered.iter() {
+            if kinds.contains(&kind) {
+                seen += tx.send(event.clone()).unwrap_or(0);
+            }
+        }
+        seen + self.tx.send(event).unwrap_or(0)
+    }
+
+    /// Subscribe to events. Returns a receiver that yields all
+    /// future events (does not replay past ones).
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+
+    /// Subscribe to events of `kinds` only. The receiver has a channel
+    /// of its own, so other events neither wake it nor make it lag.
+    pub fn subscribe_filtered(&self, kinds: &[EventKind]) -> broadcast::Receiver<Event> {
+        let (tx, rx) = broadcast::channel(self.capacity);
+        self.filtered
+            .lock()
+            .expect("event bus lock poisoned")
+            .push((kinds.to_vec(), tx));
+        rx
+    }
+}
+
+impl Default for EventBus {
+    /// Room for a burst of parallel tool calls before slow subscribers
+    /// lag.
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn emit_reaches_subscriber() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+
+        bus.emit(Event::ModelChanged {
+            model: "claude-sonnet-4-20250514".to_string(),
+        });
+
+        let event = rx.recv().await.unwrap();
+        match event {
+            Event::ModelChanged { model } => assert_eq!(model, "claude-sonnet-4-20250514"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn multiple_subscribers_receive_event() {
+        let bus = EventBus::default();
+        let mut rx1 = bus.subscribe();
+        let mut rx2 = bus.subscribe();
+
+        bus.emit(Event::ModelChanged {
+            model: "opus".to_string(),
+        });
+
+        let e1 = rx1.recv().await.unwrap();
+        let e2 = rx2.recv().await.unwrap();
+
+        match (e1, e2) {
+            (Event::ModelChanged { model: m1 }, Event::ModelChanged { model: m2 }) => {
+                assert_eq!(m1, "opus");
+                assert_eq!(m2, "opus");
+            }
+            other => panic!("unexpected events: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn filtered_subscribers_see_only_their_kinds() {
+        let bus = EventBus::default();
+        let mut all = bus.subscribe();
+        let mut phases = bus.subscribe_filtered(&[EventKind::Phase, EventKind::Progress]);
+        let mut models = bus.subscribe_filtered(&[EventKind::ModelChanged]);
+
+        let phase = Event::Phase {
+            phase: "thinking...".to_string(),
+        };
+        assert_eq!(phase.kind(), EventKind::Phase);
+        assert_eq!(bus.emit(phase), 2);
+        assert_eq!(
+            bus.emit(Event::TaskStarted {
+                task: "t".to_string()
+            }),
+            1
+        );
+        assert_eq!(
+            bus.emit(Event::ModelChanged {
+                model: "opus".to_string()
+            }),
+            2
+        );
+
+        assert!(matches!(phases.recv().await.unwrap(), Event::Phase { .. }));
+        assert!(phases.try_recv().is_err());
+        assert!(matches!(
+            models.recv().await.unwrap(),
+            Event::ModelChanged { .. }
+        ));
+        for kind in [
+            EventKind::Phase,
+            EventKind::TaskStarted,
+            EventKind::ModelChanged,
+        ] {
+            assert_eq!(all.recv().await.unwrap().kind(), kind);
+        }
+
+        // A dropped filtered receiver stops counting
+        drop(models);
+        assert_eq!(
+            bus.emit(Event::ModelChanged {
+                model: "haiku".to_string()
+            }),
+            1
+        );
+        assert_eq!(bus.filtered.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn relayed_events_reach_the_parent_bus() {
+        let parent = Arc::new(EventBus::default());
+        let mut rx = parent.subscribe();
+        let child = EventBus::relaying_to(Arc::clone(&parent), &[EventKind::TaskStarted]);
+        let mut own = child.subscribe();
+        child.emit(Event::TaskStarted {
+            task: "sub".to_string(),
+        });
+        child.emit(Event::IterationStarted { iteration: 1 });
+        assert_eq!(own.recv().await.unwrap().kind(), EventKind::TaskStarted);
+        assert_eq!(rx.recv().await.unwrap().kind(), EventKind::IterationStarted);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn emit_without_subscribers_returns_zero() {
+        let bus = EventBus::default();
+        let count = bus.emit(Event::ModelChanged {
+            model: "test".to_string(),
+        });
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn emit_with_subscriber_returns_count() {
+        let bus = EventBus::default();
+        let _rx1 = bus.subscribe();
+        let _rx2 = bus.subscribe();
+
+        let count = bus.emit(Event::ModelChanged {
+            model: "test".to_string(),
+        });
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,228 @@
+//! `--json-events`: a task's progress as one JSON object per line.
+//!
+//! [`JsonEvents`] writes a line to stdout when the task starts, for every
+//! thought, tool call and tool result, and when it finishes — the last
+//! line carries `--output json`'s fields. Every line has a `type` and a
+//! `ts` (Unix milliseconds). Nothing else goes to stdout, so a call that
+//! needs confirmation is an `approval` line too, answered with `y` or `n`
+//! on a line of stdin.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::banner::task_json;
+use crate::engine::TaskResult;
+use crate::engine::hooks::{Hook, ToolDecision};
+use crate::thinker::{Step, ToolCall};
+use crate::tools::{Approver, Outcome, ToolResult};
+
+/// One line of output.
+#[derive(Debug, Serialize)]
+struct Line<'a> {
+    ts: u64,
+    #[serde(flatten)]
+    event: JsonEvent<'a>,
+}
+
+/// What happened.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonEvent<'a> {
+    TaskStart {
+        task: &'a str,
+    },
+    Thought {
+        text: &'a str,
+    },
+    ToolCall {
+        tool: &'a str,
+        args: BTreeMap<&'a str, &'a str>,
+    },
+    /// A call waits for `y` or `n` on stdin.
+    Approval {
+        tool: &'a str,
+        args: BTreeMap<&'a str, &'a str>,
+    },
+    ToolResult {
+        tool: &'a str,
+        ok: bool,
+        output: &'a str,
+    },
+    /// `--output json`'s document.
+    Finish {
+        #[serde(flatten)]
+        result: serde_json::Value,
+    },
+}
+
+/// Writes the events of the tasks it hooks into, and asks for approvals
+/// with them.
+#[derive(Clone)]
+pub struct JsonEvents {
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl JsonEvents {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(out)),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+
+    fn emit(&self, event: JsonEvent) {
+        let line = Line {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            event,
+        };
+        let json = serde_json::to_string(&line).expect("events always serialize");
+        let mut out = self.out.lock().expect("event output lock poisoned");
+        // A reader that went away shouldn't stop the task
+        let _ = writeln!(out, "{json}").and_then(|_| out.flush());
+    }
+}
+
+fn sorted(args: &HashMap<String, String>) -> BTreeMap<&str, &str> {
+    args.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+}
+
+#[async_trait]
+impl Hook for JsonEvents {
+    async fn on_task_start(&self, task: &str) {
+        self.emit(JsonEvent::TaskStart { task });
+    }
+
+    async fn on_step(&self, step: &Step) {
+        if let Step::Act { thought, .. } = step
+            && !thought.is_empty()
+        {
+            self.emit(JsonEvent::Thought { text: thought });
+        }
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall) -> ToolDecision {
+        self.emit(JsonEvent::ToolCall {
+            tool: &call.tool,
+            args: sorted(&call.args),
+        });
+        ToolDecision::Allow
+    }
+
+    async fn on_tool_result(&self, _call: &ToolCall, result: &ToolResult) {
+        let (ok, output) = match &result.outcome {
+            Outcome::Success(output) => (true, output),
+            Outcome::Error(error) => (false, error),
+        };
+        self.emit(JsonEvent::ToolResult {
+            tool: &result.tool,
+            ok,
+            output,
+        });
+    }
+
+    async fn on_task_end(&self, _task: &str, result: &Result<TaskResult>) {
+        self.emit(JsonEvent::Finish {
+            result: task_json(result),
+        });
+    }
+}
+
+#[async_trait]
+impl Approver for JsonEvents {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        self.emit(JsonEvent::Approval {
+            tool,
+            args: sorted(args),
+        });
+        crate::prompt::terminal().confirm("").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Collects what is written, for reading back.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn one_object_per_event() {
+        let buffer = Buffer::default();
+        let events = JsonEvents::new(Box::new(buffer.clone()));
+        let call = ToolCall {
+            tool: "shell".to_string(),
+            args: HashMap::from([("command".to_string(), "df -h".to_string())]),
+        };
+        events.on_task_start("disk?").await;
+        events
+            .on_step(&Step::Act {
+                thought: "check the disks".to_string(),
+                calls: vec![call.clone()],
+            })
+            .await;
+        assert_eq!(events.on_tool_call(&call).await, ToolDecision::Allow);
+        let result = ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Error("exit 1".to_string()),
+        };
+        events.on_tool_result(&call, &result).await;
+        let done = Ok(TaskResult {
+            answer: "plenty".to_string(),
+            iterations: 2,
+            tool_calls: 1,
+            usage: Default::default(),
+            duration: Duration::from_millis(1500),
+            status: crate::engine::TaskStatus::Completed,
+        });
+        events.on_task_end("disk?", &done).await;
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let types: Vec<_> = lines.iter().map(|l| l["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            [
+                "task_start",
+                "thought",
+                "tool_call",
+                "tool_result",
+                "finish"
+            ]
+        );
+        assert!(lines.iter().all(|l| l["ts"].as_u64().unwrap() > 0));
+        assert_eq!(lines[0]["task"], "disk?");
+        assert_eq!(lines[1]["text"], "check the disks");
+        assert_eq!(lines[2]["args"]["command"], "df -h");
+        assert_eq!(lines[3]["ok"], false);
+        assert_eq!(lines[3]["output"], "exit 1");
+        assert_eq!(lines[4]["status"], "completed");
+        assert_eq!(lines[4]["answer"], "plenty");
+        assert_eq!(lines[4]["duration_ms"], 1500);
+    }
+}
@@ -1,12 +1,32 @@
 pub mod auth;
 pub mod banner;
+pub mod batch;
+pub mod builder;
+pub mod chat;
+pub mod ci;
 pub mod commands;
 pub mod config;
 pub mod consts;
+#[cfg(unix)]
+pub mod daemon;
+pub mod db;
+pub mod discord;
+pub mod doctor;
+pub mod editor;
 pub mod engine;
 pub mod events;
+pub mod json_events;
+pub mod matrix;
 pub mod memory;
+pub mod metrics;
+pub mod prompt;
 pub mod prompts;
+pub mod replay;
+pub mod schedule;
+pub mod serve;
+pub mod slack;
 pub mod spinner;
+pub mod telemetry;
 pub mod thinker;
 pub mod tools;
+pub mod transcript;
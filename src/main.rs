@@ -1,26 +1,58 @@
-use std::io::{self, Write};
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Write};
 use std::sync::Arc;
-use std::time::Duration;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand, ValueEnum};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use anyhow::Context;
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
-use golem::auth::oauth;
 use golem::auth::storage::{AuthStorage, Credential};
-use golem::banner::{BannerInfo, print_banner, print_session_summary};
+use golem::banner::{
+    BannerInfo, BannerStyle, print_answer_text, print_auth_status, print_banner,
+    print_batch_summary, print_doctor_report, print_replay_outcome, print_resume_recap,
+    print_schedule_runs, print_schedules, print_session_summary, print_task_json,
+    print_task_result, print_task_stats,
+};
+use golem::batch::{BatchResult, TaskQueue, read_tasks, resolve_task, run_batch, write_report};
+use golem::builder::{Golem, GolemBuilder, resolve_db_path, resolve_project};
+use golem::ci;
+use golem::commands::Command as _;
+use golem::commands::macros::MacroCommand;
 use golem::commands::{CommandRegistry, CommandResult, SessionInfo, StateChange};
 use golem::config::Config;
-use golem::consts::{DEFAULT_MODEL, default_db_path};
-use golem::engine::Engine;
-use golem::engine::react::{ReactConfig, ReactEngine};
+use golem::config::settings::{
+    EmbeddingSettings, Settings, ShellSettings, TelemetrySettings, ToolSettings,
+};
+use golem::config::wizard::{self, Login};
+use golem::consts::{
+    DEFAULT_SESSION_NAME, NO_DATABASE, PROJECT_CONFIG_FILE, history_path, legacy_auth_path,
+    transcripts_dir, user_config_path,
+};
+use golem::doctor;
+use golem::editor::{EditMode, Input, KeyBindings, LineEditor, ModelNames};
+use golem::engine::react::{PromptBudgetApprover, ReactConfig, ReactEngine};
+use golem::engine::step::StepMode;
+use golem::engine::{Engine, Interrupted, TaskResult};
+use golem::events::log::EventLog;
+use golem::events::{Event, EventKind};
+use golem::json_events::JsonEvents;
+use golem::memory::export::SessionExport;
+use golem::memory::in_memory::InMemoryMemory;
 use golem::memory::sqlite::SqliteMemory;
-use golem::thinker::Thinker;
-use golem::thinker::anthropic::AnthropicThinker;
-use golem::thinker::human::HumanThinker;
+use golem::memory::{Memory, embedding};
+use golem::metrics::MetricsStore;
+use golem::replay::{read_recording, recorded_tools};
+use golem::schedule::{self, ScheduleStore};
+use golem::thinker::replay::{ReplayThinker, ResponseQueue};
+use golem::thinker::{DEFAULT_PARSE_RETRIES, Thinker};
 use golem::tools::ToolRegistry;
-use golem::tools::shell::{ShellConfig, ShellMode, ShellTool};
+use golem::tools::shell::{ShellMode, ShellTool};
+use golem::transcript::Transcript;
+use tokio::sync::broadcast::error::TryRecvError;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum Provider {
@@ -28,47 +60,231 @@ enum Provider {
     Anthropic,
 }
 
+/// What a single `-r` task prints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Thoughts and tool output as they happen, then the answer
+    #[default]
+    Text,
+    /// Only one JSON document with the result, for scripts
+    Json,
+}
+
 #[derive(Parser)]
 #[command(name = "golem", version, about = "A clay body, animated by words.")]
 struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
 
-    /// LLM provider
-    #[arg(short, long, value_enum, default_value_t = Provider::Anthropic)]
-    provider: Provider,
+    /// LLM provider [default: anthropic]
+    #[arg(short, long, value_enum, env = "GOLEM_PROVIDER")]
+    provider: Option<Provider>,
 
     /// Model name (provider-specific, ignored for human)
-    #[arg(long)]
+    #[arg(long, env = "GOLEM_MODEL")]
     model: Option<String>,
 
-    /// SQLite database path (use :memory: for ephemeral)
-    #[arg(short, long)]
+    /// SQLite database path (use :memory: for ephemeral, `none` to skip SQLite for memory)
+    #[arg(short, long, env = "GOLEM_DB")]
     db: Option<String>,
 
-    /// Maximum ReAct loop iterations before giving up
-    #[arg(short, long, default_value_t = 20)]
-    max_iterations: usize,
+    /// Maximum ReAct loop iterations before giving up [default: 20]
+    #[arg(short, long, env = "GOLEM_MAX_ITERATIONS")]
+    max_iterations: Option<usize>,
+
+    /// Tool execution timeout in seconds [default: 30]
+    #[arg(short, long, env = "GOLEM_TIMEOUT")]
+    timeout: Option<u64>,
+
+    /// Stop a task once it has used more than this many tokens
+    #[arg(long, env = "GOLEM_MAX_TOKENS_PER_TASK")]
+    max_tokens_per_task: Option<u64>,
+
+    /// Stop a task once it has cost more than this many USD (priced models only)
+    #[arg(long, env = "GOLEM_MAX_COST_PER_TASK")]
+    max_cost_per_task: Option<f64>,
+
+    /// Bytes of tool output the model sees verbatim; older output is shortened (0 = no limit) [default: 65536]
+    #[arg(long, env = "GOLEM_OBSERVATION_BUDGET")]
+    observation_budget: Option<usize>,
+
+    /// Retry a failed model call this many times before the task fails [default: 2]
+    #[arg(long, env = "GOLEM_RETRIES")]
+    retries: Option<u32>,
+
+    /// Ask the model to correct a response that isn't valid JSON or a step this many times [default: 1]
+    #[arg(long, env = "GOLEM_PARSE_RETRIES")]
+    parse_retries: Option<usize>,
 
-    /// Tool execution timeout in seconds
-    #[arg(short, long, default_value_t = 30)]
-    timeout: u64,
+    /// Language the model writes its thoughts and answers in, e.g. `he` or `German`; commands stay as they are
+    #[arg(long, env = "GOLEM_LANGUAGE")]
+    language: Option<String>,
 
     /// Allow write operations in shell tool (default: read-only)
     #[arg(long, default_value_t = false)]
     allow_write: bool,
 
     /// Working directory for shell commands
-    #[arg(short, long)]
+    #[arg(short, long, env = "GOLEM_WORK_DIR")]
     work_dir: Option<PathBuf>,
 
     /// Skip confirmation prompts before executing commands
     #[arg(long, default_value_t = false)]
     no_confirm: bool,
 
-    /// Run a single task and exit (non-interactive)
+    /// Hide and refuse tools that may reach the network (the shell in read-write mode)
+    #[arg(long, default_value_t = false, env = "GOLEM_NO_NETWORK")]
+    no_network: bool,
+
+    /// Hide and refuse tools that may irreversibly change or delete data
+    #[arg(long, default_value_t = false, env = "GOLEM_NO_DESTRUCTIVE")]
+    no_destructive: bool,
+
+    /// Run a task and exit (non-interactive); `@file` reads it from a file; repeat to queue several
     #[arg(short, long)]
-    run: Option<String>,
+    run: Vec<String>,
+
+    /// Print only the final answer: no banner, thoughts or tool output
+    #[arg(short, long, default_value_t = false)]
+    quiet: bool,
+
+    /// Output of a single -r task [default: text]
+    #[arg(long, value_enum, env = "GOLEM_OUTPUT")]
+    output: Option<OutputFormat>,
+
+    /// Print one JSON object per line as a single -r task runs: start, thoughts, tool calls,
+    /// results and the finish; confirmations are read as y/n lines from stdin
+    #[arg(long, default_value_t = false, conflicts_with_all = ["output", "ci", "step", "plan"])]
+    json_events: bool,
+
+    /// How many queued tasks run at once (batch, repeated -r, /queue run)
+    #[arg(long, global = true, default_value_t = 1)]
+    parallel: usize,
+
+    /// Write a JSON report of queued tasks here (batch defaults to <file>.report.json)
+    #[arg(long, global = true)]
+    report: Option<PathBuf>,
+
+    /// Print the tool calls the agent would make for each task, without running any
+    #[arg(long, default_value_t = false)]
+    plan: bool,
+
+    /// Pause before every tool call to run, skip, edit or abort it (toggle with /step)
+    #[arg(long, default_value_t = false)]
+    step: bool,
+
+    /// Start with a recap of the previous session's last tasks
+    #[arg(long, default_value_t = false)]
+    resume: bool,
+
+    /// Share session memory across all directories instead of per project
+    #[arg(long, default_value_t = false)]
+    global_memory: bool,
+
+    /// Prompt keybindings [default: emacs]
+    #[arg(long, value_enum, env = "GOLEM_EDIT_MODE")]
+    edit_mode: Option<EditMode>,
+
+    /// Send a desktop notification when a task takes at least this many seconds
+    #[arg(long, value_name = "SECS", env = "GOLEM_NOTIFY_AFTER")]
+    notify_after: Option<u64>,
+
+    /// Use this stored account of the provider, and keep using it
+    #[arg(long, global = true, env = "GOLEM_ACCOUNT")]
+    account: Option<String>,
+
+    /// Startup banner [default: full]
+    #[arg(long, value_enum, env = "GOLEM_BANNER")]
+    banner: Option<BannerStyle>,
+
+    /// Don't print the startup banner; wins over --banner
+    #[arg(long, default_value_t = false)]
+    no_banner: bool,
+
+    /// GitHub Actions mode: one task (-r or GOLEM_TASK) without prompts; the answer goes to
+    /// the job summary and step outputs, tool errors become annotations, exit 0/1/2
+    #[arg(long, default_value_t = false, conflicts_with_all = ["step", "output"])]
+    ci: bool,
+
+    /// Append raw LLM traffic and tool executions to ~/.golem/transcripts/<session>.jsonl
+    #[arg(long, default_value_t = false)]
+    transcript: bool,
+
+    /// Keep every engine event (task start, thoughts, tool calls, usage, finish) in the
+    /// database, by session; `golem events` prints them
+    #[arg(long, global = true, default_value_t = false, env = "GOLEM_EVENT_LOG")]
+    event_log: bool,
+
+    /// Send OpenTelemetry spans of every task to this OTLP/HTTP collector, e.g.
+    /// http://localhost:4318
+    #[arg(long, value_name = "URL", global = true, env = "GOLEM_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+}
+
+impl Cli {
+    /// Flags and environment variables as the top settings layer.
+    fn settings(&self) -> Settings {
+        Settings {
+            provider: self
+                .provider
+                .as_ref()
+                .and_then(|p| p.to_possible_value())
+                .map(|p| p.get_name().to_string()),
+            account: self.account.clone(),
+            model: self.model.clone(),
+            db: self.db.clone(),
+            max_iterations: self.max_iterations,
+            timeout: self.timeout,
+            max_tokens_per_task: self.max_tokens_per_task,
+            max_cost_per_task: self.max_cost_per_task,
+            retries: self.retries,
+            parse_retries: self.parse_retries,
+            language: self.language.clone(),
+            observation_budget: self.observation_budget,
+            global_memory: self.global_memory.then_some(true),
+            transcript: self.transcript.then_some(true),
+            event_log: self.event_log.then_some(true),
+            edit_mode: self.edit_mode,
+            notify_after: self.notify_after,
+            banner: if self.no_banner {
+                Some(BannerStyle::Off)
+            } else {
+                self.banner
+            },
+            shell: ShellSettings {
+                mode: self.allow_write.then_some(ShellMode::ReadWrite),
+                work_dir: self.work_dir.clone(),
+                confirm: self.no_confirm.then_some(false),
+                max_output_bytes: None,
+            },
+            tools: ToolSettings {
+                allow_network: self.no_network.then_some(false),
+                allow_destructive: self.no_destructive.then_some(false),
+            },
+            keys: KeyBindings::default(),
+            macros: BTreeMap::new(),
+            examples: Vec::new(),
+            listen: match &self.command {
+                Some(Command::Serve { listen }) => listen.clone(),
+                _ => None,
+            },
+            socket: match &self.command {
+                Some(Command::Daemon { socket, .. } | Command::Attach { socket }) => socket.clone(),
+                _ => None,
+            },
+            metrics_listen: match &self.command {
+                Some(Command::Daemon { metrics_listen, .. }) => metrics_listen.clone(),
+                _ => None,
+            },
+            webhooks: BTreeMap::new(),
+            schedules: BTreeMap::new(),
+            telemetry: TelemetrySettings {
+                endpoint: self.otlp_endpoint.clone(),
+                headers: BTreeMap::new(),
+            },
+            embeddings: EmbeddingSettings::default(),
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -78,6 +294,14 @@ enum Command {
         /// Provider to log in to
         #[arg(value_enum, default_value_t = LoginProvider::Anthropic)]
         provider: LoginProvider,
+        /// Paste the code from the browser instead of catching its redirect
+        /// on localhost (for a browser on another machine)
+        #[arg(long, default_value_t = false)]
+        paste: bool,
+        /// Show a code to enter on any device with a browser, where the
+        /// provider offers it
+        #[arg(long, default_value_t = false, conflicts_with = "paste")]
+        device: bool,
     },
     /// Log out from an LLM provider
     Logout {
@@ -85,6 +309,143 @@ enum Command {
         #[arg(value_enum, default_value_t = LoginProvider::Anthropic)]
         provider: LoginProvider,
     },
+    /// Inspect stored credentials
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommand,
+    },
+    /// Continue the previous session (same as --resume)
+    Resume,
+    /// Pick up a task that was interrupted or failed, keeping its tool work
+    ResumeTask,
+    /// Import a session exported with `/export json` as a new named session
+    Import {
+        /// Path to the exported JSON file
+        file: PathBuf,
+        /// Session name (defaults to the name stored in the export)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Show duration, iteration, tool call, and token totals for past tasks
+    Stats,
+    /// Print a session's logged events as JSON lines, oldest first (see --event-log)
+    Events {
+        /// Named session [default: the active one]
+        #[arg(long)]
+        session: Option<String>,
+        /// How many of the latest events
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Run the tasks in a file (one per line, or a YAML list) and write a report
+    Batch {
+        /// Task file; `.yaml`/`.yml` files are read as a list of strings
+        file: PathBuf,
+    },
+    /// Re-run the tasks in a transcript with the model responses it recorded
+    Replay {
+        /// Transcript file (see --transcript)
+        file: PathBuf,
+        /// Really run tool calls instead of replaying their recorded output
+        #[arg(long, default_value_t = false)]
+        execute: bool,
+    },
+    /// Run as a Slack bot (Socket Mode): mentions become tasks, answered in thread
+    Slack {
+        /// App-level token (xapp-…) with connections:write
+        #[arg(long, env = "SLACK_APP_TOKEN", hide_env_values = true)]
+        app_token: String,
+        /// Bot token (xoxb-…) with app_mentions:read and chat:write
+        #[arg(long, env = "SLACK_BOT_TOKEN", hide_env_values = true)]
+        bot_token: String,
+    },
+    /// Run as a Discord bot: `/golem <task>` runs a task, progress edited in place
+    Discord {
+        /// Bot token from the Discord developer portal
+        #[arg(long, env = "DISCORD_TOKEN", hide_env_values = true)]
+        token: String,
+    },
+    /// Run as a Matrix bot: `!golem <task>` or a mention runs a task, answered in a thread
+    Matrix {
+        /// Homeserver URL, e.g. https://matrix.example.org
+        #[arg(long, env = "MATRIX_HOMESERVER")]
+        homeserver: String,
+        /// Access token of the bot's account
+        #[arg(long, env = "MATRIX_ACCESS_TOKEN", hide_env_values = true)]
+        access_token: String,
+    },
+    /// Add, list or remove scheduled tasks, and show their results
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommand,
+    },
+    /// Serve webhooks and run schedules: signed requests and due schedules become tasks
+    Serve {
+        /// Address to listen on [default: 127.0.0.1:8787]
+        #[arg(long, env = "GOLEM_LISTEN")]
+        listen: Option<String>,
+    },
+    /// Keep an engine warm in the background for `golem attach` (unix socket)
+    Daemon {
+        /// Socket to listen on [default: ~/.golem/daemon.sock]
+        #[arg(long, env = "GOLEM_SOCKET")]
+        socket: Option<PathBuf>,
+        /// Also serve Prometheus metrics at http://<ADDR>/metrics
+        #[arg(long, value_name = "ADDR", env = "GOLEM_METRICS_LISTEN")]
+        metrics_listen: Option<String>,
+    },
+    /// Open a REPL on a running `golem daemon`, sharing its engine and session
+    Attach {
+        /// Socket the daemon listens on [default: ~/.golem/daemon.sock]
+        #[arg(long, env = "GOLEM_SOCKET")]
+        socket: Option<PathBuf>,
+    },
+    /// Check credentials, database, config files and tools, and suggest fixes
+    Doctor,
+    /// Print the man page (roff) to stdout, e.g. `golem man > golem.1`
+    Man,
+}
+
+#[derive(Subcommand)]
+enum AuthCommand {
+    /// List stored credentials per provider and account, with their expiry
+    Status {
+        /// Make a minimal authenticated call with each to check it still works
+        #[arg(long, default_value_t = false)]
+        verify: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommand {
+    /// Run a task on a cron schedule (minute hour day month weekday, local time)
+    Add {
+        /// Cron expression, e.g. "0 9 * * 1-5"
+        cron: String,
+        /// The task; `@file` reads it from a file
+        task: String,
+        /// Id for the schedule [default: the lowest free number]
+        #[arg(long)]
+        id: Option<String>,
+        /// URL to POST each result to
+        #[arg(long)]
+        notify: Option<String>,
+    },
+    /// List schedules, stored and configured, with when they run next
+    List,
+    /// Remove a schedule added with `golem schedule add`
+    Remove {
+        /// The schedule's id
+        id: String,
+    },
+    /// Show the latest results of scheduled runs
+    Runs {
+        /// Only this schedule's runs
+        id: Option<String>,
+        /// How many runs to show
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -94,176 +455,384 @@ enum LoginProvider {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    cli.run = cli
+        .run
+        .iter()
+        .map(|arg| resolve_task(arg))
+        .collect::<anyhow::Result<_>>()?;
+    if cli.ci {
+        if cli.run.is_empty()
+            && let Ok(task) = std::env::var(ci::TASK_ENV)
+        {
+            cli.run.push(resolve_task(&task)?);
+        }
+        if cli.run.len() != 1 || cli.plan || cli.command.is_some() {
+            anyhow::bail!(
+                "--ci runs exactly one task, from -r or {}, without --plan or a subcommand",
+                ci::TASK_ENV
+            );
+        }
+    }
+    let json = cli.output == Some(OutputFormat::Json);
+    if json && (cli.run.len() != 1 || cli.plan || cli.command.is_some()) {
+        anyhow::bail!("--output json needs exactly one -r task, without --plan or a subcommand");
+    }
+    if cli.json_events && (cli.run.len() != 1 || cli.command.is_some()) {
+        anyhow::bail!("--json-events needs exactly one -r task, without a subcommand");
+    }
+    // Progress goes to stdout unless it is kept for the answer alone
+    let echo = !json && !cli.json_events && !cli.quiet;
+    // Before the config files are loaded, so a broken one gets diagnosed
+    if matches!(cli.command, Some(Command::Doctor)) {
+        return handle_doctor(&cli).await;
+    }
+    // Config files, then environment variables and flags on top
+    let settings = Settings::load(&std::env::current_dir()?)?.merge(cli.settings());
 
     // Handle subcommands
     if let Some(command) = &cli.command {
         match command {
-            Command::Login { provider } => {
-                return handle_login(provider).await;
+            Command::Login {
+                provider,
+                paste,
+                device,
+            } => {
+                let db_path = credentials_db(&settings)?;
+                return handle_login(
+                    &db_path,
+                    provider,
+                    settings.account.as_deref(),
+                    *paste,
+                    *device,
+                )
+                .await;
             }
             Command::Logout { provider } => {
-                return handle_logout(provider);
+                let db_path = credentials_db(&settings)?;
+                return handle_logout(&db_path, provider, settings.account.as_deref());
             }
-        }
-    }
-
-    // Resolve database path — single DB for memory, credentials, and config
-    let db_path = cli
-        .db
-        .clone()
-        .unwrap_or_else(|| default_db_path().to_string_lossy().to_string());
-
-    // Ensure parent directory exists for file-based DBs
-    if db_path != ":memory:"
-        && let Some(parent) = std::path::Path::new(&db_path).parent()
-    {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    // Wire up the thinker based on provider + model
-    let (thinker, provider_name, mut model_name, mut auth_status): (
-        Box<dyn Thinker>,
-        &str,
-        String,
-        String,
-    ) = match cli.provider {
-        Provider::Human => {
-            if cli.model.is_some() {
-                eprintln!("warning: --model is ignored for human provider");
+            Command::Auth {
+                command: AuthCommand::Status { verify },
+            } => {
+                let db_path = credentials_db(&settings)?;
+                return handle_auth_status(&db_path, *verify).await;
             }
-            (
-                Box::new(HumanThinker),
-                "human",
-                "—".to_string(),
-                "N/A".to_string(),
-            )
-        }
-        Provider::Anthropic => {
-            let auth = AuthStorage::open(&db_path)?;
-            let auth_status = match auth.get("anthropic")? {
-                Some(Credential::OAuth(_)) => "OAuth ✓".to_string(),
-                Some(Credential::ApiKey { .. }) => "API key ✓".to_string(),
-                None => {
-                    if std::env::var("ANTHROPIC_API_KEY")
-                        .map(|k| !k.is_empty())
-                        .unwrap_or(false)
-                    {
-                        "API key (env) ✓".to_string()
-                    } else {
-                        "not authenticated".to_string()
-                    }
-                }
-            };
-            // Model resolution: --model flag > config DB > default
-            let model = cli.model.clone().or_else(|| {
-                Config::open(&db_path)
-                    .ok()
-                    .and_then(|c| c.get("model").ok().flatten())
-            });
-            let thinker = Box::new(AnthropicThinker::new(model.clone(), auth));
-            let model_name = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-            (thinker, "anthropic", model_name, auth_status)
+            Command::Resume
+            | Command::ResumeTask
+            | Command::Batch { .. }
+            | Command::Replay { .. } => {}
+            Command::Import { file, name } => {
+                let db_path = resolve_db_path(&settings)?;
+                let project = resolve_project(&settings);
+                return handle_import(
+                    &db_path,
+                    project,
+                    &settings.embeddings,
+                    file,
+                    name.as_deref(),
+                )
+                .await;
+            }
+            Command::Stats => {
+                return handle_stats(&resolve_db_path(&settings)?);
+            }
+            Command::Events { session, limit } => {
+                return handle_events(&resolve_db_path(&settings)?, session.as_deref(), *limit);
+            }
+            Command::Slack {
+                app_token,
+                bot_token,
+            } => {
+                prepare_bot(&settings, "slack")?;
+                return golem::slack::run(settings, app_token.clone(), bot_token.clone()).await;
+            }
+            Command::Discord { token } => {
+                prepare_bot(&settings, "discord")?;
+                return golem::discord::run(settings, token.clone()).await;
+            }
+            Command::Matrix {
+                homeserver,
+                access_token,
+            } => {
+                prepare_bot(&settings, "matrix")?;
+                return golem::matrix::run(settings, homeserver.clone(), access_token.clone())
+                    .await;
+            }
+            Command::Daemon { .. } | Command::Attach { .. } => {
+                return handle_daemon(&settings, matches!(command, Command::Daemon { .. })).await;
+            }
+            Command::Schedule { command } => {
+                return handle_schedule(&settings, command);
+            }
+            Command::Serve { .. } => {
+                prepare_bot(&settings, "serve")?;
+                return golem::serve::run(settings).await;
+            }
+            Command::Man => {
+                return handle_man();
+            }
+            Command::Doctor => unreachable!("handled before settings are loaded"),
         }
-    };
-
-    let shell_mode = if cli.allow_write {
-        ShellMode::ReadWrite
+    }
+    // A first interactive run with nothing set up starts with the wizard
+    let setup_db = resolve_db_path(&settings)?;
+    let settings = if needs_setup(&cli, &settings, &setup_db)? {
+        run_setup(&setup_db).await?;
+        Settings::load(&std::env::current_dir()?)?.merge(cli.settings())
     } else {
-        ShellMode::ReadOnly
-    };
-    let working_dir = cli
-        .work_dir
-        .unwrap_or_else(|| std::env::temp_dir().join("golem-sandbox"));
-
-    let shell_config = ShellConfig {
-        mode: shell_mode,
-        working_dir: working_dir.clone(),
-        require_confirmation: !cli.no_confirm,
-        ..ShellConfig::default()
+        settings
     };
 
-    let memory_label = if db_path == ":memory:" {
-        "ephemeral".to_string()
-    } else {
-        db_path.clone()
-    };
+    let resume_task = matches!(cli.command, Some(Command::ResumeTask));
+    let resume = cli.resume || resume_task || matches!(cli.command, Some(Command::Resume));
 
-    let shell_label = if shell_mode == ShellMode::ReadWrite {
-        "read-write"
-    } else {
-        "read-only"
-    };
+    let db_path = resolve_db_path(&settings)?;
+    if db_path != NO_DATABASE && db_path != ":memory:" {
+        import_legacy_auth(&db_path)?;
+    }
+    if cli.ci && matches!(provider(&settings)?, Provider::Human) {
+        anyhow::bail!("--ci needs a model provider; the human provider asks at the terminal");
+    }
+    if matches!(provider(&settings)?, Provider::Human) && settings.model.is_some() {
+        eprintln!("warning: --model is ignored for human provider");
+    }
 
-    print_banner(&BannerInfo {
+    // Queued and replayed tasks each get an engine with a clean
+    // in-process memory; the session's stays untouched
+    let queued = matches!(
+        cli.command,
+        Some(Command::Batch { .. } | Command::Replay { .. })
+    ) || (cli.run.len() > 1 && !cli.plan);
+    let step_mode = Arc::new(StepMode::new(cli.step));
+    let mut builder = GolemBuilder::new(settings.clone())
+        .echo(echo)
+        .hook(step_mode.clone());
+    if queued {
+        builder = builder.memory(Box::new(InMemoryMemory::new()));
+    }
+    if cli.ci {
+        builder = builder
+            .approver(Box::new(ci::CiApprover))
+            .hook(Arc::new(ci::Annotations));
+    }
+    if cli.json_events {
+        let events = JsonEvents::stdout();
+        builder = builder
+            .approver(Box::new(events.clone()))
+            .hook(Arc::new(events));
+    }
+    let golem = builder.build().await?;
+    let queue_engine = golem.engine_factory();
+    let Golem {
+        mut engine,
+        thinker,
+        tools,
+        events,
+        config,
+        shell: mut shell_config,
+        db_path,
         provider: provider_name,
-        model: &model_name,
-        auth_status: &auth_status,
-        shell_mode: shell_label,
-        working_dir: &working_dir,
-        memory: &memory_label,
-    });
+        model: mut model_name,
+        mut auth_status,
+        memory_label,
+        ..
+    } = golem;
+    let mut shell_label = shell_config.mode.label();
 
-    let tools = Arc::new(ToolRegistry::new());
-    tools.register(Arc::new(ShellTool::new(shell_config))).await;
+    if echo {
+        print_banner(
+            settings.banner.unwrap_or_default(),
+            &BannerInfo {
+                provider: &provider_name,
+                model: &model_name,
+                auth_status: &auth_status,
+                shell_mode: shell_label,
+                working_dir: &shell_config.working_dir,
+                memory: &memory_label,
+            },
+        );
+    }
+
+    if let Some(Command::Batch { file }) = &cli.command {
+        let tasks = read_tasks(file)?;
+        let report = cli
+            .report
+            .clone()
+            .unwrap_or_else(|| file.with_extension("report.json"));
+        let make_engine = || queue_engine(engine.config());
+        let results = run_queue(&tasks, cli.parallel, make_engine, Some(&report)).await?;
+        return check_queue(&results);
+    }
+    if cli.run.len() > 1 && !cli.plan {
+        let make_engine = || queue_engine(engine.config());
+        let results = run_queue(&cli.run, cli.parallel, make_engine, cli.report.as_deref()).await?;
+        return check_queue(&results);
+    }
+
+    // One engine per recorded task, answering from the recording
+    if let Some(Command::Replay { file, execute }) = &cli.command {
+        let parse_retries = settings.parse_retries.unwrap_or(DEFAULT_PARSE_RETRIES);
+        return handle_replay(file, *execute, parse_retries, thinker, tools, config).await;
+    }
 
     // Collect tool names for /tools command
-    let tool_names: Vec<String> = tools
-        .descriptions()
-        .await
-        .iter()
-        .map(|t| format!("{} — {}", t.name, t.description))
-        .collect();
+    let mut tool_names = tool_lines(&tools).await;
+
+    let app_config = Config::open(&db_path)?;
+    let mut session_name = engine.active_session().await?;
+    if resume && echo {
+        print_resume_recap(&session_name, &engine.session_history().await?);
+    }
 
-    let memory = Box::new(SqliteMemory::new(&db_path)?);
+    // A single task has nobody to ask, so it stops at the budget
+    if cli.run.is_empty() && shell_config.require_confirmation {
+        engine.set_budget_approver(Box::new(PromptBudgetApprover));
+    }
 
-    let config = ReactConfig {
-        max_iterations: cli.max_iterations,
-        tool_timeout: Duration::from_secs(cli.timeout),
+    let transcript = if settings.transcript.unwrap_or(false) {
+        let transcript = Arc::new(Transcript::open(transcripts_dir(), &session_name)?);
+        if echo {
+            println!("  transcript: {}", transcript.path().display());
+        }
+        engine.set_transcript(Arc::clone(&transcript)).await;
+        Some(transcript)
+    } else {
+        None
     };
+    let mut commands = CommandRegistry::new();
+    for (name, prompt) in &settings.macros {
+        let command = MacroCommand::new(name, prompt);
+        if commands.all_triggers().contains(&command.name()) {
+            eprintln!(
+                "warning: macro {} has the name of a built-in command; ignoring it",
+                command.name()
+            );
+            continue;
+        }
+        commands.register(Arc::new(command));
+    }
 
-    let mut engine = ReactEngine::new(thinker, tools, memory, config);
-    let commands = CommandRegistry::new();
-    let app_config = Config::open(&db_path)?;
+    // Pick up an interrupted task, then exit
+    if resume_task {
+        print_answer(run_interruptible(&mut engine, None).await, echo);
+        if echo {
+            print_session_summary(engine.session_usage());
+        }
+        return Ok(());
+    }
 
-    // Single task mode
-    if let Some(task) = cli.run {
-        match engine.run(&task).await {
-            Ok(answer) => println!("\n=> {}", answer),
-            Err(e) => eprintln!("\nerror: {}", e),
+    // Single task mode (several tasks only get here to be planned)
+    if cli.ci {
+        let task = &cli.run[0];
+        let result = run_interruptible(&mut engine, Some(task)).await;
+        if let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") {
+            ci::write_step_summary(Path::new(&path), task, &result)?;
+        }
+        if let Some(path) = std::env::var_os("GITHUB_OUTPUT") {
+            ci::write_outputs(Path::new(&path), &result)?;
+        }
+        let code = ci::exit_code(&result);
+        print_answer(result, echo);
+        std::process::exit(code);
+    }
+    if cli.json_events {
+        // The finish event carries the answer
+        if run_interruptible(&mut engine, Some(&cli.run[0]))
+            .await
+            .is_err()
+        {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if json {
+        let result = run_interruptible(&mut engine, Some(&cli.run[0])).await;
+        print_task_json(&result);
+        if result.is_err() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if !cli.run.is_empty() {
+        for task in &cli.run {
+            if cli.plan {
+                print_plan(engine.plan(task).await);
+            } else {
+                print_answer(run_interruptible(&mut engine, Some(task)).await, echo);
+            }
+        }
+        if echo {
+            print_session_summary(engine.session_usage());
         }
-        print_session_summary(engine.session_usage());
         return Ok(());
     }
+    let queue = TaskQueue::new();
 
-    // REPL — async stdin so Ctrl+C is caught at the prompt too
-    let stdin = BufReader::new(tokio::io::stdin());
-    let mut lines = stdin.lines();
+    // REPL — the editor reads Ctrl+C and Ctrl+D as keys at the prompt
+    let model_names = ModelNames::default();
+    {
+        // Listed in the background so the prompt comes up right away
+        let thinker = Arc::clone(&thinker);
+        let model_names = Arc::clone(&model_names);
+        tokio::spawn(async move {
+            if let Ok(models) = thinker.read().await.models().await {
+                *model_names.lock().expect("model names lock poisoned") =
+                    models.into_iter().map(|m| m.id).collect();
+            }
+        });
+    }
+    let mut editor = LineEditor::new(
+        settings.edit_mode.unwrap_or_default(),
+        &settings.keys,
+        Some(history_path()),
+        commands
+            .all_triggers()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        model_names,
+    )?;
+
+    let mut event_rx = events.subscribe_filtered(&[EventKind::ShellModeChanged]);
 
     loop {
-        print!("\ngolem> ");
-        io::stdout().flush()?;
-
-        // Read next line, interruptible by Ctrl+C
-        let line = tokio::select! {
-            result = lines.next_line() => {
-                match result {
-                    Ok(Some(line)) => line,
-                    Ok(None) => {
-                        // Ctrl+D (EOF)
-                        println!();
-                        break;
-                    }
-                    Err(e) => {
-                        eprintln!("input error: {}", e);
-                        break;
-                    }
+        // Only mode changes come here, and only the last one matters
+        loop {
+            match event_rx.try_recv() {
+                Ok(Event::ShellModeChanged { mode }) => {
+                    shell_label = mode.label();
+                    tool_names = tool_lines(&tools).await;
                 }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(_) => break,
             }
-            _ = tokio::signal::ctrl_c() => {
+        }
+
+        println!();
+        let session = if session_name == DEFAULT_SESSION_NAME {
+            String::new()
+        } else {
+            format!("[{session_name}]")
+        };
+        // Write access is easy to forget once granted
+        let mode = if shell_config.mode == ShellMode::ReadWrite {
+            " (rw)"
+        } else {
+            ""
+        };
+        let prompt = format!("golem{session}{mode}> ");
+
+        let line = match tokio::task::block_in_place(|| editor.read_line(&prompt)) {
+            Ok(Input::Line(line)) => line,
+            Ok(Input::Interrupted | Input::Eof) => {
                 println!();
                 break;
             }
+            Err(e) => {
+                eprintln!("input error: {}", e);
+                break;
+            }
         };
 
         let task = line.trim();
@@ -274,20 +843,27 @@ async fn main() -> anyhow::Result<()> {
 
         // Built-in slash commands (includes /quit, quit, exit)
         let session_info = SessionInfo {
-            provider: provider_name,
+            provider: &provider_name,
             model: &model_name,
             auth_status: &auth_status,
             shell_mode: shell_label,
+            work_dir: &shell_config.working_dir,
+            step_mode: step_mode.enabled(),
             tools: &tool_names,
             usage: engine.session_usage(),
             db_path: &db_path,
             engine: Some(&engine),
+            queue: Some(&queue),
         };
-        match commands.dispatch(task, &session_info).await {
+        let task = match commands.dispatch(task, &session_info).await {
             CommandResult::Handled => continue,
             CommandResult::StateChanged(change) => {
                 match change {
                     StateChange::Auth(new_status) => {
+                        events.emit(Event::AuthChanged {
+                            provider: provider_name.clone(),
+                            status: new_status.clone(),
+                        });
                         auth_status = new_status;
                     }
                     StateChange::Model(new_model) => {
@@ -295,83 +871,663 @@ async fn main() -> anyhow::Result<()> {
                         if let Err(e) = app_config.set("model", &new_model) {
                             eprintln!("  warning: failed to persist model preference: {e}");
                         }
+                        events.emit(Event::ModelChanged {
+                            model: new_model.clone(),
+                        });
                         model_name = new_model;
                     }
+                    StateChange::Session(name) => {
+                        if let Err(e) = app_config.set("session", &name) {
+                            eprintln!("  warning: failed to persist session: {e}");
+                        }
+                        if let Some(transcript) = &transcript
+                            && let Err(e) = transcript.set_session(&name)
+                        {
+                            eprintln!("  warning: {e}");
+                        }
+                        session_name = name;
+                    }
+                    StateChange::StepMode(enabled) => step_mode.set_enabled(enabled),
+                    StateChange::ShellMode(mode) => {
+                        shell_config.mode = mode;
+                        tools
+                            .register(Arc::new(ShellTool::new(shell_config.clone())))
+                            .await;
+                        events.emit(Event::ShellModeChanged { mode });
+                    }
+                    StateChange::MaxIterations(max) => {
+                        engine.set_max_iterations(max);
+                        if let Err(e) = app_config.set("max_iterations", &max.to_string()) {
+                            eprintln!("  warning: failed to persist max iterations: {e}");
+                        }
+                    }
+                    StateChange::ToolTimeout(timeout) => {
+                        engine.set_tool_timeout(timeout);
+                        let secs = timeout.as_secs().to_string();
+                        if let Err(e) = app_config.set("timeout", &secs) {
+                            eprintln!("  warning: failed to persist timeout: {e}");
+                        }
+                    }
+                    StateChange::Persona(persona) => {
+                        engine.set_persona(persona);
+                        let saved = match persona {
+                            Some(persona) => app_config.set("persona", persona.name),
+                            None => app_config.remove("persona"),
+                        };
+                        if let Err(e) = saved {
+                            eprintln!("  warning: failed to persist persona: {e}");
+                        }
+                    }
+                    StateChange::Language(language) => {
+                        let saved = match &language {
+                            Some(language) => app_config.set("language", language),
+                            None => app_config.remove("language"),
+                        };
+                        if let Err(e) = saved {
+                            eprintln!("  warning: failed to persist language: {e}");
+                        }
+                        engine.set_language(language);
+                    }
+                    StateChange::WorkDir(dir) => {
+                        // Replaces the registered shell tool, for queued
+                        // tasks and sub-agents too
+                        shell_config.working_dir = dir;
+                        tools
+                            .register(Arc::new(ShellTool::new(shell_config.clone())))
+                            .await;
+                    }
+                    StateChange::RunQueue => {
+                        let tasks = queue.take();
+                        // With the settings changed since startup, e.g. by /timeout
+                        let make_engine = || queue_engine(engine.config());
+                        let run =
+                            run_queue(&tasks, cli.parallel, make_engine, cli.report.as_deref());
+                        // Dropping the run kills the commands still running
+                        tokio::select! {
+                            result = run => {
+                                if let Err(e) = result {
+                                    eprintln!("  ✗ {e}");
+                                }
+                            }
+                            _ = tokio::signal::ctrl_c() => {
+                                println!("\n\nqueue interrupted — running commands were terminated");
+                            }
+                        }
+                    }
                 }
                 continue;
             }
             CommandResult::Quit => break,
-            CommandResult::NotACommand => {}
+            CommandResult::RunTask(text) => text,
+            CommandResult::NotACommand => task.to_string(),
+        };
+
+        if cli.plan {
+            print_plan(engine.plan(&task).await);
+            continue;
         }
 
         // Ctrl+C during task execution cancels the task, not the REPL
-        tokio::select! {
-            result = engine.run(task) => {
-                match result {
-                    Ok(answer) => println!("\n=> {}", answer),
-                    Err(e) => eprintln!("\nerror: {}", e),
-                }
-            }
-            _ = tokio::signal::ctrl_c() => {
-                println!("\n\ninterrupted");
+        print_answer(run_interruptible(&mut engine, Some(&task)).await, echo);
+    }
+
+    if echo {
+        print_session_summary(engine.session_usage());
+    }
+    Ok(())
+}
+
+/// One line per tool the policy offers: name, capabilities, description.
+async fn tool_lines(tools: &ToolRegistry) -> Vec<String> {
+    tools
+        .descriptions()
+        .await
+        .iter()
+        .map(|t| {
+            let labels = t.capabilities.labels();
+            if labels.is_empty() {
+                format!("{} — {}", t.name, t.description)
+            } else {
+                format!("{} [{}] — {}", t.name, labels.join(", "), t.description)
             }
+        })
+        .collect()
+}
+
+/// Run `tasks` on fresh engines from `make_engine`, `parallel` at a time,
+/// then print a summary and write the report, if asked for one.
+async fn run_queue(
+    tasks: &[String],
+    parallel: usize,
+    make_engine: impl Fn() -> ReactEngine,
+    report: Option<&Path>,
+) -> anyhow::Result<Vec<BatchResult>> {
+    let results = run_batch(tasks, parallel, make_engine).await;
+    if let Some(report) = report {
+        write_report(report, &results)?;
+    }
+    print_batch_summary(&results, report);
+    Ok(results)
+}
+
+/// Fail (for a non-zero exit code) if any queued task failed.
+fn check_queue(results: &[BatchResult]) -> anyhow::Result<()> {
+    let failed = results.iter().filter(|r| !r.succeeded()).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} tasks failed", results.len());
+    }
+    Ok(())
+}
+
+/// Run `task`, or resume the unfinished one if `None`. Ctrl+C cancels it
+/// cleanly: running commands are killed and the steps so far are kept.
+async fn run_interruptible(
+    engine: &mut ReactEngine,
+    task: Option<&str>,
+) -> anyhow::Result<TaskResult> {
+    let cancel = CancellationToken::new();
+    engine.set_cancellation(cancel.clone());
+    let run = async {
+        match task {
+            Some(task) => engine.run(task).await,
+            None => engine.resume().await,
+        }
+    };
+    tokio::pin!(run);
+    tokio::select! {
+        result = &mut run => result,
+        _ = tokio::signal::ctrl_c() => {
+            cancel.cancel();
+            run.await
         }
     }
+}
 
-    print_session_summary(engine.session_usage());
+/// Print the answer, with how long it took unless `echo` is off.
+fn print_answer(result: anyhow::Result<TaskResult>, echo: bool) {
+    match result {
+        Ok(result) if !echo => println!("{}", result.answer),
+        Ok(result) => {
+            print_answer_text(&result.answer);
+            print_task_result(&result);
+        }
+        Err(e) if e.is::<Interrupted>() => {
+            println!("\n\ninterrupted — running commands were terminated");
+            println!("  `golem resume-task` continues it, until another task starts");
+        }
+        Err(e) => eprintln!("\nerror: {}", e),
+    }
+}
+
+fn print_plan(result: anyhow::Result<String>) {
+    match result {
+        Ok(plan) => println!("\nPlan (nothing was run):\n{}", plan.trim_end()),
+        Err(e) => eprintln!("\nerror: {}", e),
+    }
+}
+
+async fn handle_doctor(cli: &Cli) -> anyhow::Result<()> {
+    let dir = std::env::current_dir()?;
+    let mut checks = doctor::check_config(&user_config_path(), &dir.join(PROJECT_CONFIG_FILE));
+    // A file that doesn't parse was reported above; check the rest without it
+    let settings = Settings::load(&dir)
+        .unwrap_or_default()
+        .merge(cli.settings());
+    let db_path = resolve_db_path(&settings)?;
+    if db_path != NO_DATABASE && db_path != ":memory:" {
+        checks.push(doctor::check_database(&db_path));
+    }
+    match provider(&settings)? {
+        Provider::Anthropic => checks
+            .push(doctor::check_anthropic(&db_path, std::env::var("ANTHROPIC_API_KEY").ok()).await),
+        Provider::Human => {}
+    }
+    checks.extend(doctor::check_programs(std::env::var_os("PATH").as_deref()));
+
+    print_doctor_report(&checks);
+    if checks.iter().any(|c| c.status == doctor::Status::Fail) {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-async fn handle_login(provider: &LoginProvider) -> anyhow::Result<()> {
-    let db_path = default_db_path();
-    let db_str = db_path.to_string_lossy();
+/// Write the man page: what clap knows about flags and subcommands, plus
+/// the REPL commands and the files golem reads.
+fn handle_man() -> anyhow::Result<()> {
+    use clap_mangen::roff::{Roff, bold, italic, roman};
 
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
+    let man = clap_mangen::Man::new(Cli::command());
+    let mut out = Vec::new();
+    man.render_title(&mut out)?;
+    man.render_name_section(&mut out)?;
+    man.render_synopsis_section(&mut out)?;
+    man.render_description_section(&mut out)?;
+    man.render_options_section(&mut out)?;
+    man.render_subcommands_section(&mut out)?;
+
+    let mut roff = Roff::new();
+    roff.control("SH", ["REPL COMMANDS"]);
+    for (label, description) in CommandRegistry::new().help_entries() {
+        roff.control("TP", []);
+        roff.text([bold(label)]);
+        roff.text([roman(description)]);
+    }
+    roff.control("SH", ["FILES"]);
+    // Spelled with `~` rather than this user's home directory
+    for (path, description) in [
+        (
+            "~/.golem/golem.db",
+            "memory, sessions, credentials, facts and metrics (see --db)",
+        ),
+        ("~/.golem/config.toml", "user settings"),
+        (PROJECT_CONFIG_FILE, "project settings, over the user's"),
+        ("~/.golem/history", "prompt history"),
+        (
+            "~/.golem/transcripts/",
+            "transcripts written with --transcript",
+        ),
+    ] {
+        roff.control("TP", []);
+        roff.text([italic(path)]);
+        roff.text([roman(description)]);
     }
+    roff.to_writer(&mut out)?;
 
-    let provider_name = match provider {
-        LoginProvider::Anthropic => "anthropic",
-    };
+    man.render_version_section(&mut out)?;
+    man.render_authors_section(&mut out)?;
 
-    println!("Logging in to {provider_name} (Claude Pro/Max)...\n");
+    // `golem man | head` closing the pipe early is not an error
+    match io::stdout().write_all(&out) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
 
-    let (url, verifier) = oauth::build_authorize_url();
-    let _ = open::that(&url);
+async fn handle_replay(
+    file: &Path,
+    execute: bool,
+    parse_retries: usize,
+    thinker: Arc<RwLock<Box<dyn Thinker>>>,
+    tools: Arc<ToolRegistry>,
+    config: ReactConfig,
+) -> anyhow::Result<()> {
+    let tasks = read_recording(file, !execute)?;
+    let model = tasks.iter().find_map(|t| t.model.clone());
+    let responses: ResponseQueue = Arc::default();
+    // Swapped in place so the delegate tool's sub-agents replay too
+    *thinker.write().await = Box::new(
+        ReplayThinker::new(
+            model.unwrap_or_else(|| "recorded".to_string()),
+            Arc::clone(&responses),
+        )
+        .with_parse_retries(parse_retries),
+    );
 
-    println!("Open this URL to authenticate:\n");
-    println!("  {url}\n");
+    let mut diverged = 0;
+    for (i, task) in tasks.iter().enumerate() {
+        println!(
+            "\nreplaying task {} of {}: {}",
+            i + 1,
+            tasks.len(),
+            task.task
+        );
+        *responses.lock().expect("replay queue lock poisoned") =
+            task.responses.iter().cloned().collect();
+        let tools = if execute {
+            Arc::clone(&tools)
+        } else {
+            Arc::new(recorded_tools(task).await)
+        };
+        let mut engine = ReactEngine::with_shared_thinker(
+            Arc::clone(&thinker),
+            tools,
+            Box::new(InMemoryMemory::new()),
+            config.clone(),
+        );
+        let result = engine.run(&task.task).await;
+        if !task.matches(&result) {
+            diverged += 1;
+        }
+        print_replay_outcome(task.answer.as_deref(), &result);
+    }
+    if diverged > 0 {
+        anyhow::bail!(
+            "{diverged} of {} tasks diverged from the recording",
+            tasks.len()
+        );
+    }
+    Ok(())
+}
 
-    print!("Paste the authorization code: ");
-    io::stdout().flush()?;
-    let mut code = String::new();
-    io::stdin().read_line(&mut code)?;
-    let code = code.trim();
+/// List the stored credentials and any API key in the environment;
+/// with `verify`, also check each credential against its provider.
+async fn handle_auth_status(db_path: &str, verify: bool) -> anyhow::Result<()> {
+    let storage = AuthStorage::open(db_path)?;
+    let mut credentials = Vec::new();
+    for stored in golem::auth::stored(&storage)? {
+        let checked = if verify {
+            Some(
+                golem::auth::verify(db_path, &stored.provider, &stored.account)
+                    .await
+                    .map_err(|e| format!("{e:#}")),
+            )
+        } else {
+            None
+        };
+        credentials.push((stored, checked));
+    }
+    let env_keys: Vec<&str> = ["ANTHROPIC_API_KEY"]
+        .into_iter()
+        .filter(|key| std::env::var(key).is_ok_and(|v| !v.is_empty()))
+        .collect();
+    print_auth_status(&credentials, &env_keys);
+    if credentials
+        .iter()
+        .any(|(_, checked)| matches!(checked, Some(Err(_))))
+    {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// The database `golem login`, `golem logout` and `golem auth` use: the REPL's, with
+/// any legacy `auth.json` imported into it.
+fn credentials_db(settings: &Settings) -> anyhow::Result<String> {
+    let db_path = resolve_db_path(settings)?;
+    if db_path == NO_DATABASE || db_path == ":memory:" {
+        anyhow::bail!("credentials need a database file; drop --db {db_path}");
+    }
+    import_legacy_auth(&db_path)?;
+    Ok(db_path)
+}
+
+/// Check that a bot or server has a model to think with, and its
+/// credentials.
+fn prepare_bot(settings: &Settings, bot: &str) -> anyhow::Result<()> {
+    if matches!(provider(settings)?, Provider::Human) {
+        anyhow::bail!("golem {bot} needs a model provider, not human");
+    }
+    let db_path = resolve_db_path(settings)?;
+    if db_path != NO_DATABASE && db_path != ":memory:" {
+        import_legacy_auth(&db_path)?;
+    }
+    Ok(())
+}
+
+/// Move credentials from a pre-database `~/.golem/auth.json` into `db_path`.
+fn import_legacy_auth(db_path: &str) -> anyhow::Result<()> {
+    let path = legacy_auth_path();
+    let imported = golem::auth::import_legacy(&AuthStorage::open(db_path)?, &path)?;
+    if imported > 0 {
+        eprintln!(
+            "✓ Imported {imported} credential(s) from {} into {db_path}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// The provider to use: the one set by a flag, variable or config file,
+/// else Anthropic.
+fn provider(settings: &Settings) -> anyhow::Result<Provider> {
+    match settings.provider.as_deref() {
+        Some(name) => Provider::from_str(name, true).map_err(|_| {
+            anyhow::anyhow!("unknown provider \"{name}\" (expected anthropic or human)")
+        }),
+        None => Ok(Provider::Anthropic),
+    }
+}
+
+fn open_memory(
+    db_path: &str,
+    project: Option<String>,
+    embeddings: &EmbeddingSettings,
+) -> anyhow::Result<SqliteMemory> {
+    let memory = SqliteMemory::new(db_path)?.with_embedder(embedding::from_settings(embeddings)?);
+    Ok(match project {
+        Some(project) => memory.with_project(project),
+        None => memory,
+    })
+}
+
+async fn handle_import(
+    db_path: &str,
+    project: Option<String>,
+    embeddings: &EmbeddingSettings,
+    file: &Path,
+    name: Option<&str>,
+) -> anyhow::Result<()> {
+    if db_path == NO_DATABASE {
+        anyhow::bail!("import needs a database; drop `--db none`");
+    }
+    let json = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let export: SessionExport = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a golem session export", file.display()))?;
+    let name = name.unwrap_or(&export.session);
+
+    let memory = open_memory(db_path, project, embeddings)?;
+    memory.import_session(name, &export.tasks).await?;
+
+    println!(
+        "✓ imported {} task(s) into session {name}. Use `/session switch {name}` to continue it.",
+        export.tasks.len()
+    );
+    Ok(())
+}
+
+fn handle_stats(db_path: &str) -> anyhow::Result<()> {
+    if db_path == NO_DATABASE {
+        anyhow::bail!("stats needs a database; drop `--db none`");
+    }
+    print_task_stats(&MetricsStore::open(db_path)?.summary()?);
+    Ok(())
+}
+
+fn handle_events(db_path: &str, session: Option<&str>, limit: usize) -> anyhow::Result<()> {
+    if db_path == NO_DATABASE {
+        anyhow::bail!("events needs a database; drop `--db none`");
+    }
+    let session = match session {
+        Some(session) => session.to_string(),
+        None => Config::open(db_path)?
+            .get("session")?
+            .unwrap_or_else(|| DEFAULT_SESSION_NAME.to_string()),
+    };
+    let log = EventLog::open(db_path, &session)?;
+    for event in log.timeline(&session, limit)? {
+        println!("{}", event.to_json());
+    }
+    Ok(())
+}
+
+/// Run `golem daemon`, or attach to one.
+#[cfg(unix)]
+async fn handle_daemon(settings: &Settings, daemon: bool) -> anyhow::Result<()> {
+    let socket = settings
+        .socket
+        .clone()
+        .unwrap_or_else(golem::consts::daemon_socket_path);
+    if daemon {
+        prepare_bot(settings, "daemon")?;
+        golem::daemon::run(settings.clone(), &socket).await
+    } else {
+        golem::daemon::attach::run(settings, &socket).await
+    }
+}
+
+#[cfg(not(unix))]
+async fn handle_daemon(_settings: &Settings, _daemon: bool) -> anyhow::Result<()> {
+    anyhow::bail!("golem daemon and golem attach need unix sockets, which this platform lacks")
+}
 
-    if code.is_empty() {
-        anyhow::bail!("no authorization code provided");
+fn handle_schedule(settings: &Settings, command: &ScheduleCommand) -> anyhow::Result<()> {
+    let db_path = resolve_db_path(settings)?;
+    if db_path == NO_DATABASE || db_path == ":memory:" {
+        anyhow::bail!("schedules need a database file; drop --db {db_path}");
     }
+    let store = ScheduleStore::open(&db_path)?;
+    match command {
+        ScheduleCommand::Add {
+            cron,
+            task,
+            id,
+            notify,
+        } => {
+            let task = resolve_task(task)?;
+            let id = store.add(id.as_deref(), cron, &task, notify.as_deref())?;
+            let added = schedule::Schedule {
+                id: id.clone(),
+                cron: cron.clone(),
+                task,
+                notify: notify.clone(),
+            };
+            if settings.schedules.contains_key(&id) {
+                println!("✓ Added schedule {id}; a config file's schedule {id} wins over it");
+            } else {
+                let next = added.next_after(&chrono::Local::now())?;
+                println!(
+                    "✓ Added schedule {id}, next run {}",
+                    next.format("%Y-%m-%d %H:%M")
+                );
+            }
+            println!("  `golem serve` runs schedules; restart it to pick this one up");
+        }
+        ScheduleCommand::List => {
+            let now = chrono::Local::now();
+            let schedules: Vec<_> = schedule::all(settings, &store)?
+                .into_iter()
+                .map(|s| {
+                    let next = s.next_after(&now).ok();
+                    (
+                        s,
+                        next.map(|next| next.format("%Y-%m-%d %H:%M").to_string()),
+                    )
+                })
+                .collect();
+            print_schedules(&schedules);
+        }
+        ScheduleCommand::Remove { id } => {
+            if !store.remove(id)? {
+                if settings.schedules.contains_key(id) {
+                    anyhow::bail!("schedule {id} is in a config file; remove it there");
+                }
+                anyhow::bail!("no schedule {id}");
+            }
+            println!("✓ Removed schedule {id}");
+        }
+        ScheduleCommand::Runs { id, limit } => {
+            print_schedule_runs(&store.runs(id.as_deref(), *limit)?);
+        }
+    }
+    Ok(())
+}
 
-    println!("\nExchanging code for tokens...");
-    golem::auth::login(&db_str, provider_name, code, &verifier).await?;
+async fn handle_login(
+    db_str: &str,
+    provider: &LoginProvider,
+    account: Option<&str>,
+    paste: bool,
+    device: bool,
+) -> anyhow::Result<()> {
+    let provider_name = match provider {
+        LoginProvider::Anthropic => "anthropic",
+    };
 
+    if let Some(account) = account {
+        AuthStorage::open(db_str)?.set_account(provider_name, account)?;
+    }
+    if device {
+        println!("Logging in to {provider_name} with a device code...\n");
+        golem::auth::device_login(db_str, provider_name).await?;
+    } else {
+        oauth_login(db_str, provider_name, paste).await?;
+    }
     println!("✓ Logged in to {provider_name} successfully!");
     Ok(())
 }
 
-fn handle_logout(provider: &LoginProvider) -> anyhow::Result<()> {
-    let db_path = default_db_path();
-    let db_str = db_path.to_string_lossy();
+/// The browser login, announced.
+async fn oauth_login(db_path: &str, provider_name: &str, paste: bool) -> anyhow::Result<()> {
+    println!("Logging in to {provider_name} (Claude Pro/Max)...\n");
+    golem::auth::browser_login(db_path, provider_name, paste).await
+}
+
+/// Whether to start with the setup wizard: an interactive REPL with no
+/// config file, no provider chosen by flag or env, and no credentials.
+fn needs_setup(cli: &Cli, settings: &Settings, db_path: &str) -> anyhow::Result<bool> {
+    let interactive = cli.command.is_none()
+        && cli.run.is_empty()
+        && !cli.quiet
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal();
+    if !interactive
+        || settings.provider.is_some()
+        || db_path == ":memory:"
+        || db_path == NO_DATABASE
+        || user_config_path().exists()
+        || Path::new(PROJECT_CONFIG_FILE).exists()
+        || std::env::var("ANTHROPIC_API_KEY").is_ok_and(|k| !k.is_empty())
+    {
+        return Ok(false);
+    }
+    Ok(AuthStorage::open(db_path)?.get("anthropic")?.is_none())
+}
 
-    if let Some(parent) = db_path.parent() {
+/// Run the setup wizard, log in as asked and write the user config file.
+/// Skipped with Ctrl+D, which leaves everything as it was.
+async fn run_setup(db_path: &str) -> anyhow::Result<()> {
+    println!("Welcome to golem! A few questions to get you set up (Ctrl+D skips).");
+    // The wizard reads stdin line by line, so it runs on a blocking thread.
+    let asked =
+        tokio::task::spawn_blocking(|| wizard::ask(&mut io::stdin().lock(), &mut io::stdout()))
+            .await?;
+    let setup = match asked {
+        Ok(setup) => setup,
+        Err(_) => {
+            println!("\nsetup skipped — it runs again until ~/.golem/config.toml exists");
+            return Ok(());
+        }
+    };
+    match &setup.login {
+        Login::OAuth => {
+            println!();
+            if let Err(e) = oauth_login(db_path, setup.provider, false).await {
+                eprintln!("✗ login failed: {e:#}; try again with `golem login`");
+            } else {
+                println!("✓ Logged in");
+            }
+        }
+        Login::ApiKey(key) => {
+            AuthStorage::open(db_path)?
+                .set(setup.provider, Credential::ApiKey { key: key.clone() })?;
+            println!("✓ API key saved");
+        }
+        Login::Skip => {}
+    }
+    let path = user_config_path();
+    if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
+    std::fs::write(&path, setup.to_toml())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    println!("✓ Wrote {} — edit it any time\n", path.display());
+    Ok(())
+}
 
+fn handle_logout(
+    db_str: &str,
+    provider: &LoginProvider,
+    account: Option<&str>,
+) -> anyhow::Result<()> {
     let provider_name = match provider {
         LoginProvider::Anthropic => "anthropic",
     };
 
-    golem::auth::logout(&db_str, provider_name)?;
+    if let Some(account) = account {
+        AuthStorage::open(db_str)?.set_account(provider_name, account)?;
+    }
+    golem::auth::logout(db_str, provider_name)?;
     println!("✓ Logged out from {provider_name}.");
     Ok(())
 }
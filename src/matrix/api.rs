@@ -0,0 +1,499 @@
+//! The parts of the Matrix client-server API the bot uses.
+//!
+//! Everything goes through the homeserver with the bot account's access
+//! token: `/sync` long-polls for invites, messages and reactions, and
+//! `/send` posts replies. Encrypted rooms aren't read.
+
+use anyhow::{Context, Result, bail};
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::thinker::http::shared_client;
+
+/// Only the events the bot reacts to come down `/sync`.
+const SYNC_FILTER: &str = r#"{"presence":{"types":[]},"account_data":{"types":[]},"room":{"timeline":{"types":["m.room.message","m.reaction"]},"state":{"lazy_load_members":true},"ephemeral":{"types":[]},"account_data":{"types":[]}}}"#;
+
+/// Reactions that approve a call; those in [`DENY_KEYS`] deny it.
+pub const APPROVE_KEYS: &[&str] = &["✅", "👍", "✔️", "✔"];
+pub const DENY_KEYS: &[&str] = &["❌", "👎", "✖️", "✖"];
+
+/// A client-server API client for one account.
+pub struct MatrixApi {
+    client: reqwest::Client,
+    homeserver: String,
+    token: String,
+    /// Makes each sent event's transaction id unique.
+    txn: AtomicU64,
+}
+
+impl MatrixApi {
+    /// A client for `homeserver`, e.g. `https://matrix.example.org`.
+    pub fn new(homeserver: String, token: String) -> Self {
+        Self {
+            client: shared_client(),
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            token,
+            txn: AtomicU64::new(0),
+        }
+    }
+
+    /// The account's user id (`/account/whoami`).
+    pub async fn whoami(&self) -> Result<String> {
+        let data = self
+            .call(Method::GET, &["account", "whoami"], &[], None)
+            .await?;
+        data["user_id"]
+            .as_str()
+            .map(str::to_string)
+            .context("whoami returned no user_id")
+    }
+
+    /// `user`'s display name, if it has one.
+    pub async fn display_name(&self, user: &str) -> Result<Option<String>> {
+        let data = self
+            .call(Method::GET, &["profile", user, "displayname"], &[], None)
+            .await?;
+        Ok(data["displayname"].as_str().map(str::to_string))
+    }
+
+    /// What happened since `since`, waiting up to `timeout` for something
+    /// to; without `since`, the rooms' current state.
+    pub async fn sync(&self, since: Option<&str>, timeout: Duration) -> Result<SyncBatch> {
+        let timeout = timeout.as_millis().to_string();
+        let mut query = vec![("filter", SYNC_FILTER), ("timeout", timeout.as_str())];
+        if let Some(since) = since {
+            query.push(("since", since));
+        }
+        let data = self.call(Method::GET, &["sync"], &query, None).await?;
+        serde_json::from_value(data).context("invalid /sync response")
+    }
+
+    /// Join `room`, accepting an invite to it.
+    pub async fn join(&self, room: &str) -> Result<()> {
+        self.call(Method::POST, &["join", room], &[], Some(json!({})))
+            .await?;
+        Ok(())
+    }
+
+    /// Send an event of `kind` to `room`; returns its event id.
+    pub async fn send(&self, room: &str, kind: &str, content: Value) -> Result<String> {
+        let txn = format!(
+            "golem-{}-{}",
+            std::process::id(),
+            self.txn.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = ["rooms", room, "send", kind, &txn];
+        let data = self.call(Method::PUT, &path, &[], Some(content)).await?;
+        data["event_id"]
+            .as_str()
+            .map(str::to_string)
+            .context("send returned no event_id")
+    }
+
+    async fn call(
+        &self,
+        method: Method,
+        path: &[&str],
+        query: &[(&str, &str)],
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let mut url = endpoint(&self.homeserver, path)?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let name = path[0];
+        let mut request = self.client.request(method, url).bearer_auth(&self.token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("{name} request failed"))?;
+        let status = response.status();
+        let data: Value = response
+            .json()
+            .await
+            .with_context(|| format!("invalid {name} response"))?;
+        if !status.is_success() {
+            bail!(
+                "{name} failed ({status}): {}",
+                data["error"]
+                    .as_str()
+                    .or(data["errcode"].as_str())
+                    .unwrap_or("unknown error")
+            );
+        }
+        Ok(data)
+    }
+}
+
+/// `/_matrix/client/v3/<path>` on `homeserver`, each part escaped.
+fn endpoint(homeserver: &str, path: &[&str]) -> Result<Url> {
+    let mut url =
+        Url::parse(homeserver).with_context(|| format!("invalid homeserver URL {homeserver}"))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("invalid homeserver URL {homeserver}"))?
+        .pop_if_empty()
+        .extend(["_matrix", "client", "v3"])
+        .extend(path);
+    Ok(url)
+}
+
+/// A `/sync` response, as far as the bot reads it.
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncBatch {
+    pub next_batch: String,
+    #[serde(default)]
+    rooms: Rooms,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Rooms {
+    #[serde(default)]
+    join: HashMap<String, JoinedRoom>,
+    #[serde(default)]
+    invite: HashMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct JoinedRoom {
+    #[serde(default)]
+    timeline: Timeline,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Timeline {
+    #[serde(default)]
+    events: Vec<RoomEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoomEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event_id: String,
+    sender: String,
+    #[serde(default)]
+    content: Value,
+}
+
+/// Whom the bot answers to: messages that start with one of these, or
+/// with `!golem`, or that mention its user id.
+#[derive(Debug, Clone)]
+pub struct Names {
+    pub user_id: String,
+    names: Vec<String>,
+}
+
+impl Names {
+    /// The names of `user_id` (`@golem:example.org`): itself, its
+    /// localpart and `display_name`.
+    pub fn new(user_id: &str, display_name: Option<&str>) -> Self {
+        let localpart = user_id
+            .trim_start_matches('@')
+            .split(':')
+            .next()
+            .unwrap_or_default();
+        let mut names = vec![user_id.to_string(), localpart.to_string()];
+        names.extend(display_name.map(str::to_string));
+        // Longest first, so a name isn't cut by a shorter one it starts with
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        Self {
+            user_id: user_id.to_string(),
+            names,
+        }
+    }
+}
+
+/// What a sync brought for the bot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming {
+    /// Someone invited the bot to a room.
+    Invite(String),
+    /// Someone gave the bot a task.
+    Task(Message),
+    /// Someone reacted to a message.
+    Reaction(Reaction),
+}
+
+/// A message addressed to the bot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub room: String,
+    pub event_id: String,
+    pub sender: String,
+    /// The task, empty if the message only named the bot.
+    pub task: String,
+    /// The thread the message is in, if any.
+    pub thread: Option<String>,
+}
+
+impl Message {
+    /// The thread replies go to: the one the message is in, else a new
+    /// one under it.
+    pub fn thread(&self) -> &str {
+        self.thread.as_deref().unwrap_or(&self.event_id)
+    }
+}
+
+/// A reaction to message `event_id`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reaction {
+    pub room: String,
+    pub event_id: String,
+    pub sender: String,
+    pub key: String,
+}
+
+impl Reaction {
+    /// Whether it answers an approval question: yes, no or neither.
+    pub fn approves(&self) -> Option<bool> {
+        if APPROVE_KEYS.contains(&self.key.as_str()) {
+            Some(true)
+        } else if DENY_KEYS.contains(&self.key.as_str()) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl SyncBatch {
+    /// Invites, tasks and reactions, skipping the bot's own events.
+    pub fn incoming(&self, names: &Names) -> Vec<Incoming> {
+        let mut incoming: Vec<_> = self
+            .rooms
+            .invite
+            .keys()
+            .map(|room| Incoming::Invite(room.clone()))
+            .collect();
+        for (room, joined) in &self.rooms.join {
+            for event in &joined.timeline.events {
+                if event.sender == names.user_id {
+                    continue;
+                }
+                let item = match event.kind.as_str() {
+                    "m.room.message" => message(room, event, names).map(Incoming::Task),
+                    "m.reaction" => reaction(room, event).map(Incoming::Reaction),
+                    _ => None,
+                };
+                incoming.extend(item);
+            }
+        }
+        incoming
+    }
+}
+
+fn message(room: &str, event: &RoomEvent, names: &Names) -> Option<Message> {
+    let content = &event.content;
+    let relates_to = &content["m.relates_to"];
+    // Edits repeat the message they change
+    if content["msgtype"] != "m.text" || relates_to["rel_type"] == "m.replace" {
+        return None;
+    }
+    let mentioned = content["m.mentions"]["user_ids"]
+        .as_array()
+        .is_some_and(|ids| ids.iter().any(|id| id == names.user_id.as_str()));
+    let task = task_text(content["body"].as_str()?, names, mentioned)?;
+    let thread = (relates_to["rel_type"] == "m.thread")
+        .then(|| relates_to["event_id"].as_str().map(str::to_string))
+        .flatten();
+    Some(Message {
+        room: room.to_string(),
+        event_id: event.event_id.clone(),
+        sender: event.sender.clone(),
+        task,
+        thread,
+    })
+}
+
+fn reaction(room: &str, event: &RoomEvent) -> Option<Reaction> {
+    let relates_to = &event.content["m.relates_to"];
+    if relates_to["rel_type"] != "m.annotation" {
+        return None;
+    }
+    Some(Reaction {
+        room: room.to_string(),
+        event_id: relates_to["event_id"].as_str()?.to_string(),
+        sender: event.sender.clone(),
+        key: relates_to["key"].as_str()?.to_string(),
+    })
+}
+
+/// The task in a message's `body`, or `None` if it isn't addressed to the
+/// bot: it starts with `!golem` or one of the bot's names (as mention
+/// pills do), or `mentioned` says it names the bot elsewhere. A reply's
+/// quote of the message it answers is dropped.
+pub fn task_text(body: &str, names: &Names, mentioned: bool) -> Option<String> {
+    let body = body
+        .lines()
+        .skip_while(|line| line.starts_with('>'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let body = body.trim();
+    let addressed = std::iter::once("!golem")
+        .chain(names.names.iter().map(String::as_str))
+        .find_map(|name| {
+            let rest = body.strip_prefix(name)?;
+            let rest = rest.strip_prefix([':', ',']).unwrap_or(rest);
+            (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+        });
+    let task = match addressed {
+        Some(rest) => rest,
+        None if mentioned => body,
+        None => return None,
+    };
+    Some(task.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// A message's content: `text` as `msgtype` (`m.text` or `m.notice`) in
+/// the thread under `thread`.
+pub fn thread_message(msgtype: &str, text: &str, thread: &str) -> Value {
+    json!({
+        "msgtype": msgtype,
+        "body": text,
+        "m.relates_to": {
+            "rel_type": "m.thread",
+            "event_id": thread,
+            // Clients without threads show it as a plain reply
+            "is_falling_back": true,
+            "m.in_reply_to": { "event_id": thread },
+        },
+    })
+}
+
+/// A reaction's content: `key` on message `event_id`.
+pub fn reaction_content(event_id: &str, key: &str) -> Value {
+    json!({
+        "m.relates_to": { "rel_type": "m.annotation", "event_id": event_id, "key": key }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> Names {
+        Names::new("@golem:example.org", Some("Golem Bot"))
+    }
+
+    #[test]
+    fn escapes_endpoints() {
+        let url = endpoint(
+            "https://matrix.example.org/",
+            &["rooms", "!abc:example.org", "send", "m.room.message", "t1"],
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://matrix.example.org/_matrix/client/v3/rooms/!abc:example.org/send/m.room.message/t1"
+        );
+        let url = endpoint(
+            "https://example.org/matrix",
+            &["join", "#ops/x:example.org"],
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.org/matrix/_matrix/client/v3/join/%23ops%2Fx:example.org"
+        );
+    }
+
+    #[test]
+    fn reads_a_sync() {
+        let batch: SyncBatch = serde_json::from_str(
+            r#"{"next_batch":"s2","rooms":{
+                "invite":{"!new:example.org":{}},
+                "join":{"!ops:example.org":{"timeline":{"events":[
+                    {"type":"m.room.message","event_id":"$1","sender":"@ann:example.org",
+                     "content":{"msgtype":"m.text","body":"golem: disk usage?"}},
+                    {"type":"m.room.message","event_id":"$2","sender":"@ann:example.org",
+                     "content":{"msgtype":"m.text","body":"lunch?"}},
+                    {"type":"m.room.message","event_id":"$3","sender":"@golem:example.org",
+                     "content":{"msgtype":"m.text","body":"!golem talking to myself"}},
+                    {"type":"m.room.message","event_id":"$4","sender":"@ann:example.org",
+                     "content":{"msgtype":"m.text","body":"* golem: disk usage??",
+                     "m.relates_to":{"rel_type":"m.replace","event_id":"$1"}}},
+                    {"type":"m.room.message","event_id":"$5","sender":"@bob:example.org",
+                     "content":{"msgtype":"m.text","body":"!golem and memory",
+                     "m.relates_to":{"rel_type":"m.thread","event_id":"$1"}}},
+                    {"type":"m.reaction","event_id":"$6","sender":"@bob:example.org",
+                     "content":{"m.relates_to":{"rel_type":"m.annotation","event_id":"$q","key":"✅"}}}
+                ]}}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(batch.next_batch, "s2");
+        let incoming = batch.incoming(&names());
+        assert_eq!(incoming.len(), 4, "{incoming:?}");
+        assert_eq!(
+            incoming[0],
+            Incoming::Invite("!new:example.org".to_string())
+        );
+        let Incoming::Task(first) = &incoming[1] else {
+            panic!("expected a task, got {:?}", incoming[1]);
+        };
+        assert_eq!(first.task, "disk usage?");
+        assert_eq!(first.thread(), "$1");
+        let Incoming::Task(second) = &incoming[2] else {
+            panic!("expected a task, got {:?}", incoming[2]);
+        };
+        assert_eq!(second.task, "and memory");
+        assert_eq!(second.thread(), "$1");
+        let Incoming::Reaction(reaction) = &incoming[3] else {
+            panic!("expected a reaction, got {:?}", incoming[3]);
+        };
+        assert_eq!(reaction.event_id, "$q");
+        assert_eq!(reaction.approves(), Some(true));
+    }
+
+    #[test]
+    fn message_text_becomes_a_task() {
+        let names = names();
+        let task = |body, mentioned| task_text(body, &names, mentioned);
+        assert_eq!(
+            task("!golem  check   the disk", false).as_deref(),
+            Some("check the disk")
+        );
+        assert_eq!(
+            task("Golem Bot: uptime?", false).as_deref(),
+            Some("uptime?")
+        );
+        assert_eq!(
+            task("@golem:example.org, uptime?", false).as_deref(),
+            Some("uptime?")
+        );
+        assert_eq!(task("golem", false).as_deref(), Some(""));
+        // Not the bot's name, just starting like it
+        assert_eq!(task("golems are clay", false), None);
+        assert_eq!(
+            task("ask Golem Bot about it", true).as_deref(),
+            Some("ask Golem Bot about it")
+        );
+        assert_eq!(
+            task(
+                "> <@ann:example.org> old question\n\ngolem: follow up",
+                false
+            )
+            .as_deref(),
+            Some("follow up")
+        );
+    }
+
+    #[test]
+    fn reactions_answer_approvals() {
+        let reaction = |key: &str| Reaction {
+            room: "!r".to_string(),
+            event_id: "$q".to_string(),
+            sender: "@ann:example.org".to_string(),
+            key: key.to_string(),
+        };
+        assert_eq!(reaction("👍").approves(), Some(true));
+        assert_eq!(reaction("❌").approves(), Some(false));
+        assert_eq!(reaction("🎉").approves(), None);
+    }
+}
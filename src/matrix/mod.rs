@@ -0,0 +1,302 @@
+//! `golem matrix`: a Matrix bot, for teams on their own homeserver.
+//!
+//! The bot joins rooms it is invited to. A message that starts with
+//! `!golem` or the bot's name, or mentions it, gives it a task. Each room
+//! gets an engine of its own whose history lives in the named session
+//! `matrix-<room id>`, so later tasks there build on earlier ones; a room
+//! runs its tasks one at a time, rooms run side by side. Tool calls and
+//! the answer are posted in a thread under the message. With a writable
+//! shell, a command that would write asks there; anyone in the room
+//! answers by reacting with ✅ or ❌.
+
+pub mod api;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::banner::format_task_result;
+use crate::chat::{self, Approvals};
+use crate::config::settings::Settings;
+use crate::engine::hooks::Hook;
+use crate::engine::{Engine, TaskResult};
+use crate::thinker::ToolCall;
+use crate::tools::{Approver, ToolResult};
+use api::{Incoming, MatrixApi, Message, Names, Reaction};
+
+/// How long one `/sync` waits for something to happen.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait before syncing again after a sync failed.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Posted answers are cut to this many characters; homeservers refuse
+/// events over 64 KiB.
+const MAX_MESSAGE_CHARS: usize = 30_000;
+
+/// The named session a room's tasks are kept in.
+pub fn session_name(room: &str) -> String {
+    format!("matrix-{room}")
+}
+
+/// Approval questions waiting for a reaction: their event ids, and the
+/// [`Approvals`] request each asks.
+#[derive(Default)]
+struct Questions {
+    approvals: Approvals,
+    asked: Mutex<HashMap<String, String>>,
+}
+
+impl Questions {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.asked.lock().expect("questions lock poisoned")
+    }
+}
+
+/// Run the bot until Ctrl+C; failed syncs are retried.
+pub async fn run(settings: Settings, homeserver: String, token: String) -> Result<()> {
+    let api = Arc::new(MatrixApi::new(homeserver, token));
+    // A bad token or homeserver fails here rather than in the loop
+    let user_id = api.whoami().await?;
+    let display_name = api.display_name(&user_id).await.unwrap_or_default();
+    let names = Names::new(&user_id, display_name.as_deref());
+    let questions = Arc::new(Questions::default());
+    let mut rooms: HashMap<String, mpsc::UnboundedSender<Message>> = HashMap::new();
+    // Messages from before the bot started aren't tasks; invites still count
+    let first = api.sync(None, Duration::ZERO).await?;
+    for incoming in first.incoming(&names) {
+        if let Incoming::Invite(room) = incoming {
+            join(&api, &room).await;
+        }
+    }
+    let mut since = first.next_batch;
+    println!(
+        "signed in to Matrix as {user_id}; invite it to a room and start a message with !golem"
+    );
+    loop {
+        let batch = tokio::select! {
+            batch = api.sync(Some(&since), SYNC_TIMEOUT) => batch,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(e) => {
+                eprintln!("warning: Matrix sync failed: {e:#}");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        for incoming in batch.incoming(&names) {
+            match incoming {
+                Incoming::Invite(room) => join(&api, &room).await,
+                Incoming::Task(message) => {
+                    let worker = rooms.entry(message.room.clone()).or_insert_with(|| {
+                        let (tx, rx) = mpsc::unbounded_channel();
+                        let thread = Thread::new(
+                            Arc::clone(&api),
+                            Arc::clone(&questions),
+                            message.room.clone(),
+                            chat::shell_writes(&settings),
+                        );
+                        tokio::spawn(serve_room(settings.clone(), thread, rx));
+                        tx
+                    });
+                    let _ = worker.send(message);
+                }
+                Incoming::Reaction(reaction) => {
+                    tokio::spawn(settle(Arc::clone(&api), Arc::clone(&questions), reaction));
+                }
+            }
+        }
+        since = batch.next_batch;
+    }
+}
+
+/// Accept an invite to `room`, warning if it can't be.
+async fn join(api: &MatrixApi, room: &str) {
+    match api.join(room).await {
+        Ok(()) => println!("joined {room}"),
+        Err(e) => eprintln!("warning: failed to join {room}: {e:#}"),
+    }
+}
+
+/// Run a room's messages as tasks, one after another.
+async fn serve_room(
+    settings: Settings,
+    thread: Thread,
+    mut messages: mpsc::UnboundedReceiver<Message>,
+) {
+    let mut engine = None;
+    while let Some(message) = messages.recv().await {
+        thread.reply_to(&message);
+        if message.task.is_empty() {
+            thread
+                .say("Give me a task after my name, e.g. `!golem how full is the disk?`")
+                .await;
+            continue;
+        }
+        if engine.is_none() {
+            let session = session_name(&thread.room);
+            let built = chat::channel_engine(
+                &settings,
+                &session,
+                Box::new(thread.clone()),
+                Arc::new(thread.clone()),
+            )
+            .await;
+            match built {
+                Ok(built) => engine = Some(built),
+                Err(e) => {
+                    thread.say(&format!("✗ {e:#}")).await;
+                    continue;
+                }
+            }
+        }
+        let Some(engine) = engine.as_mut() else {
+            continue;
+        };
+        let result = engine.run(&message.task).await;
+        thread.answer(&answer_text(&result)).await;
+    }
+}
+
+/// A task's answer as a reply, with its stats (which say if it is partial).
+fn answer_text(result: &Result<TaskResult>) -> String {
+    match result {
+        Ok(result) => format!(
+            "{}\n\n{}",
+            chat::cut(&result.answer, MAX_MESSAGE_CHARS),
+            format_task_result(result).trim()
+        ),
+        Err(e) => format!("✗ {e:#}"),
+    }
+}
+
+/// Settle an approval question someone reacted to, and say who answered.
+async fn settle(api: Arc<MatrixApi>, questions: Arc<Questions>, reaction: Reaction) {
+    let Some(approved) = reaction.approves() else {
+        return;
+    };
+    let Some(id) = questions.lock().remove(&reaction.event_id) else {
+        return;
+    };
+    let text = if !questions.approvals.settle(&id, approved) {
+        "This request has expired.".to_string()
+    } else if approved {
+        format!("✓ Approved by {}", reaction.sender)
+    } else {
+        format!("✗ Denied by {}", reaction.sender)
+    };
+    let content = serde_json::json!({
+        "msgtype": "m.notice",
+        "body": text,
+        "m.relates_to": { "m.in_reply_to": { "event_id": reaction.event_id } },
+    });
+    if let Err(e) = api.send(&reaction.room, "m.room.message", content).await {
+        eprintln!("warning: failed to post to Matrix: {e:#}");
+    }
+}
+
+/// Where a room's current task replies: the thread of the message it came
+/// from. It reports the task's tool calls and asks for approvals.
+#[derive(Clone)]
+struct Thread {
+    api: Arc<MatrixApi>,
+    questions: Arc<Questions>,
+    room: String,
+    root: Arc<Mutex<String>>,
+    /// Whether the shell may write, so write commands need approval.
+    shell_writes: bool,
+}
+
+impl Thread {
+    fn new(
+        api: Arc<MatrixApi>,
+        questions: Arc<Questions>,
+        room: String,
+        shell_writes: bool,
+    ) -> Self {
+        Self {
+            api,
+            questions,
+            room,
+            root: Arc::default(),
+            shell_writes,
+        }
+    }
+
+    fn reply_to(&self, message: &Message) {
+        *self.root.lock().expect("thread lock poisoned") = message.thread().to_string();
+    }
+
+    async fn post(&self, msgtype: &str, text: &str) -> Result<String> {
+        let root = self.root.lock().expect("thread lock poisoned").clone();
+        let content = api::thread_message(msgtype, text, &root);
+        self.api.send(&self.room, "m.room.message", content).await
+    }
+
+    /// Post `text` as a notice, warning on stderr if Matrix won't take it.
+    async fn say(&self, text: &str) {
+        if let Err(e) = self.post("m.notice", text).await {
+            eprintln!("warning: failed to post to Matrix: {e:#}");
+        }
+    }
+
+    /// Post `text` as a message, which notifies like one from a person.
+    async fn answer(&self, text: &str) {
+        if let Err(e) = self.post("m.text", text).await {
+            eprintln!("warning: failed to post to Matrix: {e:#}");
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for Thread {
+    async fn on_tool_result(&self, call: &ToolCall, result: &ToolResult) {
+        self.say(&chat::progress_line(call, result)).await;
+    }
+}
+
+#[async_trait]
+impl Approver for Thread {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        if !chat::needs_approval(tool, args, self.shell_writes) {
+            return Ok(true);
+        }
+        let approvals = &self.questions.approvals;
+        let (id, answer) = approvals.open();
+        let question = format!(
+            "{}\nReact with ✅ to approve or ❌ to deny.",
+            chat::approval_question(tool, args)
+        );
+        let event_id = match self.post("m.text", &question).await {
+            Ok(event_id) => event_id,
+            Err(e) => {
+                approvals.settle(&id, false);
+                bail!("couldn't ask for approval in Matrix: {e:#}");
+            }
+        };
+        self.questions.lock().insert(event_id.clone(), id.clone());
+        // Offered as reactions, so answering is one click
+        for key in ["✅", "❌"] {
+            let content = api::reaction_content(&event_id, key);
+            let _ = self.api.send(&self.room, "m.reaction", content).await;
+        }
+        let approved = approvals.wait(&id, answer, tool).await;
+        self.questions.lock().remove(&event_id);
+        approved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rooms_get_their_own_session() {
+        assert_eq!(session_name("!abc:example.org"), "matrix-!abc:example.org");
+    }
+}
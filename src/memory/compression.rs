@@ -0,0 +1,118 @@
+//! Transparent zstd compression for large stored entries.
+//!
+//! Build logs and other long tool output make entries big. Anything over
+//! [`COMPRESSION_THRESHOLD`] is stored as a zstd BLOB; smaller entries stay
+//! TEXT. Readers tell them apart by SQLite type, so rows written before
+//! compression existed still read back unchanged.
+
+use anyhow::{Context, Result, bail};
+use rusqlite::types::Value;
+use std::io::Write;
+
+use crate::consts::COMPRESSION_THRESHOLD;
+
+/// zstd level: fast enough to run on every store, still shrinks logs well.
+const LEVEL: i32 = 3;
+
+/// Turn serialized `text` into the value to store.
+pub fn encode(text: String) -> Result<Value> {
+    if text.len() <= COMPRESSION_THRESHOLD {
+        return Ok(Value::Text(text));
+    }
+    let compressed = zstd::encode_all(text.as_bytes(), LEVEL).context("compression failed")?;
+    Ok(Value::Blob(compressed))
+}
+
+/// Like [`encode`], for text that arrives in pieces: once it passes the
+/// threshold, later pieces are compressed as they come instead of being
+/// held in full.
+#[derive(Default)]
+pub struct Encoder {
+    text: String,
+    zstd: Option<zstd::Encoder<'static, Vec<u8>>>,
+}
+
+impl Encoder {
+    pub fn push(&mut self, piece: &str) -> Result<()> {
+        if let Some(zstd) = &mut self.zstd {
+            return zstd
+                .write_all(piece.as_bytes())
+                .context("compression failed");
+        }
+        self.text.push_str(piece);
+        if self.text.len() > COMPRESSION_THRESHOLD {
+            let mut zstd = zstd::Encoder::new(Vec::new(), LEVEL).context("compression failed")?;
+            zstd.write_all(self.text.as_bytes())
+                .context("compression failed")?;
+            self.text = String::new();
+            self.zstd = Some(zstd);
+        }
+        Ok(())
+    }
+
+    /// The value to store for everything pushed.
+    pub fn finish(self) -> Result<Value> {
+        match self.zstd {
+            Some(zstd) => Ok(Value::Blob(zstd.finish().context("compression failed")?)),
+            None => Ok(Value::Text(self.text)),
+        }
+    }
+}
+
+/// Turn a stored value back into the text [`encode`] was given.
+pub fn decode(value: Value) -> Result<String> {
+    match value {
+        Value::Text(text) => Ok(text),
+        Value::Blob(bytes) => {
+            let raw = zstd::decode_all(bytes.as_slice()).context("decompression failed")?;
+            String::from_utf8(raw).context("decompressed entry is not UTF-8")
+        }
+        other => bail!("unexpected stored entry type: {:?}", other.data_type()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_text_is_stored_as_is() {
+        let value = encode("short".to_string()).unwrap();
+        assert_eq!(value, Value::Text("short".to_string()));
+        assert_eq!(decode(value).unwrap(), "short");
+    }
+
+    #[test]
+    fn large_text_round_trips_compressed() {
+        let log = "Compiling crate v0.1.0\n".repeat(1000);
+        let value = encode(log.clone()).unwrap();
+        let Value::Blob(bytes) = &value else {
+            panic!("expected a blob, got {value:?}");
+        };
+        assert!(bytes.len() < log.len() / 10);
+        assert_eq!(decode(value).unwrap(), log);
+    }
+
+    #[test]
+    fn encoder_matches_encode() {
+        let mut small = Encoder::default();
+        small.push("sh").unwrap();
+        small.push("ort").unwrap();
+        assert_eq!(small.finish().unwrap(), Value::Text("short".to_string()));
+
+        let line = "Compiling crate v0.1.0\n";
+        let mut large = Encoder::default();
+        for _ in 0..1000 {
+            large.push(line).unwrap();
+        }
+        let value = large.finish().unwrap();
+        assert!(matches!(value, Value::Blob(_)));
+        assert_eq!(decode(value).unwrap(), line.repeat(1000));
+    }
+
+    #[test]
+    fn rejects_other_types() {
+        assert!(decode(Value::Integer(1)).is_err());
+        assert!(decode(Value::Blob(b"not zstd".to_vec())).is_err());
+    }
+}
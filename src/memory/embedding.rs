@@ -0,0 +1,331 @@
+//! Text embeddings for semantic recall.
+//!
+//! With `[embeddings]` configured, [`HttpEmbedder`] asks an embedding
+//! model, so recall finds memories that mean what the task means. Without
+//! it, [`HashEmbedder`] hashes words and trigrams locally, which only
+//! finds memories worded like the task. [`from_settings`] picks one.
+//!
+//! Vectors are stored as little-endian `f32` blobs next to the text they
+//! embed, and indexed for nearest-neighbour search by
+//! [`vector_index`](super::vector_index).
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::config::settings::EmbeddingSettings;
+use crate::thinker::http::{HttpClient, HttpRequest, ReqwestClient};
+
+/// Turns text into fixed-size vectors.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Stable identifier stored with each vector. Vectors from a different
+    /// embedder are ignored rather than compared.
+    fn id(&self) -> &str;
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Local embedder using feature hashing over words and character trigrams.
+///
+/// No model download or network access. It captures lexical overlap
+/// (including partial words and typos) and nothing else: "disk full" and
+/// "out of space" don't match. That's enough to surface earlier tasks
+/// about the same files, commands and errors.
+pub struct HashEmbedder {
+    dims: usize,
+}
+
+impl HashEmbedder {
+    pub const DEFAULT_DIMS: usize = 384;
+
+    pub fn new(dims: usize) -> Self {
+        Self { dims }
+    }
+
+    fn embed_sync(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dims];
+        let lower = text.to_lowercase();
+        for word in lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.len() > 1)
+        {
+            self.add(&mut vector, word.as_bytes(), 1.0);
+            let padded: Vec<char> = format!(" {word} ").chars().collect();
+            for tri in padded.windows(3) {
+                let tri: String = tri.iter().collect();
+                self.add(&mut vector, tri.as_bytes(), 0.5);
+            }
+        }
+        normalize(&mut vector);
+        vector
+    }
+
+    fn add(&self, vector: &mut [f32], feature: &[u8], weight: f32) {
+        let hash = fnv1a(feature);
+        let index = (hash % self.dims as u64) as usize;
+        // Sign bit from a different part of the hash reduces collision bias
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[index] += sign * weight;
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DIMS)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    fn id(&self) -> &str {
+        "hash-v1"
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_sync(text))
+    }
+}
+
+/// Embeds text with a model behind an OpenAI-compatible embeddings
+/// endpoint: OpenAI's own, or a local server such as Ollama or llama.cpp.
+pub struct HttpEmbedder {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    client: Arc<dyn HttpClient>,
+}
+
+impl HttpEmbedder {
+    /// An embedder for the endpoint and model `settings` name. The API
+    /// key, if any, is read from its variable now.
+    pub fn new(settings: &EmbeddingSettings) -> Result<Self> {
+        let (Some(url), Some(model)) = (&settings.url, &settings.model) else {
+            bail!("[embeddings] needs both url and model");
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!("embeddings url {url:?} must be an http:// or https:// URL");
+        }
+        let api_key = match &settings.api_key_env {
+            Some(var) => Some(
+                std::env::var(var)
+                    .with_context(|| format!("{var} (the embeddings API key) is not set"))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            url: url.clone(),
+            model: model.clone(),
+            api_key,
+            client: Arc::new(ReqwestClient),
+        })
+    }
+
+    /// Send requests through `client` instead of the network.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    fn id(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut request =
+            HttpRequest::post(&self.url, json!({ "model": self.model, "input": [text] }));
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let response = self.client.send(request).await?;
+        if !response.is_success() {
+            bail!(
+                "embeddings endpoint returned {}: {}",
+                response.status,
+                response.body
+            );
+        }
+        let body: serde_json::Value =
+            serde_json::from_str(&response.body).context("embeddings response is not JSON")?;
+        let vector = body["data"][0]["embedding"]
+            .as_array()
+            .context("embeddings response has no data[0].embedding")?
+            .iter()
+            .map(|x| x.as_f64().map(|x| x as f32))
+            .collect::<Option<Vec<f32>>>()
+            .context("embedding holds something other than numbers")?;
+        if vector.is_empty() {
+            bail!("embeddings endpoint returned an empty vector");
+        }
+        Ok(vector)
+    }
+}
+
+/// The embedder `settings` configure: the model they name, or the local
+/// [`HashEmbedder`] if they name none.
+pub fn from_settings(settings: &EmbeddingSettings) -> Result<Arc<dyn Embedder>> {
+    Ok(match settings.url {
+        Some(_) => Arc::new(HttpEmbedder::new(settings)?),
+        None => Arc::new(HashEmbedder::default()),
+    })
+}
+
+/// FNV-1a — stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+/// Cosine similarity. Returns 0 for mismatched or zero vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thinker::http::HttpResponse;
+    use std::sync::Mutex;
+
+    #[test]
+    fn embeddings_are_normalized() {
+        let v = HashEmbedder::default().embed_sync("list files in /tmp");
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn empty_text_embeds_to_zero() {
+        let v = HashEmbedder::default().embed_sync("");
+        assert!(v.iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn related_text_scores_higher() {
+        let e = HashEmbedder::default();
+        let query = e.embed_sync("restart nginx");
+        let related = e.embed_sync("nginx restarted and config reloaded");
+        let unrelated = e.embed_sync("count lines in main.rs");
+        assert!(cosine(&query, &related) > cosine(&query, &unrelated));
+    }
+
+    #[test]
+    fn embedding_is_deterministic() {
+        let e = HashEmbedder::default();
+        assert_eq!(e.embed_sync("same text"), e.embed_sync("same text"));
+    }
+
+    struct Endpoint {
+        body: String,
+        sent: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for Endpoint {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            self.sent.lock().unwrap().push(request);
+            Ok(HttpResponse {
+                status: 200,
+                body: self.body.clone(),
+            })
+        }
+    }
+
+    fn settings() -> EmbeddingSettings {
+        EmbeddingSettings {
+            url: Some("http://localhost:11434/v1/embeddings".to_string()),
+            model: Some("nomic-embed-text".to_string()),
+            api_key_env: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn http_embedder_asks_the_configured_model() {
+        let endpoint = Arc::new(Endpoint {
+            body: r#"{"data":[{"embedding":[0.5,-0.25]}]}"#.to_string(),
+            sent: Mutex::new(Vec::new()),
+        });
+        let embedder = HttpEmbedder::new(&settings())
+            .unwrap()
+            .with_client(Arc::clone(&endpoint) as Arc<dyn HttpClient>);
+
+        assert_eq!(embedder.id(), "nomic-embed-text");
+        assert_eq!(embedder.embed("disk full").await.unwrap(), vec![0.5, -0.25]);
+        let sent = endpoint.sent.lock().unwrap();
+        assert_eq!(sent[0].url, "http://localhost:11434/v1/embeddings");
+        let body = sent[0].body.as_ref().unwrap();
+        assert_eq!(body["model"], "nomic-embed-text");
+        assert_eq!(body["input"][0], "disk full");
+    }
+
+    #[tokio::test]
+    async fn http_embedder_rejects_malformed_responses() {
+        let embedder = HttpEmbedder::new(&settings())
+            .unwrap()
+            .with_client(Arc::new(Endpoint {
+                body: r#"{"error":"no such model"}"#.to_string(),
+                sent: Mutex::new(Vec::new()),
+            }));
+        let err = embedder.embed("x").await.unwrap_err();
+        assert!(err.to_string().contains("data[0].embedding"), "{err:#}");
+    }
+
+    #[test]
+    fn settings_pick_the_embedder() {
+        let local = from_settings(&EmbeddingSettings::default()).unwrap();
+        assert_eq!(local.id(), "hash-v1");
+        assert_eq!(from_settings(&settings()).unwrap().id(), "nomic-embed-text");
+        let ftp = EmbeddingSettings {
+            url: Some("ftp://example.com".to_string()),
+            ..settings()
+        };
+        assert!(from_settings(&ftp).is_err());
+    }
+
+    #[test]
+    fn blob_roundtrip() {
+        let v = vec![0.25, -1.5, 3.0];
+        assert_eq!(from_blob(&to_blob(&v)), v);
+    }
+
+    #[test]
+    fn cosine_handles_mismatched_lengths() {
+        assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+}
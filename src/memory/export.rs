@@ -0,0 +1,186 @@
+//! Render a session's task records for sharing or archiving.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{MemoryEntry, SessionRecord};
+use crate::thinker::TokenUsage;
+use crate::tools::{Outcome, format_args};
+
+/// File format for `/export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    /// Parse `md`/`markdown`/`json`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess from a file extension, defaulting to Markdown.
+    pub fn from_path(path: &str) -> Self {
+        match std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+        {
+            Some(ext) => Self::parse(ext).unwrap_or(Self::Markdown),
+            None => Self::Markdown,
+        }
+    }
+}
+
+/// A whole session as written by the JSON export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub session: String,
+    pub tasks: Vec<SessionRecord>,
+    pub usage: TokenUsage,
+}
+
+impl SessionExport {
+    pub fn new(session: &str, tasks: Vec<SessionRecord>) -> Self {
+        let mut usage = TokenUsage::default();
+        for task in &tasks {
+            usage.add(task.usage);
+        }
+        Self {
+            session: session.to_string(),
+            tasks,
+            usage,
+        }
+    }
+
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ExportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# Session: {}\n", self.session);
+        out.push_str(&format!(
+            "\n{} task(s), {} input + {} output tokens\n",
+            self.tasks.len(),
+            self.usage.input_tokens,
+            self.usage.output_tokens
+        ));
+
+        for (i, record) in self.tasks.iter().enumerate() {
+            out.push_str(&format!("\n## {}. {}\n\n", i + 1, record.task));
+            out.push_str(&format!("_{} UTC", record.timestamp));
+            if record.usage.total() > 0 {
+                out.push_str(&format!(
+                    " · {} input + {} output tokens",
+                    record.usage.input_tokens, record.usage.output_tokens
+                ));
+            }
+            out.push_str("_\n");
+
+            for entry in &record.entries {
+                if let MemoryEntry::Iteration {
+                    thought,
+                    calls,
+                    results,
+                } = entry
+                {
+                    out.push_str(&format!("\n**Thought:** {thought}\n"));
+                    for (i, result) in results.iter().enumerate() {
+                        let (mark, text) = match &result.outcome {
+                            Outcome::Success(out) => ("✓", out),
+                            Outcome::Error(err) => ("✗", err),
+                        };
+                        let call = match calls.get(i) {
+                            Some(c) => format!(" `{}`", format_args(&c.args)),
+                            None => String::new(),
+                        };
+                        out.push_str(&format!(
+                            "\n`{}`{call} {mark}\n\n```\n{}\n```\n",
+                            result.tool,
+                            text.trim_end()
+                        ));
+                    }
+                }
+            }
+
+            out.push_str(&format!("\n**Answer:** {}\n", record.answer));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thinker::ToolCall;
+    use crate::tools::ToolResult;
+    use std::collections::HashMap;
+
+    fn sample() -> SessionExport {
+        SessionExport::new(
+            "work",
+            vec![SessionRecord {
+                timestamp: "2026-01-02 03:04:05".to_string(),
+                task: "list files".to_string(),
+                answer: "a.txt".to_string(),
+                entries: vec![
+                    MemoryEntry::Task {
+                        content: "list files".to_string(),
+                    },
+                    MemoryEntry::Iteration {
+                        thought: "use ls".to_string(),
+                        calls: vec![ToolCall {
+                            tool: "shell".to_string(),
+                            args: HashMap::from([("command".to_string(), "ls".to_string())]),
+                        }],
+                        results: vec![ToolResult {
+                            tool: "shell".to_string(),
+                            outcome: Outcome::Success("a.txt\n".to_string()),
+                        }],
+                    },
+                ],
+                usage: TokenUsage {
+                    input_tokens: 100,
+                    output_tokens: 20,
+                    cache_read_tokens: 0,
+                },
+            }],
+        )
+    }
+
+    #[test]
+    fn format_parsing() {
+        assert_eq!(ExportFormat::parse("MD"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse("json"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse("txt"), None);
+        assert_eq!(ExportFormat::from_path("out.json"), ExportFormat::Json);
+        assert_eq!(ExportFormat::from_path("out"), ExportFormat::Markdown);
+    }
+
+    #[test]
+    fn markdown_includes_steps_and_answer() {
+        let md = sample().render(ExportFormat::Markdown).unwrap();
+        assert!(md.starts_with("# Session: work"));
+        assert!(md.contains("## 1. list files"));
+        assert!(md.contains("100 input + 20 output tokens"));
+        assert!(md.contains("**Thought:** use ls"));
+        assert!(md.contains("`shell` `ls` ✓\n\n```\na.txt\n```"));
+        assert!(md.contains("**Answer:** a.txt"));
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let json = sample().render(ExportFormat::Json).unwrap();
+        let back: SessionExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.session, "work");
+        assert_eq!(back.tasks[0].entries.len(), 2);
+        assert_eq!(back.usage.total(), 120);
+    }
+}
@@ -0,0 +1,117 @@
+//! Long-term facts the agent keeps across sessions.
+//!
+//! Facts are short statements ("prod server is web-01") written by the
+//! `remember` tool and removed by `forget`. Every one of them is included
+//! in the system prompt, so the store is meant for a handful of durable
+//! preferences, not for task output.
+
+use anyhow::{Context, Result, bail};
+use rusqlite::Connection;
+use std::sync::Mutex;
+
+/// A stored fact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fact {
+    pub id: i64,
+    pub text: String,
+    /// UTC time the fact was stored, as `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+}
+
+/// Persistent fact store backed by SQLite.
+pub struct FactStore {
+    conn: Mutex<Connection>,
+}
+
+impl FactStore {
+    /// Open or create the facts table in the given database.
+    /// Use `":memory:"` for tests.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = crate::db::open(path).context("failed to open facts database")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Store a fact and return its id. Remembering the same text twice
+    /// returns the existing id.
+    pub fn remember(&self, text: &str) -> Result<i64> {
+        let text = text.trim();
+        if text.is_empty() {
+            bail!("fact is empty");
+        }
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT OR IGNORE INTO facts (text) VALUES (?1)", [text])?;
+        let id = conn.query_row("SELECT id FROM facts WHERE text = ?1", [text], |row| {
+            row.get(0)
+        })?;
+        Ok(id)
+    }
+
+    /// Remove a fact by id. Returns `false` if there was no such fact.
+    pub fn forget(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute("DELETE FROM facts WHERE id = ?1", [id])?;
+        Ok(removed > 0)
+    }
+
+    /// All facts, oldest first.
+    pub fn list(&self) -> Result<Vec<Fact>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, text, created_at FROM facts ORDER BY id")?;
+        let facts = stmt
+            .query_map([], |row| {
+                Ok(Fact {
+                    id: row.get(0)?,
+                    text: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(facts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> FactStore {
+        FactStore::open(":memory:").unwrap()
+    }
+
+    #[test]
+    fn remember_and_list() {
+        let facts = store();
+        let a = facts.remember("I use fish shell").unwrap();
+        let b = facts.remember("prod server is web-01").unwrap();
+        let list = facts.list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].id, a);
+        assert_eq!(list[0].text, "I use fish shell");
+        assert_eq!(list[1].id, b);
+    }
+
+    #[test]
+    fn remember_is_idempotent() {
+        let facts = store();
+        let a = facts.remember("I use fish shell").unwrap();
+        let b = facts.remember("  I use fish shell ").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(facts.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn remember_rejects_empty() {
+        assert!(store().remember("   ").is_err());
+    }
+
+    #[test]
+    fn forget_removes_by_id() {
+        let facts = store();
+        let id = facts.remember("temporary").unwrap();
+        assert!(facts.forget(id).unwrap());
+        assert!(!facts.forget(id).unwrap());
+        assert!(facts.list().unwrap().is_empty());
+    }
+}
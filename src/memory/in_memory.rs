@@ -0,0 +1,570 @@
+//! Memory kept entirely in process, without SQLite.
+//!
+//! Behaves like [`SqliteMemory`](super::sqlite::SqliteMemory) for a single
+//! process — named sessions, session history, recall — but nothing
+//! survives a restart. Useful when embedding golem as a library and for
+//! tests that don't need persistence. Full-text recall is a plain
+//! case-insensitive word match rather than FTS5 ranking.
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::embedding::{self, Embedder, HashEmbedder};
+use super::{
+    HitSource, MATCH_END, MATCH_START, Memory, MemoryEntry, MemoryStats, NamedSession, RecallHit,
+    SemanticHit, SessionEntry, SessionMatch, SessionRecord, TableStats,
+};
+use crate::consts::DEFAULT_SESSION_NAME;
+use crate::consts::format_utc;
+use crate::thinker::TokenUsage;
+use crate::tools::Outcome;
+
+/// Words of context kept on each side of the first match in a snippet.
+const SNIPPET_WORDS: usize = 6;
+
+struct Session {
+    name: String,
+    created_at: String,
+}
+
+struct StoredRecord {
+    id: i64,
+    session: usize,
+    record: SessionRecord,
+}
+
+struct StoredEmbedding {
+    session: usize,
+    source: HitSource,
+    task: String,
+    text: String,
+    vector: Vec<f32>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Per-task history, cleared every task.
+    entries: Vec<MemoryEntry>,
+    /// Every entry ever stored, for recall across tasks.
+    recall_index: Vec<MemoryEntry>,
+    sessions: Vec<Session>,
+    records: Vec<StoredRecord>,
+    embeddings: Vec<StoredEmbedding>,
+    next_record_id: i64,
+    current_task: String,
+    /// Index into `sessions`.
+    active: usize,
+}
+
+/// In-process memory. See the module docs.
+pub struct InMemoryMemory {
+    state: Mutex<State>,
+    embedder: Arc<dyn Embedder>,
+}
+
+impl InMemoryMemory {
+    pub fn new() -> Self {
+        let state = State {
+            sessions: vec![Session {
+                name: DEFAULT_SESSION_NAME.to_string(),
+                created_at: utc_timestamp(),
+            }],
+            next_record_id: 1,
+            ..State::default()
+        };
+        Self {
+            state: Mutex::new(state),
+            embedder: Arc::new(HashEmbedder::default()),
+        }
+    }
+
+    /// Use a different embedder for semantic recall.
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = embedder;
+        self
+    }
+
+    async fn store_embedding(
+        &self,
+        session: usize,
+        source: HitSource,
+        task: &str,
+        text: &str,
+    ) -> Result<()> {
+        // An embeddings server that's down costs recall, not the entry
+        let vector = match self.embedder.embed(&format!("{task}\n{text}")).await {
+            Ok(vector) => vector,
+            Err(e) => {
+                eprintln!("warning: not indexed for semantic recall: {e:#}");
+                return Ok(());
+            }
+        };
+        self.state.lock().unwrap().embeddings.push(StoredEmbedding {
+            session,
+            source,
+            task: task.to_string(),
+            text: text.to_string(),
+            vector,
+        });
+        Ok(())
+    }
+
+    fn search(&self, query: &str, limit: Option<usize>) -> Vec<RecallHit> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return vec![];
+        }
+        let state = self.state.lock().unwrap();
+        let mut hits: Vec<(usize, RecallHit)> = state
+            .recall_index
+            .iter()
+            .filter_map(|entry| {
+                let text = entry.text();
+                let lower = text.to_lowercase();
+                if !terms.iter().all(|t| lower.contains(t.as_str())) {
+                    return None;
+                }
+                let count = terms
+                    .iter()
+                    .map(|t| lower.matches(t.as_str()).count())
+                    .sum();
+                Some((
+                    count,
+                    RecallHit {
+                        entry: entry.clone(),
+                        snippet: snippet(&text, &terms),
+                    },
+                ))
+            })
+            .collect();
+        // Most matches first; newer entries win ties
+        hits.reverse();
+        hits.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+        hits.truncate(limit.unwrap_or(usize::MAX));
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+}
+
+impl Default for InMemoryMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Memory for InMemoryMemory {
+    async fn store(&self, entry: MemoryEntry) -> Result<()> {
+        let (session, task) = {
+            let mut state = self.state.lock().unwrap();
+            if let MemoryEntry::Task { content } = &entry {
+                state.current_task = content.clone();
+            }
+            state.entries.push(entry.clone());
+            state.recall_index.push(entry.clone());
+            (state.active, state.current_task.clone())
+        };
+
+        if let MemoryEntry::Iteration { results, .. } = &entry {
+            for r in results {
+                if let Outcome::Success(out) = &r.outcome {
+                    let text = format!("[{}] {}", r.tool, out);
+                    self.store_embedding(session, HitSource::Observation, &task, &text)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn history(&self) -> Result<Vec<MemoryEntry>> {
+        Ok(self.state.lock().unwrap().entries.clone())
+    }
+
+    async fn history_len(&self) -> Result<usize> {
+        Ok(self.state.lock().unwrap().entries.len())
+    }
+
+    async fn history_page(&self, offset: usize, limit: usize) -> Result<Vec<MemoryEntry>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .entries
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn recall(&self, query: &str) -> Result<Vec<MemoryEntry>> {
+        Ok(self
+            .search(query, None)
+            .into_iter()
+            .map(|hit| hit.entry)
+            .collect())
+    }
+
+    async fn recall_snippets(&self, query: &str, limit: usize) -> Result<Vec<RecallHit>> {
+        Ok(self.search(query, Some(limit)))
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.state.lock().unwrap().entries.clear();
+        Ok(())
+    }
+
+    async fn unfinished_task(&self) -> Result<Option<String>> {
+        let mut state = self.state.lock().unwrap();
+        let task = super::unfinished_task(&state.entries).map(str::to_string);
+        if let Some(task) = &task {
+            state.current_task = task.clone();
+        }
+        Ok(task)
+    }
+
+    // --- Session memory ---
+
+    async fn store_session(&self, entry: SessionEntry) -> Result<()> {
+        let session = {
+            let mut state = self.state.lock().unwrap();
+            let id = state.next_record_id;
+            state.next_record_id += 1;
+            let record = SessionRecord {
+                timestamp: utc_timestamp(),
+                task: entry.task.clone(),
+                answer: entry.answer.clone(),
+                entries: state.entries.clone(),
+                usage: TokenUsage::default(),
+            };
+            let session = state.active;
+            state.records.push(StoredRecord {
+                id,
+                session,
+                record,
+            });
+            session
+        };
+        self.store_embedding(session, HitSource::Session, &entry.task, &entry.answer)
+            .await
+    }
+
+    async fn record_task_usage(&self, usage: TokenUsage) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let active = state.active;
+        if let Some(stored) = state.records.iter_mut().rfind(|r| r.session == active) {
+            stored.record.usage = usage;
+        }
+        Ok(())
+    }
+
+    async fn session_records(&self) -> Result<Vec<SessionRecord>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .records
+            .iter()
+            .filter(|r| r.session == state.active)
+            .map(|r| r.record.clone())
+            .collect())
+    }
+
+    async fn session_history(&self, limit: usize) -> Result<Vec<SessionEntry>> {
+        let state = self.state.lock().unwrap();
+        let visible: Vec<_> = state
+            .records
+            .iter()
+            .filter(|r| r.session == state.active)
+            .collect();
+        let start = visible.len().saturating_sub(limit);
+        Ok(visible[start..]
+            .iter()
+            .map(|r| SessionEntry {
+                task: r.record.task.clone(),
+                answer: r.record.answer.clone(),
+            })
+            .collect())
+    }
+
+    async fn session_len(&self) -> Result<usize> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .records
+            .iter()
+            .filter(|r| r.session == state.active)
+            .count())
+    }
+
+    async fn compact_session(&self, count: usize, summary: SessionEntry) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let active = state.active;
+        let ids: Vec<i64> = state
+            .records
+            .iter()
+            .filter(|r| r.session == active)
+            .take(count)
+            .map(|r| r.id)
+            .collect();
+        let Some(&first) = ids.first() else {
+            return Ok(());
+        };
+        let position = state
+            .records
+            .iter()
+            .position(|r| r.id == first)
+            .expect("compacted record exists");
+        state.records.retain(|r| !ids.contains(&r.id));
+        // Reuse the oldest id so the summary sorts before everything kept
+        state.records.insert(
+            position,
+            StoredRecord {
+                id: first,
+                session: active,
+                record: SessionRecord {
+                    timestamp: utc_timestamp(),
+                    task: summary.task,
+                    answer: summary.answer,
+                    entries: vec![],
+                    usage: TokenUsage::default(),
+                },
+            },
+        );
+        Ok(())
+    }
+
+    async fn clear_session(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let active = state.active;
+        state.records.retain(|r| r.session != active);
+        state.embeddings.retain(|e| e.session != active);
+        Ok(())
+    }
+
+    async fn search_session(
+        &self,
+        query: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<SessionMatch>> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let state = self.state.lock().unwrap();
+        let mut matches: Vec<_> = state
+            .records
+            .iter()
+            .filter(|r| since.is_none_or(|since| r.record.timestamp.as_str() >= since))
+            .filter(|r| until.is_none_or(|until| r.record.timestamp.as_str() < until))
+            .filter(|r| {
+                let task = r.record.task.to_lowercase();
+                let answer = r.record.answer.to_lowercase();
+                words
+                    .iter()
+                    .all(|w| task.contains(w.as_str()) || answer.contains(w.as_str()))
+            })
+            .map(|r| SessionMatch {
+                session: state.sessions[r.session].name.clone(),
+                timestamp: r.record.timestamp.clone(),
+                task: r.record.task.clone(),
+                answer: r.record.answer.clone(),
+            })
+            .collect();
+        // Records are stored oldest first
+        matches.reverse();
+        Ok(matches)
+    }
+
+    async fn recall_semantic(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>> {
+        let query = self.embedder.embed(query).await?;
+        let state = self.state.lock().unwrap();
+        let mut hits: Vec<SemanticHit> = state
+            .embeddings
+            .iter()
+            .filter(|e| e.session == state.active)
+            .map(|e| SemanticHit {
+                source: e.source,
+                task: e.task.clone(),
+                text: e.text.clone(),
+                score: embedding::cosine(&query, &e.vector),
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    // --- Named sessions ---
+
+    async fn active_session(&self) -> Result<String> {
+        let state = self.state.lock().unwrap();
+        Ok(state.sessions[state.active].name.clone())
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<NamedSession>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(i, s)| NamedSession {
+                name: s.name.clone(),
+                created_at: s.created_at.clone(),
+                entries: state.records.iter().filter(|r| r.session == i).count(),
+            })
+            .collect())
+    }
+
+    async fn create_session(&self, name: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        create(&mut state, name)?;
+        Ok(())
+    }
+
+    async fn switch_session(&self, name: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match state.sessions.iter().position(|s| s.name == name) {
+            Some(index) => {
+                state.active = index;
+                Ok(())
+            }
+            None => bail!("no session named '{name}'"),
+        }
+    }
+
+    async fn import_session(&self, name: &str, records: &[SessionRecord]) -> Result<()> {
+        let session = {
+            let mut state = self.state.lock().unwrap();
+            let session = create(&mut state, name)?;
+            for record in records {
+                let id = state.next_record_id;
+                state.next_record_id += 1;
+                state.records.push(StoredRecord {
+                    id,
+                    session,
+                    record: record.clone(),
+                });
+            }
+            session
+        };
+        for record in records {
+            self.store_embedding(session, HitSource::Session, &record.task, &record.answer)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn stats(&self) -> Result<MemoryStats> {
+        let state = self.state.lock().unwrap();
+        let table = |name: &str, rows: usize, times: Vec<&String>| TableStats {
+            name: name.to_string(),
+            rows: rows as u64,
+            oldest: times.iter().min().map(|t| t.to_string()),
+            newest: times.iter().max().map(|t| t.to_string()),
+        };
+        let tables = vec![
+            table("embeddings", state.embeddings.len(), vec![]),
+            table("memory", state.entries.len(), vec![]),
+            table(
+                "session_history",
+                state.records.len(),
+                state.records.iter().map(|r| &r.record.timestamp).collect(),
+            ),
+            table(
+                "sessions",
+                state.sessions.len(),
+                state.sessions.iter().map(|s| &s.created_at).collect(),
+            ),
+        ];
+        Ok(MemoryStats {
+            tables,
+            database_bytes: None,
+            fts_bytes: None,
+            vectors: state.embeddings.len() as u64,
+            vector_bytes: state
+                .embeddings
+                .iter()
+                .map(|e| (e.vector.len() * std::mem::size_of::<f32>()) as u64)
+                .sum(),
+        })
+    }
+}
+
+/// Add a session and return its index. Fails if the name is taken.
+fn create(state: &mut State, name: &str) -> Result<usize> {
+    if state.sessions.iter().any(|s| s.name == name) {
+        bail!("session '{name}' already exists");
+    }
+    state.sessions.push(Session {
+        name: name.to_string(),
+        created_at: utc_timestamp(),
+    });
+    Ok(state.sessions.len() - 1)
+}
+
+/// A few words around the first matching term, with every matching word
+/// marked like the FTS snippets.
+fn snippet(text: &str, terms: &[String]) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let is_match = |w: &str| {
+        let lower = w.to_lowercase();
+        terms.iter().any(|t| lower.contains(t.as_str()))
+    };
+    let first = words.iter().position(|w| is_match(w)).unwrap_or(0);
+    let start = first.saturating_sub(SNIPPET_WORDS);
+    let end = (first + SNIPPET_WORDS + 1).min(words.len());
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let shown: Vec<String> = words[start..end]
+        .iter()
+        .map(|w| {
+            if is_match(w) {
+                format!("{MATCH_START}{w}{MATCH_END}")
+            } else {
+                w.to_string()
+            }
+        })
+        .collect();
+    out.push_str(&shown.join(" "));
+    if end < words.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// Current UTC time as `YYYY-MM-DD HH:MM:SS`, matching SQLite's `datetime('now')`.
+fn utc_timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_utc(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracketed(snippet: String) -> String {
+        RecallHit {
+            entry: MemoryEntry::Task {
+                content: String::new(),
+            },
+            snippet,
+        }
+        .marked("[", "]")
+    }
+
+    #[test]
+    fn snippet_brackets_matches() {
+        let terms = vec!["nginx".to_string()];
+        assert_eq!(
+            bracketed(snippet("restart nginx now", &terms)),
+            "restart [nginx] now"
+        );
+        let long = "a b c d e f g h nginx i j k l m n o p";
+        assert_eq!(
+            bracketed(snippet(long, &terms)),
+            "…c d e f g h [nginx] i j k l m n…"
+        );
+    }
+}
@@ -1,4 +1,10 @@
+pub mod compression;
+pub mod embedding;
+pub mod export;
+pub mod facts;
+pub mod in_memory;
 pub mod sqlite;
+pub mod vector_index;
 
 use std::fmt;
 
@@ -6,7 +12,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::tools::{Outcome, ToolResult};
+use crate::thinker::{TokenUsage, ToolCall};
+use crate::tools::{Outcome, ToolResult, format_args};
 
 /// A single entry in the agent's memory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +23,17 @@ pub enum MemoryEntry {
     /// A thought + action + observations from one ReAct iteration.
     Iteration {
         thought: String,
+        /// The calls as the thinker issued them, in the same order as
+        /// `results`. Empty for entries stored before calls were kept.
+        #[serde(default)]
+        calls: Vec<ToolCall>,
         results: Vec<ToolResult>,
     },
     /// The final answer.
     Answer { thought: String, content: String },
+    /// A thinker call that failed and was retried, so the model knows a
+    /// turn was lost.
+    Retry { error: String },
 }
 
 impl fmt::Display for MemoryEntry {
@@ -28,7 +42,9 @@ impl fmt::Display for MemoryEntry {
             MemoryEntry::Task { content } => {
                 write!(f, "Task: {}", content)
             }
-            MemoryEntry::Iteration { thought, results } => {
+            MemoryEntry::Iteration {
+                thought, results, ..
+            } => {
                 write!(f, "Thought: {}", thought)?;
                 for r in results {
                     match &r.outcome {
@@ -46,7 +62,50 @@ impl fmt::Display for MemoryEntry {
             MemoryEntry::Answer { thought, content } => {
                 write!(f, "Answer ({}): {}", thought, content)
             }
+            MemoryEntry::Retry { error } => {
+                write!(f, "Retried after error: {}", error)
+            }
+        }
+    }
+}
+
+impl MemoryEntry {
+    /// Full untruncated text of the entry, used for search indexing.
+    pub fn text(&self) -> String {
+        match self {
+            MemoryEntry::Task { content } => content.clone(),
+            MemoryEntry::Iteration {
+                thought,
+                calls,
+                results,
+            } => {
+                let mut text = thought.clone();
+                for c in calls {
+                    text.push('\n');
+                    text.push_str(&format_args(&c.args));
+                }
+                for r in results {
+                    let (Outcome::Success(out) | Outcome::Error(out)) = &r.outcome;
+                    text.push('\n');
+                    text.push_str(out);
+                }
+                text
+            }
+            MemoryEntry::Answer { thought, content } => format!("{thought}\n{content}"),
+            MemoryEntry::Retry { error } => error.clone(),
+        }
+    }
+}
+
+/// The task `history` starts with, unless it already ends in an answer.
+fn unfinished_task(history: &[MemoryEntry]) -> Option<&str> {
+    match (history.first(), history.last()) {
+        (Some(MemoryEntry::Task { content }), Some(last))
+            if !matches!(last, MemoryEntry::Answer { .. }) =>
+        {
+            Some(content)
         }
+        _ => None,
     }
 }
 
@@ -66,6 +125,123 @@ pub struct SessionEntry {
     pub answer: String,
 }
 
+/// Opens a matched term in [`RecallHit::snippet`]. A control character, so
+/// it can't be confused with anything in the stored text.
+pub const MATCH_START: char = '\u{2}';
+/// Closes a matched term in [`RecallHit::snippet`].
+pub const MATCH_END: char = '\u{3}';
+
+/// A ranked full-text match from memory.
+#[derive(Debug, Clone)]
+pub struct RecallHit {
+    pub entry: MemoryEntry,
+    /// Short excerpt around the match, with matched terms between
+    /// [`MATCH_START`] and [`MATCH_END`]; see [`RecallHit::marked`].
+    pub snippet: String,
+}
+
+impl RecallHit {
+    /// The snippet with matched terms between `open` and `close`, e.g.
+    /// `[` and `]`, or terminal escapes.
+    pub fn marked(&self, open: &str, close: &str) -> String {
+        self.snippet
+            .replace(MATCH_START, open)
+            .replace(MATCH_END, close)
+    }
+}
+
+/// A completed task with everything that happened while solving it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// UTC time the task finished, as `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+    pub task: String,
+    pub answer: String,
+    /// The task's full per-task history (task, iterations, answer).
+    /// Empty for summary entries and tasks stored before transcripts were kept.
+    pub entries: Vec<MemoryEntry>,
+    pub usage: TokenUsage,
+}
+
+/// A completed task found by [`Memory::search_session`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionMatch {
+    /// Name of the session the task belongs to.
+    pub session: String,
+    /// UTC time the task finished, as `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+    pub task: String,
+    pub answer: String,
+}
+
+/// A named session: a separately scoped session history.
+#[derive(Debug, Clone)]
+pub struct NamedSession {
+    pub name: String,
+    pub created_at: String,
+    /// Number of completed tasks in the session.
+    pub entries: usize,
+}
+
+/// Row count and age range of one stored table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: u64,
+    /// Earliest and latest timestamps, for tables that record one.
+    pub oldest: Option<String>,
+    pub newest: Option<String>,
+}
+
+/// How much the agent has stored, for deciding when to prune.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryStats {
+    pub tables: Vec<TableStats>,
+    /// Size of the whole database, `None` when memory isn't in SQLite.
+    pub database_bytes: Option<u64>,
+    /// Size of the full-text index, `None` when there is no such index.
+    pub fts_bytes: Option<u64>,
+    /// Stored embedding vectors and their total size.
+    pub vectors: u64,
+    pub vector_bytes: u64,
+}
+
+/// Where a semantic recall hit came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitSource {
+    /// A completed task's answer.
+    Session,
+    /// A successful tool observation made while working on a task.
+    Observation,
+}
+
+impl HitSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HitSource::Session => "session",
+            HitSource::Observation => "observation",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "observation" => HitSource::Observation,
+            _ => HitSource::Session,
+        }
+    }
+}
+
+/// A prior answer or observation similar to a query.
+#[derive(Debug, Clone)]
+pub struct SemanticHit {
+    pub source: HitSource,
+    /// The task that produced this text.
+    pub task: String,
+    pub text: String,
+    /// Cosine similarity to the query, in `[-1, 1]`.
+    pub score: f32,
+}
+
 /// What the agent remembers. Could be in-memory, SQLite, etc.
 #[async_trait]
 pub trait Memory: Send + Sync {
@@ -73,15 +249,71 @@ pub trait Memory: Send + Sync {
 
     async fn store(&self, entry: MemoryEntry) -> Result<()>;
     async fn history(&self) -> Result<Vec<MemoryEntry>>;
+    /// Number of entries in per-task memory.
+    async fn history_len(&self) -> Result<usize>;
+    /// Up to `limit` per-task entries from the `offset`th on, oldest
+    /// first, for reading a long task a page at a time.
+    async fn history_page(&self, offset: usize, limit: usize) -> Result<Vec<MemoryEntry>>;
+    /// Entries matching `query`, best match first. Searches every task, not
+    /// just the current one.
     async fn recall(&self, query: &str) -> Result<Vec<MemoryEntry>>;
+    /// Like `recall`, but at most `limit` hits with a snippet for each.
+    async fn recall_snippets(&self, query: &str, limit: usize) -> Result<Vec<RecallHit>>;
     async fn clear(&self) -> Result<()>;
+    /// The task a run started but never answered (interrupted or failed),
+    /// if per-task memory still holds one. Entries stored afterwards
+    /// continue it.
+    async fn unfinished_task(&self) -> Result<Option<String>>;
 
     // --- Session memory (persists across tasks) ---
 
-    /// Store a completed task summary.
+    /// Store a completed task summary, along with a snapshot of the
+    /// current per-task history as its transcript.
     async fn store_session(&self, entry: SessionEntry) -> Result<()>;
+    /// Attach token usage to the most recently stored session entry.
+    async fn record_task_usage(&self, usage: TokenUsage) -> Result<()>;
+    /// Every entry of the active session with transcripts, oldest first.
+    async fn session_records(&self) -> Result<Vec<SessionRecord>>;
     /// Retrieve the last `limit` session entries (oldest first).
     async fn session_history(&self, limit: usize) -> Result<Vec<SessionEntry>>;
+    /// Number of stored session entries.
+    async fn session_len(&self) -> Result<usize>;
+    /// Replace the `count` oldest session entries with `summary`, which
+    /// takes their place at the start of the history.
+    async fn compact_session(&self, count: usize, summary: SessionEntry) -> Result<()>;
     /// Clear all session history (e.g. `/new` command).
     async fn clear_session(&self) -> Result<()>;
+    /// Prior answers and observations most similar to `query`, best first.
+    /// How close "similar" gets to meaning depends on the embedder; the
+    /// default one only compares wording.
+    async fn recall_semantic(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>>;
+    /// Completed tasks from every session whose task or answer contains
+    /// each word of `query` (case-insensitive; empty matches all), finished
+    /// at or after `since` and before `until`. Bounds are UTC timestamps
+    /// or prefixes of one (`2026-10-06`). Newest first.
+    async fn search_session(
+        &self,
+        query: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<SessionMatch>>;
+
+    // --- Named sessions (session memory is scoped to the active one) ---
+
+    /// Name of the active session.
+    async fn active_session(&self) -> Result<String>;
+    /// All sessions, oldest first.
+    async fn list_sessions(&self) -> Result<Vec<NamedSession>>;
+    /// Create a session. Fails if the name is taken.
+    async fn create_session(&self, name: &str) -> Result<()>;
+    /// Make an existing session the active one.
+    async fn switch_session(&self, name: &str) -> Result<()>;
+    /// Create a session holding `records` (e.g. from an export), without
+    /// switching to it. Fails if the name is taken.
+    async fn import_session(&self, name: &str, records: &[SessionRecord]) -> Result<()>;
+
+    // --- Maintenance ---
+
+    /// Entry counts and index sizes across everything stored.
+    async fn stats(&self) -> Result<MemoryStats>;
 }
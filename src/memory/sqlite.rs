@@ -1,118 +1,842 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use async_trait::async_trait;
-use rusqlite::Connection;
-use std::sync::Mutex;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
 
-use super::{Memory, MemoryEntry, SessionEntry};
+use super::compression;
+use super::embedding::{self, Embedder, HashEmbedder};
+use super::vector_index;
+use super::{
+    HitSource, MATCH_END, MATCH_START, Memory, MemoryEntry, MemoryStats, NamedSession, RecallHit,
+    SemanticHit, SessionEntry, SessionMatch, SessionRecord, TableStats,
+};
+use crate::db::ReadPool;
+use crate::thinker::TokenUsage;
+use crate::tools::Outcome;
 
 /// SQLite-backed persistent memory.
 pub struct SqliteMemory {
-    conn: Mutex<Connection>,
+    conn: Arc<Mutex<Connection>>,
+    /// Read-only connections, so reads don't wait on `conn`. `None` for
+    /// an in-memory database; reads then share `conn`.
+    readers: Option<Arc<ReadPool>>,
+    embedder: Arc<dyn Embedder>,
+    /// The task currently being worked on, so observations can be attributed.
+    current_task: Mutex<String>,
+    /// Active named session; session history and embeddings are scoped to it.
+    session_id: Mutex<i64>,
+    /// Project the session history and embeddings are partitioned by.
+    /// `None` reads everything and writes global entries.
+    project: Option<String>,
+    /// Observations still being embedded in the background.
+    pending: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// SQL condition for rows visible from the project bound to parameter
+/// `?n`: that project's own rows plus global (`NULL`) ones. A `NULL`
+/// parameter (global memory) sees everything.
+fn project_filter(n: usize) -> String {
+    format!("(?{n} IS NULL OR project IS NULL OR project = ?{n})")
 }
 
 impl SqliteMemory {
     pub fn new(path: &str) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS memory (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                timestamp TEXT NOT NULL DEFAULT (datetime('now')),
-                entry TEXT NOT NULL
-            );
-            CREATE TABLE IF NOT EXISTS session_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                timestamp TEXT NOT NULL DEFAULT (datetime('now')),
-                task TEXT NOT NULL,
-                answer TEXT NOT NULL
-            );",
-        )?;
+        let conn = crate::db::open(path)?;
         Ok(Self {
-            conn: Mutex::new(conn),
+            conn: Arc::new(Mutex::new(conn)),
+            readers: ReadPool::new(path).map(Arc::new),
+            embedder: Arc::new(HashEmbedder::default()),
+            current_task: Mutex::new(String::new()),
+            session_id: Mutex::new(1),
+            project: None,
+            pending: Mutex::default(),
         })
     }
 
     pub fn in_memory() -> Result<Self> {
         Self::new(":memory:")
     }
+
+    /// Use a different embedder for semantic recall. Vectors written by
+    /// other embedders are ignored.
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = embedder;
+        self
+    }
+
+    /// Partition session history and embeddings by `project` (typically
+    /// a canonical working directory). Without it memory is global.
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    /// Run `f` against the connection on the blocking thread pool, so a
+    /// large history read doesn't stall the async runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap()))
+            .await
+            .context("database task panicked")?
+    }
+
+    /// Like [`with_conn`](Self::with_conn), for `f` that only reads: it
+    /// gets a connection of its own where the database allows one.
+    async fn with_reader<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    {
+        let Some(readers) = &self.readers else {
+            return self.with_conn(|conn| f(conn)).await;
+        };
+        let readers = Arc::clone(readers);
+        tokio::task::spawn_blocking(move || readers.with(f))
+            .await
+            .context("database task panicked")?
+    }
+
+    /// Per-task entries from the `offset`th on, at most `limit` of them.
+    async fn read_history(&self, offset: i64, limit: i64) -> Result<Vec<MemoryEntry>> {
+        self.with_reader(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT entry FROM memory ORDER BY id ASC LIMIT ?1 OFFSET ?2")?;
+            let stored = stmt
+                .query_map([limit, offset], |row| row.get::<_, Value>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            stored
+                .into_iter()
+                .map(|value| Ok(serde_json::from_str(&compression::decode(value)?)?))
+                .collect()
+        })
+        .await
+    }
+
+    /// Embed an observation in the background, so a slow embeddings
+    /// server doesn't hold up the task's next step.
+    fn store_embedding_later(&self, task: String, text: String) {
+        let embedding = store_embedding(
+            Arc::clone(&self.conn),
+            Arc::clone(&self.embedder),
+            self.project.clone(),
+            self.session_id(),
+            HitSource::Observation,
+            task,
+            text,
+        );
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|handle| !handle.is_finished());
+        pending.push(tokio::spawn(embedding));
+    }
+
+    /// Wait for the observations still being embedded.
+    async fn embedded(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for handle in pending {
+            if let Err(e) = handle.await {
+                eprintln!("warning: not indexed for semantic recall: {e}");
+            }
+        }
+    }
+
+    async fn store_embedding_in(&self, session_id: i64, source: HitSource, task: &str, text: &str) {
+        store_embedding(
+            Arc::clone(&self.conn),
+            Arc::clone(&self.embedder),
+            self.project.clone(),
+            session_id,
+            source,
+            task.to_string(),
+            text.to_string(),
+        )
+        .await
+    }
+
+    fn session_id(&self) -> i64 {
+        *self.session_id.lock().unwrap()
+    }
+
+    async fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<RecallHit>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(vec![]);
+        };
+        self.with_reader(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT entry, snippet(memory_fts, 0, ?3, ?4, '…', 12)
+                 FROM memory_fts WHERE memory_fts MATCH ?1
+                 ORDER BY rank LIMIT ?2",
+            )?;
+            let rows = stmt
+                .query_map(
+                    rusqlite::params![
+                        query,
+                        limit.map_or(-1, |l| l as i64),
+                        MATCH_START.to_string(),
+                        MATCH_END.to_string()
+                    ],
+                    |row| Ok((row.get::<_, Value>(0)?, row.get::<_, String>(1)?)),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(|(stored, snippet)| {
+                    Ok(RecallHit {
+                        entry: serde_json::from_str(&compression::decode(stored)?)?,
+                        snippet,
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+}
+
+/// Embed `text` and store the vector, indexed under `session_id`. An
+/// embeddings server that's down, or a vector the index can't take (one
+/// of another width, say), costs semantic recall with a warning, never
+/// the entry.
+async fn store_embedding(
+    conn: Arc<Mutex<Connection>>,
+    embedder: Arc<dyn Embedder>,
+    project: Option<String>,
+    session_id: i64,
+    source: HitSource,
+    task: String,
+    text: String,
+) {
+    let vector = match embedder.embed(&format!("{task}\n{text}")).await {
+        Ok(vector) => vector,
+        Err(e) => {
+            eprintln!("warning: not indexed for semantic recall: {e:#}");
+            return;
+        }
+    };
+    let model = embedder.id().to_string();
+    let stored = tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut conn = conn.lock().unwrap();
+        let mut tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO embeddings (source, task, text, model, vector, session_id, project)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                source.as_str(),
+                task,
+                text,
+                model,
+                embedding::to_blob(&vector),
+                session_id,
+                project
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        {
+            // Dropped uncommitted, the savepoint undoes a half-made index
+            let index = tx.savepoint()?;
+            match vector_index::insert(&index, id, &model, session_id, project.as_deref(), &vector)
+            {
+                Ok(()) => index.commit()?,
+                Err(e) => eprintln!("warning: not indexed for semantic recall: {e:#}"),
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    })
+    .await;
+    match stored {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("warning: not indexed for semantic recall: {e:#}"),
+        Err(e) => eprintln!("warning: not indexed for semantic recall: {e}"),
+    }
 }
 
 #[async_trait]
 impl Memory for SqliteMemory {
     async fn store(&self, entry: MemoryEntry) -> Result<()> {
-        let json = serde_json::to_string(&entry)?;
-        let conn = self.conn.lock().unwrap();
-        conn.execute("INSERT INTO memory (entry) VALUES (?1)", [&json])?;
+        let stored = compression::encode(serde_json::to_string(&entry)?)?;
+        let text = entry.text();
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_conn(move |conn| {
+            conn.execute("INSERT INTO memory (entry) VALUES (?1)", [&stored])?;
+            conn.execute(
+                "INSERT INTO memory_fts (text, entry, session_id, project)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![text, stored, session_id, project],
+            )?;
+            Ok(())
+        })
+        .await?;
+
+        match &entry {
+            MemoryEntry::Task { content } => {
+                *self.current_task.lock().unwrap() = content.clone();
+            }
+            MemoryEntry::Iteration { results, .. } => {
+                let task = self.current_task.lock().unwrap().clone();
+                for r in results {
+                    if let Outcome::Success(out) = &r.outcome {
+                        self.store_embedding_later(task.clone(), format!("[{}] {}", r.tool, out));
+                    }
+                }
+            }
+            MemoryEntry::Answer { .. } | MemoryEntry::Retry { .. } => {}
+        }
         Ok(())
     }
 
     async fn history(&self) -> Result<Vec<MemoryEntry>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT entry FROM memory ORDER BY id ASC")?;
-        let jsons = stmt
-            .query_map([], |row| row.get::<_, String>(0))?
-            .collect::<Result<Vec<_>, _>>()?;
-        let entries = jsons
-            .iter()
-            .map(|json| serde_json::from_str(json))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(entries)
+        // SQLite reads a negative LIMIT as no limit
+        self.read_history(0, -1).await
+    }
+
+    async fn history_len(&self) -> Result<usize> {
+        self.with_reader(|conn| {
+            let count: i64 = conn.query_row("SELECT COUNT(*) FROM memory", [], |row| row.get(0))?;
+            Ok(count as usize)
+        })
+        .await
+    }
+
+    async fn history_page(&self, offset: usize, limit: usize) -> Result<Vec<MemoryEntry>> {
+        self.read_history(offset as i64, limit as i64).await
     }
 
     async fn recall(&self, query: &str) -> Result<Vec<MemoryEntry>> {
-        // Simple substring search for now. Could be upgraded to FTS5 or vector search.
-        let conn = self.conn.lock().unwrap();
-        let mut stmt =
-            conn.prepare("SELECT entry FROM memory WHERE entry LIKE ?1 ORDER BY id ASC")?;
-        let pattern = format!("%{query}%");
-        let jsons = stmt
-            .query_map([&pattern], |row| row.get::<_, String>(0))?
-            .collect::<Result<Vec<_>, _>>()?;
-        let entries = jsons
-            .iter()
-            .map(|json| serde_json::from_str(json))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(entries)
+        Ok(self
+            .search(query, None)
+            .await?
+            .into_iter()
+            .map(|hit| hit.entry)
+            .collect())
+    }
+
+    async fn recall_snippets(&self, query: &str, limit: usize) -> Result<Vec<RecallHit>> {
+        self.search(query, Some(limit)).await
     }
 
     async fn clear(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM memory", [])?;
-        Ok(())
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM memory", [])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn unfinished_task(&self) -> Result<Option<String>> {
+        // Only the first and last entries matter
+        let len = self.history_len().await?;
+        let mut ends = self.history_page(0, 1).await?;
+        if len > 1 {
+            ends.extend(self.history_page(len - 1, 1).await?);
+        }
+        let task = super::unfinished_task(&ends).map(str::to_string);
+        if let Some(task) = &task {
+            *self.current_task.lock().unwrap() = task.clone();
+        }
+        Ok(task)
     }
 
     // --- Session memory ---
 
     async fn store_session(&self, entry: SessionEntry) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO session_history (task, answer) VALUES (?1, ?2)",
-            [&entry.task, &entry.answer],
-        )?;
+        // The task's entries as one JSON array, built a row at a time: the
+        // rows already hold each entry's JSON, so nothing is parsed
+        let transcript = self
+            .with_reader(|conn| {
+                let mut stmt = conn.prepare("SELECT entry FROM memory ORDER BY id ASC")?;
+                let mut rows = stmt.query([])?;
+                let mut transcript = compression::Encoder::default();
+                transcript.push("[")?;
+                let mut first = true;
+                while let Some(row) = rows.next()? {
+                    if !first {
+                        transcript.push(",")?;
+                    }
+                    first = false;
+                    transcript.push(&compression::decode(row.get(0)?)?)?;
+                }
+                transcript.push("]")?;
+                transcript.finish()
+            })
+            .await?;
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        let (task, answer) = (entry.task.clone(), entry.answer.clone());
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO session_history (task, answer, session_id, entries, project)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![task, answer, session_id, transcript, project],
+            )?;
+            Ok(())
+        })
+        .await?;
+        // The task is over, so its observations needn't be in the background
+        self.embedded().await;
+        self.store_embedding_in(session_id, HitSource::Session, &entry.task, &entry.answer)
+            .await;
         Ok(())
     }
 
-    async fn session_history(&self, limit: usize) -> Result<Vec<SessionEntry>> {
-        let conn = self.conn.lock().unwrap();
-        // Get the last `limit` entries, but return them in chronological order
-        let mut stmt = conn.prepare(
-            "SELECT task, answer FROM (
-                SELECT task, answer, id FROM session_history ORDER BY id DESC LIMIT ?1
-            ) ORDER BY id ASC",
-        )?;
-        let entries = stmt
-            .query_map([limit as i64], |row| {
-                Ok(SessionEntry {
-                    task: row.get(0)?,
-                    answer: row.get(1)?,
+    async fn record_task_usage(&self, usage: TokenUsage) -> Result<()> {
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_conn(move |conn| {
+            conn.execute(
+                &format!(
+                    "UPDATE session_history SET input_tokens = ?1, output_tokens = ?2
+                     WHERE id = (SELECT MAX(id) FROM session_history
+                                 WHERE session_id = ?3 AND {})",
+                    project_filter(4)
+                ),
+                rusqlite::params![
+                    usage.input_tokens as i64,
+                    usage.output_tokens as i64,
+                    session_id,
+                    project
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn session_records(&self) -> Result<Vec<SessionRecord>> {
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_reader(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT timestamp, task, answer, entries, input_tokens, output_tokens
+                 FROM session_history WHERE session_id = ?1 AND {} ORDER BY id ASC",
+                project_filter(2)
+            ))?;
+            let rows = stmt
+                .query_map(rusqlite::params![session_id, project], |row| {
+                    Ok((
+                        SessionRecord {
+                            timestamp: row.get(0)?,
+                            task: row.get(1)?,
+                            answer: row.get(2)?,
+                            entries: vec![],
+                            usage: TokenUsage {
+                                input_tokens: row.get::<_, i64>(4)? as u64,
+                                output_tokens: row.get::<_, i64>(5)? as u64,
+                                ..TokenUsage::default()
+                            },
+                        },
+                        row.get::<_, Option<Value>>(3)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.into_iter()
+                .map(|(mut record, entries)| {
+                    if let Some(stored) = entries {
+                        record.entries = serde_json::from_str(&compression::decode(stored)?)?;
+                    }
+                    Ok(record)
                 })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(entries)
+                .collect()
+        })
+        .await
+    }
+
+    async fn session_history(&self, limit: usize) -> Result<Vec<SessionEntry>> {
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_reader(move |conn| {
+            // Get the last `limit` entries, but return them in chronological order
+            let mut stmt = conn.prepare(&format!(
+                "SELECT task, answer FROM (
+                    SELECT task, answer, id FROM session_history
+                    WHERE session_id = ?1 AND {} ORDER BY id DESC LIMIT ?2
+                ) ORDER BY id ASC",
+                project_filter(3)
+            ))?;
+            let entries = stmt
+                .query_map(
+                    rusqlite::params![session_id, limit as i64, project],
+                    |row| {
+                        Ok(SessionEntry {
+                            task: row.get(0)?,
+                            answer: row.get(1)?,
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn session_len(&self) -> Result<usize> {
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_reader(move |conn| {
+            let len: i64 = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM session_history WHERE session_id = ?1 AND {}",
+                    project_filter(2)
+                ),
+                rusqlite::params![session_id, project],
+                |row| row.get(0),
+            )?;
+            Ok(len as usize)
+        })
+        .await
+    }
+
+    async fn compact_session(&self, count: usize, summary: SessionEntry) -> Result<()> {
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let ids = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT id FROM session_history WHERE session_id = ?1 AND {}
+                     ORDER BY id ASC LIMIT ?2",
+                    project_filter(3)
+                ))?;
+                stmt.query_map(
+                    rusqlite::params![session_id, count as i64, project],
+                    |row| row.get::<_, i64>(0),
+                )?
+                .collect::<Result<Vec<_>, _>>()?
+            };
+            let Some(first) = ids.first() else {
+                return Ok(());
+            };
+            // Other projects' rows may sit between these ids; delete exactly these
+            for id in &ids {
+                tx.execute("DELETE FROM session_history WHERE id = ?1", [id])?;
+            }
+            // Reuse the oldest id so the summary sorts before everything kept
+            tx.execute(
+                "INSERT INTO session_history (id, task, answer, session_id, project)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![first, summary.task, summary.answer, session_id, project],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
     }
 
     async fn clear_session(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM session_history", [])?;
+        // Or they would land in the cleared session afterwards
+        self.embedded().await;
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let ids = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT id FROM embeddings WHERE session_id = ?1 AND {}",
+                    project_filter(2)
+                ))?;
+                stmt.query_map(rusqlite::params![session_id, project], |row| row.get(0))?
+                    .collect::<Result<Vec<i64>, _>>()?
+            };
+            vector_index::remove(&tx, &ids)?;
+            for table in ["session_history", "embeddings", "memory_fts"] {
+                // A new session shouldn't be primed with the old one's context
+                tx.execute(
+                    &format!(
+                        "DELETE FROM {table} WHERE session_id = ?1 AND {}",
+                        project_filter(2)
+                    ),
+                    rusqlite::params![session_id, project],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // --- Named sessions ---
+
+    async fn active_session(&self) -> Result<String> {
+        let session_id = self.session_id();
+        self.with_reader(move |conn| {
+            Ok(conn.query_row(
+                "SELECT name FROM sessions WHERE id = ?1",
+                [session_id],
+                |row| row.get(0),
+            )?)
+        })
+        .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<NamedSession>> {
+        let project = self.project.clone();
+        self.with_reader(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT s.name, s.created_at,
+                        (SELECT COUNT(*) FROM session_history
+                         WHERE session_id = s.id AND {})
+                 FROM sessions s ORDER BY s.id ASC",
+                project_filter(1)
+            ))?;
+            let sessions = stmt
+                .query_map([&project], |row| {
+                    Ok(NamedSession {
+                        name: row.get(0)?,
+                        created_at: row.get(1)?,
+                        entries: row.get::<_, i64>(2)? as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(sessions)
+        })
+        .await
+    }
+
+    async fn create_session(&self, name: &str) -> Result<()> {
+        let name = name.to_string();
+        self.with_conn(move |conn| {
+            let created =
+                conn.execute("INSERT OR IGNORE INTO sessions (name) VALUES (?1)", [&name])?;
+            if created == 0 {
+                bail!("session '{name}' already exists");
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn switch_session(&self, name: &str) -> Result<()> {
+        let owned = name.to_string();
+        let id = self
+            .with_conn(move |conn| {
+                Ok(conn
+                    .query_row("SELECT id FROM sessions WHERE name = ?1", [owned], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .optional()?)
+            })
+            .await?;
+        match id {
+            Some(id) => {
+                *self.session_id.lock().unwrap() = id;
+                Ok(())
+            }
+            None => bail!("no session named '{name}'"),
+        }
+    }
+
+    async fn import_session(&self, name: &str, records: &[SessionRecord]) -> Result<()> {
+        let name = name.to_string();
+        let owned = records.to_vec();
+        let project = self.project.clone();
+        let session_id = self
+            .with_conn(move |conn| {
+                let tx = conn.transaction()?;
+                let created =
+                    tx.execute("INSERT OR IGNORE INTO sessions (name) VALUES (?1)", [&name])?;
+                if created == 0 {
+                    bail!("session '{name}' already exists");
+                }
+                let session_id = tx.last_insert_rowid();
+                for record in &owned {
+                    tx.execute(
+                        "INSERT INTO session_history
+                            (timestamp, task, answer, session_id, entries, input_tokens,
+                             output_tokens, project)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        rusqlite::params![
+                            record.timestamp,
+                            record.task,
+                            record.answer,
+                            session_id,
+                            compression::encode(serde_json::to_string(&record.entries)?)?,
+                            record.usage.input_tokens as i64,
+                            record.usage.output_tokens as i64,
+                            project
+                        ],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(session_id)
+            })
+            .await?;
+
+        for record in records {
+            self.store_embedding_in(session_id, HitSource::Session, &record.task, &record.answer)
+                .await;
+        }
         Ok(())
     }
+
+    async fn search_session(
+        &self,
+        query: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<SessionMatch>> {
+        let mut params = vec![
+            since.map_or(Value::Null, |s| Value::Text(s.to_string())),
+            until.map_or(Value::Null, |s| Value::Text(s.to_string())),
+            self.project.clone().map_or(Value::Null, Value::Text),
+        ];
+        let mut sql = format!(
+            "SELECT s.name, h.timestamp, h.task, h.answer
+             FROM session_history h JOIN sessions s ON s.id = h.session_id
+             WHERE (?1 IS NULL OR h.timestamp >= ?1) AND (?2 IS NULL OR h.timestamp < ?2)
+               AND {}",
+            project_filter(3)
+        );
+        for word in query.split_whitespace() {
+            params.push(Value::Text(format!("%{}%", like_escape(word))));
+            let n = params.len();
+            sql.push_str(&format!(
+                " AND (h.task LIKE ?{n} ESCAPE '\\' OR h.answer LIKE ?{n} ESCAPE '\\')"
+            ));
+        }
+        sql.push_str(" ORDER BY h.timestamp DESC, h.id DESC");
+
+        self.with_reader(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let matches = stmt
+                .query_map(rusqlite::params_from_iter(params), |row| {
+                    Ok(SessionMatch {
+                        session: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        task: row.get(2)?,
+                        answer: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(matches)
+        })
+        .await
+    }
+
+    async fn recall_semantic(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>> {
+        self.embedded().await;
+        let query = self.embedder.embed(query).await?;
+        let model = self.embedder.id().to_string();
+        let session_id = self.session_id();
+        let project = self.project.clone();
+        self.with_reader(move |conn| {
+            let nearest =
+                vector_index::nearest(conn, &model, session_id, project.as_deref(), &query, limit)?;
+            let mut stmt =
+                conn.prepare("SELECT source, task, text FROM embeddings WHERE id = ?1")?;
+            nearest
+                .into_iter()
+                .map(|(id, score)| {
+                    Ok(stmt.query_row([id], |row| {
+                        Ok(SemanticHit {
+                            source: HitSource::parse(&row.get::<_, String>(0)?),
+                            task: row.get(1)?,
+                            text: row.get(2)?,
+                            score,
+                        })
+                    })?)
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn stats(&self) -> Result<MemoryStats> {
+        self.with_reader(|conn| {
+            // Every user table in the file (config, credentials, facts too),
+            // minus SQLite internals and the FTS and vector indexes' shadow
+            // tables
+            let names = {
+                let mut stmt = conn.prepare(
+                    "SELECT name FROM sqlite_master
+                     WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+                       AND name NOT LIKE 'memory_fts_%'
+                       AND name NOT GLOB 'embedding_index_*_*'
+                     ORDER BY name",
+                )?;
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+            let mut tables = Vec::with_capacity(names.len());
+            for name in names {
+                let rows: i64 =
+                    conn.query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                        row.get(0)
+                    })?;
+                let time_column: Option<String> = conn
+                    .query_row(
+                        &format!(
+                            "SELECT name FROM pragma_table_info('{name}')
+                             WHERE name IN ('timestamp', 'created_at') LIMIT 1"
+                        ),
+                        [],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                let (oldest, newest) = match time_column {
+                    Some(col) => conn.query_row(
+                        &format!("SELECT MIN({col}), MAX({col}) FROM \"{name}\""),
+                        [],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?,
+                    None => (None, None),
+                };
+                tables.push(TableStats {
+                    name,
+                    rows: rows as u64,
+                    oldest,
+                    newest,
+                });
+            }
+
+            let database_bytes: i64 = conn.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count, pragma_page_size",
+                [],
+                |row| row.get(0),
+            )?;
+            let fts_bytes: i64 = conn.query_row(
+                "SELECT COALESCE(SUM(length(block)), 0) FROM memory_fts_data",
+                [],
+                |row| row.get(0),
+            )?;
+            let (vectors, vector_bytes): (i64, i64) = conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(length(vector)), 0) FROM embeddings",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok(MemoryStats {
+                tables,
+                database_bytes: Some(database_bytes as u64),
+                fts_bytes: Some(fts_bytes as u64),
+                vectors: vectors as u64,
+                vector_bytes: vector_bytes as u64,
+            })
+        })
+        .await
+    }
+}
+
+/// Escape `LIKE` wildcards so user input matches literally.
+fn like_escape(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Turn free text into an FTS5 query: every word must match, and FTS
+/// operators in user input are treated as plain text.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
 }
@@ -0,0 +1,182 @@
+//! Nearest-neighbour index over stored embeddings, using sqlite-vec.
+//!
+//! `embeddings` keeps every vector next to its text. Each embedder's
+//! vectors are also indexed in a `vec0` table of their own, since vectors
+//! of different sizes can't share one; `embedding_indexes` names it. Rows
+//! share the embedding's id and are partitioned by session, so a search
+//! only reads the active session's vectors. Global entries are filed
+//! under the project `''`, which every project sees.
+
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::Once;
+
+use super::embedding;
+
+/// Load sqlite-vec into every connection opened from now on.
+pub fn register() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        // SAFETY: `sqlite3_vec_init` is an SQLite extension entry point,
+        // which is what `sqlite3_auto_extension` expects
+        unsafe {
+            rusqlite::ffi::sqlite3_auto_extension(Some(std::mem::transmute::<
+                *const (),
+                unsafe extern "C" fn(
+                    *mut rusqlite::ffi::sqlite3,
+                    *mut *mut std::os::raw::c_char,
+                    *const rusqlite::ffi::sqlite3_api_routines,
+                ) -> i32,
+            >(
+                sqlite_vec::sqlite3_vec_init as *const (),
+            )));
+        }
+    });
+}
+
+/// The index table holding `model`'s vectors, if it has one.
+pub fn table(conn: &Connection, model: &str) -> Result<Option<String>> {
+    let id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM embedding_indexes WHERE model = ?1",
+            [model],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id.map(|id| format!("embedding_index_{id}")))
+}
+
+/// Create the index for `model`'s `dims`-wide vectors and fill it from
+/// what `embeddings` already holds.
+fn create(conn: &Connection, model: &str, dims: usize) -> Result<()> {
+    conn.execute(
+        "INSERT INTO embedding_indexes (model, dims) VALUES (?1, ?2)",
+        rusqlite::params![model, dims as i64],
+    )?;
+    let table = format!("embedding_index_{}", conn.last_insert_rowid());
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE {table} USING vec0(
+            session_id INTEGER PARTITION KEY,
+            project TEXT,
+            vector FLOAT[{dims}] distance_metric=cosine
+         )"
+    ))?;
+    // Zero vectors (nothing to embed) are near nothing, so stay out
+    conn.execute(
+        &format!(
+            "INSERT INTO {table} (rowid, session_id, project, vector)
+             SELECT id, session_id, COALESCE(project, ''), vector FROM embeddings
+             WHERE model = ?1 AND length(vector) = ?2 AND vector != zeroblob(?2)"
+        ),
+        rusqlite::params![model, (dims * 4) as i64],
+    )?;
+    Ok(())
+}
+
+/// Index every stored vector, for databases that kept them unindexed.
+pub fn build(conn: &Connection) -> Result<()> {
+    let mut stmt =
+        conn.prepare("SELECT model, MAX(length(vector)) FROM embeddings GROUP BY model")?;
+    let models = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (model, bytes) in models {
+        if table(conn, &model)?.is_none() {
+            create(conn, &model, bytes as usize / 4)?;
+        }
+    }
+    Ok(())
+}
+
+/// Index the vector stored in `embeddings` under `id`.
+pub fn insert(
+    conn: &Connection,
+    id: i64,
+    model: &str,
+    session_id: i64,
+    project: Option<&str>,
+    vector: &[f32],
+) -> Result<()> {
+    match table(conn, model)? {
+        // Creating the index fills it from `embeddings`, this vector included
+        None => create(conn, model, vector.len()),
+        Some(_) if vector.iter().all(|x| *x == 0.0) => Ok(()),
+        Some(table) => {
+            conn.execute(
+                &format!(
+                    "INSERT INTO {table} (rowid, session_id, project, vector)
+                     VALUES (?1, ?2, ?3, ?4)"
+                ),
+                rusqlite::params![
+                    id,
+                    session_id,
+                    project.unwrap_or(""),
+                    embedding::to_blob(vector)
+                ],
+            )
+            .with_context(|| format!("failed to index a {}-wide {model} vector", vector.len()))?;
+            Ok(())
+        }
+    }
+}
+
+/// The `limit` vectors of `model` nearest to `query` in a session, as
+/// embedding ids and cosine similarities, best first. `project` sees its
+/// own and global entries; `None` sees every project's.
+pub fn nearest(
+    conn: &Connection,
+    model: &str,
+    session_id: i64,
+    project: Option<&str>,
+    query: &[f32],
+    limit: usize,
+) -> Result<Vec<(i64, f32)>> {
+    let Some(table) = table(conn, model)? else {
+        return Ok(Vec::new());
+    };
+    if limit == 0 || query.iter().all(|x| *x == 0.0) {
+        return Ok(Vec::new());
+    }
+    let mut sql = format!(
+        "SELECT rowid, distance FROM {table}
+         WHERE vector MATCH ?1 AND k = ?2 AND session_id = ?3"
+    );
+    let mut params: Vec<Value> = vec![
+        Value::Blob(embedding::to_blob(query)),
+        Value::Integer(limit as i64),
+        Value::Integer(session_id),
+    ];
+    if let Some(project) = project {
+        sql.push_str(" AND project IN (?4, '')");
+        params.push(Value::Text(project.to_string()));
+    }
+    sql.push_str(" ORDER BY distance");
+    let mut stmt = conn.prepare(&sql)?;
+    let hits = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            // Cosine distance is one minus the similarity
+            Ok((row.get::<_, i64>(0)?, 1.0 - row.get::<_, f64>(1)? as f32))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hits)
+}
+
+/// Drop the embeddings with these ids from every index.
+pub fn remove(conn: &Connection, ids: &[i64]) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id FROM embedding_indexes")?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, i64>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for table in tables {
+        let mut delete = conn.prepare(&format!(
+            "DELETE FROM embedding_index_{table} WHERE rowid = ?1"
+        ))?;
+        for id in ids {
+            delete.execute([id])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! Per-task metrics storage backed by SQLite.
+//!
+//! The engine records one row per finished task — how long it took, how
+//! many iterations and tool calls it needed, its token usage, and whether
+//! it succeeded. `golem stats` aggregates them and `/tokens` lists the
+//! latest.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::sync::Mutex;
+
+use crate::thinker::TokenUsage;
+
+/// What happened while running one task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskMetrics {
+    pub task: String,
+    pub model: String,
+    pub duration_ms: u64,
+    pub iterations: u64,
+    pub tool_calls: u64,
+    pub usage: TokenUsage,
+    /// `None` if the task produced an answer.
+    pub error: Option<String>,
+}
+
+/// Totals over a group of recorded tasks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsTotals {
+    pub tasks: u64,
+    pub succeeded: u64,
+    pub duration_ms: u64,
+    pub iterations: u64,
+    pub tool_calls: u64,
+    pub usage: TokenUsage,
+}
+
+impl MetricsTotals {
+    /// Mean wall-clock time per task, in milliseconds.
+    pub fn avg_duration_ms(&self) -> u64 {
+        self.duration_ms.checked_div(self.tasks).unwrap_or(0)
+    }
+
+    /// Mean iterations per task.
+    pub fn avg_iterations(&self) -> f64 {
+        if self.tasks == 0 {
+            0.0
+        } else {
+            self.iterations as f64 / self.tasks as f64
+        }
+    }
+}
+
+/// One recorded task, as `/tokens` lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRecord {
+    /// UTC time the task finished, as `YYYY-MM-DD HH:MM:SS`.
+    pub timestamp: String,
+    /// `None` for tasks recorded before the task text was kept.
+    pub task: Option<String>,
+    pub model: String,
+    pub usage: TokenUsage,
+    pub succeeded: bool,
+}
+
+/// Aggregated metrics, overall and per model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSummary {
+    pub overall: MetricsTotals,
+    /// Sorted by task count, most used first.
+    pub by_model: Vec<(String, MetricsTotals)>,
+}
+
+/// Persistent per-task metrics store.
+pub struct MetricsStore {
+    conn: Mutex<Connection>,
+}
+
+impl MetricsStore {
+    /// Open or create the metrics table in the given database.
+    /// Use `":memory:"` for tests.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = crate::db::open(path).context("failed to open metrics database")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Record one finished task.
+    pub fn record(&self, metrics: &TaskMetrics) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO task_metrics
+                (task, model, duration_ms, iterations, tool_calls, input_tokens,
+                 output_tokens, cache_read_tokens, success, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                metrics.task,
+                metrics.model,
+                metrics.duration_ms as i64,
+                metrics.iterations as i64,
+                metrics.tool_calls as i64,
+                metrics.usage.input_tokens as i64,
+                metrics.usage.output_tokens as i64,
+                metrics.usage.cache_read_tokens as i64,
+                metrics.error.is_none(),
+                metrics.error
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Aggregate every recorded task.
+    pub fn summary(&self) -> Result<MetricsSummary> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT model, COUNT(*), SUM(success), SUM(duration_ms), SUM(iterations),
+                    SUM(tool_calls), SUM(input_tokens), SUM(output_tokens),
+                    SUM(cache_read_tokens)
+             FROM task_metrics GROUP BY model ORDER BY COUNT(*) DESC, model ASC",
+        )?;
+        let by_model = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    MetricsTotals {
+                        tasks: row.get::<_, i64>(1)? as u64,
+                        succeeded: row.get::<_, i64>(2)? as u64,
+                        duration_ms: row.get::<_, i64>(3)? as u64,
+                        iterations: row.get::<_, i64>(4)? as u64,
+                        tool_calls: row.get::<_, i64>(5)? as u64,
+                        usage: TokenUsage {
+                            input_tokens: row.get::<_, i64>(6)? as u64,
+                            output_tokens: row.get::<_, i64>(7)? as u64,
+                            cache_read_tokens: row.get::<_, i64>(8)? as u64,
+                        },
+                    },
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut overall = MetricsTotals::default();
+        for (_, totals) in &by_model {
+            overall.tasks += totals.tasks;
+            overall.succeeded += totals.succeeded;
+            overall.duration_ms += totals.duration_ms;
+            overall.iterations += totals.iterations;
+            overall.tool_calls += totals.tool_calls;
+            overall.usage.add(totals.usage);
+        }
+        Ok(MetricsSummary { overall, by_model })
+    }
+
+    /// The last `limit` recorded tasks, newest first.
+    pub fn recent(&self, limit: usize) -> Result<Vec<TaskRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, task, model, input_tokens, output_tokens, cache_read_tokens,
+                    success
+             FROM task_metrics ORDER BY id DESC LIMIT ?1",
+        )?;
+        let records = stmt
+            .query_map([limit as i64], |row| {
+                Ok(TaskRecord {
+                    timestamp: row.get(0)?,
+                    task: row.get(1)?,
+                    model: row.get(2)?,
+                    usage: TokenUsage {
+                        input_tokens: row.get::<_, i64>(3)? as u64,
+                        output_tokens: row.get::<_, i64>(4)? as u64,
+                        cache_read_tokens: row.get::<_, i64>(5)? as u64,
+                    },
+                    succeeded: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(model: &str, duration_ms: u64, error: Option<&str>) -> TaskMetrics {
+        TaskMetrics {
+            task: format!("task on {model}"),
+            model: model.to_string(),
+            duration_ms,
+            iterations: 2,
+            tool_calls: 3,
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 10,
+                cache_read_tokens: 40,
+            },
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn empty_summary() {
+        let store = MetricsStore::open(":memory:").unwrap();
+        let summary = store.summary().unwrap();
+        assert_eq!(summary.overall.tasks, 0);
+        assert_eq!(summary.overall.avg_duration_ms(), 0);
+        assert_eq!(summary.overall.avg_iterations(), 0.0);
+        assert!(summary.by_model.is_empty());
+    }
+
+    #[test]
+    fn aggregates_overall_and_per_model() {
+        let store = MetricsStore::open(":memory:").unwrap();
+        store.record(&task("sonnet", 1000, None)).unwrap();
+        store
+            .record(&task("sonnet", 3000, Some("max iterations")))
+            .unwrap();
+        store.record(&task("haiku", 500, None)).unwrap();
+
+        let summary = store.summary().unwrap();
+        assert_eq!(summary.overall.tasks, 3);
+        assert_eq!(summary.overall.succeeded, 2);
+        assert_eq!(summary.overall.tool_calls, 9);
+        assert_eq!(summary.overall.usage.total(), 330);
+        assert_eq!(summary.overall.avg_duration_ms(), 1500);
+        assert_eq!(summary.overall.avg_iterations(), 2.0);
+
+        assert_eq!(summary.by_model[0].0, "sonnet");
+        assert_eq!(summary.by_model[0].1.tasks, 2);
+        assert_eq!(summary.by_model[0].1.succeeded, 1);
+        assert_eq!(summary.by_model[1].0, "haiku");
+    }
+
+    #[test]
+    fn recent_tasks_newest_first() {
+        let store = MetricsStore::open(":memory:").unwrap();
+        store.record(&task("sonnet", 1000, None)).unwrap();
+        store.record(&task("haiku", 500, Some("boom"))).unwrap();
+        store.record(&task("opus", 800, None)).unwrap();
+
+        let recent = store.recent(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].model, "opus");
+        assert_eq!(recent[0].task.as_deref(), Some("task on opus"));
+        assert_eq!(recent[0].usage.cache_read_tokens, 40);
+        assert!(recent[0].succeeded);
+        assert_eq!(recent[1].model, "haiku");
+        assert!(!recent[1].succeeded);
+        assert_eq!(
+            store.summary().unwrap().overall.usage.cache_read_tokens,
+            120
+        );
+    }
+}
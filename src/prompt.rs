@@ -0,0 +1,222 @@
+//! Questions for the user while a task or command runs.
+//!
+//! Reading `std::io::stdin` straight from async code blocks a runtime
+//! worker until the user answers. [`Prompter::ask`] reads on a blocking
+//! thread instead, and [`terminal`] hands every caller — tool approvals,
+//! step mode, budget questions, the human thinker, `/login`, `/model`,
+//! `/mode` — the same prompter, so questions asked at once (say, by
+//! parallel tool calls) take turns instead of interleaving.
+//!
+//! A question can be abandoned — the task is interrupted, or the tool
+//! call it guards is dropped. The read then stops without consuming
+//! anything, so the next line typed still reaches the REPL.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Asks the user for a line of input.
+#[async_trait]
+pub trait Prompter: Send + Sync {
+    /// Show `question` and wait for the answer, trimmed; `None` once input
+    /// has ended.
+    async fn ask(&self, question: &str) -> Result<Option<String>>;
+
+    /// Ask a yes/no `question`; anything but `y` is no.
+    async fn confirm(&self, question: &str) -> Result<bool> {
+        Ok(self
+            .ask(question)
+            .await?
+            .is_some_and(|answer| answer.eq_ignore_ascii_case("y")))
+    }
+}
+
+/// Asks on stdout and reads stdin, one question at a time.
+#[derive(Default)]
+pub struct TerminalPrompter {
+    turn: tokio::sync::Mutex<()>,
+}
+
+#[async_trait]
+impl Prompter for TerminalPrompter {
+    async fn ask(&self, question: &str) -> Result<Option<String>> {
+        let _turn = self.turn.lock().await;
+        print!("{question}");
+        io::stdout().flush()?;
+        let abandoned = Abandoned::default();
+        let flag = Arc::clone(&abandoned.0);
+        let line = tokio::task::spawn_blocking(move || read_line(&flag)).await??;
+        Ok(line.map(|line| line.trim().to_string()))
+    }
+}
+
+/// Set when the question it belongs to is dropped unanswered.
+#[derive(Default)]
+struct Abandoned(Arc<AtomicBool>);
+
+impl Drop for Abandoned {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// How often a waiting read checks whether it was abandoned.
+#[cfg(unix)]
+const POLL_MS: i32 = 100;
+
+/// Read a line from stdin, giving up once `abandoned` is set. `None` once
+/// input has ended. Bytes are read straight from the descriptor, one at a
+/// time and only once they're ready, so an abandoned read takes nothing
+/// and a finished one nothing past its newline.
+#[cfg(unix)]
+fn read_line(abandoned: &AtomicBool) -> io::Result<Option<String>> {
+    read_line_from(libc::STDIN_FILENO, abandoned)
+}
+
+#[cfg(unix)]
+fn read_line_from(input: i32, abandoned: &AtomicBool) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    loop {
+        // Finish a line once started, rather than leave half of it
+        if line.is_empty() && abandoned.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let mut fd = libc::pollfd {
+            fd: input,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fd` is a single valid pollfd
+        let ready = unsafe { libc::poll(&mut fd, 1, POLL_MS) };
+        let mut byte = 0u8;
+        let read = match ready {
+            0 => continue,
+            // SAFETY: reads at most one byte into `byte`
+            n if n > 0 => unsafe { libc::read(input, (&raw mut byte).cast(), 1) },
+            _ => -1,
+        };
+        match read {
+            1 if byte == b'\n' => break,
+            1 => line.push(byte),
+            0 if line.is_empty() => return Ok(None),
+            0 => break,
+            _ => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+        }
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+/// Read a line from stdin. Elsewhere than unix the read can't be
+/// abandoned, so a dropped question still takes the next line.
+#[cfg(not(unix))]
+fn read_line(_abandoned: &AtomicBool) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = io::stdin().read_line(&mut line)?;
+    Ok((read > 0).then_some(line))
+}
+
+/// The prompter for the terminal golem runs in, shared by everyone who
+/// asks there.
+pub fn terminal() -> Arc<dyn Prompter> {
+    static TERMINAL: OnceLock<Arc<TerminalPrompter>> = OnceLock::new();
+    TERMINAL.get_or_init(Arc::default).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Gives the next of its answers to each question.
+    struct Scripted(Mutex<Vec<Option<&'static str>>>);
+
+    #[async_trait]
+    impl Prompter for Scripted {
+        async fn ask(&self, _question: &str) -> Result<Option<String>> {
+            Ok(self.0.lock().unwrap().remove(0).map(String::from))
+        }
+    }
+
+    #[tokio::test]
+    async fn only_y_confirms() {
+        let prompter = Scripted(Mutex::new(vec![
+            Some("Y"),
+            Some("yes please"),
+            Some(""),
+            None,
+        ]));
+        assert!(prompter.confirm("?").await.unwrap());
+        assert!(!prompter.confirm("?").await.unwrap());
+        assert!(!prompter.confirm("?").await.unwrap());
+        assert!(!prompter.confirm("?").await.unwrap());
+    }
+
+    /// A pipe holding `input`, left open for writing.
+    #[cfg(unix)]
+    fn pipe(input: &[u8]) -> (i32, i32) {
+        let mut fds = [0; 2];
+        unsafe {
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            assert_eq!(
+                libc::write(fds[1], input.as_ptr().cast(), input.len()),
+                input.len() as isize
+            );
+        }
+        (fds[0], fds[1])
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_one_line_and_leaves_the_rest() {
+        let (read, write) = pipe(b"y\n/next\n");
+        let abandoned = AtomicBool::new(false);
+        assert_eq!(
+            read_line_from(read, &abandoned).unwrap().as_deref(),
+            Some("y")
+        );
+        assert_eq!(
+            read_line_from(read, &abandoned).unwrap().as_deref(),
+            Some("/next")
+        );
+        unsafe {
+            libc::close(write);
+        }
+        assert_eq!(read_line_from(read, &abandoned).unwrap(), None);
+        unsafe {
+            libc::close(read);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn an_abandoned_read_takes_nothing() {
+        let (read, write) = pipe(b"/next\n");
+        let abandoned = AtomicBool::new(true);
+        assert_eq!(read_line_from(read, &abandoned).unwrap(), None);
+
+        // The line is still there for the next reader
+        assert_eq!(
+            read_line_from(read, &AtomicBool::new(false))
+                .unwrap()
+                .as_deref(),
+            Some("/next")
+        );
+        unsafe {
+            libc::close(read);
+            libc::close(write);
+        }
+    }
+
+    #[test]
+    fn everyone_shares_the_terminal() {
+        let (a, b) = (terminal(), terminal());
+        assert!(std::ptr::addr_eq(Arc::as_ptr(&a), Arc::as_ptr(&b)));
+    }
+}
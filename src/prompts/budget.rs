@@ -0,0 +1,37 @@
+use crate::consts::WRAP_UP_ITERATIONS;
+use crate::thinker::IterationBudget;
+
+/// The line added after the latest observation so the model sees how many
+/// iterations it has left, and is told to wrap up near the end.
+pub fn build_budget_note(budget: IterationBudget) -> String {
+    let remaining = budget.remaining();
+    let position = format!("This is {budget}.");
+    match remaining {
+        0 => format!("{position} It is the last one: give your final answer now."),
+        n if n < WRAP_UP_ITERATIONS => {
+            let s = if n == 1 { "" } else { "s" };
+            format!(
+                "{position} Only {n} more iteration{s} after this one: wrap up and work toward \
+                 your final answer."
+            )
+        }
+        _ => position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(current: usize) -> String {
+        build_budget_note(IterationBudget { current, max: 20 })
+    }
+
+    #[test]
+    fn states_position_and_warns_near_the_end() {
+        assert_eq!(note(3), "This is iteration 3 of 20.");
+        assert!(note(18).contains("Only 2 more iterations after this one"));
+        assert!(note(19).contains("Only 1 more iteration after"));
+        assert!(note(20).ends_with("give your final answer now."));
+    }
+}
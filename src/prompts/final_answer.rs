@@ -0,0 +1,14 @@
+/// The note sent once the iteration budget is spent, so the task ends
+/// with what was learned instead of nothing.
+pub const FINAL_ANSWER_REQUEST: &str = "You are out of iterations and no more tools will run. Respond now with the answer format: give your best answer to the task from what you have learned so far, and say plainly what is unverified or still missing.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_out_further_tool_calls() {
+        assert!(FINAL_ANSWER_REQUEST.contains("no more tools will run"));
+        assert!(FINAL_ANSWER_REQUEST.contains("answer format"));
+    }
+}
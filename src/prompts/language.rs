@@ -0,0 +1,22 @@
+/// The system prompt section asking for thoughts and answers in
+/// `language` (a name like `German` or a code like `he`), with everything
+/// a tool reads left as it must be.
+pub fn build_language_section(language: &str) -> String {
+    format!(
+        "\nLanguage: write the thought and the answer in {language}, whatever language the task \
+         is in. Keep the JSON keys, tool names and args — commands, paths, code — exactly as they \
+         must be; never translate them.\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_language_and_protects_tool_args() {
+        let section = build_language_section("he");
+        assert!(section.contains("answer in he,"));
+        assert!(section.contains("never translate"));
+    }
+}
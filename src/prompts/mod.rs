@@ -1,3 +1,20 @@
+pub mod budget;
+pub mod final_answer;
+pub mod language;
+pub mod persona;
+pub mod plan;
 pub mod react;
+pub mod reflection;
+pub mod summary;
 
-pub use react::build_react_system_prompt;
+pub use budget::build_budget_note;
+pub use final_answer::FINAL_ANSWER_REQUEST;
+pub use language::build_language_section;
+pub use persona::build_persona_section;
+pub use plan::PLAN_REQUEST;
+pub use react::{
+    PromptVariant, build_react_system_prompt, build_system_prompt, load_react_template,
+    render_react_template,
+};
+pub use reflection::build_reflection_prompt;
+pub use summary::{SUMMARY_SYSTEM_PROMPT, build_summary_request};
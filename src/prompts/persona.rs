@@ -0,0 +1,61 @@
+/// A preset that changes how the agent works and talks: its
+/// instructions are added to the system prompt, on top of the rules.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Persona {
+    /// What `/persona` takes, e.g. `sysadmin`.
+    pub name: &'static str,
+    /// One line for the `/persona` list.
+    pub description: &'static str,
+    pub instructions: &'static str,
+}
+
+pub const PERSONAS: &[Persona] = &[
+    Persona {
+        name: "sysadmin",
+        description: "terse: the command, the result, nothing else",
+        instructions: "Work like a terse, experienced sysadmin. Prefer standard tools and one-liners. Keep the answer to the facts and the commands that matter, with no pleasantries or explanations unless asked. Say plainly when something is risky.",
+    },
+    Persona {
+        name: "teacher",
+        description: "verbose: explains each step and why",
+        instructions: "Work like a patient teacher. In the answer, walk through what each step did and why, define terms a newcomer may not know, and suggest what to try next. Prefer clarity over brevity.",
+    },
+    Persona {
+        name: "reviewer",
+        description: "code review: findings by severity, with fixes",
+        instructions: "Work like a careful code reviewer. Read the relevant code before judging it. In the answer, list findings by severity (bugs, then risks, then style), each with its file and line and a concrete fix. Don't change files unless asked.",
+    },
+];
+
+/// The persona called `name`, ignoring case.
+pub fn find_persona(name: &str) -> Option<&'static Persona> {
+    PERSONAS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// The system prompt section for `persona`, added after everything else.
+pub fn build_persona_section(persona: &Persona) -> String {
+    format!(
+        "\nPersona ({}), on top of the rules above:\n{}\n",
+        persona.name, persona.instructions
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_personas_by_name() {
+        assert_eq!(find_persona("teacher").unwrap().name, "teacher");
+        assert_eq!(find_persona("SysAdmin").unwrap().name, "sysadmin");
+        assert!(find_persona("pirate").is_none());
+    }
+
+    #[test]
+    fn section_keeps_the_json_rules() {
+        let section = build_persona_section(find_persona("reviewer").unwrap());
+        assert!(section.contains("Persona (reviewer)"));
+        assert!(section.contains("on top of the rules"));
+        assert!(section.contains("by severity"));
+    }
+}
@@ -0,0 +1,14 @@
+/// The note that turns the next step into a plan: the thinker answers
+/// with the tool calls it would make instead of making them.
+pub const PLAN_REQUEST: &str = "Do not call any tools yet. Plan first: respond with the answer format, and in \"answer\" list every tool call you expect to make for this task, in order, one per line as `N. tool: exact args — why`. Say which calls depend on the output of an earlier one. Nothing runs until the user has reviewed the plan.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asks_for_answer_not_action() {
+        assert!(PLAN_REQUEST.starts_with("Do not call any tools"));
+        assert!(PLAN_REQUEST.contains("answer format"));
+    }
+}
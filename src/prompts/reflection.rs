@@ -0,0 +1,23 @@
+/// The note added after the latest observation once `failures` iterations
+/// in a row produced nothing but tool errors.
+pub fn build_reflection_prompt(failures: usize) -> String {
+    format!(
+        "Your last {failures} attempts all failed. Before your next action, stop and reflect: \
+         in your thought, explain why they failed — wrong command, wrong path, missing \
+         permission, a tool that can't do this — and choose a different approach. Do not \
+         repeat a call that already failed. If the task can't be done, give a final answer \
+         that says so and why."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_failure_count() {
+        let prompt = build_reflection_prompt(3);
+        assert!(prompt.starts_with("Your last 3 attempts all failed."));
+        assert!(prompt.contains("different approach"));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::memory::SessionEntry;
+
+pub const SUMMARY_SYSTEM_PROMPT: &str = "You compress an AI agent's session history. Summarize the completed tasks below into a short plain-text digest that preserves facts a later task might need: file names, paths, numbers, decisions, and outcomes. Drop pleasantries and reasoning. Respond with the summary only — no JSON, no markdown headings.";
+
+/// The user message asking the thinker to summarize `entries`.
+pub fn build_summary_request(entries: &[SessionEntry]) -> String {
+    let mut request = String::from("Completed tasks, oldest first:\n");
+    for (i, entry) in entries.iter().enumerate() {
+        request.push_str(&format!(
+            "\n{}. Task: {}\n   Answer: {}\n",
+            i + 1,
+            entry.task,
+            entry.answer
+        ));
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_lists_entries_in_order() {
+        let entries = vec![
+            SessionEntry {
+                task: "list files".to_string(),
+                answer: "a.txt".to_string(),
+            },
+            SessionEntry {
+                task: "delete a.txt".to_string(),
+                answer: "deleted".to_string(),
+            },
+        ];
+        let request = build_summary_request(&entries);
+        let first = request.find("1. Task: list files").unwrap();
+        let second = request.find("2. Task: delete a.txt").unwrap();
+        assert!(first < second);
+        assert!(request.contains("Answer: deleted"));
+    }
+
+    #[test]
+    fn system_prompt_asks_for_plain_text() {
+        assert!(SUMMARY_SYSTEM_PROMPT.contains("no JSON"));
+    }
+}
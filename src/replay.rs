@@ -0,0 +1,320 @@
+//! Replay a recorded transcript through the engine.
+//!
+//! `golem replay <transcript.jsonl>` feeds each recorded task's model
+//! responses back through a [`ReplayThinker`], so the engine takes the
+//! same steps again without calling a model. Tool calls get their recorded
+//! output by default, or really run with `--execute`. Either way the run
+//! prints every thought and tool result, and a task whose answer differs
+//! from the recorded one shows where the agent went another way.
+//!
+//! [`ReplayThinker`]: crate::thinker::replay::ReplayThinker
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::engine::TaskResult;
+use crate::prompts::{PLAN_REQUEST, SUMMARY_SYSTEM_PROMPT};
+use crate::thinker::replay::RecordedResponse;
+use crate::thinker::{Step, parse_response};
+use crate::tools::{Capabilities, Tool, ToolRegistry, ToolSource};
+use crate::transcript::{TranscriptEvent, read_transcript};
+
+/// A tool execution captured in a transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRun {
+    pub tool: String,
+    pub args: HashMap<String, String>,
+    pub success: bool,
+    pub output: String,
+}
+
+/// One task of a transcript and everything recorded while it ran.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordedTask {
+    pub task: String,
+    /// The model that answered, if any response was recorded.
+    pub model: Option<String>,
+    /// Step responses in order; summaries and plans are left out.
+    pub responses: Vec<RecordedResponse>,
+    pub runs: Vec<RecordedRun>,
+    /// `None` if the task was interrupted or failed.
+    pub answer: Option<String>,
+}
+
+impl RecordedTask {
+    /// Whether `result` ended the way the recording did: the same answer,
+    /// or no answer at all.
+    pub fn matches(&self, result: &Result<TaskResult>) -> bool {
+        match (&self.answer, result) {
+            (Some(recorded), Ok(result)) => *recorded == result.answer,
+            (None, Err(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Read the tasks recorded in the transcript at `path`.
+///
+/// With `skip_delegated`, responses a sub-agent got while a `delegate`
+/// call ran are left out — a stubbed `delegate` replays the sub-agent's
+/// answer, so its steps must not reach the parent.
+pub fn read_recording(path: &Path, skip_delegated: bool) -> Result<Vec<RecordedTask>> {
+    let mut tasks: Vec<RecordedTask> = Vec::new();
+    let mut side_request = false;
+    let mut delegating = 0;
+    for line in read_transcript(path)? {
+        if let TranscriptEvent::Task { task } = line.event {
+            tasks.push(RecordedTask {
+                task,
+                ..RecordedTask::default()
+            });
+            delegating = 0;
+            continue;
+        }
+        // Resumed runs keep appending to the task they continue
+        let Some(current) = tasks.last_mut() else {
+            continue;
+        };
+        match line.event {
+            TranscriptEvent::Task { .. } | TranscriptEvent::Interrupted => {}
+            TranscriptEvent::LlmRequest {
+                system, messages, ..
+            } => side_request = system == SUMMARY_SYSTEM_PROMPT || asks_for_plan(&messages),
+            TranscriptEvent::LlmResponse { model, text, usage } => {
+                if side_request || delegating > 0 {
+                    continue;
+                }
+                if skip_delegated && let Ok(Step::Act { calls, .. }) = parse_response(&text) {
+                    delegating = calls.iter().filter(|c| c.tool == "delegate").count();
+                }
+                current.model.get_or_insert(model);
+                current.responses.push(RecordedResponse { text, usage });
+            }
+            TranscriptEvent::Tool {
+                tool,
+                args,
+                success,
+                output,
+                ..
+            } => {
+                if tool == "delegate" {
+                    delegating = delegating.saturating_sub(1);
+                }
+                current.runs.push(RecordedRun {
+                    tool,
+                    args,
+                    success,
+                    output,
+                });
+            }
+            TranscriptEvent::Answer { answer } => current.answer = Some(answer),
+        }
+    }
+    if tasks.is_empty() {
+        bail!("no tasks recorded in {}", path.display());
+    }
+    Ok(tasks)
+}
+
+fn asks_for_plan(messages: &serde_json::Value) -> bool {
+    messages.as_array().is_some_and(|messages| {
+        messages.iter().any(|m| {
+            m.get("content")
+                .and_then(|c| c.as_str())
+                .is_some_and(|c| c.contains(PLAN_REQUEST))
+        })
+    })
+}
+
+/// A registry whose tools answer with the output recorded for `task`.
+pub async fn recorded_tools(task: &RecordedTask) -> ToolRegistry {
+    let runs = Arc::new(Mutex::new(task.runs.clone()));
+    let registry = ToolRegistry::new();
+    let mut names: Vec<&str> = task.runs.iter().map(|run| run.tool.as_str()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        registry
+            .register(Arc::new(RecordedTool {
+                name: name.to_string(),
+                runs: Arc::clone(&runs),
+            }))
+            .await;
+    }
+    registry
+}
+
+/// Stands in for a tool, replaying its recorded runs.
+struct RecordedTool {
+    name: String,
+    runs: Arc<Mutex<Vec<RecordedRun>>>,
+}
+
+#[async_trait]
+impl Tool for RecordedTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "replays recorded output"
+    }
+
+    /// Replaying touches nothing, so there is nothing to approve.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read_only: true,
+            network: false,
+            destructive: false,
+            needs_confirmation: false,
+        }
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::BuiltIn
+    }
+
+    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+        let mut runs = self.runs.lock().expect("recorded runs lock poisoned");
+        let Some(i) = runs
+            .iter()
+            .position(|run| run.tool == self.name && run.args == *args)
+        else {
+            bail!("no recorded run of {} with these args", self.name);
+        };
+        let run = runs.remove(i);
+        if run.success {
+            Ok(run.output)
+        } else {
+            Err(anyhow!(run.output))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Transcript;
+
+    fn respond(transcript: &Transcript, text: &str) {
+        transcript.record(TranscriptEvent::LlmRequest {
+            model: "m".to_string(),
+            system: "react".to_string(),
+            messages: serde_json::json!([]),
+        });
+        transcript.record(TranscriptEvent::LlmResponse {
+            model: "m".to_string(),
+            text: text.to_string(),
+            usage: None,
+        });
+    }
+
+    fn tool(transcript: &Transcript, tool: &str, output: &str) {
+        transcript.record(TranscriptEvent::Tool {
+            tool: tool.to_string(),
+            args: HashMap::from([("command".to_string(), "ls".to_string())]),
+            success: true,
+            output: output.to_string(),
+            duration_ms: 1,
+        });
+    }
+
+    const LS: &str = r#"{"thought": "look", "action": {"calls": [{"tool": "shell", "args": {"command": "ls"}}]}}"#;
+    const DELEGATE: &str = r#"{"thought": "hand off", "action": {"calls": [{"tool": "delegate", "args": {"command": "ls"}}]}}"#;
+    const DONE: &str = r#"{"thought": "done", "answer": "a.txt"}"#;
+
+    #[test]
+    fn groups_events_by_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = Transcript::open(dir.path(), "s").unwrap();
+        transcript.record(TranscriptEvent::Task {
+            task: "list".to_string(),
+        });
+        respond(&transcript, LS);
+        tool(&transcript, "shell", "a.txt");
+        transcript.record(TranscriptEvent::LlmRequest {
+            model: "m".to_string(),
+            system: SUMMARY_SYSTEM_PROMPT.to_string(),
+            messages: serde_json::json!([]),
+        });
+        transcript.record(TranscriptEvent::LlmResponse {
+            model: "m".to_string(),
+            text: "a summary".to_string(),
+            usage: None,
+        });
+        respond(&transcript, DONE);
+        transcript.record(TranscriptEvent::Answer {
+            answer: "a.txt".to_string(),
+        });
+        transcript.record(TranscriptEvent::Task {
+            task: "again".to_string(),
+        });
+        transcript.record(TranscriptEvent::Interrupted);
+
+        let tasks = read_recording(&transcript.path(), true).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].task, "list");
+        assert_eq!(tasks[0].model.as_deref(), Some("m"));
+        assert_eq!(tasks[0].responses.len(), 2);
+        assert_eq!(tasks[0].runs[0].output, "a.txt");
+        assert_eq!(tasks[0].answer.as_deref(), Some("a.txt"));
+        assert!(tasks[1].responses.is_empty());
+        assert!(tasks[1].answer.is_none());
+    }
+
+    #[test]
+    fn sub_agent_responses_are_skipped_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = Transcript::open(dir.path(), "s").unwrap();
+        transcript.record(TranscriptEvent::Task {
+            task: "hand off".to_string(),
+        });
+        respond(&transcript, DELEGATE);
+        respond(&transcript, LS);
+        tool(&transcript, "shell", "a.txt");
+        respond(&transcript, DONE);
+        tool(&transcript, "delegate", "a.txt");
+        respond(&transcript, DONE);
+
+        let stubbed = read_recording(&transcript.path(), true).unwrap();
+        assert_eq!(stubbed[0].responses.len(), 2);
+        let executed = read_recording(&transcript.path(), false).unwrap();
+        assert_eq!(executed[0].responses.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn recorded_tools_answer_matching_calls_once() {
+        let task = RecordedTask {
+            runs: vec![RecordedRun {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "ls".to_string())]),
+                success: false,
+                output: "no such dir".to_string(),
+            }],
+            ..RecordedTask::default()
+        };
+        let tools = recorded_tools(&task).await;
+        let ls = HashMap::from([("command".to_string(), "ls".to_string())]);
+
+        let first = tools.execute("shell", &ls).await;
+        assert!(matches!(first.outcome, crate::tools::Outcome::Error(ref e) if e == "no such dir"));
+        let second = tools.execute("shell", &ls).await;
+        assert!(
+            matches!(second.outcome, crate::tools::Outcome::Error(ref e) if e.starts_with("no recorded run"))
+        );
+    }
+
+    #[test]
+    fn matching_compares_answers() {
+        let task = RecordedTask {
+            answer: Some("a".to_string()),
+            ..RecordedTask::default()
+        };
+        assert!(!task.matches(&Err(anyhow!("boom"))));
+        let unanswered = RecordedTask::default();
+        assert!(unanswered.matches(&Err(anyhow!("boom"))));
+    }
+}
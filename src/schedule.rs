@@ -0,0 +1,289 @@
+//! Scheduled tasks: cron expressions `golem serve` runs tasks on.
+//!
+//! Schedules come from `golem schedule add`, which keeps them in the
+//! database, and from `[schedules.<id>]` tables in the settings, which
+//! win over a stored schedule with the same id. Expressions have the five
+//! usual fields (minute, hour, day of month, month, day of week) and are
+//! read in local time. Every run's outcome is stored with its schedule's
+//! id, for `golem schedule runs`.
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local};
+use croner::Cron;
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::Mutex;
+
+use crate::banner::task_json;
+use crate::config::settings::Settings;
+use crate::engine::TaskResult;
+
+/// A task and when to run it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub id: String,
+    pub cron: String,
+    pub task: String,
+    /// URL the result is posted to.
+    pub notify: Option<String>,
+}
+
+impl Schedule {
+    /// The first time after `time` the schedule is due.
+    pub fn next_after(&self, time: &DateTime<Local>) -> Result<DateTime<Local>> {
+        parse_cron(&self.cron)?
+            .find_next_occurrence(time, false)
+            .with_context(|| format!("schedule {} never runs again", self.id))
+    }
+}
+
+/// Parse a five-field cron expression, e.g. `0 9 * * 1-5`.
+pub fn parse_cron(expr: &str) -> Result<Cron> {
+    Cron::new(expr)
+        .parse()
+        .map_err(|e| anyhow!("invalid cron expression {expr:?}: {e}"))
+}
+
+/// One finished run of a schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleRun {
+    pub schedule: String,
+    /// Local time the run was due, as `YYYY-MM-DD HH:MM`.
+    pub due: String,
+    /// `completed`, `partial`, `failed` or `interrupted`.
+    pub status: String,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Stored schedules and the results of their runs.
+pub struct ScheduleStore {
+    conn: Mutex<Connection>,
+}
+
+impl ScheduleStore {
+    /// Open or create the schedule tables in the given database.
+    /// Use `":memory:"` for tests.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = crate::db::open(path).context("failed to open schedule database")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Store a schedule, under `id` or else the lowest free number.
+    /// Returns its id.
+    pub fn add(
+        &self,
+        id: Option<&str>,
+        cron: &str,
+        task: &str,
+        notify: Option<&str>,
+    ) -> Result<String> {
+        parse_cron(cron)?;
+        let conn = self.conn.lock().unwrap();
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => {
+                let mut n = 1;
+                while exists(&conn, &n.to_string())? {
+                    n += 1;
+                }
+                n.to_string()
+            }
+        };
+        if exists(&conn, &id)? {
+            bail!("schedule {id} already exists");
+        }
+        conn.execute(
+            "INSERT INTO schedules (id, cron, task, notify) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![id, cron, task, notify],
+        )?;
+        Ok(id)
+    }
+
+    /// Remove a stored schedule; false if there was none with that id.
+    /// Its runs are kept.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM schedules WHERE id = ?1", [id])? > 0)
+    }
+
+    /// Every stored schedule, oldest first.
+    pub fn list(&self) -> Result<Vec<Schedule>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT id, cron, task, notify FROM schedules ORDER BY rowid")?;
+        let schedules = stmt
+            .query_map([], |row| {
+                Ok(Schedule {
+                    id: row.get(0)?,
+                    cron: row.get(1)?,
+                    task: row.get(2)?,
+                    notify: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(schedules)
+    }
+
+    /// Record how the run of `schedule` due at `due` ended.
+    pub fn record(
+        &self,
+        schedule: &str,
+        due: &DateTime<Local>,
+        result: &Result<TaskResult>,
+    ) -> Result<()> {
+        let doc = task_json(result);
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO schedule_runs (schedule, due, status, answer, error)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                schedule,
+                due.format("%Y-%m-%d %H:%M").to_string(),
+                doc["status"].as_str(),
+                doc["answer"].as_str(),
+                doc["error"].as_str(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The last `limit` runs, of `schedule` or of every schedule, newest
+    /// first.
+    pub fn runs(&self, schedule: Option<&str>, limit: usize) -> Result<Vec<ScheduleRun>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT schedule, due, status, answer, error FROM schedule_runs
+             WHERE ?1 IS NULL OR schedule = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let runs = stmt
+            .query_map(rusqlite::params![schedule, limit as i64], |row| {
+                Ok(ScheduleRun {
+                    schedule: row.get(0)?,
+                    due: row.get(1)?,
+                    status: row.get(2)?,
+                    answer: row.get(3)?,
+                    error: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(runs)
+    }
+}
+
+fn exists(conn: &Connection, id: &str) -> Result<bool> {
+    let found = conn
+        .query_row("SELECT 1 FROM schedules WHERE id = ?1", [id], |_| Ok(()))
+        .optional()?;
+    Ok(found.is_some())
+}
+
+/// Every schedule to run: the stored ones, with the settings' on top.
+pub fn all(settings: &Settings, store: &ScheduleStore) -> Result<Vec<Schedule>> {
+    let mut schedules: Vec<Schedule> = store
+        .list()?
+        .into_iter()
+        .filter(|stored| !settings.schedules.contains_key(&stored.id))
+        .collect();
+    for (id, schedule) in &settings.schedules {
+        schedules.push(Schedule {
+            id: id.clone(),
+            cron: schedule.cron.clone(),
+            task: schedule.task.clone(),
+            notify: schedule.notify.clone(),
+        });
+    }
+    Ok(schedules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::ScheduleSettings;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn finds_the_next_run() {
+        let schedule = Schedule {
+            id: "1".to_string(),
+            cron: "0 9 * * 1-5".to_string(),
+            task: "summarize yesterday's logs".to_string(),
+            notify: None,
+        };
+        // A Friday, after nine: next is Monday at nine
+        let next = schedule.next_after(&at(2026, 10, 16, 9, 0)).unwrap();
+        assert_eq!(next, at(2026, 10, 19, 9, 0));
+        assert!(parse_cron("every day").is_err());
+    }
+
+    #[test]
+    fn stores_schedules_and_their_runs() {
+        let store = ScheduleStore::open(":memory:").unwrap();
+        assert_eq!(store.add(None, "0 9 * * *", "logs", None).unwrap(), "1");
+        assert_eq!(
+            store
+                .add(Some("disk"), "*/30 * * * *", "disk", Some("https://x"))
+                .unwrap(),
+            "disk"
+        );
+        assert_eq!(store.add(None, "0 * * * *", "hourly", None).unwrap(), "2");
+        assert!(store.add(Some("disk"), "0 * * * *", "again", None).is_err());
+        assert!(store.add(None, "61 * * * *", "bad", None).is_err());
+        assert!(store.remove("2").unwrap());
+        assert!(!store.remove("2").unwrap());
+        let ids: Vec<_> = store.list().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, ["1", "disk"]);
+
+        let done = Ok(TaskResult {
+            answer: "all quiet".to_string(),
+            iterations: 1,
+            tool_calls: 0,
+            usage: Default::default(),
+            duration: Duration::from_secs(1),
+            status: crate::engine::TaskStatus::Completed,
+        });
+        store.record("1", &at(2026, 10, 16, 9, 0), &done).unwrap();
+        store
+            .record("disk", &at(2026, 10, 16, 9, 30), &Err(anyhow!("boom")))
+            .unwrap();
+        let runs = store.runs(None, 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].error.as_deref(), Some("boom"));
+        let runs = store.runs(Some("1"), 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].due, "2026-10-16 09:00");
+        assert_eq!(runs[0].status, "completed");
+        assert_eq!(runs[0].answer.as_deref(), Some("all quiet"));
+    }
+
+    #[test]
+    fn configured_schedules_win() {
+        let store = ScheduleStore::open(":memory:").unwrap();
+        store
+            .add(Some("logs"), "0 9 * * *", "stored", None)
+            .unwrap();
+        store.add(Some("disk"), "0 * * * *", "disk", None).unwrap();
+        let settings = Settings {
+            schedules: BTreeMap::from([(
+                "logs".to_string(),
+                ScheduleSettings {
+                    cron: "0 8 * * *".to_string(),
+                    task: "configured".to_string(),
+                    notify: None,
+                },
+            )]),
+            ..Settings::default()
+        };
+        let schedules = all(&settings, &store).unwrap();
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0].id, "disk");
+        assert_eq!(schedules[1].task, "configured");
+    }
+}
@@ -0,0 +1,244 @@
+//! Just enough HTTP/1.1 for `golem serve`: one request per connection,
+//! bodies by `Content-Length`, and the connection closed after the
+//! response, or after the last of a stream of Server-Sent Events.
+
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::sync::watch;
+
+/// Requests with a larger body are refused.
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Headers beyond this many are refused.
+const MAX_HEADERS: usize = 100;
+
+/// A request line or header longer than this is refused.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// A parsed request.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// The path, without the query string.
+    pub path: String,
+    /// Header names lowercased.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Read one request from `stream`.
+pub async fn read_request(stream: impl AsyncRead + Unpin) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let line = read_line(&mut reader, "request line").await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        bail!("malformed request line");
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut headers = HashMap::new();
+    for count in 0.. {
+        let line = read_line(&mut reader, "header").await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        // Counted by line, as repeated names don't add entries
+        if count == MAX_HEADERS {
+            bail!("too many headers");
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = match headers.get("content-length") {
+        Some(length) => length.parse().context("invalid Content-Length")?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        bail!("body over {MAX_BODY_BYTES} bytes");
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Read one line of at most [`MAX_LINE_BYTES`], never buffering more.
+async fn read_line(reader: &mut (impl AsyncBufRead + Unpin), what: &str) -> Result<String> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_line(&mut line)
+        .await?;
+    if line.len() > MAX_LINE_BYTES {
+        bail!("{what} over {MAX_LINE_BYTES} bytes");
+    }
+    Ok(line)
+}
+
+/// Write a response with a JSON `body` and close the connection.
+pub async fn respond(
+    mut stream: impl AsyncWrite + Unpin,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<()> {
+    respond_with(&mut stream, status, "application/json", &body.to_string()).await
+}
+
+/// Write a response with `body` of `content_type` and close the
+/// connection.
+pub async fn respond_with(
+    mut stream: impl AsyncWrite + Unpin,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Answer with Server-Sent Events: every frame `frames` holds, then each
+/// one added, until its sender is dropped; then close the connection. A
+/// client that hangs up early is no error.
+pub async fn stream_events(
+    mut stream: impl AsyncWrite + Unpin,
+    mut frames: watch::Receiver<Vec<String>>,
+) -> Result<()> {
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                Cache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return Ok(());
+    }
+    let mut sent = 0;
+    loop {
+        let pending = {
+            let frames = frames.borrow_and_update();
+            let pending = frames[sent..].concat();
+            sent = frames.len();
+            pending
+        };
+        if stream.write_all(pending.as_bytes()).await.is_err() || stream.flush().await.is_err() {
+            return Ok(());
+        }
+        if frames.changed().await.is_err() {
+            // The sender is gone; the frames it added last may be unsent
+            let rest = frames.borrow()[sent..].concat();
+            let _ = stream.write_all(rest.as_bytes()).await;
+            break;
+        }
+    }
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_a_request_with_a_body() {
+        let raw = b"POST /webhooks/alerts?x=1 HTTP/1.1\r\nHost: localhost\r\n\
+                    X-Hub-Signature-256: sha256=ab\r\nContent-Length: 7\r\n\r\n{\"a\":1}";
+        let request = read_request(&raw[..]).await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/webhooks/alerts");
+        assert_eq!(request.header("x-hub-signature-256"), Some("sha256=ab"));
+        assert_eq!(request.body, b"{\"a\":1}");
+    }
+
+    #[tokio::test]
+    async fn refuses_huge_bodies() {
+        let raw = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_BYTES + 1
+        );
+        let err = read_request(raw.as_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("bytes"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn refuses_long_lines_and_too_many_headers() {
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_BYTES));
+        let err = read_request(long.as_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("request line"), "{err}");
+
+        let header = format!(
+            "GET / HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_BYTES)
+        );
+        let err = read_request(header.as_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("header over"), "{err}");
+
+        let repeated = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X: 1\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let err = read_request(repeated.as_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("too many headers"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn streams_events_until_the_sender_is_dropped() {
+        let (tx, rx) = watch::channel(vec!["data: 1\n\n".to_string()]);
+        let writer = tokio::spawn(async move {
+            let mut out = Vec::new();
+            stream_events(&mut out, rx).await.unwrap();
+            String::from_utf8(out).unwrap()
+        });
+        tx.send_modify(|frames| frames.push("data: 2\n\n".to_string()));
+        drop(tx);
+        let out = writer.await.unwrap();
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"), "{out}");
+        assert!(out.contains("text/event-stream"), "{out}");
+        assert!(out.ends_with("\r\n\r\ndata: 1\n\ndata: 2\n\n"), "{out}");
+    }
+
+    #[tokio::test]
+    async fn writes_a_json_response() {
+        let mut out = Vec::new();
+        respond(&mut out, 202, &serde_json::json!({"ok": true}))
+            .await
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 202 Accepted\r\n"), "{out}");
+        assert!(out.ends_with("\r\n\r\n{\"ok\":true}"), "{out}");
+    }
+}
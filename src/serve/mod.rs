@@ -0,0 +1,406 @@
+//! `golem serve`: an HTTP server that runs tasks for webhooks, and on
+//! schedules.
+//!
+//! Each `[webhooks.<name>]` table in the settings answers
+//! `POST /webhooks/<name>`. A request signed with the webhook's secret is
+//! accepted with `202`, its body rendered into the webhook's task, and the
+//! task queued; a replayed one gets `409`, and one that finds the queue
+//! full `503`. Each schedule ([`crate::schedule`]) queues its task when
+//! it is due. Tasks run one at a time, each on a fresh engine with no
+//! history. The `202` carries the task's id, and `GET /tasks/<id>/events`
+//! follows the task live as Server-Sent Events ([`streams`]). When a task
+//! ends its result is posted to the webhook's or schedule's `notify` URL,
+//! if it has one, and a schedule's run is recorded. Nobody is around to
+//! confirm commands, so calls that need confirmation are refused.
+//! `GET /metrics` answers with Prometheus metrics
+//! ([`crate::telemetry::prometheus`]).
+
+pub mod http;
+pub mod streams;
+pub mod webhooks;
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::banner::{format_task_result, one_line, task_json};
+use crate::builder::GolemBuilder;
+use crate::config::settings::Settings;
+use crate::consts::DEFAULT_LISTEN;
+use crate::engine::Engine;
+use crate::engine::react::ReactEngine;
+use crate::events::EventBus;
+use crate::memory::in_memory::InMemoryMemory;
+use crate::schedule::{self, Schedule, ScheduleStore};
+use crate::telemetry::prometheus::{self, Prometheus};
+use crate::thinker::http::shared_client;
+use crate::tools::Approver;
+use streams::{Streams, TASK_KINDS};
+use tokio::sync::watch;
+use webhooks::{DELIVERY_HEADER, SIGNATURE_HEADER, Webhook};
+
+/// A client that hasn't sent its whole request by then is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tasks are logged up to this many characters.
+const LOG_CHARS: usize = 100;
+
+/// Tasks waiting to run at most; webhooks beyond it are turned away.
+const QUEUE_CAPACITY: usize = 100;
+
+/// A task waiting for its turn.
+struct Job {
+    /// Its id in [`Streams`].
+    id: String,
+    source: Source,
+    task: String,
+    /// Where its events go as it runs.
+    frames: watch::Sender<Vec<String>>,
+}
+
+/// What queued a task.
+enum Source {
+    Webhook(Arc<Webhook>),
+    /// A schedule, and when the run was due.
+    Schedule(Arc<Schedule>, DateTime<Local>),
+}
+
+impl Source {
+    fn label(&self) -> String {
+        match self {
+            Source::Webhook(webhook) => format!("webhook {}", webhook.name),
+            Source::Schedule(schedule, _) => format!("schedule {}", schedule.id),
+        }
+    }
+}
+
+/// Refuses every call that needs confirmation, saying how to allow it.
+struct Unattended;
+
+#[async_trait]
+impl Approver for Unattended {
+    async fn approve(&self, tool: &str, _args: &HashMap<String, String>) -> Result<bool> {
+        bail!(
+            "{tool} needs confirmation, and nobody can confirm in golem serve; set `confirm = false` to allow it"
+        )
+    }
+}
+
+/// Serve until the listener fails.
+pub async fn run(settings: Settings) -> Result<()> {
+    let webhooks: HashMap<String, Arc<Webhook>> = settings
+        .webhooks
+        .iter()
+        .map(|(name, webhook)| Ok((name.clone(), Arc::new(Webhook::new(name, webhook)?))))
+        .collect::<Result<_>>()?;
+    let golem = GolemBuilder::new(settings.clone())
+        .echo(false)
+        .memory(Box::new(InMemoryMemory::new()))
+        .approver(Box::new(Unattended))
+        .build()
+        .await?;
+    let store = Arc::new(ScheduleStore::open(&golem.db_path)?);
+    let schedules = schedule::all(&settings, &store)?;
+    if webhooks.is_empty() && schedules.is_empty() {
+        bail!(
+            "nothing to serve; add a [webhooks.<name>] or [schedules.<id>] table to the config, or run `golem schedule add`"
+        );
+    }
+    let addr = settings.listen.as_deref().unwrap_or(DEFAULT_LISTEN);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to listen on {addr}"))?;
+
+    let (jobs, queue) = mpsc::channel(QUEUE_CAPACITY);
+    let (make_engine, config) = (golem.engine_factory(), golem.config.clone());
+    let events = Arc::clone(&golem.events);
+    tokio::spawn(work(move || make_engine(&config), events, store, queue));
+    let streams = Arc::new(Streams::default());
+
+    println!("listening on http://{addr}");
+    println!("  GET /metrics");
+    println!("  GET /tasks/<id>/events");
+    for name in webhooks.keys() {
+        println!("  POST /webhooks/{name}");
+    }
+    for schedule in schedules {
+        println!(
+            "  {} {}: {}",
+            schedule.cron,
+            schedule.id,
+            one_line(&schedule.task, LOG_CHARS)
+        );
+        tokio::spawn(run_schedule(
+            Arc::new(schedule),
+            jobs.clone(),
+            Arc::clone(&streams),
+        ));
+    }
+    let webhooks = Arc::new(webhooks);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let (webhooks, jobs) = (Arc::clone(&webhooks), jobs.clone());
+        let (prometheus, streams) = (Arc::clone(&golem.prometheus), Arc::clone(&streams));
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &webhooks, &jobs, &streams, &prometheus).await {
+                eprintln!("warning: request failed: {e:#}");
+            }
+        });
+    }
+}
+
+/// Answer one connection's request.
+async fn handle(
+    mut stream: TcpStream,
+    webhooks: &HashMap<String, Arc<Webhook>>,
+    jobs: &mpsc::Sender<Job>,
+    streams: &Streams,
+    metrics: &Prometheus,
+) -> Result<()> {
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, http::read_request(&mut stream)).await
+    {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => return http::respond(stream, 400, &json!({ "error": e.to_string() })).await,
+        Err(_) => return Ok(()),
+    };
+    if request.path == "/metrics" {
+        if request.method != "GET" {
+            return http::respond(stream, 405, &json!({ "error": "use GET" })).await;
+        }
+        return http::respond_with(stream, 200, prometheus::CONTENT_TYPE, &metrics.render()).await;
+    }
+    if let Some(id) = request
+        .path
+        .strip_prefix("/tasks/")
+        .and_then(|rest| rest.strip_suffix("/events"))
+    {
+        if request.method != "GET" {
+            return http::respond(stream, 405, &json!({ "error": "use GET" })).await;
+        }
+        return match streams.get(id) {
+            Some(frames) => http::stream_events(stream, frames).await,
+            None => http::respond(stream, 404, &json!({ "error": "no such task" })).await,
+        };
+    }
+    let Some(webhook) = request
+        .path
+        .strip_prefix("/webhooks/")
+        .and_then(|name| webhooks.get(name))
+    else {
+        return http::respond(stream, 404, &json!({ "error": "not found" })).await;
+    };
+    if request.method != "POST" {
+        return http::respond(stream, 405, &json!({ "error": "use POST" })).await;
+    }
+    let signature = request.header(SIGNATURE_HEADER).unwrap_or_default();
+    if !webhook.verify(&request.body, Some(signature)) {
+        return http::respond(stream, 401, &json!({ "error": "bad signature" })).await;
+    }
+    let delivery = request.header(DELIVERY_HEADER);
+    if !webhook.first_delivery(delivery, signature) {
+        return http::respond(stream, 409, &json!({ "error": "already delivered" })).await;
+    }
+    let permit = match jobs.try_reserve() {
+        Ok(permit) => permit,
+        Err(e) => {
+            // Turned away, so a retry of it should still run
+            webhook.forget_delivery(delivery, signature);
+            return match e {
+                mpsc::error::TrySendError::Full(()) => {
+                    let error = json!({ "error": "the task queue is full" });
+                    http::respond(stream, 503, &error).await
+                }
+                mpsc::error::TrySendError::Closed(()) => bail!("the task queue has stopped"),
+            };
+        }
+    };
+    let (id, frames) = streams.open();
+    let job = Job {
+        id: id.clone(),
+        source: Source::Webhook(Arc::clone(webhook)),
+        task: webhooks::render(&webhook.task, &request.body),
+        frames,
+    };
+    log_job(&job);
+    permit.send(job);
+    let events = format!("/tasks/{id}/events");
+    let body = json!({ "queued": true, "id": id, "events": events });
+    http::respond(stream, 202, &body).await
+}
+
+/// Queue `schedule`'s task every time it is due. A run missed while
+/// golem wasn't serving isn't made up.
+async fn run_schedule(schedule: Arc<Schedule>, jobs: mpsc::Sender<Job>, streams: Arc<Streams>) {
+    let mut last = Local::now();
+    loop {
+        let due = match schedule.next_after(&last) {
+            Ok(due) => due,
+            Err(e) => {
+                eprintln!("warning: {e:#}");
+                return;
+            }
+        };
+        let wait = (due - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        // Waits for room rather than skipping the run
+        let Ok(permit) = jobs.reserve().await else {
+            return;
+        };
+        let (id, frames) = streams.open();
+        let job = Job {
+            id,
+            source: Source::Schedule(Arc::clone(&schedule), due),
+            task: schedule.task.clone(),
+            frames,
+        };
+        log_job(&job);
+        permit.send(job);
+        last = due;
+    }
+}
+
+fn log_job(job: &Job) {
+    println!(
+        "▶ {} ({}): {}",
+        job.source.label(),
+        job.id,
+        one_line(&job.task, LOG_CHARS)
+    );
+}
+
+/// Run queued tasks one after another, recording and posting each result.
+/// Each task's events go to its stream, and on to `events`.
+async fn work(
+    engine: impl Fn() -> ReactEngine + Send + 'static,
+    events: Arc<EventBus>,
+    store: Arc<ScheduleStore>,
+    mut queue: mpsc::Receiver<Job>,
+) {
+    let client = shared_client();
+    while let Some(job) = queue.recv().await {
+        let label = job.source.label();
+        let mut engine = engine();
+        let task_events = Arc::new(EventBus::relaying_to(Arc::clone(&events), &[]));
+        engine.set_events(Arc::clone(&task_events));
+        tokio::spawn(streams::feed(
+            task_events.subscribe_filtered(TASK_KINDS),
+            job.frames,
+        ));
+        let result = engine.run(&job.task).await;
+        // Ends the stream if the task never got to finish
+        drop((engine, task_events));
+        match &result {
+            Ok(done) => println!("✓ {label}:{}", format_task_result(done)),
+            Err(e) => println!("✗ {label}: {e:#}"),
+        }
+        let mut body = task_json(&result);
+        body["task"] = json!(job.task);
+        let posted = match &job.source {
+            Source::Webhook(webhook) => {
+                body["webhook"] = json!(webhook.name);
+                let body = body.to_string();
+                let signature = webhook.sign(body.as_bytes());
+                match &webhook.notify {
+                    Some(url) => post_result(&client, url, body, Some(signature)).await,
+                    None => Ok(()),
+                }
+            }
+            Source::Schedule(schedule, due) => {
+                if let Err(e) = store.record(&schedule.id, due, &result) {
+                    eprintln!("warning: {label}: failed to record the run: {e:#}");
+                }
+                body["schedule"] = json!(schedule.id);
+                match &schedule.notify {
+                    Some(url) => post_result(&client, url, body.to_string(), None).await,
+                    None => Ok(()),
+                }
+            }
+        };
+        if let Err(e) = posted {
+            eprintln!("warning: {label}: {e:#}");
+        }
+    }
+}
+
+/// POST a task's result to `url`, with a webhook signature if given.
+async fn post_result(
+    client: &reqwest::Client,
+    url: &str,
+    body: String,
+    signature: Option<String>,
+) -> Result<()> {
+    let mut request = client.post(url).header("Content-Type", "application/json");
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    let resp = request
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("POST {url} failed"))?;
+    if !resp.status().is_success() {
+        bail!("POST {url} failed ({})", resp.status());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::WebhookSettings;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// POST `body` to `webhook` through [`handle`] as delivery `delivery`,
+    /// returning the response's status.
+    async fn deliver(
+        webhooks: &HashMap<String, Arc<Webhook>>,
+        jobs: &mpsc::Sender<Job>,
+        streams: &Streams,
+        delivery: &str,
+        body: &str,
+    ) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let signature = webhooks["alerts"].sign(body.as_bytes());
+        let request = format!(
+            "POST /webhooks/alerts HTTP/1.1\r\n{SIGNATURE_HEADER}: {signature}\r\n\
+             {DELIVERY_HEADER}: {delivery}\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        handle(server, webhooks, jobs, streams, &Prometheus::default())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response[9..12].parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_delivery_turned_away_by_a_full_queue_can_be_retried() {
+        let settings = WebhookSettings {
+            task: "triage {{payload}}".to_string(),
+            secret: Some("s3cret".to_string()),
+            ..WebhookSettings::default()
+        };
+        let webhook = Arc::new(Webhook::new("alerts", &settings).unwrap());
+        let webhooks = HashMap::from([("alerts".to_string(), webhook)]);
+        let (jobs, mut queue) = mpsc::channel(1);
+        let streams = Streams::default();
+
+        assert_eq!(deliver(&webhooks, &jobs, &streams, "1", "{}").await, 202);
+        assert_eq!(deliver(&webhooks, &jobs, &streams, "2", "{}").await, 503);
+        queue.recv().await.unwrap();
+        assert_eq!(deliver(&webhooks, &jobs, &streams, "2", "{}").await, 202);
+        assert_eq!(deliver(&webhooks, &jobs, &streams, "2", "{}").await, 409);
+    }
+}
@@ -0,0 +1,161 @@
+//! Live events of the tasks `golem serve` queues, for
+//! `GET /tasks/<id>/events`.
+//!
+//! Each queued task gets a random id and a stream. While the task runs,
+//! its engine's lifecycle events — iterations, thoughts, tool calls,
+//! token usage, the finish — are appended to the stream as Server-Sent
+//! Events frames, named and shaped like the event log's. A client gets
+//! every frame from the start, whenever it connects, then follows along
+//! until the task finishes. Streams of queued and running tasks are
+//! always kept, and of finished tasks the last [`MAX_STREAMS`] in all.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+
+use crate::events::log::describe;
+use crate::events::{Event, EventKind};
+
+/// Streams kept at most, unless more tasks than that are unfinished; the
+/// oldest finished task's goes first.
+const MAX_STREAMS: usize = 100;
+
+/// The events a task's stream carries.
+pub const TASK_KINDS: &[EventKind] = &[
+    EventKind::Phase,
+    EventKind::Progress,
+    EventKind::TaskStarted,
+    EventKind::IterationStarted,
+    EventKind::ThoughtProduced,
+    EventKind::ToolCallStarted,
+    EventKind::ToolCallFinished,
+    EventKind::TokenUsageRecorded,
+    EventKind::TaskFinished,
+];
+
+/// A task's frames so far. Dropping the sender ends the stream.
+pub type Frames = watch::Receiver<Vec<String>>;
+
+/// The streams of recent tasks, by id.
+#[derive(Default)]
+pub struct Streams {
+    streams: Mutex<VecDeque<(String, Frames)>>,
+}
+
+impl Streams {
+    /// A new task's id, and the sender its frames go to.
+    pub fn open(&self) -> (String, watch::Sender<Vec<String>>) {
+        // Random, as the id is all it takes to read the task's events
+        let id = format!("{:032x}", rand::random::<u128>());
+        let (tx, rx) = watch::channel(Vec::new());
+        let mut streams = self.streams.lock().expect("streams lock poisoned");
+        if streams.len() >= MAX_STREAMS {
+            // A stream whose sender is gone has all its frames
+            let finished = streams
+                .iter()
+                .position(|(_, frames)| frames.has_changed().is_err());
+            if let Some(finished) = finished {
+                streams.remove(finished);
+            }
+        }
+        streams.push_back((id.clone(), rx));
+        (id, tx)
+    }
+
+    /// The stream of task `id`, if it is still kept.
+    pub fn get(&self, id: &str) -> Option<Frames> {
+        let streams = self.streams.lock().expect("streams lock poisoned");
+        streams
+            .iter()
+            .find(|(stream, _)| stream == id)
+            .map(|(_, frames)| frames.clone())
+    }
+}
+
+/// Append each event `events` delivers to `frames`, until the task
+/// finishes or its bus is gone.
+pub async fn feed(mut events: broadcast::Receiver<Event>, frames: watch::Sender<Vec<String>>) {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                let finished = event.kind() == EventKind::TaskFinished;
+                frames.send_modify(|frames| {
+                    let frame = frame(frames.len(), &event);
+                    frames.push(frame);
+                });
+                if finished {
+                    return;
+                }
+            }
+            Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// `event` as the `id`th Server-Sent Events frame.
+fn frame(id: usize, event: &Event) -> String {
+    let (kind, data) = describe(event);
+    format!("id: {id}\nevent: {kind}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBus, TaskError};
+
+    #[tokio::test]
+    async fn frames_follow_the_task_until_it_finishes() {
+        let streams = Streams::default();
+        let (id, tx) = streams.open();
+        let bus = EventBus::default();
+        let feeding = tokio::spawn(feed(bus.subscribe_filtered(TASK_KINDS), tx));
+        bus.emit(Event::IterationStarted { iteration: 1 });
+        bus.emit(Event::ModelChanged {
+            model: "m".to_string(),
+        });
+        bus.emit(Event::TaskFinished {
+            task: "t".to_string(),
+            result: Err(TaskError {
+                message: "no".to_string(),
+                interrupted: false,
+            }),
+        });
+        feeding.await.unwrap();
+
+        // Connecting afterwards still gets everything
+        let mut frames = streams.get(&id).unwrap();
+        let frames: Vec<String> = frames.borrow_and_update().clone();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            frames[0],
+            "id: 0\nevent: iteration_started\ndata: {\"iteration\":1}\n\n"
+        );
+        assert!(frames[1].starts_with("id: 1\nevent: task_finished\n"));
+        assert!(streams.get("nope").is_none());
+    }
+
+    #[test]
+    fn keeps_only_recent_streams() {
+        let streams = Streams::default();
+        let (first, _) = streams.open();
+        for _ in 0..MAX_STREAMS {
+            streams.open();
+        }
+        assert!(streams.get(&first).is_none());
+    }
+
+    #[test]
+    fn keeps_the_streams_of_unfinished_tasks() {
+        let streams = Streams::default();
+        let (running, _frames) = streams.open();
+        let (finished, _) = streams.open();
+        for _ in 0..MAX_STREAMS {
+            streams.open();
+        }
+        // The oldest finished tasks went instead
+        assert!(streams.get(&running).is_some());
+        assert!(streams.get(&finished).is_none());
+    }
+}
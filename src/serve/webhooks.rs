@@ -0,0 +1,286 @@
+//! Inbound webhooks: checking a request's signature, turning its body into
+//! a task, and posting the result on.
+//!
+//! Requests are signed the way GitHub signs them: `X-Hub-Signature-256`
+//! holds `sha256=` and the hex HMAC-SHA256 of the body under the webhook's
+//! secret. Results posted to `notify` are signed the same way.
+//!
+//! A signature only proves who sent a body, not when, so each webhook
+//! remembers what it accepted for a day: a request with the same
+//! `X-GitHub-Delivery` id is a replay, and so is one without an id that
+//! repeats a signed body.
+
+use anyhow::{Context, Result, bail};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::settings::WebhookSettings;
+
+/// The header requests are signed in, lowercased.
+pub const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+
+/// The header GitHub puts a unique id for each delivery in, lowercased.
+pub const DELIVERY_HEADER: &str = "x-github-delivery";
+
+/// How long an accepted delivery is remembered.
+const REPLAY_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Deliveries remembered at most; the oldest are forgotten first.
+const MAX_REMEMBERED: usize = 10_000;
+
+/// What a delivery is remembered by: the id the sender gave it, or else
+/// what signs its body.
+fn delivery_key(delivery: Option<&str>, signature: &str) -> String {
+    match delivery {
+        Some(id) => format!("delivery:{id}"),
+        None => format!("signature:{signature}"),
+    }
+}
+
+/// A configured webhook, its secret resolved.
+pub struct Webhook {
+    pub name: String,
+    /// The task template.
+    pub task: String,
+    secret: Vec<u8>,
+    pub notify: Option<String>,
+    /// Delivery ids and signatures accepted within [`REPLAY_WINDOW`].
+    seen: Mutex<Seen>,
+}
+
+/// Keys of accepted deliveries, oldest first, and when each was accepted.
+#[derive(Default)]
+struct Seen {
+    order: VecDeque<(Instant, String)>,
+    keys: HashMap<String, Instant>,
+}
+
+impl Seen {
+    fn forget_old(&mut self, now: Instant) {
+        while let Some((at, key)) = self.order.front() {
+            let expired = now.duration_since(*at) > REPLAY_WINDOW;
+            if !expired && self.order.len() <= MAX_REMEMBERED {
+                break;
+            }
+            // A key accepted again later has a newer entry of its own
+            if self.keys.get(key) == Some(at) {
+                self.keys.remove(key);
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+impl Webhook {
+    /// The webhook `[webhooks.<name>]` describes, reading its secret from
+    /// the environment if it names a variable.
+    pub fn new(name: &str, settings: &WebhookSettings) -> Result<Self> {
+        let secret = match (&settings.secret, &settings.secret_env) {
+            (Some(secret), _) => secret.clone(),
+            (None, Some(var)) => std::env::var(var)
+                .with_context(|| format!("webhook {name:?}: ${var} is not set"))?,
+            (None, None) => bail!("webhook {name:?} needs one of secret or secret_env"),
+        };
+        if secret.is_empty() {
+            bail!("webhook {name:?} has an empty secret");
+        }
+        Ok(Self {
+            name: name.to_string(),
+            task: settings.task.clone(),
+            secret: secret.into_bytes(),
+            notify: settings.notify.clone(),
+            seen: Mutex::default(),
+        })
+    }
+
+    /// Record a verified request by its delivery id, or its signature if
+    /// it has none; false if that was accepted before, making this a
+    /// replay.
+    pub fn first_delivery(&self, delivery: Option<&str>, signature: &str) -> bool {
+        let key = delivery_key(delivery, signature);
+        let now = Instant::now();
+        let mut seen = self.seen.lock().expect("webhook lock poisoned");
+        seen.forget_old(now);
+        if seen.keys.contains_key(&key) {
+            return false;
+        }
+        seen.keys.insert(key.clone(), now);
+        seen.order.push_back((now, key));
+        true
+    }
+
+    /// Forget a delivery [`first_delivery`](Self::first_delivery)
+    /// recorded, so a retry of it is accepted.
+    pub fn forget_delivery(&self, delivery: Option<&str>, signature: &str) {
+        let key = delivery_key(delivery, signature);
+        let mut seen = self.seen.lock().expect("webhook lock poisoned");
+        // Its entry in `order` no longer matches, so is dropped in turn
+        seen.keys.remove(&key);
+    }
+
+    /// Whether `signature` (the header's value) signs `body`.
+    pub fn verify(&self, body: &[u8], signature: Option<&str>) -> bool {
+        let Some(bytes) = signature
+            .and_then(|s| s.strip_prefix("sha256="))
+            .and_then(from_hex)
+        else {
+            return false;
+        };
+        // Compares in constant time
+        self.mac(body).verify_slice(&bytes).is_ok()
+    }
+
+    /// The header value that signs `body`.
+    pub fn sign(&self, body: &[u8]) -> String {
+        let digest = self.mac(body).finalize().into_bytes();
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        format!("sha256={hex}")
+    }
+
+    fn mac(&self, body: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC takes any key");
+        mac.update(body);
+        mac
+    }
+}
+
+/// `template` with `{{payload}}` replaced by the body (pretty-printed if
+/// it is JSON) and `{{payload.a.b}}` by the value at that path in it.
+/// Paths that lead nowhere become empty; other placeholders stay as they
+/// are.
+pub fn render(template: &str, body: &[u8]) -> String {
+    let raw = String::from_utf8_lossy(body);
+    let json: Option<Value> = serde_json::from_slice(body).ok();
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let name = rest[2..end].trim();
+        match placeholder(name, &raw, json.as_ref()) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// What `{{name}}` becomes, or `None` if it isn't about the payload.
+fn placeholder(name: &str, raw: &str, json: Option<&Value>) -> Option<String> {
+    if name == "payload" {
+        return Some(match json {
+            Some(json) => serde_json::to_string_pretty(json).expect("JSON always serializes"),
+            None => raw.to_string(),
+        });
+    }
+    let path = name.strip_prefix("payload.")?;
+    let Some(json) = json else {
+        return Some(String::new());
+    };
+    let value = path.split('.').try_fold(json, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    });
+    Some(match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    })
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook() -> Webhook {
+        let settings = WebhookSettings {
+            task: "triage this alert: {{payload}}".to_string(),
+            secret: Some("It's a Secret to Everybody".to_string()),
+            ..WebhookSettings::default()
+        };
+        Webhook::new("alerts", &settings).unwrap()
+    }
+
+    #[test]
+    fn checks_signatures() {
+        let webhook = webhook();
+        // The example from GitHub's webhook documentation
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert_eq!(webhook.sign(b"Hello, World!"), signature);
+        assert!(webhook.verify(b"Hello, World!", Some(signature)));
+        assert!(!webhook.verify(b"Hello, World?", Some(signature)));
+        assert!(!webhook.verify(b"Hello, World!", Some("sha256=zz")));
+        assert!(!webhook.verify(b"Hello, World!", None));
+    }
+
+    #[test]
+    fn refuses_replayed_deliveries() {
+        let webhook = webhook();
+        let (a, b) = (webhook.sign(b"a"), webhook.sign(b"b"));
+        assert!(webhook.first_delivery(Some("1"), &a));
+        // A new body reusing a delivery id
+        assert!(!webhook.first_delivery(Some("1"), &b));
+        // The same body is a new event under a new delivery id
+        assert!(webhook.first_delivery(Some("2"), &a));
+        // Without ids, bodies are told apart by their signatures
+        assert!(webhook.first_delivery(None, &a));
+        assert!(!webhook.first_delivery(None, &a));
+        assert!(webhook.first_delivery(None, &b));
+    }
+
+    #[test]
+    fn forgotten_deliveries_are_accepted_again() {
+        let webhook = webhook();
+        let a = webhook.sign(b"a");
+        assert!(webhook.first_delivery(Some("1"), &a));
+        webhook.forget_delivery(Some("1"), &a);
+        assert!(webhook.first_delivery(Some("1"), &a));
+        assert!(!webhook.first_delivery(Some("1"), &a));
+    }
+
+    #[test]
+    fn secrets_come_from_the_environment() {
+        let settings = WebhookSettings {
+            task: "x".to_string(),
+            secret_env: Some("GOLEM_TEST_UNSET_WEBHOOK_SECRET".to_string()),
+            ..WebhookSettings::default()
+        };
+        let err = Webhook::new("a", &settings).err().unwrap();
+        assert!(err.to_string().contains("is not set"), "{err}");
+    }
+
+    #[test]
+    fn renders_payloads_into_tasks() {
+        let body = br#"{"alert":{"name":"disk full","hosts":["web1"]},"count":3}"#;
+        assert_eq!(
+            render(
+                "check {{ payload.alert.name }} on {{payload.alert.hosts.0}} ({{payload.count}}){{payload.nope}}",
+                body
+            ),
+            "check disk full on web1 (3)"
+        );
+        assert!(render("triage: {{payload}}", body).contains("\n  \"alert\": {"));
+        assert_eq!(render("log: {{payload}}", b"plain text"), "log: plain text");
+        assert_eq!(render("log: {{payload.a}}", b"plain text"), "log: ");
+        assert_eq!(render("{{other}} {{payload", b"x"), "{{other}} {{payload");
+    }
+}
@@ -0,0 +1,314 @@
+//! The parts of Slack's Web API and Socket Mode the bot uses.
+//!
+//! Socket Mode needs an app-level token (`xapp-…`, with
+//! `connections:write`) to open the WebSocket; posting needs the bot token
+//! (`xoxb-…`, with `app_mentions:read` and `chat:write`).
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::thinker::http::shared_client;
+
+const API_URL: &str = "https://slack.com/api";
+
+/// A Web API client holding both tokens.
+pub struct SlackApi {
+    client: reqwest::Client,
+    app_token: String,
+    bot_token: String,
+}
+
+impl SlackApi {
+    pub fn new(app_token: String, bot_token: String) -> Self {
+        Self {
+            client: shared_client(),
+            app_token,
+            bot_token,
+        }
+    }
+
+    /// A fresh Socket Mode WebSocket URL (`apps.connections.open`).
+    pub async fn connection_url(&self) -> Result<String> {
+        let data = self
+            .call("apps.connections.open", &self.app_token, json!({}))
+            .await?;
+        data["url"]
+            .as_str()
+            .map(str::to_string)
+            .context("apps.connections.open returned no url")
+    }
+
+    /// Post `text` to the thread under `thread_ts` in `channel`, with
+    /// Block Kit `blocks` if given; returns the new message's `ts`.
+    pub async fn post(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        text: &str,
+        blocks: Option<Value>,
+    ) -> Result<String> {
+        let mut body = json!({ "channel": channel, "thread_ts": thread_ts, "text": text });
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks;
+        }
+        let data = self.call("chat.postMessage", &self.bot_token, body).await?;
+        data["ts"]
+            .as_str()
+            .map(str::to_string)
+            .context("chat.postMessage returned no ts")
+    }
+
+    /// Replace the message at `ts` with plain `text`, dropping its blocks.
+    pub async fn update(&self, channel: &str, ts: &str, text: &str) -> Result<()> {
+        let body = json!({ "channel": channel, "ts": ts, "text": text, "blocks": [] });
+        self.call("chat.update", &self.bot_token, body).await?;
+        Ok(())
+    }
+
+    async fn call(&self, method: &str, token: &str, body: Value) -> Result<Value> {
+        let data: Value = self
+            .client
+            .post(format!("{API_URL}/{method}"))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("{method} failed"))?
+            .json()
+            .await
+            .with_context(|| format!("invalid {method} response"))?;
+        if data["ok"].as_bool() != Some(true) {
+            bail!(
+                "{method} failed: {}",
+                data["error"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(data)
+    }
+}
+
+/// A Socket Mode message. Those with an `envelope_id` must be acknowledged.
+#[derive(Debug, Deserialize)]
+pub struct Envelope {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub envelope_id: Option<String>,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+/// What an envelope asks of the bot.
+#[derive(Debug, PartialEq)]
+pub enum Incoming {
+    /// Someone mentioned the bot.
+    Mention(Mention),
+    /// Someone clicked an approval button.
+    Approval(Approval),
+    /// Slack is about to close the connection; open another.
+    Disconnect,
+    /// Anything else (`hello`, other events).
+    Other,
+}
+
+/// An `app_mention` event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Mention {
+    pub channel: String,
+    pub user: String,
+    pub text: String,
+    pub ts: String,
+    /// Set when the mention is a reply in a thread.
+    pub thread_ts: Option<String>,
+}
+
+impl Mention {
+    /// The thread replies go to: the one the mention is in, else a new
+    /// one under it.
+    pub fn thread(&self) -> &str {
+        self.thread_ts.as_deref().unwrap_or(&self.ts)
+    }
+}
+
+/// A click on one of the buttons [`approval_blocks`] made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approval {
+    pub id: String,
+    pub approved: bool,
+    pub user: String,
+    pub channel: String,
+    /// The message holding the buttons.
+    pub message_ts: String,
+}
+
+const APPROVE_ACTION: &str = "golem_approve";
+const DENY_ACTION: &str = "golem_deny";
+
+impl Envelope {
+    pub fn incoming(&self) -> Incoming {
+        match self.kind.as_str() {
+            "disconnect" => Incoming::Disconnect,
+            "events_api" => {
+                let event = &self.payload["event"];
+                if event["type"] != "app_mention" {
+                    return Incoming::Other;
+                }
+                match Mention::deserialize(event) {
+                    Ok(mention) => Incoming::Mention(mention),
+                    Err(_) => Incoming::Other,
+                }
+            }
+            "interactive" => self.approval().map_or(Incoming::Other, Incoming::Approval),
+            _ => Incoming::Other,
+        }
+    }
+
+    fn approval(&self) -> Option<Approval> {
+        let payload = &self.payload;
+        if payload["type"] != "block_actions" {
+            return None;
+        }
+        let action = &payload["actions"][0];
+        let approved = match action["action_id"].as_str()? {
+            APPROVE_ACTION => true,
+            DENY_ACTION => false,
+            _ => return None,
+        };
+        let field = |value: &Value| value.as_str().map(str::to_string);
+        Some(Approval {
+            id: field(&action["value"])?,
+            approved,
+            user: field(&payload["user"]["id"])?,
+            channel: field(&payload["channel"]["id"])?,
+            message_ts: field(&payload["container"]["message_ts"])?,
+        })
+    }
+}
+
+/// The reply that acknowledges an envelope.
+pub fn ack(envelope_id: &str) -> String {
+    json!({ "envelope_id": envelope_id }).to_string()
+}
+
+/// A question with Approve and Deny buttons that answer approval `id`.
+pub fn approval_blocks(id: &str, question: &str) -> Value {
+    let button = |label: &str, style: &str, action: &str| {
+        json!({
+            "type": "button",
+            "text": { "type": "plain_text", "text": label },
+            "style": style,
+            "action_id": action,
+            "value": id,
+        })
+    };
+    json!([
+        { "type": "section", "text": { "type": "mrkdwn", "text": question } },
+        {
+            "type": "actions",
+            "elements": [
+                button("Approve", "primary", APPROVE_ACTION),
+                button("Deny", "danger", DENY_ACTION),
+            ],
+        },
+    ])
+}
+
+/// The task in a mention's text: user mentions dropped, links and
+/// channel references as plain text, and `&amp;`, `&lt;`, `&gt;` decoded.
+pub fn task_text(text: &str) -> String {
+    let mut task = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        task.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(len) = rest.find('>') else {
+            break;
+        };
+        let inner = &rest[1..len];
+        rest = &rest[len + 1..];
+        if inner.starts_with('@') {
+            continue;
+        }
+        // `<url|label>` and `<#C123|general>`: keep the url or the name
+        match (inner.strip_prefix('#'), inner.split_once('|')) {
+            (Some(_), Some((_, name))) => {
+                task.push('#');
+                task.push_str(name);
+            }
+            (_, Some((target, _))) => task.push_str(target),
+            (_, None) => task.push_str(inner),
+        }
+    }
+    task.push_str(rest);
+    task.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(json: &str) -> Envelope {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn reads_mentions() {
+        let envelope = envelope(
+            r#"{"envelope_id":"e1","type":"events_api","payload":{"event":{
+                "type":"app_mention","channel":"C1","user":"U1",
+                "text":"<@UBOT> disk usage?","ts":"1.2"}}}"#,
+        );
+        let Incoming::Mention(mention) = envelope.incoming() else {
+            panic!("expected a mention");
+        };
+        assert_eq!(mention.channel, "C1");
+        assert_eq!(mention.thread(), "1.2");
+        assert_eq!(
+            ack(envelope.envelope_id.as_deref().unwrap()),
+            r#"{"envelope_id":"e1"}"#
+        );
+    }
+
+    #[test]
+    fn reads_approval_clicks() {
+        let envelope = envelope(
+            r#"{"envelope_id":"e2","type":"interactive","payload":{"type":"block_actions",
+                "user":{"id":"U2"},"channel":{"id":"C1"},"container":{"message_ts":"3.4"},
+                "actions":[{"action_id":"golem_deny","value":"a1"}]}}"#,
+        );
+        assert_eq!(
+            envelope.incoming(),
+            Incoming::Approval(Approval {
+                id: "a1".to_string(),
+                approved: false,
+                user: "U2".to_string(),
+                channel: "C1".to_string(),
+                message_ts: "3.4".to_string(),
+            })
+        );
+        assert_eq!(
+            self::envelope(r#"{"type":"disconnect","reason":"refresh_requested"}"#).incoming(),
+            Incoming::Disconnect
+        );
+        assert_eq!(
+            self::envelope(r#"{"type":"hello"}"#).incoming(),
+            Incoming::Other
+        );
+    }
+
+    #[test]
+    fn mention_text_becomes_a_task() {
+        assert_eq!(
+            task_text(
+                "<@UBOT>  check <https://example.com|example.com> in <#C1|ops> &amp; report &lt;now&gt;"
+            ),
+            "check https://example.com in #ops & report <now>"
+        );
+        assert_eq!(task_text("<@UBOT>"), "");
+    }
+}
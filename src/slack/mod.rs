@@ -0,0 +1,254 @@
+//! `golem slack`: a Slack bot over Socket Mode.
+//!
+//! Mentioning the bot in a channel gives it a task. Each channel gets an
+//! engine of its own whose history lives in the named session
+//! `slack-<channel id>`, so later mentions there build on earlier ones;
+//! a channel runs its tasks one at a time, channels run side by side.
+//! Tool calls and the answer are posted as replies in the mention's
+//! thread. With a writable shell, a command that would write asks there
+//! with Approve and Deny buttons, which anyone in the channel can click.
+
+pub mod api;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::banner::format_task_result;
+use crate::chat::{self, Approvals};
+use crate::config::settings::Settings;
+use crate::engine::hooks::Hook;
+use crate::engine::{Engine, TaskResult};
+use crate::thinker::ToolCall;
+use crate::tools::{Approver, ToolResult};
+use api::{Approval, Envelope, Incoming, Mention, SlackApi};
+
+/// How long to wait before opening a new connection after one dropped.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Posted answers are cut to this many characters; Slack refuses
+/// messages much longer.
+const MAX_MESSAGE_CHARS: usize = 39_000;
+
+/// The named session a channel's tasks are kept in.
+pub fn session_name(channel: &str) -> String {
+    format!("slack-{channel}")
+}
+
+/// Run the bot until the connection can't be opened again.
+pub async fn run(settings: Settings, app_token: String, bot_token: String) -> Result<()> {
+    let api = Arc::new(SlackApi::new(app_token, bot_token));
+    let approvals = Arc::new(Approvals::default());
+    let mut channels: HashMap<String, mpsc::UnboundedSender<Mention>> = HashMap::new();
+    // Bad tokens fail the first connection; later ones are retried
+    let mut url = api.connection_url().await?;
+    println!("connected to Slack; mention the bot in a channel to give it a task");
+    loop {
+        if let Err(e) = serve(&url, &settings, &api, &approvals, &mut channels).await {
+            eprintln!("warning: Slack connection dropped: {e:#}");
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+        url = api.connection_url().await?;
+    }
+}
+
+/// Handle envelopes from one Socket Mode connection until Slack closes it.
+async fn serve(
+    url: &str,
+    settings: &Settings,
+    api: &Arc<SlackApi>,
+    approvals: &Arc<Approvals>,
+    channels: &mut HashMap<String, mpsc::UnboundedSender<Mention>>,
+) -> Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+    while let Some(message) = socket.next().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let Ok(envelope) = serde_json::from_str::<Envelope>(&text) else {
+            continue;
+        };
+        if let Some(id) = &envelope.envelope_id {
+            socket.send(Message::text(api::ack(id))).await?;
+        }
+        match envelope.incoming() {
+            Incoming::Mention(mention) => {
+                let channel = mention.channel.clone();
+                let worker = channels.entry(channel.clone()).or_insert_with(|| {
+                    let (tx, rx) = mpsc::unbounded_channel();
+                    let thread = Thread::new(
+                        Arc::clone(api),
+                        Arc::clone(approvals),
+                        channel,
+                        chat::shell_writes(settings),
+                    );
+                    tokio::spawn(serve_channel(settings.clone(), thread, rx));
+                    tx
+                });
+                let _ = worker.send(mention);
+            }
+            Incoming::Approval(approval) => {
+                tokio::spawn(settle(Arc::clone(api), Arc::clone(approvals), approval));
+            }
+            Incoming::Disconnect => break,
+            Incoming::Other => {}
+        }
+    }
+    Ok(())
+}
+
+/// Run a channel's mentions as tasks, one after another.
+async fn serve_channel(
+    settings: Settings,
+    thread: Thread,
+    mut mentions: mpsc::UnboundedReceiver<Mention>,
+) {
+    let mut engine = None;
+    while let Some(mention) = mentions.recv().await {
+        thread.reply_to(&mention);
+        let task = api::task_text(&mention.text);
+        if task.is_empty() {
+            thread
+                .say("Mention me with a task, e.g. `@golem how full is the disk?`")
+                .await;
+            continue;
+        }
+        if engine.is_none() {
+            let session = session_name(&thread.channel);
+            let built = chat::channel_engine(
+                &settings,
+                &session,
+                Box::new(thread.clone()),
+                Arc::new(thread.clone()),
+            )
+            .await;
+            match built {
+                Ok(built) => engine = Some(built),
+                Err(e) => {
+                    thread.say(&format!("✗ {e:#}")).await;
+                    continue;
+                }
+            }
+        }
+        let Some(engine) = engine.as_mut() else {
+            continue;
+        };
+        let result = engine.run(&task).await;
+        thread.say(&answer_text(&result)).await;
+    }
+}
+
+/// A task's answer as a reply, with its stats (which say if it is partial).
+fn answer_text(result: &Result<TaskResult>) -> String {
+    match result {
+        Ok(result) => format!(
+            "{}\n\n_{}_",
+            chat::cut(&result.answer, MAX_MESSAGE_CHARS),
+            format_task_result(result).trim()
+        ),
+        Err(e) => format!("✗ {e:#}"),
+    }
+}
+
+/// Settle a click and replace the buttons with who answered.
+async fn settle(api: Arc<SlackApi>, approvals: Arc<Approvals>, approval: Approval) {
+    let text = if !approvals.settle(&approval.id, approval.approved) {
+        "This request has expired.".to_string()
+    } else if approval.approved {
+        format!("✓ Approved by <@{}>", approval.user)
+    } else {
+        format!("✗ Denied by <@{}>", approval.user)
+    };
+    if let Err(e) = api
+        .update(&approval.channel, &approval.message_ts, &text)
+        .await
+    {
+        eprintln!("warning: failed to update Slack message: {e:#}");
+    }
+}
+
+/// Where a channel's current task replies: the thread of the mention it
+/// came from. It reports the task's tool calls and asks for approvals.
+#[derive(Clone)]
+struct Thread {
+    api: Arc<SlackApi>,
+    approvals: Arc<Approvals>,
+    channel: String,
+    ts: Arc<Mutex<String>>,
+    /// Whether the shell may write, so write commands need approval.
+    shell_writes: bool,
+}
+
+impl Thread {
+    fn new(
+        api: Arc<SlackApi>,
+        approvals: Arc<Approvals>,
+        channel: String,
+        shell_writes: bool,
+    ) -> Self {
+        Self {
+            api,
+            approvals,
+            channel,
+            ts: Arc::default(),
+            shell_writes,
+        }
+    }
+
+    fn reply_to(&self, mention: &Mention) {
+        *self.ts.lock().expect("thread lock poisoned") = mention.thread().to_string();
+    }
+
+    async fn post(&self, text: &str, blocks: Option<serde_json::Value>) -> Result<String> {
+        let ts = self.ts.lock().expect("thread lock poisoned").clone();
+        self.api.post(&self.channel, &ts, text, blocks).await
+    }
+
+    /// Post `text`, warning on stderr if Slack won't take it.
+    async fn say(&self, text: &str) {
+        if let Err(e) = self.post(text, None).await {
+            eprintln!("warning: failed to post to Slack: {e:#}");
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for Thread {
+    async fn on_tool_result(&self, call: &ToolCall, result: &ToolResult) {
+        self.say(&chat::progress_line(call, result)).await;
+    }
+}
+
+#[async_trait]
+impl Approver for Thread {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        if !chat::needs_approval(tool, args, self.shell_writes) {
+            return Ok(true);
+        }
+        let (id, answer) = self.approvals.open();
+        let question = chat::approval_question(tool, args);
+        let blocks = api::approval_blocks(&id, &question);
+        if let Err(e) = self.post(&question, Some(blocks)).await {
+            self.approvals.settle(&id, false);
+            bail!("couldn't ask for approval in Slack: {e:#}");
+        }
+        self.approvals.wait(&id, answer, tool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_get_their_own_session() {
+        assert_eq!(session_name("C0123"), "slack-C0123");
+    }
+}
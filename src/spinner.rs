@@ -1,20 +1,29 @@
 //! A minimal terminal spinner for visual feedback during async operations.
 
 use std::io::Write;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 
+use crate::banner::format_progress;
+use crate::events::{Event, Progress};
+
 /// Braille spinner frames.
 const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 /// Frame interval.
 const INTERVAL: Duration = Duration::from_millis(80);
 
+/// Elapsed time is shown once the spinner has run this long.
+const SHOW_ELAPSED_AFTER: Duration = Duration::from_secs(1);
+
 /// A terminal spinner that runs in a background task.
 ///
 /// Call [`Spinner::start`] to begin, then [`Spinner::stop`] when done.
-/// The spinner writes to stderr so it doesn't interfere with stdout output.
+/// After a second it also shows how long it has been running, so a long
+/// silence doesn't look like a hang. The spinner writes to stderr so it
+/// doesn't interfere with stdout output.
 pub struct Spinner {
     handle: JoinHandle<()>,
     cancel: tokio::sync::watch::Sender<bool>,
@@ -23,20 +32,47 @@ pub struct Spinner {
 impl Spinner {
     /// Start a spinner with the given message (e.g. `"thinking"`).
     pub fn start(message: &str) -> Self {
+        Self::spawn(message, None, None)
+    }
+
+    /// Start a spinner showing `message` and the task's `progress`, which
+    /// the [`Event::Phase`] and [`Event::Progress`] arriving on `events`
+    /// replace.
+    pub fn follow(
+        message: &str,
+        progress: Option<Progress>,
+        events: broadcast::Receiver<Event>,
+    ) -> Self {
+        Self::spawn(message, progress, Some(events))
+    }
+
+    fn spawn(
+        message: &str,
+        mut progress: Option<Progress>,
+        mut events: Option<broadcast::Receiver<Event>>,
+    ) -> Self {
         let (cancel_tx, mut cancel_rx) = tokio::sync::watch::channel(false);
-        let message = message.to_string();
+        let mut message = message.to_string();
+        let started = Instant::now();
 
         let handle = tokio::spawn(async move {
             let mut i = 0;
             loop {
                 let frame = FRAMES[i % FRAMES.len()];
+                let line = status_line(frame, &message, started.elapsed(), progress.as_ref());
                 // \r moves to start of line, \x1b[2K clears the line
-                eprint!("\x1b[2K\r{frame} {message}");
+                eprint!("\x1b[2K\r{}", fit(&line, terminal_width()));
                 let _ = std::io::stderr().flush();
 
                 tokio::select! {
                     _ = tokio::time::sleep(INTERVAL) => {}
                     _ = cancel_rx.changed() => break,
+                    event = next_event(&mut events) => match event {
+                        Ok(Event::Phase { phase }) => message = phase,
+                        Ok(Event::Progress(update)) => progress = Some(update),
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => events = None,
+                    },
                 }
                 i += 1;
             }
@@ -58,9 +94,53 @@ impl Spinner {
     }
 }
 
+/// The next event, or never without a receiver.
+async fn next_event(
+    events: &mut Option<broadcast::Receiver<Event>>,
+) -> Result<Event, broadcast::error::RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// `⠋ thinking... 12s · 3/20 · 12,345 tokens`: the frame, the message,
+/// the time spent once it is worth showing, and the task's counters.
+fn status_line(
+    frame: &str,
+    message: &str,
+    elapsed: Duration,
+    progress: Option<&Progress>,
+) -> String {
+    let mut line = format!("{frame} {message}");
+    if elapsed >= SHOW_ELAPSED_AFTER {
+        line.push_str(&format!(" {}s", elapsed.as_secs()));
+    }
+    if let Some(progress) = progress {
+        line.push_str(&format!(" · {}", format_progress(progress)));
+    }
+    line
+}
+
+/// Columns of the terminal, or a common default when unknown.
+fn terminal_width() -> usize {
+    let (columns, _) = termimad::terminal_size();
+    columns as usize
+}
+
+/// `line` cut to fit in `width` columns; a wrapped line couldn't be
+/// redrawn in place.
+fn fit(line: &str, width: usize) -> &str {
+    match line.char_indices().nth(width.saturating_sub(1)) {
+        Some((i, _)) => &line[..i],
+        None => line,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::EventBus;
 
     #[test]
     fn frames_are_non_empty() {
@@ -84,6 +164,49 @@ mod tests {
         spinner.stop().await;
     }
 
+    #[test]
+    fn elapsed_time_appears_after_a_second() {
+        assert_eq!(
+            status_line("⠋", "thinking...", Duration::from_millis(400), None),
+            "⠋ thinking..."
+        );
+        assert_eq!(
+            status_line("⠋", "thinking...", Duration::from_millis(12_700), None),
+            "⠋ thinking... 12s"
+        );
+    }
+
+    #[test]
+    fn counters_follow_the_message() {
+        let progress = Progress {
+            iteration: 2,
+            max_iterations: 20,
+            tokens: 1500,
+            cost: None,
+        };
+        assert_eq!(
+            status_line("⠋", "thinking...", Duration::ZERO, Some(&progress)),
+            "⠋ thinking... · 2/20 · 1,500 tokens"
+        );
+    }
+
+    #[test]
+    fn long_lines_are_cut_to_the_terminal() {
+        assert_eq!(fit("⠋ running shell: cargo build", 10), "⠋ running");
+        assert_eq!(fit("⠋ thinking...", 80), "⠋ thinking...");
+    }
+
+    #[tokio::test]
+    async fn follows_phase_events() {
+        let bus = EventBus::default();
+        let spinner = Spinner::follow("thinking...", None, bus.subscribe());
+        bus.emit(Event::Phase {
+            phase: "running shell: cargo build".to_string(),
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        spinner.stop().await;
+    }
+
     #[tokio::test]
     async fn spinner_immediate_stop() {
         let spinner = Spinner::start("quick");
@@ -0,0 +1,362 @@
+//! OpenTelemetry traces of tasks, exported over OTLP/HTTP.
+//!
+//! With `[telemetry] endpoint` set, every task is a trace: a `task` span
+//! holding an `iteration` span per step of the ReAct loop, which holds a
+//! `chat <model>` span per model call and an `execute_tool <tool>` span per
+//! tool call. Token counts and durations are attributes, named after the
+//! OpenTelemetry GenAI conventions where there is one. A task's spans are
+//! sent as OTLP JSON to `<endpoint>/v1/traces` when it ends; a collector
+//! that is down only costs the spans, so failures only warn.
+//!
+//! [`prometheus`] counts the same things for `/metrics`.
+
+pub mod prometheus;
+
+use anyhow::{Context as _, Result, bail};
+use serde_json::{Value, json};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::settings::TelemetrySettings;
+use crate::thinker::TokenUsage;
+use crate::thinker::http::shared_client;
+
+/// How long an export may take before it is given up.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Collects finished spans and exports them.
+pub struct Tracer {
+    url: String,
+    headers: Vec<(String, String)>,
+    client: reqwest::Client,
+    finished: Mutex<Vec<Value>>,
+}
+
+impl Tracer {
+    /// A tracer exporting to the collector `settings` names.
+    pub fn new(settings: &TelemetrySettings) -> Result<Self> {
+        let Some(endpoint) = &settings.endpoint else {
+            bail!("no OTLP endpoint configured");
+        };
+        let endpoint = endpoint.trim_end_matches('/');
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            bail!("OTLP endpoint {endpoint:?} must be an http:// or https:// URL");
+        }
+        let url = if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{endpoint}/v1/traces")
+        };
+        Ok(Self {
+            url,
+            headers: settings
+                .headers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            client: shared_client(),
+            finished: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Start a span with no parent, and a trace of its own.
+    pub fn root(self: &Arc<Self>, name: impl Into<String>) -> Span {
+        Span::open(Arc::clone(self), rand::random(), None, name.into())
+    }
+
+    /// Send every span finished so far to the collector.
+    pub async fn export(&self) -> Result<()> {
+        let spans = std::mem::take(&mut *self.finished.lock().expect("tracer lock poisoned"));
+        if spans.is_empty() {
+            return Ok(());
+        }
+        let mut request = self.client.post(&self.url).timeout(EXPORT_TIMEOUT);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let resp = request
+            .json(&encode(spans))
+            .send()
+            .await
+            .with_context(|| format!("failed to export spans to {}", self.url))?;
+        if !resp.status().is_success() {
+            bail!("failed to export spans to {} ({})", self.url, resp.status());
+        }
+        Ok(())
+    }
+}
+
+/// The OTLP request for `spans`, all from this process.
+fn encode(spans: Vec<Value>) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", "golem"),
+                    attribute("service.version", env!("CARGO_PKG_VERSION")),
+                ],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "golem", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+/// An attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attr {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<&str> for Attr {
+    fn from(value: &str) -> Self {
+        Attr::Str(value.to_string())
+    }
+}
+
+impl From<String> for Attr {
+    fn from(value: String) -> Self {
+        Attr::Str(value)
+    }
+}
+
+impl From<u64> for Attr {
+    fn from(value: u64) -> Self {
+        Attr::Int(value as i64)
+    }
+}
+
+impl From<usize> for Attr {
+    fn from(value: usize) -> Self {
+        Attr::Int(value as i64)
+    }
+}
+
+impl From<bool> for Attr {
+    fn from(value: bool) -> Self {
+        Attr::Bool(value)
+    }
+}
+
+fn attribute(key: &str, value: impl Into<Attr>) -> Value {
+    let value = match value.into() {
+        Attr::Str(s) => json!({ "stringValue": s }),
+        // OTLP JSON carries 64-bit integers as strings
+        Attr::Int(n) => json!({ "intValue": n.to_string() }),
+        Attr::Bool(b) => json!({ "boolValue": b }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+/// A span in progress, exported once dropped. Without a tracer it records
+/// nothing, so code can trace unconditionally.
+#[derive(Default)]
+pub struct Span(Option<Box<OpenSpan>>);
+
+struct OpenSpan {
+    tracer: Arc<Tracer>,
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent: Option<[u8; 8]>,
+    name: String,
+    start: SystemTime,
+    started: Instant,
+    attributes: Vec<Value>,
+    error: Option<String>,
+}
+
+impl Span {
+    fn open(
+        tracer: Arc<Tracer>,
+        trace_id: [u8; 16],
+        parent: Option<[u8; 8]>,
+        name: String,
+    ) -> Self {
+        Span(Some(Box::new(OpenSpan {
+            tracer,
+            trace_id,
+            span_id: rand::random(),
+            parent,
+            name,
+            start: SystemTime::now(),
+            started: Instant::now(),
+            attributes: Vec::new(),
+            error: None,
+        })))
+    }
+
+    /// Start a span inside this one.
+    pub fn child(&self, name: impl Into<String>) -> Span {
+        match &self.0 {
+            Some(span) => Span::open(
+                Arc::clone(&span.tracer),
+                span.trace_id,
+                Some(span.span_id),
+                name.into(),
+            ),
+            None => Span(None),
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<Attr>) {
+        if let Some(span) = &mut self.0 {
+            span.attributes.push(attribute(key, value));
+        }
+    }
+
+    /// Record a model call's token counts.
+    pub fn set_usage(&mut self, usage: TokenUsage) {
+        self.set("gen_ai.usage.input_tokens", usage.input_tokens);
+        self.set("gen_ai.usage.output_tokens", usage.output_tokens);
+        self.set("golem.usage.cache_read_tokens", usage.cache_read_tokens);
+    }
+
+    /// Mark the span as failed with `error`.
+    pub fn fail(&mut self, error: impl Display) {
+        if let Some(span) = &mut self.0 {
+            span.error = Some(format!("{error:#}"));
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(span) = self.0.take() else {
+            return;
+        };
+        let duration = span.started.elapsed();
+        let start = span.start.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut attributes = span.attributes;
+        attributes.push(attribute("golem.duration_ms", duration.as_millis() as u64));
+        let status = match &span.error {
+            Some(message) => json!({ "code": 2, "message": message }),
+            None => json!({ "code": 1 }),
+        };
+        let encoded = json!({
+            "traceId": hex(&span.trace_id),
+            "spanId": hex(&span.span_id),
+            "parentSpanId": span.parent.as_ref().map(|id| hex(id)).unwrap_or_default(),
+            "name": span.name,
+            "kind": 1,
+            "startTimeUnixNano": start.as_nanos().to_string(),
+            "endTimeUnixNano": (start + duration).as_nanos().to_string(),
+            "attributes": attributes,
+            "status": status,
+        });
+        span.tracer
+            .finished
+            .lock()
+            .expect("tracer lock poisoned")
+            .push(encoded);
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serve::http;
+    use std::collections::BTreeMap;
+    use tokio::net::TcpListener;
+
+    fn tracer(endpoint: &str) -> Arc<Tracer> {
+        Arc::new(
+            Tracer::new(&TelemetrySettings {
+                endpoint: Some(endpoint.to_string()),
+                headers: BTreeMap::from([("x-api-key".to_string(), "k".to_string())]),
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn endpoints_get_the_traces_path() {
+        assert_eq!(
+            tracer("http://otel:4318/").url,
+            "http://otel:4318/v1/traces"
+        );
+        assert_eq!(
+            tracer("https://otel/v1/traces").url,
+            "https://otel/v1/traces"
+        );
+        let settings = TelemetrySettings {
+            endpoint: Some("otel:4318".to_string()),
+            ..TelemetrySettings::default()
+        };
+        assert!(Tracer::new(&settings).is_err());
+    }
+
+    #[test]
+    fn spans_nest_and_carry_attributes() {
+        let tracer = tracer("http://otel:4318");
+        let mut task = tracer.root("task");
+        task.set("golem.task", "df -h");
+        let mut call = task.child("chat claude");
+        call.set_usage(TokenUsage {
+            input_tokens: 120,
+            output_tokens: 30,
+            cache_read_tokens: 100,
+        });
+        call.fail("overloaded");
+        drop(call);
+        drop(task);
+        // Without a tracer nothing is recorded
+        Span::default()
+            .child("iteration")
+            .set("golem.iteration", 1usize);
+
+        let spans = tracer.finished.lock().unwrap().clone();
+        assert_eq!(spans.len(), 2);
+        let (call, task) = (&spans[0], &spans[1]);
+        assert_eq!(call["name"], "chat claude");
+        assert_eq!(call["traceId"], task["traceId"]);
+        assert_eq!(call["parentSpanId"], task["spanId"]);
+        assert_eq!(task["parentSpanId"], "");
+        assert_eq!(
+            call["status"],
+            json!({ "code": 2, "message": "overloaded" })
+        );
+        assert_eq!(
+            call["attributes"][0],
+            json!({ "key": "gen_ai.usage.input_tokens", "value": { "intValue": "120" } })
+        );
+        assert_eq!(
+            task["attributes"][0],
+            json!({ "key": "golem.task", "value": { "stringValue": "df -h" } })
+        );
+        assert_eq!(task["attributes"][1]["key"], "golem.duration_ms");
+    }
+
+    #[tokio::test]
+    async fn exports_to_the_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let collector = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = http::read_request(&mut stream).await.unwrap();
+            http::respond(stream, 200, &json!({})).await.unwrap();
+            request
+        });
+
+        let tracer = tracer(&format!("http://{addr}"));
+        drop(tracer.root("task"));
+        tracer.export().await.unwrap();
+        // Nothing left to send
+        tracer.export().await.unwrap();
+
+        let request = collector.await.unwrap();
+        assert_eq!(request.path, "/v1/traces");
+        assert_eq!(request.header("x-api-key"), Some("k"));
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let spans = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans[0]["name"], "task");
+    }
+}
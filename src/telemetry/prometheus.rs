@@ -0,0 +1,360 @@
+//! Prometheus metrics for `/metrics` in `golem serve` and `golem daemon`.
+//!
+//! Engines count into one shared [`Prometheus`]: tasks by how they ended,
+//! iterations and duration per task, tokens, tool latency, and every
+//! provider API response by status code through [`CountedHttp`]. It
+//! renders in the Prometheus text format, counting since golem started.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use crate::serve::http;
+use crate::thinker::TokenUsage;
+use crate::thinker::http::{HttpClient, HttpRequest, HttpResponse};
+
+/// `Content-Type` of the text format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+const ITERATION_BUCKETS: &[f64] = &[1.0, 2.0, 3.0, 5.0, 8.0, 13.0, 20.0, 30.0, 50.0];
+const TASK_SECONDS_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+const TOOL_SECONDS_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Counters and histograms since golem started.
+#[derive(Default)]
+pub struct Prometheus {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    tasks: BTreeMap<String, u64>,
+    iterations: Option<Histogram>,
+    task_seconds: Option<Histogram>,
+    tokens: TokenUsage,
+    tool_seconds: BTreeMap<String, Histogram>,
+    tool_errors: BTreeMap<String, u64>,
+    api_responses: BTreeMap<String, u64>,
+}
+
+/// Observations counted into cumulative buckets.
+#[derive(Debug, Clone, PartialEq)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// Observations at most each bound; the last is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|&bound| value <= bound)
+            .unwrap_or(self.bounds.len());
+        for count in &mut self.counts[bucket..] {
+            *count += 1;
+        }
+        self.sum += value;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"{bound}\"}} {count}");
+        }
+        let total = self.counts[self.bounds.len()];
+        let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {total}");
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        let _ = writeln!(out, "{name}_sum{labels} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{labels} {total}");
+    }
+}
+
+impl Prometheus {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("metrics lock poisoned")
+    }
+
+    /// Count a task that ended as `status` (`completed`, `partial`,
+    /// `failed` or `interrupted`).
+    pub fn task_finished(
+        &self,
+        status: &str,
+        iterations: u64,
+        usage: TokenUsage,
+        duration: Duration,
+    ) {
+        let mut state = self.state();
+        *state.tasks.entry(status.to_string()).or_default() += 1;
+        state
+            .iterations
+            .get_or_insert_with(|| Histogram::new(ITERATION_BUCKETS))
+            .observe(iterations as f64);
+        state
+            .task_seconds
+            .get_or_insert_with(|| Histogram::new(TASK_SECONDS_BUCKETS))
+            .observe(duration.as_secs_f64());
+        state.tokens.add(usage);
+    }
+
+    /// Count a finished call of `tool`.
+    pub fn tool_finished(&self, tool: &str, duration: Duration, ok: bool) {
+        let mut state = self.state();
+        state
+            .tool_seconds
+            .entry(tool.to_string())
+            .or_insert_with(|| Histogram::new(TOOL_SECONDS_BUCKETS))
+            .observe(duration.as_secs_f64());
+        if !ok {
+            *state.tool_errors.entry(tool.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Count a provider API response with `status`, or `error` for a
+    /// request that got no response.
+    pub fn api_response(&self, status: &str) {
+        *self
+            .state()
+            .api_responses
+            .entry(status.to_string())
+            .or_default() += 1;
+    }
+
+    /// Everything counted so far, in the text format.
+    pub fn render(&self) -> String {
+        let state = self.state();
+        let mut out = String::new();
+        family(
+            &mut out,
+            "golem_tasks_total",
+            "counter",
+            "Tasks run, by how they ended.",
+        );
+        for (status, n) in &state.tasks {
+            let _ = writeln!(out, "golem_tasks_total{{status=\"{status}\"}} {n}");
+        }
+        family(
+            &mut out,
+            "golem_task_iterations",
+            "histogram",
+            "Iterations of the ReAct loop per task.",
+        );
+        if let Some(histogram) = &state.iterations {
+            histogram.render(&mut out, "golem_task_iterations", "");
+        }
+        family(
+            &mut out,
+            "golem_task_duration_seconds",
+            "histogram",
+            "How long tasks took.",
+        );
+        if let Some(histogram) = &state.task_seconds {
+            histogram.render(&mut out, "golem_task_duration_seconds", "");
+        }
+        family(
+            &mut out,
+            "golem_tokens_total",
+            "counter",
+            "Tokens used by tasks; cache_read is part of input.",
+        );
+        for (kind, n) in [
+            ("input", state.tokens.input_tokens),
+            ("output", state.tokens.output_tokens),
+            ("cache_read", state.tokens.cache_read_tokens),
+        ] {
+            let _ = writeln!(out, "golem_tokens_total{{kind=\"{kind}\"}} {n}");
+        }
+        family(
+            &mut out,
+            "golem_tool_duration_seconds",
+            "histogram",
+            "How long tool calls took, by tool.",
+        );
+        for (tool, histogram) in &state.tool_seconds {
+            let labels = format!("tool=\"{}\"", escape(tool));
+            histogram.render(&mut out, "golem_tool_duration_seconds", &labels);
+        }
+        family(
+            &mut out,
+            "golem_tool_errors_total",
+            "counter",
+            "Tool calls that failed, by tool.",
+        );
+        for (tool, n) in &state.tool_errors {
+            let _ = writeln!(
+                out,
+                "golem_tool_errors_total{{tool=\"{}\"}} {n}",
+                escape(tool)
+            );
+        }
+        family(
+            &mut out,
+            "golem_api_responses_total",
+            "counter",
+            "Model provider API responses, by HTTP status; `error` when none came.",
+        );
+        for (status, n) in &state.api_responses {
+            let _ = writeln!(out, "golem_api_responses_total{{status=\"{status}\"}} {n}");
+        }
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// A label value with `\`, `"` and line breaks escaped.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Counts every response of the client it wraps.
+pub struct CountedHttp {
+    inner: Arc<dyn HttpClient>,
+    prometheus: Arc<Prometheus>,
+}
+
+impl CountedHttp {
+    pub fn new(inner: Arc<dyn HttpClient>, prometheus: Arc<Prometheus>) -> Self {
+        Self { inner, prometheus }
+    }
+}
+
+#[async_trait]
+impl HttpClient for CountedHttp {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let response = self.inner.send(request).await;
+        match &response {
+            Ok(response) => self.prometheus.api_response(&response.status.to_string()),
+            Err(_) => self.prometheus.api_response("error"),
+        }
+        response
+    }
+}
+
+/// Answer `GET /metrics` on `listener` until it fails; anything else is
+/// `404`.
+pub async fn serve(listener: TcpListener, prometheus: Arc<Prometheus>) -> Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let prometheus = Arc::clone(&prometheus);
+        tokio::spawn(async move {
+            let request = match http::read_request(&mut stream).await {
+                Ok(request) => request,
+                Err(e) => {
+                    let _ = http::respond(stream, 400, &json!({ "error": e.to_string() })).await;
+                    return;
+                }
+            };
+            let _ = if request.method == "GET" && request.path == "/metrics" {
+                http::respond_with(stream, 200, CONTENT_TYPE, &prometheus.render()).await
+            } else {
+                http::respond(stream, 404, &json!({ "error": "not found" })).await
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Status(u16);
+
+    #[async_trait]
+    impl HttpClient for Status {
+        async fn send(&self, _request: HttpRequest) -> Result<HttpResponse> {
+            match self.0 {
+                0 => anyhow::bail!("connection refused"),
+                status => Ok(HttpResponse {
+                    status,
+                    body: String::new(),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn histograms_are_cumulative() {
+        let mut histogram = Histogram::new(&[1.0, 5.0]);
+        for value in [0.5, 3.0, 4.0, 9.0] {
+            histogram.observe(value);
+        }
+        assert_eq!(histogram.counts, [1, 3, 4]);
+        let mut out = String::new();
+        histogram.render(&mut out, "x", "tool=\"shell\"");
+        assert_eq!(
+            out,
+            "x_bucket{tool=\"shell\",le=\"1\"} 1\n\
+             x_bucket{tool=\"shell\",le=\"5\"} 3\n\
+             x_bucket{tool=\"shell\",le=\"+Inf\"} 4\n\
+             x_sum{tool=\"shell\"} 16.5\n\
+             x_count{tool=\"shell\"} 4\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn renders_what_was_counted() {
+        let prometheus = Arc::new(Prometheus::default());
+        let usage = TokenUsage {
+            input_tokens: 1200,
+            output_tokens: 300,
+            cache_read_tokens: 1000,
+        };
+        prometheus.task_finished("completed", 3, usage, Duration::from_secs(12));
+        prometheus.task_finished("failed", 1, TokenUsage::default(), Duration::from_secs(2));
+        prometheus.tool_finished("shell", Duration::from_millis(40), true);
+        prometheus.tool_finished("shell", Duration::from_millis(700), false);
+        for status in [200, 200, 529, 0] {
+            let http = CountedHttp::new(Arc::new(Status(status)), Arc::clone(&prometheus));
+            let _ = http.send(HttpRequest::get("https://api")).await;
+        }
+
+        let text = prometheus.render();
+        for line in [
+            "# TYPE golem_tasks_total counter",
+            "golem_tasks_total{status=\"completed\"} 1",
+            "golem_tasks_total{status=\"failed\"} 1",
+            "golem_task_iterations_bucket{le=\"3\"} 2",
+            "golem_task_iterations_count 2",
+            "golem_task_duration_seconds_sum 14",
+            "golem_tokens_total{kind=\"input\"} 1200",
+            "golem_tokens_total{kind=\"cache_read\"} 1000",
+            "golem_tool_duration_seconds_bucket{tool=\"shell\",le=\"0.05\"} 1",
+            "golem_tool_duration_seconds_count{tool=\"shell\"} 2",
+            "golem_tool_errors_total{tool=\"shell\"} 1",
+            "golem_api_responses_total{status=\"200\"} 2",
+            "golem_api_responses_total{status=\"529\"} 1",
+            "golem_api_responses_total{status=\"error\"} 1",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {line:?} in\n{text}"
+            );
+        }
+    }
+}
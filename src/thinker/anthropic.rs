@@ -1,16 +1,23 @@
 use anyhow::{Result, bail};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::auth::AuthStorage;
 use crate::consts::DEFAULT_MODEL;
-use crate::memory::MemoryEntry;
-use crate::prompts::build_react_system_prompt;
+use crate::memory::{HitSource, MemoryEntry, SessionEntry};
+use crate::prompts::react::Example;
+use crate::prompts::{
+    PromptVariant, SUMMARY_SYSTEM_PROMPT, build_budget_note, build_language_section,
+    build_persona_section, build_summary_request, build_system_prompt, render_react_template,
+};
 use crate::tools::Outcome;
+use crate::transcript::{Transcript, TranscriptEvent};
 
+use super::http::{HttpClient, HttpRequest, ReqwestClient};
 use super::{
-    Context, MAX_PARSE_RETRIES, ModelInfo, PARSE_RETRY_PROMPT, StepResult, Thinker, TokenUsage,
-    parse_response,
+    Context, DEFAULT_PARSE_RETRIES, ModelInfo, StepResult, SummaryResult, Thinker, TokenUsage,
+    correction_prompt, parse_response,
 };
 
 const API_URL: &str = "https://api.anthropic.com/v1/messages";
@@ -19,11 +26,20 @@ const API_VERSION: &str = "2023-06-01";
 const MAX_TOKENS: u32 = 8192;
 const OAUTH_BETA: &str = "claude-code-20250219,oauth-2025-04-20";
 const CLAUDE_CODE_VERSION: &str = "2.1.2";
+/// Each recalled snippet is cut to this many characters in the prompt.
+const RELEVANT_MEMORY_MAX_CHARS: usize = 500;
 
 /// An LLM thinker that calls the Anthropic Messages API.
 pub struct AnthropicThinker {
     model: String,
     auth: AuthStorage,
+    transcript: Option<Arc<Transcript>>,
+    http: Arc<dyn HttpClient>,
+    parse_retries: usize,
+    /// The user's system prompt template, if they have one.
+    prompt_template: Option<String>,
+    /// Worked examples shown in the system prompt.
+    examples: Vec<Example>,
 }
 
 impl AnthropicThinker {
@@ -31,9 +47,41 @@ impl AnthropicThinker {
         Self {
             model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
             auth,
+            transcript: None,
+            http: Arc::new(ReqwestClient),
+            parse_retries: DEFAULT_PARSE_RETRIES,
+            prompt_template: None,
+            examples: Vec::new(),
         }
     }
 
+    /// Ask the model to correct a response that isn't a step up to
+    /// `retries` times before failing.
+    pub fn with_parse_retries(mut self, retries: usize) -> Self {
+        self.parse_retries = retries;
+        self
+    }
+
+    /// Send API requests through `http` instead of the network, e.g. a
+    /// cassette in tests.
+    /// Build the system prompt from `template` instead of the built-in
+    /// text, see [`render_react_template`].
+    pub fn with_prompt_template(mut self, template: String) -> Self {
+        self.prompt_template = Some(template);
+        self
+    }
+
+    /// Show these worked examples in the system prompt.
+    pub fn with_examples(mut self, examples: Vec<Example>) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    pub fn with_http(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.http = http;
+        self
+    }
+
     fn build_messages(context: &Context) -> Vec<Message> {
         let mut messages: Vec<Message> = Vec::new();
 
@@ -55,10 +103,27 @@ impl AnthropicThinker {
             });
         }
 
-        // The current task
+        // The current task, preceded by related context from earlier work
+        let mut task_msg = String::new();
+        if !context.relevant_memory.is_empty() {
+            task_msg.push_str("Possibly relevant context from earlier tasks:\n");
+            for hit in &context.relevant_memory {
+                task_msg.push_str(&format!(
+                    "- ({}) {} → {}\n",
+                    hit.task,
+                    match hit.source {
+                        HitSource::Session => "answer",
+                        HitSource::Observation => "observed",
+                    },
+                    truncate(&hit.text, RELEVANT_MEMORY_MAX_CHARS)
+                ));
+            }
+            task_msg.push('\n');
+        }
+        task_msg.push_str(&format!("Task: {}", context.task));
         messages.push(Message {
             role: "user".to_string(),
-            content: format!("Task: {}", context.task),
+            content: task_msg,
         });
 
         // Convert history into assistant/user message pairs
@@ -67,17 +132,34 @@ impl AnthropicThinker {
                 MemoryEntry::Task { .. } => {
                     // Already handled as the first message
                 }
-                MemoryEntry::Iteration { thought, results } => {
-                    // Reconstruct what the assistant said
-                    let calls: Vec<serde_json::Value> = results
-                        .iter()
-                        .map(|r| {
-                            serde_json::json!({
-                                "tool": r.tool,
-                                "args": {}
+                MemoryEntry::Iteration {
+                    thought,
+                    calls,
+                    results,
+                } => {
+                    // Reconstruct what the assistant said. Entries stored
+                    // before calls were kept only know the tool names.
+                    let calls: Vec<serde_json::Value> = if calls.is_empty() {
+                        results
+                            .iter()
+                            .map(|r| {
+                                serde_json::json!({
+                                    "tool": r.tool,
+                                    "args": {}
+                                })
+                            })
+                            .collect()
+                    } else {
+                        calls
+                            .iter()
+                            .map(|c| {
+                                serde_json::json!({
+                                    "tool": c.tool,
+                                    "args": c.args
+                                })
                             })
-                        })
-                        .collect();
+                            .collect()
+                    };
 
                     let assistant_msg = serde_json::json!({
                         "thought": thought,
@@ -112,22 +194,54 @@ impl AnthropicThinker {
                 MemoryEntry::Answer { .. } => {
                     // Shouldn't appear in mid-loop context, but ignore gracefully
                 }
+                MemoryEntry::Retry { error } => {
+                    // The failed turn left no assistant message; say so on
+                    // the user message it would have answered
+                    if let Some(last) = messages.last_mut() {
+                        last.content.push_str(&format!(
+                            "\n(Your previous response to this was lost to an error and the turn was retried: {error})"
+                        ));
+                    }
+                }
             }
         }
 
+        // The last message is always the user's (task or observation)
+        if let Some(note) = &context.note
+            && let Some(last) = messages.last_mut()
+        {
+            last.content.push_str(&format!("\n{note}"));
+        }
+        if let Some(budget) = context.iteration
+            && let Some(last) = messages.last_mut()
+        {
+            last.content
+                .push_str(&format!("\n{}", build_budget_note(budget)));
+        }
+
         messages
     }
 }
 
 /// Whether an API key is an OAuth token (vs a plain API key).
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
+}
+
 fn is_oauth_token(api_key: &str) -> bool {
     api_key.starts_with("sk-ant-oat")
 }
 
-/// Apply Anthropic auth headers to a request builder.
-fn apply_auth(builder: reqwest::RequestBuilder, api_key: &str) -> reqwest::RequestBuilder {
+/// Add the API version and auth headers to a request.
+fn apply_auth(request: HttpRequest, api_key: &str) -> HttpRequest {
+    let request = request
+        .header("anthropic-version", API_VERSION)
+        .header("content-type", "application/json");
     if is_oauth_token(api_key) {
-        builder
+        request
             .header("authorization", format!("Bearer {api_key}"))
             .header("anthropic-beta", OAUTH_BETA)
             .header(
@@ -136,7 +250,7 @@ fn apply_auth(builder: reqwest::RequestBuilder, api_key: &str) -> reqwest::Reque
             )
             .header("x-app", "cli")
     } else {
-        builder.header("x-api-key", api_key)
+        request.header("x-api-key", api_key)
     }
 }
 
@@ -161,23 +275,25 @@ impl AnthropicThinker {
             messages,
         };
 
-        let client = reqwest::Client::new();
-        let req = client
-            .post(API_URL)
-            .header("anthropic-version", API_VERSION)
-            .header("content-type", "application/json");
-
-        let req = apply_auth(req, api_key);
+        if let Some(transcript) = &self.transcript {
+            transcript.record(TranscriptEvent::LlmRequest {
+                model: self.model.clone(),
+                system: system.to_string(),
+                messages: serde_json::to_value(messages)?,
+            });
+        }
 
-        let resp = req.json(&body).send().await?;
+        let req = apply_auth(
+            HttpRequest::post(API_URL, serde_json::to_value(&body)?),
+            api_key,
+        );
+        let resp = self.http.send(req).await?;
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            bail!("Anthropic API error ({}): {}", status, text);
+        if !resp.is_success() {
+            bail!("Anthropic API error ({}): {}", resp.status, resp.body);
         }
 
-        let api_resp: ApiResponse = resp.json().await?;
+        let api_resp: ApiResponse = serde_json::from_str(&resp.body)?;
 
         let text: String = api_resp
             .content
@@ -196,11 +312,23 @@ impl AnthropicThinker {
             bail!("Anthropic API returned empty response");
         }
 
+        // The API counts cache reads and writes apart from other input
         let usage = api_resp.usage.map(|u| TokenUsage {
-            input_tokens: u.input_tokens,
+            input_tokens: u.input_tokens
+                + u.cache_read_input_tokens
+                + u.cache_creation_input_tokens,
             output_tokens: u.output_tokens,
+            cache_read_tokens: u.cache_read_input_tokens,
         });
 
+        if let Some(transcript) = &self.transcript {
+            transcript.record(TranscriptEvent::LlmResponse {
+                model: self.model.clone(),
+                text: text.clone(),
+                usage,
+            });
+        }
+
         Ok(RawResponse { text, usage })
     }
 }
@@ -208,23 +336,18 @@ impl AnthropicThinker {
 impl AnthropicThinker {
     /// Fetch the list of models from the Anthropic API.
     async fn fetch_models(&self, api_key: &str) -> Result<Vec<ModelInfo>> {
-        let client = reqwest::Client::new();
-        let req = client
-            .get(MODELS_API_URL)
-            .header("anthropic-version", API_VERSION)
-            .header("content-type", "application/json");
-
-        let req = apply_auth(req, api_key);
-
-        let resp = req.send().await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            bail!("Anthropic models API error ({status}): {text}");
+        let req = apply_auth(HttpRequest::get(MODELS_API_URL), api_key);
+        let resp = self.http.send(req).await?;
+
+        if !resp.is_success() {
+            bail!(
+                "Anthropic models API error ({}): {}",
+                resp.status,
+                resp.body
+            );
         }
 
-        let list: ModelsListResponse = resp.json().await?;
+        let list: ModelsListResponse = serde_json::from_str(&resp.body)?;
 
         Ok(parse_models_response(list))
     }
@@ -254,6 +377,40 @@ impl Thinker for AnthropicThinker {
         self.model = model;
     }
 
+    // Steps come back as JSON in the text, not through the API's tool use,
+    // and every Claude model follows the full protocol
+    fn prompt_variant(&self) -> PromptVariant {
+        PromptVariant::Standard
+    }
+
+    fn set_transcript(&mut self, transcript: Arc<Transcript>) {
+        self.transcript = Some(transcript);
+    }
+
+    async fn summarize(&self, entries: &[SessionEntry]) -> Result<SummaryResult> {
+        let api_key = self
+            .auth
+            .get_api_key("anthropic", "ANTHROPIC_API_KEY")
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no Anthropic credentials found. Run `golem login` or set ANTHROPIC_API_KEY."
+                )
+            })?;
+
+        let messages = [Message {
+            role: "user".to_string(),
+            content: build_summary_request(entries),
+        }];
+        let raw = self
+            .call_api(&api_key, SUMMARY_SYSTEM_PROMPT, &messages)
+            .await?;
+        Ok(SummaryResult {
+            text: raw.text.trim().to_string(),
+            usage: raw.usage,
+        })
+    }
+
     async fn next_step(&self, context: &Context) -> Result<StepResult> {
         let api_key = self
             .auth
@@ -265,12 +422,32 @@ impl Thinker for AnthropicThinker {
                 )
             })?;
 
-        let system = build_react_system_prompt(&context.available_tools);
+        let mut system = match &self.prompt_template {
+            Some(template) => render_react_template(
+                template,
+                &context.available_tools,
+                &context.facts,
+                &self.examples,
+                &context.session_history,
+            ),
+            None => build_system_prompt(
+                self.prompt_variant(),
+                &context.available_tools,
+                &context.facts,
+                &self.examples,
+            ),
+        };
+        if let Some(persona) = context.persona {
+            system.push_str(&build_persona_section(persona));
+        }
+        if let Some(language) = &context.language {
+            system.push_str(&build_language_section(language));
+        }
         let mut messages = Self::build_messages(context);
         let mut total_usage = TokenUsage::default();
 
-        // Try parsing, with up to MAX_PARSE_RETRIES correction rounds
-        for attempt in 0..=MAX_PARSE_RETRIES {
+        // Try parsing, with up to parse_retries correction rounds
+        for attempt in 0..=self.parse_retries {
             let raw = self.call_api(&api_key, &system, &messages).await?;
 
             if let Some(usage) = raw.usage {
@@ -290,9 +467,9 @@ impl Thinker for AnthropicThinker {
                     });
                 }
                 Err(parse_err) => {
-                    if attempt < MAX_PARSE_RETRIES {
+                    if attempt < self.parse_retries {
                         eprintln!(
-                            "warning: LLM returned invalid JSON (attempt {}), retrying with correction",
+                            "warning: LLM returned an unusable response (attempt {}), retrying with correction",
                             attempt + 1
                         );
                         // Append the malformed response + correction as context
@@ -302,7 +479,7 @@ impl Thinker for AnthropicThinker {
                         });
                         messages.push(Message {
                             role: "user".to_string(),
-                            content: PARSE_RETRY_PROMPT.to_string(),
+                            content: correction_prompt(&parse_err),
                         });
                     } else {
                         return Err(parse_err);
@@ -349,6 +526,10 @@ struct ContentBlock {
 struct Usage {
     input_tokens: u64,
     output_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
 }
 
 // --- Models API types ---
@@ -387,7 +568,7 @@ fn parse_models_response(list: ModelsListResponse) -> Vec<ModelInfo> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::thinker::Context;
+    use crate::thinker::{Context, IterationBudget};
 
     #[test]
     fn build_messages_task_only() {
@@ -395,7 +576,13 @@ mod tests {
             task: "do something".to_string(),
             history: vec![],
             session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -416,6 +603,7 @@ mod tests {
                 },
                 MemoryEntry::Iteration {
                     thought: "let me check".to_string(),
+                    calls: vec![],
                     results: vec![ToolResult {
                         tool: "shell".to_string(),
                         outcome: Outcome::Success("6.18.8".to_string()),
@@ -423,7 +611,13 @@ mod tests {
                 },
             ],
             session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -437,6 +631,104 @@ mod tests {
         assert!(messages[2].content.contains("✓"));
     }
 
+    #[test]
+    fn build_messages_includes_tool_args() {
+        use crate::thinker::ToolCall;
+        use crate::tools::{Outcome, ToolResult};
+        use std::collections::HashMap;
+
+        let context = Context {
+            task: "check kernel".to_string(),
+            history: vec![MemoryEntry::Iteration {
+                thought: "let me check".to_string(),
+                calls: vec![ToolCall {
+                    tool: "shell".to_string(),
+                    args: HashMap::from([("command".to_string(), "uname -r".to_string())]),
+                }],
+                results: vec![ToolResult {
+                    tool: "shell".to_string(),
+                    outcome: Outcome::Success("6.18.8".to_string()),
+                }],
+            }],
+            session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
+            available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
+        };
+
+        let messages = AnthropicThinker::build_messages(&context);
+        let assistant: serde_json::Value = serde_json::from_str(&messages[1].content).unwrap();
+        assert_eq!(
+            assistant["action"]["calls"][0]["args"]["command"],
+            "uname -r"
+        );
+    }
+
+    #[test]
+    fn build_messages_appends_note_to_last_observation() {
+        use crate::tools::{Outcome, ToolResult};
+
+        let context = Context {
+            task: "test".to_string(),
+            history: vec![MemoryEntry::Iteration {
+                thought: "try again".to_string(),
+                calls: vec![],
+                results: vec![ToolResult {
+                    tool: "shell".to_string(),
+                    outcome: Outcome::Error("not found".to_string()),
+                }],
+            }],
+            session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
+            available_tools: vec![],
+            note: Some("Reflect first.".to_string()),
+            iteration: None,
+            persona: None,
+            language: None,
+        };
+
+        let messages = AnthropicThinker::build_messages(&context);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2].role, "user");
+        assert!(
+            messages[2]
+                .content
+                .ends_with("[shell] ✗ not found\n\nReflect first.")
+        );
+    }
+
+    #[test]
+    fn build_messages_with_iteration_budget() {
+        let context = Context {
+            task: "test".to_string(),
+            history: vec![],
+            session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
+            available_tools: vec![],
+            note: None,
+            iteration: Some(IterationBudget {
+                current: 18,
+                max: 20,
+            }),
+            persona: None,
+            language: None,
+        };
+
+        let messages = AnthropicThinker::build_messages(&context);
+        assert_eq!(messages.len(), 1);
+        assert!(
+            messages[0]
+                .content
+                .contains("Task: test\nThis is iteration 18 of 20. Only 2 more")
+        );
+    }
+
     #[test]
     fn build_messages_with_error_result() {
         use crate::tools::{Outcome, ToolResult};
@@ -449,6 +741,7 @@ mod tests {
                 },
                 MemoryEntry::Iteration {
                     thought: "try something".to_string(),
+                    calls: vec![],
                     results: vec![ToolResult {
                         tool: "shell".to_string(),
                         outcome: Outcome::Error("command not found".to_string()),
@@ -456,7 +749,13 @@ mod tests {
                 },
             ],
             session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -465,6 +764,36 @@ mod tests {
         assert!(messages[2].content.contains("command not found"));
     }
 
+    #[test]
+    fn build_messages_prefixes_task_with_relevant_memory() {
+        use crate::memory::SemanticHit;
+
+        let context = Context {
+            task: "fix nginx".to_string(),
+            history: vec![],
+            session_history: vec![],
+            relevant_memory: vec![SemanticHit {
+                source: HitSource::Observation,
+                task: "why is nginx failing".to_string(),
+                text: "[shell] bind() to 0.0.0.0:80 failed".to_string(),
+                score: 0.8,
+            }],
+            facts: vec![],
+            available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
+        };
+
+        let messages = AnthropicThinker::build_messages(&context);
+        assert_eq!(messages.len(), 1);
+        let content = &messages[0].content;
+        assert!(content.contains("relevant context"));
+        assert!(content.contains("(why is nginx failing) observed → [shell] bind()"));
+        assert!(content.ends_with("Task: fix nginx"));
+    }
+
     #[test]
     fn build_messages_includes_session_history() {
         use crate::memory::SessionEntry;
@@ -476,7 +805,13 @@ mod tests {
                 task: "list files in /tmp".to_string(),
                 answer: "a.txt (10KB), b.txt (50KB), c.txt (1KB)".to_string(),
             }],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -507,7 +842,13 @@ mod tests {
                     answer: "answer 2".to_string(),
                 },
             ],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -653,7 +994,13 @@ mod tests {
                 },
             ],
             session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
             available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
         };
 
         let messages = AnthropicThinker::build_messages(&context);
@@ -0,0 +1,267 @@
+//! VCR-style cassettes: record provider HTTP traffic to a file once, then
+//! replay it so tests run offline and always see the same responses.
+//!
+//! A cassette is a JSON file of request/response pairs. Requests are
+//! stored without headers, so no credentials end up on disk. Replay hands
+//! out the responses in recorded order and fails as soon as a request's
+//! method or URL differs from the recording.
+
+use anyhow::{Context as _, Result, bail};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::consts::RECORD_CASSETTES_ENV;
+
+/// One request and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: InteractionRequest,
+    pub response: InteractionResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InteractionRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InteractionResponse {
+    pub status: u16,
+    /// JSON bodies are stored as JSON so cassettes stay readable; anything
+    /// else as a string.
+    pub body: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode {
+    Record {
+        inner: Arc<dyn HttpClient>,
+        recorded: Vec<Interaction>,
+    },
+    Replay(VecDeque<Interaction>),
+}
+
+/// An [`HttpClient`] that records to or replays from a cassette file.
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mutex<Mode>,
+}
+
+impl Cassette {
+    /// Send requests through `inner` and write each interaction to `path`,
+    /// replacing whatever the file held.
+    pub fn record(path: impl Into<PathBuf>, inner: Arc<dyn HttpClient>) -> Self {
+        Self {
+            path: path.into(),
+            mode: Mutex::new(Mode::Record {
+                inner,
+                recorded: Vec::new(),
+            }),
+        }
+    }
+
+    /// Answer requests from the cassette at `path`, never the network.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("cannot read cassette {}", path.display()))?;
+        let file: CassetteFile = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not a cassette", path.display()))?;
+        Ok(Self {
+            path,
+            mode: Mutex::new(Mode::Replay(file.interactions.into())),
+        })
+    }
+
+    /// Replay `path`, or record it through `inner` when
+    /// `GOLEM_RECORD_CASSETTES` is set.
+    pub fn from_env(path: impl Into<PathBuf>, inner: Arc<dyn HttpClient>) -> Result<Self> {
+        if std::env::var_os(RECORD_CASSETTES_ENV).is_some() {
+            Ok(Self::record(path, inner))
+        } else {
+            Self::replay(path)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn next_interaction(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let mut mode = self.mode.lock().expect("cassette lock poisoned");
+        let Mode::Replay(interactions) = &mut *mode else {
+            bail!("cassette {} is recording", self.path.display());
+        };
+        let Some(interaction) = interactions.pop_front() else {
+            bail!(
+                "cassette {} has no more interactions for {} {}",
+                self.path.display(),
+                request.method,
+                request.url
+            );
+        };
+        let recorded = &interaction.request;
+        if recorded.method != request.method || recorded.url != request.url {
+            bail!(
+                "cassette {} expected {} {}, got {} {}",
+                self.path.display(),
+                recorded.method,
+                recorded.url,
+                request.method,
+                request.url
+            );
+        }
+        Ok(HttpResponse {
+            status: interaction.response.status,
+            body: body_text(&interaction.response.body),
+        })
+    }
+
+    fn save(&self, interaction: Interaction) -> Result<()> {
+        let mut mode = self.mode.lock().expect("cassette lock poisoned");
+        let Mode::Record { recorded, .. } = &mut *mode else {
+            bail!("cassette {} is replaying", self.path.display());
+        };
+        recorded.push(interaction);
+        let file = CassetteFile {
+            interactions: recorded.clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&file)?;
+        std::fs::write(&self.path, json + "\n")
+            .with_context(|| format!("cannot write cassette {}", self.path.display()))
+    }
+
+    fn recording_client(&self) -> Option<Arc<dyn HttpClient>> {
+        match &*self.mode.lock().expect("cassette lock poisoned") {
+            Mode::Record { inner, .. } => Some(Arc::clone(inner)),
+            Mode::Replay(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl HttpClient for Cassette {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let Some(inner) = self.recording_client() else {
+            return self.next_interaction(&request);
+        };
+        let response = inner.send(request.clone()).await?;
+        self.save(Interaction {
+            request: InteractionRequest {
+                method: request.method,
+                url: request.url,
+                body: request.body,
+            },
+            response: InteractionResponse {
+                status: response.status,
+                body: body_value(&response.body),
+            },
+        })?;
+        Ok(response)
+    }
+}
+
+fn body_value(text: &str) -> serde_json::Value {
+    serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.to_string()))
+}
+
+fn body_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every request with its own URL.
+    struct Echo;
+
+    #[async_trait]
+    impl HttpClient for Echo {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            Ok(HttpResponse {
+                status: 200,
+                body: format!("{{\"url\": \"{}\"}}", request.url),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn records_then_replays_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes/echo.json");
+
+        let recorder = Cassette::record(&path, Arc::new(Echo));
+        let request = HttpRequest::post("https://a.test/one", serde_json::json!({"n": 1}))
+            .header("x-api-key", "secret");
+        recorder.send(request).await.unwrap();
+        recorder
+            .send(HttpRequest::get("https://a.test/two"))
+            .await
+            .unwrap();
+        let file = std::fs::read_to_string(&path).unwrap();
+        assert!(!file.contains("secret"));
+
+        let player = Cassette::replay(&path).unwrap();
+        let first = player
+            .send(HttpRequest::post(
+                "https://a.test/one",
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(first.status, 200);
+        let body: serde_json::Value = serde_json::from_str(&first.body).unwrap();
+        assert_eq!(body["url"], "https://a.test/one");
+        player
+            .send(HttpRequest::get("https://a.test/two"))
+            .await
+            .unwrap();
+        let err = player
+            .send(HttpRequest::get("https://a.test/two"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no more interactions"));
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_unexpected_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("one.json");
+        let recorder = Cassette::record(&path, Arc::new(Echo));
+        recorder
+            .send(HttpRequest::get("https://a.test/one"))
+            .await
+            .unwrap();
+
+        let player = Cassette::replay(&path).unwrap();
+        let err = player
+            .send(HttpRequest::get("https://a.test/other"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("expected GET https://a.test/one"));
+    }
+
+    #[test]
+    fn non_json_bodies_round_trip_as_text() {
+        assert_eq!(body_text(&body_value("plain text")), "plain text");
+        assert_eq!(body_text(&body_value("{\"a\":1}")), "{\"a\":1}");
+    }
+}
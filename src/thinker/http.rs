@@ -0,0 +1,111 @@
+//! HTTP for model providers.
+//!
+//! Thinkers send requests through [`HttpClient`] instead of calling
+//! reqwest directly, so tests can put a [`Cassette`] in front of the
+//! network — or in place of it. Everything that does call reqwest uses
+//! [`shared_client`].
+//!
+//! [`Cassette`]: super::cassette::Cassette
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::sync::OnceLock;
+
+use crate::consts::HTTP_CONNECT_TIMEOUT;
+
+/// The reqwest client all of golem's requests go through, so they share
+/// one pool of connections — and their TLS sessions — instead of opening
+/// new ones per call. Proxies come from `HTTPS_PROXY`, `HTTP_PROXY` and
+/// `NO_PROXY`, as reqwest reads them.
+pub fn shared_client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .connect_timeout(HTTP_CONNECT_TIMEOUT)
+                .build()
+                .expect("the HTTP client always builds")
+        })
+        // A handle to the same pool
+        .clone()
+}
+
+/// A request to a provider API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    /// Sent as is, never recorded — they carry credentials.
+    pub headers: Vec<(String, String)>,
+    /// JSON body, if any.
+    pub body: Option<serde_json::Value>,
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn post(url: &str, body: serde_json::Value) -> Self {
+        Self {
+            method: "POST".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Some(body),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+/// What came back: the status code and the body text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// The real network, through [`shared_client`].
+pub struct ReqwestClient;
+
+#[async_trait]
+impl HttpClient for ReqwestClient {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let client = shared_client();
+        let mut builder = match request.method.as_str() {
+            "GET" => client.get(&request.url),
+            "POST" => client.post(&request.url),
+            other => bail!("unsupported HTTP method: {other}"),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+        let resp = builder.send().await?;
+        Ok(HttpResponse {
+            status: resp.status().as_u16(),
+            body: resp.text().await?,
+        })
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::io::{self, Write};
+
+use crate::prompts::build_budget_note;
 
 use super::{Context, ModelInfo, Step, StepResult, Thinker, ToolCall};
 
@@ -9,12 +10,9 @@ use super::{Context, ModelInfo, Step, StepResult, Thinker, ToolCall};
 pub struct HumanThinker;
 
 impl HumanThinker {
-    fn read_line(prompt: &str) -> Result<String> {
-        print!("{}", prompt);
-        io::stdout().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        Ok(input.trim().to_string())
+    async fn read_line(prompt: &str) -> Result<String> {
+        let input = crate::prompt::terminal().ask(prompt).await?;
+        Ok(input.unwrap_or_default())
     }
 
     fn print_context(context: &Context) {
@@ -30,6 +28,22 @@ impl HumanThinker {
             println!("{}", "-".repeat(60));
         }
 
+        if !context.facts.is_empty() {
+            println!("Facts:");
+            for fact in &context.facts {
+                println!("  [{}] {}", fact.id, fact.text);
+            }
+            println!("{}", "-".repeat(60));
+        }
+
+        if !context.relevant_memory.is_empty() {
+            println!("Relevant memory:");
+            for hit in &context.relevant_memory {
+                println!("  ({:.2}) {} → {}", hit.score, hit.task, hit.text);
+            }
+            println!("{}", "-".repeat(60));
+        }
+
         println!("Task: {}", context.task);
         println!("{}", "-".repeat(60));
 
@@ -44,6 +58,16 @@ impl HumanThinker {
             println!("{}", "-".repeat(60));
         }
 
+        if let Some(note) = &context.note {
+            println!("{note}");
+            println!("{}", "-".repeat(60));
+        }
+
+        if let Some(budget) = context.iteration {
+            println!("{}", build_budget_note(budget));
+            println!("{}", "-".repeat(60));
+        }
+
         println!("Available tools:");
         for tool in &context.available_tools {
             println!("  {} — {}", tool.name, tool.description);
@@ -69,11 +93,11 @@ impl Thinker for HumanThinker {
     async fn next_step(&self, context: &Context) -> Result<StepResult> {
         Self::print_context(context);
 
-        let thought = Self::read_line("\nThought: ")?;
-        let action = Self::read_line("Action (tool:arg or 'finish'): ")?;
+        let thought = Self::read_line("\nThought: ").await?;
+        let action = Self::read_line("Action (tool:arg or 'finish'): ").await?;
 
         if action == "finish" {
-            let answer = Self::read_line("Answer: ")?;
+            let answer = Self::read_line("Answer: ").await?;
             return Ok(StepResult {
                 step: Step::Finish { thought, answer },
                 usage: None,
@@ -1,21 +1,93 @@
 pub mod anthropic;
+pub mod cassette;
+pub mod http;
 pub mod human;
 pub mod mock;
+pub mod repair;
+pub mod replay;
 
-use anyhow::{Result, bail};
+use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use crate::memory::{MemoryEntry, SessionEntry};
+use crate::consts::{CACHE_READ_PRICE, MODEL_PRICES};
+use crate::memory::facts::Fact;
+use crate::memory::{MemoryEntry, SemanticHit, SessionEntry};
+use crate::prompts::PromptVariant;
+use crate::prompts::persona::Persona;
+use crate::tools::Capabilities;
+use crate::transcript::Transcript;
 
-/// Maximum number of retry attempts when the LLM returns unparseable JSON.
-pub const MAX_PARSE_RETRIES: usize = 1;
+/// Correction rounds when the LLM returns a response that isn't a step,
+/// unless `parse_retries` says otherwise.
+pub const DEFAULT_PARSE_RETRIES: usize = 1;
 
-/// Correction prompt sent to the LLM after a parse failure.
-pub const PARSE_RETRY_PROMPT: &str = "Your previous response was not valid JSON. You MUST respond with a JSON object only — no prose, no markdown, no explanation outside the JSON. Respond now with the correct JSON format.";
+/// Characters of the response on each side of a JSON error quoted back
+/// to the model.
+const SNIPPET_CHARS: usize = 40;
 
-/// A single tool invocation request.
+/// Why a response isn't a step.
 #[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// Not JSON, even after repair: serde's error and the text around it.
+    InvalidJson {
+        error: String,
+        snippet: String,
+        raw: String,
+    },
+    /// JSON with an `action` whose calls all lack a `tool` or `args`.
+    NoCalls { raw: String },
+    /// JSON with neither an `answer` nor an `action`.
+    NotAStep { raw: String },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidJson { error, raw, .. } => {
+                write!(
+                    f,
+                    "failed to parse LLM response as JSON: {error}\nraw: {raw}"
+                )
+            }
+            ParseError::NoCalls { raw } => {
+                write!(f, "LLM returned action with no valid tool calls: {raw}")
+            }
+            ParseError::NotAStep { raw } => {
+                write!(
+                    f,
+                    "LLM response is neither an answer nor a tool call: {raw}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The message that asks the model to correct a response
+/// [`parse_response`] failed with `error`, saying what was wrong with it.
+pub fn correction_prompt(error: &anyhow::Error) -> String {
+    const FORMATS: &str = "Respond with {\"thought\": \"...\", \"answer\": \"...\"} to finish, or {\"thought\": \"...\", \"action\": {\"calls\": [{\"tool\": \"...\", \"args\": {...}}]}} to use tools.";
+    match error.downcast_ref::<ParseError>() {
+        Some(ParseError::NoCalls { .. }) => format!(
+            "Your previous response had an \"action\" but none of its calls had both a \"tool\" and \"args\". {FORMATS}"
+        ),
+        Some(ParseError::NotAStep { .. }) => format!(
+            "Your previous response was valid JSON but had neither an \"answer\" nor an \"action\". {FORMATS}"
+        ),
+        Some(ParseError::InvalidJson { error, snippet, .. }) => format!(
+            "Your previous response was not valid JSON: {error}, at `{snippet}`. You MUST respond with a JSON object only — no prose, no markdown, no explanation outside the JSON. {FORMATS}"
+        ),
+        None => format!(
+            "Your previous response could not be used: {error}. You MUST respond with a JSON object only. {FORMATS}"
+        ),
+    }
+}
+
+/// A single tool invocation request.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ToolCall {
     pub tool: String,
     pub args: HashMap<String, String>,
@@ -34,10 +106,14 @@ pub enum Step {
 }
 
 /// Token usage from a single LLM call.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct TokenUsage {
+    /// Every input token, including those read from the prompt cache.
     pub input_tokens: u64,
     pub output_tokens: u64,
+    /// The part of `input_tokens` served from the prompt cache.
+    #[serde(default)]
+    pub cache_read_tokens: u64,
 }
 
 impl TokenUsage {
@@ -45,12 +121,24 @@ impl TokenUsage {
     pub fn add(&mut self, other: TokenUsage) {
         self.input_tokens += other.input_tokens;
         self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
     }
 
     /// Total tokens (input + output).
     pub fn total(&self) -> u64 {
         self.input_tokens + self.output_tokens
     }
+
+    /// Cost in USD at `model`'s list price, or `None` for unpriced models.
+    pub fn cost(&self, model: &str) -> Option<f64> {
+        let (_, input, output) = MODEL_PRICES
+            .iter()
+            .find(|(pattern, _, _)| model.contains(pattern))?;
+        let fresh = self.input_tokens.saturating_sub(self.cache_read_tokens);
+        let input_cost =
+            fresh as f64 * input + self.cache_read_tokens as f64 * input * CACHE_READ_PRICE;
+        Some((input_cost + self.output_tokens as f64 * output) / 1_000_000.0)
+    }
 }
 
 /// The result of a single thinker step: the step itself + optional token usage.
@@ -65,7 +153,42 @@ pub struct Context {
     pub history: Vec<MemoryEntry>,
     /// Summaries of prior tasks in this session (oldest first).
     pub session_history: Vec<SessionEntry>,
+    /// Earlier answers and observations similar to the task, best first.
+    pub relevant_memory: Vec<SemanticHit>,
+    /// Long-term facts saved with the `remember` tool.
+    pub facts: Vec<Fact>,
     pub available_tools: Vec<ToolDescription>,
+    /// An extra instruction from the engine, shown after the latest
+    /// observation: a nudge to rethink after several failed iterations in
+    /// a row, or a request for a plan instead of an action.
+    pub note: Option<String>,
+    /// Where the task stands against its iteration budget; `None` outside
+    /// the ReAct loop.
+    pub iteration: Option<IterationBudget>,
+    /// The persona the task runs as, added to the system prompt.
+    pub persona: Option<&'static Persona>,
+    /// The language thoughts and answers are written in.
+    pub language: Option<String>,
+}
+
+/// Iteration `current` of at most `max`, counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IterationBudget {
+    pub current: usize,
+    pub max: usize,
+}
+
+impl IterationBudget {
+    /// Iterations left after this one.
+    pub fn remaining(&self) -> usize {
+        self.max.saturating_sub(self.current)
+    }
+}
+
+impl std::fmt::Display for IterationBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "iteration {} of {}", self.current, self.max)
+    }
 }
 
 /// Describes a tool so the thinker knows what's available.
@@ -73,6 +196,7 @@ pub struct Context {
 pub struct ToolDescription {
     pub name: String,
     pub description: String,
+    pub capabilities: Capabilities,
 }
 
 /// Metadata about an available model.
@@ -101,17 +225,115 @@ pub trait Thinker: Send + Sync {
 
     /// Change the active model. Takes effect on the next `next_step` call.
     fn set_model(&mut self, model: String);
+
+    /// Which system prompt suits this thinker's provider: the JSON
+    /// protocol in full, a compact one for small models, or none for
+    /// providers that call tools natively.
+    fn prompt_variant(&self) -> PromptVariant {
+        PromptVariant::Standard
+    }
+
+    /// Log raw model requests and responses to `transcript`. Thinkers that
+    /// don't call a model can ignore this.
+    fn set_transcript(&mut self, _transcript: Arc<Transcript>) {}
+
+    /// Compress completed tasks into a short digest for later context.
+    /// The default lists each task with a truncated answer, for thinkers
+    /// that can't summarize on their own.
+    async fn summarize(&self, entries: &[SessionEntry]) -> Result<SummaryResult> {
+        let text = entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "- {} → {}",
+                    e.task,
+                    truncate(&e.answer, SUMMARY_ANSWER_CHARS)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(SummaryResult { text, usage: None })
+    }
+}
+
+/// Answers are cut to this many characters by the default `summarize`.
+const SUMMARY_ANSWER_CHARS: usize = 200;
+
+/// A session summary + optional token usage.
+pub struct SummaryResult {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
 }
 
 /// Parse an LLM text response into a `Step`. Handles JSON wrapped in
-/// markdown fences or preceded/followed by prose text.
+/// markdown fences or preceded/followed by prose text, JSON that
+/// [`repair::repair_json`] can fix, and several objects in one response
+/// (e.g. a plan, then the action), of which the first that is a step wins.
+///
+/// Errors are [`ParseError`]s; [`correction_prompt`] turns them into a
+/// request to try again.
 pub fn parse_response(text: &str) -> Result<Step> {
-    let json_str = extract_json(text);
+    let error = match parse_json(extract_json(text), text).and_then(|v| parse_step(&v, text)) {
+        Ok(step) => return Ok(step),
+        Err(e) => e,
+    };
+    let objects = json_objects(text);
+    if objects.len() > 1 {
+        // Later objects tend to be the model imagining what comes next
+        for object in objects {
+            if let Ok(step) = parse_json(object, text).and_then(|v| parse_step(&v, text)) {
+                return Ok(step);
+            }
+        }
+    }
+    Err(error.into())
+}
 
-    let response: serde_json::Value = serde_json::from_str(json_str).map_err(|e| {
-        anyhow::anyhow!("failed to parse LLM response as JSON: {}\nraw: {}", e, text)
-    })?;
+/// Parse `json`, repairing it if it needs to be; `text` is the whole
+/// response, for the error.
+fn parse_json(json: &str, text: &str) -> std::result::Result<serde_json::Value, ParseError> {
+    match serde_json::from_str(json) {
+        Ok(value) => Ok(value),
+        Err(e) => repair::repair_json(json)
+            .and_then(|fixed| serde_json::from_str(&fixed).ok())
+            .ok_or_else(|| ParseError::InvalidJson {
+                snippet: snippet(json, e.line(), e.column()),
+                error: e.to_string(),
+                raw: text.to_string(),
+            }),
+    }
+}
 
+/// The text of `json` around `line` and `column` (both from 1), on one
+/// line.
+fn snippet(json: &str, line: usize, column: usize) -> String {
+    let offset: usize = json
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>()
+        + column.saturating_sub(1);
+    let chars: Vec<char> = json.chars().collect();
+    let at = json.char_indices().take_while(|(i, _)| *i < offset).count();
+    let start = at.saturating_sub(SNIPPET_CHARS);
+    let end = (at + SNIPPET_CHARS).min(chars.len());
+    chars[start..end]
+        .iter()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The step a response object describes.
+fn parse_step(response: &serde_json::Value, text: &str) -> std::result::Result<Step, ParseError> {
     let thought = response
         .get("thought")
         .and_then(|v| v.as_str())
@@ -128,30 +350,12 @@ pub fn parse_response(text: &str) -> Result<Step> {
     if let Some(action) = response.get("action")
         && let Some(calls) = action.get("calls").and_then(|c| c.as_array())
     {
-        let tool_calls: Vec<ToolCall> = calls
-            .iter()
-            .filter_map(|call| {
-                let tool = call.get("tool")?.as_str()?.to_string();
-                let args_val = call.get("args")?;
-                let args: HashMap<String, String> = if let Some(obj) = args_val.as_object() {
-                    obj.iter()
-                        .map(|(k, v)| {
-                            let val = match v {
-                                serde_json::Value::String(s) => s.clone(),
-                                other => other.to_string(),
-                            };
-                            (k.clone(), val)
-                        })
-                        .collect()
-                } else {
-                    HashMap::new()
-                };
-                Some(ToolCall { tool, args })
-            })
-            .collect();
+        let tool_calls: Vec<ToolCall> = calls.iter().filter_map(parse_call).collect();
 
         if tool_calls.is_empty() {
-            bail!("LLM returned action with no valid tool calls: {}", text);
+            return Err(ParseError::NoCalls {
+                raw: text.to_string(),
+            });
         }
 
         return Ok(Step::Act {
@@ -160,10 +364,67 @@ pub fn parse_response(text: &str) -> Result<Step> {
         });
     }
 
-    bail!(
-        "LLM response is neither an answer nor a tool call: {}",
-        text
-    )
+    Err(ParseError::NotAStep {
+        raw: text.to_string(),
+    })
+}
+
+/// The tool call an object of `action.calls` describes, if it names one.
+fn parse_call(call: &serde_json::Value) -> Option<ToolCall> {
+    let tool = call.get("tool")?.as_str()?.to_string();
+    let args_val = call.get("args")?;
+    let args: HashMap<String, String> = if let Some(obj) = args_val.as_object() {
+        obj.iter()
+            .map(|(k, v)| {
+                let val = match v {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (k.clone(), val)
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    Some(ToolCall { tool, args })
+}
+
+/// Every balanced top-level `{…}` in `text`, in order. Braces inside
+/// strings don't count.
+pub fn json_objects(text: &str) -> Vec<&str> {
+    let mut objects = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' if depth > 0 => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    start = i;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    objects.push(&text[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    objects
 }
 
 /// Extract JSON from text that may be wrapped in markdown code fences or
@@ -213,6 +474,7 @@ mod tests {
         let usage = TokenUsage {
             input_tokens: 100,
             output_tokens: 50,
+            cache_read_tokens: 0,
         };
         assert_eq!(usage.total(), 150);
     }
@@ -222,21 +484,37 @@ mod tests {
         let mut usage = TokenUsage {
             input_tokens: 100,
             output_tokens: 50,
+            cache_read_tokens: 0,
         };
         usage.add(TokenUsage {
             input_tokens: 200,
             output_tokens: 75,
+            cache_read_tokens: 0,
         });
         assert_eq!(usage.input_tokens, 300);
         assert_eq!(usage.output_tokens, 125);
         assert_eq!(usage.total(), 425);
     }
 
+    #[test]
+    fn token_usage_cost_uses_model_price() {
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 0,
+        };
+        assert_eq!(usage.cost("claude-sonnet-4-20250514"), Some(4.5));
+        assert_eq!(usage.cost("claude-opus-4-5-20251101"), Some(7.5));
+        assert_eq!(usage.cost("claude-3-haiku-20240307"), Some(0.375));
+        assert_eq!(usage.cost("mock"), None);
+    }
+
     #[test]
     fn token_usage_add_zero_is_noop() {
         let mut usage = TokenUsage {
             input_tokens: 100,
             output_tokens: 50,
+            cache_read_tokens: 0,
         };
         usage.add(TokenUsage::default());
         assert_eq!(usage.input_tokens, 100);
@@ -298,6 +576,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_repairs_malformed_json() {
+        let text = "{'thought': 'listing',\n 'action': {'calls': [{'tool': 'shell', 'args': {'command': 'ls',},},]";
+        match parse_response(text).unwrap() {
+            Step::Act { thought, calls } => {
+                assert_eq!(thought, "listing");
+                assert_eq!(calls[0].args["command"], "ls");
+            }
+            _ => panic!("expected Act"),
+        }
+    }
+
+    #[test]
+    fn parse_refuses_calls_cut_off_mid_args() {
+        let text = r#"{"thought": "check", "action": {"calls": [{"tool": "shell", "args": {"command": "df -h"#;
+        let err = parse_response(text).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParseError>(),
+            Some(ParseError::InvalidJson { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_picks_the_step_among_several_objects() {
+        let text = r#"Plan: {"plan": ["check disk", "report"]}
+Now: {"thought": "checking {disk}", "action": {"calls": [{"tool": "shell", "args": {"command": "df -h"}}]}}
+{"thought": "done", "answer": "plenty"}"#;
+        assert_eq!(json_objects(text).len(), 3);
+        match parse_response(text).unwrap() {
+            Step::Act { thought, calls } => {
+                assert_eq!(thought, "checking {disk}");
+                assert_eq!(calls[0].args["command"], "df -h");
+            }
+            _ => panic!("expected Act"),
+        }
+
+        let text = r#"{"plan": "x"} {"thought": "t", "answer": "42"}"#;
+        match parse_response(text).unwrap() {
+            Step::Finish { answer, .. } => assert_eq!(answer, "42"),
+            _ => panic!("expected Finish"),
+        }
+    }
+
+    #[test]
+    fn corrections_say_what_was_wrong() {
+        let error = parse_response("{\"thought\": \"x\" \"answer\": 42}").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ParseError>(),
+            Some(ParseError::InvalidJson { .. })
+        ));
+        let prompt = correction_prompt(&error);
+        assert!(
+            prompt.contains("expected `,` or `}` at line 1 column 17"),
+            "{prompt}"
+        );
+        assert!(
+            prompt.contains("at `{\"thought\": \"x\" \"answer\": 42}`"),
+            "{prompt}"
+        );
+
+        let error = parse_response(r#"{"thought": "hmm"}"#).unwrap_err();
+        let prompt = correction_prompt(&error);
+        assert!(
+            prompt.starts_with("Your previous response was valid JSON but had neither"),
+            "{prompt}"
+        );
+
+        let error = parse_response(r#"{"action": {"calls": [{"command": "ls"}]}}"#).unwrap_err();
+        assert!(correction_prompt(&error).contains("\"tool\" and \"args\""));
+    }
+
+    #[test]
+    fn snippets_are_cut_around_the_error() {
+        let json = format!(
+            "{{\"a\":\n\"{}\" oops {}}}",
+            "x".repeat(100),
+            "y".repeat(100)
+        );
+        let error = serde_json::from_str::<serde_json::Value>(&json).unwrap_err();
+        let snippet = snippet(&json, error.line(), error.column());
+        // On one line, with the error in the middle
+        assert!(snippet.starts_with("xxx"), "{snippet}");
+        assert!(snippet.contains("\" oops y"), "{snippet}");
+        assert!(snippet.ends_with("yyy"), "{snippet}");
+        assert!(snippet.chars().count() <= 2 * SNIPPET_CHARS, "{snippet}");
+    }
+
     #[test]
     fn parse_fenced_json() {
         let text = "```json\n{\"thought\": \"done\", \"answer\": \"hello\"}\n```";
@@ -0,0 +1,195 @@
+//! Best-effort repair of the JSON models get almost right.
+//!
+//! Weaker models leave trailing commas, write raw line breaks or single
+//! quotes in strings, or stop before closing every brace. Fixing those
+//! here saves a retry round-trip; [`parse_response`](super::parse_response)
+//! only tries it once plain parsing has failed.
+
+/// `text` with common JSON mistakes fixed: prose before the first `{`
+/// dropped, trailing commas removed, line breaks and tabs inside strings
+/// escaped, single-quoted strings turned into double-quoted ones, and
+/// unclosed strings, arrays and objects closed. `None` if there is
+/// nothing to fix, or if it was cut off inside a tool call's args: run
+/// with half its arguments, `rm -rf build/cache` is `rm -rf build`.
+pub fn repair_json(text: &str) -> Option<String> {
+    let start = text.find('{')?;
+    let mut out = String::with_capacity(text.len() + 8);
+    // The brackets still open, innermost last
+    let mut open: Vec<char> = Vec::new();
+    // The key each open bracket is the value of, `""` for array items
+    let mut path: Vec<String> = Vec::new();
+    // The last string read, and the key it became once a `:` followed
+    let mut string = String::new();
+    let mut key: Option<String> = None;
+    // The quote of the string we are in, if any
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for c in text[start..].chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                    // JSON has no `\'`
+                    if c == '\'' {
+                        out.pop();
+                    }
+                    out.push(c);
+                    string.push(c);
+                    continue;
+                }
+                match c {
+                    '\\' => {
+                        escaped = true;
+                        out.push(c);
+                    }
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c if c == q => {
+                        quote = None;
+                        out.push('"');
+                    }
+                    '"' => out.push_str("\\\""),
+                    c => out.push(c),
+                }
+                if quote.is_some() {
+                    string.push(c);
+                }
+            }
+            None => match c {
+                '"' | '\'' => {
+                    quote = Some(c);
+                    string.clear();
+                    out.push('"');
+                }
+                ':' => {
+                    key = Some(std::mem::take(&mut string));
+                    out.push(c);
+                }
+                '{' | '[' => {
+                    open.push(if c == '{' { '}' } else { ']' });
+                    path.push(key.take().unwrap_or_default());
+                    out.push(c);
+                }
+                '}' | ']' => {
+                    drop_trailing_comma(&mut out);
+                    if open.last() == Some(&c) {
+                        open.pop();
+                        path.pop();
+                    }
+                    out.push(c);
+                    // Anything after the outermost object is prose
+                    if open.is_empty() {
+                        break;
+                    }
+                }
+                c => out.push(c),
+            },
+        }
+    }
+
+    // Cut off mid-way: finish the string, then close what is still open
+    let in_args = path
+        .iter()
+        .skip_while(|key| *key != "action")
+        .skip_while(|key| *key != "calls")
+        .any(|key| key == "args");
+    if in_args {
+        return None;
+    }
+    if quote.is_some() {
+        if escaped {
+            out.pop();
+        }
+        out.push('"');
+    }
+    drop_trailing_comma(&mut out);
+    if out.trim_end().ends_with(':') {
+        out.push_str(" null");
+    }
+    while let Some(close) = open.pop() {
+        out.push(close);
+    }
+
+    (out != text).then_some(out)
+}
+
+fn drop_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end();
+    if trimmed.ends_with(',') {
+        out.truncate(trimmed.len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn repaired(text: &str) -> Value {
+        let fixed = repair_json(text).expect("something to repair");
+        serde_json::from_str(&fixed).unwrap_or_else(|e| panic!("{e}: {fixed}"))
+    }
+
+    #[test]
+    fn removes_trailing_commas() {
+        assert_eq!(
+            repaired(
+                r#"{"action": {"calls": [{"tool": "shell", "args": {"command": "ls",},},],},}"#
+            ),
+            json!({"action": {"calls": [{"tool": "shell", "args": {"command": "ls"}}]}})
+        );
+    }
+
+    #[test]
+    fn escapes_line_breaks_in_strings() {
+        assert_eq!(
+            repaired("{\"thought\": \"done\", \"answer\": \"line one\nline two\tend\"}"),
+            json!({"thought": "done", "answer": "line one\nline two\tend"})
+        );
+    }
+
+    #[test]
+    fn turns_single_quotes_into_double() {
+        assert_eq!(
+            repaired(r#"{'thought': 'say "hi"', 'answer': 'it\'s here'}"#),
+            json!({"thought": "say \"hi\"", "answer": "it's here"})
+        );
+    }
+
+    #[test]
+    fn closes_truncated_objects() {
+        assert_eq!(
+            repaired(
+                r#"{"thought": "check", "action": {"calls": [{"tool": "shell", "args": {"command": "df -h"}}"#
+            ),
+            json!({"thought": "check", "action": {"calls": [{"tool": "shell", "args": {"command": "df -h"}}]}})
+        );
+        assert_eq!(
+            repaired(r#"{"thought": "done", "answer": "the disk is fu"#),
+            json!({"thought": "done", "answer": "the disk is fu"})
+        );
+        assert_eq!(
+            repaired(r#"Sure: {"thought": "x", "answer":"#),
+            json!({"thought": "x", "answer": null})
+        );
+    }
+
+    #[test]
+    fn refuses_to_close_truncated_call_args() {
+        for text in [
+            r#"{"thought": "clean", "action": {"calls": [{"tool": "shell", "args": {"command": "rm -rf build"#,
+            r#"{"action": {"calls": [{"tool": "shell", "args": {"command": "ls", "timeout": 3"#,
+            r#"{'action': {'calls': [{'tool': 'shell', 'args': {'comm"#,
+        ] {
+            assert_eq!(repair_json(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn leaves_valid_json_alone() {
+        assert_eq!(repair_json(r#"{"answer": "a, b}"}"#), None);
+        assert_eq!(repair_json("no json here"), None);
+    }
+}
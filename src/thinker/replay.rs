@@ -0,0 +1,141 @@
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::{
+    Context, DEFAULT_PARSE_RETRIES, ModelInfo, StepResult, Thinker, TokenUsage, parse_response,
+};
+
+/// A model response captured in a transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedResponse {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+}
+
+/// Responses waiting to be replayed, shared so the caller can load the
+/// next task's while the thinker sits inside an engine.
+pub type ResponseQueue = Arc<Mutex<VecDeque<RecordedResponse>>>;
+
+/// Answers from a recording instead of a model, for `golem replay`.
+/// Parses recorded text exactly like a live thinker, including the
+/// correction rounds after a response that isn't a step.
+pub struct ReplayThinker {
+    model: String,
+    responses: ResponseQueue,
+    parse_retries: usize,
+}
+
+impl ReplayThinker {
+    pub fn new(model: String, responses: ResponseQueue) -> Self {
+        Self {
+            model,
+            responses,
+            parse_retries: DEFAULT_PARSE_RETRIES,
+        }
+    }
+
+    /// Replay up to `retries` correction rounds, as the recording made.
+    pub fn with_parse_retries(mut self, retries: usize) -> Self {
+        self.parse_retries = retries;
+        self
+    }
+
+    fn next_response(&self) -> Result<RecordedResponse> {
+        match self
+            .responses
+            .lock()
+            .expect("replay queue lock poisoned")
+            .pop_front()
+        {
+            Some(response) => Ok(response),
+            None => bail!("the recording has no more model responses"),
+        }
+    }
+}
+
+#[async_trait]
+impl Thinker for ReplayThinker {
+    async fn models(&self) -> Result<Vec<ModelInfo>> {
+        Ok(vec![])
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, _model: String) {
+        // The recording decides what the model said
+    }
+
+    async fn next_step(&self, _context: &Context) -> Result<StepResult> {
+        let mut total_usage = TokenUsage::default();
+        for attempt in 0..=self.parse_retries {
+            let response = self.next_response()?;
+            if let Some(usage) = response.usage {
+                total_usage.add(usage);
+            }
+            match parse_response(&response.text) {
+                Ok(step) => {
+                    let usage = (total_usage.total() > 0).then_some(total_usage);
+                    return Ok(StepResult { step, usage });
+                }
+                Err(e) if attempt == self.parse_retries => return Err(e),
+                Err(_) => {}
+            }
+        }
+        bail!("unexpected: parse retry loop exited without result")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thinker::Step;
+
+    fn response(text: &str) -> RecordedResponse {
+        RecordedResponse {
+            text: text.to_string(),
+            usage: Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 1,
+                cache_read_tokens: 0,
+            }),
+        }
+    }
+
+    fn context() -> Context {
+        Context {
+            task: "t".to_string(),
+            history: vec![],
+            session_history: vec![],
+            relevant_memory: vec![],
+            facts: vec![],
+            available_tools: vec![],
+            note: None,
+            iteration: None,
+            persona: None,
+            language: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_in_order_and_retries_bad_json() {
+        let queue: ResponseQueue = Arc::new(Mutex::new(VecDeque::from([
+            response("not json"),
+            response(r#"{"thought": "done", "answer": "42"}"#),
+        ])));
+        let thinker = ReplayThinker::new("recorded".to_string(), Arc::clone(&queue));
+
+        let result = thinker.next_step(&context()).await.unwrap();
+        assert!(matches!(result.step, Step::Finish { ref answer, .. } if answer == "42"));
+        assert_eq!(result.usage.unwrap().input_tokens, 20);
+        assert_eq!(thinker.model(), "recorded");
+
+        let Err(err) = thinker.next_step(&context()).await else {
+            panic!("expected the recording to run out");
+        };
+        assert!(err.to_string().contains("no more model responses"));
+    }
+}
@@ -0,0 +1,187 @@
+//! `delegate`: hand a self-contained subtask to a sub-agent.
+//!
+//! The sub-agent is a fresh `ReactEngine` with its own in-process memory
+//! and a restricted tool set. Only its final answer comes back as the
+//! observation, so a big multi-part job doesn't flood the parent's context
+//! with every intermediate step. It runs under the parent task's live
+//! config, hooks and event bus, is capped at what is left of the parent's
+//! budgets, and its iterations and tokens are charged to the parent task,
+//! through [`crate::engine::meter`].
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::TryRecvError;
+
+use super::{Capabilities, Tool, ToolRegistry, ToolResult, ToolSource};
+use crate::engine::Engine;
+use crate::engine::hooks::{Hook, ToolDecision};
+use crate::engine::meter::{caller, charge};
+use crate::engine::react::{ReactConfig, ReactEngine};
+use crate::events::{EventBus, EventKind};
+use crate::memory::in_memory::InMemoryMemory;
+use crate::thinker::{Context, Step, Thinker, ToolCall};
+
+const NAME: &str = "delegate";
+
+/// Runs a subtask in a child engine and returns its answer.
+pub struct DelegateTool {
+    thinker: Arc<RwLock<Box<dyn Thinker>>>,
+    /// The parent's tools. Weak because this tool is registered in it.
+    tools: Weak<ToolRegistry>,
+}
+
+impl DelegateTool {
+    /// Sub-agents take their config from the task calling the tool, or
+    /// the defaults when it's called outside one.
+    pub fn new(thinker: Arc<RwLock<Box<dyn Thinker>>>, tools: &Arc<ToolRegistry>) -> Self {
+        Self {
+            thinker,
+            tools: Arc::downgrade(tools),
+        }
+    }
+}
+
+/// One of the parent's hooks, run inside a sub-agent: it sees the
+/// sub-agent's steps and tool calls, but not its start and end, which
+/// aren't the parent's.
+struct Nested(Arc<dyn Hook>);
+
+#[async_trait]
+impl Hook for Nested {
+    async fn on_step_start(&self, context: &Context) {
+        self.0.on_step_start(context).await;
+    }
+
+    async fn on_step(&self, step: &Step) {
+        self.0.on_step(step).await;
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall) -> ToolDecision {
+        self.0.on_tool_call(call).await
+    }
+
+    async fn on_tool_result(&self, call: &ToolCall, result: &ToolResult) {
+        self.0.on_tool_result(call, result).await;
+    }
+}
+
+#[async_trait]
+impl Tool for DelegateTool {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn description(&self) -> &str {
+        "Hand a self-contained subtask to a sub-agent with a clean memory; only its final answer comes back. Use it to keep your own context small on big multi-part jobs. Args: {\"task\": \"<complete instructions>\", \"tools\": \"<optional comma-separated tool names; default all>\"}"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        // The sub-agent's own tool calls are checked (and confirmed) one by one
+        Capabilities {
+            read_only: false,
+            network: false,
+            destructive: false,
+            needs_confirmation: false,
+        }
+    }
+
+    /// The sub-agent is bounded by its own iteration cap and tool timeouts.
+    fn uses_timeout(&self) -> bool {
+        false
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::BuiltIn
+    }
+
+    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+        let task = args
+            .get("task")
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("missing required arg: task"))?;
+        let Some(parent) = self.tools.upgrade() else {
+            bail!("tool registry is gone");
+        };
+
+        let available = parent.names().await;
+        let allowed: Vec<String> = match args.get("tools").map(|t| t.trim()) {
+            None | Some("") => available
+                .iter()
+                .filter(|name| *name != NAME)
+                .cloned()
+                .collect(),
+            Some(list) => {
+                let requested: Vec<String> = list
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                for name in &requested {
+                    if name == NAME {
+                        bail!("a sub-agent can't delegate further");
+                    }
+                    if !available.contains(name) {
+                        bail!("unknown tool: {name}");
+                    }
+                }
+                requested
+            }
+        };
+
+        let caller = caller();
+        let config = caller
+            .as_ref()
+            .map_or_else(ReactConfig::default, |c| c.config.clone());
+        if config.max_iterations == 0 || config.max_tokens_per_task == Some(0) {
+            bail!("no budget left for a sub-agent; finish the task yourself");
+        }
+
+        let tools = parent
+            .restricted(|name| allowed.iter().any(|a| a == name))
+            .await;
+        let echo = config.echo;
+        let mut child = ReactEngine::with_shared_thinker(
+            Arc::clone(&self.thinker),
+            Arc::new(tools),
+            Box::new(InMemoryMemory::new()),
+            config,
+        );
+        // The parent's listeners follow the sub-agent's work, but its start
+        // and finish would read as tasks of their own
+        let events = Arc::new(match &caller {
+            Some(caller) => EventBus::relaying_to(
+                Arc::clone(&caller.events),
+                &[EventKind::TaskStarted, EventKind::TaskFinished],
+            ),
+            None => EventBus::default(),
+        });
+        for hook in caller.iter().flat_map(|c| &c.hooks) {
+            child.add_hook(Arc::new(Nested(Arc::clone(hook))));
+        }
+        child.set_events(Arc::clone(&events));
+        // Counted from events, so a run that fails is charged too
+        let mut iterations = events.subscribe_filtered(&[EventKind::IterationStarted]);
+        if echo {
+            println!("  ↳ delegating: {task}");
+        }
+        let result = child.run(task).await;
+        let mut spent = 0;
+        loop {
+            match iterations.try_recv() {
+                Ok(_) => spent += 1,
+                Err(TryRecvError::Lagged(missed)) => spent += missed as usize,
+                Err(_) => break,
+            }
+        }
+        charge(spent, child.session_usage_by_model());
+        let result = result?;
+        if echo {
+            println!("  ↲ delegate finished");
+        }
+        Ok(result.answer)
+    }
+}
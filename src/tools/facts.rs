@@ -0,0 +1,101 @@
+//! `remember` / `forget`: let the agent manage its long-term facts.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{Capabilities, Tool, ToolSource};
+use crate::memory::facts::FactStore;
+
+/// Both tools only touch the agent's own database.
+const FACT_CAPABILITIES: Capabilities = Capabilities {
+    read_only: false,
+    network: false,
+    destructive: false,
+    needs_confirmation: false,
+};
+
+/// Stores a durable fact that is shown in every future system prompt.
+pub struct RememberTool {
+    facts: Arc<FactStore>,
+}
+
+impl RememberTool {
+    pub fn new(facts: Arc<FactStore>) -> Self {
+        Self { facts }
+    }
+}
+
+#[async_trait]
+impl Tool for RememberTool {
+    fn name(&self) -> &str {
+        "remember"
+    }
+
+    fn description(&self) -> &str {
+        "Save a durable fact about the user or their environment for all future sessions (preferences, hostnames, conventions). Args: {\"fact\": \"<one short sentence>\"}"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        FACT_CAPABILITIES
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::BuiltIn
+    }
+
+    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+        let fact = args
+            .get("fact")
+            .ok_or_else(|| anyhow::anyhow!("missing required arg: fact"))?;
+        let id = self.facts.remember(fact)?;
+        Ok(format!("remembered fact {id}"))
+    }
+}
+
+/// Removes a fact by the id shown in the system prompt.
+pub struct ForgetTool {
+    facts: Arc<FactStore>,
+}
+
+impl ForgetTool {
+    pub fn new(facts: Arc<FactStore>) -> Self {
+        Self { facts }
+    }
+}
+
+#[async_trait]
+impl Tool for ForgetTool {
+    fn name(&self) -> &str {
+        "forget"
+    }
+
+    fn description(&self) -> &str {
+        "Remove a remembered fact that is wrong or outdated. Args: {\"id\": \"<fact id>\"}"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        FACT_CAPABILITIES
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::BuiltIn
+    }
+
+    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+        let id = args
+            .get("id")
+            .ok_or_else(|| anyhow::anyhow!("missing required arg: id"))?;
+        let id: i64 = id
+            .trim()
+            .trim_start_matches('#')
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid fact id: {id}"))?;
+        if self.facts.forget(id)? {
+            Ok(format!("forgot fact {id}"))
+        } else {
+            anyhow::bail!("no fact with id {id}")
+        }
+    }
+}
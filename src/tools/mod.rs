@@ -1,7 +1,10 @@
+pub mod delegate;
+pub mod facts;
 pub mod shell;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -22,17 +25,200 @@ pub struct ToolResult {
     pub outcome: Outcome,
 }
 
+/// What a tool may do when executed. Lets the registry, approval and the
+/// system prompt treat tools generically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Never modifies anything outside the agent.
+    pub read_only: bool,
+    /// May reach the network.
+    pub network: bool,
+    /// May irreversibly change or delete data.
+    pub destructive: bool,
+    /// Every call must be approved before it runs.
+    pub needs_confirmation: bool,
+}
+
+impl Default for Capabilities {
+    /// Tools that don't declare capabilities are assumed to do anything.
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            network: true,
+            destructive: true,
+            needs_confirmation: true,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Short labels for display, e.g. `["read-only"]`.
+    pub fn labels(&self) -> Vec<&'static str> {
+        let mut labels = Vec::new();
+        if self.read_only {
+            labels.push("read-only");
+        }
+        if self.network {
+            labels.push("network");
+        }
+        if self.destructive {
+            labels.push("destructive");
+        }
+        if self.needs_confirmation {
+            labels.push("confirm");
+        }
+        labels
+    }
+}
+
+/// Where a tool comes from, for `/tools -v`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolSource {
+    /// Ships with golem.
+    BuiltIn,
+    /// Registered by a program embedding golem.
+    Plugin,
+}
+
+impl ToolSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ToolSource::BuiltIn => "built-in",
+            ToolSource::Plugin => "plugin",
+        }
+    }
+}
+
 /// Something the agent can do.
 #[async_trait]
 pub trait Tool: Send + Sync {
     fn name(&self) -> &str;
+    /// What the model is told about the tool, ending in or containing its
+    /// args as `Args: {"name": "<what it holds>"}`.
     fn description(&self) -> &str;
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+    /// Tools golem ships override this; anything else was plugged in.
+    fn source(&self) -> ToolSource {
+        ToolSource::Plugin
+    }
+    /// Whether the engine's per-call timeout applies. Tools that bound
+    /// their own run time can opt out.
+    fn uses_timeout(&self) -> bool {
+        true
+    }
+    /// Refuse a call that can't run, before anyone is asked to approve
+    /// it. The error is what the model sees.
+    fn precheck(&self, _args: &HashMap<String, String>) -> Result<()> {
+        Ok(())
+    }
     async fn execute(&self, args: &HashMap<String, String>) -> Result<String>;
 }
 
+/// Which tools the registry will offer and run, by capability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolPolicy {
+    pub allow_network: bool,
+    pub allow_destructive: bool,
+}
+
+impl Default for ToolPolicy {
+    fn default() -> Self {
+        Self {
+            allow_network: true,
+            allow_destructive: true,
+        }
+    }
+}
+
+impl ToolPolicy {
+    pub fn permits(&self, caps: &Capabilities) -> bool {
+        (self.allow_network || !caps.network) && (self.allow_destructive || !caps.destructive)
+    }
+}
+
+/// Everything `/tools -v` shows about a tool.
+#[derive(Debug, Clone)]
+pub struct ToolDetails {
+    pub name: String,
+    /// The description without its `Args: {…}` part.
+    pub summary: String,
+    /// Each arg with what it holds, from the description. `None` if the
+    /// description doesn't list them in the usual form.
+    pub args: Option<Vec<(String, String)>>,
+    pub capabilities: Capabilities,
+    pub uses_timeout: bool,
+    pub source: ToolSource,
+}
+
+/// Split a tool description into its prose and the args it lists as
+/// `Args: {"name": "<what it holds>"}`.
+pub fn split_args(description: &str) -> (String, Option<Vec<(String, String)>>) {
+    let Some(start) = description.find("Args:") else {
+        return (description.to_string(), None);
+    };
+    let json = description[start + "Args:".len()..].trim_start();
+    let mut stream =
+        serde_json::Deserializer::from_str(json).into_iter::<serde_json::Map<String, Value>>();
+    let Some(Ok(map)) = stream.next() else {
+        return (description.to_string(), None);
+    };
+    let after = json[stream.byte_offset()..].trim_start_matches('.').trim();
+    let before = description[..start].trim_end();
+    let summary = if after.is_empty() {
+        before.to_string()
+    } else {
+        format!("{before} {after}")
+    };
+    let args = map
+        .into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            (name, text)
+        })
+        .collect();
+    (summary, Some(args))
+}
+
+/// One-line rendering of tool arguments for display.
+pub fn format_args(args: &HashMap<String, String>) -> String {
+    // Single-arg tools (like shell) read better without the key
+    if args.len() == 1 {
+        args.values().next().cloned().unwrap_or_default()
+    } else {
+        let mut pairs: Vec<_> = args.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        pairs.sort();
+        pairs.join(" ")
+    }
+}
+
+/// Decides whether a call to a tool that needs confirmation may run.
+#[async_trait]
+pub trait Approver: Send + Sync {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool>;
+}
+
+/// Asks the user at the terminal.
+pub struct PromptApprover;
+
+#[async_trait]
+impl Approver for PromptApprover {
+    async fn approve(&self, tool: &str, args: &HashMap<String, String>) -> Result<bool> {
+        crate::prompt::terminal()
+            .confirm(&format!("  Execute {tool}: {} [y/N] ", format_args(args)))
+            .await
+    }
+}
+
 /// Holds all registered tools. RwLock allows runtime registration + parallel reads.
 pub struct ToolRegistry {
     tools: RwLock<HashMap<String, Arc<dyn Tool>>>,
+    policy: RwLock<ToolPolicy>,
+    approver: Arc<dyn Approver>,
 }
 
 impl Default for ToolRegistry {
@@ -43,11 +229,71 @@ impl Default for ToolRegistry {
 
 impl ToolRegistry {
     pub fn new() -> Self {
+        Self::with_approver(Box::new(PromptApprover))
+    }
+
+    /// Use a custom approver for tools that need confirmation.
+    pub fn with_approver(approver: Box<dyn Approver>) -> Self {
         Self {
             tools: RwLock::new(HashMap::new()),
+            policy: RwLock::new(ToolPolicy::default()),
+            approver: Arc::from(approver),
         }
     }
 
+    /// A registry with only the tools `keep` accepts, sharing this one's
+    /// current policy and approver.
+    pub async fn restricted(&self, keep: impl Fn(&str) -> bool) -> ToolRegistry {
+        let tools = self
+            .tools
+            .read()
+            .await
+            .iter()
+            .filter(|(name, _)| keep(name))
+            .map(|(name, tool)| (name.clone(), Arc::clone(tool)))
+            .collect();
+        ToolRegistry {
+            tools: RwLock::new(tools),
+            policy: RwLock::new(self.policy().await),
+            approver: Arc::clone(&self.approver),
+        }
+    }
+
+    /// Whether calls to `name` should be cut off by the engine's timeout.
+    /// Unknown tools fail immediately, so the answer doesn't matter for them.
+    pub async fn uses_timeout(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .await
+            .get(name)
+            .is_none_or(|tool| tool.uses_timeout())
+    }
+
+    /// Whether calls to `name` ask the user before they run.
+    pub async fn needs_confirmation(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .await
+            .get(name)
+            .is_some_and(|tool| tool.capabilities().needs_confirmation)
+    }
+
+    /// Names of all registered tools, sorted.
+    pub async fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.tools.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub async fn policy(&self) -> ToolPolicy {
+        *self.policy.read().await
+    }
+
+    /// Restrict which tools are offered and run. Takes effect on the next call.
+    pub async fn set_policy(&self, policy: ToolPolicy) {
+        *self.policy.write().await = policy;
+    }
+
     pub async fn register(&self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
         self.tools.write().await.insert(name, tool);
@@ -57,34 +303,117 @@ impl ToolRegistry {
         self.tools.write().await.remove(name);
     }
 
-    pub async fn execute(&self, tool_name: &str, args: &HashMap<String, String>) -> ToolResult {
-        let tools = self.tools.read().await;
-        match tools.get(tool_name) {
+    /// Check a call against the policy and the tool's precheck, then ask
+    /// the approver if the tool needs confirmation. The error is the result
+    /// to report instead of running the call.
+    pub async fn approve(
+        &self,
+        tool_name: &str,
+        args: &HashMap<String, String>,
+    ) -> Result<(), ToolResult> {
+        let error = |msg: String| ToolResult {
+            tool: tool_name.to_string(),
+            outcome: Outcome::Error(msg),
+        };
+
+        let tool = match self.tools.read().await.get(tool_name) {
+            Some(tool) => Arc::clone(tool),
+            None => return Err(error(format!("unknown tool: {}", tool_name))),
+        };
+
+        let caps = tool.capabilities();
+        if !self.policy().await.permits(&caps) {
+            return Err(error(format!(
+                "blocked: tool '{tool_name}' is disabled by policy"
+            )));
+        }
+        if let Err(e) = tool.precheck(args) {
+            return Err(error(e.to_string()));
+        }
+        if caps.needs_confirmation {
+            match self.approver.approve(tool_name, args).await {
+                Ok(true) => {}
+                Ok(false) => return Err(error("cancelled by user".to_string())),
+                Err(e) => return Err(error(e.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a call [`approve`](Self::approve) has let through, without
+    /// asking again.
+    pub async fn execute_approved(
+        &self,
+        tool_name: &str,
+        args: &HashMap<String, String>,
+    ) -> ToolResult {
+        let tool = self.tools.read().await.get(tool_name).map(Arc::clone);
+        let outcome = match tool {
             Some(tool) => match tool.execute(args).await {
-                Ok(output) => ToolResult {
-                    tool: tool_name.to_string(),
-                    outcome: Outcome::Success(output),
-                },
-                Err(e) => ToolResult {
-                    tool: tool_name.to_string(),
-                    outcome: Outcome::Error(e.to_string()),
-                },
-            },
-            None => ToolResult {
-                tool: tool_name.to_string(),
-                outcome: Outcome::Error(format!("unknown tool: {}", tool_name)),
+                Ok(output) => Outcome::Success(output),
+                Err(e) => Outcome::Error(e.to_string()),
             },
+            None => Outcome::Error(format!("unknown tool: {}", tool_name)),
+        };
+        ToolResult {
+            tool: tool_name.to_string(),
+            outcome,
         }
     }
 
+    /// Approve a call, then run it.
+    pub async fn execute(&self, tool_name: &str, args: &HashMap<String, String>) -> ToolResult {
+        match self.approve(tool_name, args).await {
+            Ok(()) => self.execute_approved(tool_name, args).await,
+            Err(refusal) => refusal,
+        }
+    }
+
+    /// Details of every tool the current policy permits, by name.
+    pub async fn details(&self) -> Vec<ToolDetails> {
+        let policy = self.policy().await;
+        let mut details: Vec<ToolDetails> = self
+            .tools
+            .read()
+            .await
+            .values()
+            .filter(|t| policy.permits(&t.capabilities()))
+            .map(|t| {
+                let (summary, args) = split_args(t.description());
+                ToolDetails {
+                    name: t.name().to_string(),
+                    summary,
+                    args,
+                    capabilities: t.capabilities(),
+                    uses_timeout: t.uses_timeout(),
+                    source: t.source(),
+                }
+            })
+            .collect();
+        details.sort_by(|a, b| a.name.cmp(&b.name));
+        details
+    }
+
+    /// Descriptions of every tool the current policy permits.
     pub async fn descriptions(&self) -> Vec<ToolDescription> {
+        let policy = self.policy().await;
+        self.descriptions_where(|caps| policy.permits(caps)).await
+    }
+
+    /// Descriptions of the tools whose capabilities match `filter`.
+    pub async fn descriptions_where(
+        &self,
+        filter: impl Fn(&Capabilities) -> bool,
+    ) -> Vec<ToolDescription> {
         self.tools
             .read()
             .await
             .values()
+            .filter(|t| filter(&t.capabilities()))
             .map(|t| ToolDescription {
                 name: t.name().to_string(),
                 description: t.description().to_string(),
+                capabilities: t.capabilities(),
             })
             .collect()
     }
@@ -0,0 +1,1674 @@
+//! Read/write/blocked classification of parsed shell commands.
+//!
+//! Decisions are made on the real argv of each simple command (after
+//! quote removal and unwrapping `sudo`, `env`, `xargs`, `sh -c`, ...),
+//! so `ls copy/` is a read while `r""m -rf x` is still a write. Only
+//! programs known to read are reads; anything else is a write.
+
+use anyhow::Result;
+
+use super::parser::{self, Script, SimpleCommand, Word};
+
+/// Safety verdict for a command string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// Only reads state.
+    Read,
+    /// Modifies files, processes, or remote state. Carries the offending program.
+    Write(String),
+    /// Never allowed, regardless of mode. Carries the reason.
+    Blocked(String),
+}
+
+impl Verdict {
+    fn severity(&self) -> u8 {
+        match self {
+            Verdict::Read => 0,
+            Verdict::Write(_) => 1,
+            Verdict::Blocked(_) => 2,
+        }
+    }
+
+    fn max(self, other: Verdict) -> Verdict {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Classify a raw command string. Fails if the command cannot be parsed.
+pub fn classify(cmd: &str) -> Result<Verdict> {
+    classify_script(&parser::parse(cmd)?, 0)
+}
+
+/// Programs that always modify state.
+const WRITE_PROGRAMS: &[&str] = &[
+    "rm", "rmdir", "mv", "cp", "mkdir", "touch", "chmod", "chown", "chgrp", "ln", "install",
+    "truncate", "shred", "unlink", "fdisk", "sfdisk", "parted", "mkswap", "swapon", "swapoff",
+    "mount", "umount", "kill", "killall", "pkill", "rsync", "scp", "patch", "chattr", "setfacl",
+    "mknod", "mkfifo", "useradd", "userdel", "usermod", "groupadd", "groupdel", "passwd",
+    "modprobe", "insmod", "rmmod", "split", "yay", "paru",
+];
+
+/// Programs that take down the machine.
+const POWER_PROGRAMS: &[&str] = &["shutdown", "reboot", "halt", "poweroff"];
+
+/// Shells whose `-c` argument is itself a script.
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "ash", "fish"];
+
+/// Block devices that must never be written to directly.
+const BLOCK_DEVICE_PREFIXES: &[&str] = &[
+    "/dev/sd",
+    "/dev/hd",
+    "/dev/vd",
+    "/dev/xvd",
+    "/dev/nvme",
+    "/dev/mmcblk",
+    "/dev/disk",
+];
+
+/// Writing programs whose device arguments are mount sources, not write targets.
+const DEVICE_USERS: &[&str] = &["mount", "umount", "swapon", "swapoff"];
+
+/// Interpreters that run inline code (`-c`, `-e`) or a script file.
+const INTERPRETERS: &[&str] = &[
+    "python", "python2", "python3", "node", "nodejs", "deno", "bun", "perl", "ruby", "php", "lua",
+    "tclsh", "Rscript",
+];
+
+/// Programs that run their arguments on another machine.
+const REMOTE_PROGRAMS: &[&str] = &["ssh", "rsh", "mosh", "telnet"];
+
+/// Redirect targets that discard or echo output rather than writing a file.
+const HARMLESS_SINKS: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr", "/dev/tty"];
+
+/// Programs and builtins that only read, whatever their arguments. The
+/// ones whose arguments decide are matched in [`is_write`].
+const READ_PROGRAMS: &[&str] = &[
+    "ls",
+    "dir",
+    "cat",
+    "tac",
+    "head",
+    "tail",
+    "less",
+    "more",
+    "grep",
+    "egrep",
+    "fgrep",
+    "rg",
+    "ag",
+    "ack",
+    "wc",
+    "uniq",
+    "cut",
+    "tr",
+    "paste",
+    "join",
+    "column",
+    "nl",
+    "fold",
+    "fmt",
+    "expand",
+    "unexpand",
+    "rev",
+    "comm",
+    "diff",
+    "cmp",
+    "sdiff",
+    "diff3",
+    "file",
+    "stat",
+    "du",
+    "df",
+    "free",
+    "uptime",
+    "uname",
+    "whoami",
+    "id",
+    "groups",
+    "who",
+    "w",
+    "last",
+    "lastlog",
+    "users",
+    "cal",
+    "ps",
+    "pgrep",
+    "pidof",
+    "top",
+    "htop",
+    "vmstat",
+    "iostat",
+    "mpstat",
+    "lsof",
+    "lsblk",
+    "blkid",
+    "lscpu",
+    "lsmem",
+    "lspci",
+    "lsusb",
+    "lsmod",
+    "ss",
+    "netstat",
+    "ping",
+    "ping6",
+    "traceroute",
+    "tracepath",
+    "dig",
+    "nslookup",
+    "host",
+    "whois",
+    "which",
+    "whereis",
+    "type",
+    "realpath",
+    "readlink",
+    "basename",
+    "dirname",
+    "pwd",
+    "printenv",
+    "locale",
+    "tty",
+    "nproc",
+    "getconf",
+    "arch",
+    "md5sum",
+    "sha1sum",
+    "sha224sum",
+    "sha256sum",
+    "sha384sum",
+    "sha512sum",
+    "b2sum",
+    "cksum",
+    "sum",
+    "base32",
+    "base64",
+    "od",
+    "hexdump",
+    "strings",
+    "jq",
+    "tree",
+    "fd",
+    "fdfind",
+    "locate",
+    "zcat",
+    "zgrep",
+    "zless",
+    "bzcat",
+    "xzcat",
+    "zstdcat",
+    "seq",
+    "yes",
+    "sleep",
+    "true",
+    "false",
+    "echo",
+    "printf",
+    "expr",
+    "bc",
+    "test",
+    "[",
+    "[[",
+    "getent",
+    "lsattr",
+    "namei",
+    "numfmt",
+    "tput",
+    "clear",
+    "man",
+    "info",
+    "apropos",
+    "whatis",
+    "help",
+    "cd",
+    "export",
+    "set",
+    "unset",
+    "read",
+    "local",
+    "declare",
+    "typeset",
+    "shift",
+    "exit",
+    "return",
+    "break",
+    "continue",
+    "wait",
+    "jobs",
+    "hash",
+    "let",
+    "getopts",
+    ":",
+];
+
+fn classify_script(script: &Script, depth: usize) -> Result<Verdict> {
+    let mut verdict = Verdict::Read;
+
+    // Fork bomb: a function that pipes into itself.
+    for pipeline in &script.pipelines {
+        for name in &script.functions {
+            let calls = pipeline
+                .iter()
+                .filter(|c| c.argv.first().is_some_and(|w| &w.text == name))
+                .count();
+            if calls >= 2 {
+                return Ok(Verdict::Blocked("fork bomb".to_string()));
+            }
+        }
+    }
+
+    for cmd in script.commands() {
+        verdict = verdict.max(classify_command(cmd, &script.functions, depth)?);
+        if matches!(verdict, Verdict::Blocked(_)) {
+            break;
+        }
+    }
+    Ok(verdict)
+}
+
+fn classify_command(cmd: &SimpleCommand, functions: &[String], depth: usize) -> Result<Verdict> {
+    let mut verdict = Verdict::Read;
+
+    for redirect in cmd.redirects.iter().filter(|r| r.is_output()) {
+        let target = &redirect.target.text;
+        if is_block_device(target) {
+            return Ok(Verdict::Blocked(format!("write to block device {target}")));
+        }
+        if redirect.target.dynamic || !is_harmless_sink(target) {
+            verdict = verdict.max(Verdict::Write(format!("redirect to {target}")));
+        }
+    }
+
+    // A call to a function of the script; its body is classified itself
+    if cmd
+        .argv
+        .first()
+        .is_some_and(|w| !w.dynamic && functions.contains(&w.text))
+    {
+        return Ok(verdict);
+    }
+    Ok(verdict.max(classify_argv(&cmd.argv, depth)?))
+}
+
+fn classify_argv(argv: &[Word], depth: usize) -> Result<Verdict> {
+    let argv = strip_wrappers(argv);
+    let Some(first) = argv.first() else {
+        return Ok(Verdict::Read);
+    };
+    if first.dynamic {
+        return Ok(Verdict::Write(format!(
+            "dynamic command name {}",
+            first.text
+        )));
+    }
+
+    let name = program_name(&first.text);
+    let args: Vec<&str> = argv[1..].iter().map(|w| w.text.as_str()).collect();
+
+    if let Some(reason) = blocked_reason(name, &args) {
+        return Ok(Verdict::Blocked(reason));
+    }
+
+    // Programs that run other commands.
+    match name {
+        _ if SHELLS.contains(&name) => return classify_shell(name, &argv[1..], depth),
+        "eval" => {
+            if argv[1..].iter().any(|w| w.dynamic) {
+                return Ok(Verdict::Write("eval of dynamic input".to_string()));
+            }
+            return classify_script(
+                &parser::parse_nested(&args.join(" "), depth + 1)?,
+                depth + 1,
+            );
+        }
+        "xargs" => return classify_argv(skip_options(&argv[1..], XARGS_VALUE_OPTS), depth),
+        "find" => return classify_find(&argv[1..], depth),
+        "source" | "." => return Ok(Verdict::Write(format!("{name} runs a script"))),
+        // Only left by `strip_wrappers` when a shell was asked for
+        "sudo" | "doas" => {
+            let command = skip_options(&argv[1..], SUDO_VALUE_OPTS);
+            if command.is_empty() {
+                return Ok(Verdict::Write(format!("{name} reads commands from stdin")));
+            }
+            return classify_argv(command, depth);
+        }
+        "su" | "runuser" => return classify_su(name, &argv[1..], depth),
+        "watch" => return classify_watch(&argv[1..], depth),
+        "flock" => return classify_flock(&argv[1..], depth),
+        "parallel" => return classify_parallel(&argv[1..], depth),
+        "chroot" => {
+            // The new root, then the command; without one, a shell
+            let rest = skip_options(&argv[1..], &[]);
+            return match rest.get(1..) {
+                Some(command) if !command.is_empty() => classify_argv(command, depth),
+                _ => Ok(Verdict::Write("chroot runs a shell".to_string())),
+            };
+        }
+        "strace" | "ltrace" => {
+            let writes_log = args.iter().any(|a| a.starts_with("-o"));
+            let command = classify_argv(skip_options(&argv[1..], TRACE_VALUE_OPTS), depth)?;
+            return Ok(if writes_log {
+                command.max(Verdict::Write(format!("{name} -o")))
+            } else {
+                command
+            });
+        }
+        "alias" => {
+            // What the alias runs counts, and so does redefining a command
+            let mut verdict = Verdict::Write("alias redefines commands".to_string());
+            for definition in &argv[1..] {
+                let Some((_, body)) = definition.text.split_once('=') else {
+                    continue;
+                };
+                if definition.dynamic {
+                    continue;
+                }
+                let nested = parser::parse_nested(body, depth + 1)?;
+                verdict = verdict.max(classify_script(&nested, depth + 1)?);
+            }
+            return Ok(verdict);
+        }
+        _ => {}
+    }
+
+    if is_write(name, &args) {
+        if !DEVICE_USERS.contains(&name)
+            && let Some(device) = args.iter().find(|a| is_block_device(a))
+        {
+            return Ok(Verdict::Blocked(format!("{name} on block device {device}")));
+        }
+        Ok(Verdict::Write(name.to_string()))
+    } else {
+        Ok(Verdict::Read)
+    }
+}
+
+/// Strip a path from a program name: `/usr/bin/rm` → `rm`.
+fn program_name(text: &str) -> &str {
+    text.rsplit('/').next().unwrap_or(text)
+}
+
+fn is_block_device(path: &str) -> bool {
+    let path = normalize_path(path);
+    BLOCK_DEVICE_PREFIXES.iter().any(|p| path.starts_with(p))
+}
+
+/// Collapse repeated slashes and `.`/`..` segments of an absolute path,
+/// so `//`, `/./` and `/dev//sda` compare like `/` and `/dev/sda`.
+/// Relative paths and options are returned unchanged.
+fn normalize_path(path: &str) -> String {
+    if !path.starts_with('/') {
+        return path.to_string();
+    }
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+fn is_harmless_sink(path: &str) -> bool {
+    HARMLESS_SINKS.contains(&path) || path.starts_with("/dev/fd/")
+}
+
+const SUDO_VALUE_OPTS: &[&str] = &["-u", "-g", "-C", "-h", "-p", "-U", "-r", "-t"];
+const XARGS_VALUE_OPTS: &[&str] = &["-n", "-I", "-d", "-L", "-P", "-s", "-E", "-a", "-i"];
+const TIMEOUT_VALUE_OPTS: &[&str] = &["-s", "-k", "--signal", "--kill-after"];
+const NICE_VALUE_OPTS: &[&str] = &["-n"];
+const IONICE_VALUE_OPTS: &[&str] = &["-c", "-n", "-p"];
+const TRACE_VALUE_OPTS: &[&str] = &[
+    "-e", "-o", "-p", "-s", "-u", "-E", "-a", "-b", "-I", "-P", "-X", "-S", "-O", "-n",
+];
+const SU_VALUE_OPTS: &[&str] = &[
+    "-s",
+    "--shell",
+    "-g",
+    "--group",
+    "-G",
+    "--supp-group",
+    "-w",
+    "--whitelist-environment",
+    "-u",
+    "--user",
+];
+const FLOCK_VALUE_OPTS: &[&str] = &["-w", "--timeout", "-E", "--conflict-exit-code"];
+const WATCH_VALUE_OPTS: &[&str] = &["-n", "--interval", "-q", "--equexit"];
+const PARALLEL_VALUE_OPTS: &[&str] = &[
+    "-j",
+    "--jobs",
+    "-N",
+    "-n",
+    "-L",
+    "-I",
+    "--delay",
+    "--timeout",
+    "--colsep",
+    "-d",
+    "-E",
+    "--tmpdir",
+    "--tag-string",
+];
+
+/// Skip leading options (and their values) of a wrapper program.
+fn skip_options<'a>(args: &'a [Word], value_opts: &[&str]) -> &'a [Word] {
+    let mut i = 0;
+    while i < args.len() {
+        let text = args[i].text.as_str();
+        if text == "--" {
+            return &args[i + 1..];
+        }
+        if !text.starts_with('-') || text == "-" {
+            break;
+        }
+        i += if value_opts.contains(&text) { 2 } else { 1 };
+    }
+    &args[i.min(args.len())..]
+}
+
+/// Peel off programs that just run their arguments as a command.
+fn strip_wrappers(mut argv: &[Word]) -> &[Word] {
+    loop {
+        let Some(first) = argv.first() else {
+            return argv;
+        };
+        if first.dynamic {
+            return argv;
+        }
+        let rest = &argv[1..];
+        argv = match program_name(&first.text) {
+            // `-s` and `-i` run a shell; `classify_argv` handles that
+            "sudo" | "doas" if asks_for_shell(rest) => return argv,
+            "sudo" | "doas" => skip_options(rest, SUDO_VALUE_OPTS),
+            "setsid" => skip_options(rest, &[]),
+            "nohup" | "exec" | "unbuffer" | "busybox" | "chronic" | "time" => rest,
+            "nice" => skip_options(rest, NICE_VALUE_OPTS),
+            "ionice" => skip_options(rest, IONICE_VALUE_OPTS),
+            "stdbuf" => skip_options(rest, &[]),
+            "env" => {
+                let rest = skip_options(rest, &["-u", "-C", "-S"]);
+                let skip = rest
+                    .iter()
+                    .take_while(|w| w.text.contains('=') && !w.text.starts_with('-'))
+                    .count();
+                &rest[skip..]
+            }
+            "timeout" => {
+                let rest = skip_options(rest, TIMEOUT_VALUE_OPTS);
+                rest.get(1..).unwrap_or(&[])
+            }
+            "command" => {
+                // `command -v foo` only looks a program up.
+                if rest
+                    .first()
+                    .is_some_and(|w| w.text == "-v" || w.text == "-V")
+                {
+                    return &[];
+                }
+                skip_options(rest, &[])
+            }
+            _ => return argv,
+        };
+    }
+}
+
+/// Whether `sudo`/`doas` options before the command ask for a shell.
+fn asks_for_shell(args: &[Word]) -> bool {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let text = arg.text.as_str();
+        if text == "--" || !text.starts_with('-') {
+            return false;
+        }
+        if matches!(text, "--shell" | "--login")
+            || (!text.starts_with("--") && text[1..].contains(['s', 'i']))
+        {
+            return true;
+        }
+        i += if SUDO_VALUE_OPTS.contains(&text) {
+            2
+        } else {
+            1
+        };
+    }
+    false
+}
+
+/// A script given as one word, or a write if it can't be seen.
+fn classify_word_script(name: &str, script: Option<&Word>, depth: usize) -> Result<Verdict> {
+    match script {
+        Some(script) if !script.dynamic => {
+            classify_script(&parser::parse_nested(&script.text, depth + 1)?, depth + 1)
+        }
+        _ => Ok(Verdict::Write(format!("{name} with a dynamic command"))),
+    }
+}
+
+/// `su -c cmd` and `runuser -c cmd` run a script; `runuser -u user cmd`
+/// runs a command; without either they read commands from stdin.
+fn classify_su(name: &str, args: &[Word], depth: usize) -> Result<Verdict> {
+    for (i, arg) in args.iter().enumerate() {
+        let text = arg.text.as_str();
+        if matches!(text, "-c" | "--command" | "-C" | "--session-command") {
+            return classify_word_script(name, args.get(i + 1), depth);
+        }
+        if let Some(script) = text
+            .strip_prefix("--command=")
+            .or_else(|| text.strip_prefix("--session-command="))
+        {
+            if arg.dynamic {
+                return Ok(Verdict::Write(format!("{name} with a dynamic command")));
+            }
+            return classify_script(&parser::parse_nested(script, depth + 1)?, depth + 1);
+        }
+    }
+    if name == "runuser" && args.iter().any(|a| a.text == "-u" || a.text == "--user") {
+        let command = skip_options(args, SU_VALUE_OPTS);
+        if !command.is_empty() {
+            return classify_argv(command, depth);
+        }
+    }
+    Ok(Verdict::Write(format!("{name} reads commands from stdin")))
+}
+
+/// `watch cmd args` runs its arguments through `sh -c`; `-x` execs them.
+fn classify_watch(args: &[Word], depth: usize) -> Result<Verdict> {
+    let exec = args
+        .iter()
+        .take_while(|a| a.text.starts_with('-'))
+        .any(|a| a.text == "-x" || a.text == "--exec");
+    let command = skip_options(args, WATCH_VALUE_OPTS);
+    if exec || command.iter().any(|w| w.dynamic) {
+        return classify_argv(command, depth);
+    }
+    let script: Vec<&str> = command.iter().map(|w| w.text.as_str()).collect();
+    classify_script(
+        &parser::parse_nested(&script.join(" "), depth + 1)?,
+        depth + 1,
+    )
+}
+
+/// `flock lock cmd args` and `flock lock -c cmd`; `flock fd` runs nothing.
+fn classify_flock(args: &[Word], depth: usize) -> Result<Verdict> {
+    let rest = skip_options(args, FLOCK_VALUE_OPTS);
+    let Some(command) = rest.get(1..).filter(|c| !c.is_empty()) else {
+        return Ok(Verdict::Read);
+    };
+    if matches!(command[0].text.as_str(), "-c" | "--command") {
+        return classify_word_script("flock", command.get(1), depth);
+    }
+    classify_argv(command, depth)
+}
+
+/// GNU parallel runs its command once per input, through a shell. With no
+/// command, each input is a command itself.
+fn classify_parallel(args: &[Word], depth: usize) -> Result<Verdict> {
+    if args.iter().any(|a| {
+        matches!(
+            a.text.as_str(),
+            "-S" | "--sshlogin" | "--joblog" | "--results"
+        ) || a.text.starts_with("--sshlogin=")
+            || a.text.starts_with("--joblog=")
+            || a.text.starts_with("--results=")
+    }) {
+        return Ok(Verdict::Write(
+            "parallel runs remotely or writes logs".to_string(),
+        ));
+    }
+    let is_separator = |w: &Word| w.text.starts_with(":::");
+    let rest = skip_options(args, PARALLEL_VALUE_OPTS);
+    let end = rest.iter().position(is_separator).unwrap_or(rest.len());
+    let command = &rest[..end];
+    if !command.is_empty() {
+        if command.iter().any(|w| w.dynamic) {
+            return Ok(Verdict::Write(
+                "parallel with a dynamic command".to_string(),
+            ));
+        }
+        let script: Vec<&str> = command.iter().map(|w| w.text.as_str()).collect();
+        let script = script.join(" ");
+        // Each input goes in place of `{}`, or after the command
+        let inputs: Vec<String> = rest[end..]
+            .iter()
+            .filter(|w| !is_separator(w))
+            .map(|w| format!("'{}'", w.text.replace('\'', r"'\''")))
+            .collect();
+        let mut verdict = classify_script(&parser::parse_nested(&script, depth + 1)?, depth + 1)?;
+        for input in inputs {
+            let filled = if script.contains("{}") {
+                script.replace("{}", &input)
+            } else {
+                format!("{script} {input}")
+            };
+            let nested = parser::parse_nested(&filled, depth + 1)?;
+            verdict = verdict.max(classify_script(&nested, depth + 1)?);
+        }
+        return Ok(verdict);
+    }
+    // Inputs from `:::` are visible; from stdin, `::::` files or `-a`, not
+    let mut verdict = Verdict::Read;
+    let mut visible = false;
+    let mut group = "";
+    for word in &rest[end..] {
+        if is_separator(word) {
+            group = word.text.as_str();
+            continue;
+        }
+        if group != ":::" && group != ":::+" {
+            return Ok(Verdict::Write(
+                "parallel runs commands it reads".to_string(),
+            ));
+        }
+        visible = true;
+        verdict = verdict.max(classify_word_script("parallel", Some(word), depth)?);
+    }
+    if !visible
+        || args
+            .iter()
+            .any(|a| a.text == "-a" || a.text.starts_with("--arg-file"))
+    {
+        return Ok(Verdict::Write(
+            "parallel runs commands it reads".to_string(),
+        ));
+    }
+    Ok(verdict)
+}
+
+fn classify_shell(name: &str, args: &[Word], depth: usize) -> Result<Verdict> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg.text == "-c" || (arg.text.starts_with('-') && arg.text.ends_with('c')) {
+            return match iter.next() {
+                Some(script) if !script.dynamic => {
+                    classify_script(&parser::parse_nested(&script.text, depth + 1)?, depth + 1)
+                }
+                _ => Ok(Verdict::Write(format!("{name} -c with dynamic script"))),
+            };
+        }
+        if !arg.text.starts_with('-') {
+            return Ok(Verdict::Write(format!("{name} runs a script file")));
+        }
+    }
+    // No -c and no file: the shell executes whatever arrives on stdin.
+    Ok(Verdict::Write(format!("{name} reads commands from stdin")))
+}
+
+fn classify_find(args: &[Word], depth: usize) -> Result<Verdict> {
+    let mut verdict = Verdict::Read;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].text.as_str() {
+            "-delete" => verdict = verdict.max(Verdict::Write("find -delete".to_string())),
+            "-fprint" | "-fprint0" | "-fprintf" | "-fls" => {
+                verdict = verdict.max(Verdict::Write(format!("find {}", args[i].text)))
+            }
+            "-exec" | "-execdir" | "-ok" | "-okdir" => {
+                let start = i + 1;
+                let end = args[start..]
+                    .iter()
+                    .position(|w| w.text == ";" || w.text == "+")
+                    .map(|p| start + p)
+                    .unwrap_or(args.len());
+                verdict = verdict.max(classify_argv(&args[start..end], depth)?);
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(verdict)
+}
+
+/// Reasons a command must never run.
+fn blocked_reason(name: &str, args: &[&str]) -> Option<String> {
+    let recursive = args.iter().any(|a| {
+        *a == "--recursive"
+            || (a.starts_with('-') && !a.starts_with("--") && a.contains(['r', 'R']))
+    });
+    let targets_root = args
+        .iter()
+        .any(|a| *a == "--no-preserve-root" || matches!(normalize_path(a).as_str(), "/" | "/*"));
+
+    match name {
+        "rm" if recursive && targets_root => Some("recursive delete of /".to_string()),
+        "chmod" | "chown" | "chgrp" if recursive && targets_root => {
+            Some(format!("recursive {name} of /"))
+        }
+        _ if name.starts_with("mkfs") => Some("formatting a filesystem".to_string()),
+        "dd" if args
+            .iter()
+            .any(|a| a.strip_prefix("of=").is_some_and(is_block_device)) =>
+        {
+            Some("dd to a block device".to_string())
+        }
+        _ if POWER_PROGRAMS.contains(&name) => Some(format!("{name} is not allowed")),
+        "init" | "telinit" if args.first().is_some_and(|a| *a == "0" || *a == "6") => {
+            Some(format!("{name} {} is not allowed", args[0]))
+        }
+        "systemctl"
+            if subcommand(args, &[])
+                .is_some_and(|s| matches!(s, "poweroff" | "reboot" | "halt" | "kexec")) =>
+        {
+            Some("systemctl power action is not allowed".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The first non-option argument, skipping options listed as taking a value.
+fn subcommand<'a>(args: &[&'a str], value_opts: &[&str]) -> Option<&'a str> {
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i];
+        if !a.starts_with('-') {
+            return Some(a);
+        }
+        i += if value_opts.contains(&a) { 2 } else { 1 };
+    }
+    None
+}
+
+/// Whether any short-option cluster (e.g. `-ni`) contains `flag`.
+fn has_short_flag(args: &[&str], flag: char) -> bool {
+    args.iter()
+        .any(|a| a.starts_with('-') && !a.starts_with("--") && a[1..].contains(flag))
+}
+
+fn is_write(name: &str, args: &[&str]) -> bool {
+    if WRITE_PROGRAMS.contains(&name) {
+        return true;
+    }
+    match name {
+        "dd" => args.iter().any(|a| a.starts_with("of=")),
+        "tee" => args
+            .iter()
+            .any(|a| !a.starts_with('-') && !is_harmless_sink(a)),
+        "sed" => sed_writes(args),
+        "sort" => args.iter().any(|a| {
+            a.starts_with("--output") || a.starts_with("--compress-program") || {
+                short_cluster_flag(a, "kStT", 'o')
+            }
+        }),
+        "date" => date_writes(args),
+        "hostname" => args
+            .iter()
+            .any(|a| !a.starts_with('-') || matches!(*a, "-F" | "--file" | "-b" | "--boot")),
+        "ip" => ip_writes(args),
+        "ifconfig" => args.iter().filter(|a| !a.starts_with('-')).count() > 1,
+        "journalctl" => args.iter().any(|a| {
+            [
+                "--vacuum",
+                "--rotate",
+                "--flush",
+                "--sync",
+                "--relinquish-var",
+                "--smart-relinquish-var",
+                "--setup-keys",
+                "--update-catalog",
+            ]
+            .iter()
+            .any(|opt| a.starts_with(opt))
+        }),
+        "dmesg" => {
+            has_short_flag(args, 'c')
+                || has_short_flag(args, 'C')
+                || args
+                    .iter()
+                    .any(|a| matches!(*a, "--clear" | "--read-clear"))
+        }
+        _ if INTERPRETERS.contains(&name) => interpreter_runs_code(name, args),
+        _ if REMOTE_PROGRAMS.contains(&name) => true,
+        "awk" | "gawk" | "mawk" | "nawk" => awk_writes(args),
+        "curl" => curl_writes(args),
+        "wget" => wget_writes(args),
+        "git" => git_writes(args),
+        "docker" | "podman" => docker_writes(args),
+        "systemctl" => subcommand(args, &[]).is_some_and(|s| {
+            !matches!(
+                s,
+                "status"
+                    | "show"
+                    | "cat"
+                    | "help"
+                    | "list-units"
+                    | "list-unit-files"
+                    | "list-timers"
+                    | "list-sockets"
+                    | "list-dependencies"
+                    | "is-active"
+                    | "is-enabled"
+                    | "is-failed"
+                    | "is-system-running"
+            )
+        }),
+        "apt" | "apt-get" | "dnf" | "yum" | "zypper" | "apk" | "brew" | "snap" | "flatpak" => {
+            subcommand(args, &[]).is_some_and(|s| {
+                !matches!(
+                    s,
+                    "list"
+                        | "search"
+                        | "show"
+                        | "info"
+                        | "policy"
+                        | "depends"
+                        | "rdepends"
+                        | "help"
+                )
+            })
+        }
+        "pacman" => pacman_writes(args),
+        "pip" | "pip3" => subcommand(args, &[]).is_some_and(|s| s == "install" || s == "uninstall"),
+        // Anything that runs the project's code, or changes its files
+        "cargo" => subcommand(args, &[]).is_some_and(|s| {
+            let checking = s == "fmt" && args.contains(&"--check");
+            !checking
+                && !matches!(
+                    s,
+                    "build"
+                        | "b"
+                        | "check"
+                        | "c"
+                        | "clippy"
+                        | "doc"
+                        | "d"
+                        | "tree"
+                        | "metadata"
+                        | "search"
+                        | "version"
+                        | "help"
+                        | "locate-project"
+                        | "pkgid"
+                        | "verify-project"
+                        | "read-manifest"
+                )
+        }),
+        "npm" | "pnpm" | "yarn" => {
+            let Some(sub) = subcommand(args, &[]) else {
+                // A bare `yarn` installs
+                return name == "yarn";
+            };
+            let pos = args.iter().position(|a| *a == sub).unwrap_or(0);
+            let rest = &args[pos + 1..];
+            match sub {
+                "ls" | "list" | "ll" | "la" | "view" | "v" | "info" | "show" | "search" | "s"
+                | "find" | "outdated" | "help" | "root" | "prefix" | "bin" | "whoami" | "ping"
+                | "doctor" | "explain" | "why" => false,
+                "audit" => rest.contains(&"fix"),
+                "config" => !rest
+                    .first()
+                    .is_some_and(|s| matches!(*s, "get" | "list" | "ls")),
+                "version" => !rest.is_empty(),
+                _ => true,
+            }
+        }
+        "go" => subcommand(args, &[]).is_some_and(|s| match s {
+            "version" | "list" | "doc" | "vet" | "help" | "build" => false,
+            "env" => args.iter().any(|a| *a == "-w" || *a == "-u"),
+            _ => true,
+        }),
+        "crontab" => args.iter().any(|a| *a != "-l" && *a != "-u"),
+        "tar" => tar_writes(args),
+        "unzip" => !args.contains(&"-l"),
+        "zip" | "gzip" | "gunzip" | "bzip2" | "bunzip2" | "xz" | "unxz" | "zstd" => {
+            !has_short_flag(args, 'c') && !has_short_flag(args, 'l') && !has_short_flag(args, 't')
+        }
+        "iptables" | "ip6tables" | "nft" => args.iter().any(|a| {
+            matches!(
+                *a,
+                "-A" | "-D" | "-I" | "-R" | "-F" | "-X" | "-P" | "-N" | "add" | "delete" | "flush"
+            )
+        }),
+        "sysctl" => has_short_flag(args, 'w'),
+        "hostnamectl" | "timedatectl" | "localectl" => {
+            subcommand(args, &[]).is_some_and(|s| s.starts_with("set-"))
+        }
+        _ => !READ_PROGRAMS.contains(&name),
+    }
+}
+
+/// Whether the short-option cluster `arg` (e.g. `-rno`) sets `flag`.
+/// Scanning stops at the first option in `value_opts`, whose value is
+/// the rest of the cluster.
+fn short_cluster_flag(arg: &str, value_opts: &str, flag: char) -> bool {
+    let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.starts_with('-')) else {
+        return false;
+    };
+    for c in cluster.chars() {
+        if c == flag {
+            return true;
+        }
+        if value_opts.contains(c) {
+            return false;
+        }
+    }
+    false
+}
+
+/// `sed -i` edits files, `-f` loads a script we can't see, and the `w`
+/// and `e` commands (or `s///w`, `s///e`) write files and run commands.
+fn sed_writes(args: &[&str]) -> bool {
+    let mut scripts = Vec::new();
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            a if a.starts_with("--in-place") || a.starts_with("--file") => return true,
+            "-e" | "--expression" => scripts.extend(iter.next().copied()),
+            a if a.starts_with("--expression=") => scripts.push(&a["--expression=".len()..]),
+            "-l" | "--line-length" => {
+                iter.next();
+            }
+            a if a.starts_with("--") => {}
+            a if a.starts_with('-') && a.len() > 1 => {
+                let cluster = &a[1..];
+                for (i, c) in cluster.char_indices() {
+                    match c {
+                        'i' | 'f' => return true,
+                        'e' => {
+                            let rest = &cluster[i + 1..];
+                            if rest.is_empty() {
+                                scripts.extend(iter.next().copied());
+                            } else {
+                                scripts.push(rest);
+                            }
+                            break;
+                        }
+                        'l' => break,
+                        _ => {}
+                    }
+                }
+            }
+            a => positional.push(a),
+        }
+    }
+    if scripts.is_empty() {
+        scripts.extend(positional.first().copied());
+    }
+    scripts.iter().any(|script| sed_script_writes(script))
+}
+
+fn sed_script_writes(script: &str) -> bool {
+    let chars: Vec<char> = script.chars().collect();
+    let mut i = 0;
+    // Skip up to and past `delim`, honouring backslash escapes.
+    let skip_to = |mut i: usize, delim: char| {
+        while i < chars.len() && chars[i] != delim {
+            i += if chars[i] == '\\' { 2 } else { 1 };
+        }
+        i + 1
+    };
+    while i < chars.len() {
+        match chars[i] {
+            // Addresses, separators and blocks
+            c if c.is_ascii_digit() || c.is_whitespace() => i += 1,
+            '$' | ',' | '!' | '+' | '~' | ';' | '{' | '}' => i += 1,
+            '/' => i = skip_to(i + 1, '/'),
+            '\\' if i + 1 < chars.len() => i = skip_to(i + 2, chars[i + 1]),
+            'w' | 'W' | 'e' => return true,
+            's' | 'y' if i + 1 < chars.len() => {
+                let command = chars[i];
+                let delim = chars[i + 1];
+                i = skip_to(skip_to(i + 2, delim), delim);
+                if command == 's' {
+                    while i < chars.len() && !matches!(chars[i], ';' | '\n' | '}') {
+                        if matches!(chars[i], 'w' | 'e') {
+                            return true;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            // Commands whose argument runs to the end of the line
+            'a' | 'i' | 'c' | 'r' | 'R' | 'b' | 't' | 'T' | ':' | 'l' | 'q' | 'Q' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    false
+}
+
+/// `date -s`, or a bare `MMDDhhmm` argument, sets the clock.
+fn date_writes(args: &[&str]) -> bool {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "-d" | "--date" | "-f" | "--file" | "-r" | "--reference" => {
+                iter.next();
+            }
+            a if a.starts_with("-s") || a.starts_with("--set") => return true,
+            a if a.starts_with('-') || a.starts_with('+') => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// `ip` only reads when the verb after its object is missing or one of
+/// `show`, `list` or `get` (or an abbreviation of them).
+fn ip_writes(args: &[&str]) -> bool {
+    let mut iter = args.iter();
+    let mut words = Vec::new();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "-b" | "-batch" | "--batch" => return true,
+            "-n" | "-netns" | "-f" | "-family" | "-rc" | "-rcvbuf" => {
+                iter.next();
+            }
+            a if a.starts_with('-') => {}
+            a => words.push(a),
+        }
+    }
+    match words.get(1) {
+        None => false,
+        Some(verb) => !["show", "list", "lst", "get", "help"]
+            .iter()
+            .any(|read| read.starts_with(verb)),
+    }
+}
+
+/// An interpreter only reads when asked for its version or help; anything
+/// else runs inline code, a script file, or a script piped to stdin.
+fn interpreter_runs_code(name: &str, args: &[&str]) -> bool {
+    let version = |a: &&str| match *a {
+        "--version" | "-V" | "--help" | "-h" => true,
+        // `python -v` is verbose mode, not a version query.
+        "-v" => !name.starts_with("python"),
+        _ => false,
+    };
+    args.is_empty() || !args.iter().all(version)
+}
+
+/// awk programs can run commands (`system()`, `| "cmd"`) and write files
+/// (`print > "f"`), and `-f`/`-i` load code we cannot see.
+fn awk_writes(args: &[&str]) -> bool {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "-f" | "-i" | "-l" | "-E" => return true,
+            a if a.starts_with("--file")
+                || a.starts_with("--include")
+                || a.starts_with("--load") =>
+            {
+                return true;
+            }
+            "-v" | "-F" => {
+                iter.next();
+            }
+            a if a.starts_with('-') => {}
+            program => return awk_program_writes(program),
+        }
+    }
+    false
+}
+
+fn awk_program_writes(program: &str) -> bool {
+    if program.contains("system") || program.replace("||", "").contains('|') {
+        return true;
+    }
+    // `>` is a comparison unless it follows a print statement.
+    program.match_indices("print").any(|(i, _)| {
+        program[i..]
+            .split([';', '}', '\n'])
+            .next()
+            .is_some_and(|stmt| stmt.contains('>'))
+    })
+}
+
+/// Whether any option cluster asks tar to create, extract or modify an
+/// archive, or to run a program. The first argument may be a dashless
+/// cluster (`tar xf a.tar`).
+fn tar_writes(args: &[&str]) -> bool {
+    args.iter().enumerate().any(|(i, a)| {
+        if let Some(long) = a.strip_prefix("--") {
+            return matches!(
+                long.split('=').next(),
+                Some(
+                    "create"
+                        | "extract"
+                        | "get"
+                        | "append"
+                        | "update"
+                        | "concatenate"
+                        | "catenate"
+                        | "delete"
+                        | "to-command"
+                        | "use-compress-program"
+                        | "checkpoint-action"
+                        | "info-script"
+                        | "new-volume-script"
+                        | "rsh-command"
+                        | "rmt-command"
+                )
+            );
+        }
+        let cluster = match a.strip_prefix('-') {
+            Some(c) => c,
+            None if i == 0 => a,
+            None => return false,
+        };
+        // `-I prog` and `-F script` run programs
+        cluster.contains(['c', 'x', 'r', 'u', 'A', 'I', 'F'])
+    })
+}
+
+/// curl options that take a value, which fills the rest of a cluster.
+const CURL_VALUE_FLAGS: &str = "AbCEhHKmQrtuUwxyYzeP";
+
+fn curl_writes(args: &[&str]) -> bool {
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        let method = match *arg {
+            "-X" | "--request" => iter.peek().map(|m| m.to_ascii_uppercase()),
+            a if a.starts_with("--request=") => Some(a[10..].to_ascii_uppercase()),
+            a if a.starts_with('-') && !a.starts_with("--") => {
+                // A cluster such as `-sSo out` or `-dkey=value`
+                let cluster = &a[1..];
+                let mut method = None;
+                for (i, c) in cluster.char_indices() {
+                    match c {
+                        // Data, uploads and files written (`-D` headers, `-c` cookies)
+                        'd' | 'F' | 'T' | 'o' | 'O' | 'D' | 'c' => return true,
+                        'X' => {
+                            let rest = &cluster[i + 1..];
+                            method = if rest.is_empty() {
+                                iter.peek().map(|m| m.to_ascii_uppercase())
+                            } else {
+                                Some(rest.to_ascii_uppercase())
+                            };
+                            break;
+                        }
+                        c if CURL_VALUE_FLAGS.contains(c) => break,
+                        _ => {}
+                    }
+                }
+                method
+            }
+            _ => None,
+        };
+        if method.is_some_and(|m| matches!(m.as_str(), "POST" | "PUT" | "DELETE" | "PATCH")) {
+            return true;
+        }
+        if matches!(
+            *arg,
+            "--form"
+                | "--upload-file"
+                | "--output"
+                | "--remote-name"
+                | "--remote-name-all"
+                | "--output-dir"
+                | "--dump-header"
+                | "--cookie-jar"
+        ) || arg.starts_with("--data")
+            || arg.starts_with("--json")
+            || arg.starts_with("--form")
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn wget_writes(args: &[&str]) -> bool {
+    if args.iter().any(|a| a.starts_with("--post")) {
+        return true;
+    }
+    // Only `-O -` (write to stdout) keeps wget from creating files.
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if *arg == "--output-document=-" {
+            return false;
+        }
+        if arg.starts_with('-') && !arg.starts_with("--") && arg.ends_with('O') {
+            if iter.peek() == Some(&&"-") {
+                return false;
+            }
+        } else if arg.starts_with('-') && !arg.starts_with("--") && arg.ends_with("O-") {
+            return false;
+        }
+    }
+    true
+}
+
+fn git_writes(args: &[&str]) -> bool {
+    let value_opts = ["-C", "-c", "--git-dir", "--work-tree", "--namespace"];
+    if git_config_runs_code(args) {
+        return true;
+    }
+    let Some(sub) = subcommand(args, &value_opts) else {
+        return false;
+    };
+    let pos = args.iter().position(|a| *a == sub).unwrap_or(0);
+    let rest = &args[pos + 1..];
+    // `git diff --output=f`, `git log --output f`
+    if rest.iter().any(|a| a.starts_with("--output")) {
+        return true;
+    }
+    match sub {
+        "stash" => !matches!(rest.first(), Some(&"list") | Some(&"show")),
+        "branch" | "tag" => rest.iter().any(|a| {
+            matches!(
+                *a,
+                "-d" | "-D" | "-m" | "-M" | "-c" | "-C" | "-f" | "--delete" | "--move" | "--force"
+            ) || !a.starts_with('-')
+        }),
+        "remote" => rest.first().is_some_and(|s| {
+            matches!(*s, "add" | "remove" | "rm" | "rename" | "set-url" | "prune")
+        }),
+        "config" => {
+            let reading = rest
+                .iter()
+                .any(|a| a.starts_with("--get") || matches!(*a, "--list" | "-l"));
+            !reading && rest.iter().filter(|a| !a.starts_with('-')).count() >= 2
+        }
+        "reflog" => rest
+            .first()
+            .is_some_and(|s| matches!(*s, "expire" | "delete")),
+        "worktree" | "submodule" => !matches!(rest.first(), Some(&"list") | Some(&"status")),
+        // Anything else, aliases included, may write
+        _ => !matches!(
+            sub,
+            "status"
+                | "log"
+                | "diff"
+                | "show"
+                | "blame"
+                | "annotate"
+                | "grep"
+                | "ls-files"
+                | "ls-tree"
+                | "ls-remote"
+                | "rev-parse"
+                | "rev-list"
+                | "describe"
+                | "shortlog"
+                | "cat-file"
+                | "show-ref"
+                | "for-each-ref"
+                | "whatchanged"
+                | "help"
+                | "version"
+                | "fetch"
+                | "count-objects"
+                | "var"
+                | "check-ignore"
+                | "check-attr"
+                | "merge-base"
+                | "name-rev"
+                | "cherry"
+                | "range-diff"
+                | "diff-tree"
+                | "diff-files"
+                | "diff-index"
+                | "verify-commit"
+                | "verify-tag"
+                | "fsck"
+        ),
+    }
+}
+
+/// `git -c key=value` can point pagers, aliases, hooks and ssh at arbitrary
+/// commands; only display settings are treated as read-only.
+fn git_config_runs_code(args: &[&str]) -> bool {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let setting = match *arg {
+            "-c" => iter.next().copied(),
+            a if a.starts_with("--config-env") => return true,
+            a if !a.starts_with('-') => return false,
+            _ => continue,
+        };
+        let key = setting
+            .and_then(|s| s.split('=').next())
+            .unwrap_or_default();
+        let key = key.to_ascii_lowercase();
+        if !(key.starts_with("color.") || key == "core.quotepath" || key.starts_with("i18n.")) {
+            return true;
+        }
+    }
+    false
+}
+
+fn docker_writes(args: &[&str]) -> bool {
+    let Some(sub) = subcommand(args, &["-H", "--host", "--context", "-c", "--config"]) else {
+        return false;
+    };
+    let pos = args.iter().position(|a| *a == sub).unwrap_or(0);
+    let verb = match sub {
+        "container" | "image" | "volume" | "network" | "system" | "builder" | "compose" => {
+            match subcommand(&args[pos + 1..], &["-f", "--file", "-p", "--project-name"]) {
+                Some(v) => v,
+                None => return false,
+            }
+        }
+        other => other,
+    };
+    matches!(
+        verb,
+        "rm" | "rmi"
+            | "stop"
+            | "kill"
+            | "run"
+            | "start"
+            | "restart"
+            | "create"
+            | "exec"
+            | "build"
+            | "pull"
+            | "push"
+            | "tag"
+            | "commit"
+            | "cp"
+            | "pause"
+            | "unpause"
+            | "prune"
+            | "up"
+            | "down"
+            | "remove"
+            | "update"
+            | "rename"
+            | "load"
+            | "import"
+    )
+}
+
+fn pacman_writes(args: &[&str]) -> bool {
+    args.iter().any(|a| {
+        let Some(flags) = a.strip_prefix('-').filter(|f| !f.starts_with('-')) else {
+            return false;
+        };
+        match flags.chars().next() {
+            Some('R') | Some('U') | Some('D') => true,
+            Some('S') => {
+                let query = flags.contains(['s', 'i', 'l', 'g', 'p']);
+                let upgrade = flags.contains(['y', 'u']);
+                !query || upgrade
+            }
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(cmd: &str) -> Verdict {
+        classify(cmd).unwrap()
+    }
+
+    fn is_read(cmd: &str) -> bool {
+        verdict(cmd) == Verdict::Read
+    }
+
+    fn is_blocked(cmd: &str) -> bool {
+        matches!(verdict(cmd), Verdict::Blocked(_))
+    }
+
+    #[test]
+    fn substrings_in_arguments_are_not_writes() {
+        assert!(is_read("ls copy/"));
+        assert!(is_read("cat cp.txt"));
+        assert!(is_read("echo 'rm -rf /'"));
+        assert!(is_read("grep -r 'git push' ."));
+        assert!(is_read("ls -la > /dev/null 2>&1"));
+    }
+
+    #[test]
+    fn obfuscated_program_names_are_detected() {
+        assert!(!is_read(r#"r""m file"#));
+        assert!(!is_read(r"\rm file"));
+        assert!(!is_read("/bin/rm file"));
+        assert!(!is_read(r"$'\x72m' file"));
+    }
+
+    #[test]
+    fn wrappers_are_unwrapped() {
+        assert!(!is_read("sudo rm x"));
+        assert!(!is_read("sudo -u root rm x"));
+        assert!(!is_read("env FOO=1 rm x"));
+        assert!(!is_read("nice -n 5 timeout 10 rm x"));
+        assert!(!is_read("ls | xargs -n 1 rm"));
+        assert!(is_read("command -v rm"));
+    }
+
+    #[test]
+    fn nested_scripts_are_classified() {
+        assert!(!is_read("sh -c 'rm x'"));
+        assert!(!is_read("bash -lc \"touch y\""));
+        assert!(!is_read("echo $(rm x)"));
+        assert!(!is_read("eval rm x"));
+        assert!(!is_read("curl example.com | sh"));
+        assert!(is_read("sh -c 'ls -la'"));
+    }
+
+    #[test]
+    fn find_actions_are_classified() {
+        assert!(is_read("find . -name '*.rs'"));
+        assert!(!is_read("find . -delete"));
+        assert!(!is_read("find . -exec rm {} ;"));
+        assert!(is_read("find . -exec cat {} +"));
+    }
+
+    #[test]
+    fn subcommands_are_classified() {
+        assert!(is_read("git status"));
+        assert!(is_read("git log --oneline"));
+        assert!(is_read("git -C repo diff"));
+        assert!(!is_read("git push origin main"));
+        assert!(!is_read("git -C repo commit -m x"));
+        assert!(is_read("git branch"));
+        assert!(!is_read("git branch -D old"));
+        assert!(is_read("docker ps"));
+        assert!(!is_read("docker container rm x"));
+        assert!(is_read("systemctl status nginx"));
+        assert!(!is_read("systemctl restart nginx"));
+        assert!(is_read("pacman -Ss vim"));
+        assert!(!is_read("pacman -Syu"));
+        assert!(is_read("cargo build"));
+        assert!(!is_read("cargo install ripgrep"));
+    }
+
+    #[test]
+    fn network_mutations_are_writes() {
+        assert!(is_read("curl https://example.com"));
+        assert!(!is_read("curl -X POST https://example.com"));
+        assert!(!is_read("curl -XDELETE https://example.com"));
+        assert!(!is_read("curl -o out.html https://example.com"));
+        assert!(is_read("wget -qO- https://example.com"));
+        assert!(!is_read("wget https://example.com"));
+    }
+
+    #[test]
+    fn in_place_edits_are_writes() {
+        assert!(!is_read("sed -i 's/a/b/' f"));
+        assert!(!is_read("sed -ni 's/a/b/p' f"));
+        assert!(is_read("sed 's/a/b/' f"));
+        assert!(!is_read("echo x | tee out.txt"));
+        assert!(is_read("echo x | tee /dev/null"));
+    }
+
+    #[test]
+    fn dynamic_command_names_are_writes() {
+        assert!(!is_read("$CMD file"));
+        assert!(!is_read("$(echo rm) file"));
+    }
+
+    #[test]
+    fn dangerous_commands_are_blocked() {
+        assert!(is_blocked("rm -rf /"));
+        assert!(is_blocked("rm -fr /*"));
+        assert!(is_blocked("sudo rm -r -f /"));
+        assert!(is_blocked("r\"\"m -rf /"));
+        assert!(is_blocked("mkfs.ext4 /dev/sda1"));
+        assert!(is_blocked("dd if=/dev/zero of=/dev/sda"));
+        assert!(is_blocked("echo x > /dev/sda"));
+        assert!(is_blocked(":(){ :|:& };:"));
+        assert!(is_blocked("chmod -R 777 /"));
+        assert!(is_blocked("shutdown -h now"));
+        assert!(is_blocked("init 0"));
+        assert!(is_blocked("systemctl reboot"));
+        assert!(is_blocked("sh -c 'reboot'"));
+    }
+
+    #[test]
+    fn harmless_mentions_are_not_blocked() {
+        assert!(!is_blocked("echo shutdown"));
+        assert!(!is_blocked("rm -rf ./build"));
+        assert!(!is_blocked("dd if=in.img of=out.img"));
+    }
+
+    #[test]
+    fn tar_option_clusters_are_all_scanned() {
+        assert!(!is_read("tar -C / -xf a.tar"));
+        assert!(!is_read("tar -v --extract -f a.tar"));
+        assert!(!is_read("tar xf a.tar"));
+        assert!(is_read("tar -tvf a.tar"));
+        assert!(is_read("tar -C dir -tf a.tar"));
+    }
+
+    #[test]
+    fn root_paths_are_normalized() {
+        assert!(is_blocked("rm -rf //"));
+        assert!(is_blocked("rm -rf /./"));
+        assert!(is_blocked("rm -rf /usr/.."));
+        assert!(!is_blocked("rm -rf /tmp/x"));
+    }
+
+    #[test]
+    fn writing_programs_on_block_devices_are_blocked() {
+        assert!(is_blocked("cp /dev/zero /dev/sda"));
+        assert!(is_blocked("shred /dev/sda"));
+        assert!(is_blocked("echo x | sudo tee /dev/sda"));
+        assert!(is_blocked("cat x > //dev/sda"));
+        assert!(!is_blocked("mount /dev/sda1 /mnt"));
+    }
+
+    #[test]
+    fn remote_and_inline_code_execution_are_writes() {
+        assert!(!is_read("ssh host 'rm -rf /tmp/x'"));
+        assert!(!is_read("python3 -c 'import os; os.remove(\"x\")'"));
+        assert!(!is_read("node -e 'require(\"fs\").rmSync(\"x\")'"));
+        assert!(!is_read("perl -e 'unlink \"x\"'"));
+        assert!(!is_read("curl example.com | python3"));
+        assert!(!is_read("awk 'BEGIN{system(\"rm x\")}'"));
+        assert!(!is_read("awk '{print > \"out\"}' f"));
+        assert!(!is_read("git -c core.pager='rm x' log"));
+        assert!(is_read("python3 --version"));
+        assert!(is_read("awk '$3 > 10 {print $1}' f"));
+        assert!(is_read("awk -F: '{print $1}' /etc/passwd"));
+        assert!(is_read("git -c color.ui=never log"));
+    }
+
+    #[test]
+    fn command_runners_are_unwrapped() {
+        assert!(is_blocked("su -c 'rm -rf /'"));
+        assert!(is_blocked("su root --command='rm -rf /'"));
+        assert!(is_blocked("runuser -u nobody -- rm -rf /"));
+        assert!(is_blocked("watch rm -rf /"));
+        assert!(is_blocked("watch -n 5 'rm -rf /'"));
+        assert!(is_blocked("setsid shutdown now"));
+        assert!(is_blocked("strace -f rm -rf /"));
+        assert!(is_blocked("ltrace -e malloc rm -rf /"));
+        assert!(is_blocked("flock /tmp/l rm -rf /"));
+        assert!(is_blocked("flock -w 5 /tmp/l -c 'rm -rf /'"));
+        assert!(is_blocked("chroot / rm -rf /"));
+        assert!(is_blocked("parallel ::: 'rm -rf /'"));
+        assert!(is_blocked("parallel rm -rf ::: /"));
+        assert!(is_blocked("sudo -s rm -rf /"));
+        assert!(is_read("watch -n 5 df -h"));
+        assert!(is_read("flock /tmp/l ls"));
+        assert!(is_read("strace -p 1"));
+        assert!(!is_read("strace -o trace.log ls"));
+        assert!(!is_read("chroot /srv/jail"));
+    }
+
+    #[test]
+    fn shells_reading_stdin_are_writes() {
+        assert!(!is_read("echo 'rm -rf /' | sudo -s"));
+        assert!(!is_read("echo 'rm -rf /' | sudo -i"));
+        assert!(!is_read("echo 'rm -rf /' | su"));
+        assert!(!is_read("echo 'rm -rf /' | su - root"));
+        assert!(!is_read("echo 'rm -rf /' | parallel"));
+    }
+
+    #[test]
+    fn aliases_are_writes() {
+        assert!(!is_read("alias ls='rm -rf'; ls"));
+        assert!(is_blocked("alias x='rm -rf /'"));
+    }
+
+    #[test]
+    fn unknown_programs_are_writes() {
+        assert_eq!(verdict("make"), Verdict::Write("make".to_string()));
+        assert!(!is_read("make install"));
+        assert!(!is_read("./deploy.sh"));
+        assert!(!is_read("kubectl delete pod x"));
+        assert!(is_read("ls -la | grep x | wc -l"));
+        assert!(is_read("f() { ls; }; f"));
+    }
+
+    #[test]
+    fn arguments_that_write_are_writes() {
+        assert!(!is_read("sort -o out.txt in.txt"));
+        assert!(!is_read("sort -ro out.txt in.txt"));
+        assert!(!is_read("sort --output=out.txt in.txt"));
+        assert!(is_read("sort -t, -k2 in.txt"));
+        assert!(!is_read("sed 'w out.txt' f"));
+        assert!(!is_read("sed 's/a/b/w out.txt' f"));
+        assert!(!is_read("sed '1e rm x' f"));
+        assert!(!is_read("sed 's/.*/rm &/e' f"));
+        assert!(!is_read("sed -e p -e 'w out' f"));
+        assert!(!is_read("sed -f script.sed f"));
+        assert!(is_read("sed -n '/error/p' f"));
+        assert!(is_read("sed 's/west/east/g' f"));
+        assert!(!is_read("tar --to-command='rm x' -xf a.tar"));
+        assert!(!is_read("tar -tf a.tar --use-compress-program='rm x'"));
+        assert!(!is_read("tar -tf a.tar -I 'rm x'"));
+        assert!(!is_read("git diff --output=patch.txt"));
+        assert!(!is_read("git log --output patch.txt"));
+        assert!(!is_read("git format-patch HEAD~1"));
+        assert!(!is_read("git my-alias"));
+        assert!(is_read("git fetch origin"));
+        assert!(!is_read("cargo run"));
+        assert!(!is_read("cargo test"));
+        assert!(is_read("cargo clippy"));
+        assert!(!is_read("npm run build"));
+        assert!(!is_read("npm exec foo"));
+        assert!(is_read("npm ls"));
+        assert!(!is_read("go run main.go"));
+        assert!(is_read("go version"));
+        assert!(!is_read("ip link set eth0 down"));
+        assert!(!is_read("ip route add default via 10.0.0.1"));
+        assert!(is_read("ip addr"));
+        assert!(is_read("ip -br a s"));
+        assert!(is_read("ip route get 1.1.1.1"));
+        assert!(!is_read("hostname x"));
+        assert!(is_read("hostname -f"));
+        assert!(!is_read("date -s '2020-01-01'"));
+        assert!(!is_read("date 010112002020"));
+        assert!(is_read("date +%s"));
+        assert!(is_read("date -d yesterday +%F"));
+        assert!(!is_read("curl -dfoo=bar https://example.com"));
+        assert!(!is_read("curl -sSo out.html https://example.com"));
+        assert!(!is_read("curl -sXPOST https://example.com"));
+        assert!(is_read(
+            "curl -sSL -H 'Accept: text/plain' https://example.com"
+        ));
+    }
+
+    #[test]
+    fn unparsable_commands_fail() {
+        assert!(classify("echo 'unterminated").is_err());
+    }
+}
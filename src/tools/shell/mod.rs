@@ -1,93 +1,27 @@
+mod classify;
+pub mod parser;
+
 use anyhow::{Result, bail};
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::io::{self, Write};
 use std::path::PathBuf;
+use std::process::Stdio;
 use tokio::process::Command;
 
-use super::Tool;
+use super::{Capabilities, Tool, ToolSource};
+pub use classify::{Verdict, classify};
 
 /// Maximum output size in bytes. Anything beyond this is truncated.
 const MAX_OUTPUT_BYTES: usize = 50_000;
 
-/// Commands that are never allowed regardless of mode.
-const BLOCKED_COMMANDS: &[&str] = &[
-    "rm -rf /",
-    "rm -rf /*",
-    "mkfs",
-    "dd if=",
-    ":(){ :|:& };:",
-    "> /dev/sda",
-    "chmod -R 777 /",
-    "fork bomb",
-    "shutdown",
-    "reboot",
-    "halt",
-    "init 0",
-    "init 6",
-];
-
-/// Commands/patterns that require write mode.
-const WRITE_PATTERNS: &[&str] = &[
-    "rm ",
-    "rmdir",
-    "mv ",
-    "cp ",
-    "mkdir",
-    "touch ",
-    "chmod",
-    "chown",
-    "chgrp",
-    "ln ",
-    "install ",
-    "dd ",
-    "mkfs",
-    "fdisk",
-    "parted",
-    "mount",
-    "umount",
-    "kill",
-    "killall",
-    "pkill",
-    "systemctl start",
-    "systemctl stop",
-    "systemctl restart",
-    "systemctl enable",
-    "systemctl disable",
-    "docker rm",
-    "docker stop",
-    "docker kill",
-    "apt ",
-    "yay ",
-    "pacman -S",
-    "pacman -R",
-    "pip install",
-    "cargo install",
-    "npm install",
-    "git push",
-    "git commit",
-    "git reset",
-    "git checkout",
-    "git merge",
-    "git rebase",
-    "curl.*-X POST",
-    "curl.*-X PUT",
-    "curl.*-X DELETE",
-    "wget ",
-    "> ",
-    ">> ",
-    "tee ",
-    "sed -i",
-    "truncate",
-];
-
 /// Safe environment variables to pass through. Everything else is stripped.
 const SAFE_ENV_VARS: &[&str] = &[
     "PATH", "HOME", "USER", "SHELL", "LANG", "LC_ALL", "TERM", "TZ",
 ];
 
 /// Shell execution mode.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ShellMode {
     /// Only read-only commands allowed (default).
     ReadOnly,
@@ -95,6 +29,16 @@ pub enum ShellMode {
     ReadWrite,
 }
 
+impl ShellMode {
+    /// `read-only` or `read-write`, as in config files.
+    pub fn label(self) -> &'static str {
+        match self {
+            ShellMode::ReadOnly => "read-only",
+            ShellMode::ReadWrite => "read-write",
+        }
+    }
+}
+
 /// Configuration for the shell tool.
 #[derive(Debug, Clone)]
 pub struct ShellConfig {
@@ -125,56 +69,6 @@ impl ShellTool {
         Self { config }
     }
 
-    /// Check if a command is always blocked.
-    fn is_blocked(cmd: &str) -> bool {
-        let lower = cmd.to_lowercase();
-        BLOCKED_COMMANDS.iter().any(|pat| lower.contains(pat))
-    }
-
-    /// Check if a command requires write mode.
-    fn is_write_command(cmd: &str) -> bool {
-        let trimmed = cmd.trim();
-
-        // Pipe chains: check each segment
-        for segment in trimmed.split('|') {
-            let seg = segment.trim();
-            if Self::segment_is_write(seg) {
-                return true;
-            }
-        }
-
-        // Command chains: ;, &&, ||
-        for segment in trimmed.split(&[';', '&', '|'][..]) {
-            let seg = segment.trim();
-            if Self::segment_is_write(seg) {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn segment_is_write(segment: &str) -> bool {
-        let seg = segment.trim();
-        if seg.is_empty() {
-            return false;
-        }
-
-        // Check for output redirection
-        if seg.contains("> ") || seg.contains(">>") {
-            return true;
-        }
-
-        WRITE_PATTERNS.iter().any(|pat| {
-            // Check if pattern matches the start of the command or appears after sudo
-            let seg_lower = seg.to_lowercase();
-            let pat_lower = pat.to_lowercase();
-            seg_lower.starts_with(&pat_lower)
-                || seg_lower.starts_with(&format!("sudo {}", pat_lower))
-                || seg_lower.contains(&pat_lower)
-        })
-    }
-
     fn truncate_output(output: &str, max_bytes: usize) -> String {
         if output.len() <= max_bytes {
             return output.to_string();
@@ -199,13 +93,36 @@ impl ShellTool {
             .filter_map(|key| std::env::var(key).ok().map(|val| (key.to_string(), val)))
             .collect()
     }
+}
+
+/// Kills a child's process group when dropped, unless disarmed first.
+///
+/// `kill_on_drop` only reaches the immediate `sh` process; pipelines and
+/// background jobs it started would otherwise keep running.
+struct ProcessGroupGuard {
+    pgid: Option<u32>,
+}
 
-    fn confirm(cmd: &str) -> Result<bool> {
-        print!("  Execute: {} [y/N] ", cmd);
-        io::stdout().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        Ok(input.trim().eq_ignore_ascii_case("y"))
+impl ProcessGroupGuard {
+    fn new(pgid: Option<u32>) -> Self {
+        Self { pgid }
+    }
+
+    /// The command finished on its own — nothing to clean up.
+    fn disarm(mut self) {
+        self.pgid = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid.and_then(|id| libc::pid_t::try_from(id).ok()) {
+            // SAFETY: killpg only sends a signal; a stale group id yields ESRCH.
+            unsafe {
+                libc::killpg(pgid, libc::SIGKILL);
+            }
+        }
     }
 }
 
@@ -226,28 +143,47 @@ impl Tool for ShellTool {
         }
     }
 
-    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+    fn capabilities(&self) -> Capabilities {
+        let writes = self.config.mode == ShellMode::ReadWrite;
+        Capabilities {
+            read_only: !writes,
+            // Even read-only commands reach the network (curl, ssh, git
+            // fetch, /dev/tcp), too many ways to tell them apart
+            network: true,
+            destructive: writes,
+            needs_confirmation: self.config.require_confirmation,
+        }
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::BuiltIn
+    }
+
+    /// Classify the parsed command: dangerous ones are always blocked,
+    /// writes only in read-only mode.
+    fn precheck(&self, args: &HashMap<String, String>) -> Result<()> {
         let cmd = args
             .get("command")
             .ok_or_else(|| anyhow::anyhow!("missing required arg: command"))?;
-
-        // Always block dangerous commands
-        if Self::is_blocked(cmd) {
-            bail!("blocked: command is on the deny list");
-        }
-
-        // Check write mode
-        if self.config.mode == ShellMode::ReadOnly && Self::is_write_command(cmd) {
-            bail!(
-                "blocked: write operation not allowed in read-only mode. \
-                 Start golem with --allow-write to enable write operations."
-            );
+        match classify(cmd) {
+            Err(e) => bail!("blocked: could not parse command: {e}"),
+            Ok(Verdict::Blocked(reason)) => {
+                bail!("blocked: command is on the deny list ({reason})")
+            }
+            Ok(Verdict::Write(what)) if self.config.mode == ShellMode::ReadOnly => {
+                bail!(
+                    "blocked: write operation not allowed in read-only mode ({what}). \
+                     Start golem with --allow-write (or /mode read-write) to enable write operations."
+                );
+            }
+            Ok(_) => Ok(()),
         }
+    }
 
-        // Confirmation prompt
-        if self.config.require_confirmation && !Self::confirm(cmd)? {
-            bail!("cancelled by user");
-        }
+    async fn execute(&self, args: &HashMap<String, String>) -> Result<String> {
+        // Checked again for callers that skip the registry
+        self.precheck(args)?;
+        let cmd = &args["command"];
 
         // Ensure working directory exists
         let work_dir = &self.config.working_dir;
@@ -255,16 +191,29 @@ impl Tool for ShellTool {
             tokio::fs::create_dir_all(work_dir).await?;
         }
 
-        // Build command with sanitized environment
+        // Build command with sanitized environment. The child leads its own
+        // process group so cancellation can take down everything it spawned.
         let env_vars = Self::filtered_env();
-        let output = Command::new("sh")
+        let mut command = Command::new("sh");
+        command
             .arg("-c")
             .arg(cmd)
             .current_dir(work_dir)
             .env_clear()
             .envs(env_vars)
-            .output()
-            .await?;
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let child = command.spawn()?;
+        // If this future is dropped (timeout, Ctrl+C) before the command
+        // finishes, the guard kills the whole process group.
+        let guard = ProcessGroupGuard::new(child.id());
+        let output = child.wait_with_output().await?;
+        guard.disarm();
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -0,0 +1,785 @@
+//! Minimal POSIX shell lexer and parser.
+//!
+//! This is not an interpreter. It understands just enough of the shell
+//! grammar — quoting, escapes, operators, redirections, and command
+//! substitution — to recover the argv of every simple command a string
+//! would run, so safety checks can look at real program names instead
+//! of substrings.
+
+use anyhow::{Result, bail};
+
+/// Maximum nesting depth for command substitutions and `sh -c` scripts.
+pub const MAX_DEPTH: usize = 8;
+
+/// A single word after quote removal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    /// Literal text with quotes removed and escapes resolved.
+    pub text: String,
+    /// Whether the word contains an expansion the shell resolves at
+    /// runtime (`$VAR`, `$(...)`, backticks), so `text` is not final.
+    pub dynamic: bool,
+}
+
+/// A redirection attached to a command, e.g. `> out.txt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    pub op: String,
+    pub target: Word,
+}
+
+impl Redirect {
+    /// Whether this redirection writes to its target.
+    pub fn is_output(&self) -> bool {
+        match self.op.as_str() {
+            ">" | ">>" | ">|" | "&>" | "&>>" | "<>" => true,
+            // `>&2` duplicates a descriptor; `>&file` writes to a file.
+            ">&" => {
+                let target = &self.target.text;
+                target != "-" && !target.chars().all(|c| c.is_ascii_digit())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// One program invocation: `FOO=1 cmd arg1 arg2 > file`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SimpleCommand {
+    pub assignments: Vec<String>,
+    pub argv: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+/// Everything a command string would run.
+#[derive(Debug, Default)]
+pub struct Script {
+    /// Pipelines in order; each is a list of commands connected by `|`.
+    /// Commands from substitutions and subshells appear as their own pipelines.
+    pub pipelines: Vec<Vec<SimpleCommand>>,
+    /// Names of shell functions defined in the script.
+    pub functions: Vec<String>,
+}
+
+impl Script {
+    /// All simple commands, flattened across pipelines.
+    pub fn commands(&self) -> impl Iterator<Item = &SimpleCommand> {
+        self.pipelines.iter().flatten()
+    }
+
+    fn absorb(&mut self, other: Script) {
+        self.pipelines.extend(other.pipelines);
+        self.functions.extend(other.functions);
+    }
+}
+
+/// Parse a command string into its simple commands.
+pub fn parse(input: &str) -> Result<Script> {
+    parse_nested(input, 0)
+}
+
+/// Parse a script that appears inside another one (substitution, `sh -c`).
+pub fn parse_nested(input: &str, depth: usize) -> Result<Script> {
+    if depth > MAX_DEPTH {
+        bail!("command nesting is too deep");
+    }
+    let mut lexer = Lexer::new(input, depth);
+    let tokens = lexer.tokenize()?;
+    let mut script = build(tokens)?;
+    script.absorb(lexer.nested);
+    Ok(script)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(Word),
+    /// `|` or `|&` — joins commands into a pipeline.
+    Pipe,
+    /// `;`, `&`, `&&`, `||`, `;;`, newline — ends a pipeline.
+    Separator,
+    LParen,
+    RParen,
+    Redirect(String),
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    /// Scripts found inside substitutions, parsed recursively.
+    nested: Script,
+}
+
+impl Lexer {
+    fn new(input: &str, depth: usize) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            depth,
+            nested: Script::default(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn tokenize(&mut self) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut word = String::new();
+        let mut in_word = false;
+        let mut dynamic = false;
+
+        macro_rules! flush {
+            () => {
+                if in_word {
+                    tokens.push(Token::Word(Word {
+                        text: std::mem::take(&mut word),
+                        dynamic,
+                    }));
+                    in_word = false;
+                    dynamic = false;
+                }
+            };
+        }
+
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.pos += 1;
+                    flush!();
+                }
+                '\n' => {
+                    self.pos += 1;
+                    flush!();
+                    tokens.push(Token::Separator);
+                }
+                '#' if !in_word => {
+                    while let Some(c) = self.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                '\'' => {
+                    self.pos += 1;
+                    in_word = true;
+                    loop {
+                        match self.peek() {
+                            Some('\'') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            Some(c) => {
+                                word.push(c);
+                                self.pos += 1;
+                            }
+                            None => bail!("unterminated single quote"),
+                        }
+                    }
+                }
+                '"' => {
+                    self.pos += 1;
+                    in_word = true;
+                    dynamic |= self.double_quoted(&mut word)?;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(c) => {
+                            word.push(c);
+                            in_word = true;
+                            self.pos += 1;
+                        }
+                        None => {}
+                    }
+                }
+                '$' => {
+                    in_word = true;
+                    dynamic |= self.dollar(&mut word)?;
+                }
+                '`' => {
+                    self.pos += 1;
+                    in_word = true;
+                    dynamic = true;
+                    let inner = self.backtick()?;
+                    self.substitute(&inner)?;
+                }
+                '|' => {
+                    flush!();
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('|') => {
+                            self.pos += 1;
+                            tokens.push(Token::Separator);
+                        }
+                        Some('&') => {
+                            self.pos += 1;
+                            tokens.push(Token::Pipe);
+                        }
+                        _ => tokens.push(Token::Pipe),
+                    }
+                }
+                '&' => {
+                    flush!();
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('&') => {
+                            self.pos += 1;
+                            tokens.push(Token::Separator);
+                        }
+                        Some('>') => {
+                            self.pos += 1;
+                            if self.peek() == Some('>') {
+                                self.pos += 1;
+                                tokens.push(Token::Redirect("&>>".to_string()));
+                            } else {
+                                tokens.push(Token::Redirect("&>".to_string()));
+                            }
+                        }
+                        _ => tokens.push(Token::Separator),
+                    }
+                }
+                ';' => {
+                    flush!();
+                    self.pos += 1;
+                    if self.peek() == Some(';') {
+                        self.pos += 1;
+                    }
+                    tokens.push(Token::Separator);
+                }
+                '(' => {
+                    flush!();
+                    self.pos += 1;
+                    tokens.push(Token::LParen);
+                }
+                ')' => {
+                    flush!();
+                    self.pos += 1;
+                    tokens.push(Token::RParen);
+                }
+                '<' | '>' => {
+                    // Process substitution: <(cmd) or >(cmd)
+                    if self.peek_at(1) == Some('(') {
+                        self.pos += 2;
+                        let inner = self.balanced(')')?;
+                        self.substitute(&inner)?;
+                        in_word = true;
+                        dynamic = true;
+                        continue;
+                    }
+                    // A word made only of digits right before the operator is an fd number.
+                    if in_word && !dynamic && word.chars().all(|c| c.is_ascii_digit()) {
+                        word.clear();
+                        in_word = false;
+                    }
+                    flush!();
+                    tokens.push(Token::Redirect(self.redirect_op()));
+                }
+                _ => {
+                    word.push(c);
+                    in_word = true;
+                    self.pos += 1;
+                }
+            }
+        }
+        if in_word {
+            tokens.push(Token::Word(Word {
+                text: word,
+                dynamic,
+            }));
+        }
+        Ok(tokens)
+    }
+
+    fn redirect_op(&mut self) -> String {
+        let first = self.chars[self.pos];
+        self.pos += 1;
+        let mut op = first.to_string();
+        match (first, self.peek()) {
+            ('>', Some('>')) | ('>', Some('|')) | ('>', Some('&')) => {
+                op.push(self.chars[self.pos]);
+                self.pos += 1;
+            }
+            ('<', Some('<')) => {
+                op.push('<');
+                self.pos += 1;
+                if matches!(self.peek(), Some('<') | Some('-')) {
+                    op.push(self.chars[self.pos]);
+                    self.pos += 1;
+                }
+            }
+            ('<', Some('&')) | ('<', Some('>')) => {
+                op.push(self.chars[self.pos]);
+                self.pos += 1;
+            }
+            _ => {}
+        }
+        op
+    }
+
+    /// Consume the body of a double-quoted string (opening quote already
+    /// consumed). Returns whether it contained an expansion.
+    fn double_quoted(&mut self, word: &mut String) -> Result<bool> {
+        let mut dynamic = false;
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(dynamic);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c @ ('$' | '`' | '"' | '\\')) => {
+                            word.push(c);
+                            self.pos += 1;
+                        }
+                        Some('\n') => self.pos += 1,
+                        Some(c) => {
+                            word.push('\\');
+                            word.push(c);
+                            self.pos += 1;
+                        }
+                        None => bail!("unterminated double quote"),
+                    }
+                }
+                Some('$') => dynamic |= self.dollar(word)?,
+                Some('`') => {
+                    self.pos += 1;
+                    dynamic = true;
+                    let inner = self.backtick()?;
+                    self.substitute(&inner)?;
+                }
+                Some(c) => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+                None => bail!("unterminated double quote"),
+            }
+        }
+    }
+
+    /// Handle a `$` at the current position. Returns whether the result is dynamic.
+    fn dollar(&mut self, word: &mut String) -> Result<bool> {
+        self.pos += 1;
+        match self.peek() {
+            Some('(') if self.peek_at(1) == Some('(') => {
+                // Arithmetic expansion: $(( ... ))
+                self.pos += 2;
+                let inner = self.balanced(')')?;
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                }
+                word.push_str(&format!("$(({inner}))"));
+                Ok(true)
+            }
+            Some('(') => {
+                self.pos += 1;
+                let inner = self.balanced(')')?;
+                self.substitute(&inner)?;
+                Ok(true)
+            }
+            Some('{') => {
+                self.pos += 1;
+                let inner = self.balanced('}')?;
+                word.push_str(&format!("${{{inner}}}"));
+                Ok(true)
+            }
+            Some('\'') => {
+                // ANSI-C quoting: $'\x72m' is "rm", so resolve escapes.
+                self.pos += 1;
+                self.ansi_c_quoted(word)?;
+                Ok(false)
+            }
+            Some(c) if c.is_ascii_alphanumeric() || "_@*#?$!-".contains(c) => {
+                word.push('$');
+                if c.is_ascii_alphabetic() || c == '_' {
+                    while let Some(c) = self.peek() {
+                        if !(c.is_ascii_alphanumeric() || c == '_') {
+                            break;
+                        }
+                        word.push(c);
+                        self.pos += 1;
+                    }
+                } else {
+                    word.push(c);
+                    self.pos += 1;
+                }
+                Ok(true)
+            }
+            _ => {
+                word.push('$');
+                Ok(false)
+            }
+        }
+    }
+
+    fn ansi_c_quoted(&mut self, word: &mut String) -> Result<()> {
+        loop {
+            match self.peek() {
+                Some('\'') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let Some(c) = self.peek() else {
+                        bail!("unterminated $'...' string");
+                    };
+                    self.pos += 1;
+                    match c {
+                        'n' => word.push('\n'),
+                        't' => word.push('\t'),
+                        'r' => word.push('\r'),
+                        'x' => {
+                            let code = self.radix_digits(16, 2);
+                            word.push(char::from_u32(code).unwrap_or('?'));
+                        }
+                        '0'..='7' => {
+                            self.pos -= 1;
+                            let code = self.radix_digits(8, 3);
+                            word.push(char::from_u32(code).unwrap_or('?'));
+                        }
+                        other => word.push(other),
+                    }
+                }
+                Some(c) => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+                None => bail!("unterminated $'...' string"),
+            }
+        }
+    }
+
+    fn radix_digits(&mut self, radix: u32, max: usize) -> u32 {
+        let mut value = 0;
+        for _ in 0..max {
+            match self.peek().and_then(|c| c.to_digit(radix)) {
+                Some(d) => {
+                    value = value * radix + d;
+                    self.pos += 1;
+                }
+                None => break,
+            }
+        }
+        value
+    }
+
+    /// Read until the matching `close`, respecting quotes and nesting.
+    /// The opening delimiter must already be consumed.
+    fn balanced(&mut self, close: char) -> Result<String> {
+        let open = if close == ')' { '(' } else { '{' };
+        let mut depth = 1;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    out.push(c);
+                    if let Some(next) = self.peek() {
+                        out.push(next);
+                        self.pos += 1;
+                    }
+                    continue;
+                }
+                '\'' | '"' => {
+                    out.push(c);
+                    while let Some(q) = self.peek() {
+                        self.pos += 1;
+                        out.push(q);
+                        if q == '\\' && c == '"' {
+                            if let Some(next) = self.peek() {
+                                out.push(next);
+                                self.pos += 1;
+                            }
+                        } else if q == c {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                _ if c == open => depth += 1,
+                _ if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(out);
+                    }
+                }
+                _ => {}
+            }
+            out.push(c);
+        }
+        bail!("unterminated '{open}'")
+    }
+
+    fn backtick(&mut self) -> Result<String> {
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '`' => return Ok(out),
+                '\\' => {
+                    if let Some(next) = self.peek() {
+                        self.pos += 1;
+                        if !matches!(next, '`' | '\\' | '$') {
+                            out.push('\\');
+                        }
+                        out.push(next);
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        bail!("unterminated backtick")
+    }
+
+    fn substitute(&mut self, inner: &str) -> Result<()> {
+        let script = parse_nested(inner, self.depth + 1)?;
+        self.nested.absorb(script);
+        Ok(())
+    }
+}
+
+/// Reserved words that may appear where a command name is expected but
+/// are not themselves programs.
+const KEYWORDS: &[&str] = &[
+    "!", "{", "}", "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "time",
+    "esac",
+];
+
+/// Compound-command headers whose remaining words are not a command.
+const HEADERS: &[&str] = &["for", "select", "case"];
+
+fn is_assignment(text: &str) -> bool {
+    match text.split_once('=') {
+        Some((name, _)) => {
+            let mut chars = name.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+fn build(tokens: Vec<Token>) -> Result<Script> {
+    let mut script = Script::default();
+    let mut pipeline: Vec<SimpleCommand> = Vec::new();
+    let mut current = SimpleCommand::default();
+    let mut pending_redirect: Option<String> = None;
+    let mut skipping_header = false;
+
+    fn finish_command(current: &mut SimpleCommand, pipeline: &mut Vec<SimpleCommand>) {
+        let cmd = std::mem::take(current);
+        if !cmd.argv.is_empty() || !cmd.redirects.is_empty() || !cmd.assignments.is_empty() {
+            pipeline.push(cmd);
+        }
+    }
+
+    fn finish_pipeline(pipeline: &mut Vec<SimpleCommand>, script: &mut Script) {
+        if !pipeline.is_empty() {
+            script.pipelines.push(std::mem::take(pipeline));
+        }
+    }
+
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Word(word) => {
+                if let Some(op) = pending_redirect.take() {
+                    current.redirects.push(Redirect { op, target: word });
+                } else if skipping_header {
+                    // `for x in a b c` — loop variables and values, not a command.
+                    if word.text == "do" {
+                        skipping_header = false;
+                    }
+                } else if current.argv.is_empty() && is_assignment(&word.text) {
+                    current.assignments.push(word.text);
+                } else if current.argv.is_empty() && KEYWORDS.contains(&word.text.as_str()) {
+                    // Reserved word — the command follows it.
+                } else if current.argv.is_empty() && HEADERS.contains(&word.text.as_str()) {
+                    skipping_header = true;
+                } else if current.argv.is_empty() && word.text == "function" {
+                    if let Some(Token::Word(name)) = iter.next() {
+                        script.functions.push(name.text);
+                    }
+                } else {
+                    current.argv.push(word);
+                }
+            }
+            Token::Redirect(op) => {
+                if pending_redirect.is_some() {
+                    bail!("syntax error near redirection '{op}'");
+                }
+                pending_redirect = Some(op);
+            }
+            Token::Pipe => {
+                if pending_redirect.is_some() {
+                    bail!("syntax error: redirection without a target");
+                }
+                finish_command(&mut current, &mut pipeline);
+            }
+            Token::Separator => {
+                if pending_redirect.is_some() {
+                    bail!("syntax error: redirection without a target");
+                }
+                skipping_header = false;
+                finish_command(&mut current, &mut pipeline);
+                finish_pipeline(&mut pipeline, &mut script);
+            }
+            Token::LParen => {
+                // `name() { ... }` defines a function.
+                if current.argv.len() == 1 && iter.peek() == Some(&Token::RParen) {
+                    iter.next();
+                    let name = current.argv.pop().expect("checked len");
+                    script.functions.push(name.text);
+                } else {
+                    // Subshell — its contents are ordinary commands.
+                    finish_command(&mut current, &mut pipeline);
+                    finish_pipeline(&mut pipeline, &mut script);
+                }
+            }
+            Token::RParen => {
+                skipping_header = false;
+                finish_command(&mut current, &mut pipeline);
+                finish_pipeline(&mut pipeline, &mut script);
+            }
+        }
+    }
+
+    if pending_redirect.is_some() {
+        bail!("syntax error: redirection without a target");
+    }
+    finish_command(&mut current, &mut pipeline);
+    finish_pipeline(&mut pipeline, &mut script);
+    Ok(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argvs(input: &str) -> Vec<Vec<String>> {
+        parse(input)
+            .unwrap()
+            .commands()
+            .map(|c| c.argv.iter().map(|w| w.text.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn splits_simple_words() {
+        assert_eq!(argvs("ls -la /tmp"), vec![vec!["ls", "-la", "/tmp"]]);
+    }
+
+    #[test]
+    fn removes_quotes() {
+        assert_eq!(argvs(r#"r""m -rf 'a b'"#), vec![vec!["rm", "-rf", "a b"]]);
+        assert_eq!(argvs(r"\r\m x"), vec![vec!["rm", "x"]]);
+    }
+
+    #[test]
+    fn resolves_ansi_c_quotes() {
+        assert_eq!(argvs(r"$'\x72\x6d' x"), vec![vec!["rm", "x"]]);
+    }
+
+    #[test]
+    fn splits_pipelines_and_lists() {
+        let script = parse("cat a | grep b && echo c; ls || pwd & date").unwrap();
+        assert_eq!(script.pipelines.len(), 5);
+        assert_eq!(script.pipelines[0].len(), 2);
+    }
+
+    #[test]
+    fn operators_inside_quotes_are_literal() {
+        assert_eq!(
+            argvs("echo 'a | b; c > d'"),
+            vec![vec!["echo", "a | b; c > d"]]
+        );
+    }
+
+    #[test]
+    fn collects_redirects() {
+        let script = parse("echo hi > out.txt 2>/dev/null").unwrap();
+        let cmd = script.commands().next().unwrap();
+        assert_eq!(cmd.argv.len(), 2);
+        assert_eq!(cmd.redirects.len(), 2);
+        assert_eq!(cmd.redirects[0].op, ">");
+        assert_eq!(cmd.redirects[0].target.text, "out.txt");
+        assert_eq!(cmd.redirects[1].target.text, "/dev/null");
+        assert!(cmd.redirects.iter().all(|r| r.is_output()));
+    }
+
+    #[test]
+    fn fd_duplication_is_not_output() {
+        let script = parse("ls 2>&1").unwrap();
+        let cmd = script.commands().next().unwrap();
+        assert_eq!(cmd.redirects[0].op, ">&");
+        assert!(!cmd.redirects[0].is_output());
+    }
+
+    #[test]
+    fn parses_command_substitution() {
+        let cmds = argvs("echo $(rm x) `touch y`");
+        assert!(cmds.contains(&vec!["rm".to_string(), "x".to_string()]));
+        assert!(cmds.contains(&vec!["touch".to_string(), "y".to_string()]));
+    }
+
+    #[test]
+    fn substitution_marks_word_dynamic() {
+        let script = parse("$(echo rm) file").unwrap();
+        let outer = script.pipelines.first().unwrap().first().unwrap();
+        assert!(outer.argv[0].dynamic);
+    }
+
+    #[test]
+    fn skips_assignments_and_keywords() {
+        assert_eq!(argvs("FOO=1 BAR=2 env"), vec![vec!["env"]]);
+        assert_eq!(
+            argvs("if true; then ls; fi"),
+            vec![vec!["true"], vec!["ls"]]
+        );
+    }
+
+    #[test]
+    fn records_function_definitions() {
+        let script = parse(":(){ :|:& };:").unwrap();
+        assert_eq!(script.functions, vec![":"]);
+    }
+
+    #[test]
+    fn for_loop_header_is_not_a_command() {
+        assert_eq!(
+            argvs("for f in a b; do cat $f; done"),
+            vec![vec!["cat", "$f"]]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_error() {
+        assert!(parse("echo 'oops").is_err());
+        assert!(parse("echo \"oops").is_err());
+        assert!(parse("echo $(oops").is_err());
+    }
+
+    #[test]
+    fn dangling_redirect_is_error() {
+        assert!(parse("echo >").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_error() {
+        let mut cmd = "ls".to_string();
+        for _ in 0..=MAX_DEPTH + 1 {
+            cmd = format!("echo $({cmd})");
+        }
+        assert!(parse(&cmd).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Raw transcript logging.
+//!
+//! When enabled, every LLM request/response and tool execution is appended
+//! as one JSON object per line to `<dir>/<session>.jsonl`. The files are
+//! meant for debugging, replay, and evals — only `golem replay` reads them
+//! back, never a running task, so write failures only warn.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::thinker::TokenUsage;
+
+/// One line in a transcript file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptLine {
+    /// Milliseconds since the Unix epoch.
+    pub ts: u64,
+    #[serde(flatten)]
+    pub event: TranscriptEvent,
+}
+
+/// What happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    /// A task was handed to the engine.
+    Task { task: String },
+    /// The exact request body sent to the model.
+    LlmRequest {
+        model: String,
+        system: String,
+        messages: serde_json::Value,
+    },
+    /// The raw text the model returned, before parsing.
+    LlmResponse {
+        model: String,
+        text: String,
+        usage: Option<TokenUsage>,
+    },
+    /// A single tool execution.
+    Tool {
+        tool: String,
+        args: HashMap<String, String>,
+        success: bool,
+        output: String,
+        duration_ms: u64,
+    },
+    /// The task finished with this answer.
+    Answer { answer: String },
+    /// The task was cancelled before it finished.
+    Interrupted,
+}
+
+/// Appends transcript lines to one file per named session.
+pub struct Transcript {
+    dir: PathBuf,
+    file: Mutex<(PathBuf, File)>,
+}
+
+impl Transcript {
+    /// Open (or create) the transcript for `session` inside `dir`.
+    pub fn open(dir: impl Into<PathBuf>, session: &str) -> Result<Self> {
+        let dir = dir.into();
+        let file = open_session_file(&dir, session)?;
+        Ok(Self {
+            dir,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Path of the file currently being written.
+    pub fn path(&self) -> PathBuf {
+        self.file
+            .lock()
+            .expect("transcript lock poisoned")
+            .0
+            .clone()
+    }
+
+    /// Start writing to the file for another named session.
+    pub fn set_session(&self, session: &str) -> Result<()> {
+        let file = open_session_file(&self.dir, session)?;
+        *self.file.lock().expect("transcript lock poisoned") = file;
+        Ok(())
+    }
+
+    /// Append one event. Failures are reported on stderr, never returned —
+    /// a full disk should not abort the task being logged.
+    pub fn record(&self, event: TranscriptEvent) {
+        let line = TranscriptLine {
+            ts: now_millis(),
+            event,
+        };
+        let result = serde_json::to_string(&line)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| {
+                let mut guard = self.file.lock().expect("transcript lock poisoned");
+                writeln!(guard.1, "{json}")?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            eprintln!("  warning: failed to write transcript: {e}");
+        }
+    }
+}
+
+/// Session names are user input; keep the file name to a safe character set.
+fn file_name(session: &str) -> String {
+    let safe: String = session
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let safe = safe.trim_start_matches('.');
+    if safe.is_empty() {
+        "session.jsonl".to_string()
+    } else {
+        format!("{safe}.jsonl")
+    }
+}
+
+fn open_session_file(dir: &Path, session: &str) -> Result<(PathBuf, File)> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("cannot create transcript directory {}", dir.display()))?;
+    let path = dir.join(file_name(session));
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("cannot open transcript {}", path.display()))?;
+    Ok((path, file))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Parse a transcript file back into lines.
+pub fn read_transcript(path: &Path) -> Result<Vec<TranscriptLine>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("cannot read transcript {}", path.display()))?;
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).map_err(anyhow::Error::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_one_json_object_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = Transcript::open(dir.path(), "default").unwrap();
+        transcript.record(TranscriptEvent::Task {
+            task: "list files".to_string(),
+        });
+        transcript.record(TranscriptEvent::Answer {
+            answer: "done".to_string(),
+        });
+
+        let path = dir.path().join("default.jsonl");
+        assert_eq!(transcript.path(), path);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with("{\"ts\":"));
+        assert!(text.contains("\"type\":\"task\""));
+
+        let lines = read_transcript(&path).unwrap();
+        assert_eq!(
+            lines[1].event,
+            TranscriptEvent::Answer {
+                answer: "done".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn reopening_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        for _ in 0..2 {
+            let transcript = Transcript::open(dir.path(), "default").unwrap();
+            transcript.record(TranscriptEvent::Task {
+                task: "t".to_string(),
+            });
+        }
+        let lines = read_transcript(&dir.path().join("default.jsonl")).unwrap();
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn set_session_switches_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = Transcript::open(dir.path(), "default").unwrap();
+        transcript.set_session("work").unwrap();
+        transcript.record(TranscriptEvent::Task {
+            task: "t".to_string(),
+        });
+        assert_eq!(transcript.path(), dir.path().join("work.jsonl"));
+        assert_eq!(
+            read_transcript(&dir.path().join("work.jsonl"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn file_name_is_sanitized() {
+        assert_eq!(file_name("my project"), "my_project.jsonl");
+        assert_eq!(file_name("../etc/passwd"), "_etc_passwd.jsonl");
+        assert_eq!(file_name("..."), "session.jsonl");
+    }
+}
@@ -162,6 +162,38 @@ fn remove_one_preserves_others() {
     assert!(storage.get("openai").unwrap().is_some());
 }
 
+// ── Accounts ──────────────────────────────────────────────────────
+
+fn api_key(storage: &AuthStorage) -> Option<String> {
+    match storage.get("anthropic").unwrap() {
+        Some(Credential::ApiKey { key }) => Some(key),
+        _ => None,
+    }
+}
+
+#[test]
+fn accounts_hold_separate_credentials() {
+    let storage = mem_storage();
+    assert_eq!(storage.account("anthropic").unwrap(), "default");
+    let key = |key: &str| Credential::ApiKey {
+        key: key.to_string(),
+    };
+    storage.set("anthropic", key("personal-key")).unwrap();
+
+    storage.set_account("anthropic", "work").unwrap();
+    assert_eq!(api_key(&storage), None);
+    storage.set("anthropic", key("work-key")).unwrap();
+    assert_eq!(api_key(&storage).as_deref(), Some("work-key"));
+    assert_eq!(storage.accounts("anthropic").unwrap(), ["default", "work"]);
+
+    storage.set_account("anthropic", "default").unwrap();
+    assert_eq!(api_key(&storage).as_deref(), Some("personal-key"));
+    storage.remove("anthropic").unwrap();
+    assert_eq!(storage.accounts("anthropic").unwrap(), ["work"]);
+    // Other providers keep their own choice
+    assert_eq!(storage.account("openai").unwrap(), "default");
+}
+
 // ── get_api_key resolution ────────────────────────────────────────
 
 #[tokio::test]
@@ -293,6 +325,40 @@ fn file_based_storage_persists() {
     }
 }
 
+#[test]
+fn legacy_auth_json_is_imported_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let legacy = dir.path().join("auth.json");
+    std::fs::write(
+        &legacy,
+        r#"{
+            "anthropic": {"type": "api_key", "key": "sk-legacy"},
+            "openai": {"type": "oauth", "access": "a", "refresh": "r", "expires": 0}
+        }"#,
+    )
+    .unwrap();
+    let storage = mem_storage();
+    storage
+        .set(
+            "openai",
+            Credential::ApiKey {
+                key: "sk-newer".to_string(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(auth::import_legacy(&storage, &legacy).unwrap(), 1);
+    assert_eq!(api_key(&storage).as_deref(), Some("sk-legacy"));
+    // The database's own credential wins
+    match storage.get("openai").unwrap().unwrap() {
+        Credential::ApiKey { key } => assert_eq!(key, "sk-newer"),
+        _ => panic!("expected ApiKey"),
+    }
+    assert!(!legacy.exists());
+    assert!(dir.path().join("auth.json.imported").exists());
+    assert_eq!(auth::import_legacy(&storage, &legacy).unwrap(), 0);
+}
+
 // ── OAuth URL ─────────────────────────────────────────────────────
 
 #[test]
@@ -322,7 +388,7 @@ fn authorize_url_is_unique_per_call() {
 
 #[tokio::test]
 async fn login_rejects_unsupported_provider() {
-    let err = auth::login(":memory:", "openai", "code", "verifier")
+    let err = auth::login(":memory:", "openai", "code", "verifier", "uri")
         .await
         .unwrap_err();
     assert!(
@@ -333,7 +399,7 @@ async fn login_rejects_unsupported_provider() {
 
 #[tokio::test]
 async fn login_rejects_empty_provider() {
-    let err = auth::login(":memory:", "", "code", "verifier")
+    let err = auth::login(":memory:", "", "code", "verifier", "uri")
         .await
         .unwrap_err();
     assert!(
@@ -345,6 +411,17 @@ async fn login_rejects_empty_provider() {
 // Note: login happy path cannot be tested without mocking the Anthropic
 // token endpoint — exchange_code makes a real HTTP call.
 
+#[tokio::test]
+async fn device_login_needs_a_provider_that_offers_it() {
+    let err = auth::device_login(":memory:", "anthropic")
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("--paste"),
+        "unexpected error: {err}"
+    );
+}
+
 // ── auth::logout ──────────────────────────────────────────────────
 
 #[test]
@@ -1,5 +1,11 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use golem::memory::embedding::Embedder;
+use golem::memory::in_memory::InMemoryMemory;
 use golem::memory::sqlite::SqliteMemory;
-use golem::memory::{Memory, MemoryEntry, SessionEntry};
+use golem::memory::{HitSource, Memory, MemoryEntry, SessionEntry};
+use golem::thinker::ToolCall;
 use golem::tools::{Outcome, ToolResult};
 
 #[tokio::test]
@@ -40,6 +46,42 @@ async fn history_preserves_order() {
     assert!(matches!(&history[1], MemoryEntry::Answer { .. }));
 }
 
+#[tokio::test]
+async fn history_reads_a_page_at_a_time() {
+    let memories: [Box<dyn Memory>; 2] = [
+        Box::new(SqliteMemory::in_memory().unwrap()),
+        Box::new(InMemoryMemory::new()),
+    ];
+    for mem in memories {
+        for i in 0..5 {
+            mem.store(MemoryEntry::Task {
+                content: format!("entry {i}"),
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(mem.history_len().await.unwrap(), 5);
+
+        let contents = |page: Vec<MemoryEntry>| -> Vec<String> {
+            page.into_iter()
+                .map(|entry| match entry {
+                    MemoryEntry::Task { content } => content,
+                    other => panic!("unexpected {other:?}"),
+                })
+                .collect()
+        };
+        assert_eq!(
+            contents(mem.history_page(1, 2).await.unwrap()),
+            ["entry 1", "entry 2"]
+        );
+        assert_eq!(
+            contents(mem.history_page(4, 10).await.unwrap()),
+            ["entry 4"]
+        );
+        assert!(mem.history_page(5, 10).await.unwrap().is_empty());
+    }
+}
+
 #[tokio::test]
 async fn recall_finds_matching_entries() {
     let mem = SqliteMemory::in_memory().unwrap();
@@ -97,6 +139,7 @@ fn display_answer() {
 fn display_iteration_with_success_and_error() {
     let entry = MemoryEntry::Iteration {
         thought: "trying two things".to_string(),
+        calls: vec![],
         results: vec![
             ToolResult {
                 tool: "shell".to_string(),
@@ -119,6 +162,7 @@ fn display_iteration_truncates_long_output() {
     let long_output = "x".repeat(500);
     let entry = MemoryEntry::Iteration {
         thought: "checking".to_string(),
+        calls: vec![],
         results: vec![ToolResult {
             tool: "shell".to_string(),
             outcome: Outcome::Success(long_output),
@@ -134,6 +178,42 @@ fn display_iteration_truncates_long_output() {
 
 // ── Session memory ────────────────────────────────────────────────
 
+#[test]
+fn iteration_without_calls_still_deserializes() {
+    // Entries written before tool calls were stored lack the field
+    let json =
+        r#"{"Iteration":{"thought":"t","results":[{"tool":"shell","outcome":{"Success":"ok"}}]}}"#;
+    let entry: MemoryEntry = serde_json::from_str(json).unwrap();
+    match entry {
+        MemoryEntry::Iteration { calls, results, .. } => {
+            assert!(calls.is_empty());
+            assert_eq!(results.len(), 1);
+        }
+        other => panic!("expected iteration, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn recall_matches_tool_arguments() {
+    let mem = SqliteMemory::in_memory().unwrap();
+    mem.store(MemoryEntry::Iteration {
+        thought: "look around".to_string(),
+        calls: vec![ToolCall {
+            tool: "shell".to_string(),
+            args: HashMap::from([("command".to_string(), "journalctl -u sshd".to_string())]),
+        }],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Success("no entries".to_string()),
+        }],
+    })
+    .await
+    .unwrap();
+
+    let hits = mem.recall("journalctl").await.unwrap();
+    assert_eq!(hits.len(), 1);
+}
+
 #[tokio::test]
 async fn session_store_and_retrieve() {
     let mem = SqliteMemory::in_memory().unwrap();
@@ -266,6 +346,45 @@ async fn task_clear_does_not_affect_session_memory() {
     assert_eq!(session[0].task, "prior task");
 }
 
+#[tokio::test]
+async fn session_compact_replaces_oldest_with_summary() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    for i in 1..=5 {
+        mem.store_session(SessionEntry {
+            task: format!("task {i}"),
+            answer: format!("answer {i}"),
+        })
+        .await
+        .unwrap();
+    }
+    assert_eq!(mem.session_len().await.unwrap(), 5);
+
+    mem.compact_session(
+        3,
+        SessionEntry {
+            task: "summary".to_string(),
+            answer: "tasks 1-3".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let history = mem.session_history(50).await.unwrap();
+    let tasks: Vec<_> = history.iter().map(|e| e.task.as_str()).collect();
+    assert_eq!(tasks, ["summary", "task 4", "task 5"]);
+
+    // New entries still go after the summary
+    mem.store_session(SessionEntry {
+        task: "task 6".to_string(),
+        answer: "answer 6".to_string(),
+    })
+    .await
+    .unwrap();
+    let history = mem.session_history(50).await.unwrap();
+    assert_eq!(history.last().unwrap().task, "task 6");
+}
+
 #[tokio::test]
 async fn session_persists_to_file() {
     let dir = tempfile::tempdir().unwrap();
@@ -291,3 +410,974 @@ async fn session_persists_to_file() {
         assert_eq!(history[0].task, "persisted task");
     }
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn file_memory_reads_alongside_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("golem.db");
+    let mem = Arc::new(SqliteMemory::new(path.to_str().unwrap()).unwrap());
+
+    let writer = {
+        let mem = Arc::clone(&mem);
+        tokio::spawn(async move {
+            for i in 0..20 {
+                mem.store_session(SessionEntry {
+                    task: format!("task {i}"),
+                    answer: "done".to_string(),
+                })
+                .await
+                .unwrap();
+            }
+        })
+    };
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let mem = Arc::clone(&mem);
+            tokio::spawn(async move {
+                let mut seen = 0;
+                for _ in 0..20 {
+                    let len = mem.session_len().await.unwrap();
+                    // Reads only ever see more as writes land
+                    assert!(len >= seen);
+                    seen = len;
+                    assert!(mem.session_history(100).await.unwrap().len() >= len);
+                }
+            })
+        })
+        .collect();
+    writer.await.unwrap();
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    // Reads see every write once it returns
+    assert_eq!(mem.session_len().await.unwrap(), 20);
+}
+
+// ── Project scoping ───────────────────────────────────────────────
+
+fn session(task: &str) -> SessionEntry {
+    SessionEntry {
+        task: task.to_string(),
+        answer: format!("answer to {task}"),
+    }
+}
+
+#[tokio::test]
+async fn project_history_is_isolated() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("projects.db");
+    let path = path.to_str().unwrap();
+    let repo_a = SqliteMemory::new(path).unwrap().with_project("/src/a");
+    let repo_b = SqliteMemory::new(path).unwrap().with_project("/src/b");
+
+    repo_a.store_session(session("build a")).await.unwrap();
+    repo_b.store_session(session("build b")).await.unwrap();
+
+    let a = repo_a.session_history(50).await.unwrap();
+    assert_eq!(a.len(), 1);
+    assert_eq!(a[0].task, "build a");
+    assert_eq!(repo_b.session_records().await.unwrap().len(), 1);
+    assert_eq!(repo_a.session_len().await.unwrap(), 1);
+
+    // Global memory sees every project
+    let global = SqliteMemory::new(path).unwrap();
+    assert_eq!(global.session_history(50).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn global_entries_are_visible_in_every_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("projects.db");
+    let path = path.to_str().unwrap();
+    SqliteMemory::new(path)
+        .unwrap()
+        .store_session(session("shared"))
+        .await
+        .unwrap();
+
+    let repo = SqliteMemory::new(path).unwrap().with_project("/src/a");
+    let history = repo.session_history(50).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].task, "shared");
+}
+
+#[tokio::test]
+async fn semantic_recall_is_project_scoped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("projects.db");
+    let path = path.to_str().unwrap();
+    let repo_a = SqliteMemory::new(path).unwrap().with_project("/src/a");
+    let repo_b = SqliteMemory::new(path).unwrap().with_project("/src/b");
+
+    repo_a
+        .store_session(SessionEntry {
+            task: "restart nginx".to_string(),
+            answer: "nginx restarted".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert!(!repo_a.recall_semantic("nginx", 5).await.unwrap().is_empty());
+    assert!(repo_b.recall_semantic("nginx", 5).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn compact_and_clear_leave_other_projects_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("projects.db");
+    let path = path.to_str().unwrap();
+    let repo_a = SqliteMemory::new(path).unwrap().with_project("/src/a");
+    let repo_b = SqliteMemory::new(path).unwrap().with_project("/src/b");
+
+    // Interleave so project b's row sits between project a's ids
+    repo_a.store_session(session("a1")).await.unwrap();
+    repo_b.store_session(session("b1")).await.unwrap();
+    repo_a.store_session(session("a2")).await.unwrap();
+
+    repo_a.compact_session(2, session("summary")).await.unwrap();
+    let a = repo_a.session_history(50).await.unwrap();
+    assert_eq!(a.len(), 1);
+    assert_eq!(a[0].task, "summary");
+    assert_eq!(repo_b.session_history(50).await.unwrap()[0].task, "b1");
+
+    repo_a.clear_session().await.unwrap();
+    assert!(repo_a.session_history(50).await.unwrap().is_empty());
+    assert_eq!(repo_b.session_history(50).await.unwrap().len(), 1);
+}
+
+// ── Full-text recall ──────────────────────────────────────────────
+
+#[tokio::test]
+async fn recall_ranks_and_stems() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Task {
+        content: "restart the web server".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store(MemoryEntry::Answer {
+        thought: "nginx restarted".to_string(),
+        content: "nginx config reloaded; nginx is serving again".to_string(),
+    })
+    .await
+    .unwrap();
+
+    // Porter stemming: "restarting" matches "restart" and "restarted"
+    let results = mem.recall("restarting").await.unwrap();
+    assert_eq!(results.len(), 2);
+
+    let results = mem.recall("nginx").await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(&results[0], MemoryEntry::Answer { .. }));
+}
+
+#[tokio::test]
+async fn recall_snippets_highlight_matches() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Iteration {
+        thought: "check the logs".to_string(),
+        calls: vec![],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Success("error: disk quota exceeded".to_string()),
+        }],
+    })
+    .await
+    .unwrap();
+
+    let hits = mem.recall_snippets("quota", 10).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    let snippet = hits[0].marked("[", "]");
+    assert!(snippet.contains("[quota]"), "{snippet}");
+}
+
+#[tokio::test]
+async fn recall_treats_operators_as_text() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Task {
+        content: "count lines in \"main.rs\" AND lib.rs".to_string(),
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(mem.recall("\"main.rs").await.unwrap().len(), 1);
+    assert_eq!(mem.recall("NOT AND").await.unwrap().len(), 0);
+    assert!(mem.recall("   ").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn recall_spans_cleared_tasks() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Task {
+        content: "earlier needle".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.clear().await.unwrap();
+
+    assert!(mem.history().await.unwrap().is_empty());
+    assert_eq!(mem.recall("needle").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn session_clear_forgets_only_that_sessions_recall() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Task {
+        content: "kept needle".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.create_session("work").await.unwrap();
+    mem.switch_session("work").await.unwrap();
+    mem.store(MemoryEntry::Task {
+        content: "cleared needle".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.clear_session().await.unwrap();
+
+    let hits = mem.recall("needle").await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(matches!(&hits[0], MemoryEntry::Task { content } if content == "kept needle"));
+}
+
+// ── Semantic recall ───────────────────────────────────────────────
+
+#[tokio::test]
+async fn recall_semantic_finds_related_sessions_and_observations() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Task {
+        content: "why is nginx failing".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store(MemoryEntry::Iteration {
+        thought: "check the service".to_string(),
+        calls: vec![],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Success("nginx: bind() to 0.0.0.0:80 failed".to_string()),
+        }],
+    })
+    .await
+    .unwrap();
+    mem.store_session(SessionEntry {
+        task: "why is nginx failing".to_string(),
+        answer: "port 80 is already in use by apache".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store_session(SessionEntry {
+        task: "count lines in main.rs".to_string(),
+        answer: "412".to_string(),
+    })
+    .await
+    .unwrap();
+
+    let hits = mem.recall_semantic("nginx keeps failing", 2).await.unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|h| h.task == "why is nginx failing"));
+    assert!(
+        hits.iter()
+            .any(|h| h.source == HitSource::Observation && h.text.starts_with("[shell]"))
+    );
+    assert!(hits[0].score >= hits[1].score);
+}
+
+#[tokio::test]
+async fn recall_semantic_skips_failed_observations() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store(MemoryEntry::Iteration {
+        thought: "try it".to_string(),
+        calls: vec![],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Error("command not found".to_string()),
+        }],
+    })
+    .await
+    .unwrap();
+
+    assert!(mem.recall_semantic("command", 5).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn session_clear_forgets_semantic_memory() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.store_session(SessionEntry {
+        task: "deploy".to_string(),
+        answer: "deployed".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.clear_session().await.unwrap();
+
+    assert!(mem.recall_semantic("deploy", 5).await.unwrap().is_empty());
+}
+
+struct ConstantEmbedder;
+
+#[async_trait::async_trait]
+impl Embedder for ConstantEmbedder {
+    fn id(&self) -> &str {
+        "constant"
+    }
+
+    async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+        Ok(vec![1.0, 0.0])
+    }
+}
+
+#[tokio::test]
+async fn recall_semantic_ignores_other_embedders() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embed.db");
+    let path_str = path.to_str().unwrap();
+
+    SqliteMemory::new(path_str)
+        .unwrap()
+        .store_session(SessionEntry {
+            task: "old".to_string(),
+            answer: "hashed".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let mem = SqliteMemory::new(path_str)
+        .unwrap()
+        .with_embedder(Arc::new(ConstantEmbedder));
+    assert!(mem.recall_semantic("old", 5).await.unwrap().is_empty());
+
+    mem.store_session(SessionEntry {
+        task: "new".to_string(),
+        answer: "constant".to_string(),
+    })
+    .await
+    .unwrap();
+    let hits = mem.recall_semantic("anything", 5).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!((hits[0].score - 1.0).abs() < 1e-6);
+}
+
+/// Stands in for an embedding model: texts about running out of disk
+/// space land on one axis, whatever their wording.
+struct TopicEmbedder;
+
+#[async_trait::async_trait]
+impl Embedder for TopicEmbedder {
+    fn id(&self) -> &str {
+        "topics"
+    }
+
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        let disk = ["disk full", "out of space", "no space left"]
+            .iter()
+            .any(|phrase| text.contains(phrase));
+        Ok(if disk { vec![1.0, 0.1] } else { vec![0.1, 1.0] })
+    }
+}
+
+#[tokio::test]
+async fn recall_semantic_finds_differently_worded_memories() {
+    let mem = SqliteMemory::in_memory()
+        .unwrap()
+        .with_embedder(Arc::new(TopicEmbedder));
+    for (task, answer) in [
+        ("why did the build fail", "no space left on /var"),
+        ("restart nginx", "restarted"),
+    ] {
+        mem.store_session(SessionEntry {
+            task: task.to_string(),
+            answer: answer.to_string(),
+        })
+        .await
+        .unwrap();
+    }
+
+    let hits = mem.recall_semantic("disk full again", 1).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].task, "why did the build fail");
+    assert!(hits[0].score > 0.9, "{}", hits[0].score);
+}
+
+struct FailingEmbedder;
+
+#[async_trait::async_trait]
+impl Embedder for FailingEmbedder {
+    fn id(&self) -> &str {
+        "down"
+    }
+
+    async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+        anyhow::bail!("connection refused")
+    }
+}
+
+#[tokio::test]
+async fn an_unreachable_embedder_still_stores_entries() {
+    let mem = SqliteMemory::in_memory()
+        .unwrap()
+        .with_embedder(Arc::new(FailingEmbedder));
+    mem.store_session(SessionEntry {
+        task: "deploy".to_string(),
+        answer: "done".to_string(),
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(mem.session_history(10).await.unwrap().len(), 1);
+    assert!(mem.recall_semantic("deploy", 5).await.is_err());
+}
+
+/// Embeds texts mentioning "wide" one dimension wider than the rest.
+struct MixedWidthEmbedder;
+
+#[async_trait::async_trait]
+impl Embedder for MixedWidthEmbedder {
+    fn id(&self) -> &str {
+        "mixed"
+    }
+
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        Ok(if text.contains("wide") {
+            vec![1.0, 0.0, 0.0]
+        } else {
+            vec![1.0, 0.0]
+        })
+    }
+}
+
+#[tokio::test]
+async fn a_vector_the_index_cannot_take_still_stores_the_entry() {
+    let mem = SqliteMemory::in_memory()
+        .unwrap()
+        .with_embedder(Arc::new(MixedWidthEmbedder));
+    for task in ["narrow", "wide"] {
+        mem.store_session(SessionEntry {
+            task: task.to_string(),
+            answer: "done".to_string(),
+        })
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(mem.session_history(10).await.unwrap().len(), 2);
+    let hits = mem.recall_semantic("narrow", 5).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].task, "narrow");
+}
+
+/// Embeds nothing until it is let through.
+struct GatedEmbedder(Arc<tokio::sync::Semaphore>);
+
+#[async_trait::async_trait]
+impl Embedder for GatedEmbedder {
+    fn id(&self) -> &str {
+        "gated"
+    }
+
+    async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+        let _permit = self.0.acquire().await?;
+        Ok(vec![1.0, 0.0])
+    }
+}
+
+#[tokio::test]
+async fn observations_are_embedded_without_holding_up_the_task() {
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let mem = SqliteMemory::in_memory()
+        .unwrap()
+        .with_embedder(Arc::new(GatedEmbedder(Arc::clone(&gate))));
+    let iteration = MemoryEntry::Iteration {
+        thought: "checking".to_string(),
+        calls: vec![],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Success("42G free".to_string()),
+        }],
+    };
+    let stored = tokio::time::timeout(std::time::Duration::from_secs(5), mem.store(iteration));
+    stored
+        .await
+        .expect("store waited for the embedder")
+        .unwrap();
+
+    gate.add_permits(1);
+    let hits = mem.recall_semantic("disk", 5).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].text, "[shell] 42G free");
+
+    mem.clear_session().await.unwrap();
+    gate.add_permits(1);
+    assert!(mem.recall_semantic("disk", 5).await.unwrap().is_empty());
+}
+
+// ── Named sessions ────────────────────────────────────────────────
+
+#[tokio::test]
+async fn sessions_scope_history() {
+    let mem = SqliteMemory::in_memory().unwrap();
+    assert_eq!(mem.active_session().await.unwrap(), "default");
+
+    mem.store_session(SessionEntry {
+        task: "default task".to_string(),
+        answer: "a".to_string(),
+    })
+    .await
+    .unwrap();
+
+    mem.create_session("work").await.unwrap();
+    mem.switch_session("work").await.unwrap();
+    assert_eq!(mem.active_session().await.unwrap(), "work");
+    assert!(mem.session_history(50).await.unwrap().is_empty());
+    assert!(
+        mem.recall_semantic("default task", 5)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    mem.store_session(SessionEntry {
+        task: "work task".to_string(),
+        answer: "b".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.clear_session().await.unwrap();
+
+    // Clearing "work" leaves "default" untouched
+    mem.switch_session("default").await.unwrap();
+    let history = mem.session_history(50).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].task, "default task");
+
+    let sessions = mem.list_sessions().await.unwrap();
+    let names: Vec<_> = sessions.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["default", "work"]);
+    assert_eq!(sessions[0].entries, 1);
+    assert_eq!(sessions[1].entries, 0);
+}
+
+#[tokio::test]
+async fn session_names_are_unique_and_must_exist() {
+    let mem = SqliteMemory::in_memory().unwrap();
+
+    mem.create_session("work").await.unwrap();
+    assert!(mem.create_session("work").await.is_err());
+    assert!(mem.switch_session("nope").await.is_err());
+    assert_eq!(mem.active_session().await.unwrap(), "default");
+}
+
+#[tokio::test]
+async fn pre_session_databases_migrate_to_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("old.db");
+
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE session_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+                task TEXT NOT NULL,
+                answer TEXT NOT NULL
+            );
+            INSERT INTO session_history (task, answer) VALUES ('old task', 'old answer');",
+        )
+        .unwrap();
+    }
+
+    let mem = SqliteMemory::new(path.to_str().unwrap()).unwrap();
+    let history = mem.session_history(50).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].task, "old task");
+}
+
+#[tokio::test]
+async fn exported_session_imports_as_new_session() {
+    use golem::memory::export::{ExportFormat, SessionExport};
+
+    let source = SqliteMemory::in_memory().unwrap();
+    source
+        .store(MemoryEntry::Task {
+            content: "list files".to_string(),
+        })
+        .await
+        .unwrap();
+    source
+        .store_session(SessionEntry {
+            task: "list files".to_string(),
+            answer: "a.txt".to_string(),
+        })
+        .await
+        .unwrap();
+    let export = SessionExport::new("laptop", source.session_records().await.unwrap());
+    let json = export.render(ExportFormat::Json).unwrap();
+
+    let target = SqliteMemory::in_memory().unwrap();
+    let parsed: SessionExport = serde_json::from_str(&json).unwrap();
+    target
+        .import_session(&parsed.session, &parsed.tasks)
+        .await
+        .unwrap();
+
+    // Import doesn't switch sessions
+    assert_eq!(target.active_session().await.unwrap(), "default");
+    assert!(target.session_history(50).await.unwrap().is_empty());
+
+    target.switch_session("laptop").await.unwrap();
+    let records = target.session_records().await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].answer, "a.txt");
+    assert_eq!(records[0].timestamp, export.tasks[0].timestamp);
+    assert_eq!(records[0].entries.len(), 1);
+    assert_eq!(
+        target.recall_semantic("list files", 1).await.unwrap()[0].text,
+        "a.txt"
+    );
+
+    // Importing the same name twice is refused
+    assert!(
+        target
+            .import_session("laptop", &parsed.tasks)
+            .await
+            .is_err()
+    );
+}
+
+// ── In-process memory ─────────────────────────────────────────────
+
+#[tokio::test]
+async fn in_memory_task_history_and_clear() {
+    let mem = InMemoryMemory::new();
+    mem.store(MemoryEntry::Task {
+        content: "count files".to_string(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(mem.history().await.unwrap().len(), 1);
+
+    mem.clear().await.unwrap();
+    assert!(mem.history().await.unwrap().is_empty());
+    // Recall still spans cleared tasks
+    let hits = mem.recall_snippets("count", 10).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].marked("[", "]"), "[count] files");
+}
+
+#[tokio::test]
+async fn in_memory_session_history_limit_and_compact() {
+    let mem = InMemoryMemory::new();
+    for i in 0..4 {
+        mem.store_session(session(&format!("t{i}"))).await.unwrap();
+    }
+    let last_two = mem.session_history(2).await.unwrap();
+    assert_eq!(last_two[0].task, "t2");
+    assert_eq!(last_two[1].task, "t3");
+
+    mem.compact_session(3, session("summary")).await.unwrap();
+    let history = mem.session_history(50).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].task, "summary");
+    assert_eq!(history[1].task, "t3");
+}
+
+#[tokio::test]
+async fn in_memory_named_sessions_and_context() {
+    let mem = InMemoryMemory::new();
+    mem.store_session(session("default task")).await.unwrap();
+    mem.record_task_usage(golem::thinker::TokenUsage {
+        input_tokens: 5,
+        output_tokens: 1,
+        cache_read_tokens: 0,
+    })
+    .await
+    .unwrap();
+
+    mem.create_session("work").await.unwrap();
+    assert!(mem.create_session("work").await.is_err());
+    mem.switch_session("work").await.unwrap();
+    assert_eq!(mem.active_session().await.unwrap(), "work");
+    assert!(mem.session_history(50).await.unwrap().is_empty());
+    assert!(
+        mem.recall_semantic("default task", 5)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    mem.switch_session("default").await.unwrap();
+    let records = mem.session_records().await.unwrap();
+    assert_eq!(records[0].usage.input_tokens, 5);
+
+    let sessions = mem.list_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].entries, 1);
+}
+
+#[tokio::test]
+async fn in_memory_semantic_recall_and_clear_session() {
+    let mem = InMemoryMemory::new();
+    mem.store_session(SessionEntry {
+        task: "restart nginx".to_string(),
+        answer: "nginx restarted".to_string(),
+    })
+    .await
+    .unwrap();
+    let hits = mem.recall_semantic("nginx", 5).await.unwrap();
+    assert_eq!(hits[0].source, HitSource::Session);
+
+    mem.clear_session().await.unwrap();
+    assert!(mem.session_history(50).await.unwrap().is_empty());
+    assert!(mem.recall_semantic("nginx", 5).await.unwrap().is_empty());
+}
+
+// ── Stats ─────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn stats_report_tables_and_index_sizes() {
+    let mem = SqliteMemory::in_memory().unwrap();
+    mem.store(MemoryEntry::Task {
+        content: "restart nginx".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store_session(session("restart nginx")).await.unwrap();
+
+    let stats = mem.stats().await.unwrap();
+    let table = |name: &str| stats.tables.iter().find(|t| t.name == name).unwrap();
+    assert_eq!(table("memory").rows, 1);
+    assert_eq!(table("session_history").rows, 1);
+    assert!(table("session_history").oldest.is_some());
+    assert_eq!(table("sessions").rows, 1);
+    assert!(table("embeddings").oldest.is_none());
+    assert!(
+        stats
+            .tables
+            .iter()
+            .all(|t| !t.name.starts_with("memory_fts_"))
+    );
+    assert!(stats.database_bytes.unwrap() > 0);
+    assert!(stats.fts_bytes.unwrap() > 0);
+    assert_eq!(stats.vectors, 1);
+    assert_eq!(stats.vector_bytes, 384 * 4);
+}
+
+#[tokio::test]
+async fn in_memory_stats_have_no_database_size() {
+    let mem = InMemoryMemory::new();
+    mem.store_session(session("t")).await.unwrap();
+    let stats = mem.stats().await.unwrap();
+    assert!(stats.database_bytes.is_none());
+    assert_eq!(stats.vectors, 1);
+    assert_eq!(
+        stats
+            .tables
+            .iter()
+            .find(|t| t.name == "session_history")
+            .unwrap()
+            .rows,
+        1
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn connections_to_one_file_write_concurrently() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("golem.db");
+    let path = path.to_str().unwrap();
+    let a = Arc::new(SqliteMemory::new(path).unwrap());
+    let b = Arc::new(SqliteMemory::new(path).unwrap());
+
+    let writes = (0..20).map(|i| {
+        let memory = if i % 2 == 0 { &a } else { &b };
+        let memory = Arc::clone(memory);
+        tokio::spawn(async move {
+            memory
+                .store_session(SessionEntry {
+                    task: format!("task {i}"),
+                    answer: "ok".to_string(),
+                })
+                .await
+        })
+    });
+    for result in futures::future::join_all(writes).await {
+        result.unwrap().unwrap();
+    }
+    assert_eq!(a.session_len().await.unwrap(), 20);
+}
+
+#[tokio::test]
+async fn large_observations_are_compressed_transparently() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("golem.db");
+    let mem = SqliteMemory::new(path.to_str().unwrap()).unwrap();
+    let log = "warning: unused variable `x`\n".repeat(2000);
+
+    mem.store(MemoryEntry::Task {
+        content: "build it".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store(MemoryEntry::Iteration {
+        thought: "run the build".to_string(),
+        calls: vec![],
+        results: vec![ToolResult {
+            tool: "shell".to_string(),
+            outcome: Outcome::Success(log.clone()),
+        }],
+    })
+    .await
+    .unwrap();
+    mem.store_session(session("build it")).await.unwrap();
+
+    let history = mem.history().await.unwrap();
+    assert!(matches!(
+        &history[1],
+        MemoryEntry::Iteration { results, .. }
+            if matches!(&results[0].outcome, Outcome::Success(out) if *out == log)
+    ));
+    let records = mem.session_records().await.unwrap();
+    assert_eq!(records[0].entries.len(), 2);
+    let hits = mem.recall_snippets("unused variable", 5).await.unwrap();
+    assert!(matches!(&hits[0].entry, MemoryEntry::Iteration { .. }));
+
+    // The task stays plain text; the big observation is a compressed blob
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let types: Vec<String> = conn
+        .prepare("SELECT typeof(entry) FROM memory ORDER BY id")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(types, ["text", "blob"]);
+    let stored: i64 = conn
+        .query_row("SELECT length(entry) FROM memory WHERE id = 2", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert!((stored as usize) < log.len() / 10);
+}
+
+fn dated(timestamp: &str, task: &str, answer: &str) -> golem::memory::SessionRecord {
+    golem::memory::SessionRecord {
+        timestamp: timestamp.to_string(),
+        task: task.to_string(),
+        answer: answer.to_string(),
+        entries: vec![],
+        usage: Default::default(),
+    }
+}
+
+async fn check_search_session(mem: &dyn Memory) {
+    mem.import_session(
+        "ops",
+        &[
+            dated("2026-10-12 09:00:00", "restart nginx", "done"),
+            dated(
+                "2026-10-13 10:00:00",
+                "why is NGINX returning 502",
+                "upstream was down",
+            ),
+            dated("2026-10-13 11:00:00", "list files", "a.txt"),
+            dated(
+                "2026-10-14 08:00:00",
+                "check nginx config",
+                "50% of vhosts lack TLS",
+            ),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let tasks = |matches: Vec<golem::memory::SessionMatch>| -> Vec<String> {
+        matches.into_iter().map(|m| m.task).collect()
+    };
+
+    // Newest first, case-insensitive, across sessions
+    let all = mem.search_session("nginx", None, None).await.unwrap();
+    assert_eq!(all[0].session, "ops");
+    assert_eq!(
+        tasks(all),
+        [
+            "check nginx config",
+            "why is NGINX returning 502",
+            "restart nginx"
+        ]
+    );
+
+    // Day range: since inclusive, until exclusive
+    let tuesday = mem
+        .search_session("nginx", Some("2026-10-13"), Some("2026-10-14"))
+        .await
+        .unwrap();
+    assert_eq!(tasks(tuesday), ["why is NGINX returning 502"]);
+
+    // Every word must match, in the task or the answer
+    let both = mem
+        .search_session("nginx upstream", None, None)
+        .await
+        .unwrap();
+    assert_eq!(tasks(both), ["why is NGINX returning 502"]);
+
+    // LIKE wildcards in the query are literal
+    let percent = mem.search_session("50%", None, None).await.unwrap();
+    assert_eq!(tasks(percent), ["check nginx config"]);
+
+    // No text: everything in range
+    let since = mem
+        .search_session("", Some("2026-10-13 10:30:00"), None)
+        .await
+        .unwrap();
+    assert_eq!(tasks(since), ["check nginx config", "list files"]);
+}
+
+#[tokio::test]
+async fn search_session_by_text_and_time() {
+    check_search_session(&SqliteMemory::in_memory().unwrap()).await;
+    check_search_session(&InMemoryMemory::new()).await;
+}
+
+async fn check_unfinished_task(mem: &dyn Memory) {
+    assert_eq!(mem.unfinished_task().await.unwrap(), None);
+
+    mem.store(MemoryEntry::Task {
+        content: "build the project".to_string(),
+    })
+    .await
+    .unwrap();
+    mem.store(MemoryEntry::Iteration {
+        thought: "compile".to_string(),
+        calls: vec![],
+        results: vec![],
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        mem.unfinished_task().await.unwrap().as_deref(),
+        Some("build the project")
+    );
+
+    mem.store(MemoryEntry::Answer {
+        thought: "done".to_string(),
+        content: "built".to_string(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(mem.unfinished_task().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn unfinished_task_is_one_without_an_answer() {
+    check_unfinished_task(&SqliteMemory::in_memory().unwrap()).await;
+    check_unfinished_task(&InMemoryMemory::new()).await;
+}
@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use golem::engine::Engine;
 use golem::engine::react::{ReactConfig, ReactEngine};
+use golem::engine::{Engine, TaskStatus};
+use golem::events::{Event, EventBus};
+use golem::memory::in_memory::InMemoryMemory;
 use golem::memory::sqlite::SqliteMemory;
+use golem::memory::{Memory, MemoryEntry, SessionEntry};
 use golem::thinker::mock::MockThinker;
 use golem::thinker::{Step, StepResult, Thinker, ToolCall};
-use golem::tools::ToolRegistry;
 use golem::tools::shell::{ShellConfig, ShellMode, ShellTool};
+use golem::tools::{Outcome, ToolRegistry};
+use golem::transcript::{Transcript, TranscriptEvent, read_transcript};
 
 /// Wrap steps into StepResults with no token usage (convenience for tests).
 fn wrap(steps: Vec<Step>) -> Vec<StepResult> {
@@ -41,7 +45,7 @@ async fn finish_immediately() {
     .await;
 
     let result = engine.run("do nothing").await.unwrap();
-    assert_eq!(result, "done");
+    assert_eq!(result.answer, "done");
 }
 
 #[tokio::test]
@@ -62,7 +66,10 @@ async fn single_tool_call_then_finish() {
     .await;
 
     let result = engine.run("say hello").await.unwrap();
-    assert_eq!(result, "hello");
+    assert_eq!(result.answer, "hello");
+    assert_eq!(result.iterations, 2);
+    assert_eq!(result.tool_calls, 1);
+    assert_eq!(result.status, TaskStatus::Completed);
 }
 
 #[tokio::test]
@@ -89,7 +96,7 @@ async fn parallel_tool_calls() {
     .await;
 
     let result = engine.run("parallel test").await.unwrap();
-    assert_eq!(result, "parallel works");
+    assert_eq!(result.answer, "parallel works");
 }
 
 #[tokio::test]
@@ -110,7 +117,7 @@ async fn unknown_tool_produces_error_observation() {
     .await;
 
     let result = engine.run("bad tool test").await.unwrap();
-    assert_eq!(result, "handled");
+    assert_eq!(result.answer, "handled");
 }
 
 #[tokio::test]
@@ -132,6 +139,31 @@ async fn max_iterations_enforced() {
     assert!(result.unwrap_err().to_string().contains("max iterations"));
 }
 
+#[tokio::test]
+async fn best_effort_answer_after_max_iterations() {
+    let mut steps: Vec<Step> = (0..20)
+        .map(|i| Step::Act {
+            thought: format!("iteration {}", i),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "echo loop".to_string())]),
+            }],
+        })
+        .collect();
+    steps.push(Step::Finish {
+        thought: "out of steps".to_string(),
+        answer: "probably loop".to_string(),
+    });
+
+    let mut engine = build_engine(steps).await;
+
+    let result = engine.run("infinite loop").await.unwrap();
+    assert_eq!(result.answer, "probably loop");
+    assert_eq!(result.status, TaskStatus::Partial);
+    assert_eq!(result.iterations, 20);
+    assert_eq!(result.tool_calls, 20);
+}
+
 #[tokio::test]
 async fn swap_thinker_at_runtime() {
     // Start with a thinker that does one action
@@ -142,7 +174,7 @@ async fn swap_thinker_at_runtime() {
     .await;
 
     let result = engine.run("task 1").await.unwrap();
-    assert_eq!(result, "answer from brain 1");
+    assert_eq!(result.answer, "answer from brain 1");
 
     // Swap to a different thinker
     let new_thinker: Box<dyn Thinker> = Box::new(MockThinker::new(wrap(vec![Step::Finish {
@@ -152,7 +184,7 @@ async fn swap_thinker_at_runtime() {
     engine.set_thinker(new_thinker).await;
 
     let result = engine.run("task 2").await.unwrap();
-    assert_eq!(result, "answer from brain 2");
+    assert_eq!(result.answer, "answer from brain 2");
 }
 
 #[tokio::test]
@@ -168,6 +200,7 @@ async fn session_usage_accumulates_across_runs() {
             usage: Some(TokenUsage {
                 input_tokens: 100,
                 output_tokens: 50,
+                cache_read_tokens: 0,
             }),
         },
         StepResult {
@@ -178,6 +211,7 @@ async fn session_usage_accumulates_across_runs() {
             usage: Some(TokenUsage {
                 input_tokens: 200,
                 output_tokens: 75,
+                cache_read_tokens: 0,
             }),
         },
     ];
@@ -204,6 +238,59 @@ async fn session_usage_accumulates_across_runs() {
     assert_eq!(usage.total(), 425);
 }
 
+#[tokio::test]
+async fn session_records_keep_transcript_and_usage() {
+    use golem::memory::MemoryEntry;
+    use golem::thinker::TokenUsage;
+
+    let steps = vec![
+        StepResult {
+            step: Step::Act {
+                thought: "look".to_string(),
+                calls: vec![ToolCall {
+                    tool: "shell".to_string(),
+                    args: HashMap::from([("command".to_string(), "echo hi".to_string())]),
+                }],
+            },
+            usage: Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cache_read_tokens: 0,
+            }),
+        },
+        StepResult {
+            step: Step::Finish {
+                thought: "done".to_string(),
+                answer: "hi".to_string(),
+            },
+            usage: Some(TokenUsage {
+                input_tokens: 20,
+                output_tokens: 7,
+                cache_read_tokens: 0,
+            }),
+        },
+    ];
+    let mut engine = build_engine(vec![]).await;
+    engine.set_thinker(Box::new(MockThinker::new(steps))).await;
+
+    engine.run("greet").await.unwrap();
+
+    let records = engine.session_records().await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].task, "greet");
+    assert_eq!(records[0].usage.input_tokens, 30);
+    assert_eq!(records[0].usage.output_tokens, 12);
+    assert!(matches!(records[0].entries[0], MemoryEntry::Task { .. }));
+    match &records[0].entries[1] {
+        MemoryEntry::Iteration { calls, results, .. } => {
+            assert_eq!(calls.len(), results.len());
+            assert_eq!(calls[0].args["command"], "echo hi");
+        }
+        other => panic!("expected iteration, got {other:?}"),
+    }
+    assert!(matches!(records[0].entries[2], MemoryEntry::Answer { .. }));
+}
+
 #[tokio::test]
 async fn session_usage_zero_when_no_tokens() {
     let mut engine = build_engine(vec![Step::Finish {
@@ -332,6 +419,42 @@ async fn clear_session_resets_history() {
 
 // ── Model management ──────────────────────────────────────────────
 
+#[tokio::test]
+async fn overflowing_session_history_is_summarized() {
+    let memory = SqliteMemory::in_memory().unwrap();
+    for i in 1..=55 {
+        memory
+            .store_session(SessionEntry {
+                task: format!("task {i}"),
+                answer: format!("answer {i}"),
+            })
+            .await
+            .unwrap();
+    }
+
+    let thinker = Box::new(MockThinker::new(wrap(vec![Step::Finish {
+        thought: "done".to_string(),
+        answer: "fresh".to_string(),
+    }])));
+    let mut engine = ReactEngine::new(
+        thinker,
+        Arc::new(ToolRegistry::new()),
+        Box::new(memory),
+        ReactConfig::default(),
+    );
+    engine.run("one more").await.unwrap();
+
+    let history = engine.session_history().await.unwrap();
+    // 55 - 15 folded + 1 summary + 1 new task
+    assert_eq!(history.len(), 42);
+    assert!(history[0].task.contains("summary of 15"));
+    assert!(history[0].answer.contains("- task 1 → answer 1"));
+    assert!(history[0].answer.contains("- task 15 → answer 15"));
+    assert!(!history[0].answer.contains("task 16"));
+    assert_eq!(history[1].task, "task 16");
+    assert_eq!(history.last().unwrap().task, "one more");
+}
+
 #[tokio::test]
 async fn engine_model_returns_mock_default() {
     let engine = build_engine(vec![Step::Finish {
@@ -413,3 +536,1128 @@ fn config_model_persists_to_file() {
         assert_eq!(model.unwrap(), "claude-opus-4-20250514");
     }
 }
+
+#[tokio::test]
+async fn transcript_records_task_tools_and_answer() {
+    let dir = tempfile::tempdir().unwrap();
+    let transcript = Arc::new(Transcript::open(dir.path(), "default").unwrap());
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "look".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "echo hi".to_string())]),
+            }],
+        },
+        Step::Finish {
+            thought: "done".to_string(),
+            answer: "hi".to_string(),
+        },
+    ])
+    .await;
+    engine.set_transcript(Arc::clone(&transcript)).await;
+
+    engine.run("say hi").await.unwrap();
+
+    let lines = read_transcript(&transcript.path()).unwrap();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0].event,
+        TranscriptEvent::Task {
+            task: "say hi".to_string()
+        }
+    );
+    match &lines[1].event {
+        TranscriptEvent::Tool {
+            tool,
+            args,
+            success,
+            output,
+            ..
+        } => {
+            assert_eq!(tool, "shell");
+            assert_eq!(args["command"], "echo hi");
+            assert!(success);
+            assert!(output.contains("hi"));
+        }
+        other => panic!("expected tool event, got {other:?}"),
+    }
+    assert_eq!(
+        lines[2].event,
+        TranscriptEvent::Answer {
+            answer: "hi".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn runs_with_in_process_memory() {
+    let thinker = Box::new(MockThinker::new(wrap(vec![Step::Finish {
+        thought: "easy".to_string(),
+        answer: "42".to_string(),
+    }])));
+    let memory = Box::new(InMemoryMemory::new());
+    let mut engine = ReactEngine::new(
+        thinker,
+        Arc::new(ToolRegistry::new()),
+        memory,
+        ReactConfig::default(),
+    );
+
+    assert_eq!(engine.run("answer").await.unwrap().answer, "42");
+    let history = engine.session_history().await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].answer, "42");
+}
+
+#[tokio::test]
+async fn metrics_recorded_for_success_and_failure() {
+    use golem::metrics::MetricsStore;
+
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "check twice".to_string(),
+            calls: vec![
+                ToolCall {
+                    tool: "shell".to_string(),
+                    args: HashMap::from([("command".to_string(), "echo a".to_string())]),
+                },
+                ToolCall {
+                    tool: "shell".to_string(),
+                    args: HashMap::from([("command".to_string(), "echo b".to_string())]),
+                },
+            ],
+        },
+        Step::Finish {
+            thought: "done".to_string(),
+            answer: "ab".to_string(),
+        },
+    ])
+    .await;
+    let metrics = Arc::new(MetricsStore::open(":memory:").unwrap());
+    engine.set_metrics(Arc::clone(&metrics));
+
+    engine.run("echo twice").await.unwrap();
+    // The mock has run out of steps, so this task fails
+    assert!(engine.run("one more").await.is_err());
+
+    let summary = metrics.summary().unwrap();
+    assert_eq!(summary.overall.tasks, 2);
+    assert_eq!(summary.overall.succeeded, 1);
+    assert_eq!(summary.overall.iterations, 3);
+    assert_eq!(summary.overall.tool_calls, 2);
+    assert_eq!(summary.by_model[0].0, "mock");
+}
+
+/// Delegates to a `MockThinker` and records the note it was shown each step.
+struct NoteRecorder {
+    inner: MockThinker,
+    seen: Arc<std::sync::Mutex<Vec<Option<String>>>>,
+}
+
+#[async_trait::async_trait]
+impl Thinker for NoteRecorder {
+    async fn next_step(&self, context: &golem::thinker::Context) -> anyhow::Result<StepResult> {
+        self.seen.lock().unwrap().push(context.note.clone());
+        self.inner.next_step(context).await
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<golem::thinker::ModelInfo>> {
+        Ok(vec![])
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn set_model(&mut self, _model: String) {}
+}
+
+#[tokio::test]
+async fn repeated_failures_trigger_reflection() {
+    let failing = |i: usize| Step::Act {
+        thought: format!("attempt {i}"),
+        calls: vec![ToolCall {
+            tool: "no_such_tool".to_string(),
+            args: HashMap::new(),
+        }],
+    };
+    let mut steps: Vec<Step> = (1..=4).map(failing).collect();
+    steps.push(Step::Finish {
+        thought: "giving up".to_string(),
+        answer: "can't".to_string(),
+    });
+
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let thinker = Box::new(NoteRecorder {
+        inner: MockThinker::new(wrap(steps)),
+        seen: Arc::clone(&seen),
+    });
+    let memory = Box::new(SqliteMemory::in_memory().unwrap());
+    let config = ReactConfig {
+        reflect_after: 3,
+        ..ReactConfig::default()
+    };
+    let mut engine = ReactEngine::new(thinker, Arc::new(ToolRegistry::new()), memory, config);
+
+    assert_eq!(
+        engine.run("do the impossible").await.unwrap().answer,
+        "can't"
+    );
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 5);
+    // Only the step right after the third failure gets the note
+    assert!(seen[..3].iter().all(Option::is_none));
+    assert!(
+        seen[3]
+            .as_ref()
+            .unwrap()
+            .contains("last 3 attempts all failed")
+    );
+    assert!(seen[4].is_none());
+}
+
+/// Fails its first `failures` calls, then delegates to a `MockThinker`,
+/// recording the history it was shown each call.
+struct FlakyThinker {
+    inner: MockThinker,
+    failures: std::sync::atomic::AtomicUsize,
+    histories: Arc<std::sync::Mutex<Vec<Vec<MemoryEntry>>>>,
+}
+
+#[async_trait::async_trait]
+impl Thinker for FlakyThinker {
+    async fn next_step(&self, context: &golem::thinker::Context) -> anyhow::Result<StepResult> {
+        self.histories.lock().unwrap().push(context.history.clone());
+        let left = self.failures.load(std::sync::atomic::Ordering::SeqCst);
+        if left > 0 {
+            self.failures
+                .store(left - 1, std::sync::atomic::Ordering::SeqCst);
+            anyhow::bail!("connection reset");
+        }
+        self.inner.next_step(context).await
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<golem::thinker::ModelInfo>> {
+        Ok(vec![])
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn set_model(&mut self, _model: String) {}
+}
+
+fn flaky_engine(
+    failures: usize,
+    thinker_retries: u32,
+) -> (ReactEngine, Arc<std::sync::Mutex<Vec<Vec<MemoryEntry>>>>) {
+    let histories = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let thinker = Box::new(FlakyThinker {
+        inner: MockThinker::new(wrap(vec![Step::Finish {
+            thought: "fine now".to_string(),
+            answer: "done".to_string(),
+        }])),
+        failures: std::sync::atomic::AtomicUsize::new(failures),
+        histories: Arc::clone(&histories),
+    });
+    let config = ReactConfig {
+        thinker_retries,
+        retry_delay: std::time::Duration::ZERO,
+        ..ReactConfig::default()
+    };
+    let engine = ReactEngine::new(
+        thinker,
+        Arc::new(ToolRegistry::new()),
+        Box::new(SqliteMemory::in_memory().unwrap()),
+        config,
+    );
+    (engine, histories)
+}
+
+#[tokio::test]
+async fn thinker_errors_are_retried_and_remembered() {
+    let (mut engine, histories) = flaky_engine(2, 2);
+
+    let result = engine.run("flaky").await.unwrap();
+    assert_eq!(result.answer, "done");
+    assert_eq!(result.iterations, 1);
+    let histories = histories.lock().unwrap();
+    assert_eq!(histories.len(), 3);
+    // Each retry sees the failures before it
+    let retries = |history: &[MemoryEntry]| {
+        history
+            .iter()
+            .filter(|e| matches!(e, MemoryEntry::Retry { error } if error == "connection reset"))
+            .count()
+    };
+    assert_eq!(retries(&histories[0]), 0);
+    assert_eq!(retries(&histories[2]), 2);
+}
+
+#[tokio::test]
+async fn many_retries_do_not_overflow_the_delay() {
+    // 2^32 would overflow the doubling
+    let (mut engine, histories) = flaky_engine(34, 40);
+
+    assert_eq!(engine.run("flaky").await.unwrap().answer, "done");
+    assert_eq!(histories.lock().unwrap().len(), 35);
+}
+
+#[tokio::test]
+async fn thinker_error_fails_task_once_retries_run_out() {
+    let (mut engine, histories) = flaky_engine(2, 1);
+
+    let Err(err) = engine.run("flaky").await else {
+        panic!("expected the task to fail");
+    };
+    assert_eq!(err.to_string(), "connection reset");
+    assert_eq!(histories.lock().unwrap().len(), 2);
+}
+
+/// Answers every budget question the same way and counts the questions.
+struct FixedBudgetApprover {
+    extend: bool,
+    asked: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl golem::engine::react::BudgetApprover for FixedBudgetApprover {
+    async fn extend(&self, reason: &str) -> anyhow::Result<bool> {
+        self.asked.lock().unwrap().push(reason.to_string());
+        Ok(self.extend)
+    }
+}
+
+/// Three `echo` iterations of 600 tokens each, then an answer.
+fn budget_engine(max_tokens: u64) -> ReactEngine {
+    use golem::thinker::TokenUsage;
+
+    let echo = |i: usize| StepResult {
+        step: Step::Act {
+            thought: format!("step {i}"),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), format!("echo {i}"))]),
+            }],
+        },
+        usage: Some(TokenUsage {
+            input_tokens: 500,
+            output_tokens: 100,
+            cache_read_tokens: 0,
+        }),
+    };
+    let mut steps: Vec<StepResult> = (1..=3).map(echo).collect();
+    steps.push(StepResult {
+        step: Step::Finish {
+            thought: "done".to_string(),
+            answer: "echoed".to_string(),
+        },
+        usage: None,
+    });
+
+    let memory = Box::new(SqliteMemory::in_memory().unwrap());
+    let config = ReactConfig {
+        max_tokens_per_task: Some(max_tokens),
+        ..ReactConfig::default()
+    };
+    ReactEngine::new(
+        Box::new(MockThinker::new(steps)),
+        Arc::new(ToolRegistry::new()),
+        memory,
+        config,
+    )
+}
+
+#[tokio::test]
+async fn token_budget_stops_task() {
+    let mut engine = budget_engine(1000);
+    let asked = Arc::new(std::sync::Mutex::new(Vec::new()));
+    engine.set_budget_approver(Box::new(FixedBudgetApprover {
+        extend: false,
+        asked: Arc::clone(&asked),
+    }));
+
+    let err = engine.run("echo a lot").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "token budget exceeded: used 1,200 of 1,000 tokens"
+    );
+    assert_eq!(asked.lock().unwrap().len(), 1);
+    assert_eq!(engine.session_usage().total(), 1200);
+}
+
+#[tokio::test]
+async fn token_budget_stops_without_approver() {
+    let mut engine = budget_engine(1000);
+    assert!(engine.run("echo a lot").await.is_err());
+}
+
+#[tokio::test]
+async fn extended_token_budget_lets_task_finish() {
+    let mut engine = budget_engine(1000);
+    let asked = Arc::new(std::sync::Mutex::new(Vec::new()));
+    engine.set_budget_approver(Box::new(FixedBudgetApprover {
+        extend: true,
+        asked: Arc::clone(&asked),
+    }));
+
+    assert_eq!(engine.run("echo a lot").await.unwrap().answer, "echoed");
+    // 1,800 tokens fit in the doubled budget, so the user is asked once
+    assert_eq!(
+        *asked.lock().unwrap(),
+        vec!["token budget exceeded: used 1,200 of 1,000 tokens"]
+    );
+}
+
+/// A `MockThinker` whose model can be switched like a real provider's.
+struct SwitchableModel {
+    inner: MockThinker,
+    model: String,
+}
+
+#[async_trait::async_trait]
+impl Thinker for SwitchableModel {
+    async fn next_step(&self, context: &golem::thinker::Context) -> anyhow::Result<StepResult> {
+        self.inner.next_step(context).await
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<golem::thinker::ModelInfo>> {
+        Ok(vec![])
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+}
+
+#[tokio::test]
+async fn session_usage_is_split_by_model() {
+    use golem::thinker::TokenUsage;
+
+    let answer = |input_tokens| StepResult {
+        step: Step::Finish {
+            thought: "done".to_string(),
+            answer: "ok".to_string(),
+        },
+        usage: Some(TokenUsage {
+            input_tokens,
+            output_tokens: 10,
+            cache_read_tokens: 0,
+        }),
+    };
+    let mut engine = ReactEngine::new(
+        Box::new(SwitchableModel {
+            inner: MockThinker::new(vec![answer(100), answer(200), answer(300)]),
+            model: "model-a".to_string(),
+        }),
+        Arc::new(ToolRegistry::new()),
+        Box::new(InMemoryMemory::new()),
+        ReactConfig::default(),
+    );
+
+    engine.run("first").await.unwrap();
+    engine.set_model("model-b".to_string()).await;
+    engine.run("second").await.unwrap();
+    engine.set_model("model-a".to_string()).await;
+    engine.run("third").await.unwrap();
+
+    let by_model: Vec<(&str, u64)> = engine
+        .session_usage_by_model()
+        .iter()
+        .map(|(model, usage)| (model.as_str(), usage.input_tokens))
+        .collect();
+    assert_eq!(by_model, [("model-a", 400), ("model-b", 200)]);
+    assert_eq!(engine.session_usage().input_tokens, 600);
+}
+
+/// Delegates to a `MockThinker` and records the history it was shown each step.
+struct HistoryRecorder {
+    inner: MockThinker,
+    seen: Arc<std::sync::Mutex<Vec<Vec<MemoryEntry>>>>,
+}
+
+#[async_trait::async_trait]
+impl Thinker for HistoryRecorder {
+    async fn next_step(&self, context: &golem::thinker::Context) -> anyhow::Result<StepResult> {
+        self.seen.lock().unwrap().push(context.history.clone());
+        self.inner.next_step(context).await
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<golem::thinker::ModelInfo>> {
+        Ok(vec![])
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn set_model(&mut self, _model: String) {}
+}
+
+#[tokio::test]
+async fn context_history_grows_with_each_iteration() {
+    let echo = |text: &str| Step::Act {
+        thought: format!("say {text}"),
+        calls: vec![ToolCall {
+            tool: "shell".to_string(),
+            args: HashMap::from([("command".to_string(), format!("echo {text}"))]),
+        }],
+    };
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let steps = |last: &str| {
+        wrap(vec![
+            echo("one"),
+            echo("two"),
+            Step::Finish {
+                thought: "done".to_string(),
+                answer: last.to_string(),
+            },
+        ])
+    };
+    let mut engine = ReactEngine::new(
+        Box::new(HistoryRecorder {
+            inner: MockThinker::new(steps("first").into_iter().chain(steps("second")).collect()),
+            seen: Arc::clone(&seen),
+        }),
+        Arc::new(ToolRegistry::new()),
+        Box::new(SqliteMemory::in_memory().unwrap()),
+        ReactConfig::default(),
+    );
+    engine.run("count").await.unwrap();
+
+    let history = engine.history().await.unwrap();
+    {
+        let seen = seen.lock().unwrap();
+        let lengths: Vec<_> = seen.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [1, 2, 3]);
+        // What the thinker saw is what memory holds, minus the answer
+        assert_eq!(format!("{:?}", seen[2]), format!("{:?}", &history[..3]));
+    }
+
+    // The next task starts from its own entry alone
+    seen.lock().unwrap().clear();
+    engine.run("again").await.unwrap();
+    let seen = seen.lock().unwrap();
+    assert!(matches!(&seen[0][..], [MemoryEntry::Task { content }] if content == "again"));
+    assert_eq!(seen[2].len(), 3);
+}
+
+#[tokio::test]
+async fn resume_continues_interrupted_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("golem.db");
+    let db = db.to_str().unwrap();
+
+    // The first run does one step of tool work, then fails
+    let config = ReactConfig {
+        max_iterations: 1,
+        ..ReactConfig::default()
+    };
+    let mut engine = ReactEngine::new(
+        Box::new(MockThinker::new(wrap(vec![Step::Act {
+            thought: "look first".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "echo found".to_string())]),
+            }],
+        }]))),
+        Arc::new(ToolRegistry::new()),
+        Box::new(SqliteMemory::new(db).unwrap()),
+        config,
+    );
+    assert!(engine.run("find it").await.is_err());
+
+    // A new process picks the task up with the step already in context
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut engine = ReactEngine::new(
+        Box::new(HistoryRecorder {
+            inner: MockThinker::new(wrap(vec![Step::Finish {
+                thought: "already looked".to_string(),
+                answer: "found".to_string(),
+            }])),
+            seen: Arc::clone(&seen),
+        }),
+        Arc::new(ToolRegistry::new()),
+        Box::new(SqliteMemory::new(db).unwrap()),
+        ReactConfig::default(),
+    );
+    assert_eq!(engine.resume().await.unwrap().answer, "found");
+    assert_eq!(seen.lock().unwrap()[0].len(), 2);
+
+    let history = engine.history().await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(
+        matches!(&history[1], MemoryEntry::Iteration { thought, .. } if thought == "look first")
+    );
+    let session = engine.session_history().await.unwrap();
+    assert_eq!(session.len(), 1);
+    assert_eq!(session[0].task, "find it");
+
+    // Nothing is left to resume once the task is answered
+    let err = engine.resume().await.unwrap_err();
+    assert_eq!(err.to_string(), "no unfinished task to resume");
+}
+
+#[tokio::test]
+async fn cancellation_kills_tools_and_records_interruption() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("finished");
+    let command = format!("sleep 1 && touch {}", marker.display());
+
+    let mut engine = build_engine(vec![Step::Act {
+        thought: "wait a bit".to_string(),
+        calls: vec![ToolCall {
+            tool: "shell".to_string(),
+            args: HashMap::from([("command".to_string(), command)]),
+        }],
+    }])
+    .await;
+    let cancel = tokio_util::sync::CancellationToken::new();
+    engine.set_cancellation(cancel.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        cancel.cancel();
+    });
+
+    let err = engine.run("slow task").await.unwrap_err();
+    assert!(err.is::<golem::engine::Interrupted>());
+
+    // The cut-off step is kept, so the task can be resumed
+    let history = engine.history().await.unwrap();
+    assert_eq!(history.len(), 2);
+    let MemoryEntry::Iteration { results, .. } = &history[1] else {
+        panic!("expected the interrupted iteration, got {:?}", history[1]);
+    };
+    assert!(
+        matches!(&results[0].outcome, golem::tools::Outcome::Error(e) if e.contains("interrupted"))
+    );
+
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(
+        !marker.exists(),
+        "the command kept running after cancellation"
+    );
+}
+
+/// Denies `rm`, rewrites `echo hi` to `echo bye`, and logs everything it sees.
+struct GuardHook {
+    events: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl golem::engine::hooks::Hook for GuardHook {
+    async fn on_task_start(&self, task: &str) {
+        self.events.lock().unwrap().push(format!("start {task}"));
+    }
+
+    async fn on_step_start(&self, _context: &golem::thinker::Context) {
+        self.events.lock().unwrap().push("step start".to_string());
+    }
+
+    async fn on_step(&self, step: &Step) {
+        let kind = match step {
+            Step::Act { .. } => "act",
+            Step::Finish { .. } => "finish",
+        };
+        self.events.lock().unwrap().push(format!("step {kind}"));
+    }
+
+    async fn on_tool_call(&self, call: &ToolCall) -> golem::engine::hooks::ToolDecision {
+        use golem::engine::hooks::ToolDecision;
+        match call.args.get("command").map(String::as_str) {
+            Some(cmd) if cmd.starts_with("rm ") => ToolDecision::Deny("no deleting".to_string()),
+            Some("echo hi") => ToolDecision::Modify(ToolCall {
+                tool: call.tool.clone(),
+                args: HashMap::from([("command".to_string(), "echo bye".to_string())]),
+            }),
+            _ => ToolDecision::Allow,
+        }
+    }
+
+    async fn on_tool_result(&self, call: &ToolCall, result: &golem::tools::ToolResult) {
+        let outcome = match &result.outcome {
+            golem::tools::Outcome::Success(out) => format!("ok {}", out.trim()),
+            golem::tools::Outcome::Error(err) => format!("error {err}"),
+        };
+        self.events.lock().unwrap().push(format!(
+            "{} -> {outcome}",
+            call.args.get("command").unwrap_or(&call.tool)
+        ));
+    }
+
+    async fn on_task_end(&self, task: &str, result: &anyhow::Result<golem::engine::TaskResult>) {
+        let answer = result.as_ref().map_or("failed", |r| r.answer.as_str());
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("end {task}: {answer}"));
+    }
+}
+
+#[tokio::test]
+async fn hooks_see_steps_and_guard_tool_calls() {
+    let shell = |cmd: &str| ToolCall {
+        tool: "shell".to_string(),
+        args: HashMap::from([("command".to_string(), cmd.to_string())]),
+    };
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "clean up".to_string(),
+            calls: vec![shell("echo hi"), shell("rm -rf /tmp/nothing")],
+        },
+        Step::Finish {
+            thought: "done".to_string(),
+            answer: "cleaned".to_string(),
+        },
+    ])
+    .await;
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    engine.add_hook(Arc::new(GuardHook {
+        events: Arc::clone(&events),
+    }));
+
+    assert_eq!(engine.run("tidy").await.unwrap().answer, "cleaned");
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "start tidy",
+            "step start",
+            "step act",
+            "echo bye -> ok bye",
+            "rm -rf /tmp/nothing -> error denied: no deleting",
+            "step start",
+            "step finish",
+            "end tidy: cleaned",
+        ]
+    );
+
+    // The model sees the rewritten call, not the one it asked for
+    let history = engine.history().await.unwrap();
+    let MemoryEntry::Iteration { calls, .. } = &history[1] else {
+        panic!("expected an iteration, got {:?}", history[1]);
+    };
+    assert_eq!(calls[0].args["command"], "echo bye");
+}
+
+/// Skips `ls`, aborts on `rm`, runs everything else.
+struct ScriptedReviewer;
+
+#[async_trait::async_trait]
+impl golem::engine::step::StepReviewer for ScriptedReviewer {
+    async fn review(&self, call: &ToolCall) -> anyhow::Result<golem::engine::hooks::ToolDecision> {
+        use golem::engine::hooks::ToolDecision;
+        Ok(match call.args["command"].as_str() {
+            "ls" => ToolDecision::Deny("skipped by the user".to_string()),
+            cmd if cmd.starts_with("rm ") => ToolDecision::Abort("aborted by the user".to_string()),
+            _ => ToolDecision::Allow,
+        })
+    }
+}
+
+#[tokio::test]
+async fn step_mode_skips_and_aborts_calls() {
+    use golem::engine::step::StepMode;
+    let shell = |cmd: &str| ToolCall {
+        tool: "shell".to_string(),
+        args: HashMap::from([("command".to_string(), cmd.to_string())]),
+    };
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "look around".to_string(),
+            calls: vec![shell("ls"), shell("echo hi")],
+        },
+        Step::Act {
+            thought: "clean up".to_string(),
+            calls: vec![shell("echo first"), shell("rm -rf /tmp/nothing")],
+        },
+    ])
+    .await;
+    engine.add_hook(Arc::new(StepMode::with_reviewer(
+        true,
+        Box::new(ScriptedReviewer),
+    )));
+
+    let err = engine.run("tidy").await.unwrap_err();
+    assert_eq!(err.to_string(), "aborted by the user");
+
+    // The skipped call is an error the model sees; the aborted step is not stored
+    let history = engine.history().await.unwrap();
+    assert_eq!(history.len(), 2);
+    let MemoryEntry::Iteration { results, .. } = &history[1] else {
+        panic!("expected an iteration, got {:?}", history[1]);
+    };
+    assert!(matches!(
+        &results[0].outcome,
+        golem::tools::Outcome::Error(e) if e == "denied: skipped by the user"
+    ));
+    assert!(matches!(
+        &results[1].outcome,
+        golem::tools::Outcome::Success(_)
+    ));
+}
+
+#[tokio::test]
+async fn plan_asks_for_calls_without_running_them() {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let thinker = Box::new(NoteRecorder {
+        inner: MockThinker::new(wrap(vec![
+            Step::Finish {
+                thought: "planned".to_string(),
+                answer: "1. shell: ls — see what's there".to_string(),
+            },
+            // A thinker that acts anyway gets its calls listed
+            Step::Act {
+                thought: "start with a listing".to_string(),
+                calls: vec![ToolCall {
+                    tool: "shell".to_string(),
+                    args: HashMap::from([("command".to_string(), "ls".to_string())]),
+                }],
+            },
+        ])),
+        seen: Arc::clone(&seen),
+    });
+    let memory = Box::new(SqliteMemory::in_memory().unwrap());
+    let mut engine = ReactEngine::new(
+        thinker,
+        Arc::new(ToolRegistry::new()),
+        memory,
+        ReactConfig::default(),
+    );
+
+    let plan = engine.plan("tidy up").await.unwrap();
+    assert_eq!(plan, "1. shell: ls — see what's there");
+    assert_eq!(
+        seen.lock().unwrap()[0].as_deref(),
+        Some(golem::prompts::PLAN_REQUEST)
+    );
+
+    let plan = engine.plan("tidy up").await.unwrap();
+    assert_eq!(plan, "start with a listing\n1. shell: ls\n");
+
+    // Planning leaves no trace in memory
+    assert!(engine.history().await.unwrap().is_empty());
+    assert!(engine.session_history().await.unwrap().is_empty());
+}
+
+/// The phases emitted so far.
+fn phases(rx: &mut tokio::sync::broadcast::Receiver<Event>) -> Vec<String> {
+    let mut phases = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let Event::Phase { phase } = event {
+            phases.push(phase);
+        }
+    }
+    phases
+}
+
+#[tokio::test]
+async fn phases_are_announced_on_the_event_bus() {
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "build it".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "echo built".to_string())]),
+            }],
+        },
+        Step::Finish {
+            thought: "built".to_string(),
+            answer: "done".to_string(),
+        },
+    ])
+    .await;
+    let events = Arc::new(EventBus::new(64));
+    let mut rx = events.subscribe();
+    engine.set_events(Arc::clone(&events));
+    engine.run("build").await.unwrap();
+
+    assert_eq!(
+        phases(&mut rx),
+        ["thinking...", "running shell: echo built", "thinking..."]
+    );
+
+    let (mut engine, _) = flaky_engine(1, 1);
+    let mut rx = events.subscribe();
+    engine.set_events(Arc::clone(&events));
+    engine.run("flaky").await.unwrap();
+    assert_eq!(
+        phases(&mut rx),
+        [
+            "thinking...",
+            "retrying after connection reset",
+            "thinking..."
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lifecycle_is_announced_on_the_event_bus() {
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "check".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "exit 3".to_string())]),
+            }],
+        },
+        Step::Finish {
+            thought: "checked".to_string(),
+            answer: "it fails".to_string(),
+        },
+    ])
+    .await;
+    let events = Arc::new(EventBus::new(64));
+    let mut rx = events.subscribe();
+    engine.set_events(Arc::clone(&events));
+    engine.run("check").await.unwrap();
+
+    let mut lifecycle = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        lifecycle.push(match event {
+            Event::TaskStarted { task } => format!("start {task}"),
+            Event::IterationStarted { iteration } => format!("iteration {iteration}"),
+            Event::ThoughtProduced { thought } => format!("thought {thought}"),
+            Event::ToolCallStarted { call } => format!("call {}", call.tool),
+            Event::ToolCallFinished { result, .. } => {
+                let ok = matches!(result.outcome, Outcome::Success(_));
+                format!("result {} ok={ok}", result.tool)
+            }
+            Event::TaskFinished { task, result } => {
+                format!("finish {task}: {}", result.unwrap().answer)
+            }
+            _ => continue,
+        });
+    }
+    assert_eq!(
+        lifecycle,
+        [
+            "start check",
+            "iteration 1",
+            "thought check",
+            "call shell",
+            "result shell ok=false",
+            "iteration 2",
+            "thought checked",
+            "finish check: it fails",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn progress_counts_iterations() {
+    let mut engine = build_engine(vec![
+        Step::Act {
+            thought: "look".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "true".to_string())]),
+            }],
+        },
+        Step::Finish {
+            thought: "seen".to_string(),
+            answer: "done".to_string(),
+        },
+    ])
+    .await;
+    let events = Arc::new(EventBus::new(64));
+    let mut rx = events.subscribe();
+    engine.set_events(Arc::clone(&events));
+    engine.run("look").await.unwrap();
+
+    let mut iterations = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let Event::Progress(progress) = event {
+            assert_eq!(
+                progress.max_iterations,
+                ReactConfig::default().max_iterations
+            );
+            iterations.push(progress.iteration);
+        }
+    }
+    assert_eq!(iterations, [1, 2]);
+}
+
+/// An engine whose `delegate` sub-agent runs one `echo`, with every model
+/// call costing `tokens` input tokens: parent, sub-agent, sub-agent, parent.
+async fn delegating_engine(tokens: u64, config: ReactConfig) -> ReactEngine {
+    use golem::thinker::TokenUsage;
+    use golem::tools::delegate::DelegateTool;
+
+    let calls = |tool: &str, arg: (&str, &str)| Step::Act {
+        thought: format!("use {tool}"),
+        calls: vec![ToolCall {
+            tool: tool.to_string(),
+            args: HashMap::from([(arg.0.to_string(), arg.1.to_string())]),
+        }],
+    };
+    let finish = |answer: &str| Step::Finish {
+        thought: "done".to_string(),
+        answer: answer.to_string(),
+    };
+    let steps = [
+        calls("delegate", ("task", "say hi")),
+        calls("shell", ("command", "echo hi")),
+        finish("said hi"),
+        finish("the sub-agent said hi"),
+    ]
+    .into_iter()
+    .map(|step| StepResult {
+        step,
+        usage: Some(TokenUsage {
+            input_tokens: tokens,
+            ..TokenUsage::default()
+        }),
+    })
+    .collect();
+    let thinker: Box<dyn Thinker> = Box::new(MockThinker::new(steps));
+    let thinker = Arc::new(tokio::sync::RwLock::new(thinker));
+    let tools = Arc::new(ToolRegistry::new());
+    tools
+        .register(Arc::new(ShellTool::new(ShellConfig {
+            working_dir: std::env::current_dir().unwrap(),
+            require_confirmation: false,
+            ..ShellConfig::default()
+        })))
+        .await;
+    tools
+        .register(Arc::new(DelegateTool::new(Arc::clone(&thinker), &tools)))
+        .await;
+    ReactEngine::with_shared_thinker(thinker, tools, Box::new(InMemoryMemory::new()), config)
+}
+
+#[tokio::test]
+async fn sub_agent_usage_is_charged_to_the_task() {
+    let mut engine = delegating_engine(100, ReactConfig::default()).await;
+    let result = engine.run("have someone say hi").await.unwrap();
+    assert_eq!(result.answer, "the sub-agent said hi");
+    // Two iterations of its own and two of the sub-agent's
+    assert_eq!(result.iterations, 4);
+    assert_eq!(result.usage.total(), 400);
+    assert_eq!(engine.session_usage().total(), 400);
+}
+
+#[tokio::test]
+async fn sub_agent_usage_counts_towards_the_token_budget() {
+    let config = ReactConfig {
+        max_tokens_per_task: Some(1000),
+        ..ReactConfig::default()
+    };
+    let mut engine = delegating_engine(400, config).await;
+    let err = engine.run("have someone say hi").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "token budget exceeded: used 1,200 of 1,000 tokens"
+    );
+}
+
+#[tokio::test]
+async fn sub_agents_get_what_is_left_of_the_iteration_budget() {
+    let config = ReactConfig {
+        max_iterations: 2,
+        ..ReactConfig::default()
+    };
+    let mut engine = delegating_engine(100, config).await;
+    let result = engine.run("have someone say hi").await.unwrap();
+    // The sub-agent had one iteration left, so both wrap up early
+    assert_eq!(result.iterations, 2);
+    assert_eq!(result.status, TaskStatus::Partial);
+    assert_eq!(result.answer, "the sub-agent said hi");
+}
+
+#[tokio::test]
+async fn sub_agent_tool_calls_go_through_the_parent_hooks_and_events() {
+    let mut engine = delegating_engine(100, ReactConfig::default()).await;
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    engine.add_hook(Arc::new(GuardHook {
+        events: Arc::clone(&log),
+    }));
+    let events = Arc::new(EventBus::default());
+    engine.set_events(Arc::clone(&events));
+    let mut rx = events.subscribe();
+
+    let result = engine.run("have someone say hi").await.unwrap();
+    assert_eq!(result.answer, "the sub-agent said hi");
+    let log = log.lock().unwrap();
+    // The hook rewrote the sub-agent's `echo hi`
+    assert!(log.contains(&"echo bye -> ok bye".to_string()), "{log:?}");
+    assert_eq!(log.iter().filter(|e| e.starts_with("start ")).count(), 1);
+    assert_eq!(log.iter().filter(|e| e.starts_with("end ")).count(), 1);
+
+    let mut started = Vec::new();
+    let mut tasks = 0;
+    while let Ok(event) = rx.try_recv() {
+        match event {
+            Event::ToolCallStarted { call } => started.push(call.tool),
+            Event::TaskStarted { .. } => tasks += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(started, ["delegate", "shell"]);
+    assert_eq!(tasks, 1);
+}
+
+/// Takes its time, then approves.
+struct SlowApprover(std::time::Duration);
+
+#[async_trait::async_trait]
+impl golem::tools::Approver for SlowApprover {
+    async fn approve(&self, _tool: &str, _args: &HashMap<String, String>) -> anyhow::Result<bool> {
+        tokio::time::sleep(self.0).await;
+        Ok(true)
+    }
+}
+
+#[tokio::test]
+async fn waiting_for_approval_does_not_count_against_the_tool_timeout() {
+    let thinker = Box::new(MockThinker::new(wrap(vec![
+        Step::Act {
+            thought: "check".to_string(),
+            calls: vec![ToolCall {
+                tool: "shell".to_string(),
+                args: HashMap::from([("command".to_string(), "echo approved".to_string())]),
+            }],
+        },
+        Step::Finish {
+            thought: "done".to_string(),
+            answer: "done".to_string(),
+        },
+    ])));
+    let tools = Arc::new(ToolRegistry::with_approver(Box::new(SlowApprover(
+        std::time::Duration::from_millis(300),
+    ))));
+    tools
+        .register(Arc::new(ShellTool::new(ShellConfig {
+            mode: ShellMode::ReadWrite,
+            working_dir: std::env::current_dir().unwrap(),
+            require_confirmation: true,
+            ..ShellConfig::default()
+        })))
+        .await;
+    let config = ReactConfig {
+        tool_timeout: std::time::Duration::from_millis(100),
+        ..ReactConfig::default()
+    };
+    let memory = Box::new(SqliteMemory::in_memory().unwrap());
+    let mut engine = ReactEngine::new(thinker, tools, memory, config);
+    let events = Arc::new(EventBus::default());
+    engine.set_events(Arc::clone(&events));
+    let mut rx = events.subscribe();
+
+    engine.run("echo something").await.unwrap();
+    let mut outcomes = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let Event::ToolCallFinished { result, .. } = event {
+            outcomes.push(result.outcome);
+        }
+    }
+    assert!(
+        matches!(&outcomes[..], [Outcome::Success(out)] if out.contains("approved")),
+        "{outcomes:?}"
+    );
+}
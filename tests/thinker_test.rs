@@ -0,0 +1,121 @@
+//! AnthropicThinker against recorded API traffic in `tests/cassettes/`.
+//!
+//! Runs offline by default. To re-record a cassette against the live API,
+//! set `GOLEM_RECORD_CASSETTES=1` and `ANTHROPIC_API_KEY` and run the test.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use golem::auth::storage::{AuthStorage, Credential};
+use golem::consts::RECORD_CASSETTES_ENV;
+use golem::thinker::anthropic::AnthropicThinker;
+use golem::thinker::cassette::Cassette;
+use golem::thinker::http::ReqwestClient;
+use golem::thinker::{Context, Step, Thinker};
+
+/// A thinker whose API calls go through the cassette `name`. `key` is
+/// the credential to use; `None` means the real one, from the environment
+/// while recording.
+fn thinker(name: &str, key: Option<&str>) -> AnthropicThinker {
+    let auth = AuthStorage::open(":memory:").unwrap();
+    let recording = std::env::var_os(RECORD_CASSETTES_ENV).is_some();
+    let key = key.or((!recording).then_some("sk-ant-api03-replay"));
+    if let Some(key) = key {
+        auth.set(
+            "anthropic",
+            Credential::ApiKey {
+                key: key.to_string(),
+            },
+        )
+        .unwrap();
+    }
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(format!("{name}.json"));
+    let cassette = Cassette::from_env(path, Arc::new(ReqwestClient)).unwrap();
+    AnthropicThinker::new(Some("claude-haiku-4-5".to_string()), auth).with_http(Arc::new(cassette))
+}
+
+fn context(task: &str) -> Context {
+    Context {
+        task: task.to_string(),
+        history: vec![],
+        session_history: vec![],
+        relevant_memory: vec![],
+        facts: vec![],
+        available_tools: vec![],
+        note: None,
+        iteration: None,
+        persona: None,
+        language: None,
+    }
+}
+
+#[tokio::test]
+async fn finish_step_with_usage() {
+    let thinker = thinker("anthropic_finish", None);
+    let result = thinker
+        .next_step(&context("What is 6 times 7?"))
+        .await
+        .unwrap();
+    let Step::Finish { answer, .. } = result.step else {
+        panic!("expected a finish step");
+    };
+    assert_eq!(answer, "42");
+    let usage = result.usage.unwrap();
+    assert_eq!(usage.input_tokens, 618);
+    assert_eq!(usage.output_tokens, 24);
+}
+
+#[tokio::test]
+async fn act_step_from_fenced_json() {
+    let thinker = thinker("anthropic_act", None);
+    let result = thinker
+        .next_step(&context("List the files in the current directory"))
+        .await
+        .unwrap();
+    let Step::Act { calls, .. } = result.step else {
+        panic!("expected an act step");
+    };
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].tool, "shell");
+    assert_eq!(calls[0].args["command"], "ls -la");
+}
+
+#[tokio::test]
+async fn invalid_json_gets_one_correction_round() {
+    let thinker = thinker("anthropic_parse_retry", None);
+    let result = thinker
+        .next_step(&context("What is 6 times 7?"))
+        .await
+        .unwrap();
+    assert!(matches!(result.step, Step::Finish { ref answer, .. } if answer == "42"));
+    // Both calls are billed
+    assert_eq!(result.usage.unwrap().input_tokens, 618 + 660);
+}
+
+#[tokio::test]
+async fn models_are_listed_sorted() {
+    let thinker = thinker("anthropic_models", None);
+    let models = thinker.models().await.unwrap();
+    let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "claude-haiku-4-5-20251001",
+            "claude-opus-4-5-20251101",
+            "claude-sonnet-4-20250514"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn api_errors_carry_status_and_body() {
+    let thinker = thinker("anthropic_unauthorized", Some("sk-ant-api03-invalid"));
+    let Err(err) = thinker.next_step(&context("What is 6 times 7?")).await else {
+        panic!("expected an API error");
+    };
+    let message = err.to_string();
+    assert!(message.contains("Anthropic API error (401)"), "{message}");
+    assert!(message.contains("authentication_error"), "{message}");
+}